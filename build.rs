@@ -18,7 +18,19 @@ fn main() -> anyhow::Result<()> {
     );
     let mut out = String::new();
 
-    for strategy in &["Cranelift"] {
+    let mut strategies = vec!["Cranelift"];
+    // Winch only supports x86_64 System V and aarch64 Linux targets.
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    if cfg!(feature = "winch")
+        && ((arch == "x86_64" && os != "windows") || (arch == "aarch64" && os == "linux"))
+    {
+        strategies.push("Winch");
+    }
+    // Tail calls are only implemented for x86_64 outside of Windows so far.
+    let tail_calls = arch == "x86_64" && os != "windows";
+
+    for strategy in strategies {
         writeln!(out, "#[cfg(test)]")?;
         writeln!(out, "#[allow(non_snake_case)]")?;
         writeln!(out, "mod {} {{", strategy)?;
@@ -30,6 +42,9 @@ fn main() -> anyhow::Result<()> {
             test_directory_module(out, "tests/misc_testsuite/threads", strategy)?;
             test_directory_module(out, "tests/misc_testsuite/memory64", strategy)?;
             test_directory_module(out, "tests/misc_testsuite/component-model", strategy)?;
+            if tail_calls {
+                test_directory_module(out, "tests/misc_testsuite/tail-call", strategy)?;
+            }
             Ok(())
         })?;
 
@@ -50,6 +65,13 @@ fn main() -> anyhow::Result<()> {
                     "tests/spec_testsuite/proposals/relaxed-simd",
                     strategy,
                 )?;
+                if tail_calls {
+                    test_directory_module(
+                        out,
+                        "tests/spec_testsuite/proposals/tail-call",
+                        strategy,
+                    )?;
+                }
             } else {
                 println!(
                     "cargo:warning=The spec testsuite is disabled. To enable, run `git submodule \
@@ -166,10 +188,11 @@ fn write_testsuite_tests(
     writeln!(out, "    let _ = env_logger::try_init();")?;
     writeln!(
         out,
-        "    crate::wast::run_wast(r#\"{}\"#, crate::wast::Strategy::{}, {}).unwrap();",
+        "    crate::wast::run_wast(r#\"{}\"#, crate::wast::Strategy::{}, {}, {}).unwrap();",
         path.display(),
         strategy,
         pooling,
+        strategy == "Winch" && winch_skips_unsupported(testsuite, &testname),
     )?;
     writeln!(out, "}}")?;
     writeln!(out)?;
@@ -178,13 +201,15 @@ fn write_testsuite_tests(
 
 /// Ignore tests that aren't supported yet.
 fn ignore(testsuite: &str, testname: &str, strategy: &str) -> bool {
-    assert_eq!(strategy, "Cranelift");
-
     // This is an empty file right now which the `wast` crate doesn't parse
     if testname.contains("memory_copy1") {
         return true;
     }
 
+    if strategy == "Winch" {
+        return ignore_winch(testsuite, testname);
+    }
+
     match env::var("CARGO_CFG_TARGET_ARCH").unwrap().as_str() {
         "s390x" => {
             // FIXME: These tests fail under qemu due to a qemu bug.
@@ -202,3 +227,54 @@ fn ignore(testsuite: &str, testname: &str, strategy: &str) -> bool {
         _ => false,
     }
 }
+
+/// Ignore the tests which use WebAssembly features that Winch doesn't support
+/// yet: SIMD, the atomics of the threads proposal and tail calls.
+fn ignore_winch(testsuite: &str, testname: &str) -> bool {
+    ["simd", "relaxed_simd", "threads", "tail_call"].contains(&testsuite)
+        || testname.contains("simd")
+        || testname == "threads"
+}
+
+/// Whether Winch runs the test skipping the modules which use `externref`s,
+/// which it doesn't support, along with the directives using them.
+fn winch_skips_unsupported(testsuite: &str, testname: &str) -> bool {
+    if testsuite == "misc_testsuite" {
+        return [
+            "externref_id_function",
+            "many_table_gets_lead_to_gc",
+            "mutable_externref_globals",
+            "no_mixup_stack_maps",
+            "no_panic",
+            "simple_ref_is_null",
+            "some_externref_modules",
+        ]
+        .contains(&testname);
+    }
+
+    // The specification tests, including the copies of them in the proposals'
+    // test suites.
+    [
+        "binary",
+        "br_table",
+        "bulk",
+        "elem",
+        "global",
+        "imports",
+        "linking",
+        "ref_func",
+        "ref_is_null",
+        "ref_null",
+        "select",
+        "table",
+        "table_copy",
+        "table_fill",
+        "table_get",
+        "table_grow",
+        "table_init",
+        "table_set",
+        "table_size",
+        "unreached_invalid",
+    ]
+    .contains(&testname)
+}
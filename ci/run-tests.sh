@@ -3,6 +3,7 @@
 cargo test \
     --features "test-programs/test_programs" \
     --features wasi-threads \
+    --features winch \
     --workspace \
     --exclude 'wasmtime-wasi-*' \
     --exclude wasi-crypto \
@@ -0,0 +1,166 @@
+//! Instrumentation to collect block profiles.
+//!
+//! This pass inserts code which counts how many times each block of a
+//! function is executed, and how many times each edge out of a conditional
+//! branch is taken, into an array of 64-bit counters. The counts can then be
+//! turned into a `BlockProfile` for a later compilation of the same function.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::ir::condcodes::IntCC;
+use crate::ir::{self, Function, InstBuilder, InstructionData, MemFlags, ProfileCounter};
+use crate::timing;
+use alloc::vec::Vec;
+
+/// Instrument `func` to count block executions and taken edges in the array
+/// of `u64` counters at the address given by the global value `counters`, of
+/// type `addr_ty`.
+///
+/// Returns what each counter of the array counts; the array must have at
+/// least as many entries.
+pub fn do_block_profiling(
+    func: &mut Function,
+    counters: ir::GlobalValue,
+    addr_ty: ir::Type,
+) -> Vec<ProfileCounter> {
+    let _tt = timing::block_profiling();
+    let mut layout = Vec::new();
+    let entry = match func.layout.entry_block() {
+        Some(entry) => entry,
+        None => return layout,
+    };
+    let blocks: Vec<_> = func.layout.blocks().collect();
+
+    let mut pos = FuncCursor::new(func);
+    pos.goto_first_insertion_point(entry);
+    let base = pos.ins().global_value(addr_ty, counters);
+
+    for block in blocks {
+        if block != entry {
+            pos.goto_first_insertion_point(block);
+        }
+        let offset = slot_offset(layout.len());
+        layout.push(ProfileCounter::Block(block));
+        increment(&mut pos, base, offset);
+
+        let terminator = match pos.func.layout.last_inst(block) {
+            Some(inst) => inst,
+            None => continue,
+        };
+        pos.goto_inst(terminator);
+        match pos.func.dfg.insts[terminator] {
+            InstructionData::Brif { arg, .. } => {
+                // Select the counter of the `then` edge or of the `else` one
+                // with the condition of the branch.
+                let offset = slot_offset(layout.len());
+                layout.push(ProfileCounter::Edge(block, 0));
+                layout.push(ProfileCounter::Edge(block, 1));
+                let then_slot = pos.ins().iconst(addr_ty, 0);
+                let else_slot = pos.ins().iconst(addr_ty, 8);
+                let slot = pos.ins().select(arg, then_slot, else_slot);
+                let addr = pos.ins().iadd(base, slot);
+                increment(&mut pos, addr, offset);
+            }
+            InstructionData::BranchTable { arg, table, .. } => {
+                // The default block is successor 0, and entry `i` of the
+                // table is successor `i + 1`.
+                let len = pos.func.dfg.jump_tables[table].as_slice().len();
+                let offset = slot_offset(layout.len());
+                layout.extend((0..=len as u32).map(|i| ProfileCounter::Edge(block, i)));
+                let index_ty = pos.func.dfg.value_type(arg);
+                let in_range = pos.ins().icmp_imm(IntCC::UnsignedLessThan, arg, len as i64);
+                let entry_slot = pos.ins().iadd_imm(arg, 1);
+                let default_slot = pos.ins().iconst(index_ty, 0);
+                let slot = pos.ins().select(in_range, entry_slot, default_slot);
+                let slot = if index_ty.bits() < addr_ty.bits() {
+                    pos.ins().uextend(addr_ty, slot)
+                } else if index_ty.bits() > addr_ty.bits() {
+                    pos.ins().ireduce(addr_ty, slot)
+                } else {
+                    slot
+                };
+                let slot = pos.ins().ishl_imm(slot, 3);
+                let addr = pos.ins().iadd(base, slot);
+                increment(&mut pos, addr, offset);
+            }
+            _ => {}
+        }
+    }
+    layout
+}
+
+/// Returns the byte offset of the counter with the given index.
+fn slot_offset(index: usize) -> i32 {
+    i32::try_from(index * 8).expect("too many block profiling counters")
+}
+
+/// Inserts code incrementing the counter at `addr + offset`.
+fn increment(pos: &mut FuncCursor, addr: ir::Value, offset: i32) {
+    let flags = MemFlags::trusted();
+    let count = pos.ins().load(ir::types::I64, flags, addr, offset);
+    let count = pos.ins().iadd_imm(count, 1);
+    pos.ins().store(flags, count, addr, offset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::types::*;
+    use crate::ir::{
+        AbiParam, ArgumentPurpose, BlockProfile, GlobalValueData, Signature, UserFuncName,
+    };
+    use crate::isa::CallConv;
+    use crate::verifier::verify_function;
+
+    #[test]
+    fn counters_cover_blocks_and_edges() {
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params.push(AbiParam::new(I32));
+        sig.params
+            .push(AbiParam::special(I64, ArgumentPurpose::VMContext));
+        let mut func = Function::with_name_signature(UserFuncName::testcase("f"), sig);
+        let vmctx = func.create_global_value(GlobalValueData::VMContext);
+        let blocks: Vec<_> = (0..4).map(|_| func.dfg.make_block()).collect();
+        let arg = func.dfg.append_block_param(blocks[0], I32);
+        func.dfg.append_block_param(blocks[0], I64);
+
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(blocks[0]);
+        pos.ins().brif(arg, blocks[1], &[], blocks[2], &[]);
+        pos.insert_block(blocks[1]);
+        let default = pos.func.dfg.block_call(blocks[3], &[]);
+        let entry = pos.func.dfg.block_call(blocks[2], &[]);
+        let table = pos
+            .func
+            .create_jump_table(ir::JumpTableData::new(default, &[entry, entry]));
+        pos.ins().br_table(arg, table);
+        pos.insert_block(blocks[2]);
+        pos.ins().jump(blocks[3], &[]);
+        pos.insert_block(blocks[3]);
+        pos.ins().return_(&[]);
+
+        let layout = do_block_profiling(&mut func, vmctx, I64);
+        let flags = crate::settings::Flags::new(crate::settings::builder());
+        verify_function(&func, &flags).unwrap();
+
+        let b = |i: usize| blocks[i];
+        assert_eq!(
+            layout,
+            [
+                ProfileCounter::Block(b(0)),
+                ProfileCounter::Edge(b(0), 0),
+                ProfileCounter::Edge(b(0), 1),
+                ProfileCounter::Block(b(1)),
+                ProfileCounter::Edge(b(1), 0),
+                ProfileCounter::Edge(b(1), 1),
+                ProfileCounter::Edge(b(1), 2),
+                ProfileCounter::Block(b(2)),
+                ProfileCounter::Block(b(3)),
+            ]
+        );
+
+        let profile = BlockProfile::from_counters(&layout, &[5, 4, 1, 4, 0, 3, 1, 5, 4]);
+        assert_eq!(profile.block_count(b(2)), Some(5));
+        assert_eq!(profile.edge_count(b(1), 1), Some(3));
+        assert_eq!(profile.edge_count(b(2), 0), None);
+    }
+}
@@ -10,15 +10,18 @@
 //! single ISA instance.
 
 use crate::alias_analysis::AliasAnalysis;
+use crate::block_profiling::do_block_profiling;
 use crate::dce::do_dce;
 use crate::dominator_tree::DominatorTree;
 use crate::egraph::EgraphPass;
 use crate::flowgraph::ControlFlowGraph;
-use crate::ir::Function;
+use crate::inline::{do_inlining, InlineOptions, InlinedCall};
+use crate::ir::{Function, GlobalValue, ProfileCounter, UserExternalName};
 use crate::isa::TargetIsa;
 use crate::legalizer::simple_legalize;
 use crate::licm::do_licm;
 use crate::loop_analysis::LoopAnalysis;
+use crate::loop_opts::do_loop_opts;
 use crate::machinst::{CompiledCode, CompiledCodeStencil};
 use crate::nan_canonicalization::do_nan_canonicalization;
 use crate::remove_constant_phis::do_remove_constant_phis;
@@ -183,6 +186,10 @@ impl Context {
         }
 
         self.compute_domtree();
+        if isa.flags().use_egraphs() && opt_level != OptLevel::None {
+            self.compute_loop_analysis();
+            self.loop_opts(isa)?;
+        }
         self.eliminate_unreachable_code(isa)?;
 
         if opt_level != OptLevel::None {
@@ -335,6 +342,19 @@ impl Context {
         self.verify_if(fisa)
     }
 
+    /// Fully unroll the function's small loops if `isa` optimizes for speed, and
+    /// strength-reduce the multiplications of induction variables.
+    pub fn loop_opts(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
+        do_loop_opts(
+            &mut self.func,
+            &mut self.cfg,
+            &mut self.domtree,
+            &mut self.loop_analysis,
+            isa.flags().opt_level() == OptLevel::Speed,
+        );
+        self.verify_if(isa)
+    }
+
     /// Perform LICM on the function.
     pub fn licm(&mut self, isa: &dyn TargetIsa) -> CodegenResult<()> {
         do_licm(
@@ -366,6 +386,42 @@ impl Context {
         Ok(())
     }
 
+    /// Instrument the function to count how many times its blocks are
+    /// executed, and how many times the edges out of its conditional branches
+    /// are taken, in the array of `u64` counters whose address is given by
+    /// the global value `counters`.
+    ///
+    /// Returns what each counter of the array counts, which is how the
+    /// counters are turned back into a `BlockProfile` with
+    /// `BlockProfile::from_counters`.
+    pub fn instrument_block_profile(
+        &mut self,
+        isa: &dyn TargetIsa,
+        counters: GlobalValue,
+    ) -> CodegenResult<Vec<ProfileCounter>> {
+        let layout = do_block_profiling(&mut self.func, counters, isa.pointer_type());
+        self.verify_if(isa)?;
+        Ok(layout)
+    }
+
+    /// Inline the calls of the function to the callees that `lookup` returns
+    /// the body of, within the limits of `options`.
+    ///
+    /// This is meant to run on the function before it's compiled, and
+    /// returns the calls which were inlined and the blocks of each copy of a
+    /// callee. The inlined instructions keep the source locations of the
+    /// callee.
+    pub fn inline_calls<'a>(
+        &mut self,
+        isa: &dyn TargetIsa,
+        options: &InlineOptions,
+        lookup: impl FnMut(&UserExternalName) -> Option<&'a Function>,
+    ) -> CodegenResult<Vec<InlinedCall>> {
+        let inlined = do_inlining(&mut self.func, options, lookup);
+        self.verify_if(isa)?;
+        Ok(inlined)
+    }
+
     /// Harvest candidate left-hand sides for superoptimization with Souper.
     #[cfg(feature = "souper-harvest")]
     pub fn souper_harvest(
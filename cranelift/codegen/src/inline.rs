@@ -0,0 +1,709 @@
+//! Function inlining.
+//!
+//! This pass replaces `call` instructions with copies of the bodies of their
+//! callees. The embedder provides the bodies of the functions that may be
+//! inlined, looked up by their names, and each body is inlined as it was
+//! given: calls in an inlined body aren't themselves inlined.
+//!
+//! Inlined instructions keep the source locations of the callee, so that an
+//! embedder can tell the code of a callee from the code of its caller, and
+//! `inline_calls` returns the calls which were inlined along with the blocks
+//! holding each copy of a callee.
+
+use crate::dominator_tree::DominatorTree;
+use crate::entity::{EntityRef, SecondaryMap};
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::{
+    self, ArgumentPurpose, Block, BlockCall, ExternalName, Function, GlobalValue, GlobalValueData,
+    Inst, InstBuilder, InstructionData, JumpTableData, Opcode, SourceLoc, UserExternalName,
+    UserFuncName, Value, ValueList,
+};
+use crate::packed_option::PackedOption;
+use crate::timing;
+use alloc::vec::Vec;
+
+/// The cost of a call, in `inline_cost`, relative to other instructions.
+const CALL_COST: u32 = 4;
+
+/// Limits on which calls are inlined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InlineOptions {
+    /// The maximum cost, as computed by `inline_cost`, of a callee to inline.
+    pub max_callee_cost: u32,
+    /// The cost which a caller may grow to through inlining; calls which
+    /// would make it grow larger aren't inlined.
+    pub max_caller_cost: u32,
+}
+
+impl Default for InlineOptions {
+    fn default() -> Self {
+        Self {
+            max_callee_cost: 40,
+            max_caller_cost: 10_000,
+        }
+    }
+}
+
+/// A call which was inlined.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InlinedCall {
+    /// The name of the callee.
+    pub callee: UserExternalName,
+    /// The source location of the call.
+    pub srcloc: SourceLoc,
+    /// The blocks holding this copy of the callee's body, which set it apart
+    /// from other copies of the same callee.
+    pub blocks: Vec<Block>,
+}
+
+/// Estimate the size of the code of `func`, to decide whether to inline it.
+///
+/// This is the number of instructions of the function, where calls count for
+/// more since they clobber registers, and jumps and returns don't count since
+/// an inlined body mostly falls through to the code after the call.
+pub fn inline_cost(func: &Function) -> u32 {
+    let mut cost = 0u32;
+    for block in func.layout.blocks() {
+        for inst in func.layout.block_insts(block) {
+            cost = cost.saturating_add(match func.dfg.insts[inst].opcode() {
+                Opcode::Jump | Opcode::Return => 0,
+                Opcode::Call | Opcode::CallIndirect => CALL_COST,
+                _ => 1,
+            });
+        }
+    }
+    cost
+}
+
+/// Inline the calls of `func` to the functions which `lookup` returns a body
+/// for, within the limits of `options`.
+///
+/// Calls in cold blocks and calls of `func` itself are never inlined.
+pub(crate) fn do_inlining<'a>(
+    func: &mut Function,
+    options: &InlineOptions,
+    mut lookup: impl FnMut(&UserExternalName) -> Option<&'a Function>,
+) -> Vec<InlinedCall> {
+    let _tt = timing::inline();
+    let mut calls = Vec::new();
+    for block in func.layout.blocks() {
+        if func.layout.is_cold(block) {
+            continue;
+        }
+        for inst in func.layout.block_insts(block) {
+            if let InstructionData::Call { func_ref, .. } = func.dfg.insts[inst] {
+                calls.push((inst, func_ref));
+            }
+        }
+    }
+
+    let mut cost = inline_cost(func);
+    let mut inlined = Vec::new();
+    for (call, func_ref) in calls {
+        let name = match func.dfg.ext_funcs[func_ref].name {
+            ExternalName::User(name) => func.params.user_named_funcs()[name].clone(),
+            _ => continue,
+        };
+        if matches!(&func.name, UserFuncName::User(own) if *own == name) {
+            continue;
+        }
+        let callee = match lookup(&name) {
+            Some(callee) => callee,
+            None => continue,
+        };
+        let callee_cost = inline_cost(callee);
+        if callee_cost > options.max_callee_cost
+            || cost.saturating_add(callee_cost) > options.max_caller_cost
+            || !can_inline(func, call, callee)
+        {
+            continue;
+        }
+        let srcloc = func.srcloc(call);
+        let blocks = Inliner::new(func, callee).inline(call);
+        cost += callee_cost;
+        inlined.push(InlinedCall {
+            callee: name,
+            srcloc,
+            blocks,
+        });
+    }
+    inlined
+}
+
+/// Can the body of `callee` replace the call instruction `call` of `func`?
+fn can_inline(func: &Function, call: Inst, callee: &Function) -> bool {
+    let entry = match callee.layout.entry_block() {
+        Some(entry) => entry,
+        None => return false,
+    };
+
+    // The call must match the callee's signature, and the callee can't take
+    // arguments which are passed in special ways.
+    let args = func.dfg.inst_args(call);
+    let results = func.dfg.inst_results(call);
+    let sig = &callee.signature;
+    if args.len() != sig.params.len()
+        || results.len() != sig.returns.len()
+        || args
+            .iter()
+            .zip(&sig.params)
+            .any(|(arg, param)| func.dfg.value_type(*arg) != param.value_type)
+        || results
+            .iter()
+            .zip(&sig.returns)
+            .any(|(result, ret)| func.dfg.value_type(*result) != ret.value_type)
+        || sig.params.iter().any(|param| {
+            !matches!(
+                param.purpose,
+                ArgumentPurpose::Normal | ArgumentPurpose::VMContext
+            )
+        })
+    {
+        return false;
+    }
+
+    // Global values of the callee derived from its `VMContext` are only the
+    // same in the caller if the caller passes its own `VMContext`.
+    let uses_vmctx = callee
+        .global_values
+        .values()
+        .any(|gv| matches!(gv, GlobalValueData::VMContext));
+    if uses_vmctx {
+        let index = match sig.special_param_index(ArgumentPurpose::VMContext) {
+            Some(index) => index,
+            None => return false,
+        };
+        let vmctx = func.special_param(ArgumentPurpose::VMContext);
+        if vmctx.map(|v| func.dfg.resolve_aliases(v)) != Some(func.dfg.resolve_aliases(args[index]))
+        {
+            return false;
+        }
+    }
+
+    // Stack frames and dynamic types belong to the function they're created
+    // in, and the entry block is replaced by the arguments of the call so it
+    // can't be branched to.
+    if !callee.dynamic_stack_slots.is_empty() || !callee.tables.is_empty() {
+        return false;
+    }
+    for block in callee.layout.blocks() {
+        for inst in callee.layout.block_insts(block) {
+            let data = &callee.dfg.insts[inst];
+            match data.opcode() {
+                Opcode::ReturnCall
+                | Opcode::GetFramePointer
+                | Opcode::GetStackPointer
+                | Opcode::GetReturnAddress => return false,
+                _ => {}
+            }
+            if data
+                .branch_destination(&callee.dfg.jump_tables)
+                .iter()
+                .any(|dest| dest.block(&callee.dfg.value_lists) == entry)
+            {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Copies the body of a callee into a caller, mapping the entities of the
+/// callee to new entities of the caller.
+struct Inliner<'a> {
+    func: &'a mut Function,
+    callee: &'a Function,
+    blocks: SecondaryMap<Block, PackedOption<Block>>,
+    values: SecondaryMap<Value, PackedOption<Value>>,
+    global_values: SecondaryMap<GlobalValue, PackedOption<GlobalValue>>,
+    sig_refs: Vec<ir::SigRef>,
+    func_refs: Vec<ir::FuncRef>,
+    stack_slots: Vec<ir::StackSlot>,
+    constants: Vec<ir::Constant>,
+    immediates: Vec<ir::Immediate>,
+}
+
+impl<'a> Inliner<'a> {
+    fn new(func: &'a mut Function, callee: &'a Function) -> Self {
+        let sig_refs = callee
+            .dfg
+            .signatures
+            .values()
+            .map(|sig| func.import_signature(sig.clone()))
+            .collect::<Vec<_>>();
+        let func_refs = callee
+            .dfg
+            .ext_funcs
+            .values()
+            .map(|ext_func| {
+                let name = match &ext_func.name {
+                    ExternalName::User(name) => {
+                        ExternalName::User(func.declare_imported_user_function(
+                            callee.params.user_named_funcs()[*name].clone(),
+                        ))
+                    }
+                    name => name.clone(),
+                };
+                func.import_function(ir::ExtFuncData {
+                    name,
+                    signature: sig_refs[ext_func.signature.index()],
+                    colocated: ext_func.colocated,
+                })
+            })
+            .collect();
+        let stack_slots = callee
+            .sized_stack_slots
+            .values()
+            .map(|data| func.create_sized_stack_slot(data.clone()))
+            .collect();
+        let constants = callee
+            .dfg
+            .constants
+            .iter()
+            .map(|(_, data)| func.dfg.constants.insert(data.clone()))
+            .collect();
+        let immediates = callee
+            .dfg
+            .immediates
+            .values()
+            .map(|data| func.dfg.immediates.push(data.clone()))
+            .collect();
+        Self {
+            func,
+            callee,
+            blocks: SecondaryMap::new(),
+            values: SecondaryMap::new(),
+            global_values: SecondaryMap::new(),
+            sig_refs,
+            func_refs,
+            stack_slots,
+            constants,
+            immediates,
+        }
+    }
+
+    /// Replace `call` with the body of the callee, returning the blocks of the
+    /// copied body.
+    fn inline(mut self, call: Inst) -> Vec<Block> {
+        let callee = self.callee;
+        let entry = callee.layout.entry_block().unwrap();
+
+        // Split the calling block after the call. The results of the call
+        // become the parameters of the block which the callee returns to.
+        let cont = self.func.dfg.make_block();
+        let next = self
+            .func
+            .layout
+            .next_inst(call)
+            .expect("a call can't terminate a block");
+        self.func.layout.split_block(cont, next);
+        let results = self.func.dfg.detach_results(call);
+        let results = results.as_slice(&self.func.dfg.value_lists).to_vec();
+        for result in results {
+            let ty = self.func.dfg.value_type(result);
+            let param = self.func.dfg.append_block_param(cont, ty);
+            self.func.dfg.change_to_alias(result, param);
+        }
+
+        // The parameters of the callee's entry block are the arguments of the
+        // call. Blocks which are unreachable in the callee are left out.
+        let cfg = ControlFlowGraph::with_function(callee);
+        let domtree = DominatorTree::with_function(callee, &cfg);
+        let args = self.func.dfg.inst_args(call).to_vec();
+        for (param, arg) in callee.dfg.block_params(entry).iter().zip(args) {
+            self.values[*param] = arg.into();
+        }
+        for block in callee.layout.blocks() {
+            if !domtree.is_reachable(block) {
+                continue;
+            }
+            let new_block = self.func.dfg.make_block();
+            self.func.layout.insert_block(new_block, cont);
+            if callee.layout.is_cold(block) {
+                self.func.layout.set_cold(new_block);
+            }
+            self.blocks[block] = new_block.into();
+            if block != entry {
+                for param in callee.dfg.block_params(block) {
+                    let ty = callee.dfg.value_type(*param);
+                    self.values[*param] = self.func.dfg.append_block_param(new_block, ty).into();
+                }
+            }
+        }
+        let new_blocks = callee
+            .layout
+            .blocks()
+            .filter_map(|block| self.blocks[block].expand())
+            .collect::<Vec<_>>();
+        let new_entry = self.blocks[entry].unwrap();
+        self.func.dfg.replace(call).jump(new_entry, &[]);
+
+        // Copy the instructions in reverse postorder, which visits the
+        // definition of each value before its uses.
+        for &block in domtree.cfg_postorder().iter().rev() {
+            let new_block = self.blocks[block].unwrap();
+            for inst in callee.layout.block_insts(block) {
+                let new_inst = self.copy_inst(inst, cont);
+                self.func.layout.append_inst(new_inst, new_block);
+                let srcloc = callee.srcloc(inst);
+                if !srcloc.is_default() {
+                    self.func.set_srcloc(new_inst, srcloc);
+                }
+            }
+        }
+        new_blocks
+    }
+
+    /// Create a copy of `inst` in the caller, where returns become jumps to
+    /// `cont`.
+    fn copy_inst(&mut self, inst: Inst, cont: Block) -> Inst {
+        let callee = self.callee;
+        if callee.dfg.insts[inst].opcode() == Opcode::Return {
+            let args = self.map_values(callee.dfg.inst_args(inst));
+            let destination = self.func.dfg.block_call(cont, &args);
+            return self.func.dfg.make_inst(InstructionData::Jump {
+                opcode: Opcode::Jump,
+                destination,
+            });
+        }
+
+        let data = match callee.dfg.insts[inst] {
+            InstructionData::Brif {
+                opcode,
+                arg,
+                blocks,
+            } => InstructionData::Brif {
+                opcode,
+                arg: self.map_value(arg),
+                blocks: [
+                    self.map_block_call(blocks[0]),
+                    self.map_block_call(blocks[1]),
+                ],
+            },
+            InstructionData::Jump {
+                opcode,
+                destination,
+            } => InstructionData::Jump {
+                opcode,
+                destination: self.map_block_call(destination),
+            },
+            InstructionData::BranchTable { opcode, arg, table } => {
+                let table = &callee.dfg.jump_tables[table];
+                let default = self.map_block_call(table.default_block());
+                let entries = table
+                    .as_slice()
+                    .iter()
+                    .map(|entry| self.map_block_call(*entry))
+                    .collect::<Vec<_>>();
+                InstructionData::BranchTable {
+                    opcode,
+                    arg: self.map_value(arg),
+                    table: self
+                        .func
+                        .create_jump_table(JumpTableData::new(default, &entries)),
+                }
+            }
+            InstructionData::Call {
+                opcode,
+                ref args,
+                func_ref,
+            } => InstructionData::Call {
+                opcode,
+                args: self.map_value_list(args),
+                func_ref: self.func_refs[func_ref.index()],
+            },
+            InstructionData::CallIndirect {
+                opcode,
+                ref args,
+                sig_ref,
+            } => InstructionData::CallIndirect {
+                opcode,
+                args: self.map_value_list(args),
+                sig_ref: self.sig_refs[sig_ref.index()],
+            },
+            InstructionData::MultiAry { opcode, ref args } => InstructionData::MultiAry {
+                opcode,
+                args: self.map_value_list(args),
+            },
+            InstructionData::FuncAddr { opcode, func_ref } => InstructionData::FuncAddr {
+                opcode,
+                func_ref: self.func_refs[func_ref.index()],
+            },
+            InstructionData::Shuffle { opcode, args, imm } => InstructionData::Shuffle {
+                opcode,
+                args: [self.map_value(args[0]), self.map_value(args[1])],
+                imm: self.immediates[imm.index()],
+            },
+            InstructionData::StackLoad {
+                opcode,
+                stack_slot,
+                offset,
+            } => InstructionData::StackLoad {
+                opcode,
+                stack_slot: self.stack_slots[stack_slot.index()],
+                offset,
+            },
+            InstructionData::StackStore {
+                opcode,
+                arg,
+                stack_slot,
+                offset,
+            } => InstructionData::StackStore {
+                opcode,
+                arg: self.map_value(arg),
+                stack_slot: self.stack_slots[stack_slot.index()],
+                offset,
+            },
+            InstructionData::UnaryConst {
+                opcode,
+                constant_handle,
+            } => InstructionData::UnaryConst {
+                opcode,
+                constant_handle: self.constants[constant_handle.index()],
+            },
+            InstructionData::UnaryGlobalValue {
+                opcode,
+                global_value,
+            } => InstructionData::UnaryGlobalValue {
+                opcode,
+                global_value: self.map_global_value(global_value),
+            },
+            // The remaining formats only refer to values in fixed-size
+            // arrays, outside of the value list pool.
+            mut data => {
+                let args = self.map_values(data.arguments(&callee.dfg.value_lists));
+                data.arguments_mut(&mut self.func.dfg.value_lists)
+                    .copy_from_slice(&args);
+                data
+            }
+        };
+
+        let new_inst = self.func.dfg.make_inst(data);
+        self.func
+            .dfg
+            .make_inst_results(new_inst, callee.dfg.ctrl_typevar(inst));
+        for (result, new_result) in callee
+            .dfg
+            .inst_results(inst)
+            .iter()
+            .zip(self.func.dfg.inst_results(new_inst))
+        {
+            self.values[*result] = (*new_result).into();
+        }
+        new_inst
+    }
+
+    fn map_value(&self, value: Value) -> Value {
+        self.values[self.callee.dfg.resolve_aliases(value)]
+            .expect("value used before its definition")
+    }
+
+    fn map_values(&self, values: &[Value]) -> Vec<Value> {
+        values.iter().map(|value| self.map_value(*value)).collect()
+    }
+
+    fn map_value_list(&mut self, list: &ValueList) -> ValueList {
+        let values = self.map_values(list.as_slice(&self.callee.dfg.value_lists));
+        ValueList::from_slice(&values, &mut self.func.dfg.value_lists)
+    }
+
+    fn map_block_call(&mut self, call: BlockCall) -> BlockCall {
+        let pool = &self.callee.dfg.value_lists;
+        let block = self.blocks[call.block(pool)].expect("branch to an unreachable block");
+        let args = self.map_values(call.args_slice(pool));
+        self.func.dfg.block_call(block, &args)
+    }
+
+    fn map_global_value(&mut self, gv: GlobalValue) -> GlobalValue {
+        if let Some(new_gv) = self.global_values[gv].expand() {
+            return new_gv;
+        }
+        let data = match self.callee.global_values[gv].clone() {
+            GlobalValueData::Load {
+                base,
+                offset,
+                global_type,
+                readonly,
+            } => GlobalValueData::Load {
+                base: self.map_global_value(base),
+                offset,
+                global_type,
+                readonly,
+            },
+            GlobalValueData::IAddImm {
+                base,
+                offset,
+                global_type,
+            } => GlobalValueData::IAddImm {
+                base: self.map_global_value(base),
+                offset,
+                global_type,
+            },
+            GlobalValueData::Symbol {
+                name: ExternalName::User(name),
+                offset,
+                colocated,
+                tls,
+            } => GlobalValueData::Symbol {
+                name: ExternalName::User(self.func.declare_imported_user_function(
+                    self.callee.params.user_named_funcs()[name].clone(),
+                )),
+                offset,
+                colocated,
+                tls,
+            },
+            data => data,
+        };
+        let new_gv = self.func.create_global_value(data);
+        self.global_values[gv] = new_gv.into();
+        new_gv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::{Cursor, FuncCursor};
+    use crate::ir::types::*;
+    use crate::ir::{AbiParam, ExtFuncData, MemFlags, Signature};
+    use crate::isa::CallConv;
+    use crate::settings;
+    use crate::verifier::verify_function;
+
+    fn signature() -> Signature {
+        let mut sig = Signature::new(CallConv::SystemV);
+        sig.params
+            .push(AbiParam::special(I64, ArgumentPurpose::VMContext));
+        sig.params.push(AbiParam::new(I32));
+        sig.returns.push(AbiParam::new(I32));
+        sig
+    }
+
+    fn name(index: u32) -> UserExternalName {
+        UserExternalName {
+            namespace: 0,
+            index,
+        }
+    }
+
+    /// A function returning its argument plus a value loaded from its
+    /// `VMContext`, or 0 if the argument is 0.
+    fn callee() -> Function {
+        let mut func = Function::with_name_signature(UserFuncName::User(name(1)), signature());
+        let vmctx = func.create_global_value(GlobalValueData::VMContext);
+        let field = func.create_global_value(GlobalValueData::Load {
+            base: vmctx,
+            offset: 8.into(),
+            global_type: I32,
+            readonly: true,
+        });
+        let entry = func.dfg.make_block();
+        let nonzero = func.dfg.make_block();
+        func.dfg.append_block_param(entry, I64);
+        let arg = func.dfg.append_block_param(entry, I32);
+
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(entry);
+        pos.set_srcloc(SourceLoc::new(100));
+        pos.ins().brif(arg, nonzero, &[], nonzero, &[]);
+        pos.insert_block(nonzero);
+        pos.set_srcloc(SourceLoc::new(104));
+        let value = pos.ins().global_value(I32, field);
+        let sum = pos.ins().iadd(arg, value);
+        pos.ins().return_(&[sum]);
+        func
+    }
+
+    /// A function calling function 1 with the given `VMContext` and storing
+    /// the result.
+    fn caller(pass_own_vmctx: bool) -> Function {
+        let mut sig = signature();
+        sig.params.push(AbiParam::new(I64));
+        let mut func = Function::with_name_signature(UserFuncName::User(name(0)), sig);
+        let callee_sig = func.import_signature(signature());
+        let callee_name = func.declare_imported_user_function(name(1));
+        let callee_ref = func.import_function(ExtFuncData {
+            name: ExternalName::User(callee_name),
+            signature: callee_sig,
+            colocated: true,
+        });
+        let entry = func.dfg.make_block();
+        let vmctx = func.dfg.append_block_param(entry, I64);
+        let arg = func.dfg.append_block_param(entry, I32);
+        let other = func.dfg.append_block_param(entry, I64);
+
+        let mut pos = FuncCursor::new(&mut func);
+        pos.insert_block(entry);
+        pos.set_srcloc(SourceLoc::new(10));
+        let callee_vmctx = if pass_own_vmctx { vmctx } else { other };
+        let call = pos.ins().call(callee_ref, &[callee_vmctx, arg]);
+        let result = pos.func.dfg.first_result(call);
+        pos.set_srcloc(SourceLoc::new(14));
+        pos.ins().store(MemFlags::trusted(), result, vmctx, 0);
+        pos.ins().return_(&[result]);
+        func
+    }
+
+    fn has_calls(func: &Function) -> bool {
+        func.layout.blocks().any(|block| {
+            func.layout
+                .block_insts(block)
+                .any(|inst| func.dfg.insts[inst].opcode().is_call())
+        })
+    }
+
+    #[test]
+    fn inline_small_callee() {
+        let callee = callee();
+        let mut func = caller(true);
+        let inlined = do_inlining(&mut func, &InlineOptions::default(), |name| {
+            assert_eq!(*name, self::name(1));
+            Some(&callee)
+        });
+        let flags = settings::Flags::new(settings::builder());
+        verify_function(&func, &flags).unwrap();
+
+        assert_eq!(inlined.len(), 1);
+        assert_eq!(inlined[0].callee, name(1));
+        assert_eq!(inlined[0].srcloc, SourceLoc::new(10));
+        assert!(!has_calls(&func));
+
+        // The inlined instructions keep their source locations, and are the
+        // instructions of the returned blocks.
+        let srclocs = |blocks: &mut dyn Iterator<Item = Block>| {
+            blocks
+                .flat_map(|block| func.layout.block_insts(block))
+                .map(|inst| func.srcloc(inst).bits())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            srclocs(&mut func.layout.blocks()),
+            [10, 100, 104, 104, 104, 14, 14]
+        );
+        assert_eq!(
+            srclocs(&mut inlined[0].blocks.iter().copied()),
+            [100, 104, 104, 104]
+        );
+    }
+
+    #[test]
+    fn limits_and_vmctx() {
+        let callee = callee();
+
+        // The callee only sees the same `VMContext` if it's passed the
+        // caller's own.
+        let mut func = caller(false);
+        let inlined = do_inlining(&mut func, &InlineOptions::default(), |_| Some(&callee));
+        assert!(inlined.is_empty());
+        assert!(has_calls(&func));
+
+        let mut func = caller(true);
+        let options = InlineOptions {
+            max_callee_cost: inline_cost(&callee) - 1,
+            ..InlineOptions::default()
+        };
+        assert!(do_inlining(&mut func, &options, |_| Some(&callee)).is_empty());
+        assert!(do_inlining(&mut func, &InlineOptions::default(), |_| None).is_empty());
+        assert!(has_calls(&func));
+    }
+}
@@ -188,6 +188,10 @@ pub struct FunctionStencil {
     /// ensure that a trap happens if the stack pointer goes below the
     /// threshold specified here.
     pub stack_limit: Option<ir::GlobalValue>,
+
+    /// Execution counts of the blocks of this function, used to guide code
+    /// layout. Empty unless a profile was provided.
+    pub block_profile: ir::BlockProfile,
 }
 
 impl FunctionStencil {
@@ -201,6 +205,7 @@ impl FunctionStencil {
         self.layout.clear();
         self.srclocs.clear();
         self.stack_limit = None;
+        self.block_profile.clear();
     }
 
     /// Creates a jump table in the function, to be used by `br_table` instructions.
@@ -398,6 +403,7 @@ impl Function {
                 layout: Layout::new(),
                 srclocs: SecondaryMap::new(),
                 stack_limit: None,
+                block_profile: ir::BlockProfile::new(),
             },
             params: FunctionParameters::new(),
         }
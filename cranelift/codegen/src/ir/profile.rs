@@ -0,0 +1,111 @@
+//! Execution profiles.
+//!
+//! A [`BlockProfile`] records how many times the blocks of a function, and
+//! the edges between them, were executed. Code generation uses it to lay out
+//! hot blocks along fallthrough paths and to move code which never ran out of
+//! the way, to the end of the function.
+
+use crate::ir::Block;
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "enable-serde")]
+use serde::{Deserialize, Serialize};
+
+/// Execution counts of the blocks of a function and of the edges between
+/// them.
+///
+/// Edges are named by their predecessor and by the index of the successor in
+/// its terminator, in the order of `InstructionData::branch_destination`: the
+/// `then` block of a `brif` is successor 0, and the default block of a
+/// `br_table` is successor 0 followed by the entries of its jump table.
+///
+/// A profile is either empty, in which case it is ignored, or is assumed to
+/// cover the whole function: blocks and edges which aren't in a non-empty
+/// profile have never been executed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct BlockProfile {
+    blocks: BTreeMap<Block, u64>,
+    edges: BTreeMap<(Block, u32), u64>,
+}
+
+impl BlockProfile {
+    /// Create an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Is this profile empty?
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.edges.is_empty()
+    }
+
+    /// Clear all counts.
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.edges.clear();
+    }
+
+    /// Set the number of times `block` was executed.
+    pub fn set_block_count(&mut self, block: Block, count: u64) {
+        self.blocks.insert(block, count);
+    }
+
+    /// Get the number of times `block` was executed, if it's in the profile.
+    pub fn block_count(&self, block: Block) -> Option<u64> {
+        self.blocks.get(&block).copied()
+    }
+
+    /// Set the number of times the edge from `block` to its successor with
+    /// index `succ_idx` was taken.
+    pub fn set_edge_count(&mut self, block: Block, succ_idx: u32, count: u64) {
+        self.edges.insert((block, succ_idx), count);
+    }
+
+    /// Get the number of times the edge from `block` to its successor with
+    /// index `succ_idx` was taken, if it's in the profile.
+    pub fn edge_count(&self, block: Block, succ_idx: u32) -> Option<u64> {
+        self.edges.get(&(block, succ_idx)).copied()
+    }
+
+    /// Iterate over the block counts of the profile.
+    pub fn block_counts(&self) -> impl Iterator<Item = (Block, u64)> + '_ {
+        self.blocks.iter().map(|(block, count)| (*block, *count))
+    }
+
+    /// Iterate over the edge counts of the profile, as `(block, succ_idx,
+    /// count)`.
+    pub fn edge_counts(&self) -> impl Iterator<Item = (Block, u32, u64)> + '_ {
+        self.edges
+            .iter()
+            .map(|((block, succ_idx), count)| (*block, *succ_idx, *count))
+    }
+
+    /// Build a profile from the values of the counters inserted by
+    /// `Context::instrument_block_profile`, where `counters` describes what
+    /// each of them counts.
+    pub fn from_counters(counters: &[ProfileCounter], values: &[u64]) -> Self {
+        debug_assert_eq!(counters.len(), values.len());
+        let mut profile = Self::new();
+        for (counter, value) in counters.iter().zip(values) {
+            match *counter {
+                ProfileCounter::Block(block) => profile.set_block_count(block, *value),
+                ProfileCounter::Edge(block, succ_idx) => {
+                    profile.set_edge_count(block, succ_idx, *value)
+                }
+            }
+        }
+        profile
+    }
+}
+
+/// What a counter inserted by `Context::instrument_block_profile` counts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub enum ProfileCounter {
+    /// The number of times a block was executed.
+    Block(Block),
+    /// The number of times the edge from a block to its successor with the
+    /// given index was taken.
+    Edge(Block, u32),
+}
@@ -67,9 +67,20 @@ impl CallConv {
     }
 
     /// Does this calling convention support tail calls?
+    ///
+    /// Functions using the `tail` convention pop their own stack arguments,
+    /// so a tail call between them may pass more stack arguments than the
+    /// caller received; Wasmtime compiles WebAssembly functions with it when
+    /// the tail calls proposal is enabled. The Wasmtime conventions support
+    /// tail calls too, but since the caller's caller pops the stack arguments
+    /// there, the callee must not need more stack argument space than the
+    /// caller.
     pub fn supports_tail_calls(&self) -> bool {
         match self {
-            CallConv::Tail => true,
+            CallConv::Tail
+            | CallConv::WasmtimeSystemV
+            | CallConv::WasmtimeFastcall
+            | CallConv::WasmtimeAppleAarch64 => true,
             _ => false,
         }
     }
@@ -235,7 +235,13 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         Inst::Args { args }
     }
 
-    fn gen_ret(_setup_frame: bool, _isa_flags: &Self::F, rets: Vec<RetPair>) -> Inst {
+    fn gen_ret(
+        _setup_frame: bool,
+        _isa_flags: &Self::F,
+        rets: Vec<RetPair>,
+        stack_bytes_to_pop: u32,
+    ) -> Inst {
+        debug_assert_eq!(stack_bytes_to_pop, 0);
         Inst::Ret { rets }
     }
 
@@ -95,6 +95,9 @@ impl TargetIsa for Riscv64Backend {
             buffer,
             frame_size,
             vcode: emit_result.disasm,
+            vcode_srclocs: emit_result.disasm_srclocs,
+            lowered_vcode: emit_result.lowered_disasm,
+            lowered_vcode_srclocs: emit_result.lowered_disasm_srclocs,
             value_labels_ranges,
             sized_stackslot_offsets,
             dynamic_stackslot_offsets,
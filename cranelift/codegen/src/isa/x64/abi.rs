@@ -198,7 +198,9 @@ impl ABIMachineSpec for X64ABIMachineSpec {
                     // Note that in all cases 16-byte stack alignment happens
                     // separately after all args.
                     let size = reg_ty.bits() / 8;
-                    let size = if args_or_rets == ArgsOrRets::Rets && call_conv.extends_wasmtime() {
+                    let size = if args_or_rets == ArgsOrRets::Rets
+                        && (call_conv.extends_wasmtime() || call_conv == CallConv::Tail)
+                    {
                         size
                     } else {
                         std::cmp::max(size, 8)
@@ -251,6 +253,15 @@ impl ABIMachineSpec for X64ABIMachineSpec {
             return Err(CodegenError::ImplLimitExceeded);
         }
 
+        // Functions using the tail calling convention pop their stack
+        // arguments with `ret imm16`.
+        if args_or_rets == ArgsOrRets::Args
+            && call_conv == CallConv::Tail
+            && next_stack > u32::from(u16::MAX)
+        {
+            return Err(CodegenError::ImplLimitExceeded);
+        }
+
         Ok((next_stack, extra_arg))
     }
 
@@ -301,8 +312,9 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         _setup_frame: bool,
         _isa_flags: &x64_settings::Flags,
         rets: Vec<RetPair>,
+        stack_bytes_to_pop: u32,
     ) -> Self::I {
-        Inst::ret(rets)
+        Inst::ret(rets, stack_bytes_to_pop)
     }
 
     fn gen_add_imm(into_reg: Writable<Reg>, from_reg: Reg, imm: u32) -> SmallInstVec<Self::I> {
@@ -434,6 +446,7 @@ impl ABIMachineSpec for X64ABIMachineSpec {
                 defs: smallvec![],
                 clobbers: PRegSet::empty(),
                 opcode: Opcode::Call,
+                callee_conv: CallConv::Probestack,
             }),
         });
     }
@@ -536,38 +549,12 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         fixed_frame_storage_size: u32,
         _outgoing_args_size: u32,
     ) -> SmallVec<[Self::I; 16]> {
-        let mut insts = SmallVec::new();
-
         let clobbered_callee_saves =
             Self::get_clobbered_callee_saves(call_conv, flags, sig, clobbers);
         let stack_size = fixed_frame_storage_size + compute_clobber_size(&clobbered_callee_saves);
 
-        // Restore regs by loading from offsets of RSP. RSP will be
-        // returned to nominal-RSP at this point, so we can use the
-        // same offsets that we used when saving clobbers above.
-        let mut cur_offset = fixed_frame_storage_size;
-        for reg in &clobbered_callee_saves {
-            let rreg = reg.to_reg();
-            match rreg.class() {
-                RegClass::Int => {
-                    insts.push(Inst::mov64_m_r(
-                        Amode::imm_reg(cur_offset, regs::rsp()),
-                        Writable::from_reg(rreg.into()),
-                    ));
-                    cur_offset += 8;
-                }
-                RegClass::Float => {
-                    cur_offset = align_to(cur_offset, 16);
-                    insts.push(Inst::load(
-                        types::I8X16,
-                        Amode::imm_reg(cur_offset, regs::rsp()),
-                        Writable::from_reg(rreg.into()),
-                        ExtKind::None,
-                    ));
-                    cur_offset += 16;
-                }
-            }
-        }
+        let mut insts =
+            gen_clobber_restore_loads(&clobbered_callee_saves, fixed_frame_storage_size);
         // Adjust RSP back upward.
         if stack_size > 0 {
             insts.push(Inst::alu_rmi_r(
@@ -589,13 +576,20 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         clobbers: PRegSet,
         opcode: ir::Opcode,
         tmp: Writable<Reg>,
-        _callee_conv: isa::CallConv,
+        callee_conv: isa::CallConv,
         _caller_conv: isa::CallConv,
     ) -> SmallVec<[Self::I; 2]> {
         let mut insts = SmallVec::new();
         match dest {
             &CallDest::ExtName(ref name, RelocDistance::Near) => {
-                insts.push(Inst::call_known(name.clone(), uses, defs, clobbers, opcode));
+                insts.push(Inst::call_known(
+                    name.clone(),
+                    uses,
+                    defs,
+                    clobbers,
+                    opcode,
+                    callee_conv,
+                ));
             }
             &CallDest::ExtName(ref name, RelocDistance::Far) => {
                 insts.push(Inst::LoadExtName {
@@ -609,6 +603,7 @@ impl ABIMachineSpec for X64ABIMachineSpec {
                     defs,
                     clobbers,
                     opcode,
+                    callee_conv,
                 ));
             }
             &CallDest::Reg(reg) => {
@@ -618,12 +613,62 @@ impl ABIMachineSpec for X64ABIMachineSpec {
                     defs,
                     clobbers,
                     opcode,
+                    callee_conv,
                 ));
             }
         }
         insts
     }
 
+    fn supports_return_calls() -> bool {
+        true
+    }
+
+    fn gen_return_call(
+        dest: &CallDest,
+        uses: CallArgList,
+        _opcode: ir::Opcode,
+        tmp: Writable<Reg>,
+        new_stack_arg_size: u32,
+        old_stack_arg_size: u32,
+    ) -> SmallVec<[Self::I; 2]> {
+        let info = ReturnCallInfo {
+            uses,
+            new_stack_arg_size,
+            old_stack_arg_size,
+        };
+        let mut insts = SmallVec::new();
+        match dest {
+            &CallDest::ExtName(ref name, RelocDistance::Near) => {
+                insts.push(Inst::return_call_known(name.clone(), info));
+            }
+            &CallDest::ExtName(ref name, RelocDistance::Far) => {
+                insts.push(Inst::LoadExtName {
+                    dst: tmp,
+                    name: Box::new(name.clone()),
+                    offset: 0,
+                });
+                insts.push(Inst::return_call_unknown(tmp.to_reg(), info));
+            }
+            &CallDest::Reg(reg) => {
+                insts.push(Inst::return_call_unknown(reg, info));
+            }
+        }
+        insts
+    }
+
+    fn gen_return_call_clobber_restore(
+        call_conv: isa::CallConv,
+        sig: &Signature,
+        flags: &settings::Flags,
+        clobbers: &[Writable<RealReg>],
+        fixed_frame_storage_size: u32,
+    ) -> SmallVec<[Self::I; 16]> {
+        let clobbered_callee_saves =
+            Self::get_clobbered_callee_saves(call_conv, flags, sig, clobbers);
+        gen_clobber_restore_loads(&clobbered_callee_saves, fixed_frame_storage_size)
+    }
+
     fn gen_memcpy<F: FnMut(Type) -> Writable<Reg>>(
         call_conv: isa::CallConv,
         dst: Reg,
@@ -666,6 +711,7 @@ impl ABIMachineSpec for X64ABIMachineSpec {
             /* defs = */ smallvec![],
             /* clobbers = */ Self::get_regs_clobbered_by_call(call_conv),
             Opcode::Call,
+            call_conv,
         ));
         insts
     }
@@ -708,8 +754,11 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         regs: &[Writable<RealReg>],
     ) -> Vec<Writable<RealReg>> {
         let mut regs: Vec<Writable<RealReg>> = match call_conv {
-            CallConv::Tail => unimplemented!(),
-            CallConv::Fast | CallConv::Cold | CallConv::SystemV | CallConv::WasmtimeSystemV => regs
+            CallConv::Fast
+            | CallConv::Cold
+            | CallConv::Tail
+            | CallConv::SystemV
+            | CallConv::WasmtimeSystemV => regs
                 .iter()
                 .cloned()
                 .filter(|r| is_callee_save_systemv(r.to_reg(), flags.enable_pinned_reg()))
@@ -774,6 +823,42 @@ impl From<StackAMode> for SyntheticAmode {
     }
 }
 
+/// Restore the clobbered callee-saved registers saved by `gen_clobber_save`.
+fn gen_clobber_restore_loads(
+    clobbered_callee_saves: &[Writable<RealReg>],
+    fixed_frame_storage_size: u32,
+) -> SmallVec<[Inst; 16]> {
+    let mut insts = SmallVec::new();
+
+    // Restore regs by loading from offsets of RSP. RSP will be
+    // returned to nominal-RSP at this point, so we can use the
+    // same offsets that we used when saving clobbers above.
+    let mut cur_offset = fixed_frame_storage_size;
+    for reg in clobbered_callee_saves {
+        let rreg = reg.to_reg();
+        match rreg.class() {
+            RegClass::Int => {
+                insts.push(Inst::mov64_m_r(
+                    Amode::imm_reg(cur_offset, regs::rsp()),
+                    Writable::from_reg(rreg.into()),
+                ));
+                cur_offset += 8;
+            }
+            RegClass::Float => {
+                cur_offset = align_to(cur_offset, 16);
+                insts.push(Inst::load(
+                    types::I8X16,
+                    Amode::imm_reg(cur_offset, regs::rsp()),
+                    Writable::from_reg(rreg.into()),
+                    ExtKind::None,
+                ));
+                cur_offset += 16;
+            }
+        }
+    }
+    insts
+}
+
 fn get_intreg_for_arg(call_conv: &CallConv, idx: usize, arg_idx: usize) -> Option<Reg> {
     let is_fastcall = call_conv.extends_windows_fastcall();
 
@@ -824,13 +909,15 @@ fn get_intreg_for_retval(
     retval_idx: usize,
 ) -> Option<Reg> {
     match call_conv {
-        CallConv::Tail => unimplemented!(),
         CallConv::Fast | CallConv::Cold | CallConv::SystemV => match intreg_idx {
             0 => Some(regs::rax()),
             1 => Some(regs::rdx()),
             _ => None,
         },
-        CallConv::WasmtimeSystemV | CallConv::WasmtimeFastcall => {
+        // The tail calling convention returns values like the Wasmtime
+        // conventions, so that Wasmtime can use it for Wasm functions without
+        // changing how they return values to the host.
+        CallConv::Tail | CallConv::WasmtimeSystemV | CallConv::WasmtimeFastcall => {
             if intreg_idx == 0 && retval_idx == 0 {
                 Some(regs::rax())
             } else {
@@ -853,13 +940,12 @@ fn get_fltreg_for_retval(
     retval_idx: usize,
 ) -> Option<Reg> {
     match call_conv {
-        CallConv::Tail => unimplemented!(),
         CallConv::Fast | CallConv::Cold | CallConv::SystemV => match fltreg_idx {
             0 => Some(regs::xmm0()),
             1 => Some(regs::xmm1()),
             _ => None,
         },
-        CallConv::WasmtimeFastcall | CallConv::WasmtimeSystemV => {
+        CallConv::Tail | CallConv::WasmtimeFastcall | CallConv::WasmtimeSystemV => {
             if fltreg_idx == 0 && retval_idx == 0 {
                 Some(regs::xmm0())
             } else {
@@ -1462,6 +1462,7 @@ pub(crate) fn emit(
             if call_info.opcode.is_call() {
                 sink.add_call_site(call_info.opcode);
             }
+            emit_restore_sp_after_call(call_info, sink, info, state);
         }
 
         Inst::CallUnknown {
@@ -1506,11 +1507,53 @@ pub(crate) fn emit(
             if call_info.opcode.is_call() {
                 sink.add_call_site(call_info.opcode);
             }
+            emit_restore_sp_after_call(call_info, sink, info, state);
+        }
+
+        Inst::ReturnCallKnown {
+            dest,
+            info: call_info,
+        } => {
+            emit_return_call_frame_teardown(call_info, sink, info, state);
+            // jmp rel32, relocated against the callee like a direct call.
+            sink.put1(0xE9);
+            // The addend adjusts for the difference between the end of the instruction and the
+            // beginning of the immediate field.
+            emit_reloc(sink, Reloc::X86CallPCRel4, &dest, -4);
+            sink.put4(0);
+        }
+
+        Inst::ReturnCallUnknown {
+            dest,
+            info: call_info,
+        } => {
+            let dest = allocs.next(*dest);
+            debug_assert_eq!(dest, regs::r11());
+            emit_return_call_frame_teardown(call_info, sink, info, state);
+            emit_std_enc_enc(
+                sink,
+                LegacyPrefixes::None,
+                0xFF,
+                1,
+                4, /*subopcode*/
+                int_reg_enc(dest),
+                RexFlags::clear_w(),
+            );
         }
 
         Inst::Args { .. } => {}
 
-        Inst::Ret { .. } => sink.put1(0xC3),
+        Inst::Ret {
+            stack_bytes_to_pop: 0,
+            ..
+        } => sink.put1(0xC3),
+
+        Inst::Ret {
+            stack_bytes_to_pop, ..
+        } => {
+            sink.put1(0xC2);
+            sink.put2(u16::try_from(*stack_bytes_to_pop).unwrap());
+        }
 
         Inst::JmpKnown { dst } => {
             let br_start = sink.cur_offset();
@@ -3876,3 +3919,108 @@ pub(crate) fn emit(
 
     state.clear_post_insn();
 }
+
+/// Restore SP after a call to a function using the tail calling convention.
+///
+/// Such a callee pops its stack arguments when it returns, but may also have
+/// made a return call to a function that doesn't, so SP is recomputed from FP
+/// rather than adjusted by the size of the callee's stack arguments.
+fn emit_restore_sp_after_call(
+    call_info: &CallInfo,
+    sink: &mut MachBuffer<Inst>,
+    info: &EmitInfo,
+    state: &mut EmitState,
+) {
+    if call_info.callee_conv != CallConv::Tail {
+        return;
+    }
+    let sp_to_fp = state.nominal_sp_to_fp + state.virtual_sp_offset;
+    let sp_to_fp = i32::try_from(sp_to_fp).expect("frame size fits in 32 bits");
+    let inst = Inst::lea(
+        Amode::imm_reg(sp_to_fp.wrapping_neg() as u32, regs::rbp()),
+        Writable::from_reg(regs::rsp()),
+    );
+    inst.emit(&[], sink, info, state);
+}
+
+/// Tear down the current frame for a return call, right before jumping to the
+/// callee.
+///
+/// The callee's stack arguments are staged at nominal SP, above a slot for
+/// the return address (see `Caller::stack_arg_amode`). Those are moved to the
+/// top of the incoming stack argument area of the current function, which
+/// grows or shrinks to fit them, and the return address is moved right below
+/// them. This sequence only uses `%rax` and `%r10`, which are neither
+/// callee-saved nor used to pass arguments.
+fn emit_return_call_frame_teardown(
+    call_info: &ReturnCallInfo,
+    sink: &mut MachBuffer<Inst>,
+    info: &EmitInfo,
+    state: &mut EmitState,
+) {
+    let rsp = regs::rsp();
+    let rbp = regs::rbp();
+    let new_size = call_info.new_stack_arg_size;
+    let old_size = call_info.old_stack_arg_size;
+
+    if new_size == 0 && old_size == 0 {
+        // Nothing needs to move: this is the regular frame teardown.
+        Inst::mov_r_r(OperandSize::Size64, rbp, Writable::from_reg(rsp)).emit(
+            &[],
+            sink,
+            info,
+            state,
+        );
+        Inst::pop64(Writable::from_reg(rbp)).emit(&[], sink, info, state);
+        return;
+    }
+
+    debug_assert_eq!(state.virtual_sp_offset, 0);
+    let tmp = regs::r10();
+    let caller_fp = regs::rax();
+
+    // Stage the return address too, and load our caller's FP, before the
+    // copy below overwrites them.
+    Inst::mov64_m_r(Amode::imm_reg(8, rbp), Writable::from_reg(tmp)).emit(&[], sink, info, state);
+    Inst::mov_r_m(OperandSize::Size64, tmp, Amode::imm_reg(0, rsp)).emit(&[], sink, info, state);
+    Inst::mov64_m_r(Amode::imm_reg(0, rbp), Writable::from_reg(caller_fp)).emit(
+        &[],
+        sink,
+        info,
+        state,
+    );
+
+    // Copy the staged return address and arguments into place. The
+    // destination is above the source and they may overlap, so copy from
+    // the top down.
+    let dst_offset = 8 + i64::from(old_size) - i64::from(new_size);
+    let dst_offset = i32::try_from(dst_offset).unwrap();
+    for word in (0..(new_size + 8) / 8).rev() {
+        let offset = 8 * word as i32;
+        Inst::mov64_m_r(Amode::imm_reg(offset as u32, rsp), Writable::from_reg(tmp)).emit(
+            &[],
+            sink,
+            info,
+            state,
+        );
+        Inst::mov_r_m(
+            OperandSize::Size64,
+            tmp,
+            Amode::imm_reg((dst_offset + offset) as u32, rbp),
+        )
+        .emit(&[], sink, info, state);
+    }
+
+    // Point SP at the moved return address, and restore our caller's FP.
+    Inst::lea(
+        Amode::imm_reg(dst_offset as u32, rbp),
+        Writable::from_reg(rsp),
+    )
+    .emit(&[], sink, info, state);
+    Inst::mov_r_r(OperandSize::Size64, caller_fp, Writable::from_reg(rbp)).emit(
+        &[],
+        sink,
+        info,
+        state,
+    );
+}
@@ -3802,6 +3802,7 @@ fn test_x64_emit() {
             smallvec![],
             PRegSet::default(),
             Opcode::Call,
+            CallConv::SystemV,
         ),
         "E800000000",
         "call    User(userextname0)",
@@ -3816,6 +3817,7 @@ fn test_x64_emit() {
             smallvec![],
             PRegSet::default(),
             Opcode::CallIndirect,
+            CallConv::SystemV,
         )
     }
 
@@ -3875,7 +3877,8 @@ fn test_x64_emit() {
 
     // ========================================================
     // Ret
-    insns.push((Inst::ret(vec![]), "C3", "ret"));
+    insns.push((Inst::ret(vec![], 0), "C3", "ret"));
+    insns.push((Inst::ret(vec![], 16), "C21000", "ret 16"));
 
     // ========================================================
     // JmpKnown skipped for now
@@ -41,6 +41,23 @@ pub struct CallInfo {
     pub clobbers: PRegSet,
     /// The opcode of this call.
     pub opcode: Opcode,
+    /// The calling convention of the callee. Callees using the tail calling
+    /// convention pop their stack arguments, so SP is restored from FP after
+    /// calling one.
+    pub callee_conv: CallConv,
+}
+
+/// Out-of-line data for return calls, to keep the size of `Inst` down.
+#[derive(Clone, Debug)]
+pub struct ReturnCallInfo {
+    /// Register uses of this return call.
+    pub uses: CallArgList,
+    /// The size of the callee's stack arguments, which are staged at the
+    /// bottom of the current frame.
+    pub new_stack_arg_size: u32,
+    /// The size of the incoming stack argument area of the current function
+    /// that the callee's stack arguments replace.
+    pub old_stack_arg_size: u32,
 }
 
 #[test]
@@ -71,6 +88,8 @@ impl Inst {
             | Inst::Bswap { .. }
             | Inst::CallKnown { .. }
             | Inst::CallUnknown { .. }
+            | Inst::ReturnCallKnown { .. }
+            | Inst::ReturnCallUnknown { .. }
             | Inst::CheckedSRemSeq { .. }
             | Inst::CheckedSRemSeq8 { .. }
             | Inst::Cmove { .. }
@@ -512,6 +531,7 @@ impl Inst {
         defs: CallRetList,
         clobbers: PRegSet,
         opcode: Opcode,
+        callee_conv: CallConv,
     ) -> Inst {
         Inst::CallKnown {
             dest,
@@ -520,6 +540,7 @@ impl Inst {
                 defs,
                 clobbers,
                 opcode,
+                callee_conv,
             }),
         }
     }
@@ -530,6 +551,7 @@ impl Inst {
         defs: CallRetList,
         clobbers: PRegSet,
         opcode: Opcode,
+        callee_conv: CallConv,
     ) -> Inst {
         dest.assert_regclass_is(RegClass::Int);
         Inst::CallUnknown {
@@ -539,12 +561,31 @@ impl Inst {
                 defs,
                 clobbers,
                 opcode,
+                callee_conv,
             }),
         }
     }
 
-    pub(crate) fn ret(rets: Vec<RetPair>) -> Inst {
-        Inst::Ret { rets }
+    pub(crate) fn return_call_known(dest: ExternalName, info: ReturnCallInfo) -> Inst {
+        Inst::ReturnCallKnown {
+            dest,
+            info: Box::new(info),
+        }
+    }
+
+    pub(crate) fn return_call_unknown(dest: Reg, info: ReturnCallInfo) -> Inst {
+        debug_assert_eq!(dest.class(), RegClass::Int);
+        Inst::ReturnCallUnknown {
+            dest,
+            info: Box::new(info),
+        }
+    }
+
+    pub(crate) fn ret(rets: Vec<RetPair>, stack_bytes_to_pop: u32) -> Inst {
+        Inst::Ret {
+            rets,
+            stack_bytes_to_pop,
+        }
     }
 
     pub(crate) fn jmp_known(dst: MachLabel) -> Inst {
@@ -1639,6 +1680,27 @@ impl PrettyPrint for Inst {
                 format!("{} *{}", ljustify("call".to_string()), dest)
             }
 
+            Inst::ReturnCallKnown { dest, info } => {
+                format!(
+                    "{} {:?} old_stack_arg_size:{} new_stack_arg_size:{}",
+                    ljustify("return_call".to_string()),
+                    dest,
+                    info.old_stack_arg_size,
+                    info.new_stack_arg_size,
+                )
+            }
+
+            Inst::ReturnCallUnknown { dest, info } => {
+                let dest = pretty_print_reg(*dest, 8, allocs);
+                format!(
+                    "{} *{} old_stack_arg_size:{} new_stack_arg_size:{}",
+                    ljustify("return_call".to_string()),
+                    dest,
+                    info.old_stack_arg_size,
+                    info.new_stack_arg_size,
+                )
+            }
+
             Inst::Args { args } => {
                 let mut s = "args".to_string();
                 for arg in args {
@@ -1650,8 +1712,15 @@ impl PrettyPrint for Inst {
                 s
             }
 
-            Inst::Ret { rets } => {
+            Inst::Ret {
+                rets,
+                stack_bytes_to_pop,
+            } => {
                 let mut s = "ret".to_string();
+                if *stack_bytes_to_pop != 0 {
+                    use std::fmt::Write;
+                    write!(&mut s, " {}", stack_bytes_to_pop).unwrap();
+                }
                 for ret in rets {
                     use std::fmt::Write;
                     let preg = regs::show_reg(ret.preg);
@@ -2256,6 +2325,19 @@ fn x64_get_operands<F: Fn(VReg) -> VReg>(inst: &Inst, collector: &mut OperandCol
             collector.reg_clobbers(info.clobbers);
         }
 
+        Inst::ReturnCallKnown { ref info, .. } => {
+            for u in &info.uses {
+                collector.reg_fixed_use(u.vreg, u.preg);
+            }
+        }
+
+        Inst::ReturnCallUnknown { ref info, dest } => {
+            collector.reg_fixed_use(*dest, regs::r11());
+            for u in &info.uses {
+                collector.reg_fixed_use(u.vreg, u.preg);
+            }
+        }
+
         Inst::JmpTableSeq {
             ref idx,
             ref tmp1,
@@ -2312,7 +2394,7 @@ fn x64_get_operands<F: Fn(VReg) -> VReg>(inst: &Inst, collector: &mut OperandCol
             }
         }
 
-        Inst::Ret { rets } => {
+        Inst::Ret { rets, .. } => {
             // The return value(s) are live-out; we represent this
             // with register uses on the return instruction.
             for ret in rets.iter() {
@@ -2434,6 +2516,9 @@ impl MachInst for Inst {
         match self {
             // Interesting cases.
             &Self::Ret { .. } => MachTerminator::Ret,
+            &Self::ReturnCallKnown { .. } | &Self::ReturnCallUnknown { .. } => {
+                MachTerminator::RetCall
+            }
             &Self::JmpKnown { .. } => MachTerminator::Uncond,
             &Self::JmpCond { .. } => MachTerminator::Cond,
             &Self::JmpTableSeq { .. } => MachTerminator::Indirect,
@@ -26,7 +26,7 @@ use crate::{
         unwind::UnwindInst,
         x64::{
             abi::X64Caller,
-            inst::{args::*, regs, CallInfo},
+            inst::{args::*, regs, CallInfo, ReturnCallInfo},
         },
     },
     machinst::{
@@ -41,6 +41,7 @@ use std::boxed::Box;
 use std::convert::TryFrom;
 
 type BoxCallInfo = Box<CallInfo>;
+type BoxReturnCallInfo = Box<ReturnCallInfo>;
 type BoxVecMachLabel = Box<SmallVec<[MachLabel; 4]>>;
 type MachLabelSlice = [MachLabel];
 type VecArgPair = Vec<ArgPair>;
@@ -79,6 +80,47 @@ impl Context for IsleContext<'_, '_, MInst, X64Backend> {
     isle_lower_prelude_methods!();
     isle_prelude_caller_methods!(X64ABIMachineSpec, X64Caller);
 
+    fn gen_return_call(
+        &mut self,
+        sig_ref: SigRef,
+        extname: ExternalName,
+        dist: RelocDistance,
+        args: ValueSlice,
+    ) -> InstOutput {
+        let caller_conv = self.lower_ctx.abi().call_conv(self.lower_ctx.sigs());
+        let caller = X64Caller::from_func(
+            self.lower_ctx.sigs(),
+            sig_ref,
+            &extname,
+            dist,
+            Opcode::ReturnCall,
+            caller_conv,
+            self.backend.flags().clone(),
+        )
+        .unwrap();
+        self.gen_return_call_common(caller, args)
+    }
+
+    fn gen_return_call_indirect(
+        &mut self,
+        sig_ref: SigRef,
+        val: Value,
+        args: ValueSlice,
+    ) -> InstOutput {
+        let caller_conv = self.lower_ctx.abi().call_conv(self.lower_ctx.sigs());
+        let ptr = self.put_in_reg(val);
+        let caller = X64Caller::from_ptr(
+            self.lower_ctx.sigs(),
+            sig_ref,
+            ptr,
+            Opcode::ReturnCallIndirect,
+            caller_conv,
+            self.backend.flags().clone(),
+        )
+        .unwrap();
+        self.gen_return_call_common(caller, args)
+    }
+
     #[inline]
     fn operand_size_of_type_32_64(&mut self, ty: Type) -> OperandSize {
         if ty.bits() == 64 {
@@ -1047,6 +1089,40 @@ impl Context for IsleContext<'_, '_, MInst, X64Backend> {
 impl IsleContext<'_, '_, MInst, X64Backend> {
     isle_prelude_method_helpers!(X64Caller);
 
+    fn gen_return_call_common(
+        &mut self,
+        mut caller: X64Caller,
+        (inputs, off): ValueSlice,
+    ) -> InstOutput {
+        // Stack arguments are staged at the bottom of our frame, and only
+        // moved into place by the return call itself (see
+        // `check_return_calls`).
+        let num_args = caller.num_args(self.lower_ctx.sigs());
+        assert_eq!(
+            inputs.len(&self.lower_ctx.dfg().value_lists) - off,
+            num_args
+        );
+        let mut arg_regs = vec![];
+        for i in 0..num_args {
+            let input = inputs
+                .get(off + i, &self.lower_ctx.dfg().value_lists)
+                .unwrap();
+            arg_regs.push(self.put_in_regs(input));
+        }
+        for (i, arg_regs) in arg_regs.iter().enumerate() {
+            caller.emit_copy_regs_to_buffer(self.lower_ctx, i, *arg_regs);
+        }
+        for (i, arg_regs) in arg_regs.iter().enumerate() {
+            for inst in caller.gen_arg(self.lower_ctx, i, *arg_regs) {
+                self.lower_ctx.emit(inst);
+            }
+        }
+
+        caller.emit_return_call(self.lower_ctx);
+
+        InstOutput::new()
+    }
+
     fn load_xmm_unaligned(&mut self, addr: SyntheticAmode) -> Xmm {
         let tmp = self.lower_ctx.alloc_tmp(types::F32X4).only_reg().unwrap();
         self.lower_ctx.emit(MInst::XmmUnaryRmRUnaligned {
@@ -1,6 +1,6 @@
 //! X86_64-bit Instruction Set Architecture.
 
-pub use self::inst::{args, CallInfo, EmitInfo, EmitState, Inst};
+pub use self::inst::{args, CallInfo, EmitInfo, EmitState, Inst, ReturnCallInfo};
 
 use super::{OwnedTargetIsa, TargetIsa};
 use crate::dominator_tree::DominatorTree;
@@ -88,6 +88,9 @@ impl TargetIsa for X64Backend {
             buffer,
             frame_size,
             vcode: emit_result.disasm,
+            vcode_srclocs: emit_result.disasm_srclocs,
+            lowered_vcode: emit_result.lowered_disasm,
+            lowered_vcode_srclocs: emit_result.lowered_disasm_srclocs,
             value_labels_ranges,
             sized_stackslot_offsets,
             dynamic_stackslot_offsets,
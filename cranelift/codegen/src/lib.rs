@@ -78,6 +78,7 @@ pub mod data_value;
 pub mod dbg;
 pub mod dominator_tree;
 pub mod flowgraph;
+pub mod inline;
 pub mod ir;
 pub mod isa;
 pub mod loop_analysis;
@@ -92,12 +93,13 @@ pub use crate::machinst::buffer::{
     MachCallSite, MachReloc, MachSrcLoc, MachStackMap, MachTextSectionBuilder, MachTrap,
 };
 pub use crate::machinst::{
-    CompiledCode, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, Reg,
+    CompiledCode, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, MachLabel, Reg,
     TextSectionBuilder, Writable,
 };
 
 mod alias_analysis;
 mod bitset;
+mod block_profiling;
 mod constant_hash;
 mod context;
 mod ctxhash;
@@ -110,6 +112,7 @@ mod isle_prelude;
 mod iterators;
 mod legalizer;
 mod licm;
+mod loop_opts;
 mod nan_canonicalization;
 mod opts;
 mod remove_constant_phis;
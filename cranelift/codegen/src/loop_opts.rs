@@ -0,0 +1,631 @@
+//! Loop optimizations which run ahead of the egraph pass.
+//!
+//! Two transformations are performed, both built on recognizing the
+//! induction variables of a loop, i.e. the header's block parameters which are
+//! advanced by a constant on every back edge:
+//!
+//! - Loops whose trip count is a small constant are fully unrolled. The trip
+//!   count is found by evaluating the loop's exit condition one iteration at a
+//!   time from the constant initial values of its induction variables, and the
+//!   copies of the body end up as straight-line code whose block parameters the
+//!   constant phi removal and the egraph pass then fold away.
+//!
+//! - Multiplications of an induction variable by a constant, such as the
+//!   scaling of an index into an address offset, are strength-reduced: they are
+//!   replaced by a new induction variable which starts at the scaled initial
+//!   value and is advanced by the scaled step.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::DominatorTree;
+use crate::flowgraph::{BlockPredecessor, ControlFlowGraph};
+use crate::fx::{FxHashMap, FxHashSet};
+use crate::ir::condcodes::IntCC;
+use crate::ir::{
+    Block, Function, Inst, InstBuilder, InstructionData, Opcode, Type, Value, ValueDef,
+};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::timing;
+use alloc::vec::Vec;
+
+/// The largest trip count of a loop which is fully unrolled.
+const MAX_UNROLL_TRIP_COUNT: usize = 8;
+
+/// The largest number of instructions that the copies of an unrolled loop may
+/// contain in total.
+const MAX_UNROLLED_INSTS: usize = 128;
+
+/// How deep the definitions of a loop's exit condition are followed when
+/// evaluating it.
+const MAX_EVAL_DEPTH: usize = 16;
+
+/// Performs the loop optimizations, fully unrolling small loops if `unroll` is
+/// set and strength-reducing the multiplications of induction variables.
+/// Recomputes the CFG, domtree and loop analysis if a loop was unrolled.
+pub fn do_loop_opts(
+    func: &mut Function,
+    cfg: &mut ControlFlowGraph,
+    domtree: &mut DominatorTree,
+    loop_analysis: &mut LoopAnalysis,
+    unroll: bool,
+) {
+    let _tt = timing::loop_opts();
+    debug_assert!(cfg.is_valid());
+    debug_assert!(domtree.is_valid());
+    debug_assert!(loop_analysis.is_valid());
+
+    if unroll {
+        // Unrolling a loop changes the CFG around it, so the analyses are
+        // recomputed after each loop and the remaining loops are found again
+        // by their headers.
+        let headers = innermost_loops(loop_analysis)
+            .map(|lp| loop_analysis.loop_header(lp))
+            .collect::<Vec<_>>();
+        for header in headers {
+            let lp = match loop_analysis.is_loop_header(header) {
+                Some(lp) => lp,
+                None => continue,
+            };
+            if unroll_loop(func, cfg, domtree, loop_analysis, lp) {
+                cfg.compute(func);
+                domtree.compute(func, cfg);
+                loop_analysis.compute(func, cfg, domtree);
+            }
+        }
+    }
+
+    // Strength reduction doesn't change the CFG, so the analyses stay valid.
+    for lp in loop_analysis.loops() {
+        strength_reduce(func, cfg, loop_analysis, lp);
+    }
+}
+
+/// Returns the loops which don't contain any other loop.
+fn innermost_loops(loop_analysis: &LoopAnalysis) -> impl Iterator<Item = Loop> + '_ {
+    let parents = loop_analysis
+        .loops()
+        .filter_map(|lp| loop_analysis.loop_parent(lp))
+        .collect::<FxHashSet<_>>();
+    loop_analysis
+        .loops()
+        .filter(move |lp| !parents.contains(lp))
+}
+
+/// The edges into a loop's header: the single one from outside the loop and
+/// the back edges.
+struct HeaderEdges {
+    entry: Inst,
+    back_edges: Vec<Inst>,
+}
+
+/// Finds the edges into the header of `lp`, if it has a single edge from
+/// outside the loop and all the branches to it are jumps or `brif`s which
+/// branch there once.
+fn header_edges(
+    func: &Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+) -> Option<HeaderEdges> {
+    let header = loop_analysis.loop_header(lp);
+    if func.layout.entry_block() == Some(header) {
+        return None;
+    }
+    let mut entry = None;
+    let mut back_edges = Vec::new();
+    for BlockPredecessor { block, inst } in cfg.pred_iter(header) {
+        match func.dfg.insts[inst].opcode() {
+            Opcode::Jump | Opcode::Brif => {}
+            _ => return None,
+        }
+        branch_args(func, inst, header)?;
+        if loop_analysis.is_in_loop(block, lp) {
+            back_edges.push(inst);
+        } else if entry.replace(inst).is_some() {
+            return None;
+        }
+    }
+    Some(HeaderEdges {
+        entry: entry?,
+        back_edges,
+    })
+}
+
+/// Returns the arguments passed to `block` by the branch `inst`, if it branches
+/// there exactly once.
+fn branch_args(func: &Function, inst: Inst, block: Block) -> Option<&[Value]> {
+    let mut calls = func.dfg.insts[inst]
+        .branch_destination(&func.dfg.jump_tables)
+        .iter()
+        .filter(|call| call.block(&func.dfg.value_lists) == block);
+    let call = calls.next()?;
+    if calls.next().is_some() {
+        return None;
+    }
+    Some(call.args_slice(&func.dfg.value_lists))
+}
+
+/// Returns the value of `value` if it's defined by an `iconst`.
+fn const_value(func: &Function, value: Value) -> Option<u64> {
+    let value = func.dfg.resolve_aliases(value);
+    match func.dfg.value_def(value) {
+        ValueDef::Result(inst, 0) => match func.dfg.insts[inst] {
+            InstructionData::UnaryImm {
+                opcode: Opcode::Iconst,
+                imm,
+            } => Some(mask(func.dfg.value_type(value), imm.bits() as u64)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether values of `ty` are evaluated by this pass.
+fn is_small_int(ty: Type) -> bool {
+    ty.is_int() && ty.bits() <= 64
+}
+
+/// Truncates `x` to the width of `ty`.
+fn mask(ty: Type, x: u64) -> u64 {
+    if ty.bits() >= 64 {
+        x
+    } else {
+        x & ((1 << ty.bits()) - 1)
+    }
+}
+
+/// Sign-extends `x` from the width of `ty`.
+fn sign_extend(ty: Type, x: u64) -> i64 {
+    let shift = 64 - ty.bits();
+    ((x << shift) as i64) >> shift
+}
+
+/// Evaluates the integer comparison `cond` of `x` and `y`, of type `ty`.
+fn compare(cond: IntCC, ty: Type, x: u64, y: u64) -> bool {
+    let (sx, sy) = (sign_extend(ty, x), sign_extend(ty, y));
+    match cond {
+        IntCC::Equal => x == y,
+        IntCC::NotEqual => x != y,
+        IntCC::SignedLessThan => sx < sy,
+        IntCC::SignedGreaterThanOrEqual => sx >= sy,
+        IntCC::SignedGreaterThan => sx > sy,
+        IntCC::SignedLessThanOrEqual => sx <= sy,
+        IntCC::UnsignedLessThan => x < y,
+        IntCC::UnsignedGreaterThanOrEqual => x >= y,
+        IntCC::UnsignedGreaterThan => x > y,
+        IntCC::UnsignedLessThanOrEqual => x <= y,
+    }
+}
+
+/// Evaluates `value` in an iteration of the loop with header `header`, given
+/// the values of the header's parameters in `params`, or returns `None` if it
+/// isn't a constant in that iteration.
+fn eval(
+    func: &Function,
+    header: Block,
+    params: &[Option<u64>],
+    value: Value,
+    depth: usize,
+) -> Option<u64> {
+    if depth > MAX_EVAL_DEPTH {
+        return None;
+    }
+    let value = func.dfg.resolve_aliases(value);
+    let ty = func.dfg.value_type(value);
+    if !is_small_int(ty) {
+        return None;
+    }
+    let arg = |value| eval(func, header, params, value, depth + 1);
+    let inst = match func.dfg.value_def(value) {
+        ValueDef::Param(block, i) if block == header => return params[i],
+        ValueDef::Result(inst, 0) => inst,
+        _ => return None,
+    };
+    let result = match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => imm.bits() as u64,
+        InstructionData::Binary { opcode, args } => {
+            let (x, y) = (arg(args[0])?, arg(args[1])?);
+            match opcode {
+                Opcode::Iadd => x.wrapping_add(y),
+                Opcode::Isub => x.wrapping_sub(y),
+                Opcode::Imul => x.wrapping_mul(y),
+                Opcode::Band => x & y,
+                Opcode::Bor => x | y,
+                Opcode::Bxor => x ^ y,
+                _ => return None,
+            }
+        }
+        InstructionData::Unary { opcode, arg: x } => {
+            let arg_ty = func.dfg.value_type(x);
+            let x = arg(x)?;
+            match opcode {
+                Opcode::Uextend | Opcode::Ireduce => x,
+                Opcode::Sextend => sign_extend(arg_ty, x) as u64,
+                _ => return None,
+            }
+        }
+        InstructionData::IntCompare {
+            opcode: Opcode::Icmp,
+            cond,
+            args,
+        } => {
+            let arg_ty = func.dfg.value_type(args[0]);
+            compare(cond, arg_ty, arg(args[0])?, arg(args[1])?) as u64
+        }
+        InstructionData::IntCompareImm {
+            opcode: Opcode::IcmpImm,
+            cond,
+            arg: x,
+            imm,
+        } => {
+            let arg_ty = func.dfg.value_type(x);
+            let y = mask(arg_ty, imm.bits() as u64);
+            compare(cond, arg_ty, arg(x)?, y) as u64
+        }
+        _ => return None,
+    };
+    Some(mask(ty, result))
+}
+
+/// Fully unrolls `lp` if it's an innermost loop with a small constant trip
+/// count. Returns whether the loop was unrolled.
+///
+/// The loop must be entered and left through a single edge each, have a
+/// single back edge, and be left from its header or from the block with the
+/// back edge, so that the exit condition is checked exactly once per
+/// iteration. The copies of the body execute all iterations but the last one,
+/// and the original blocks execute the last one so that values used after the
+/// loop keep referring to it.
+fn unroll_loop(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+) -> bool {
+    let header = loop_analysis.loop_header(lp);
+    let edges = match header_edges(func, cfg, loop_analysis, lp) {
+        Some(edges) if edges.back_edges.len() == 1 => edges,
+        _ => return false,
+    };
+    let back_edge = edges.back_edges[0];
+    let init = match branch_args(func, edges.entry, header) {
+        Some(args) => args
+            .iter()
+            .map(|&arg| const_value(func, arg))
+            .collect::<Vec<_>>(),
+        None => return false,
+    };
+    let back_args = match branch_args(func, back_edge, header) {
+        Some(args) => args.to_vec(),
+        None => return false,
+    };
+
+    // Blocks are copied in reverse postorder so that values are defined before
+    // their uses are copied.
+    let blocks = domtree
+        .cfg_postorder()
+        .iter()
+        .rev()
+        .copied()
+        .filter(|&block| loop_analysis.is_in_loop(block, lp))
+        .collect::<Vec<_>>();
+
+    // Find the loop's single exit, which must be a `brif` in the header or the
+    // block with the back edge.
+    let mut exit = None;
+    let mut num_insts = 0;
+    for &block in &blocks {
+        num_insts += func.layout.block_insts(block).count();
+        let inst = match func.layout.last_inst(block) {
+            Some(inst) => inst,
+            None => return false,
+        };
+        match func.dfg.insts[inst].opcode() {
+            Opcode::Jump | Opcode::Brif => {}
+            Opcode::Trap | Opcode::ResumableTrap => continue,
+            _ => return false,
+        }
+        let dests = func.dfg.insts[inst].branch_destination(&func.dfg.jump_tables);
+        let exits = dests
+            .iter()
+            .filter(|call| !loop_analysis.is_in_loop(call.block(&func.dfg.value_lists), lp))
+            .count();
+        if exits == 0 {
+            continue;
+        }
+        if exits != 1
+            || dests.len() != 2
+            || exit.replace(inst).is_some()
+            || (block != header && inst != back_edge)
+        {
+            return false;
+        }
+    }
+    let exit = match exit {
+        Some(exit) => exit,
+        None => return false,
+    };
+    let exit_calls = func.dfg.insts[exit].branch_destination(&func.dfg.jump_tables);
+    let exit_on_true = !loop_analysis.is_in_loop(exit_calls[0].block(&func.dfg.value_lists), lp);
+    let cond = func.dfg.inst_args(exit)[0];
+
+    // Run the loop's induction variables through the iterations until the
+    // exit is taken.
+    let mut params = init;
+    let mut trip_count = 0;
+    loop {
+        trip_count += 1;
+        if trip_count > MAX_UNROLL_TRIP_COUNT || trip_count * num_insts > MAX_UNROLLED_INSTS {
+            return false;
+        }
+        let taken = match eval(func, header, &params, cond, 0) {
+            Some(taken) => taken != 0,
+            None => return false,
+        };
+        if taken == exit_on_true {
+            break;
+        }
+        params = back_args
+            .iter()
+            .map(|&arg| eval(func, header, &params, arg, 0))
+            .collect();
+    }
+
+    // Make the copies of the loop for the iterations before the last one,
+    // chaining each copy's back edge to the next copy's header.
+    let mut copies: Vec<FxHashMap<Block, Block>> = Vec::new();
+    for _ in 1..trip_count {
+        let mut block_map = FxHashMap::default();
+        for &block in &blocks {
+            let new_block = func.dfg.make_block();
+            func.layout.insert_block(new_block, header);
+            if func.layout.is_cold(block) {
+                func.layout.set_cold(new_block);
+            }
+            block_map.insert(block, new_block);
+        }
+        copies.push(block_map);
+    }
+    for i in 0..copies.len() {
+        let next_header = copies.get(i + 1).map_or(header, |copy| copy[&header]);
+        let mut value_map = FxHashMap::default();
+        for &block in &blocks {
+            let new_block = copies[i][&block];
+            for j in 0..func.dfg.num_block_params(block) {
+                let param = func.dfg.block_params(block)[j];
+                let ty = func.dfg.value_type(param);
+                let new_param = func.dfg.append_block_param(new_block, ty);
+                value_map.insert(param, new_param);
+            }
+            let mut next = func.layout.first_inst(block);
+            while let Some(inst) = next {
+                next = func.layout.next_inst(inst);
+                let new_inst = func.dfg.clone_inst(inst);
+                for (&result, &new_result) in func
+                    .dfg
+                    .inst_results(inst)
+                    .iter()
+                    .zip(func.dfg.inst_results(new_inst))
+                {
+                    value_map.insert(result, new_result);
+                }
+                func.dfg.map_inst_values(new_inst, |dfg, value| {
+                    let value = dfg.resolve_aliases(value);
+                    value_map.get(&value).copied().unwrap_or(value)
+                });
+                let dfg = &mut func.dfg;
+                for call in dfg.insts[new_inst].branch_destination_mut(&mut dfg.jump_tables) {
+                    let target = call.block(&dfg.value_lists);
+                    let new_target = if target == header {
+                        next_header
+                    } else {
+                        copies[i].get(&target).copied().unwrap_or(target)
+                    };
+                    call.set_block(new_target, &mut dfg.value_lists);
+                }
+                func.layout.append_inst(new_inst, new_block);
+                let srcloc = func.srcloc(inst);
+                if !srcloc.is_default() {
+                    func.set_srcloc(new_inst, srcloc);
+                }
+                if inst == exit {
+                    replace_with_jump(func, new_inst, !exit_on_true);
+                }
+            }
+        }
+    }
+
+    // Enter the loop through the first copy and leave it after the original
+    // blocks run the last iteration.
+    if let Some(copy) = copies.first() {
+        let dfg = &mut func.dfg;
+        for call in dfg.insts[edges.entry].branch_destination_mut(&mut dfg.jump_tables) {
+            if call.block(&dfg.value_lists) == header {
+                call.set_block(copy[&header], &mut dfg.value_lists);
+            }
+        }
+    }
+    replace_with_jump(func, exit, exit_on_true);
+    true
+}
+
+/// Replaces the `brif` at `inst` with a jump to its destination for a true
+/// condition if `when_true` is set, or to its destination for a false one
+/// otherwise.
+fn replace_with_jump(func: &mut Function, inst: Inst, when_true: bool) {
+    let calls = func.dfg.insts[inst].branch_destination(&func.dfg.jump_tables);
+    let call = calls[if when_true { 0 } else { 1 }];
+    let block = call.block(&func.dfg.value_lists);
+    let args = call.args_slice(&func.dfg.value_lists).to_vec();
+    func.dfg.replace(inst).jump(block, &args);
+}
+
+/// Strength-reduces the multiplications and left shifts of `lp`'s induction
+/// variables by constants.
+fn strength_reduce(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+) {
+    let header = loop_analysis.loop_header(lp);
+    let edges = match header_edges(func, cfg, loop_analysis, lp) {
+        Some(edges) => edges,
+        None => return,
+    };
+
+    // Find the induction variables, the header's parameters which every back
+    // edge advances by the same constant.
+    let mut steps = FxHashMap::default();
+    for (i, &param) in func.dfg.block_params(header).iter().enumerate() {
+        let ty = func.dfg.value_type(param);
+        if !is_small_int(ty) {
+            continue;
+        }
+        let mut step = None;
+        for &inst in &edges.back_edges {
+            let arg = match branch_args(func, inst, header) {
+                Some(args) => args[i],
+                None => return,
+            };
+            match induction_step(func, param, arg) {
+                Some(s) if step.map_or(true, |step| step == s) => step = Some(s),
+                _ => {
+                    step = None;
+                    break;
+                }
+            }
+        }
+        if let Some(step) = step {
+            steps.insert(param, step);
+        }
+    }
+    if steps.is_empty() {
+        return;
+    }
+
+    // Find the instructions in the loop which scale an induction variable by a
+    // constant.
+    let mut candidates = Vec::new();
+    for block in func.layout.blocks() {
+        if !loop_analysis.is_in_loop(block, lp) {
+            continue;
+        }
+        for inst in func.layout.block_insts(block) {
+            if let Some((param, factor)) = scaled_induction_variable(func, &steps, inst) {
+                candidates.push((inst, param, factor));
+            }
+        }
+    }
+
+    // Give each distinct scaling its own induction variable, and replace the
+    // instructions with it.
+    let mut reduced: FxHashMap<(Value, u64), Value> = FxHashMap::default();
+    for &(inst, param, factor) in &candidates {
+        let ty = func.dfg.value_type(param);
+        let scaled = match reduced.get(&(param, factor)) {
+            Some(&scaled) => scaled,
+            None => {
+                let index = func.dfg.block_params(header).len();
+                let scaled = func.dfg.append_block_param(header, ty);
+                let i = func
+                    .dfg
+                    .block_params(header)
+                    .iter()
+                    .position(|&p| p == param)
+                    .unwrap();
+
+                // The initial value is scaled before entering the loop.
+                let init = branch_args(func, edges.entry, header).unwrap()[i];
+                let mut pos = FuncCursor::new(func).at_inst(edges.entry);
+                pos.use_srcloc(inst);
+                let scale = pos.ins().iconst(ty, factor as i64);
+                let scaled_init = pos.ins().imul(init, scale);
+                append_branch_arg(func, edges.entry, header, index, scaled_init);
+
+                // Every back edge advances it by the scaled step.
+                let step = mask(ty, steps[&param].wrapping_mul(factor));
+                for &back_edge in &edges.back_edges {
+                    let mut pos = FuncCursor::new(func).at_inst(back_edge);
+                    pos.use_srcloc(inst);
+                    let step = pos.ins().iconst(ty, step as i64);
+                    let next = pos.ins().iadd(scaled, step);
+                    append_branch_arg(func, back_edge, header, index, next);
+                }
+
+                reduced.insert((param, factor), scaled);
+                scaled
+            }
+        };
+        let result = func.dfg.first_result(inst);
+        func.layout.remove_inst(inst);
+        func.dfg.detach_results(inst);
+        func.dfg.change_to_alias(result, scaled);
+    }
+}
+
+/// Returns how much `arg`, the value passed to the induction variable `param`
+/// on a back edge, advances it by if it's `param` plus or minus a constant.
+fn induction_step(func: &Function, param: Value, arg: Value) -> Option<u64> {
+    let arg = func.dfg.resolve_aliases(arg);
+    let ty = func.dfg.value_type(param);
+    let inst = match func.dfg.value_def(arg) {
+        ValueDef::Result(inst, 0) => inst,
+        _ => return None,
+    };
+    let args = match func.dfg.insts[inst] {
+        InstructionData::Binary { opcode, args } => (opcode, args),
+        _ => return None,
+    };
+    let [x, y] = args.1.map(|arg| func.dfg.resolve_aliases(arg));
+    match args.0 {
+        Opcode::Iadd if x == param => const_value(func, y),
+        Opcode::Iadd if y == param => const_value(func, x),
+        Opcode::Isub if x == param => const_value(func, y).map(|y| mask(ty, y.wrapping_neg())),
+        _ => None,
+    }
+}
+
+/// Returns the induction variable that `inst` scales by a constant, and the
+/// factor it scales it by.
+fn scaled_induction_variable(
+    func: &Function,
+    steps: &FxHashMap<Value, u64>,
+    inst: Inst,
+) -> Option<(Value, u64)> {
+    let (opcode, args) = match func.dfg.insts[inst] {
+        InstructionData::Binary { opcode, args } => (opcode, args),
+        _ => return None,
+    };
+    let [x, y] = args.map(|arg| func.dfg.resolve_aliases(arg));
+    let ty = func.dfg.value_type(func.dfg.first_result(inst));
+    let (param, factor) = match opcode {
+        Opcode::Imul if steps.contains_key(&x) => (x, const_value(func, y)?),
+        Opcode::Imul if steps.contains_key(&y) => (y, const_value(func, x)?),
+        Opcode::Ishl if steps.contains_key(&x) => {
+            let shift = const_value(func, y)? & u64::from(ty.bits() - 1);
+            (x, mask(ty, 1 << shift))
+        }
+        _ => return None,
+    };
+    // Scaling by zero or one is left to the egraph pass to simplify.
+    if factor <= 1 {
+        return None;
+    }
+    Some((param, factor))
+}
+
+/// Adds `arg` as the argument for the header parameter at `index` to the
+/// branch `inst` to `header`.
+fn append_branch_arg(func: &mut Function, inst: Inst, header: Block, index: usize, arg: Value) {
+    let dfg = &mut func.dfg;
+    for call in dfg.insts[inst].branch_destination_mut(&mut dfg.jump_tables) {
+        if call.block(&dfg.value_lists) == header {
+            debug_assert_eq!(call.args_slice(&dfg.value_lists).len(), index);
+            call.append_argument(arg, &mut dfg.value_lists);
+        }
+    }
+}
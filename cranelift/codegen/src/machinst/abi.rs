@@ -109,9 +109,10 @@ use crate::ir::{ArgumentExtension, ArgumentPurpose, DynamicStackSlot, Signature,
 use crate::isa::TargetIsa;
 use crate::settings;
 use crate::settings::ProbestackStrategy;
-use crate::CodegenResult;
 use crate::{ir, isa};
 use crate::{machinst::*, trace};
+use crate::{CodegenError, CodegenResult};
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use regalloc2::{PReg, PRegSet};
 use smallvec::{smallvec, SmallVec};
@@ -431,7 +432,16 @@ pub trait ABIMachineSpec {
     fn gen_args(isa_flags: &Self::F, args: Vec<ArgPair>) -> Self::I;
 
     /// Generate a return instruction.
-    fn gen_ret(setup_frame: bool, isa_flags: &Self::F, rets: Vec<RetPair>) -> Self::I;
+    ///
+    /// `stack_bytes_to_pop` is the size of the stack argument area that the
+    /// function pops on return. It is only non-zero for functions using the
+    /// `tail` calling convention on backends that support return calls.
+    fn gen_ret(
+        setup_frame: bool,
+        isa_flags: &Self::F,
+        rets: Vec<RetPair>,
+        stack_bytes_to_pop: u32,
+    ) -> Self::I;
 
     /// Generate an add-with-immediate. Note that even if this uses a scratch
     /// register, it must satisfy two requirements:
@@ -566,6 +576,50 @@ pub trait ABIMachineSpec {
         caller_conv: isa::CallConv,
     ) -> SmallVec<[Self::I; 2]>;
 
+    /// Does this backend support return calls (i.e. `return_call` and
+    /// `return_call_indirect`)?
+    fn supports_return_calls() -> bool {
+        false
+    }
+
+    /// Generate a return call (tail call) instruction/sequence.
+    ///
+    /// The returned instruction is a `MachTerminator::RetCall` terminator.
+    /// Right before it is emitted, the clobbered callee-saved registers are
+    /// restored (see `gen_return_call_clobber_restore`), so the callee's
+    /// address must be in a register which is not callee-saved. The
+    /// instruction itself tears down the current frame: it moves the
+    /// `new_stack_arg_size` bytes of stack arguments staged at nominal SP (see
+    /// `check_return_calls`) over the `old_stack_arg_size` bytes of incoming
+    /// stack arguments of the current function, and then jumps to the callee.
+    /// This method is provided one temporary register to use to synthesize
+    /// the called address, if needed.
+    fn gen_return_call(
+        _dest: &CallDest,
+        _uses: CallArgList,
+        _opcode: ir::Opcode,
+        _tmp: Writable<Reg>,
+        _new_stack_arg_size: u32,
+        _old_stack_arg_size: u32,
+    ) -> SmallVec<[Self::I; 2]> {
+        unimplemented!("return calls are not supported by this backend")
+    }
+
+    /// Generate the clobber-restore sequence for a return call.
+    ///
+    /// This restores the same registers as `gen_clobber_restore`, but must
+    /// leave SP at nominal SP, since the return call's stack arguments are
+    /// still staged at the bottom of the frame.
+    fn gen_return_call_clobber_restore(
+        _call_conv: isa::CallConv,
+        _sig: &Signature,
+        _flags: &settings::Flags,
+        _clobbers: &[Writable<RealReg>],
+        _fixed_frame_storage_size: u32,
+    ) -> SmallVec<[Self::I; 16]> {
+        unimplemented!("return calls are not supported by this backend")
+    }
+
     /// Generate a memcpy invocation. Used to set up struct
     /// args. Takes `src`, `dst` as read-only inputs and passes a temporary
     /// allocator.
@@ -1011,6 +1065,9 @@ pub struct Callee<M: ABIMachineSpec> {
     probestack_min_frame: Option<u32>,
     /// Whether it is necessary to generate the usual frame-setup sequence.
     setup_frame: bool,
+    /// The number of bytes of stack arguments this function pops when it
+    /// returns (see `ABIMachineSpec::gen_ret`).
+    stack_bytes_to_pop: u32,
 
     _mach: PhantomData<M>,
 }
@@ -1031,6 +1088,85 @@ fn get_special_purpose_param_register(
     }
 }
 
+/// Check that all return calls in `f` can be lowered, and compute the size
+/// of the area in which they stage their stack arguments.
+///
+/// A return call can't store the callee's stack arguments in their final
+/// location right away, as that location may overlap this function's own
+/// frame and incoming arguments. Instead they are stored into a staging area
+/// at the bottom of this function's fixed frame, and moved into place by the
+/// return call instruction once the frame is torn down. The staging area
+/// holds the return address followed by the stack arguments.
+///
+/// When this function uses the `tail` calling convention, the callee's stack
+/// arguments replace this function's incoming stack arguments, as functions
+/// using that convention pop their own stack arguments. Otherwise our caller
+/// pops the argument area it allocated for us, so the callee must not need
+/// more stack argument space than this function.
+fn check_return_calls<M: ABIMachineSpec>(
+    f: &ir::Function,
+    sigs: &SigSet,
+    sig: Sig,
+) -> CodegenResult<u32> {
+    let mut staging_size = 0;
+    for block in f.layout.blocks() {
+        for inst in f.layout.block_insts(block) {
+            let sig_ref = match f.dfg.insts[inst] {
+                ir::InstructionData::Call {
+                    opcode: ir::Opcode::ReturnCall,
+                    func_ref,
+                    ..
+                } => f.dfg.ext_funcs[func_ref].signature,
+                ir::InstructionData::CallIndirect {
+                    opcode: ir::Opcode::ReturnCallIndirect,
+                    sig_ref,
+                    ..
+                } => sig_ref,
+                _ => continue,
+            };
+            if !M::supports_return_calls() {
+                return Err(CodegenError::Unsupported(
+                    "return calls are not supported by this backend".to_string(),
+                ));
+            }
+            let callee = sigs.abi_sig_for_sig_ref(sig_ref);
+            if sigs[callee].stack_ret_arg.is_some() && sigs[sig].stack_ret_arg.is_none() {
+                return Err(CodegenError::Unsupported(
+                    "return call to a function with a stack return area".to_string(),
+                ));
+            }
+            let new_stack_arg_size = sigs[callee].sized_stack_arg_space;
+            let old_stack_arg_size = return_call_old_stack_arg_size(sigs, sig, callee);
+            if sigs[sig].call_conv != isa::CallConv::Tail
+                && new_stack_arg_size > sigs[sig].sized_stack_arg_space
+            {
+                return Err(CodegenError::Unsupported(format!(
+                    "return call in {} requires {} bytes of stack arguments but the caller only has {}",
+                    f.name,
+                    new_stack_arg_size,
+                    sigs[sig].sized_stack_arg_space,
+                )));
+            }
+            if new_stack_arg_size > 0 || old_stack_arg_size > 0 {
+                staging_size = staging_size.max(new_stack_arg_size + M::word_bytes());
+            }
+        }
+    }
+    Ok(staging_size)
+}
+
+/// Get the size of the stack argument area that a return call from a function
+/// with signature `caller` to one with signature `callee` replaces.
+fn return_call_old_stack_arg_size(sigs: &SigSet, caller: Sig, callee: Sig) -> u32 {
+    if sigs[caller].call_conv == isa::CallConv::Tail {
+        sigs[caller].sized_stack_arg_space
+    } else {
+        // Our caller pops the stack arguments it passed to us, so the
+        // callee's arguments are stored in place, at the start of that area.
+        sigs[callee].sized_stack_arg_space
+    }
+}
+
 impl<M: ABIMachineSpec> Callee<M> {
     /// Create a new body ABI instance.
     pub fn new<'a>(
@@ -1050,6 +1186,7 @@ impl<M: ABIMachineSpec> Callee<M> {
             call_conv == isa::CallConv::SystemV
                 || call_conv == isa::CallConv::Fast
                 || call_conv == isa::CallConv::Cold
+                || call_conv == isa::CallConv::Tail
                 || call_conv.extends_windows_fastcall()
                 || call_conv == isa::CallConv::AppleAarch64
                 || call_conv == isa::CallConv::WasmtimeSystemV
@@ -1058,8 +1195,11 @@ impl<M: ABIMachineSpec> Callee<M> {
             call_conv
         );
 
-        // Compute sized stackslot locations and total stackslot size.
-        let mut sized_stack_offset: u32 = 0;
+        let return_call_staging_size = check_return_calls::<M>(f, sigs, sig)?;
+
+        // Compute sized stackslot locations and total stackslot size. They
+        // are placed above the staging area for return call arguments.
+        let mut sized_stack_offset: u32 = return_call_staging_size;
         let mut sized_stackslots = PrimaryMap::new();
         for (stackslot, data) in f.sized_stack_slots.iter() {
             let off = sized_stack_offset;
@@ -1119,6 +1259,15 @@ impl<M: ABIMachineSpec> Callee<M> {
             None
         };
 
+        // Functions using the tail calling convention pop their own stack
+        // arguments on backends that support return calls, so that a return
+        // call can pass more stack arguments than the function received.
+        let stack_bytes_to_pop = if call_conv == isa::CallConv::Tail && M::supports_return_calls() {
+            sigs[sig].sized_stack_arg_space
+        } else {
+            0
+        };
+
         Ok(Self {
             ir_sig: ensure_struct_return_ptr_is_returned(&f.signature),
             sig,
@@ -1141,6 +1290,7 @@ impl<M: ABIMachineSpec> Callee<M> {
             stack_limit,
             probestack_min_frame,
             setup_frame: true,
+            stack_bytes_to_pop,
             _mach: PhantomData,
         })
     }
@@ -1389,6 +1539,12 @@ impl<M: ABIMachineSpec> Callee<M> {
         self.isa_flags.is_forward_edge_cfi_enabled()
     }
 
+    /// Get the register holding the pointer to the return-value area passed in
+    /// by our caller, if this function returns values on the stack.
+    pub fn ret_area_ptr(&self) -> Option<Reg> {
+        self.ret_area_ptr.map(|r| r.to_reg())
+    }
+
     /// Get the calling convention implemented by this ABI object.
     pub fn call_conv(&self, sigs: &SigSet) -> isa::CallConv {
         sigs[self.sig].call_conv
@@ -1657,7 +1813,12 @@ impl<M: ABIMachineSpec> Callee<M> {
 
     /// Generate a return instruction.
     pub fn gen_ret(&self, rets: Vec<RetPair>) -> M::I {
-        M::gen_ret(self.setup_frame, &self.isa_flags, rets)
+        M::gen_ret(
+            self.setup_frame,
+            &self.isa_flags,
+            rets,
+            self.stack_bytes_to_pop,
+        )
     }
 
     /// Produce an instruction that computes a sized stackslot address.
@@ -1905,12 +2066,34 @@ impl<M: ABIMachineSpec> Callee<M> {
         // This `ret` doesn't need any return registers attached
         // because we are post-regalloc and don't need to
         // represent the implicit uses anymore.
-        insts.push(M::gen_ret(self.setup_frame, &self.isa_flags, vec![]));
+        insts.push(M::gen_ret(
+            self.setup_frame,
+            &self.isa_flags,
+            vec![],
+            self.stack_bytes_to_pop,
+        ));
 
         trace!("Epilogue: {:?}", insts);
         insts
     }
 
+    /// Generate the epilogue for a return call, post-regalloc.
+    ///
+    /// This only restores the clobbered callee-saved registers: the return
+    /// call instruction, which is emitted separately afterwards, tears down
+    /// the frame and jumps to the callee.
+    pub fn gen_return_call_epilogue(&self) -> SmallInstVec<M::I> {
+        let insts = M::gen_return_call_clobber_restore(
+            self.call_conv,
+            self.signature(),
+            &self.flags,
+            &self.clobbered,
+            self.fixed_frame_storage_size,
+        );
+        trace!("Return call epilogue: {:?}", insts);
+        insts.into_iter().collect()
+    }
+
     /// Returns the full frame size for the given function, after prologue
     /// emission has run. This comprises the spill slots and stack-storage slots
     /// (but not storage for clobbered callee-save registers, arguments pushed
@@ -2025,6 +2208,7 @@ impl<M: ABIMachineSpec> Caller<M> {
         sig_ref: ir::SigRef,
         extname: &ir::ExternalName,
         dist: RelocDistance,
+        opcode: ir::Opcode,
         caller_conv: isa::CallConv,
         flags: settings::Flags,
     ) -> CodegenResult<Caller<M>> {
@@ -2036,7 +2220,7 @@ impl<M: ABIMachineSpec> Caller<M> {
             defs: smallvec![],
             clobbers,
             dest: CallDest::ExtName(extname.clone(), dist),
-            opcode: ir::Opcode::Call,
+            opcode,
             caller_conv,
             flags,
             _mach: PhantomData,
@@ -2111,6 +2295,29 @@ impl<M: ABIMachineSpec> Caller<M> {
         sigs.num_args(self.sig)
     }
 
+    /// Is this callsite a return call (tail call)?
+    fn is_return_call(&self) -> bool {
+        matches!(
+            self.opcode,
+            ir::Opcode::ReturnCall | ir::Opcode::ReturnCallIndirect
+        )
+    }
+
+    /// Get the location of a stack argument at `offset` in the outgoing
+    /// argument area.
+    ///
+    /// For regular calls, this area is allocated below SP by
+    /// `emit_stack_pre_adjust`. Return calls instead stage their arguments at
+    /// the bottom of the current function's frame, right above the slot for
+    /// the return address (see `check_return_calls`).
+    fn stack_arg_amode(&self, offset: i64, ty: Type) -> StackAMode {
+        if self.is_return_call() {
+            StackAMode::NominalSPOffset(i64::from(M::word_bytes()) + offset, ty)
+        } else {
+            StackAMode::SPOffset(offset, ty)
+        }
+    }
+
     /// Emit code to pre-adjust the stack, prior to argument copies and call.
     pub fn emit_stack_pre_adjust(&self, ctx: &mut Lower<M::I>) {
         let off =
@@ -2142,7 +2349,7 @@ impl<M: ABIMachineSpec> Caller<M> {
                 let src_ptr = from_regs.only_reg().unwrap();
                 let dst_ptr = ctx.alloc_tmp(M::word_type()).only_reg().unwrap();
                 ctx.emit(M::gen_get_stack_addr(
-                    StackAMode::SPOffset(offset, I8),
+                    self.stack_arg_amode(offset, I8),
                     dst_ptr,
                     I8,
                 ));
@@ -2288,7 +2495,7 @@ impl<M: ABIMachineSpec> Caller<M> {
                                     (*from_reg, ty)
                                 };
                             insts.push(M::gen_store_stack(
-                                StackAMode::SPOffset(offset, ty),
+                                self.stack_arg_amode(offset, ty),
                                 data,
                                 ty,
                             ));
@@ -2395,6 +2602,45 @@ impl<M: ABIMachineSpec> Caller<M> {
             ctx.emit(inst);
         }
     }
+
+    /// Emit the return call itself.
+    ///
+    /// This is the same as `emit_call` except that the callee returns
+    /// directly to our caller, so there are no return values or clobbers to
+    /// represent. If the callee returns values on the stack, it's passed the
+    /// return-value area of our own caller.
+    pub fn emit_return_call(&mut self, ctx: &mut Lower<M::I>) {
+        debug_assert!(self.is_return_call());
+        let word_type = M::word_type();
+        if let Some(i) = ctx.sigs()[self.sig].stack_ret_arg {
+            let ret_area_ptr = ctx
+                .abi()
+                .ret_area_ptr()
+                .expect("caller and callee of a return call have the same return values");
+            for inst in self.gen_arg(ctx, i.into(), ValueRegs::one(ret_area_ptr)) {
+                ctx.emit(inst);
+            }
+        }
+
+        let new_stack_arg_size = ctx.sigs()[self.sig].sized_stack_arg_space;
+        let old_stack_arg_size =
+            return_call_old_stack_arg_size(ctx.sigs(), ctx.abi().sig, self.sig);
+
+        let uses = mem::replace(&mut self.uses, Default::default());
+        let tmp = ctx.alloc_tmp(word_type).only_reg().unwrap();
+        for inst in M::gen_return_call(
+            &self.dest,
+            uses,
+            self.opcode,
+            tmp,
+            new_stack_arg_size,
+            old_stack_arg_size,
+        )
+        .into_iter()
+        {
+            ctx.emit(inst);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -59,15 +59,27 @@
 //! Furthermore, the [MachBuffer] machine-code sink performs final peephole-like
 //! branch editing that in practice elides empty blocks and simplifies some of
 //! the other redundancies that this scheme produces.
+//!
+//! When the function has a block profile (see [crate::ir::BlockProfile]) in
+//! which its entry block was executed, the DFS visits the successors of each
+//! block from the least to the most frequently taken edge, so that the hottest
+//! successor comes right after its predecessor in the reverse postorder. The
+//! [MachBuffer] then inverts conditional branches as needed so that this edge
+//! is the fallthrough. Blocks and edges which the profile shows were never
+//! executed are additionally treated as cold, and sunk to the end of the
+//! function.
 
 use crate::dominator_tree::DominatorTree;
-use crate::entity::SecondaryMap;
+use crate::entity::{EntitySet, SecondaryMap};
 use crate::fx::{FxHashMap, FxHashSet};
 use crate::inst_predicates::visit_block_succs;
 use crate::ir::{Block, Function, Inst, Opcode};
 use crate::{machinst::*, trace};
 
 use smallvec::SmallVec;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::ops::Range;
 
 /// Mapping from CLIF BBs to VCode BBs.
 #[derive(Debug)]
@@ -87,6 +99,9 @@ pub struct BlockLoweringOrder {
     /// correct. Instead, this set is used to provide `is_cold()`,
     /// which is used by VCode emission to sink the blocks at the last
     /// moment (when we actually emit bytes into the MachBuffer).
+    /// Besides the blocks marked cold in the CLIF layout, this
+    /// includes those which the block profile shows were never
+    /// executed.
     cold_blocks: FxHashSet<BlockIndex>,
     /// Lowered blocks that are indirect branch targets.
     indirect_branch_targets: FxHashSet<BlockIndex>,
@@ -185,13 +200,26 @@ impl BlockLoweringOrder {
             block_succ_range[block] = start..end;
         }
 
-        // Step 2: walk the postorder from the domtree in reverse to produce our desired node
-        // lowering order, identifying critical edges to split along the way.
+        // Step 2: walk the postorder in reverse to produce our desired node lowering order,
+        // identifying critical edges to split along the way. Without a profile, this is the
+        // postorder from the domtree; with one, it is computed to place hot successors right
+        // after their predecessor.
+
+        let entry = f.layout.entry_block();
+        let profile = &f.block_profile;
+        let profiled = entry
+            .and_then(|entry| profile.block_count(entry))
+            .map_or(false, |count| count > 0);
+        let postorder = if profiled {
+            Cow::Owned(profiled_postorder(f, &block_succs, &block_succ_range))
+        } else {
+            Cow::Borrowed(domtree.cfg_postorder())
+        };
 
         let mut lb_to_bindex = FxHashMap::default();
         let mut lowered_order = Vec::new();
 
-        for &block in domtree.cfg_postorder().iter().rev() {
+        for &block in postorder.iter().rev() {
             let lb = LoweredBlock::Orig { block };
             let bindex = BlockIndex::new(lowered_order.len());
             lb_to_bindex.insert(lb.clone(), bindex);
@@ -199,6 +227,7 @@ impl BlockLoweringOrder {
 
             if block_out_count[block] > 1 {
                 let range = block_succ_range[block].clone();
+                let edges_start = lowered_order.len();
                 for (succ_ix, lb) in block_succs[range].iter_mut().enumerate() {
                     let succ = lb.orig_block().unwrap();
                     if block_in_count[succ] > 1 {
@@ -209,14 +238,39 @@ impl BlockLoweringOrder {
                             succ,
                             succ_idx: succ_ix as u32,
                         };
-                        let bindex = BlockIndex::new(lowered_order.len());
-                        lb_to_bindex.insert(*lb, bindex);
                         lowered_order.push(*lb);
                     }
                 }
+
+                // Place the hottest edge last, so that it can fall through into its successor.
+                if profiled {
+                    lowered_order[edges_start..].sort_by_key(|lb| match *lb {
+                        LoweredBlock::CriticalEdge { pred, succ_idx, .. } => {
+                            profile.edge_count(pred, succ_idx).unwrap_or(0)
+                        }
+                        LoweredBlock::Orig { .. } => unreachable!(),
+                    });
+                }
+                for (ix, lb) in lowered_order.iter().enumerate().skip(edges_start) {
+                    lb_to_bindex.insert(*lb, BlockIndex::new(ix));
+                }
             }
         }
 
+        // Blocks and edges that the profile shows were never executed are cold, except for the
+        // entry block which is always emitted first.
+        let never_executed = |lb: &LoweredBlock| {
+            profiled
+                && match *lb {
+                    LoweredBlock::Orig { block } => {
+                        Some(block) != entry && profile.block_count(block) == Some(0)
+                    }
+                    LoweredBlock::CriticalEdge { pred, succ_idx, .. } => {
+                        profile.edge_count(pred, succ_idx) == Some(0)
+                    }
+                }
+        };
+
         // Step 3: build the successor tables given the lowering order. We can't perform this step
         // during the creation of `lowering_order`, as we need `lb_to_bindex` to be fully populated
         // first.
@@ -235,7 +289,7 @@ impl BlockLoweringOrder {
                         lowered_succ_indices
                             .extend(block_succs[range].iter().map(|lb| lb_to_bindex[lb]));
 
-                        if f.layout.is_cold(block) {
+                        if f.layout.is_cold(block) || never_executed(lb) {
                             cold_blocks.insert(bindex);
                         }
 
@@ -260,7 +314,7 @@ impl BlockLoweringOrder {
                         // Edges inherit indirect branch and cold block metadata from their
                         // successor.
 
-                        if f.layout.is_cold(succ) {
+                        if f.layout.is_cold(succ) || never_executed(lb) {
                             cold_blocks.insert(bindex);
                         }
 
@@ -310,6 +364,78 @@ impl BlockLoweringOrder {
     }
 }
 
+/// Compute a postorder of the blocks of `f` reachable from its entry block, visiting the
+/// successors of each block from the least to the most frequently taken edge according to its
+/// block profile, so that the hottest successor of each block comes right after it in the reverse
+/// postorder (unless it was already visited).
+///
+/// Like any reverse postorder, the result places each block after its dominators.
+fn profiled_postorder(
+    f: &Function,
+    block_succs: &[LoweredBlock],
+    block_succ_range: &SecondaryMap<Block, Range<usize>>,
+) -> Vec<Block> {
+    let profile = &f.block_profile;
+
+    // Successors of `block` in the order in which they should be popped from the DFS stack,
+    // i.e. the coldest last. Multiple edges to the same successor add up.
+    let succs_by_weight = |block: Block| {
+        let range = block_succ_range[block].clone();
+        let single = range.len() == 1;
+        let mut succs: SmallVec<[(Block, u64); 4]> = SmallVec::new();
+        let mut positions = FxHashMap::default();
+        for (succ_idx, lb) in block_succs[range].iter().enumerate() {
+            let succ = lb.orig_block().unwrap();
+            let count = profile
+                .edge_count(block, succ_idx as u32)
+                .or_else(|| {
+                    // Edges out of blocks with a single successor aren't counted separately.
+                    if single {
+                        profile.block_count(block)
+                    } else {
+                        profile.block_count(succ)
+                    }
+                })
+                .unwrap_or(0);
+            match positions.get(&succ) {
+                Some(&pos) => {
+                    let (_, total): &mut (Block, u64) = &mut succs[pos];
+                    *total = total.saturating_add(count);
+                }
+                None => {
+                    positions.insert(succ, succs.len());
+                    succs.push((succ, count));
+                }
+            }
+        }
+        succs.sort_by_key(|&(_, count)| Reverse(count));
+        succs
+    };
+
+    let mut postorder = Vec::new();
+    let entry = match f.layout.entry_block() {
+        Some(entry) => entry,
+        None => return postorder,
+    };
+    let mut visited = EntitySet::new();
+    visited.insert(entry);
+    let mut stack = vec![(entry, succs_by_weight(entry))];
+    while let Some((block, succs)) = stack.last_mut() {
+        match succs.pop() {
+            Some((succ, _)) => {
+                if visited.insert(succ) {
+                    stack.push((succ, succs_by_weight(succ)));
+                }
+            }
+            None => {
+                postorder.push(*block);
+                stack.pop();
+            }
+        }
+    }
+    postorder
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -321,6 +447,17 @@ mod test {
     use crate::isa::CallConv;
 
     fn build_test_func(n_blocks: usize, edges: &[(usize, usize)]) -> BlockLoweringOrder {
+        build_profiled_test_func(n_blocks, edges, &[], &[])
+    }
+
+    /// Like `build_test_func`, with a block profile giving the counts of the blocks in
+    /// `block_counts`, and of the edges in `edge_counts` as `(block, succ_idx, count)`.
+    fn build_profiled_test_func(
+        n_blocks: usize,
+        edges: &[(usize, usize)],
+        block_counts: &[u64],
+        edge_counts: &[(usize, u32, u64)],
+    ) -> BlockLoweringOrder {
         assert!(n_blocks > 0);
 
         let name = UserFuncName::testcase("test0");
@@ -359,6 +496,14 @@ mod test {
             }
         }
 
+        for (i, &count) in block_counts.iter().enumerate() {
+            func.block_profile.set_block_count(blocks[i], count);
+        }
+        for &(i, succ_idx, count) in edge_counts {
+            func.block_profile
+                .set_edge_count(blocks[i], succ_idx, count);
+        }
+
         let mut cfg = ControlFlowGraph::new();
         cfg.compute(&func);
         let dom_tree = DominatorTree::with_function(&func, &cfg);
@@ -451,4 +596,61 @@ mod test {
         assert!(order.lowered_order[8].in_edge().is_none());
         assert!(order.lowered_order[8].out_edge().is_none());
     }
+
+    fn orig_blocks(order: &BlockLoweringOrder) -> Vec<Option<u32>> {
+        order
+            .lowered_order
+            .iter()
+            .map(|lb| lb.orig_block().map(|b| b.as_u32()))
+            .collect()
+    }
+
+    #[test]
+    fn test_blockorder_profile_hot_successor_follows() {
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3)];
+        let unprofiled = build_test_func(4, &edges);
+        assert_eq!(
+            orig_blocks(&unprofiled),
+            [Some(0), Some(2), Some(1), Some(3)]
+        );
+
+        let order = build_profiled_test_func(4, &edges, &[10, 9, 1, 10], &[(0, 0, 9), (0, 1, 1)]);
+        assert_eq!(orig_blocks(&order), [Some(0), Some(1), Some(2), Some(3)]);
+        assert!(!order.is_cold(BlockIndex::new(1)));
+        assert!(!order.is_cold(BlockIndex::new(2)));
+    }
+
+    #[test]
+    fn test_blockorder_profile_cold_blocks_and_edges() {
+        //        0
+        //       / \
+        //      1   2
+        //     / \ /
+        //    4   3
+        //
+        // (1 -> 3 is a critical edge, which is never taken)
+        let edges = [(0, 1), (0, 2), (1, 3), (1, 4), (2, 3)];
+        let order = build_profiled_test_func(
+            5,
+            &edges,
+            &[7, 7, 0, 0, 7],
+            &[(0, 0, 7), (0, 1, 0), (1, 0, 0), (1, 1, 7)],
+        );
+        assert_eq!(
+            orig_blocks(&order),
+            [Some(0), Some(1), None, Some(4), Some(2), Some(3)]
+        );
+        let cold: Vec<_> = (0..order.lowered_order.len())
+            .filter(|&ix| order.is_cold(BlockIndex::new(ix)))
+            .collect();
+        assert_eq!(cold, [2, 4, 5]);
+
+        // A profile in which the function never ran is ignored.
+        let order = build_profiled_test_func(5, &edges, &[0, 0, 0, 0, 0], &[]);
+        assert_eq!(
+            orig_blocks(&order),
+            orig_blocks(&build_test_func(5, &edges))
+        );
+        assert!((0..6).all(|ix| !order.is_cold(BlockIndex::new(ix))));
+    }
 }
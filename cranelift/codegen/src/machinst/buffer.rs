@@ -704,6 +704,9 @@ impl<I: VCodeInst> MachBuffer<I> {
             }
             self.srclocs.pop();
         }
+        if let Some((start, _)) = self.cur_srcloc.as_mut() {
+            *start = std::cmp::min(*start, b.start);
+        }
         // State:
         //    [PRE CODE]
         //  cur_off, Offset b.start, b.labels_at_this_branch:
@@ -703,6 +703,7 @@ macro_rules! isle_prelude_caller_methods {
                 sig_ref,
                 &extname,
                 dist,
+                Opcode::Call,
                 caller_conv,
                 self.backend.flags().clone(),
             )
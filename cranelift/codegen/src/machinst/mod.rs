@@ -46,7 +46,7 @@
 
 use crate::binemit::{Addend, CodeInfo, CodeOffset, Reloc, StackMap};
 use crate::ir::function::FunctionParameters;
-use crate::ir::{DynamicStackSlot, RelSourceLoc, StackSlot, Type};
+use crate::ir::{DynamicStackSlot, RelSourceLoc, SourceLoc, StackSlot, Type};
 use crate::result::CodegenResult;
 use crate::settings::Flags;
 use crate::value_label::ValueLabelsRanges;
@@ -242,6 +242,8 @@ pub enum MachTerminator {
     None,
     /// A return instruction.
     Ret,
+    /// A return call (tail call) instruction.
+    RetCall,
     /// An unconditional branch to another block.
     Uncond,
     /// A conditional branch to one of two other blocks.
@@ -292,6 +294,15 @@ pub struct CompiledCodeBase<T: CompilePhase> {
     pub frame_size: u32,
     /// Disassembly, if requested.
     pub vcode: Option<String>,
+    /// Source locations of the lines of `vcode`: each entry is a byte offset
+    /// into `vcode` along with the source location of the lines from there
+    /// up to the next entry.
+    pub vcode_srclocs: Vec<(u32, T::SourceLocType)>,
+    /// Disassembly of the VCode before register allocation, if requested.
+    pub lowered_vcode: Option<String>,
+    /// Source locations of the lines of `lowered_vcode`, in the same format
+    /// as `vcode_srclocs`.
+    pub lowered_vcode_srclocs: Vec<(u32, T::SourceLocType)>,
     /// Debug info: value labels to registers/stackslots at code offsets.
     pub value_labels_ranges: ValueLabelsRanges,
     /// Debug info: stackslots to stack pointer offsets.
@@ -322,6 +333,9 @@ impl CompiledCodeStencil {
             buffer: self.buffer.apply_base_srcloc(params.base_srcloc()),
             frame_size: self.frame_size,
             vcode: self.vcode,
+            vcode_srclocs: expand_srclocs(self.vcode_srclocs, params),
+            lowered_vcode: self.lowered_vcode,
+            lowered_vcode_srclocs: expand_srclocs(self.lowered_vcode_srclocs, params),
             value_labels_ranges: self.value_labels_ranges,
             sized_stackslot_offsets: self.sized_stackslot_offsets,
             dynamic_stackslot_offsets: self.dynamic_stackslot_offsets,
@@ -332,6 +346,16 @@ impl CompiledCodeStencil {
     }
 }
 
+fn expand_srclocs(
+    srclocs: Vec<(u32, RelSourceLoc)>,
+    params: &FunctionParameters,
+) -> Vec<(u32, SourceLoc)> {
+    srclocs
+        .into_iter()
+        .map(|(offset, srcloc)| (offset, srcloc.expand(params.base_srcloc())))
+        .collect()
+}
+
 impl<T: CompilePhase> CompiledCodeBase<T> {
     /// Get a `CodeInfo` describing section sizes from this compilation result.
     pub fn code_info(&self) -> CodeInfo {
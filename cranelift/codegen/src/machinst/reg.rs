@@ -500,7 +500,9 @@ impl<'a> AllocationConsumer<'a> {
     }
 
     pub fn next(&mut self, pre_regalloc_reg: Reg) -> Reg {
-        let alloc = self.allocs.next();
+        // An explicit `Allocation::none()` stands for an operand that has not
+        // been allocated yet, when printing instructions before regalloc.
+        let alloc = self.allocs.next().filter(|alloc| !alloc.is_none());
         let alloc = alloc.map(|alloc| {
             Reg::from(
                 alloc
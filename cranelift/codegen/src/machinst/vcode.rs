@@ -204,6 +204,19 @@ pub struct EmitResult<I: VCodeInst> {
     /// epilogue(s), and makes use of the regalloc results.
     pub disasm: Option<String>,
 
+    /// The source locations of the lines of `disasm`, as a sorted list of
+    /// byte offsets into `disasm` along with the source location of all lines
+    /// from that offset up to the next entry.
+    pub disasm_srclocs: Vec<(u32, RelSourceLoc)>,
+
+    /// The pretty-printed VCode before register allocation, if a disassembly
+    /// was requested, with blocks in the same order as `disasm`.
+    pub lowered_disasm: Option<String>,
+
+    /// The source locations of the lines of `lowered_disasm`, in the same
+    /// format as `disasm_srclocs`.
+    pub lowered_disasm_srclocs: Vec<(u32, RelSourceLoc)>,
+
     /// Offsets of sized stackslots.
     pub sized_stackslot_offsets: PrimaryMap<StackSlot, u32>,
 
@@ -792,6 +805,13 @@ impl<I: VCodeInst> VCode<I> {
         }
         final_order.extend(cold_blocks.clone());
 
+        let (lowered_disasm, lowered_disasm_srclocs) = if want_disasm {
+            let (disasm, srclocs) = self.show_lowered(&final_order);
+            (Some(disasm), srclocs)
+        } else {
+            (None, Vec::new())
+        };
+
         // Compute/save info we need for the prologue: clobbers and
         // number of spillslots.
         //
@@ -816,6 +836,7 @@ impl<I: VCodeInst> VCode<I> {
         let mut state = I::State::new(&self.abi);
 
         let mut disasm = String::new();
+        let mut disasm_srclocs = Vec::new();
 
         if !self.debug_value_labels.is_empty() {
             inst_offsets.resize(self.insts.len(), 0);
@@ -942,6 +963,9 @@ impl<I: VCodeInst> VCode<I> {
                             }
                             buffer.start_srcloc(srcloc);
                             cur_srcloc = Some(srcloc);
+                            if want_disasm {
+                                disasm_srclocs.push((disasm.len() as u32, srcloc));
+                            }
                         }
                         state.pre_sourceloc(cur_srcloc.unwrap_or_default());
 
@@ -990,6 +1014,20 @@ impl<I: VCodeInst> VCode<I> {
                             for inst in self.abi.gen_epilogue() {
                                 do_emit(&inst, &[], &mut disasm, &mut buffer, &mut state);
                             }
+                        } else if self.insts[iix.index()].is_term() == MachTerminator::RetCall {
+                            // Return calls tear down this function's frame
+                            // and then jump to the callee, which returns
+                            // directly to our caller.
+                            for inst in self.abi.gen_return_call_epilogue() {
+                                do_emit(&inst, &[], &mut disasm, &mut buffer, &mut state);
+                            }
+                            do_emit(
+                                &self.insts[iix.index()],
+                                allocs,
+                                &mut disasm,
+                                &mut buffer,
+                                &mut state,
+                            );
                         } else {
                             // Emit the instruction!
                             do_emit(
@@ -1040,6 +1078,9 @@ impl<I: VCodeInst> VCode<I> {
             if cur_srcloc.is_some() {
                 buffer.end_srcloc();
                 cur_srcloc = None;
+                if want_disasm {
+                    disasm_srclocs.push((disasm.len() as u32, RelSourceLoc::default()));
+                }
             }
 
             // Do we need an island? Get the worst-case size of the
@@ -1102,6 +1143,9 @@ impl<I: VCodeInst> VCode<I> {
             inst_offsets,
             func_body_len,
             disasm: if want_disasm { Some(disasm) } else { None },
+            disasm_srclocs,
+            lowered_disasm,
+            lowered_disasm_srclocs,
             sized_stackslot_offsets: self.abi.sized_stackslot_offsets().clone(),
             dynamic_stackslot_offsets: self.abi.dynamic_stackslot_offsets().clone(),
             value_labels_ranges,
@@ -1110,6 +1154,64 @@ impl<I: VCodeInst> VCode<I> {
         }
     }
 
+    /// Pretty-prints the VCode as produced by lowering, with virtual
+    /// registers, in the same format as the disassembly produced by `emit`.
+    /// Returns the text along with the source locations of its lines.
+    fn show_lowered(&self, order: &[BlockIndex]) -> (String, Vec<(u32, RelSourceLoc)>) {
+        use core::fmt::Write;
+
+        let mut text = String::new();
+        let mut srclocs = Vec::new();
+        let mut state = Default::default();
+
+        let mut alias_keys = self.vreg_aliases.keys().cloned().collect::<Vec<_>>();
+        alias_keys.sort_unstable();
+        for key in alias_keys {
+            let dest = self.vreg_aliases.get(&key).unwrap();
+            writeln!(text, "  {:?} := {:?}", Reg::from(key), Reg::from(*dest)).unwrap();
+        }
+
+        for block in order {
+            writeln!(text, "block{}:", block.index()).unwrap();
+            let mut cur_srcloc = RelSourceLoc::default();
+            let (start, end) = self.block_ranges[block.index()];
+            for inst in start.index()..end.index() {
+                let srcloc = self.srclocs[inst];
+                if srcloc != cur_srcloc {
+                    srclocs.push((text.len() as u32, srcloc));
+                    cur_srcloc = srcloc;
+                }
+                writeln!(
+                    text,
+                    "  {}",
+                    self.pretty_print_inst_pre_regalloc(InsnIndex::new(inst), &mut state)
+                )
+                .unwrap();
+            }
+            if !cur_srcloc.is_default() {
+                srclocs.push((text.len() as u32, RelSourceLoc::default()));
+            }
+        }
+        (text, srclocs)
+    }
+
+    /// Pretty-print an instruction as it was lowered, before register
+    /// allocation.
+    ///
+    /// Operands print as their virtual registers, except for fixed
+    /// non-allocatable operands, which print as their physical register.
+    fn pretty_print_inst_pre_regalloc(&self, inst: InsnIndex, state: &mut I::State) -> String {
+        let (start, end) = self.operand_ranges[inst.index()];
+        let allocs: SmallVec<[Allocation; 8]> = self.operands[start as usize..end as usize]
+            .iter()
+            .map(|op| match op.as_fixed_nonallocatable() {
+                Some(preg) => Allocation::reg(preg),
+                None => Allocation::none(),
+            })
+            .collect();
+        self.insts[inst.index()].pretty_print_inst(&allocs, state)
+    }
+
     fn compute_value_labels_ranges(
         &self,
         regalloc: &regalloc2::Output,
@@ -1261,7 +1363,7 @@ impl<I: VCodeInst> RegallocFunction for VCode<I> {
         match self.insts[insn.index()].is_term() {
             // We treat blocks terminated by an unconditional trap like a return for regalloc.
             MachTerminator::None => self.insts[insn.index()].is_trap(),
-            MachTerminator::Ret => true,
+            MachTerminator::Ret | MachTerminator::RetCall => true,
             _ => false,
         }
     }
@@ -1363,7 +1465,7 @@ impl<I: VCodeInst> fmt::Debug for VCode<I> {
                     f,
                     "  Inst {}: {}",
                     inst,
-                    self.insts[inst].pretty_print_inst(&[], &mut state)
+                    self.pretty_print_inst_pre_regalloc(InsnIndex::new(inst), &mut state)
                 )?;
             }
         }
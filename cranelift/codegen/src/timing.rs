@@ -66,8 +66,11 @@ define_passes! {
     egraph: "Egraph based optimizations",
     gvn: "Global value numbering",
     licm: "Loop invariant code motion",
+    loop_opts: "Loop unrolling and strength reduction",
     unreachable_code: "Remove unreachable blocks",
     remove_constant_phis: "Remove constant phi-nodes",
+    block_profiling: "Block profiling instrumentation",
+    inline: "Function inlining",
 
     vcode_lower: "VCode lowering",
     vcode_emit: "VCode emission",
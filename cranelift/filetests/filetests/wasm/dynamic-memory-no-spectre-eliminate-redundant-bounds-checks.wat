@@ -0,0 +1,313 @@
+;;! target = "x86_64"
+;;!
+;;! settings = ["enable_heap_access_spectre_mitigation=false"]
+;;!
+;;! eliminate_redundant_bounds_checks = true
+;;!
+;;! [globals.vmctx]
+;;! type = "i64"
+;;! vmctx = true
+;;!
+;;! [globals.heap_base]
+;;! type = "i64"
+;;! load = { base = "vmctx", offset = 0 }
+;;!
+;;! [globals.heap_bound]
+;;! type = "i64"
+;;! load = { base = "vmctx", offset = 8 }
+;;!
+;;! [[heaps]]
+;;! base = "heap_base"
+;;! min_size = 0x10000
+;;! offset_guard_size = 0
+;;! index_type = "i64"
+;;! style = { kind = "dynamic", bound = "heap_bound" }
+
+(module
+  (memory (export "memory") i64 1)
+
+  ;; The first load checks `index + 12 <= bound`, which covers the other
+  ;; two loads.
+  (func (export "fields") (param i64) (result i32)
+    local.get 0
+    i32.load offset=8
+    local.get 0
+    i32.load offset=0
+    i32.add
+    local.get 0
+    i32.load offset=4
+    i32.add
+  )
+
+  ;; The check in the `if` doesn't dominate the store after it, and the
+  ;; store's check doesn't cover the wider load.
+  (func (export "branches") (param i64 i32) (result i32)
+    local.get 1
+    if
+      local.get 0
+      i32.load
+      drop
+    end
+    local.get 0
+    i32.const 0
+    i32.store8
+    local.get 0
+    i32.load
+  )
+
+  ;; The loop's exit condition keeps the index below `1024 * 4`, which is
+  ;; within the minimum size of the memory.
+  (func (export "loop") (result i32)
+    (local i64 i32)
+    loop
+      local.get 0
+      i64.const 2
+      i64.shl
+      i32.load
+      local.get 1
+      i32.add
+      local.set 1
+      local.get 0
+      i64.const 1
+      i64.add
+      local.tee 0
+      i64.const 1024
+      i64.lt_u
+      br_if 0
+    end
+    local.get 1
+  )
+
+  ;; Within the loop body, the index is known to be below `0xfffc` from the
+  ;; branch out of the loop.
+  (func (export "loop_while") (result i32)
+    (local i64 i32)
+    block
+      loop
+        local.get 0
+        i64.const 0xfffc
+        i64.ge_u
+        br_if 1
+        local.get 0
+        i32.load
+        local.get 1
+        i32.add
+        local.set 1
+        local.get 0
+        i64.const 4
+        i64.add
+        local.set 0
+        br 0
+      end
+    end
+    local.get 1
+  )
+
+  ;; But here the loop goes beyond the minimum size of the memory, so the
+  ;; check remains.
+  (func (export "loop_too_far") (result i32)
+    (local i64 i32)
+    block
+      loop
+        local.get 0
+        i64.const 0x10000
+        i64.ge_u
+        br_if 1
+        local.get 0
+        i32.load
+        local.get 1
+        i32.add
+        local.set 1
+        local.get 0
+        i64.const 4
+        i64.add
+        local.set 0
+        br 0
+      end
+    end
+    local.get 1
+  )
+)
+
+;; function u0:0(i64, i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64, v1: i64):
+;; @0072                               v3 = global_value.i64 gv1
+;; @0072                               v4 = iadd_imm v3, -12
+;; @0072                               v5 = icmp ugt v0, v4
+;; @0072                               trapnz v5, heap_oob
+;; @0072                               v6 = global_value.i64 gv2
+;; @0072                               v7 = iadd v6, v0
+;; @0072                               v8 = iadd_imm v7, 8
+;; @0072                               v9 = load.i32 little heap v8
+;; @0077                               v10 = global_value.i64 gv1
+;; @0077                               v11 = iadd_imm v10, -4
+;; @0077                               v12 = icmp ugt v0, v11
+;; @0077                               v13 = global_value.i64 gv2
+;; @0077                               v14 = iadd v13, v0
+;; @0077                               v15 = load.i32 little heap v14
+;; @007a                               v16 = iadd v9, v15
+;; @007d                               v17 = global_value.i64 gv1
+;; @007d                               v18 = iadd_imm v17, -8
+;; @007d                               v19 = icmp ugt v0, v18
+;; @007d                               v20 = global_value.i64 gv2
+;; @007d                               v21 = iadd v20, v0
+;; @007d                               v22 = iadd_imm v21, 4
+;; @007d                               v23 = load.i32 little heap v22
+;; @0080                               v24 = iadd v16, v23
+;; @0081                               jump block1(v24)
+;;
+;;                                 block1(v2: i32):
+;; @0081                               return v2
+;; }
+;;
+;; function u0:1(i64, i32, i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64, v1: i32, v2: i64):
+;;                                     v10 -> v0
+;; @0086                               brif v1, block2, block3
+;;
+;;                                 block2:
+;; @008a                               v4 = global_value.i64 gv1
+;; @008a                               v5 = iadd_imm v4, -4
+;; @008a                               v6 = icmp.i64 ugt v0, v5
+;; @008a                               trapnz v6, heap_oob
+;; @008a                               v7 = global_value.i64 gv2
+;; @008a                               v8 = iadd v7, v0
+;; @008a                               v9 = load.i32 little heap v8
+;; @008e                               jump block3
+;;
+;;                                 block3:
+;; @0091                               v11 = iconst.i32 0
+;; @0093                               v12 = global_value.i64 gv1
+;; @0093                               v13 = icmp.i64 uge v0, v12
+;; @0093                               trapnz v13, heap_oob
+;; @0093                               v14 = global_value.i64 gv2
+;; @0093                               v15 = iadd v14, v0
+;; @0093                               istore8 little heap v11, v15  ; v11 = 0
+;; @0098                               v16 = global_value.i64 gv1
+;; @0098                               v17 = iadd_imm v16, -4
+;; @0098                               v18 = icmp.i64 ugt v10, v17
+;; @0098                               trapnz v18, heap_oob
+;; @0098                               v19 = global_value.i64 gv2
+;; @0098                               v20 = iadd v19, v10
+;; @0098                               v21 = load.i32 little heap v20
+;; @009b                               jump block1(v21)
+;;
+;;                                 block1(v3: i32):
+;; @009b                               return v3
+;; }
+;;
+;; function u0:2(i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64):
+;; @009e                               v2 = iconst.i64 0
+;; @00a0                               v3 = iconst.i32 0
+;; @00a2                               jump block2(v2, v3)  ; v2 = 0, v3 = 0
+;;
+;;                                 block2(v4: i64, v13: i32):
+;; @00a6                               v5 = iconst.i64 2
+;; @00a8                               v6 = ishl v4, v5  ; v5 = 2
+;; @00a9                               v7 = global_value.i64 gv1
+;; @00a9                               v8 = iadd_imm v7, -4
+;; @00a9                               v9 = icmp ugt v6, v8
+;; @00a9                               v10 = global_value.i64 gv2
+;; @00a9                               v11 = iadd v10, v6
+;; @00a9                               v12 = load.i32 little heap v11
+;; @00ae                               v14 = iadd v12, v13
+;; @00b3                               v15 = iconst.i64 1
+;; @00b5                               v16 = iadd v4, v15  ; v15 = 1
+;; @00b8                               v17 = iconst.i64 1024
+;; @00bb                               v18 = icmp ult v16, v17  ; v17 = 1024
+;; @00bb                               v19 = uextend.i32 v18
+;; @00bc                               brif v19, block2(v16, v14), block4
+;;
+;;                                 block4:
+;; @00be                               jump block3
+;;
+;;                                 block3:
+;; @00c1                               jump block1(v14)
+;;
+;;                                 block1(v1: i32):
+;; @00c1                               return v1
+;; }
+;;
+;; function u0:3(i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64):
+;; @00c4                               v2 = iconst.i64 0
+;; @00c6                               v3 = iconst.i32 0
+;; @00ca                               jump block3(v2, v3)  ; v2 = 0, v3 = 0
+;;
+;;                                 block3(v4: i64, v14: i32):
+;; @00ce                               v5 = iconst.i64 0xfffc
+;; @00d2                               v6 = icmp uge v4, v5  ; v5 = 0xfffc
+;; @00d2                               v7 = uextend.i32 v6
+;; @00d3                               brif v7, block2, block5
+;;
+;;                                 block5:
+;; @00d7                               v8 = global_value.i64 gv1
+;; @00d7                               v9 = iadd_imm v8, -4
+;; @00d7                               v10 = icmp.i64 ugt v4, v9
+;; @00d7                               v11 = global_value.i64 gv2
+;; @00d7                               v12 = iadd v11, v4
+;; @00d7                               v13 = load.i32 little heap v12
+;; @00dc                               v15 = iadd v13, v14
+;; @00e1                               v16 = iconst.i64 4
+;; @00e3                               v17 = iadd.i64 v4, v16  ; v16 = 4
+;; @00e6                               jump block3(v17, v15)
+;;
+;;                                 block2:
+;; @00ec                               jump block1(v14)
+;;
+;;                                 block1(v1: i32):
+;; @00ec                               return v1
+;; }
+;;
+;; function u0:4(i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64):
+;; @00ef                               v2 = iconst.i64 0
+;; @00f1                               v3 = iconst.i32 0
+;; @00f5                               jump block3(v2, v3)  ; v2 = 0, v3 = 0
+;;
+;;                                 block3(v4: i64, v14: i32):
+;; @00f9                               v5 = iconst.i64 0x0001_0000
+;; @00fd                               v6 = icmp uge v4, v5  ; v5 = 0x0001_0000
+;; @00fd                               v7 = uextend.i32 v6
+;; @00fe                               brif v7, block2, block5
+;;
+;;                                 block5:
+;; @0102                               v8 = global_value.i64 gv1
+;; @0102                               v9 = iadd_imm v8, -4
+;; @0102                               v10 = icmp.i64 ugt v4, v9
+;; @0102                               trapnz v10, heap_oob
+;; @0102                               v11 = global_value.i64 gv2
+;; @0102                               v12 = iadd v11, v4
+;; @0102                               v13 = load.i32 little heap v12
+;; @0107                               v15 = iadd v13, v14
+;; @010c                               v16 = iconst.i64 4
+;; @010e                               v17 = iadd.i64 v4, v16  ; v16 = 4
+;; @0111                               jump block3(v17, v15)
+;;
+;;                                 block2:
+;; @0117                               jump block1(v14)
+;;
+;;                                 block1(v1: i32):
+;; @0117                               return v1
+;; }
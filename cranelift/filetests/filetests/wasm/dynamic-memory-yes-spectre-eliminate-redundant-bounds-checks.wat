@@ -0,0 +1,140 @@
+;;! target = "x86_64"
+;;!
+;;! settings = ["enable_heap_access_spectre_mitigation=true"]
+;;!
+;;! eliminate_redundant_bounds_checks = true
+;;!
+;;! [globals.vmctx]
+;;! type = "i64"
+;;! vmctx = true
+;;!
+;;! [globals.heap_base]
+;;! type = "i64"
+;;! load = { base = "vmctx", offset = 0 }
+;;!
+;;! [globals.heap_bound]
+;;! type = "i64"
+;;! load = { base = "vmctx", offset = 8 }
+;;!
+;;! [[heaps]]
+;;! base = "heap_base"
+;;! min_size = 0x10000
+;;! offset_guard_size = 0
+;;! index_type = "i64"
+;;! style = { kind = "dynamic", bound = "heap_bound" }
+
+(module
+  (memory (export "memory") i64 1)
+
+  ;; The first load checks `index + 12 <= bound`, which covers the other
+  ;; two loads.
+  (func (export "fields") (param i64) (result i32)
+    local.get 0
+    i32.load offset=8
+    local.get 0
+    i32.load offset=0
+    i32.add
+    local.get 0
+    i32.load offset=4
+    i32.add
+  )
+
+  ;; The check in the `if` doesn't dominate the store after it, and the
+  ;; store's check doesn't cover the wider load.
+  (func (export "branches") (param i64 i32) (result i32)
+    local.get 1
+    if
+      local.get 0
+      i32.load
+      drop
+    end
+    local.get 0
+    i32.const 0
+    i32.store8
+    local.get 0
+    i32.load
+  )
+)
+
+;; function u0:0(i64, i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64, v1: i64):
+;; @0047                               v3 = global_value.i64 gv1
+;; @0047                               v4 = iadd_imm v3, -12
+;; @0047                               v5 = icmp ugt v0, v4
+;; @0047                               v6 = global_value.i64 gv2
+;; @0047                               v7 = iadd v6, v0
+;; @0047                               v8 = iadd_imm v7, 8
+;; @0047                               v9 = iconst.i64 0
+;; @0047                               v10 = select_spectre_guard v5, v9, v8  ; v9 = 0
+;; @0047                               v11 = load.i32 little heap v10
+;; @004c                               v12 = global_value.i64 gv1
+;; @004c                               v13 = iadd_imm v12, -4
+;; @004c                               v14 = icmp ugt v0, v13
+;; @004c                               v15 = global_value.i64 gv2
+;; @004c                               v16 = iadd v15, v0
+;; @004c                               v17 = iconst.i64 0
+;; @004c                               v18 = select_spectre_guard v5, v17, v16  ; v17 = 0
+;; @004c                               v19 = load.i32 little heap v18
+;; @004f                               v20 = iadd v11, v19
+;; @0052                               v21 = global_value.i64 gv1
+;; @0052                               v22 = iadd_imm v21, -8
+;; @0052                               v23 = icmp ugt v0, v22
+;; @0052                               v24 = global_value.i64 gv2
+;; @0052                               v25 = iadd v24, v0
+;; @0052                               v26 = iadd_imm v25, 4
+;; @0052                               v27 = iconst.i64 0
+;; @0052                               v28 = select_spectre_guard v5, v27, v26  ; v27 = 0
+;; @0052                               v29 = load.i32 little heap v28
+;; @0055                               v30 = iadd v20, v29
+;; @0056                               jump block1(v30)
+;;
+;;                                 block1(v2: i32):
+;; @0056                               return v2
+;; }
+;;
+;; function u0:1(i64, i32, i64 vmctx) -> i32 fast {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned gv0+8
+;;     gv2 = load.i64 notrap aligned gv0
+;;
+;;                                 block0(v0: i64, v1: i32, v2: i64):
+;;                                     v12 -> v0
+;; @005b                               brif v1, block2, block3
+;;
+;;                                 block2:
+;; @005f                               v4 = global_value.i64 gv1
+;; @005f                               v5 = iadd_imm v4, -4
+;; @005f                               v6 = icmp.i64 ugt v0, v5
+;; @005f                               v7 = global_value.i64 gv2
+;; @005f                               v8 = iadd v7, v0
+;; @005f                               v9 = iconst.i64 0
+;; @005f                               v10 = select_spectre_guard v6, v9, v8  ; v9 = 0
+;; @005f                               v11 = load.i32 little heap v10
+;; @0063                               jump block3
+;;
+;;                                 block3:
+;; @0066                               v13 = iconst.i32 0
+;; @0068                               v14 = global_value.i64 gv1
+;; @0068                               v15 = icmp.i64 uge v0, v14
+;; @0068                               v16 = global_value.i64 gv2
+;; @0068                               v17 = iadd v16, v0
+;; @0068                               v18 = iconst.i64 0
+;; @0068                               v19 = select_spectre_guard v15, v18, v17  ; v18 = 0
+;; @0068                               istore8 little heap v13, v19  ; v13 = 0
+;; @006d                               v20 = global_value.i64 gv1
+;; @006d                               v21 = iadd_imm v20, -4
+;; @006d                               v22 = icmp.i64 ugt v12, v21
+;; @006d                               v23 = global_value.i64 gv2
+;; @006d                               v24 = iadd v23, v12
+;; @006d                               v25 = iconst.i64 0
+;; @006d                               v26 = select_spectre_guard v22, v25, v24  ; v25 = 0
+;; @006d                               v27 = load.i32 little heap v26
+;; @0070                               jump block1(v27)
+;;
+;;                                 block1(v3: i32):
+;; @0070                               return v3
+;; }
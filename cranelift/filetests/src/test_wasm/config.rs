@@ -32,6 +32,9 @@ pub struct TestConfig {
 
     #[serde(default)]
     pub relaxed_simd_deterministic: bool,
+
+    #[serde(default)]
+    pub eliminate_redundant_bounds_checks: bool,
 }
 
 impl TestConfig {
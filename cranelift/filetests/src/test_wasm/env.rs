@@ -383,6 +383,27 @@ impl<'a> FuncEnvironment for FuncEnv<'a> {
         )
     }
 
+    fn translate_return_call_indirect(
+        &mut self,
+        builder: &mut cranelift_frontend::FunctionBuilder,
+        table_index: cranelift_wasm::TableIndex,
+        table: ir::Table,
+        sig_index: cranelift_wasm::TypeIndex,
+        sig_ref: ir::SigRef,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> cranelift_wasm::WasmResult<()> {
+        self.inner.translate_return_call_indirect(
+            builder,
+            table_index,
+            table,
+            sig_index,
+            sig_ref,
+            callee,
+            call_args,
+        )
+    }
+
     fn translate_memory_grow(
         &mut self,
         pos: cranelift_codegen::cursor::FuncCursor,
@@ -622,4 +643,8 @@ impl<'a> FuncEnvironment for FuncEnv<'a> {
     fn is_x86(&self) -> bool {
         self.config.target.contains("x86_64")
     }
+
+    fn eliminate_redundant_bounds_checks(&self) -> bool {
+        self.config.eliminate_redundant_bounds_checks
+    }
 }
@@ -1,5 +1,8 @@
 //! Implements the function environment (e.g. a name-to-function mapping) for interpretation.
-use cranelift_codegen::ir::{FuncRef, Function};
+use crate::interpreter::LibCallValues;
+use crate::state::InterpreterFunctionRef;
+use cranelift_codegen::data_value::DataValue;
+use cranelift_codegen::ir::{FuncRef, Function, Signature, TrapCode};
 use cranelift_entity::{entity_impl, PrimaryMap};
 use std::collections::HashMap;
 
@@ -8,6 +11,8 @@ use std::collections::HashMap;
 pub struct FunctionStore<'a> {
     functions: PrimaryMap<FuncIndex, &'a Function>,
     function_names: HashMap<String, FuncIndex>,
+    host_functions: PrimaryMap<HostFuncIndex, &'a HostFunction>,
+    host_function_names: HashMap<String, HostFuncIndex>,
 }
 
 /// An opaque reference to a [`Function`](Function) stored in the [FunctionStore].
@@ -15,6 +20,50 @@ pub struct FunctionStore<'a> {
 pub struct FuncIndex(u32);
 entity_impl!(FuncIndex, "fn");
 
+/// An opaque reference to a [HostFunction] stored in the [FunctionStore].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HostFuncIndex(u32);
+entity_impl!(HostFuncIndex, "hostfn");
+
+/// The callback that runs when interpreted code calls a [HostFunction].
+pub type HostFunctionCallback =
+    dyn Fn(LibCallValues<DataValue>) -> Result<LibCallValues<DataValue>, TrapCode>;
+
+/// A function implemented by the host rather than in CLIF, e.g. a function that a program imports
+/// but does not define. Interpreted code can call it (directly or through its address) like any
+/// other function in the [FunctionStore].
+pub struct HostFunction {
+    signature: Signature,
+    callback: Box<HostFunctionCallback>,
+}
+
+impl HostFunction {
+    /// Create a host function with the given `signature`, which runs `callback` when called.
+    pub fn new(
+        signature: Signature,
+        callback: impl Fn(LibCallValues<DataValue>) -> Result<LibCallValues<DataValue>, TrapCode>
+            + 'static,
+    ) -> Self {
+        Self {
+            signature,
+            callback: Box::new(callback),
+        }
+    }
+
+    /// The signature that callers of this host function must use.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Run the host function's callback with the given arguments.
+    pub fn call(
+        &self,
+        arguments: LibCallValues<DataValue>,
+    ) -> Result<LibCallValues<DataValue>, TrapCode> {
+        (self.callback)(arguments)
+    }
+}
+
 /// This is a helpful conversion for instantiating a store from a single [Function].
 impl<'a> From<&'a Function> for FunctionStore<'a> {
     fn from(function: &'a Function) -> Self {
@@ -28,10 +77,20 @@ impl<'a> FunctionStore<'a> {
     /// Add a function by name.
     pub fn add(&mut self, name: String, function: &'a Function) {
         assert!(!self.function_names.contains_key(&name));
+        assert!(!self.host_function_names.contains_key(&name));
         let index = self.functions.push(function);
         self.function_names.insert(name, index);
     }
 
+    /// Add a host function by name; calls to a function with this name that is not in the store
+    /// are handled by the host function instead.
+    pub fn add_host_function(&mut self, name: String, function: &'a HostFunction) {
+        assert!(!self.function_names.contains_key(&name));
+        assert!(!self.host_function_names.contains_key(&name));
+        let index = self.host_functions.push(function);
+        self.host_function_names.insert(name, index);
+    }
+
     /// Retrieve the index of a function in the function store by its `name`.
     pub fn index_of(&self, name: &str) -> Option<FuncIndex> {
         self.function_names.get(name).cloned()
@@ -48,14 +107,34 @@ impl<'a> FunctionStore<'a> {
         self.get_by_index(index)
     }
 
-    /// Retrieve a function from a [FuncRef] within a [Function]. TODO this should be optimized, if possible, as
-    /// currently it retrieves the function name as a string and performs string matching.
+    /// Retrieve the index of a host function in the function store by its `name`.
+    pub fn host_function_index_of(&self, name: &str) -> Option<HostFuncIndex> {
+        self.host_function_names.get(name).cloned()
+    }
+
+    /// Retrieve a host function by its index in the function store.
+    pub fn get_host_function_by_index(&self, index: HostFuncIndex) -> Option<&'a HostFunction> {
+        self.host_functions.get(index).cloned()
+    }
+
+    /// Retrieve either a function or a host function by its name.
+    pub fn get_ref_by_name(&self, name: &str) -> Option<InterpreterFunctionRef<'a>> {
+        if let Some(function) = self.get_by_name(name) {
+            return Some(function.into());
+        }
+        let index = self.host_function_index_of(name)?;
+        self.get_host_function_by_index(index).map(Into::into)
+    }
+
+    /// Retrieve a function or a host function from a [FuncRef] within a [Function]. TODO this
+    /// should be optimized, if possible, as currently it retrieves the function name as a string
+    /// and performs string matching.
     pub fn get_from_func_ref(
         &self,
         func_ref: FuncRef,
         function: &Function,
-    ) -> Option<&'a Function> {
-        self.get_by_name(&get_function_name(func_ref, function))
+    ) -> Option<InterpreterFunctionRef<'a>> {
+        self.get_ref_by_name(&get_function_name(func_ref, function))
     }
 }
 
@@ -89,6 +168,19 @@ mod tests {
         assert!(env.get_by_name(a).is_some());
     }
 
+    #[test]
+    fn host_function_addition() {
+        let mut env = FunctionStore::default();
+        let host = HostFunction::new(Signature::new(CallConv::Fast), |args| Ok(args));
+
+        env.add_host_function("a".to_string(), &host);
+        assert!(env.get_by_name("a").is_none());
+        assert!(matches!(
+            env.get_ref_by_name("a"),
+            Some(InterpreterFunctionRef::HostFunction(_))
+        ));
+    }
+
     #[test]
     fn nonexistence() {
         let env = FunctionStore::default();
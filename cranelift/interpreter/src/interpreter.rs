@@ -3,7 +3,7 @@
 //! This module partially contains the logic for interpreting Cranelift IR.
 
 use crate::address::{Address, AddressFunctionEntry, AddressRegion, AddressSize};
-use crate::environment::{FuncIndex, FunctionStore};
+use crate::environment::{FuncIndex, FunctionStore, HostFuncIndex, HostFunction};
 use crate::frame::Frame;
 use crate::instruction::DfgInstructionContext;
 use crate::state::{InterpreterFunctionRef, MemoryError, State};
@@ -16,6 +16,7 @@ use cranelift_codegen::ir::{
 };
 use log::trace;
 use smallvec::SmallVec;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::iter;
@@ -183,6 +184,16 @@ pub enum InterpreterError {
 pub type LibCallValues<V> = SmallVec<[V; 1]>;
 pub type LibCallHandler<V> = fn(LibCall, LibCallValues<V>) -> Result<LibCallValues<V>, TrapCode>;
 
+/// A data object in the interpreter's memory; interpreted code refers to it through a symbol
+/// global value with the data object's name.
+#[derive(Clone, Debug, Default)]
+pub struct DataObject {
+    /// The current contents of the data object.
+    pub contents: Vec<u8>,
+    /// Whether interpreted code may store to the data object.
+    pub writable: bool,
+}
+
 /// Maintains the [Interpreter]'s state, implementing the [State] trait.
 pub struct InterpreterState<'a> {
     pub functions: FunctionStore<'a>,
@@ -191,6 +202,9 @@ pub struct InterpreterState<'a> {
     /// Number of bytes from the bottom of the stack where the current frame's stack space is
     pub frame_offset: usize,
     pub stack: Vec<u8>,
+    /// Data objects, in the order they were added; see [InterpreterState::with_data_object].
+    pub data_objects: Vec<DataObject>,
+    pub data_object_names: HashMap<String, usize>,
     pub pinned_reg: DataValue,
     pub native_endianness: Endianness,
 }
@@ -208,6 +222,8 @@ impl Default for InterpreterState<'_> {
             frame_stack: vec![],
             frame_offset: 0,
             stack: Vec::with_capacity(1024),
+            data_objects: vec![],
+            data_object_names: HashMap::new(),
             pinned_reg: DataValue::U64(0),
             native_endianness,
         }
@@ -225,6 +241,48 @@ impl<'a> InterpreterState<'a> {
         self
     }
 
+    /// Adds a data object that interpreted code can refer to with a symbol global value named
+    /// `name`.
+    pub fn with_data_object(mut self, name: String, object: DataObject) -> Self {
+        assert!(!self.data_object_names.contains_key(&name));
+        self.data_object_names.insert(name, self.data_objects.len());
+        self.data_objects.push(object);
+        self
+    }
+
+    /// Computes the address of the data object, function or host function named `name`.
+    pub fn symbol_address(&self, size: AddressSize, name: &str) -> Result<Address, MemoryError> {
+        match self.data_object_names.get(name) {
+            Some(&index) => Address::from_parts(size, AddressRegion::GlobalValue, index as u64, 0),
+            None => self.named_function_address(size, name),
+        }
+    }
+
+    fn named_function_address(
+        &self,
+        size: AddressSize,
+        name: &str,
+    ) -> Result<Address, MemoryError> {
+        let (entry, index) = if let Some(index) = self.functions.index_of(name) {
+            (AddressFunctionEntry::UserFunction, index.as_u32())
+        } else if let Some(index) = self.functions.host_function_index_of(name) {
+            (AddressFunctionEntry::HostFunction, index.as_u32())
+        } else {
+            return Err(MemoryError::UnknownSymbol(name.to_string()));
+        };
+
+        Address::from_parts(size, AddressRegion::Function, entry as u64, index as u64)
+    }
+
+    fn data_object(&self, addr: &Address) -> Result<&DataObject, MemoryError> {
+        self.data_objects
+            .get(addr.entry as usize)
+            .ok_or(MemoryError::InvalidEntry {
+                entry: addr.entry,
+                max: self.data_objects.len() as u64,
+            })
+    }
+
     fn current_frame_mut(&mut self) -> &mut Frame<'a> {
         let num_frames = self.frame_stack.len();
         match num_frames {
@@ -243,7 +301,7 @@ impl<'a> InterpreterState<'a> {
 }
 
 impl<'a> State<'a, DataValue> for InterpreterState<'a> {
-    fn get_function(&self, func_ref: FuncRef) -> Option<&'a Function> {
+    fn get_function(&self, func_ref: FuncRef) -> Option<InterpreterFunctionRef<'a>> {
         self.functions
             .get_from_func_ref(func_ref, self.frame_stack.last().unwrap().function)
     }
@@ -255,6 +313,14 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
         self.libcall_handler
     }
 
+    fn call_host_function(
+        &self,
+        function: &HostFunction,
+        arguments: LibCallValues<DataValue>,
+    ) -> Result<LibCallValues<DataValue>, TrapCode> {
+        function.call(arguments)
+    }
+
     fn push_frame(&mut self, function: &'a Function) {
         if let Some(frame) = self.frame_stack.iter().last() {
             self.frame_offset += frame.function.fixed_stack_size() as usize;
@@ -333,6 +399,14 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
 
                 &self.stack[addr_start..addr_end]
             }
+            AddressRegion::GlobalValue => {
+                let contents = &self.data_object(&addr)?.contents;
+                if addr_end > contents.len() {
+                    return Err(MemoryError::OutOfBoundsLoad { addr, load_size });
+                }
+
+                &contents[addr_start..addr_end]
+            }
             _ => unimplemented!(),
         };
 
@@ -365,6 +439,17 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
 
                 &mut self.stack[addr_start..addr_end]
             }
+            AddressRegion::GlobalValue => {
+                let object = self.data_object(&addr)?;
+                if addr_end > object.contents.len() {
+                    return Err(MemoryError::OutOfBoundsStore { addr, store_size });
+                }
+                if !object.writable {
+                    return Err(MemoryError::ReadOnlyStore { addr, store_size });
+                }
+
+                &mut self.data_objects[addr.entry as usize].contents[addr_start..addr_end]
+            }
             _ => unimplemented!(),
         };
 
@@ -386,20 +471,10 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
     ) -> Result<Address, MemoryError> {
         let curr_func = self.get_current_function();
         let (entry, index) = match name {
-            ExternalName::User(username) => {
-                let ext_name = &curr_func.params.user_named_funcs()[*username];
-
+            ExternalName::User(_) | ExternalName::TestCase(_) => {
                 // TODO: This is not optimal since we are looking up by string name
-                let index = self.functions.index_of(&ext_name.to_string()).unwrap();
-
-                (AddressFunctionEntry::UserFunction, index.as_u32())
-            }
-
-            ExternalName::TestCase(testname) => {
-                // TODO: This is not optimal since we are looking up by string name
-                let index = self.functions.index_of(&testname.to_string()).unwrap();
-
-                (AddressFunctionEntry::UserFunction, index.as_u32())
+                let name = name.display(Some(&curr_func.params)).to_string();
+                return self.named_function_address(size, &name);
             }
             ExternalName::LibCall(libcall) => {
                 // We don't properly have a "libcall" store, but we can use `LibCall::all()`
@@ -433,6 +508,11 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
                 .get(index as usize)
                 .copied()
                 .map(InterpreterFunctionRef::from),
+
+            AddressFunctionEntry::HostFunction => self
+                .functions
+                .get_host_function_by_index(HostFuncIndex::from_u32(index))
+                .map(InterpreterFunctionRef::from),
         }
     }
 
@@ -513,7 +593,20 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
                         action_stack.push(ResolveAction::Add(dv));
                         action_stack.push(ResolveAction::Resolve(base));
                     }
-                    GlobalValueData::Symbol { .. } => unimplemented!(),
+                    GlobalValueData::Symbol {
+                        ref name, offset, ..
+                    } => {
+                        // Like the addresses used by loads and stores, symbol addresses are
+                        // always 64 bits wide.
+                        let name = name.display(Some(&func.params)).to_string();
+                        let addr = self.symbol_address(AddressSize::_64, &name)?;
+                        current_val = DataValue::try_from(addr)?;
+
+                        let offset: i64 = offset.into();
+                        if offset != 0 {
+                            action_stack.push(ResolveAction::Add(DataValue::I64(offset)));
+                        }
+                    }
                     GlobalValueData::DynScaleTargetConst { .. } => unimplemented!(),
                 },
                 Some(ResolveAction::Add(dv)) => {
@@ -550,6 +643,16 @@ impl<'a> State<'a, DataValue> for InterpreterState<'a> {
                     });
                 }
             }
+            AddressRegion::GlobalValue => {
+                let object_len = self.data_object(addr)?.contents.len() as u64;
+
+                if addr.offset > object_len {
+                    return Err(MemoryError::InvalidOffset {
+                        offset: addr.offset,
+                        max: object_len,
+                    });
+                }
+            }
             _ => unimplemented!(),
         };
 
@@ -570,7 +673,7 @@ mod tests {
     use super::*;
     use crate::step::CraneliftTrap;
     use cranelift_codegen::ir::immediates::Ieee32;
-    use cranelift_codegen::ir::TrapCode;
+    use cranelift_codegen::ir::{SigRef, TrapCode};
     use cranelift_reader::parse_functions;
     use smallvec::smallvec;
 
@@ -1026,4 +1129,109 @@ mod tests {
 
         assert_eq!(trap, CraneliftTrap::User(TrapCode::HeapMisaligned));
     }
+
+    #[test]
+    fn host_function() {
+        let code = "function %test(i64) -> i64 {
+            sig0 = (i64) -> i64 fast
+            fn0 = %triple sig0
+        block0(v0: i64):
+            v1 = call fn0(v0)
+            v2 = func_addr.i64 fn0
+            v3 = call_indirect sig0, v2(v1)
+            return v3
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let triple =
+            HostFunction::new(
+                func.dfg.signatures[SigRef::from_u32(0)].clone(),
+                |args| match &args[..] {
+                    [DataValue::I64(a)] => Ok(smallvec![DataValue::I64(a * 3)]),
+                    _ => panic!("Unexpected args"),
+                },
+            );
+        let mut env = FunctionStore::default();
+        env.add(func.name.to_string(), &func);
+        env.add_host_function("%triple".to_string(), &triple);
+        let state = InterpreterState::default().with_function_store(env);
+
+        let result = Interpreter::new(state)
+            .call_by_name("%test", &[DataValue::I64(5)])
+            .unwrap()
+            .unwrap_return();
+
+        assert_eq!(result, vec![DataValue::I64(45)])
+    }
+
+    #[test]
+    fn data_objects() {
+        let code = "function %test() -> i32 {
+            gv0 = symbol %counter
+            gv1 = symbol %table+4
+        block0:
+            v0 = symbol_value.i64 gv0
+            v1 = load.i32 v0
+            v2 = iadd_imm v1, 1
+            store v2, v0
+            v3 = global_value.i64 gv1
+            v4 = load.i32 v3
+            v5 = load.i32 v0
+            v6 = iadd v4, v5
+            return v6
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let mut env = FunctionStore::default();
+        env.add(func.name.to_string(), &func);
+        let counter = DataObject {
+            contents: 41i32.to_ne_bytes().to_vec(),
+            writable: true,
+        };
+        let table = DataObject {
+            contents: [1i32.to_ne_bytes(), 100i32.to_ne_bytes()].concat(),
+            writable: false,
+        };
+        let state = InterpreterState::default()
+            .with_function_store(env)
+            .with_data_object("%counter".to_string(), counter)
+            .with_data_object("%table".to_string(), table);
+
+        let result = Interpreter::new(state)
+            .call_by_name("%test", &[])
+            .unwrap()
+            .unwrap_return();
+
+        assert_eq!(result, vec![DataValue::I32(142)])
+    }
+
+    #[test]
+    fn read_only_data_store_traps() {
+        let code = "function %test() {
+            gv0 = symbol %table
+        block0:
+            v0 = global_value.i64 gv0
+            v1 = iconst.i32 1
+            store v1, v0
+            return
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let mut env = FunctionStore::default();
+        env.add(func.name.to_string(), &func);
+        let table = DataObject {
+            contents: vec![0; 4],
+            writable: false,
+        };
+        let state = InterpreterState::default()
+            .with_function_store(env)
+            .with_data_object("%table".to_string(), table);
+
+        let trap = Interpreter::new(state)
+            .call_by_name("%test", &[])
+            .unwrap()
+            .unwrap_trap();
+
+        assert_eq!(trap, CraneliftTrap::User(TrapCode::HeapOutOfBounds));
+    }
 }
@@ -7,6 +7,7 @@ pub mod environment;
 pub mod frame;
 pub mod instruction;
 pub mod interpreter;
+pub mod module;
 pub mod state;
 pub mod step;
 pub mod value;
@@ -0,0 +1,271 @@
+//! Implements a [Module] whose functions are interpreted instead of compiled.
+//!
+//! The [InterpreterModule] keeps the CLIF of every function defined in it, lays out its data
+//! objects in the interpreter's memory and resolves the module's imports to [HostFunction]s, so
+//! that whole `cranelift-module` programs can be run with the [Interpreter].
+use crate::address::AddressSize;
+use crate::environment::{FunctionStore, HostFunction};
+use crate::interpreter::{DataObject, Interpreter, InterpreterState, LibCallHandler};
+use crate::step::ControlFlow;
+use cranelift_codegen::binemit::Reloc;
+use cranelift_codegen::data_value::DataValue;
+use cranelift_codegen::ir::{self, Endianness};
+use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
+use cranelift_codegen::{Context, MachReloc};
+use cranelift_entity::SecondaryMap;
+use cranelift_module::{
+    DataContext, DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleCompiledFunction,
+    ModuleDeclarations, ModuleError, ModuleExtName, ModuleResult,
+};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// A builder for [InterpreterModule].
+pub struct InterpreterBuilder {
+    isa: OwnedTargetIsa,
+    host_functions: HashMap<String, HostFunction>,
+    libcall_handler: Option<LibCallHandler<DataValue>>,
+}
+
+impl InterpreterBuilder {
+    /// Create a new `InterpreterBuilder`.
+    ///
+    /// No code is generated for `isa`, but the module's clients still use it to pick pointer
+    /// types, calling conventions and the like when building their functions.
+    pub fn new(isa: OwnedTargetIsa) -> Self {
+        Self {
+            isa,
+            host_functions: HashMap::new(),
+            libcall_handler: None,
+        }
+    }
+
+    /// Define a host function.
+    ///
+    /// The interpreter calls host functions in place of functions that are imported, but not
+    /// defined, by the module; they are matched by the name the function was declared with.
+    ///
+    /// If a host function is defined more than once, the most recent definition will be retained.
+    pub fn host_function<K>(&mut self, name: K, function: HostFunction) -> &mut Self
+    where
+        K: Into<String>,
+    {
+        self.host_functions.insert(name.into(), function);
+        self
+    }
+
+    /// Set the handler for the libcalls made by the module's functions. By default, every libcall
+    /// traps.
+    pub fn libcall_handler(&mut self, handler: LibCallHandler<DataValue>) -> &mut Self {
+        self.libcall_handler = Some(handler);
+        self
+    }
+}
+
+/// An `InterpreterModule` implements `Module` by keeping the functions defined in it as CLIF and
+/// running them with the [Interpreter].
+///
+/// See the [InterpreterBuilder] for a convenient way to construct `InterpreterModule` instances.
+pub struct InterpreterModule {
+    isa: OwnedTargetIsa,
+    host_functions: HashMap<String, HostFunction>,
+    libcall_handler: Option<LibCallHandler<DataValue>>,
+    declarations: ModuleDeclarations,
+    functions: SecondaryMap<FuncId, Option<ir::Function>>,
+    data_objects: SecondaryMap<DataId, Option<DataDescription>>,
+}
+
+impl InterpreterModule {
+    /// Create a new `InterpreterModule`.
+    pub fn new(builder: InterpreterBuilder) -> Self {
+        Self {
+            isa: builder.isa,
+            host_functions: builder.host_functions,
+            libcall_handler: builder.libcall_handler,
+            declarations: ModuleDeclarations::default(),
+            functions: SecondaryMap::new(),
+            data_objects: SecondaryMap::new(),
+        }
+    }
+
+    /// The name that the interpreter knows `func` by, e.g. for [Interpreter::call_by_name].
+    pub fn function_name(func: FuncId) -> String {
+        ModuleExtName::user(0, func.as_u32()).to_string()
+    }
+
+    /// The name that the interpreter knows `data` by, e.g. for
+    /// [InterpreterState::symbol_address].
+    pub fn data_object_name(data: DataId) -> String {
+        ModuleExtName::user(1, data.as_u32()).to_string()
+    }
+
+    /// Create a fresh interpreter state containing the functions and data objects defined so
+    /// far, along with the host functions for the module's imports.
+    ///
+    /// Every data object starts out with its initial contents, so data written by one run of the
+    /// interpreter is not visible to the next run unless it uses the same state.
+    pub fn interpreter_state(&self) -> ModuleResult<InterpreterState<'_>> {
+        let mut functions = FunctionStore::default();
+        for (id, decl) in self.declarations.get_functions() {
+            if let Some(func) = &self.functions[id] {
+                functions.add(Self::function_name(id), func);
+            } else if let Some(host_function) = self.host_functions.get(&decl.name) {
+                functions.add_host_function(Self::function_name(id), host_function);
+            }
+        }
+
+        let mut state = InterpreterState::default().with_function_store(functions);
+        if let Some(handler) = self.libcall_handler {
+            state = state.with_libcall_handler(handler);
+        }
+        let mut defined = vec![];
+        for (id, decl) in self.declarations.get_data_objects() {
+            let data = match &self.data_objects[id] {
+                Some(data) => data,
+                None => continue,
+            };
+            let contents = match &data.init {
+                Init::Uninitialized => panic!("data is not initialized yet"),
+                Init::Zeros { size } => vec![0; *size],
+                Init::Bytes { contents } => contents.to_vec(),
+            };
+            let object = DataObject {
+                contents,
+                writable: decl.writable,
+            };
+            state = state.with_data_object(Self::data_object_name(id), object);
+            defined.push(data);
+        }
+
+        // Now that every symbol has an address, patch the addresses that data objects refer to
+        // into their contents. Like every other address in the interpreter, they're 64 bits wide.
+        for (index, data) in defined.into_iter().enumerate() {
+            for reloc in data.all_relocs(Reloc::Abs8) {
+                let addr = state
+                    .symbol_address(AddressSize::_64, &reloc.name.to_string())
+                    .and_then(DataValue::try_from)
+                    .map_err(|err| ModuleError::Backend(err.into()))?;
+                let value = match addr {
+                    DataValue::I64(addr) => DataValue::I64(addr.wrapping_add(reloc.addend)),
+                    _ => unreachable!(),
+                };
+
+                let offset = reloc.offset as usize;
+                let slot = &mut state.data_objects[index].contents[offset..offset + 8];
+                match state.native_endianness {
+                    Endianness::Big => value.write_to_slice_be(slot),
+                    Endianness::Little => value.write_to_slice_le(slot),
+                }
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Interpret a call to `func` with the given arguments, starting from a fresh
+    /// [interpreter state](InterpreterModule::interpreter_state).
+    pub fn call(
+        &self,
+        func: FuncId,
+        arguments: &[DataValue],
+    ) -> ModuleResult<ControlFlow<'_, DataValue>> {
+        let state = self.interpreter_state()?;
+        Interpreter::new(state)
+            .call_by_name(&Self::function_name(func), arguments)
+            .map_err(|err| ModuleError::Backend(err.into()))
+    }
+}
+
+impl Module for InterpreterModule {
+    fn isa(&self) -> &dyn TargetIsa {
+        &*self.isa
+    }
+
+    fn declarations(&self) -> &ModuleDeclarations {
+        &self.declarations
+    }
+
+    fn declare_function(
+        &mut self,
+        name: &str,
+        linkage: Linkage,
+        signature: &ir::Signature,
+    ) -> ModuleResult<FuncId> {
+        let (id, _linkage) = self
+            .declarations
+            .declare_function(name, linkage, signature)?;
+        Ok(id)
+    }
+
+    fn declare_anonymous_function(&mut self, signature: &ir::Signature) -> ModuleResult<FuncId> {
+        self.declarations.declare_anonymous_function(signature)
+    }
+
+    fn declare_data(
+        &mut self,
+        name: &str,
+        linkage: Linkage,
+        writable: bool,
+        tls: bool,
+    ) -> ModuleResult<DataId> {
+        assert!(!tls, "the interpreter doesn't yet support TLS");
+        let (id, _linkage) = self
+            .declarations
+            .declare_data(name, linkage, writable, tls)?;
+        Ok(id)
+    }
+
+    fn declare_anonymous_data(&mut self, writable: bool, tls: bool) -> ModuleResult<DataId> {
+        assert!(!tls, "the interpreter doesn't yet support TLS");
+        self.declarations.declare_anonymous_data(writable, tls)
+    }
+
+    fn define_function(
+        &mut self,
+        id: FuncId,
+        ctx: &mut Context,
+    ) -> ModuleResult<ModuleCompiledFunction> {
+        let decl = self.declarations.get_function_decl(id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(decl.name.clone()));
+        }
+
+        if self.functions[id].is_some() {
+            return Err(ModuleError::DuplicateDefinition(decl.name.to_owned()));
+        }
+
+        self.functions[id] = Some(ctx.func.clone());
+
+        // Nothing is compiled, so there is no code to speak of.
+        Ok(ModuleCompiledFunction { size: 0 })
+    }
+
+    fn define_function_bytes(
+        &mut self,
+        id: FuncId,
+        _func: &ir::Function,
+        _alignment: u64,
+        _bytes: &[u8],
+        _relocs: &[MachReloc],
+    ) -> ModuleResult<ModuleCompiledFunction> {
+        let decl = self.declarations.get_function_decl(id);
+        Err(ModuleError::Backend(anyhow::anyhow!(
+            "the interpreter can't run machine code, which was given for {}",
+            decl.name
+        )))
+    }
+
+    fn define_data(&mut self, id: DataId, data: &DataContext) -> ModuleResult<()> {
+        let decl = self.declarations.get_data_decl(id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(decl.name.clone()));
+        }
+
+        if self.data_objects[id].is_some() {
+            return Err(ModuleError::DuplicateDefinition(decl.name.to_owned()));
+        }
+
+        self.data_objects[id] = Some(data.description().clone());
+        Ok(())
+    }
+}
@@ -1,11 +1,12 @@
 //! Cranelift instructions modify the state of the machine; the [State] trait describes these
 //! ways this can happen.
 use crate::address::{Address, AddressSize};
+use crate::environment::HostFunction;
 use crate::interpreter::LibCallHandler;
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::{
-    ExternalName, FuncRef, Function, GlobalValue, LibCall, MemFlags, Signature, StackSlot, Type,
-    Value,
+    ExternalName, FuncRef, Function, GlobalValue, LibCall, MemFlags, Signature, StackSlot,
+    TrapCode, Type, Value,
 };
 use cranelift_codegen::isa::CallConv;
 use cranelift_entity::PrimaryMap;
@@ -23,12 +24,18 @@ use thiserror::Error;
 /// [ImmutableRegisterState] for an example of this: it only exposes the values referenced by the
 /// SSA references in the current frame and not much else.
 pub trait State<'a, V> {
-    /// Retrieve a reference to a [Function].
-    fn get_function(&self, func_ref: FuncRef) -> Option<&'a Function>;
+    /// Retrieve a reference to a [Function] or [HostFunction].
+    fn get_function(&self, func_ref: FuncRef) -> Option<InterpreterFunctionRef<'a>>;
     /// Retrieve a reference to the currently executing [Function].
     fn get_current_function(&self) -> &'a Function;
     /// Retrieve the handler callback for a [LibCall](cranelift_codegen::ir::LibCall)
     fn get_libcall_handler(&self) -> LibCallHandler<V>;
+    /// Call a [HostFunction] with the given arguments, returning its results.
+    fn call_host_function(
+        &self,
+        function: &HostFunction,
+        arguments: SmallVec<[V; 1]>,
+    ) -> Result<SmallVec<[V; 1]>, TrapCode>;
     /// Record that an interpreter has called into a new [Function].
     fn push_frame(&mut self, function: &'a Function);
     /// Record that an interpreter has returned from a called [Function].
@@ -104,6 +111,7 @@ pub trait State<'a, V> {
 pub enum InterpreterFunctionRef<'a> {
     Function(&'a Function),
     LibCall(LibCall),
+    HostFunction(&'a HostFunction),
 }
 
 impl<'a> InterpreterFunctionRef<'a> {
@@ -112,6 +120,7 @@ impl<'a> InterpreterFunctionRef<'a> {
             InterpreterFunctionRef::Function(f) => f.stencil.signature.clone(),
             // CallConv here is sort of irrelevant, since we don't use it for anything
             InterpreterFunctionRef::LibCall(lc) => lc.signature(CallConv::SystemV),
+            InterpreterFunctionRef::HostFunction(hf) => hf.signature().clone(),
         }
     }
 }
@@ -128,6 +137,12 @@ impl From<LibCall> for InterpreterFunctionRef<'_> {
     }
 }
 
+impl<'a> From<&'a HostFunction> for InterpreterFunctionRef<'a> {
+    fn from(hf: &'a HostFunction) -> Self {
+        InterpreterFunctionRef::HostFunction(hf)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum MemoryError {
     #[error("Invalid DataValue passed as an address: {0}")]
@@ -146,6 +161,10 @@ pub enum MemoryError {
     MisalignedLoad { addr: Address, load_size: usize },
     #[error("Store of {store_size} bytes is misaligned at address {addr:?}")]
     MisalignedStore { addr: Address, store_size: usize },
+    #[error("Store of {store_size} bytes to read-only data at address {addr:?}")]
+    ReadOnlyStore { addr: Address, store_size: usize },
+    #[error("Unknown symbol: {0}")]
+    UnknownSymbol(String),
 }
 
 /// This dummy state allows interpretation over an immutable mapping of values in a single frame.
@@ -160,7 +179,7 @@ impl<'a, V> State<'a, V> for ImmutableRegisterState<'a, V>
 where
     V: Clone,
 {
-    fn get_function(&self, _func_ref: FuncRef) -> Option<&'a Function> {
+    fn get_function(&self, _func_ref: FuncRef) -> Option<InterpreterFunctionRef<'a>> {
         None
     }
 
@@ -172,6 +191,14 @@ where
         unimplemented!()
     }
 
+    fn call_host_function(
+        &self,
+        _function: &HostFunction,
+        _arguments: SmallVec<[V; 1]>,
+    ) -> Result<SmallVec<[V; 1]>, TrapCode> {
+        unimplemented!()
+    }
+
     fn push_frame(&mut self, _function: &'a Function) {
         unimplemented!()
     }
@@ -175,6 +175,8 @@ where
         MemoryError::OutOfBoundsLoad { .. } => TrapCode::HeapOutOfBounds,
         MemoryError::MisalignedLoad { .. } => TrapCode::HeapMisaligned,
         MemoryError::MisalignedStore { .. } => TrapCode::HeapMisaligned,
+        MemoryError::ReadOnlyStore { .. } => TrapCode::HeapOutOfBounds,
+        MemoryError::UnknownSymbol(_) => TrapCode::HeapOutOfBounds,
     };
 
     // Assigns or traps depending on the value of the result
@@ -300,8 +302,10 @@ where
             )));
         }
 
-        Ok(match func_ref {
-            InterpreterFunctionRef::Function(func) => make_ctrl_flow(func, args),
+        // We don't transfer control to a libcall or host function, we just execute it and return
+        // the results.
+        let res = match func_ref {
+            InterpreterFunctionRef::Function(func) => return Ok(make_ctrl_flow(func, args)),
             InterpreterFunctionRef::LibCall(libcall) => {
                 debug_assert!(
                     !matches!(
@@ -311,22 +315,32 @@ where
                     "Cannot tail call to libcalls"
                 );
                 let libcall_handler = state.get_libcall_handler();
-
-                // We don't transfer control to a libcall, we just execute it and return the results
-                let res = libcall_handler(libcall, args);
-                let res = match res {
-                    Err(trap) => return Ok(ControlFlow::Trap(CraneliftTrap::User(trap))),
-                    Ok(rets) => rets,
-                };
-
-                // Check that what the handler returned is what we expect.
-                if validate_signature_params(&signature.returns[..], &res[..]) {
-                    ControlFlow::Assign(res)
-                } else {
-                    ControlFlow::Trap(CraneliftTrap::User(TrapCode::BadSignature))
-                }
+                libcall_handler(libcall, args)
             }
-        })
+            InterpreterFunctionRef::HostFunction(host_function) => {
+                debug_assert!(
+                    !matches!(
+                        inst.opcode(),
+                        Opcode::ReturnCall | Opcode::ReturnCallIndirect,
+                    ),
+                    "Cannot tail call to host functions"
+                );
+                state.call_host_function(host_function, args)
+            }
+        };
+        let res = match res {
+            Err(trap) => return Ok(ControlFlow::Trap(CraneliftTrap::User(trap))),
+            Ok(rets) => rets,
+        };
+
+        // Check that what the handler returned is what we expect.
+        if validate_signature_params(&signature.returns[..], &res[..]) {
+            Ok(ControlFlow::Assign(res))
+        } else {
+            Ok(ControlFlow::Trap(CraneliftTrap::User(
+                TrapCode::BadSignature,
+            )))
+        }
     };
 
     // Interpret a Cranelift instruction.
@@ -396,17 +410,15 @@ where
                 .ok_or(StepError::UnknownFunction(func_ref))?;
 
             let args = args()?;
-            let func = match ext_data.name {
-                // These functions should be registered in the regular function store
-                ExternalName::User(_) | ExternalName::TestCase(_) => {
-                    let function = state
+            let func =
+                match ext_data.name {
+                    // These functions should be registered in the regular function store
+                    ExternalName::User(_) | ExternalName::TestCase(_) => state
                         .get_function(func_ref)
-                        .ok_or(StepError::UnknownFunction(func_ref))?;
-                    InterpreterFunctionRef::Function(function)
-                }
-                ExternalName::LibCall(libcall) => InterpreterFunctionRef::LibCall(libcall),
-                ExternalName::KnownSymbol(_) => unimplemented!(),
-            };
+                        .ok_or(StepError::UnknownFunction(func_ref))?,
+                    ExternalName::LibCall(libcall) => InterpreterFunctionRef::LibCall(libcall),
+                    ExternalName::KnownSymbol(_) => unimplemented!(),
+                };
 
             let make_control_flow = match inst.opcode() {
                 Opcode::Call => ControlFlow::Call,
@@ -563,14 +575,13 @@ where
         Opcode::DynamicStackAddr => unimplemented!("DynamicStackSlot"),
         Opcode::DynamicStackLoad => unimplemented!("DynamicStackLoad"),
         Opcode::DynamicStackStore => unimplemented!("DynamicStackStore"),
-        Opcode::GlobalValue => {
+        Opcode::GlobalValue | Opcode::SymbolValue => {
             if let InstructionData::UnaryGlobalValue { global_value, .. } = inst {
                 assign_or_memtrap(state.resolve_global_value(global_value))
             } else {
                 unreachable!()
             }
         }
-        Opcode::SymbolValue => unimplemented!("SymbolValue"),
         Opcode::TlsValue => unimplemented!("TlsValue"),
         Opcode::GetPinnedReg => assign(state.get_pinned_reg()),
         Opcode::SetPinnedReg => {
@@ -0,0 +1,210 @@
+use cranelift_codegen::data_value::DataValue;
+use cranelift_codegen::ir::*;
+use cranelift_codegen::isa::CallConv;
+use cranelift_codegen::settings;
+use cranelift_codegen::Context;
+use cranelift_frontend::*;
+use cranelift_interpreter::environment::HostFunction;
+use cranelift_interpreter::interpreter::Interpreter;
+use cranelift_interpreter::module::{InterpreterBuilder, InterpreterModule};
+use cranelift_interpreter::step::CraneliftTrap;
+use cranelift_module::*;
+use smallvec::smallvec;
+
+fn module(host_functions: Vec<(&str, HostFunction)>) -> InterpreterModule {
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut builder = InterpreterBuilder::new(isa);
+    for (name, function) in host_functions {
+        builder.host_function(name, function);
+    }
+    InterpreterModule::new(builder)
+}
+
+fn unary_signature() -> Signature {
+    Signature {
+        params: vec![AbiParam::new(types::I64)],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: CallConv::SystemV,
+    }
+}
+
+fn define_add_one(module: &mut InterpreterModule) -> FuncId {
+    let sig = unary_signature();
+    let func_id = module
+        .declare_function("add_one", Linkage::Local, &sig)
+        .unwrap();
+
+    let mut ctx = Context::for_function(Function::with_name_signature(
+        UserFuncName::user(0, func_id.as_u32()),
+        sig,
+    ));
+    let mut func_ctx = FunctionBuilderContext::new();
+    let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+    let block = bcx.create_block();
+    bcx.append_block_params_for_function_params(block);
+    bcx.switch_to_block(block);
+    let arg = bcx.block_params(block)[0];
+    let res = bcx.ins().iadd_imm(arg, 1);
+    bcx.ins().return_(&[res]);
+    bcx.seal_all_blocks();
+    bcx.finalize();
+
+    module.define_function(func_id, &mut ctx).unwrap();
+    func_id
+}
+
+/// Defines a `main` function that passes its argument through the imported `double`, then
+/// through the function in the first slot of `table`, and adds the byte that the second slot of
+/// `table` points to and the number of times it has been called.
+fn define_main(module: &mut InterpreterModule) -> FuncId {
+    let sig = unary_signature();
+    let double = module
+        .declare_function("double", Linkage::Import, &sig)
+        .unwrap();
+    let add_one = define_add_one(module);
+
+    let bytes = module
+        .declare_data("bytes", Linkage::Local, false, false)
+        .unwrap();
+    let mut data_ctx = DataContext::new();
+    data_ctx.define(Box::new([1, 2, 3, 4]));
+    module.define_data(bytes, &data_ctx).unwrap();
+
+    let table = module
+        .declare_data("table", Linkage::Local, false, false)
+        .unwrap();
+    let mut data_ctx = DataContext::new();
+    data_ctx.define_zeroinit(16);
+    let add_one_ref = module.declare_func_in_data(add_one, &mut data_ctx);
+    data_ctx.write_function_addr(0, add_one_ref);
+    let bytes_gv = module.declare_data_in_data(bytes, &mut data_ctx);
+    data_ctx.write_data_addr(8, bytes_gv, 2);
+    module.define_data(table, &data_ctx).unwrap();
+
+    let counter = module
+        .declare_data("counter", Linkage::Local, true, false)
+        .unwrap();
+    let mut data_ctx = DataContext::new();
+    data_ctx.define_zeroinit(8);
+    module.define_data(counter, &data_ctx).unwrap();
+
+    let func_id = module
+        .declare_function("main", Linkage::Export, &sig)
+        .unwrap();
+    let mut ctx = Context::for_function(Function::with_name_signature(
+        UserFuncName::user(0, func_id.as_u32()),
+        sig.clone(),
+    ));
+    let double_ref = module.declare_func_in_func(double, &mut ctx.func);
+    let table_gv = module.declare_data_in_func(table, &mut ctx.func);
+    let counter_gv = module.declare_data_in_func(counter, &mut ctx.func);
+
+    let mut func_ctx = FunctionBuilderContext::new();
+    let mut bcx = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+    let sig_ref = bcx.import_signature(sig);
+    let block = bcx.create_block();
+    bcx.append_block_params_for_function_params(block);
+    bcx.switch_to_block(block);
+    let arg = bcx.block_params(block)[0];
+    let call = bcx.ins().call(double_ref, &[arg]);
+    let doubled = bcx.inst_results(call)[0];
+
+    let table_addr = bcx.ins().global_value(types::I64, table_gv);
+    let add_one_addr = bcx.ins().load(types::I64, MemFlags::new(), table_addr, 0);
+    let call = bcx.ins().call_indirect(sig_ref, add_one_addr, &[doubled]);
+    let incremented = bcx.inst_results(call)[0];
+    let byte_addr = bcx.ins().load(types::I64, MemFlags::new(), table_addr, 8);
+    let byte = bcx.ins().uload8(types::I64, MemFlags::new(), byte_addr, 0);
+
+    let counter_addr = bcx.ins().symbol_value(types::I64, counter_gv);
+    let count = bcx.ins().load(types::I64, MemFlags::new(), counter_addr, 0);
+    let count = bcx.ins().iadd_imm(count, 1);
+    bcx.ins().store(MemFlags::new(), count, counter_addr, 0);
+
+    let res = bcx.ins().iadd(incremented, byte);
+    let res = bcx.ins().iadd(res, count);
+    bcx.ins().return_(&[res]);
+    bcx.seal_all_blocks();
+    bcx.finalize();
+
+    module.define_function(func_id, &mut ctx).unwrap();
+    func_id
+}
+
+fn double() -> HostFunction {
+    HostFunction::new(unary_signature(), |args| match &args[..] {
+        [DataValue::I64(a)] => Ok(smallvec![DataValue::I64(a * 2)]),
+        _ => panic!("Unexpected args"),
+    })
+}
+
+#[test]
+fn host_functions_and_data_objects() {
+    let mut module = module(vec![("double", double())]);
+    let main = define_main(&mut module);
+
+    // 2 * 10 + 1, plus the third byte of `bytes`, plus the first call.
+    let result = module
+        .call(main, &[DataValue::I64(10)])
+        .unwrap()
+        .unwrap_return();
+    assert_eq!(result, vec![DataValue::I64(25)]);
+
+    // Every call starts out with a fresh copy of the data objects...
+    let result = module
+        .call(main, &[DataValue::I64(10)])
+        .unwrap()
+        .unwrap_return();
+    assert_eq!(result, vec![DataValue::I64(25)]);
+
+    // ...unless the interpreter state is reused.
+    let mut interpreter = Interpreter::new(module.interpreter_state().unwrap());
+    let name = InterpreterModule::function_name(main);
+    for expected in [25, 26, 27] {
+        let result = interpreter
+            .call_by_name(&name, &[DataValue::I64(10)])
+            .unwrap()
+            .unwrap_return();
+        assert_eq!(result, vec![DataValue::I64(expected)]);
+    }
+}
+
+#[test]
+fn host_function_traps() {
+    let trapping = HostFunction::new(unary_signature(), |_| Err(TrapCode::User(42)));
+    let mut module = module(vec![("double", trapping)]);
+    let main = define_main(&mut module);
+
+    let trap = module
+        .call(main, &[DataValue::I64(10)])
+        .unwrap()
+        .unwrap_trap();
+    assert_eq!(trap, CraneliftTrap::User(TrapCode::User(42)));
+}
+
+#[test]
+fn missing_host_function() {
+    let mut module = module(vec![]);
+    let main = define_main(&mut module);
+
+    assert!(module.call(main, &[DataValue::I64(10)]).is_err());
+}
+
+#[test]
+fn error_on_define_function_bytes() {
+    let mut module = module(vec![]);
+    let sig = unary_signature();
+    let func_id = module
+        .declare_function("native", Linkage::Local, &sig)
+        .unwrap();
+    let func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+
+    module
+        .define_function_bytes(func_id, &func, 16, &[0xc3], &[])
+        .err()
+        .unwrap();
+}
@@ -71,6 +71,7 @@
 //!   <https://github.com/bytecodealliance/cranelift/pull/1236>
 //!     ("Relax verification to allow I8X16 to act as a default vector type")
 
+mod bounds_check_elimination;
 mod bounds_checks;
 
 use super::{hash_map, HashMap};
@@ -97,6 +98,8 @@ use std::convert::TryFrom;
 use std::vec::Vec;
 use wasmparser::{FuncValidator, MemArg, Operator, WasmModuleResources};
 
+pub(crate) use bounds_check_elimination::{eliminate_redundant_bounds_checks, BoundsCheck};
+
 /// Given a `Reachability<T>`, unwrap the inner `T` or, when unreachable, set
 /// `state.reachable = false` and return.
 ///
@@ -675,6 +678,55 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state.popn(num_args);
             state.pushn(inst_results);
         }
+        /******************************* Tail calls ******************************************
+         * The tail call instructions pop off their arguments from the stack and transfer
+         * control to the callee, which returns directly to the caller of the current function.
+         ************************************************************************************/
+        Operator::ReturnCall { function_index } => {
+            let (fref, num_args) = state.get_direct_func(builder.func, *function_index, environ)?;
+
+            // Bitcast any vector arguments to their default type, I8X16, before calling.
+            let args = state.peekn_mut(num_args);
+            bitcast_wasm_params(
+                environ,
+                builder.func.dfg.ext_funcs[fref].signature,
+                args,
+                builder,
+            );
+
+            environ.translate_return_call(
+                builder,
+                FuncIndex::from_u32(*function_index),
+                fref,
+                args,
+            )?;
+            state.popn(num_args);
+            state.reachable = false;
+        }
+        Operator::ReturnCallIndirect {
+            type_index,
+            table_index,
+        } => {
+            let (sigref, num_args) = state.get_indirect_sig(builder.func, *type_index, environ)?;
+            let table = state.get_or_create_table(builder.func, *table_index, environ)?;
+            let callee = state.pop1();
+
+            // Bitcast any vector arguments to their default type, I8X16, before calling.
+            let args = state.peekn_mut(num_args);
+            bitcast_wasm_params(environ, sigref, args, builder);
+
+            environ.translate_return_call_indirect(
+                builder,
+                TableIndex::from_u32(*table_index),
+                table,
+                TypeIndex::from_u32(*type_index),
+                sigref,
+                callee,
+                state.peekn(num_args),
+            )?;
+            state.popn(num_args);
+            state.reachable = false;
+        }
         /******************************* Memory management ***********************************
          * Memory management is handled by environment. It is usually translated into calls to
          * special functions.
@@ -2157,9 +2209,6 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let b_high = builder.ins().uwiden_high(b);
             state.push1(builder.ins().imul(a_high, b_high));
         }
-        Operator::ReturnCall { .. } | Operator::ReturnCallIndirect { .. } => {
-            return Err(wasm_unsupported!("proposed tail-call operator {:?}", op));
-        }
         Operator::MemoryDiscard { .. } => {
             return Err(wasm_unsupported!(
                 "proposed memory-control operator {:?}",
@@ -2596,6 +2645,7 @@ where
             index,
             offset,
             access_size,
+            &mut state.bounds_checks,
         )?,
 
         // If the offset doesn't fit within a u32, then we can't pass it
@@ -2637,6 +2687,7 @@ where
                 adjusted_index,
                 0,
                 access_size,
+                &mut state.bounds_checks,
             )?
         }
     };
@@ -0,0 +1,428 @@
+//! Removal of redundant explicit bounds checks.
+//!
+//! Every Wasm load and store that can't rely on guard pages alone gets its own
+//! explicit bounds check from `bounds_checks.rs`. Many of those checks are
+//! redundant: accessing several fields of a struct checks the same index
+//! against the same bound over and over, and a loop walking over an array
+//! checks indices whose range is already known from the loop's exit
+//! condition. This matters most for 64-bit memories, which always use explicit
+//! checks.
+//!
+//! Once a function is fully translated, we remove the check of every access
+//! that is proven to be in bounds, either
+//!
+//! * by a dominating check of the same index against the same bound, which
+//!   covers at least as many bytes past the index (counting the guard region),
+//!   or
+//!
+//! * by an upper bound on the index, derived from constants, masks, shifts and
+//!   the conditions of dominating branches, such as loop exit conditions,
+//!   together with the heap's minimum size.
+//!
+//! Wasm memories never shrink, so a check that passed remains true for the
+//! rest of the function, even across `memory.grow`.
+//!
+//! When Spectre mitigations are enabled the check is a `select_spectre_guard`
+//! and must stay, since speculative execution can bypass a dominating check
+//! or a mispredicted branch. The `select_spectre_guard` of a covered access
+//! instead reuses the dominating check's out-of-bounds condition, which is
+//! computed from the same index and bound, so that its own comparison (and
+//! overflow check) goes away. Branch conditions are never used in that case.
+
+use crate::{HashMap, HeapData, HeapStyle};
+use cranelift_codegen::dominator_tree::DominatorTree;
+use cranelift_codegen::flowgraph::{BlockPredecessor, ControlFlowGraph};
+use cranelift_codegen::ir::{
+    self,
+    condcodes::{CondCode, IntCC},
+    Block, Inst, InstBuilder, InstructionData, Opcode, Value, ValueDef,
+};
+use std::vec::Vec;
+
+/// The maximum number of steps taken to bound a single index, which keeps
+/// this pass linear in the number of checks.
+const MAX_STEPS: usize = 64;
+
+/// An explicit bounds check emitted by `bounds_check_and_compute_addr`.
+pub(crate) struct BoundsCheck {
+    /// The heap being accessed.
+    pub heap: HeapData,
+    /// The Wasm index operand, before its extension to the pointer type.
+    pub index: Value,
+    /// The number of bytes past `index` that the access touches, i.e. its
+    /// `offset + access_size`.
+    pub offset_and_size: u64,
+    /// Execution only continues past this check if `index + proven <= bound`.
+    pub proven: u64,
+    /// The condition that is true when the access is out of bounds.
+    pub oob_condition: Value,
+    /// The `trapnz` or `select_spectre_guard` consuming `oob_condition`.
+    pub check: Inst,
+    /// The `uadd_overflow_trap` computing `index + offset_and_size`, if any.
+    pub overflow_check: Option<Inst>,
+}
+
+impl BoundsCheck {
+    /// Does passing this check guarantee that `other` would pass too, assuming
+    /// that both check the same index?
+    fn covers(&self, other: &BoundsCheck) -> bool {
+        self.heap.style == other.heap.style
+            && other.offset_and_size <= self.proven.saturating_add(other.heap.offset_guard_size)
+    }
+
+    /// Is the access in bounds, or within the guard region, whenever `index`
+    /// is at most `max_index`?
+    fn in_bounds(&self, max_index: u64) -> bool {
+        let min_bound = match self.heap.style {
+            HeapStyle::Dynamic { .. } => self.heap.min_size,
+            HeapStyle::Static { bound } => bound,
+        };
+        max_index
+            .checked_add(self.offset_and_size)
+            .map_or(false, |end| {
+                end <= min_bound.saturating_add(self.heap.offset_guard_size)
+            })
+    }
+}
+
+/// Remove the checks in `checks` that are implied by other checks or by the
+/// range of their index, after `func` has been fully translated.
+pub(crate) fn eliminate_redundant_bounds_checks(func: &mut ir::Function, checks: &[BoundsCheck]) {
+    if checks.is_empty() {
+        return;
+    }
+    let cfg = ControlFlowGraph::with_function(func);
+    let domtree = DominatorTree::with_function(func, &cfg);
+
+    // The checks that stay, by index, and the redundant ones along with the
+    // check that covers them, if any.
+    let mut kept: HashMap<Value, Vec<&BoundsCheck>> = HashMap::new();
+    let mut redundant = Vec::new();
+    let mut ranges = IndexRanges {
+        func,
+        cfg: &cfg,
+        domtree: &domtree,
+        visiting: Vec::new(),
+        steps: 0,
+    };
+
+    for check in checks {
+        let block = match func.layout.inst_block(check.check) {
+            Some(block) if domtree.is_reachable(block) => block,
+            _ => continue,
+        };
+        let index = func.dfg.resolve_aliases(check.index);
+
+        let covering = kept.get(&index).and_then(|candidates| {
+            candidates
+                .iter()
+                .find(|c| c.covers(check) && domtree.dominates(c.check, check.check, &func.layout))
+                .copied()
+        });
+        if covering.is_some() {
+            redundant.push((check, covering));
+            continue;
+        }
+
+        if func.dfg.insts[check.check].opcode() == Opcode::Trapnz {
+            ranges.steps = 0;
+            if check.in_bounds(ranges.upper_bound(index, block)) {
+                redundant.push((check, None));
+                continue;
+            }
+        }
+
+        kept.entry(index).or_default().push(check);
+    }
+
+    for (check, covering) in redundant {
+        match covering {
+            Some(covering) if func.dfg.insts[check.check].opcode() != Opcode::Trapnz => {
+                func.dfg.inst_args_mut(check.check)[0] = covering.oob_condition;
+            }
+            _ => func.layout.remove_inst(check.check),
+        }
+
+        // The addition can't overflow anymore, since the access is known to
+        // end within the heap's bound and guard region.
+        if let Some(inst) = check.overflow_check {
+            let args = func.dfg.inst_args(inst);
+            let (x, y) = (args[0], args[1]);
+            func.dfg.replace(inst).iadd(x, y);
+        }
+    }
+}
+
+/// Upper bounds on the unsigned values of integers.
+struct IndexRanges<'a> {
+    func: &'a ir::Function,
+    cfg: &'a ControlFlowGraph,
+    domtree: &'a DominatorTree,
+    /// The block parameters whose bounds are being computed, which are assumed
+    /// to be unbounded when reached again through a loop.
+    visiting: Vec<Value>,
+    /// The number of steps taken so far for the current index.
+    steps: usize,
+}
+
+impl IndexRanges<'_> {
+    /// Returns an upper bound on the unsigned value of `value` anywhere in
+    /// `block`.
+    fn upper_bound(&mut self, value: Value, block: Block) -> u64 {
+        let dfg = &self.func.dfg;
+        let value = dfg.resolve_aliases(value);
+        let ty = dfg.value_type(value);
+        if !ty.is_int() || ty.bits() > 64 {
+            return u64::MAX;
+        }
+        let max = u64::MAX >> (64 - ty.bits());
+        if self.steps >= MAX_STEPS {
+            return max;
+        }
+        self.steps += 1;
+
+        let defined = self.defined_upper_bound(value, max, block);
+        if defined == 0 {
+            return defined;
+        }
+        defined.min(self.dominating_conditions_upper_bound(value, block))
+    }
+
+    /// Returns an upper bound on `value` derived from its definition.
+    fn defined_upper_bound(&mut self, value: Value, max: u64, block: Block) -> u64 {
+        let func = self.func;
+        let dfg = &func.dfg;
+        let inst = match dfg.value_def(value) {
+            ValueDef::Result(inst, 0) => inst,
+            ValueDef::Param(param_block, _) => {
+                return self.param_upper_bound(value, param_block, max);
+            }
+            _ => return max,
+        };
+        match dfg.insts[inst] {
+            InstructionData::UnaryImm {
+                opcode: Opcode::Iconst,
+                imm,
+            } => imm.bits() as u64 & max,
+            InstructionData::Unary {
+                opcode: Opcode::Uextend,
+                arg,
+            } => self.upper_bound(arg, block),
+            InstructionData::BinaryImm64 {
+                opcode: Opcode::BandImm,
+                arg,
+                imm,
+            } => (imm.bits() as u64 & max).min(self.upper_bound(arg, block)),
+            InstructionData::BinaryImm64 {
+                opcode: Opcode::IaddImm,
+                arg,
+                imm,
+            } if imm.bits() >= 0 => self
+                .upper_bound(arg, block)
+                .checked_add(imm.bits() as u64)
+                .filter(|b| *b <= max)
+                .unwrap_or(max),
+            InstructionData::Binary {
+                opcode,
+                args: [x, y],
+            } => {
+                let x = self.upper_bound(x, block);
+                match opcode {
+                    Opcode::Band => x.min(self.upper_bound(y, block)),
+                    Opcode::Ushr | Opcode::Udiv => x,
+                    Opcode::Urem => x.min(self.upper_bound(y, block).saturating_sub(1)),
+                    Opcode::Iadd => x
+                        .checked_add(self.upper_bound(y, block))
+                        .filter(|b| *b <= max)
+                        .unwrap_or(max),
+                    Opcode::Imul => x
+                        .checked_mul(self.upper_bound(y, block))
+                        .filter(|b| *b <= max)
+                        .unwrap_or(max),
+                    Opcode::Ishl => {
+                        let shift = self.upper_bound(y, block);
+                        if shift < u64::from(dfg.value_type(value).bits()) && x <= max >> shift {
+                            x << shift
+                        } else {
+                            max
+                        }
+                    }
+                    _ => max,
+                }
+            }
+            InstructionData::Ternary {
+                opcode: Opcode::Select,
+                args: [_, x, y],
+            } => self.upper_bound(x, block).max(self.upper_bound(y, block)),
+            _ => max,
+        }
+    }
+
+    /// Returns an upper bound on the block parameter `param`, from the
+    /// arguments passed to it by all of its block's predecessors.
+    fn param_upper_bound(&mut self, param: Value, block: Block, max: u64) -> u64 {
+        let func = self.func;
+        if func.layout.entry_block() == Some(block) || self.visiting.contains(&param) {
+            return max;
+        }
+        let position = func
+            .dfg
+            .block_params(block)
+            .iter()
+            .position(|p| *p == param)
+            .unwrap();
+
+        self.visiting.push(param);
+        let mut bound = 0;
+        for BlockPredecessor {
+            block: pred,
+            inst: branch,
+        } in self.cfg.pred_iter(block)
+        {
+            let destinations = func.dfg.insts[branch].branch_destination(&func.dfg.jump_tables);
+            for (i, call) in destinations.iter().enumerate() {
+                if call.block(&func.dfg.value_lists) != block {
+                    continue;
+                }
+                let arg = call.args_slice(&func.dfg.value_lists)[position];
+                let mut arg_bound = self.upper_bound(arg, pred);
+                if let Some(edge_bound) = self.edge_upper_bound(branch, i, arg, pred) {
+                    arg_bound = arg_bound.min(edge_bound);
+                }
+                bound = bound.max(arg_bound);
+            }
+            if bound >= max {
+                break;
+            }
+        }
+        self.visiting.pop();
+        bound.min(max)
+    }
+
+    /// Returns an upper bound on `value` from the conditions of the branches
+    /// that must be taken to reach `block`.
+    fn dominating_conditions_upper_bound(&mut self, value: Value, block: Block) -> u64 {
+        let func = self.func;
+        let mut bound = u64::MAX;
+        let mut block = block;
+        while self.steps < MAX_STEPS {
+            self.steps += 1;
+
+            // A block with a single incoming edge is only reached when that
+            // edge's condition holds.
+            let mut preds = self.cfg.pred_iter(block);
+            if let (Some(pred), None) = (preds.next(), preds.next()) {
+                let destinations =
+                    func.dfg.insts[pred.inst].branch_destination(&func.dfg.jump_tables);
+                let mut edges = destinations
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, call)| call.block(&func.dfg.value_lists) == block);
+                if let (Some((i, _)), None) = (edges.next(), edges.next()) {
+                    if let Some(edge_bound) = self.edge_upper_bound(pred.inst, i, value, pred.block)
+                    {
+                        bound = bound.min(edge_bound);
+                    }
+                }
+            }
+
+            match self.domtree.idom(block) {
+                Some(idom) => block = func.layout.inst_block(idom).unwrap(),
+                None => break,
+            }
+        }
+        bound
+    }
+
+    /// Returns an upper bound on `value` when the `destination`th edge of
+    /// `branch` is taken, if the branch's condition compares `value`.
+    fn edge_upper_bound(
+        &mut self,
+        branch: Inst,
+        destination: usize,
+        value: Value,
+        block: Block,
+    ) -> Option<u64> {
+        let condition = match self.func.dfg.insts[branch] {
+            InstructionData::Brif { arg, .. } => arg,
+            _ => return None,
+        };
+        let (cond, lhs, rhs) = self.comparison(condition)?;
+        let cond = if destination == 0 {
+            cond
+        } else {
+            cond.inverse()
+        };
+
+        // Put `value` on the left-hand side.
+        let value = self.func.dfg.resolve_aliases(value);
+        let (cond, rhs) = if lhs == value {
+            (cond, rhs)
+        } else if rhs == Operand::Value(value) {
+            (cond.reverse(), Operand::Value(lhs))
+        } else {
+            return None;
+        };
+        let rhs = match rhs {
+            Operand::Value(rhs) => self.upper_bound(rhs, block),
+            Operand::Imm(imm) => imm,
+        };
+        match cond {
+            IntCC::UnsignedLessThan => Some(rhs.saturating_sub(1)),
+            IntCC::UnsignedLessThanOrEqual | IntCC::Equal => Some(rhs),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer comparison computing `condition`, looking through
+    /// the extensions and `eqz`s that Wasm comparisons are translated into.
+    fn comparison(&self, condition: Value) -> Option<(IntCC, Value, Operand)> {
+        let dfg = &self.func.dfg;
+        let condition = dfg.resolve_aliases(condition);
+        let inst = match dfg.value_def(condition) {
+            ValueDef::Result(inst, 0) => inst,
+            _ => return None,
+        };
+        match dfg.insts[inst] {
+            InstructionData::IntCompare {
+                opcode: Opcode::Icmp,
+                cond,
+                args: [x, y],
+            } => Some((
+                cond,
+                dfg.resolve_aliases(x),
+                Operand::Value(dfg.resolve_aliases(y)),
+            )),
+            InstructionData::IntCompareImm {
+                opcode: Opcode::IcmpImm,
+                cond,
+                arg,
+                imm,
+            } if dfg.value_type(arg).bits() <= 64 => match (cond, imm.bits(), self.comparison(arg))
+            {
+                (IntCC::Equal, 0, Some((cond, x, y))) => Some((cond.inverse(), x, y)),
+                (IntCC::NotEqual, 0, Some(comparison)) => Some(comparison),
+                _ => {
+                    let max = u64::MAX >> (64 - dfg.value_type(arg).bits());
+                    Some((
+                        cond,
+                        dfg.resolve_aliases(arg),
+                        Operand::Imm(imm.bits() as u64 & max),
+                    ))
+                }
+            },
+            InstructionData::Unary {
+                opcode: Opcode::Uextend,
+                arg,
+            } => self.comparison(arg),
+            _ => None,
+        }
+    }
+}
+
+/// The right-hand side of a comparison.
+#[derive(PartialEq)]
+enum Operand {
+    Value(Value),
+    Imm(u64),
+}
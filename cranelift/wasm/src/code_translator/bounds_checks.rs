@@ -19,13 +19,14 @@
 //! !!!                                                                      !!!
 //! !!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!
 
-use super::Reachability;
+use super::{BoundsCheck, Reachability};
 use crate::{FuncEnvironment, HeapData, HeapStyle};
 use cranelift_codegen::{
     cursor::{Cursor, FuncCursor},
     ir::{self, condcodes::IntCC, InstBuilder, RelSourceLoc},
 };
 use cranelift_frontend::FunctionBuilder;
+use std::vec::Vec;
 use wasmtime_types::WasmResult;
 use Reachability::*;
 
@@ -34,6 +35,9 @@ use Reachability::*;
 ///
 /// Returns the `ir::Value` holding the native address of the heap access, or
 /// `None` if the heap access will unconditionally trap.
+///
+/// Every explicit check that is emitted is appended to `checks`, so that the
+/// redundant ones can be removed once the whole function is translated.
 pub fn bounds_check_and_compute_addr<Env>(
     builder: &mut FunctionBuilder,
     env: &mut Env,
@@ -44,10 +48,12 @@ pub fn bounds_check_and_compute_addr<Env>(
     offset: u32,
     // Static size of the heap access.
     access_size: u8,
+    checks: &mut Vec<BoundsCheck>,
 ) -> WasmResult<Reachability<ir::Value>>
 where
     Env: FuncEnvironment + ?Sized,
 {
+    let wasm_index = index;
     let index = cast_index_to_pointer_ty(
         index,
         heap.index_type,
@@ -56,6 +62,17 @@ where
     );
     let offset_and_size = offset_plus_size(offset, access_size);
     let spectre_mitigations_enabled = env.heap_access_spectre_mitigation();
+    let mut record = |check, oob_condition, proven, overflow_check| {
+        checks.push(BoundsCheck {
+            heap: heap.clone(),
+            index: wasm_index,
+            offset_and_size,
+            proven,
+            oob_condition,
+            check,
+            overflow_check,
+        })
+    };
 
     // We need to emit code that will trap (or compute an address that will trap
     // when accessed) if
@@ -88,7 +105,7 @@ where
             let oob = builder
                 .ins()
                 .icmp(IntCC::UnsignedGreaterThanOrEqual, index, bound);
-            Reachable(explicit_check_oob_condition_and_compute_addr(
+            let (addr, check) = explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
                 env.pointer_type(),
@@ -96,7 +113,9 @@ where
                 offset,
                 spectre_mitigations_enabled,
                 oob,
-            ))
+            );
+            record(check, oob, offset_and_size, None);
+            Reachable(addr)
         }
 
         // 2. Second special case for when we know that there are enough guard
@@ -127,7 +146,7 @@ where
         HeapStyle::Dynamic { bound_gv } if offset_and_size <= heap.offset_guard_size => {
             let bound = builder.ins().global_value(env.pointer_type(), bound_gv);
             let oob = builder.ins().icmp(IntCC::UnsignedGreaterThan, index, bound);
-            Reachable(explicit_check_oob_condition_and_compute_addr(
+            let (addr, check) = explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
                 env.pointer_type(),
@@ -135,7 +154,9 @@ where
                 offset,
                 spectre_mitigations_enabled,
                 oob,
-            ))
+            );
+            record(check, oob, 0, None);
+            Reachable(addr)
         }
 
         // 3. Third special case for when `offset + access_size <= min_size`.
@@ -151,7 +172,7 @@ where
             let oob = builder
                 .ins()
                 .icmp(IntCC::UnsignedGreaterThan, index, adjusted_bound);
-            Reachable(explicit_check_oob_condition_and_compute_addr(
+            let (addr, check) = explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
                 env.pointer_type(),
@@ -159,7 +180,9 @@ where
                 offset,
                 spectre_mitigations_enabled,
                 oob,
-            ))
+            );
+            record(check, oob, offset_and_size, None);
+            Reachable(addr)
         }
 
         // 4. General case for dynamic memories:
@@ -176,11 +199,12 @@ where
                 access_size_val,
                 ir::TrapCode::HeapOutOfBounds,
             );
+            let overflow_check = builder.func.dfg.value_def(adjusted_index).unwrap_inst();
             let bound = builder.ins().global_value(env.pointer_type(), bound_gv);
             let oob = builder
                 .ins()
                 .icmp(IntCC::UnsignedGreaterThan, adjusted_index, bound);
-            Reachable(explicit_check_oob_condition_and_compute_addr(
+            let (addr, check) = explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
                 env.pointer_type(),
@@ -188,7 +212,9 @@ where
                 offset,
                 spectre_mitigations_enabled,
                 oob,
-            ))
+            );
+            record(check, oob, offset_and_size, Some(overflow_check));
+            Reachable(addr)
         }
 
         // ====== Static Memories ======
@@ -276,7 +302,7 @@ where
                 builder
                     .ins()
                     .icmp_imm(IntCC::UnsignedGreaterThan, index, adjusted_bound as i64);
-            Reachable(explicit_check_oob_condition_and_compute_addr(
+            let (addr, check) = explicit_check_oob_condition_and_compute_addr(
                 &mut builder.cursor(),
                 heap,
                 env.pointer_type(),
@@ -284,7 +310,9 @@ where
                 offset,
                 spectre_mitigations_enabled,
                 oob,
-            ))
+            );
+            record(check, oob, offset_and_size, None);
+            Reachable(addr)
         }
     })
 }
@@ -320,7 +348,8 @@ fn cast_index_to_pointer_ty(
 }
 
 /// Emit explicit checks on the given out-of-bounds condition for the Wasm
-/// address and return the native address.
+/// address and return the native address, along with the `trapnz` or
+/// `select_spectre_guard` instruction that consumes the condition.
 ///
 /// This function deduplicates explicit bounds checks and Spectre mitigations
 /// that inherently also implement bounds checking.
@@ -336,20 +365,19 @@ fn explicit_check_oob_condition_and_compute_addr(
     // bounds (and therefore we should trap) and is zero when the heap access is
     // in bounds (and therefore we can proceed).
     oob_condition: ir::Value,
-) -> ir::Value {
+) -> (ir::Value, ir::Inst) {
     if !spectre_mitigations_enabled {
-        pos.ins()
+        let check = pos
+            .ins()
             .trapnz(oob_condition, ir::TrapCode::HeapOutOfBounds);
+        let addr = compute_addr(pos, heap, addr_ty, index, offset);
+        return (addr, check);
     }
 
-    let mut addr = compute_addr(pos, heap, addr_ty, index, offset);
-
-    if spectre_mitigations_enabled {
-        let null = pos.ins().iconst(addr_ty, 0);
-        addr = pos.ins().select_spectre_guard(oob_condition, null, addr);
-    }
-
-    addr
+    let addr = compute_addr(pos, heap, addr_ty, index, offset);
+    let null = pos.ins().iconst(addr_ty, 0);
+    let addr = pos.ins().select_spectre_guard(oob_condition, null, addr);
+    (addr, pos.func.dfg.value_def(addr).unwrap_inst())
 }
 
 /// Emit code for the native address computation of a Wasm address,
@@ -208,6 +208,41 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(pos.ins().call(callee, call_args))
     }
 
+    /// Translate a `return_call_indirect` WebAssembly instruction at the
+    /// builder's current position.
+    ///
+    /// This is the same as `translate_call_indirect`, except that the call must
+    /// be a tail call (`return_call_indirect`) whose results are returned
+    /// directly to the current function's caller.
+    #[allow(clippy::too_many_arguments)]
+    fn translate_return_call_indirect(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        table_index: TableIndex,
+        table: ir::Table,
+        sig_index: TypeIndex,
+        sig_ref: ir::SigRef,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()>;
+
+    /// Translate a `return_call` WebAssembly instruction at the builder's
+    /// current position.
+    ///
+    /// This is the same as `translate_call`, except that the call must be a
+    /// tail call (`return_call`) whose results are returned directly to the
+    /// current function's caller.
+    fn translate_return_call(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        _callee_index: FuncIndex,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        builder.ins().return_call(callee, call_args);
+        Ok(())
+    }
+
     /// Translate a `memory.grow` WebAssembly instruction.
     ///
     /// The `index` provided identifies the linear memory to grow, and `heap` is the heap reference
@@ -546,6 +581,13 @@ pub trait FuncEnvironment: TargetEnvironment {
     fn is_x86(&self) -> bool {
         false
     }
+
+    /// Whether to remove the explicit bounds checks of heap accesses that are
+    /// already proven to be in bounds, by a dominating check or by the range of
+    /// their index, once a function is translated.
+    fn eliminate_redundant_bounds_checks(&self) -> bool {
+        false
+    }
 }
 
 /// An object satisfying the `ModuleEnvironment` trait can be passed as argument to the
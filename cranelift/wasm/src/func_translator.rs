@@ -4,7 +4,9 @@
 //! function to Cranelift IR guided by a `FuncEnvironment` which provides information about the
 //! WebAssembly module and the runtime environment.
 
-use crate::code_translator::{bitcast_wasm_returns, translate_operator};
+use crate::code_translator::{
+    bitcast_wasm_returns, eliminate_redundant_bounds_checks, translate_operator,
+};
 use crate::environ::FuncEnvironment;
 use crate::state::FuncTranslationState;
 use crate::translation_utils::get_vmctx_value_label;
@@ -117,6 +119,9 @@ impl FuncTranslator {
         parse_function_body(validator, reader, &mut builder, &mut self.state, environ)?;
 
         builder.finalize();
+        if environ.eliminate_redundant_bounds_checks() {
+            eliminate_redundant_bounds_checks(func, &self.state.bounds_checks);
+        }
         log::trace!("translated Wasm to CLIF:\n{}", func.display());
         Ok(())
     }
@@ -3,6 +3,7 @@
 //! The `FuncTranslationState` struct defined in this module is used to keep track of the WebAssembly
 //! value and control stacks during the translation of a single function.
 
+use crate::code_translator::BoundsCheck;
 use crate::environ::{FuncEnvironment, GlobalVariable};
 use crate::{FuncIndex, GlobalIndex, Heap, MemoryIndex, TableIndex, TypeIndex, WasmResult};
 use crate::{HashMap, Occupied, Vacant};
@@ -241,6 +242,9 @@ pub struct FuncTranslationState {
     // `FuncEnvironment::make_direct_func()`.
     // Stores both the function reference and the number of WebAssembly arguments
     functions: HashMap<FuncIndex, (ir::FuncRef, usize)>,
+
+    /// The explicit bounds checks emitted so far, in translation order.
+    pub(crate) bounds_checks: Vec<BoundsCheck>,
 }
 
 // Public methods that are exposed to non-`cranelift_wasm` API consumers.
@@ -250,6 +254,12 @@ impl FuncTranslationState {
     pub fn reachable(&self) -> bool {
         self.reachable
     }
+
+    /// The values currently on the WebAssembly value stack, from the bottom
+    /// of the stack to its top.
+    pub fn value_stack(&self) -> &[Value] {
+        &self.stack
+    }
 }
 
 impl FuncTranslationState {
@@ -264,6 +274,7 @@ impl FuncTranslationState {
             tables: HashMap::new(),
             signatures: HashMap::new(),
             functions: HashMap::new(),
+            bounds_checks: Vec::new(),
         }
     }
 
@@ -276,6 +287,7 @@ impl FuncTranslationState {
         self.tables.clear();
         self.signatures.clear();
         self.functions.clear();
+        self.bounds_checks.clear();
     }
 
     /// Initialize the state for compiling a function with the given signature.
@@ -0,0 +1,611 @@
+/**
+ * \file wasmtime/component.h
+ *
+ * Wasmtime APIs for compiling, linking and instantiating components of the
+ * WebAssembly component model.
+ *
+ * These APIs are only available when the C API is built with the
+ * `component-model` Cargo feature, which is enabled by default. Components
+ * additionally require #wasmtime_config_wasm_component_model_set to be enabled
+ * on the #wasm_config_t used to create the engine.
+ *
+ * ### Values and ownership
+ *
+ * Values passed to and from components are represented with
+ * #wasmtime_component_val_t. A value always owns everything it points to:
+ * strings, lists, records, flags, and the payloads of variants, unions,
+ * options and results. Deleting a value with #wasmtime_component_val_delete
+ * recursively deallocates all of this.
+ *
+ * Since these contents are deallocated by Wasmtime they must also be allocated
+ * by Wasmtime. Strings and names are created with #wasm_name_new (or
+ * #wasm_byte_vec_new), vectors with the `*_new` and `*_new_uninitialized`
+ * functions below, and boxed payloads with #wasmtime_component_val_new. Memory
+ * from `malloc` or elsewhere must never be placed in a value. Note that the
+ * `*_new` vector constructors take ownership of the elements they are given.
+ *
+ * Unlike `wasmtime::component::Val` in Rust these values carry no type
+ * information. When a value is passed to a component it's checked against,
+ * and converted to, the type that the component expects. Record fields are
+ * matched up by name, and all names must be valid UTF-8.
+ */
+
+#ifndef WASMTIME_COMPONENT_H
+#define WASMTIME_COMPONENT_H
+
+#include <wasm.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// \brief Discriminant used in #wasmtime_component_val_t::kind
+typedef uint8_t wasmtime_component_valkind_t;
+
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `bool`
+#define WASMTIME_COMPONENT_BOOL 0
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `s8`
+#define WASMTIME_COMPONENT_S8 1
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `u8`
+#define WASMTIME_COMPONENT_U8 2
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `s16`
+#define WASMTIME_COMPONENT_S16 3
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `u16`
+#define WASMTIME_COMPONENT_U16 4
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `s32`
+#define WASMTIME_COMPONENT_S32 5
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `u32`
+#define WASMTIME_COMPONENT_U32 6
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `s64`
+#define WASMTIME_COMPONENT_S64 7
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `u64`
+#define WASMTIME_COMPONENT_U64 8
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `float32`
+#define WASMTIME_COMPONENT_FLOAT32 9
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `float64`
+#define WASMTIME_COMPONENT_FLOAT64 10
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `char`
+#define WASMTIME_COMPONENT_CHAR 11
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `string`
+#define WASMTIME_COMPONENT_STRING 12
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `list`
+#define WASMTIME_COMPONENT_LIST 13
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `record`
+#define WASMTIME_COMPONENT_RECORD 14
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `tuple`
+#define WASMTIME_COMPONENT_TUPLE 15
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `variant`
+#define WASMTIME_COMPONENT_VARIANT 16
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is an `enum`
+#define WASMTIME_COMPONENT_ENUM 17
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `union`
+#define WASMTIME_COMPONENT_UNION 18
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is an `option`
+#define WASMTIME_COMPONENT_OPTION 19
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `result`
+#define WASMTIME_COMPONENT_RESULT 20
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a `flags`
+#define WASMTIME_COMPONENT_FLAGS 21
+
+typedef struct wasmtime_component_val wasmtime_component_val_t;
+typedef struct wasmtime_component_valrecord_field wasmtime_component_valrecord_field_t;
+
+/// \brief An owned vector of #wasmtime_component_val_t, used for lists and
+/// tuples.
+typedef struct wasmtime_component_val_vec {
+  /// Length of this vector.
+  size_t size;
+  /// Pointer to the base of this vector.
+  wasmtime_component_val_t *data;
+} wasmtime_component_val_vec_t;
+
+/// \brief An owned vector of #wasmtime_component_valrecord_field_t, used for
+/// records.
+typedef struct wasmtime_component_valrecord {
+  /// Length of this vector.
+  size_t size;
+  /// Pointer to the base of this vector.
+  wasmtime_component_valrecord_field_t *data;
+} wasmtime_component_valrecord_t;
+
+/// \brief An owned vector of the names of the flags which are set in a
+/// `flags` value.
+typedef struct wasmtime_component_valflags {
+  /// Length of this vector.
+  size_t size;
+  /// Pointer to the base of this vector.
+  wasm_name_t *data;
+} wasmtime_component_valflags_t;
+
+/// \brief Payload of a `variant` value.
+typedef struct wasmtime_component_valvariant {
+  /// The name of this variant's case.
+  wasm_name_t discriminant;
+  /// The owned payload of this case, or `NULL` if the case has no payload.
+  wasmtime_component_val_t *val;
+} wasmtime_component_valvariant_t;
+
+/// \brief Payload of a `union` value.
+typedef struct wasmtime_component_valunioncase {
+  /// The index of this union's case.
+  uint32_t discriminant;
+  /// The owned payload of this case, which must not be `NULL`.
+  wasmtime_component_val_t *val;
+} wasmtime_component_valunioncase_t;
+
+/// \brief Payload of a `result` value.
+typedef struct wasmtime_component_valresult {
+  /// Whether this is the `err` case of the result.
+  bool is_err;
+  /// The owned payload of this case, or `NULL` if the case has no payload.
+  wasmtime_component_val_t *val;
+} wasmtime_component_valresult_t;
+
+/**
+ * \typedef wasmtime_component_valunion_t
+ * \brief Convenience alias for #wasmtime_component_valunion
+ *
+ * \union wasmtime_component_valunion
+ * \brief Container for the different kinds of component values.
+ *
+ * Which field is valid is determined by #wasmtime_component_val_t::kind.
+ */
+typedef union wasmtime_component_valunion {
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_BOOL
+  bool boolean;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S8
+  int8_t s8;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U8
+  uint8_t u8;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S16
+  int16_t s16;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U16
+  uint16_t u16;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S32
+  int32_t s32;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U32
+  uint32_t u32;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_S64
+  int64_t s64;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_U64
+  uint64_t u64;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_FLOAT32
+  float32_t float32;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_FLOAT64
+  float64_t float64;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_CHAR,
+  /// which must be a Unicode scalar value.
+  uint32_t character;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_STRING, which must be valid UTF-8.
+  wasm_name_t string;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_LIST
+  wasmtime_component_val_vec_t list;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_RECORD
+  wasmtime_component_valrecord_t record;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_TUPLE
+  wasmtime_component_val_vec_t tuple;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_VARIANT
+  wasmtime_component_valvariant_t variant;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_ENUM,
+  /// holding the name of the enum's case.
+  wasm_name_t enumeration;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_UNION
+  wasmtime_component_valunioncase_t union_;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_OPTION, which is the owned payload of `some` or `NULL`
+  /// for `none`.
+  wasmtime_component_val_t *option;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_RESULT
+  wasmtime_component_valresult_t result;
+  /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_FLAGS
+  wasmtime_component_valflags_t flags;
+} wasmtime_component_valunion_t;
+
+/**
+ * \typedef wasmtime_component_val_t
+ * \brief Convenience alias for #wasmtime_component_val
+ *
+ * \struct wasmtime_component_val
+ * \brief A value passed to or returned from a component function.
+ *
+ * See the documentation at the top of this file for the ownership rules of
+ * these values.
+ */
+struct wasmtime_component_val {
+  /// Discriminant of which field of #of is valid.
+  wasmtime_component_valkind_t kind;
+  /// Container for the value itself.
+  wasmtime_component_valunion_t of;
+};
+
+/**
+ * \brief A field of a `record` value.
+ */
+struct wasmtime_component_valrecord_field {
+  /// The name of this field.
+  wasm_name_t name;
+  /// The value of this field.
+  wasmtime_component_val_t val;
+};
+
+/**
+ * \brief Allocates a new value on the heap, initialized to the `bool` `false`.
+ *
+ * This is used to create the payloads of variants, unions, options and
+ * results. The returned value is owned by the caller until it's placed into
+ * another value, and can otherwise be deallocated with
+ * #wasmtime_component_val_free.
+ */
+WASM_API_EXTERN wasmtime_component_val_t *wasmtime_component_val_new(void);
+
+/**
+ * \brief Deallocates a value returned by #wasmtime_component_val_new,
+ * including its contents.
+ */
+WASM_API_EXTERN void wasmtime_component_val_free(wasmtime_component_val_t *val);
+
+/**
+ * \brief Deallocates the contents of `val`, leaving it as the `bool` `false`.
+ *
+ * This does not deallocate `val` itself, which may live on the stack.
+ */
+WASM_API_EXTERN void wasmtime_component_val_delete(wasmtime_component_val_t *val);
+
+/**
+ * \brief Performs a deep copy of `src` into `dst`.
+ *
+ * `dst` is assumed to be uninitialized and is overwritten without deleting
+ * its previous contents.
+ */
+WASM_API_EXTERN void wasmtime_component_val_copy(
+    wasmtime_component_val_t *dst,
+    const wasmtime_component_val_t *src
+);
+
+/// \brief Creates an empty vector.
+WASM_API_EXTERN void wasmtime_component_val_vec_new_empty(wasmtime_component_val_vec_t *out);
+/// \brief Creates a vector of `size` values, each the `bool` `false`.
+WASM_API_EXTERN void wasmtime_component_val_vec_new_uninitialized(wasmtime_component_val_vec_t *out, size_t size);
+/// \brief Creates a vector, taking ownership of the `size` values at `ptr`.
+WASM_API_EXTERN void wasmtime_component_val_vec_new(wasmtime_component_val_vec_t *out, size_t size, const wasmtime_component_val_t *ptr);
+/// \brief Performs a deep copy of `src` into `out`.
+WASM_API_EXTERN void wasmtime_component_val_vec_copy(wasmtime_component_val_vec_t *out, const wasmtime_component_val_vec_t *src);
+/// \brief Deallocates a vector and all of its elements.
+WASM_API_EXTERN void wasmtime_component_val_vec_delete(wasmtime_component_val_vec_t *vec);
+
+/// \brief Creates an empty record.
+WASM_API_EXTERN void wasmtime_component_valrecord_new_empty(wasmtime_component_valrecord_t *out);
+/// \brief Creates a record of `size` fields with empty names and `false`
+/// values.
+WASM_API_EXTERN void wasmtime_component_valrecord_new_uninitialized(wasmtime_component_valrecord_t *out, size_t size);
+/// \brief Creates a record, taking ownership of the `size` fields at `ptr`.
+WASM_API_EXTERN void wasmtime_component_valrecord_new(wasmtime_component_valrecord_t *out, size_t size, const wasmtime_component_valrecord_field_t *ptr);
+/// \brief Performs a deep copy of `src` into `out`.
+WASM_API_EXTERN void wasmtime_component_valrecord_copy(wasmtime_component_valrecord_t *out, const wasmtime_component_valrecord_t *src);
+/// \brief Deallocates a record and all of its fields.
+WASM_API_EXTERN void wasmtime_component_valrecord_delete(wasmtime_component_valrecord_t *vec);
+
+/// \brief Creates an empty set of flags.
+WASM_API_EXTERN void wasmtime_component_valflags_new_empty(wasmtime_component_valflags_t *out);
+/// \brief Creates a set of `size` empty flag names.
+WASM_API_EXTERN void wasmtime_component_valflags_new_uninitialized(wasmtime_component_valflags_t *out, size_t size);
+/// \brief Creates a set of flags, taking ownership of the `size` names at
+/// `ptr`.
+WASM_API_EXTERN void wasmtime_component_valflags_new(wasmtime_component_valflags_t *out, size_t size, const wasm_name_t *ptr);
+/// \brief Performs a deep copy of `src` into `out`.
+WASM_API_EXTERN void wasmtime_component_valflags_copy(wasmtime_component_valflags_t *out, const wasmtime_component_valflags_t *src);
+/// \brief Deallocates a set of flags and all of their names.
+WASM_API_EXTERN void wasmtime_component_valflags_delete(wasmtime_component_valflags_t *vec);
+
+/**
+ * \typedef wasmtime_component_t
+ * \brief Convenience alias for #wasmtime_component
+ *
+ * \struct wasmtime_component
+ * \brief A compiled Wasmtime component.
+ *
+ * This type represents a compiled WebAssembly component. The compiled
+ * component is ready to be instantiated and can be inspected for imports and
+ * exports. It is also safe to share a component across multiple threads.
+ */
+typedef struct wasmtime_component wasmtime_component_t;
+
+/**
+ * \brief Compiles a WebAssembly binary into a #wasmtime_component_t
+ *
+ * This function will compile a WebAssembly component binary into an owned
+ * #wasmtime_component_t. This performs the same validation and compilation as
+ * #wasmtime_module_new, but for components.
+ *
+ * If an error is returned then `ret` is not written to, otherwise the
+ * caller is expected to delete the returned component with
+ * #wasmtime_component_delete.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_new(
+    const wasm_engine_t *engine,
+    const uint8_t *wasm,
+    size_t wasm_len,
+    wasmtime_component_t **ret
+);
+
+/**
+ * \brief Deletes a component.
+ */
+WASM_API_EXTERN void wasmtime_component_delete(wasmtime_component_t *c);
+
+/**
+ * \brief Creates a shallow clone of the specified component, increasing the
+ * internal reference count.
+ */
+WASM_API_EXTERN wasmtime_component_t *wasmtime_component_clone(wasmtime_component_t *c);
+
+/**
+ * \brief This function serializes compiled component artifacts as blob data.
+ *
+ * \param component the component
+ * \param ret if the conversion is successful, this byte vector is filled in with
+ *   the serialized compiled component.
+ *
+ * \return a non-null error if parsing fails, or returns `NULL`. If parsing
+ * fails then `ret` isn't touched.
+ *
+ * This function does not take ownership of `component`, and the caller is
+ * expected to deallocate the returned #wasmtime_error_t and #wasm_byte_vec_t.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_serialize(
+    wasmtime_component_t *component,
+    wasm_byte_vec_t *ret
+);
+
+/**
+ * \brief Build a component from serialized data.
+ *
+ * This function does not take ownership of any of its arguments, but the
+ * returned error and component are owned by the caller.
+ *
+ * This function is not safe to receive arbitrary user input. See the Rust
+ * documentation for more information on what inputs are safe to pass in here
+ * (e.g. only that of #wasmtime_component_serialize)
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_deserialize(
+    wasm_engine_t *engine,
+    const uint8_t *bytes,
+    size_t bytes_len,
+    wasmtime_component_t **ret
+);
+
+/// \brief Representation of a component instance in Wasmtime.
+///
+/// Instances are represented with a 64-bit identifying integer in Wasmtime.
+/// They do not have any destructor associated with them. Instances cannot
+/// interoperate between #wasmtime_store_t instances and if the wrong instance
+/// is passed to the wrong store then it may trigger an assertion to abort the
+/// process.
+typedef struct wasmtime_component_instance {
+  /// Internal identifier of what store this belongs to, never zero.
+  uint64_t store_id;
+  /// Internal index within the store.
+  size_t index;
+} wasmtime_component_instance_t;
+
+/// \brief Representation of a function exported from a component instance.
+///
+/// Like #wasmtime_component_instance_t these have no destructor and are only
+/// valid within the store that owns them.
+typedef struct wasmtime_component_func {
+  /// Internal identifier of what store this belongs to, never zero.
+  uint64_t store_id;
+  /// Internal index within the store.
+  size_t index;
+} wasmtime_component_func_t;
+
+/**
+ * \brief Callback signature for #wasmtime_component_linker_define_func.
+ *
+ * This is the function signature for host functions imported by components.
+ * The first argument is the `data` provided to
+ * #wasmtime_component_linker_define_func and the second is the store the
+ * calling component lives in.
+ *
+ * The `args` are borrowed and are only valid for the duration of the call.
+ * Each of the `nresults` slots of `results` is initialized to the `bool`
+ * `false`, and the callback must overwrite each one with an owned value
+ * of the function's result type. Ownership of the results is transferred to
+ * Wasmtime when the callback returns.
+ *
+ * If a trap is returned then the results are ignored and the trap is
+ * propagated to the calling component.
+ */
+typedef wasm_trap_t* (*wasmtime_component_func_callback_t)(
+    void *env,
+    wasmtime_context_t *context,
+    const wasmtime_component_val_t *args,
+    size_t nargs,
+    wasmtime_component_val_t *results,
+    size_t nresults);
+
+/**
+ * \typedef wasmtime_component_linker_t
+ * \brief Alias to #wasmtime_component_linker
+ *
+ * \struct #wasmtime_component_linker
+ * \brief Object used to define the imports of components and instantiate
+ * them.
+ *
+ * This type corresponds to the `wasmtime::component::Linker` type in Rust.
+ */
+typedef struct wasmtime_component_linker wasmtime_component_linker_t;
+
+/**
+ * \brief Creates a new component linker for the specified engine.
+ *
+ * This function does not take ownership of the engine argument, and the caller
+ * is expected to delete the returned linker.
+ */
+WASM_API_EXTERN wasmtime_component_linker_t *wasmtime_component_linker_new(const wasm_engine_t *engine);
+
+/**
+ * \brief Deletes a component linker.
+ */
+WASM_API_EXTERN void wasmtime_component_linker_delete(wasmtime_component_linker_t *linker);
+
+/**
+ * \brief Configures whether this linker allows later definitions to shadow
+ * previous definitions.
+ *
+ * By default this setting is `false`.
+ */
+WASM_API_EXTERN void wasmtime_component_linker_allow_shadowing(
+    wasmtime_component_linker_t *linker,
+    bool allow_shadowing
+);
+
+/**
+ * \brief Defines a host function for a function imported by a component.
+ *
+ * \param linker the linker the function is being defined in.
+ * \param component the component whose import `name` determines the type of
+ *   the function being defined.
+ * \param name the name of the function import
+ * \param name_len the byte length of `name`
+ * \param cb the host callback to invoke when the function is called
+ * \param data the host-provided data to provide as the first argument to the callback
+ * \param finalizer an optional finalizer for the `data` argument.
+ *
+ * \return On success `NULL` is returned, otherwise an error is returned which
+ * describes why the definition failed, for example if `component` doesn't
+ * import a function named `name`.
+ *
+ * The function's parameters and results have the types of the import in
+ * `component`, and values returned from `cb` are checked against those types.
+ * The definition may still be used to instantiate other components which
+ * import a function of the same type.
+ *
+ * Only functions imported directly by `component` can be defined, so `name`
+ * is never a path into an imported instance. Functions within imported
+ * instances are not currently supported by this API.
+ *
+ * This function does not take ownership of any of its arguments.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_define_func(
+    wasmtime_component_linker_t *linker,
+    const wasmtime_component_t *component,
+    const char *name,
+    size_t name_len,
+    wasmtime_component_func_callback_t cb,
+    void *data,
+    void (*finalizer)(void*)
+);
+
+/**
+ * \brief Instantiates a component with the items defined in this linker.
+ *
+ * \param linker the linker used to instantiate the provided component.
+ * \param store the store that is used to instantiate within
+ * \param component the component that is being instantiated.
+ * \param instance where to store the returned instance
+ * \param trap where to store the returned trap
+ *
+ * This function will attempt to satisfy all of the imports of the `component`
+ * provided with items previously defined in this linker. If any name isn't
+ * defined in the linker than an error is returned.
+ *
+ * The states of return values from this function are the same as
+ * #wasmtime_linker_instantiate.
+ *
+ * This function does not take ownership of any of its arguments, and all return
+ * values are owned by the caller.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instantiate(
+    const wasmtime_component_linker_t *linker,
+    wasmtime_context_t *store,
+    const wasmtime_component_t *component,
+    wasmtime_component_instance_t *instance,
+    wasm_trap_t **trap
+);
+
+/**
+ * \brief Looks up an exported function by name from a component instance.
+ *
+ * \param instance the instance to lookup within
+ * \param store the store that owns `instance`
+ * \param name the export name to lookup
+ * \param name_len the byte length of `name`
+ * \param func where to store the function, if found
+ *
+ * \return `true` if the function was found and `func` was written to, or
+ * `false` otherwise.
+ */
+WASM_API_EXTERN bool wasmtime_component_instance_get_func(
+    const wasmtime_component_instance_t *instance,
+    wasmtime_context_t *store,
+    const char *name,
+    size_t name_len,
+    wasmtime_component_func_t *func
+);
+
+/**
+ * \brief Calls a function exported from a component instance.
+ *
+ * \param store the store which owns `func`
+ * \param func the function to call
+ * \param args the arguments to the function call
+ * \param nargs the number of arguments provided
+ * \param results where to write the results of the function call
+ * \param nresults the number of results expected
+ * \param trap where to store a trap, if one happens.
+ *
+ * The `args` are checked against, and converted to, the parameter types of
+ * `func`, and an error is returned if they don't match. The return states of
+ * this function are otherwise the same as #wasmtime_func_call.
+ *
+ * After the function returns its results are copied into `results` and the
+ * function's post-return cleanup is run, so the function may be called again
+ * straight away.
+ *
+ * Does not take ownership of #wasmtime_component_val_t arguments. Gives
+ * ownership of #wasmtime_component_val_t results, which must each be deleted
+ * with #wasmtime_component_val_delete.
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_func_call(
+    wasmtime_context_t *store,
+    const wasmtime_component_func_t *func,
+    const wasmtime_component_val_t *args,
+    size_t nargs,
+    wasmtime_component_val_t *results,
+    size_t nresults,
+    wasm_trap_t **trap
+);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif
+
+#endif // WASMTIME_COMPONENT_H
@@ -33,6 +33,13 @@ enum wasmtime_strategy_enum { // Strategy
   /// Indicates that Wasmtime will unconditionally use Cranelift to compile
   /// WebAssembly code.
   WASMTIME_STRATEGY_CRANELIFT,
+
+  /// Indicates that Wasmtime will use Winch, the baseline compiler, to compile
+  /// WebAssembly code.
+  ///
+  /// Winch has to be enabled at build time with the `winch` feature,
+  /// otherwise creating an engine with this strategy fails.
+  WASMTIME_STRATEGY_WINCH,
 };
 
 /**
@@ -197,6 +204,16 @@ WASMTIME_CONFIG_PROP(void, wasm_multi_memory, bool)
  */
 WASMTIME_CONFIG_PROP(void, wasm_memory64, bool)
 
+/**
+ * \brief Configures whether the WebAssembly component model proposal is
+ * enabled.
+ *
+ * This must be enabled to use the APIs in `wasmtime/component.h`.
+ *
+ * This setting is `false` by default.
+ */
+WASMTIME_CONFIG_PROP(void, wasm_component_model, bool)
+
 /**
  * \brief Configures how JIT code will be compiled.
  *
@@ -0,0 +1,71 @@
+use crate::{wasm_trap_t, wasmtime_component_val_t, wasmtime_error_t, CStoreContextMut};
+use anyhow::{bail, Result};
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::panic::{self, AssertUnwindSafe};
+use wasmtime::component::{Func, Type, Val};
+
+pub type wasmtime_component_func_callback_t = extern "C" fn(
+    *mut c_void,
+    CStoreContextMut<'_>,
+    *const wasmtime_component_val_t,
+    usize,
+    *mut wasmtime_component_val_t,
+    usize,
+) -> Option<Box<wasm_trap_t>>;
+
+/// Converts the C values `vals` to `Val`s of the corresponding `types`.
+pub(crate) fn to_vals(vals: &[wasmtime_component_val_t], types: &[Type]) -> Result<Vec<Val>> {
+    if vals.len() != types.len() {
+        bail!("expected {} value(s); got {}", types.len(), vals.len());
+    }
+    vals.iter()
+        .zip(types)
+        .enumerate()
+        .map(|(i, (val, ty))| {
+            val.to_val(ty)
+                .map_err(|e| e.context(format!("invalid value at index {}", i)))
+        })
+        .collect()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_func_call(
+    mut store: CStoreContextMut<'_>,
+    func: &Func,
+    args: *const wasmtime_component_val_t,
+    nargs: usize,
+    results: *mut MaybeUninit<wasmtime_component_val_t>,
+    nresults: usize,
+    trap_ret: &mut *mut wasm_trap_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let params = match to_vals(
+        crate::slice_from_raw_parts(args, nargs),
+        &func.params(&store),
+    ) {
+        Ok(params) => params,
+        Err(e) => return Some(Box::new(wasmtime_error_t::from(e))),
+    };
+    let mut wt_results = vec![Val::Bool(false); nresults];
+
+    // Like `wasmtime_func_call` panics are caught here and turned into traps.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        func.call(&mut store, &params, &mut wt_results)?;
+        func.post_return(&mut store)
+    }));
+    match result {
+        Ok(Ok(())) => {
+            let results = crate::slice_from_raw_parts_mut(results, nresults);
+            for (slot, val) in results.iter_mut().zip(wt_results.iter()) {
+                crate::initialize(slot, wasmtime_component_val_t::from_val(val));
+            }
+            None
+        }
+        Ok(Err(trap)) => crate::store_err(trap, trap_ret),
+        Err(panic) => {
+            let err = crate::error_from_panic(panic);
+            *trap_ret = Box::into_raw(Box::new(wasm_trap_t::new(err)));
+            None
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::CStoreContextMut;
+use std::str;
+use wasmtime::component::{Func, Instance};
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_instance_get_func(
+    instance: &Instance,
+    store: CStoreContextMut<'_>,
+    name: *const u8,
+    name_len: usize,
+    func: &mut Func,
+) -> bool {
+    let name = crate::slice_from_raw_parts(name, name_len);
+    let name = match str::from_utf8(name) {
+        Ok(name) => name,
+        Err(_) => return false,
+    };
+    match instance.get_func(store, name) {
+        Some(f) => {
+            *func = f;
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    bad_utf8, handle_result, wasm_engine_t, wasm_trap_t, wasmtime_component_func_callback_t,
+    wasmtime_component_t, wasmtime_component_val_t, wasmtime_error_t, CStoreContextMut, StoreData,
+};
+use anyhow::anyhow;
+use std::ffi::c_void;
+use std::str;
+use wasmtime::component::{Instance, Linker};
+
+#[repr(C)]
+pub struct wasmtime_component_linker_t {
+    linker: Linker<StoreData>,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_component_linker_t);
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_new(
+    engine: &wasm_engine_t,
+) -> Box<wasmtime_component_linker_t> {
+    Box::new(wasmtime_component_linker_t {
+        linker: Linker::new(&engine.engine),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_allow_shadowing(
+    linker: &mut wasmtime_component_linker_t,
+    allow_shadowing: bool,
+) {
+    linker.linker.allow_shadowing(allow_shadowing);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wasmtime_component_linker_define_func(
+    linker: &mut wasmtime_component_linker_t,
+    component: &wasmtime_component_t,
+    name: *const u8,
+    name_len: usize,
+    callback: wasmtime_component_func_callback_t,
+    data: *mut c_void,
+    finalizer: Option<extern "C" fn(*mut std::ffi::c_void)>,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = match str::from_utf8(crate::slice_from_raw_parts(name, name_len)) {
+        Ok(s) => s,
+        Err(_) => return bad_utf8(),
+    };
+    let component = &component.component;
+    let result_types = match component.import_func_type(name) {
+        Some((_params, results)) => results,
+        None => {
+            return Some(Box::new(wasmtime_error_t::from(anyhow!(
+                "import `{name}` not found or is not a function \
+                 (functions within imported instances are not supported)"
+            ))))
+        }
+    };
+    let foreign = crate::ForeignData { data, finalizer };
+    let cb = move |store: CStoreContextMut<'_>, params: &[_], results: &mut [_]| {
+        drop(&foreign); // move entire foreign into this closure
+
+        let params = params
+            .iter()
+            .map(wasmtime_component_val_t::from_val)
+            .collect::<Vec<_>>();
+        let mut out_results = vec![wasmtime_component_val_t::default(); results.len()];
+        let out = callback(
+            foreign.data,
+            store,
+            params.as_ptr(),
+            params.len(),
+            out_results.as_mut_ptr(),
+            out_results.len(),
+        );
+        if let Some(trap) = out {
+            return Err(trap.error);
+        }
+
+        let vals = super::func::to_vals(&out_results, &result_types)?;
+        for (slot, val) in results.iter_mut().zip(vals) {
+            *slot = val;
+        }
+        Ok(())
+    };
+    handle_result(linker.linker.root().func_new(component, name, cb), |()| ())
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_linker_instantiate(
+    linker: &wasmtime_component_linker_t,
+    store: CStoreContextMut<'_>,
+    component: &wasmtime_component_t,
+    instance_ptr: &mut Instance,
+    trap_ptr: &mut *mut wasm_trap_t,
+) -> Option<Box<wasmtime_error_t>> {
+    match linker.linker.instantiate(store, &component.component) {
+        Ok(instance) => {
+            *instance_ptr = instance;
+            None
+        }
+        Err(e) => crate::store_err(e, trap_ptr),
+    }
+}
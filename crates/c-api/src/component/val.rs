@@ -0,0 +1,384 @@
+use crate::vec::declare_vecs;
+use crate::wasm_name_t;
+use anyhow::{anyhow, bail, Context, Result};
+use std::mem::{self, MaybeUninit};
+use std::str;
+use wasmtime::component::{Type, Val};
+
+/// The C representation of a `wasmtime::component::Val`.
+///
+/// Unlike `Val` this carries no type information, so converting it back into
+/// a `Val` requires the `Type` the value is expected to have. Every value owns
+/// all of its contents, which are recursively deallocated when it's dropped.
+#[repr(C, u8)]
+#[derive(Clone)]
+pub enum wasmtime_component_val_t {
+    Bool(bool),
+    S8(i8),
+    U8(u8),
+    S16(i16),
+    U16(u16),
+    S32(i32),
+    U32(u32),
+    S64(i64),
+    U64(u64),
+    Float32(f32),
+    Float64(f64),
+    Char(u32),
+    String(wasm_name_t),
+    List(wasmtime_component_val_vec_t),
+    Record(wasmtime_component_valrecord_t),
+    Tuple(wasmtime_component_val_vec_t),
+    Variant(wasmtime_component_valvariant_t),
+    Enum(wasm_name_t),
+    Union(wasmtime_component_valunioncase_t),
+    Option(Option<Box<wasmtime_component_val_t>>),
+    Result(wasmtime_component_valresult_t),
+    Flags(wasmtime_component_valflags_t),
+}
+
+#[repr(C)]
+#[derive(Clone, Default)]
+pub struct wasmtime_component_valrecord_field_t {
+    pub name: wasm_name_t,
+    pub val: wasmtime_component_val_t,
+}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct wasmtime_component_valvariant_t {
+    pub discriminant: wasm_name_t,
+    pub val: Option<Box<wasmtime_component_val_t>>,
+}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct wasmtime_component_valunioncase_t {
+    pub discriminant: u32,
+    pub val: Option<Box<wasmtime_component_val_t>>,
+}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct wasmtime_component_valresult_t {
+    pub is_err: bool,
+    pub val: Option<Box<wasmtime_component_val_t>>,
+}
+
+declare_vecs! {
+    (
+        name: wasmtime_component_val_vec_t,
+        ty: wasmtime_component_val_t,
+        new: wasmtime_component_val_vec_new,
+        empty: wasmtime_component_val_vec_new_empty,
+        uninit: wasmtime_component_val_vec_new_uninitialized,
+        copy: wasmtime_component_val_vec_copy,
+        delete: wasmtime_component_val_vec_delete,
+    )
+    (
+        name: wasmtime_component_valrecord_t,
+        ty: wasmtime_component_valrecord_field_t,
+        new: wasmtime_component_valrecord_new,
+        empty: wasmtime_component_valrecord_new_empty,
+        uninit: wasmtime_component_valrecord_new_uninitialized,
+        copy: wasmtime_component_valrecord_copy,
+        delete: wasmtime_component_valrecord_delete,
+    )
+    (
+        name: wasmtime_component_valflags_t,
+        ty: wasm_name_t,
+        new: wasmtime_component_valflags_new,
+        empty: wasmtime_component_valflags_new_empty,
+        uninit: wasmtime_component_valflags_new_uninitialized,
+        copy: wasmtime_component_valflags_copy,
+        delete: wasmtime_component_valflags_delete,
+    )
+}
+
+impl Default for wasmtime_component_val_t {
+    fn default() -> Self {
+        wasmtime_component_val_t::Bool(false)
+    }
+}
+
+fn name(s: &str) -> wasm_name_t {
+    wasm_name_t::from_name(s.to_string())
+}
+
+fn to_str(name: &wasm_name_t) -> Result<&str> {
+    str::from_utf8(name.as_slice()).context("input was not valid utf-8")
+}
+
+fn boxed(val: Option<&Val>) -> Option<Box<wasmtime_component_val_t>> {
+    val.map(|v| Box::new(wasmtime_component_val_t::from_val(v)))
+}
+
+fn unboxed(val: &Option<Box<wasmtime_component_val_t>>, ty: Option<Type>) -> Result<Option<Val>> {
+    match (val, ty) {
+        (Some(val), Some(ty)) => Ok(Some(val.to_val(&ty)?)),
+        (None, None) => Ok(None),
+        (Some(_), None) => bail!("expected no payload"),
+        (None, Some(_)) => bail!("expected a payload"),
+    }
+}
+
+impl wasmtime_component_val_t {
+    pub(crate) fn from_val(val: &Val) -> wasmtime_component_val_t {
+        match val {
+            Val::Bool(b) => wasmtime_component_val_t::Bool(*b),
+            Val::S8(i) => wasmtime_component_val_t::S8(*i),
+            Val::U8(i) => wasmtime_component_val_t::U8(*i),
+            Val::S16(i) => wasmtime_component_val_t::S16(*i),
+            Val::U16(i) => wasmtime_component_val_t::U16(*i),
+            Val::S32(i) => wasmtime_component_val_t::S32(*i),
+            Val::U32(i) => wasmtime_component_val_t::U32(*i),
+            Val::S64(i) => wasmtime_component_val_t::S64(*i),
+            Val::U64(i) => wasmtime_component_val_t::U64(*i),
+            Val::Float32(f) => wasmtime_component_val_t::Float32(*f),
+            Val::Float64(f) => wasmtime_component_val_t::Float64(*f),
+            Val::Char(c) => wasmtime_component_val_t::Char(*c as u32),
+            Val::String(s) => wasmtime_component_val_t::String(name(s)),
+            Val::List(list) => wasmtime_component_val_t::List(
+                list.iter()
+                    .map(wasmtime_component_val_t::from_val)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Record(record) => wasmtime_component_val_t::Record(
+                record
+                    .fields()
+                    .map(|(field, val)| wasmtime_component_valrecord_field_t {
+                        name: name(field),
+                        val: wasmtime_component_val_t::from_val(val),
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Tuple(tuple) => wasmtime_component_val_t::Tuple(
+                tuple
+                    .values()
+                    .iter()
+                    .map(wasmtime_component_val_t::from_val)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Val::Variant(variant) => {
+                wasmtime_component_val_t::Variant(wasmtime_component_valvariant_t {
+                    discriminant: name(variant.discriminant()),
+                    val: boxed(variant.payload()),
+                })
+            }
+            Val::Enum(e) => wasmtime_component_val_t::Enum(name(e.discriminant())),
+            Val::Union(union) => {
+                wasmtime_component_val_t::Union(wasmtime_component_valunioncase_t {
+                    discriminant: union.discriminant(),
+                    val: boxed(Some(union.payload())),
+                })
+            }
+            Val::Option(option) => wasmtime_component_val_t::Option(boxed(option.value())),
+            Val::Result(result) => {
+                let (is_err, val) = match result.value() {
+                    Ok(val) => (false, val),
+                    Err(val) => (true, val),
+                };
+                wasmtime_component_val_t::Result(wasmtime_component_valresult_t {
+                    is_err,
+                    val: boxed(val),
+                })
+            }
+            Val::Flags(flags) => {
+                wasmtime_component_val_t::Flags(flags.flags().map(name).collect::<Vec<_>>().into())
+            }
+        }
+    }
+
+    /// Converts this value into a `Val` of type `ty`, returning an error if
+    /// the value doesn't have that type.
+    pub(crate) fn to_val(&self, ty: &Type) -> Result<Val> {
+        Ok(match (self, ty) {
+            (wasmtime_component_val_t::Bool(b), Type::Bool) => Val::Bool(*b),
+            (wasmtime_component_val_t::S8(i), Type::S8) => Val::S8(*i),
+            (wasmtime_component_val_t::U8(i), Type::U8) => Val::U8(*i),
+            (wasmtime_component_val_t::S16(i), Type::S16) => Val::S16(*i),
+            (wasmtime_component_val_t::U16(i), Type::U16) => Val::U16(*i),
+            (wasmtime_component_val_t::S32(i), Type::S32) => Val::S32(*i),
+            (wasmtime_component_val_t::U32(i), Type::U32) => Val::U32(*i),
+            (wasmtime_component_val_t::S64(i), Type::S64) => Val::S64(*i),
+            (wasmtime_component_val_t::U64(i), Type::U64) => Val::U64(*i),
+            (wasmtime_component_val_t::Float32(f), Type::Float32) => Val::Float32(*f),
+            (wasmtime_component_val_t::Float64(f), Type::Float64) => Val::Float64(*f),
+            (wasmtime_component_val_t::Char(c), Type::Char) => {
+                Val::Char(char::from_u32(*c).ok_or_else(|| anyhow!("invalid char value {:#x}", c))?)
+            }
+            (wasmtime_component_val_t::String(s), Type::String) => Val::String(to_str(s)?.into()),
+            (wasmtime_component_val_t::List(list), Type::List(ty)) => {
+                let elem = ty.ty();
+                ty.new_val(
+                    list.as_slice()
+                        .iter()
+                        .map(|v| v.to_val(&elem))
+                        .collect::<Result<_>>()?,
+                )?
+            }
+            (wasmtime_component_val_t::Record(record), Type::Record(ty)) => {
+                let fields = record.as_slice();
+                if fields.len() != ty.fields().len() {
+                    bail!(
+                        "expected {} record field(s); got {}",
+                        ty.fields().len(),
+                        fields.len()
+                    );
+                }
+                // Fields are matched up by name so they may be given in any
+                // order.
+                let mut values = Vec::with_capacity(fields.len());
+                for field in ty.fields() {
+                    let val = fields
+                        .iter()
+                        .find(|f| f.name.as_slice() == field.name.as_bytes())
+                        .ok_or_else(|| anyhow!("missing record field `{}`", field.name))?;
+                    let val = val
+                        .val
+                        .to_val(&field.ty)
+                        .with_context(|| format!("invalid record field `{}`", field.name))?;
+                    values.push((field.name, val));
+                }
+                ty.new_val(values)?
+            }
+            (wasmtime_component_val_t::Tuple(tuple), Type::Tuple(ty)) => {
+                let tuple = tuple.as_slice();
+                if tuple.len() != ty.types().len() {
+                    bail!(
+                        "expected {} tuple element(s); got {}",
+                        ty.types().len(),
+                        tuple.len()
+                    );
+                }
+                ty.new_val(
+                    tuple
+                        .iter()
+                        .zip(ty.types())
+                        .map(|(v, ty)| v.to_val(&ty))
+                        .collect::<Result<_>>()?,
+                )?
+            }
+            (wasmtime_component_val_t::Variant(variant), Type::Variant(ty)) => {
+                let discriminant = to_str(&variant.discriminant)?;
+                let case = ty
+                    .cases()
+                    .find(|case| case.name == discriminant)
+                    .ok_or_else(|| anyhow!("unknown variant case `{}`", discriminant))?;
+                let val = unboxed(&variant.val, case.ty)
+                    .with_context(|| format!("invalid variant case `{}`", discriminant))?;
+                ty.new_val(discriminant, val)?
+            }
+            (wasmtime_component_val_t::Enum(e), Type::Enum(ty)) => ty.new_val(to_str(e)?)?,
+            (wasmtime_component_val_t::Union(union), Type::Union(ty)) => {
+                let case_ty = ty
+                    .types()
+                    .nth(union.discriminant as usize)
+                    .ok_or_else(|| anyhow!("unknown union case {}", union.discriminant))?;
+                let val = unboxed(&union.val, Some(case_ty))?.unwrap();
+                ty.new_val(union.discriminant, val)?
+            }
+            (wasmtime_component_val_t::Option(option), Type::Option(ty)) => {
+                let val = match option {
+                    Some(val) => Some(val.to_val(&ty.ty())?),
+                    None => None,
+                };
+                ty.new_val(val)?
+            }
+            (wasmtime_component_val_t::Result(result), Type::Result(ty)) => {
+                if result.is_err {
+                    ty.new_val(Err(unboxed(&result.val, ty.err())?))?
+                } else {
+                    ty.new_val(Ok(unboxed(&result.val, ty.ok())?))?
+                }
+            }
+            (wasmtime_component_val_t::Flags(flags), Type::Flags(ty)) => {
+                let names = flags
+                    .as_slice()
+                    .iter()
+                    .map(to_str)
+                    .collect::<Result<Vec<_>>>()?;
+                ty.new_val(&names)?
+            }
+            (val, ty) => bail!("type mismatch: expected {}, got {}", desc(ty), val.desc()),
+        })
+    }
+
+    fn desc(&self) -> &'static str {
+        match self {
+            wasmtime_component_val_t::Bool(_) => "bool",
+            wasmtime_component_val_t::S8(_) => "s8",
+            wasmtime_component_val_t::U8(_) => "u8",
+            wasmtime_component_val_t::S16(_) => "s16",
+            wasmtime_component_val_t::U16(_) => "u16",
+            wasmtime_component_val_t::S32(_) => "s32",
+            wasmtime_component_val_t::U32(_) => "u32",
+            wasmtime_component_val_t::S64(_) => "s64",
+            wasmtime_component_val_t::U64(_) => "u64",
+            wasmtime_component_val_t::Float32(_) => "float32",
+            wasmtime_component_val_t::Float64(_) => "float64",
+            wasmtime_component_val_t::Char(_) => "char",
+            wasmtime_component_val_t::String(_) => "string",
+            wasmtime_component_val_t::List(_) => "list",
+            wasmtime_component_val_t::Record(_) => "record",
+            wasmtime_component_val_t::Tuple(_) => "tuple",
+            wasmtime_component_val_t::Variant(_) => "variant",
+            wasmtime_component_val_t::Enum(_) => "enum",
+            wasmtime_component_val_t::Union(_) => "union",
+            wasmtime_component_val_t::Option(_) => "option",
+            wasmtime_component_val_t::Result(_) => "result",
+            wasmtime_component_val_t::Flags(_) => "flags",
+        }
+    }
+}
+
+fn desc(ty: &Type) -> &'static str {
+    match ty {
+        Type::Bool => "bool",
+        Type::S8 => "s8",
+        Type::U8 => "u8",
+        Type::S16 => "s16",
+        Type::U16 => "u16",
+        Type::S32 => "s32",
+        Type::U32 => "u32",
+        Type::S64 => "s64",
+        Type::U64 => "u64",
+        Type::Float32 => "float32",
+        Type::Float64 => "float64",
+        Type::Char => "char",
+        Type::String => "string",
+        Type::List(_) => "list",
+        Type::Record(_) => "record",
+        Type::Tuple(_) => "tuple",
+        Type::Variant(_) => "variant",
+        Type::Enum(_) => "enum",
+        Type::Union(_) => "union",
+        Type::Option(_) => "option",
+        Type::Result(_) => "result",
+        Type::Flags(_) => "flags",
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_new() -> Box<wasmtime_component_val_t> {
+    Box::new(wasmtime_component_val_t::default())
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_free(_val: Option<Box<wasmtime_component_val_t>>) {}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_delete(val: &mut wasmtime_component_val_t) {
+    drop(mem::take(val));
+}
+
+#[no_mangle]
+pub extern "C" fn wasmtime_component_val_copy(
+    dst: &mut MaybeUninit<wasmtime_component_val_t>,
+    src: &wasmtime_component_val_t,
+) {
+    crate::initialize(dst, src.clone());
+}
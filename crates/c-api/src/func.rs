@@ -163,7 +163,7 @@ pub unsafe extern "C" fn wasm_func_call(
     }
 }
 
-fn error_from_panic(panic: Box<dyn Any + Send>) -> Error {
+pub(crate) fn error_from_panic(panic: Box<dyn Any + Send>) -> Error {
     if let Some(msg) = panic.downcast_ref::<String>() {
         Error::msg(msg.clone())
     } else if let Some(msg) = panic.downcast_ref::<&'static str>() {
@@ -373,7 +373,10 @@ pub unsafe extern "C" fn wasmtime_func_call_unchecked(
     }
 }
 
-fn store_err(err: Error, trap_ret: &mut *mut wasm_trap_t) -> Option<Box<wasmtime_error_t>> {
+pub(crate) fn store_err(
+    err: Error,
+    trap_ret: &mut *mut wasm_trap_t,
+) -> Option<Box<wasmtime_error_t>> {
     if err.is::<Trap>() {
         *trap_ret = Box::into_raw(Box::new(wasm_trap_t::new(err)));
         None
@@ -5,8 +5,6 @@ use crate::{
 };
 use std::mem;
 use std::mem::MaybeUninit;
-use std::ptr;
-use std::slice;
 
 pub type wasm_name_t = wasm_byte_vec_t;
 
@@ -50,7 +48,7 @@ macro_rules! declare_vecs {
                     &[]
                 } else {
                     assert!(!self.data.is_null());
-                    unsafe { slice::from_raw_parts(self.data, self.size) }
+                    unsafe { std::slice::from_raw_parts(self.data, self.size) }
                 }
             }
 
@@ -62,7 +60,7 @@ macro_rules! declare_vecs {
                     &mut []
                 } else {
                     assert!(!self.data.is_null());
-                    unsafe { slice::from_raw_parts_mut(self.data as _, self.size) }
+                    unsafe { std::slice::from_raw_parts_mut(self.data as _, self.size) }
                 }
             }
 
@@ -73,7 +71,7 @@ macro_rules! declare_vecs {
                 let vec = unsafe {
                     Vec::from_raw_parts(self.data, self.size, self.size)
                 };
-                self.data = ptr::null_mut();
+                self.data = std::ptr::null_mut();
                 self.size = 0;
                 return vec;
             }
@@ -97,6 +95,12 @@ macro_rules! declare_vecs {
             }
         }
 
+        impl$(<$lt>)? Default for $name $(<$lt>)? {
+            fn default() -> Self {
+                Vec::new().into()
+            }
+        }
+
         impl$(<$lt>)? Drop for $name $(<$lt>)? {
             fn drop(&mut self) {
                 drop(self.take());
@@ -106,7 +110,7 @@ macro_rules! declare_vecs {
         #[no_mangle]
         pub extern "C" fn $empty(out: &mut $name) {
             out.size = 0;
-            out.data = ptr::null_mut();
+            out.data = std::ptr::null_mut();
         }
 
         #[no_mangle]
@@ -139,6 +143,8 @@ macro_rules! declare_vecs {
     )*};
 }
 
+pub(crate) use declare_vecs;
+
 declare_vecs! {
     (
         name: wasm_byte_vec_t,
@@ -20,6 +20,7 @@ use anyhow::{bail, Result};
 use clap::Parser;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 use wasmtime::{Config, ProfilingStrategy};
 
 pub const SUPPORTED_WASM_FEATURES: &[(&str, &str)] = &[
@@ -40,6 +41,10 @@ pub const SUPPORTED_WASM_FEATURES: &[(&str, &str)] = &[
         "enables support for the relaxed simd proposal",
     ),
     ("threads", "enables support for WebAssembly threads"),
+    (
+        "tail-call",
+        "enables support for the tail calls proposal (x86_64 only)",
+    ),
     ("memory64", "enables support for 64-bit memories"),
     #[cfg(feature = "component-model")]
     ("component-model", "enables support for the component model"),
@@ -130,9 +135,10 @@ pub struct CommonOptions {
     #[clap(long, value_name = "MODULE,MODULE,...", parse(try_from_str = parse_wasi_modules))]
     pub wasi_modules: Option<WasiModules>,
 
-    /// Profiling strategy (valid options are: perfmap, jitdump, vtune)
-    #[clap(long)]
-    pub profile: Option<ProfilingStrategy>,
+    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, or
+    /// guest[,PATH[,INTERVAL]] for the built-in guest profiler)
+    #[clap(long, value_name = "STRATEGY", parse(try_from_str = parse_profile))]
+    pub profile: Option<Profile>,
 
     /// Generate jitdump file (supported on --features=profiling build)
     /// Run optimization passes on translated functions, on by default
@@ -210,6 +216,16 @@ pub struct CommonOptions {
     #[clap(long)]
     pub epoch_interruption: bool,
 
+    /// Check the guest's accesses to its heap for use-after-free, double free
+    /// and out-of-bounds errors, tracking allocations through the module's
+    /// exported `malloc` and `free`
+    #[clap(long)]
+    pub memory_sanitizer: bool,
+
+    /// Inline small functions into their callers in the same module
+    #[clap(long)]
+    pub inlining: bool,
+
     /// Disable the on-by-default address map from native code to wasm code
     #[clap(long)]
     pub disable_address_map: bool,
@@ -240,6 +256,11 @@ pub struct CommonOptions {
     /// performance cost.
     #[clap(long)]
     pub relaxed_simd_deterministic: bool,
+
+    /// Remove all sources of nondeterminism from WebAssembly execution, such
+    /// as NaN bit patterns, relaxed-simd results and threads.
+    #[clap(long)]
+    pub deterministic: bool,
 }
 
 impl CommonOptions {
@@ -268,7 +289,10 @@ impl CommonOptions {
             .cranelift_debug_verifier(self.enable_cranelift_debug_verifier)
             .debug_info(self.debug_info)
             .cranelift_opt_level(self.opt_level())
-            .profiler(self.profile.unwrap_or(ProfilingStrategy::None))
+            .profiler(match &self.profile {
+                Some(Profile::Native(strategy)) => *strategy,
+                Some(Profile::Guest { .. }) | None => ProfilingStrategy::None,
+            })
             .cranelift_nan_canonicalization(self.enable_cranelift_nan_canonicalization);
 
         self.enable_wasm_features(&mut config);
@@ -323,6 +347,8 @@ impl CommonOptions {
         }
 
         config.epoch_interruption(self.epoch_interruption);
+        config.memory_sanitizer(self.memory_sanitizer);
+        config.inlining(self.inlining);
         config.generate_address_map(!self.disable_address_map);
         config.memory_init_cow(!self.disable_memory_init_cow);
 
@@ -338,6 +364,9 @@ impl CommonOptions {
         }
 
         config.relaxed_simd_deterministic(self.relaxed_simd_deterministic);
+        if self.deterministic {
+            config.deterministic_execution(true);
+        }
 
         Ok(config)
     }
@@ -350,6 +379,7 @@ impl CommonOptions {
             reference_types,
             multi_value,
             threads,
+            tail_call,
             multi_memory,
             memory64,
             #[cfg(feature = "component-model")]
@@ -374,6 +404,9 @@ impl CommonOptions {
         if let Some(enable) = threads {
             config.wasm_threads(enable);
         }
+        if let Some(enable) = tail_call {
+            config.wasm_tail_call(enable);
+        }
         if let Some(enable) = multi_memory {
             config.wasm_multi_memory(enable);
         }
@@ -394,7 +427,8 @@ impl CommonOptions {
     }
 }
 
-fn parse_opt_level(opt_level: &str) -> Result<wasmtime::OptLevel> {
+/// Parses an optimization level as accepted by `--opt-level`.
+pub fn parse_opt_level(opt_level: &str) -> Result<wasmtime::OptLevel> {
     match opt_level {
         "s" => Ok(wasmtime::OptLevel::SpeedAndSize),
         "0" => Ok(wasmtime::OptLevel::None),
@@ -407,6 +441,42 @@ fn parse_opt_level(opt_level: &str) -> Result<wasmtime::OptLevel> {
     }
 }
 
+/// The profiler selected with `--profile`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Profile {
+    /// One of the native profilers, which describe the compiled code to an
+    /// external tool.
+    Native(ProfilingStrategy),
+    /// Wasmtime's built-in sampling profiler of the guest's stacks.
+    Guest {
+        /// The file the collapsed stacks are written to.
+        path: String,
+        /// The time between two samples.
+        interval: Duration,
+    },
+}
+
+/// Parses a profiling strategy as accepted by `--profile`.
+pub fn parse_profile(profile: &str) -> Result<Profile> {
+    let parts = profile.split(',').collect::<Vec<_>>();
+    match &parts[..] {
+        ["guest"] => Ok(Profile::Guest {
+            path: "wasmtime-guest-profile.folded".to_string(),
+            interval: Duration::from_millis(10),
+        }),
+        ["guest", path] => Ok(Profile::Guest {
+            path: path.to_string(),
+            interval: Duration::from_millis(10),
+        }),
+        ["guest", path, interval] => Ok(Profile::Guest {
+            path: path.to_string(),
+            interval: humantime::parse_duration(interval)?,
+        }),
+        [strategy] => Ok(Profile::Native(strategy.parse()?)),
+        _ => bail!("unknown profiling strategy `{}`", profile),
+    }
+}
+
 #[derive(Default, Clone, Copy)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct WasmFeatures {
@@ -416,6 +486,7 @@ pub struct WasmFeatures {
     pub simd: Option<bool>,
     pub relaxed_simd: Option<bool>,
     pub threads: Option<bool>,
+    pub tail_call: Option<bool>,
     pub multi_memory: Option<bool>,
     pub memory64: Option<bool>,
     #[cfg(feature = "component-model")]
@@ -467,6 +538,7 @@ fn parse_wasm_features(features: &str) -> Result<WasmFeatures> {
         simd: all.or(values["simd"]),
         relaxed_simd: all.or(values["relaxed-simd"]),
         threads: all.or(values["threads"]),
+        tail_call: all.or(values["tail-call"]),
         multi_memory: all.or(values["multi-memory"]),
         memory64: all.or(values["memory64"]),
         #[cfg(feature = "component-model")]
@@ -548,7 +620,8 @@ impl WasiModules {
     }
 }
 
-fn parse_cranelift_flag(name_and_value: &str) -> Result<(String, String)> {
+/// Parses a `NAME=VALUE` Cranelift setting as accepted by `--cranelift-set`.
+pub fn parse_cranelift_flag(name_and_value: &str) -> Result<(String, String)> {
     let mut split = name_and_value.splitn(2, '=');
     let name = if let Some(name) = split.next() {
         name.to_string()
@@ -578,6 +651,7 @@ mod test {
             simd,
             relaxed_simd,
             threads,
+            tail_call,
             multi_memory,
             memory64,
         } = options.wasm_features.unwrap();
@@ -587,6 +661,7 @@ mod test {
         assert_eq!(bulk_memory, Some(true));
         assert_eq!(simd, Some(true));
         assert_eq!(threads, Some(true));
+        assert_eq!(tail_call, Some(true));
         assert_eq!(multi_memory, Some(true));
         assert_eq!(memory64, Some(true));
         assert_eq!(relaxed_simd, Some(true));
@@ -605,6 +680,7 @@ mod test {
             simd,
             relaxed_simd,
             threads,
+            tail_call,
             multi_memory,
             memory64,
         } = options.wasm_features.unwrap();
@@ -614,6 +690,7 @@ mod test {
         assert_eq!(bulk_memory, Some(false));
         assert_eq!(simd, Some(false));
         assert_eq!(threads, Some(false));
+        assert_eq!(tail_call, Some(false));
         assert_eq!(multi_memory, Some(false));
         assert_eq!(memory64, Some(false));
         assert_eq!(relaxed_simd, Some(false));
@@ -635,6 +712,7 @@ mod test {
             simd,
             relaxed_simd,
             threads,
+            tail_call,
             multi_memory,
             memory64,
         } = options.wasm_features.unwrap();
@@ -644,6 +722,7 @@ mod test {
         assert_eq!(bulk_memory, None);
         assert_eq!(simd, Some(true));
         assert_eq!(threads, None);
+        assert_eq!(tail_call, None);
         assert_eq!(multi_memory, Some(true));
         assert_eq!(memory64, Some(true));
         assert_eq!(relaxed_simd, None);
@@ -686,6 +765,7 @@ mod test {
     feature_test!(test_simd_feature, simd, "simd");
     feature_test!(test_relaxed_simd_feature, relaxed_simd, "relaxed-simd");
     feature_test!(test_threads_feature, threads, "threads");
+    feature_test!(test_tail_call_feature, tail_call, "tail-call");
     feature_test!(test_multi_memory_feature, multi_memory, "multi-memory");
     feature_test!(test_memory64_feature, memory64, "memory64");
 
@@ -749,4 +829,35 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_profile() -> Result<()> {
+        let options = CommonOptions::try_parse_from(vec!["foo", "--profile=jitdump"])?;
+        assert_eq!(
+            options.profile,
+            Some(Profile::Native(ProfilingStrategy::JitDump))
+        );
+
+        let options = CommonOptions::try_parse_from(vec!["foo", "--profile=guest"])?;
+        assert_eq!(
+            options.profile,
+            Some(Profile::Guest {
+                path: "wasmtime-guest-profile.folded".to_string(),
+                interval: Duration::from_millis(10),
+            })
+        );
+
+        let options = CommonOptions::try_parse_from(vec!["foo", "--profile=guest,out.folded,1ms"])?;
+        assert_eq!(
+            options.profile,
+            Some(Profile::Guest {
+                path: "out.folded".to_string(),
+                interval: Duration::from_millis(1),
+            })
+        );
+
+        assert!(CommonOptions::try_parse_from(vec!["foo", "--profile=guest,a,b,c"]).is_err());
+        assert!(CommonOptions::try_parse_from(vec!["foo", "--profile=bogus"]).is_err());
+        Ok(())
+    }
 }
@@ -9,6 +9,7 @@ use cranelift_codegen::{
     CodegenResult,
 };
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use wasmtime_cranelift_shared::isa_builder::IsaBuilder;
 use wasmtime_environ::{CacheStore, CompilerBuilder, Setting};
@@ -17,6 +18,8 @@ struct Builder {
     inner: IsaBuilder<CodegenResult<OwnedTargetIsa>>,
     linkopts: LinkOptions,
     cache_store: Option<Arc<dyn CacheStore>>,
+    clif_dir: Option<PathBuf>,
+    tail_calls: bool,
 }
 
 #[derive(Clone, Default)]
@@ -37,6 +40,8 @@ pub fn builder() -> Box<dyn CompilerBuilder> {
         inner: IsaBuilder::new(|triple| isa::lookup(triple).map_err(|e| e.into())),
         linkopts: LinkOptions::default(),
         cache_store: None,
+        clif_dir: None,
+        tail_calls: false,
     })
 }
 
@@ -73,7 +78,9 @@ impl CompilerBuilder for Builder {
         Ok(Box::new(crate::compiler::Compiler::new(
             isa,
             self.cache_store.clone(),
+            self.clif_dir.clone(),
             self.linkopts.clone(),
+            self.tail_calls,
         )))
     }
 
@@ -88,6 +95,16 @@ impl CompilerBuilder for Builder {
         self.cache_store = Some(cache_store);
         Ok(())
     }
+
+    fn clif_dir(&mut self, path: &Path) -> Result<()> {
+        self.clif_dir = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    fn enable_tail_calls(&mut self) -> Result<()> {
+        self.tail_calls = true;
+        Ok(())
+    }
 }
 
 impl fmt::Debug for Builder {
@@ -5,13 +5,14 @@ use crate::{
     wasmtime_call_conv, CompiledFunction, FunctionAddressMap,
 };
 use anyhow::{Context as _, Result};
+use cranelift_codegen::inline::{self, InlineOptions};
 use cranelift_codegen::ir::{
     self, ExternalName, Function, InstBuilder, MemFlags, UserExternalName, UserFuncName, Value,
 };
 use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
 use cranelift_codegen::print_errors::pretty_error;
 use cranelift_codegen::Context;
-use cranelift_codegen::{CompiledCode, MachSrcLoc, MachStackMap};
+use cranelift_codegen::{CompiledCode, Final, MachBufferFinalized, MachSrcLoc, MachStackMap};
 use cranelift_codegen::{MachReloc, MachTrap};
 use cranelift_entity::{EntityRef, PrimaryMap};
 use cranelift_frontend::FunctionBuilder;
@@ -26,14 +27,17 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::mem;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use wasmparser::{FuncValidatorAllocations, FunctionBody};
+use wasmparser::FuncValidatorAllocations;
 use wasmtime_cranelift_shared::obj::ModuleTextBuilder;
 use wasmtime_cranelift_shared::{Relocation, RelocationTarget};
 use wasmtime_environ::{
-    AddressMapSection, CacheStore, CompileError, FilePos, FlagValue, FunctionBodyData, FunctionLoc,
-    InstructionAddressMap, ModuleTranslation, ModuleTypes, PtrSize, StackMapInformation, Trap,
-    TrapEncodingBuilder, TrapInformation, Tunables, VMOffsets, WasmFunctionInfo,
+    AddressMapSection, BlockProfileCounter, CacheStore, CompileError, FilePos, FlagValue,
+    FunctionBodyData, FunctionLoc, InlinedCall, InstructionAddressMap, ModuleTranslation,
+    ModuleTypes, PtrSize, StackMapInformation, Trap, TrapEncodingBuilder, TrapInformation,
+    Tunables, VMOffsets, WasmFunctionInfo,
 };
 
 #[cfg(feature = "component-model")]
@@ -64,6 +68,14 @@ impl Default for CompilerContext {
     }
 }
 
+/// A function translated to Cranelift IR, which hasn't been compiled yet.
+#[derive(Clone)]
+struct TranslatedFunction {
+    func: Function,
+    /// The range of the function's body in the wasm module.
+    body: Range<usize>,
+}
+
 /// A compiler that compiles a WebAssembly module with Compiler, translating
 /// the Wasm to Compiler IR, optimizing it and then translating to assembly.
 pub(crate) struct Compiler {
@@ -71,6 +83,8 @@ pub(crate) struct Compiler {
     isa: OwnedTargetIsa,
     linkopts: LinkOptions,
     cache_store: Option<Arc<dyn CacheStore>>,
+    clif_dir: Option<PathBuf>,
+    tail_calls: bool,
 }
 
 impl Drop for Compiler {
@@ -105,13 +119,17 @@ impl Compiler {
     pub(crate) fn new(
         isa: OwnedTargetIsa,
         cache_store: Option<Arc<dyn CacheStore>>,
+        clif_dir: Option<PathBuf>,
         linkopts: LinkOptions,
+        tail_calls: bool,
     ) -> Compiler {
         Compiler {
             contexts: Default::default(),
             isa,
             linkopts,
             cache_store,
+            clif_dir,
+            tail_calls,
         }
     }
 
@@ -140,16 +158,15 @@ impl Compiler {
     }
 
     fn get_function_address_map(
-        compiled_code: &CompiledCode,
-        body: &FunctionBody<'_>,
+        srclocs: &[MachSrcLoc<Final>],
+        body: &Range<usize>,
         body_len: u32,
         tunables: &Tunables,
     ) -> FunctionAddressMap {
         // Generate artificial srcloc for function start/end to identify boundary
         // within module.
-        let data = body.get_binary_reader();
-        let offset = data.original_position();
-        let len = data.bytes_remaining();
+        let offset = body.start;
+        let len = body.len();
         assert!((offset + len) <= u32::max_value() as usize);
         let start_srcloc = FilePos::new(offset as u32);
         let end_srcloc = FilePos::new((offset + len) as u32);
@@ -159,10 +176,8 @@ impl Compiler {
         let instructions = if tunables.generate_address_map {
             collect_address_maps(
                 body_len,
-                compiled_code
-                    .buffer
-                    .get_srclocs_sorted()
-                    .into_iter()
+                srclocs
+                    .iter()
                     .map(|&MachSrcLoc { start, end, loc }| (loc, start, (end - start))),
             )
         } else {
@@ -177,17 +192,16 @@ impl Compiler {
             body_len,
         }
     }
-}
 
-impl wasmtime_environ::Compiler for Compiler {
-    fn compile_function(
+    /// Translates the body of a function to Cranelift IR.
+    fn translate(
         &self,
         translation: &ModuleTranslation<'_>,
         func_index: DefinedFuncIndex,
         input: FunctionBodyData<'_>,
         tunables: &Tunables,
         types: &ModuleTypes,
-    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+    ) -> Result<TranslatedFunction, CompileError> {
         let isa = &*self.isa;
         let module = &translation.module;
         let func_index = module.func_index(func_index);
@@ -195,11 +209,12 @@ impl wasmtime_environ::Compiler for Compiler {
         let CompilerContext {
             mut func_translator,
             codegen_context: mut context,
-            incremental_cache_ctx: mut cache_ctx,
+            incremental_cache_ctx: cache_ctx,
             validator_allocations,
         } = self.take_context();
 
-        context.func.signature = func_signature(isa, translation, types, func_index);
+        context.func.signature =
+            func_signature(isa, self.tail_calls, translation, types, func_index);
         context.func.name = UserFuncName::User(UserExternalName {
             namespace: 0,
             index: func_index.as_u32(),
@@ -209,7 +224,7 @@ impl wasmtime_environ::Compiler for Compiler {
             context.func.collect_debug_info();
         }
 
-        let mut func_env = FuncEnvironment::new(isa, translation, types, tunables);
+        let mut func_env = FuncEnvironment::new(isa, translation, types, tunables, self.tail_calls);
 
         // The `stack_limit` global value below is the implementation of stack
         // overflow checks in Wasmtime.
@@ -272,11 +287,143 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut func_env,
         )?;
 
-        let (_, code_buf) = compile_maybe_cached(&mut context, isa, cache_ctx.as_mut())?;
+        let func = mem::replace(&mut context.func, Function::new());
+        self.save_context(CompilerContext {
+            func_translator,
+            codegen_context: context,
+            incremental_cache_ctx: cache_ctx,
+            validator_allocations: validator.into_allocations(),
+        });
+        Ok(TranslatedFunction {
+            func,
+            body: body.range(),
+        })
+    }
+
+    /// Compiles a function translated with `translate`, first inlining the
+    /// calls to the functions of the module in `translated`, if given.
+    fn compile_translated(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        defined_func_index: DefinedFuncIndex,
+        func: TranslatedFunction,
+        translated: Option<&PrimaryMap<DefinedFuncIndex, Box<dyn Any + Send + Sync>>>,
+        tunables: &Tunables,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let isa = &*self.isa;
+        let module = &translation.module;
+        let func_index = module.func_index(defined_func_index);
+        let offsets = VMOffsets::new(isa.pointer_bytes(), module);
+
+        let CompilerContext {
+            func_translator,
+            codegen_context: mut context,
+            incremental_cache_ctx: mut cache_ctx,
+            validator_allocations,
+        } = self.take_context();
+        let TranslatedFunction { func, body } = func;
+        context.func = func;
+
+        // Inline calls to small functions of the module, which are the
+        // functions called directly and named by their `FuncIndex`.
+        let inlined = match translated {
+            Some(translated) => context
+                .inline_calls(isa, &InlineOptions::default(), |name| {
+                    let callee = module.defined_func_index(FuncIndex::from_u32(name.index))?;
+                    translated[callee]
+                        .downcast_ref::<TranslatedFunction>()
+                        .map(|callee| &callee.func)
+                })
+                .map_err(|error| CompileError::Codegen(pretty_error(&context.func, error)))?,
+            None => Vec::new(),
+        };
+        let inlined_srclocs = InlinedSrclocs::new(&mut context.func, &inlined, translation.wasm);
+
+        if let Some(profile) = translation.block_profile.funcs.get(&defined_func_index) {
+            for &(block, count) in &profile.blocks {
+                context
+                    .func
+                    .block_profile
+                    .set_block_count(ir::Block::from_u32(block), count);
+            }
+            for &(block, succ_idx, count) in &profile.edges {
+                context.func.block_profile.set_edge_count(
+                    ir::Block::from_u32(block),
+                    succ_idx,
+                    count,
+                );
+            }
+        }
+        let block_profile_counters = if tunables.block_profiling {
+            // The counters of this function are found through the table of
+            // counter arrays, indexed by `DefinedFuncIndex`, that the
+            // `VMContext` points to.
+            let vmctx = context
+                .func
+                .create_global_value(ir::GlobalValueData::VMContext);
+            let table = context.func.create_global_value(ir::GlobalValueData::Load {
+                base: vmctx,
+                offset: i32::try_from(offsets.vmctx_block_profile_counters())
+                    .unwrap()
+                    .into(),
+                global_type: isa.pointer_type(),
+                readonly: true,
+            });
+            let counters = context.func.create_global_value(ir::GlobalValueData::Load {
+                base: table,
+                offset: i32::try_from(defined_func_index.as_u32() * u32::from(offsets.ptr.size()))
+                    .unwrap()
+                    .into(),
+                global_type: isa.pointer_type(),
+                readonly: true,
+            });
+            context
+                .instrument_block_profile(isa, counters)
+                .map_err(|error| CompileError::Codegen(pretty_error(&context.func, error)))?
+                .into_iter()
+                .map(|counter| match counter {
+                    ir::ProfileCounter::Block(block) => BlockProfileCounter::Block(block.as_u32()),
+                    ir::ProfileCounter::Edge(block, succ_idx) => {
+                        BlockProfileCounter::Edge(block.as_u32(), succ_idx)
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // When dumping each stage of compilation the incremental cache is
+        // bypassed since a cache hit wouldn't run any of the stages.
+        if let Some(path) = &self.clif_dir {
+            context.set_disasm(true);
+            write_clif_stage(
+                path,
+                translation,
+                func_index,
+                "clif",
+                &context.func.display().to_string(),
+            )?;
+        }
+        let cache_ctx_for_func = cache_ctx.as_mut().filter(|_| self.clif_dir.is_none());
+        let (_, code_buf) = compile_maybe_cached(&mut context, isa, cache_ctx_for_func)?;
         // compile_maybe_cached returns the compiled_code but that borrow has the same lifetime as
         // the mutable borrow of `context`, so the borrow checker prohibits other borrows from
         // `context` while it's alive. Borrow it again to make the borrow checker happy.
         let compiled_code = context.compiled_code().unwrap();
+
+        if let Some(path) = &self.clif_dir {
+            // Optimizations are performed in-place on `context.func`.
+            let opt_clif = context.func.display().to_string();
+            write_clif_stage(path, translation, func_index, "opt.clif", &opt_clif)?;
+            if let Some(vcode) = &compiled_code.lowered_vcode {
+                let vcode = annotate_vcode(vcode, &compiled_code.lowered_vcode_srclocs);
+                write_clif_stage(path, translation, func_index, "lowered.vcode", &vcode)?;
+            }
+            if let Some(vcode) = &compiled_code.vcode {
+                let vcode = annotate_vcode(vcode, &compiled_code.vcode_srclocs);
+                write_clif_stage(path, translation, func_index, "vcode", &vcode)?;
+            }
+        }
         let alignment = compiled_code.alignment;
 
         let func_relocs = compiled_code
@@ -305,8 +452,35 @@ impl wasmtime_environ::Compiler for Compiler {
 
         let length = u32::try_from(code_buf.len()).unwrap();
 
-        let address_transform =
-            Self::get_function_address_map(compiled_code, &body, length, tunables);
+        // Find the code of each inlined copy of a callee from its tags, which
+        // are replaced by the source locations they stand for.
+        let mut srclocs = compiled_code.buffer.get_srclocs_sorted().to_vec();
+        let mut inlined_code = vec![Vec::<Range<u32>>::new(); inlined.len()];
+        for srcloc in &mut srclocs {
+            let (copy, loc) = inlined_srclocs.resolve(srcloc.loc);
+            srcloc.loc = loc;
+            if let Some(code) = copy.map(|copy| &mut inlined_code[copy]) {
+                match code.last_mut() {
+                    Some(last) if last.end == srcloc.start => last.end = srcloc.end,
+                    _ => code.push(srcloc.start..srcloc.end),
+                }
+            }
+        }
+        let inlined_calls = inlined
+            .iter()
+            .zip(inlined_code)
+            .map(|(call, code)| InlinedCall {
+                callee: FuncIndex::from_u32(call.callee.index),
+                call_site: if call.srcloc.is_default() {
+                    FilePos::default()
+                } else {
+                    FilePos::new(call.srcloc.bits())
+                },
+                code: code.into(),
+            })
+            .collect::<Vec<_>>();
+
+        let address_transform = Self::get_function_address_map(&srclocs, &body, length, tunables);
 
         let ranges = if tunables.generate_native_debuginfo {
             Some(compiled_code.value_labels_ranges.clone())
@@ -324,13 +498,15 @@ impl wasmtime_environ::Compiler for Compiler {
             func_translator,
             codegen_context: context,
             incremental_cache_ctx: cache_ctx,
-            validator_allocations: validator.into_allocations(),
+            validator_allocations,
         });
 
         Ok((
             WasmFunctionInfo {
                 start_srcloc: address_transform.start_srcloc,
                 stack_maps: stack_maps.into(),
+                block_profile_counters: block_profile_counters.into(),
+                inlined_calls: inlined_calls.into(),
             },
             Box::new(CompiledFunction {
                 body: code_buf,
@@ -344,6 +520,51 @@ impl wasmtime_environ::Compiler for Compiler {
             }),
         ))
     }
+}
+
+impl wasmtime_environ::Compiler for Compiler {
+    fn compile_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        func_index: DefinedFuncIndex,
+        input: FunctionBodyData<'_>,
+        tunables: &Tunables,
+        types: &ModuleTypes,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let func = self.translate(translation, func_index, input, tunables, types)?;
+        self.compile_translated(translation, func_index, func, None, tunables)
+    }
+
+    fn translate_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        index: DefinedFuncIndex,
+        data: FunctionBodyData<'_>,
+        tunables: &Tunables,
+        types: &ModuleTypes,
+    ) -> Result<Box<dyn Any + Send + Sync>, CompileError> {
+        Ok(Box::new(self.translate(
+            translation,
+            index,
+            data,
+            tunables,
+            types,
+        )?))
+    }
+
+    fn compile_translated_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        index: DefinedFuncIndex,
+        translated: &PrimaryMap<DefinedFuncIndex, Box<dyn Any + Send + Sync>>,
+        tunables: &Tunables,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let func = translated[index]
+            .downcast_ref::<TranslatedFunction>()
+            .unwrap()
+            .clone();
+        self.compile_translated(translation, index, func, Some(translated), tunables)
+    }
 
     fn compile_host_to_wasm_trampoline(
         &self,
@@ -625,7 +846,7 @@ impl Compiler {
 
         // The wasm signature we're calling in this trampoline has the actual
         // ABI of the function signature described by `ty`
-        let wasm_signature = indirect_signature(isa, ty);
+        let wasm_signature = indirect_signature(isa, self.tail_calls, ty);
 
         // The host signature has the `VMTrampoline` signature where the ABI is
         // fixed.
@@ -740,7 +961,7 @@ impl Compiler {
     ) -> Result<CompiledFunction, CompileError> {
         let isa = &*self.isa;
         let pointer_type = isa.pointer_type();
-        let wasm_signature = indirect_signature(isa, ty);
+        let wasm_signature = indirect_signature(isa, self.tail_calls, ty);
         let mut host_signature = blank_sig(isa, wasmtime_call_conv(isa));
         // The host signature has an added parameter for the `values_vec`
         // input/output buffer in addition to the size of the buffer, in units
@@ -923,6 +1144,171 @@ impl Compiler {
     }
 }
 
+impl CompiledFunction {
+    /// Creates a `CompiledFunction` out of the machine code of a function that
+    /// wasn't compiled by Cranelift, so that it can be emitted through
+    /// `append_code` along with the trampolines compiled by Cranelift.
+    ///
+    /// `body` is the range of the function's body in the original wasm
+    /// module. Direct calls to wasm functions are expected to be relocated
+    /// through a `UserExternalNameRef` whose index is the callee's
+    /// `FuncIndex`.
+    pub fn from_mach_buffer(
+        buffer: &MachBufferFinalized<Final>,
+        alignment: u32,
+        body: &Range<usize>,
+        tunables: &Tunables,
+    ) -> Self {
+        let relocations = buffer
+            .relocs()
+            .iter()
+            .map(|reloc| {
+                let reloc_target = match reloc.name {
+                    ExternalName::User(name) => {
+                        RelocationTarget::UserFunc(FuncIndex::from_u32(name.as_u32()))
+                    }
+                    ExternalName::LibCall(libcall) => RelocationTarget::LibCall(libcall),
+                    _ => panic!("unrecognized external name"),
+                };
+                Relocation {
+                    reloc: reloc.kind,
+                    reloc_target,
+                    offset: reloc.offset,
+                    addend: reloc.addend,
+                }
+            })
+            .collect();
+        let traps = buffer.traps().iter().map(mach_trap_to_trap).collect();
+        let body_len = u32::try_from(buffer.data().len()).unwrap();
+        let address_map = Compiler::get_function_address_map(
+            buffer.get_srclocs_sorted(),
+            body,
+            body_len,
+            tunables,
+        );
+
+        CompiledFunction {
+            body: buffer.data().to_vec(),
+            relocations,
+            traps,
+            alignment,
+            address_map,
+            ..Default::default()
+        }
+    }
+}
+
+/// The source locations of the code inlined into a function.
+///
+/// Copies of the same callee share the source locations of the callee, so
+/// before the function is compiled they're replaced by tags past the end of
+/// the module, which also identify the copy that the code belongs to.
+struct InlinedSrclocs {
+    first_tag: u32,
+    tags: Vec<(usize, ir::SourceLoc)>,
+}
+
+impl InlinedSrclocs {
+    /// Tags the source locations of each copy in `calls` within `func`, a
+    /// function of the module `wasm`.
+    fn new(func: &mut Function, calls: &[inline::InlinedCall], wasm: &[u8]) -> Self {
+        let mut srclocs = InlinedSrclocs {
+            first_tag: u32::try_from(wasm.len()).unwrap_or(u32::MAX),
+            tags: Vec::new(),
+        };
+        let mut tags = HashMap::new();
+        for (copy, call) in calls.iter().enumerate() {
+            for &block in &call.blocks {
+                let insts = func.layout.block_insts(block).collect::<Vec<_>>();
+                for inst in insts {
+                    let srcloc = func.srcloc(inst);
+                    if srcloc.is_default() {
+                        continue;
+                    }
+                    let tag = match tags.get(&(copy, srcloc)) {
+                        Some(&tag) => tag,
+                        None => {
+                            // Past the range of tags, the original source
+                            // location is kept.
+                            let tag = u32::try_from(srclocs.tags.len())
+                                .ok()
+                                .and_then(|index| srclocs.first_tag.checked_add(index))
+                                .filter(|&tag| !ir::SourceLoc::new(tag).is_default());
+                            let tag = match tag {
+                                Some(tag) => tag,
+                                None => continue,
+                            };
+                            srclocs.tags.push((copy, srcloc));
+                            tags.insert((copy, srcloc), tag);
+                            tag
+                        }
+                    };
+                    func.set_srcloc(inst, ir::SourceLoc::new(tag));
+                }
+            }
+        }
+        srclocs
+    }
+
+    /// Returns the copy that the code at `loc` belongs to, if any, along with
+    /// the source location which `loc` stands for.
+    fn resolve(&self, loc: ir::SourceLoc) -> (Option<usize>, ir::SourceLoc) {
+        let tag = loc
+            .bits()
+            .checked_sub(self.first_tag)
+            .and_then(|index| self.tags.get(usize::try_from(index).ok()?));
+        match tag {
+            Some(&(copy, loc)) => (Some(copy), loc),
+            None => (None, loc),
+        }
+    }
+}
+
+/// Writes `contents`, the textual form of `func_index` at some stage of
+/// compilation, into the file `wasm_func_{index}.{stage}` within `dir`.
+///
+/// Functions of modules defined within a component are instead written to
+/// `wasm_module_{module}_func_{index}.{stage}`.
+fn write_clif_stage(
+    dir: &Path,
+    translation: &ModuleTranslation<'_>,
+    func_index: FuncIndex,
+    stage: &str,
+    contents: &str,
+) -> Result<(), CompileError> {
+    let name = match translation.component_module_index {
+        Some(module) => format!("wasm_module_{}_func_{}", module, func_index.as_u32()),
+        None => format!("wasm_func_{}", func_index.as_u32()),
+    };
+    let path = dir.join(format!("{}.{}", name, stage));
+    std::fs::write(&path, contents)
+        .map_err(|e| CompileError::Codegen(format!("failed to write `{}`: {}", path.display(), e)))
+}
+
+/// Prefixes each line of the VCode disassembly `text` with the source location
+/// of its instruction, if any, similarly to how CLIF instructions are printed.
+fn annotate_vcode(text: &str, srclocs: &[(u32, ir::SourceLoc)]) -> String {
+    use std::fmt::Write;
+
+    let mut result = String::with_capacity(text.len() * 2);
+    let mut srclocs = srclocs.iter().peekable();
+    let mut srcloc = ir::SourceLoc::default();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        while let Some((_, loc)) = srclocs.next_if(|(o, _)| *o as usize <= offset) {
+            srcloc = *loc;
+        }
+        offset += line.len();
+        let prefix = if srcloc.is_default() {
+            String::new()
+        } else {
+            srcloc.to_string()
+        };
+        writeln!(result, "{:<10}{}", prefix, line.trim_end()).unwrap();
+    }
+    result
+}
+
 // Collects an iterator of `InstructionAddressMap` into a `Vec` for insertion
 // into a `FunctionAddressMap`. This will automatically coalesce adjacent
 // instructions which map to the same original source position.
@@ -33,7 +33,7 @@ impl ComponentCompiler for Compiler {
 
         context.func = ir::Function::with_name_signature(
             ir::UserFuncName::user(0, 0),
-            crate::indirect_signature(isa, ty),
+            crate::indirect_signature(isa, self.tail_calls, ty),
         );
 
         let mut builder = FunctionBuilder::new(&mut context.func, func_translator.context());
@@ -168,7 +168,7 @@ impl ComponentCompiler for Compiler {
         } = self.take_context();
         context.func = ir::Function::with_name_signature(
             ir::UserFuncName::user(0, 0),
-            crate::indirect_signature(isa, ty),
+            crate::indirect_signature(isa, self.tail_calls, ty),
         );
         let mut builder = FunctionBuilder::new(&mut context.func, func_translator.context());
         let block0 = builder.create_block();
@@ -210,7 +210,7 @@ impl ComponentCompiler for Compiler {
 
         context.func = ir::Function::with_name_signature(
             ir::UserFuncName::user(0, 0),
-            crate::indirect_signature(isa, ty),
+            crate::indirect_signature(isa, self.tail_calls, ty),
         );
 
         let mut builder = FunctionBuilder::new(&mut context.func, func_translator.context());
@@ -5,6 +5,7 @@ use cranelift_codegen::ir::immediates::{Imm64, Offset32, Uimm64};
 use cranelift_codegen::ir::types::*;
 use cranelift_codegen::ir::{AbiParam, ArgumentPurpose, Function, InstBuilder, Signature};
 use cranelift_codegen::isa::{self, TargetFrontendConfig, TargetIsa};
+use cranelift_codegen::settings;
 use cranelift_entity::{EntityRef, PrimaryMap};
 use cranelift_frontend::FunctionBuilder;
 use cranelift_frontend::Variable;
@@ -14,10 +15,12 @@ use cranelift_wasm::{
 };
 use std::convert::TryFrom;
 use std::mem;
-use wasmparser::Operator;
+use wasmparser::{MemArg, Operator};
 use wasmtime_environ::{
-    BuiltinFunctionIndex, MemoryPlan, MemoryStyle, Module, ModuleTranslation, ModuleTypes, PtrSize,
-    TableStyle, Tunables, VMOffsets, WASM_PAGE_SIZE,
+    AllocatorFunction, BuiltinFunctionIndex, DebugValueKind, MemoryPlan, MemoryStyle, Module,
+    ModuleTranslation, ModuleTypes, PtrSize, TableStyle, Tunables, VMOffsets,
+    DEBUG_FRAME_VALUE_OFFSET, DEBUG_FRAME_VALUE_SIZE, DEBUG_HOOK_ENTER, DEBUG_HOOK_EXIT,
+    DEBUG_HOOK_STEP, WASM_PAGE_SIZE,
 };
 use wasmtime_environ::{FUNCREF_INIT_BIT, FUNCREF_MASK};
 
@@ -124,6 +127,10 @@ pub struct FuncEnvironment<'module_environment> {
 
     tunables: &'module_environment Tunables,
 
+    /// Whether wasm functions use the `tail` calling convention, see
+    /// `crate::wasm_call_conv`.
+    tail_calls: bool,
+
     /// A function-local variable which stores the cached value of the amount of
     /// fuel remaining to execute. If used this is modified frequently so it's
     /// stored locally as a variable instead of always referenced from the field
@@ -150,6 +157,23 @@ pub struct FuncEnvironment<'module_environment> {
     epoch_ptr_var: cranelift_frontend::Variable,
 
     fuel_consumed: i64,
+
+    /// The number of wasm locals, including parameters, of the function
+    /// being translated.
+    num_locals: usize,
+
+    /// When guest debugging is enabled, the stack slot in which the values of
+    /// the locals and the value stack are stored for the debug hook, along
+    /// with the index of the function being translated.
+    debug_frame: Option<(ir::StackSlot, u32)>,
+
+    /// The maximum number of values stored in `debug_frame` so far, which
+    /// determines its final size.
+    debug_frame_values: u32,
+
+    /// When the memory sanitizer is enabled, the allocator function that the
+    /// function being translated implements, if any.
+    sanitizer_allocator: Option<AllocatorFunction>,
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -158,6 +182,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         translation: &'module_environment ModuleTranslation<'module_environment>,
         types: &'module_environment ModuleTypes,
         tunables: &'module_environment Tunables,
+        tail_calls: bool,
     ) -> Self {
         let builtin_function_signatures = BuiltinFunctionSignatures::new(
             isa.pointer_type(),
@@ -178,6 +203,7 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             builtin_function_signatures,
             offsets: VMOffsets::new(isa.pointer_bytes(), &translation.module),
             tunables,
+            tail_calls,
             fuel_var: Variable::new(0),
             epoch_deadline_var: Variable::new(0),
             epoch_ptr_var: Variable::new(0),
@@ -186,6 +212,11 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             // Start with at least one fuel being consumed because even empty
             // functions should consume at least some fuel.
             fuel_consumed: 1,
+
+            num_locals: 0,
+            debug_frame: None,
+            debug_frame_values: 0,
+            sanitizer_allocator: None,
         }
     }
 
@@ -707,6 +738,220 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         builder.switch_to_block(continuation_block);
     }
 
+    fn debug_function_entry(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let func_index = match &builder.func.name {
+            ir::UserFuncName::User(name) => name.index,
+            ir::UserFuncName::Testcase(_) => unreachable!("wasm functions are named by index"),
+        };
+        // The size of the slot isn't known until all of the function has been
+        // translated, see `debug_function_exit`.
+        let slot = builder
+            .create_sized_stack_slot(ir::StackSlotData::new(ir::StackSlotKind::ExplicitSlot, 0));
+        self.debug_frame = Some((slot, func_index));
+        self.debug_frame_values = 0;
+        self.debug_hook(builder, DEBUG_HOOK_ENTER, &[]);
+    }
+
+    fn debug_function_exit(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let (slot, _) = self.debug_frame.unwrap();
+        let values = self.debug_frame_values.max(1);
+        builder.func.sized_stack_slots[slot].size = values * DEBUG_FRAME_VALUE_SIZE;
+    }
+
+    /// Calls the `debug_hook` builtin with the given `kind`.
+    ///
+    /// Before each instruction the current values of the locals and of the
+    /// value stack, `stack`, are first stored into the debug frame, where the
+    /// debugger can inspect them for as long as this function is executing.
+    fn debug_hook(&mut self, builder: &mut FunctionBuilder<'_>, kind: u32, stack: &[ir::Value]) {
+        let (slot, func_index) = self.debug_frame.unwrap();
+        let pointer_type = self.pointer_type();
+        let frame = builder.ins().stack_addr(pointer_type, slot, 0);
+
+        let mut values = Vec::new();
+        if kind == DEBUG_HOOK_STEP {
+            values.extend((0..self.num_locals).map(|i| builder.use_var(Variable::new(i))));
+            values.extend_from_slice(stack);
+        }
+        let flags = ir::MemFlags::new().with_notrap();
+        for (i, value) in values.iter().enumerate() {
+            let value_kind = match builder.func.dfg.value_type(*value) {
+                I32 => DebugValueKind::I32,
+                I64 => DebugValueKind::I64,
+                F32 => DebugValueKind::F32,
+                F64 => DebugValueKind::F64,
+                R32 | R64 => DebugValueKind::Ref,
+                ty if ty.is_vector() && ty.bits() == 128 => DebugValueKind::V128,
+                ty => unreachable!("unexpected wasm value type {ty}"),
+            };
+            let offset = i32::try_from(i as u32 * DEBUG_FRAME_VALUE_SIZE).unwrap();
+            let value_kind = builder.ins().iconst(I32, value_kind as i64);
+            builder.ins().store(flags, value_kind, frame, offset);
+            builder.ins().store(
+                flags,
+                *value,
+                frame,
+                offset + DEBUG_FRAME_VALUE_OFFSET as i32,
+            );
+        }
+        let num_values = u32::try_from(values.len()).unwrap();
+        self.debug_frame_values = self.debug_frame_values.max(num_values);
+
+        let debug_hook_sig = self.builtin_function_signatures.debug_hook(builder.func);
+        let (vmctx, debug_hook) = self.translate_load_builtin_function_address(
+            &mut builder.cursor(),
+            BuiltinFunctionIndex::debug_hook(),
+        );
+        let kind = builder.ins().iconst(I32, i64::from(kind));
+        let func_index = builder.ins().iconst(I32, i64::from(func_index));
+        let offset = i64::from(builder.srcloc().bits());
+        let offset = builder.ins().iconst(I32, offset);
+        let num_locals = if values.is_empty() {
+            0
+        } else {
+            self.num_locals
+        };
+        let num_locals = builder.ins().iconst(I32, num_locals as i64);
+        let num_stack = if values.is_empty() { 0 } else { stack.len() };
+        let num_stack = builder.ins().iconst(I32, num_stack as i64);
+        builder.ins().call_indirect(
+            debug_hook_sig,
+            debug_hook,
+            &[
+                vmctx, kind, func_index, offset, frame, num_locals, num_stack,
+            ],
+        );
+    }
+
+    /// Calls the `sanitizer_check` builtin for the access of `len` bytes at
+    /// `addr`, plus the static `offset`, of the default memory.
+    fn sanitizer_check(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        addr: ir::Value,
+        offset: u64,
+        len: ir::Value,
+        store: bool,
+    ) {
+        let addr =
+            self.cast_memory_index_to_i64(&mut builder.cursor(), addr, MemoryIndex::from_u32(0));
+        let addr = builder.ins().iadd_imm(addr, offset as i64);
+        let store = builder.ins().iconst(I32, i64::from(store));
+        let sanitizer_check_sig = self
+            .builtin_function_signatures
+            .sanitizer_check(builder.func);
+        let (vmctx, sanitizer_check) = self.translate_load_builtin_function_address(
+            &mut builder.cursor(),
+            BuiltinFunctionIndex::sanitizer_check(),
+        );
+        builder.ins().call_indirect(
+            sanitizer_check_sig,
+            sanitizer_check,
+            &[vmctx, addr, len, store],
+        );
+    }
+
+    /// Checks the accesses to the default memory that `op` is about to
+    /// perform, whose operands are at the top of `stack`.
+    fn sanitizer_before_op(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        op: &Operator,
+        stack: &[ir::Value],
+    ) {
+        let operand = |depth: usize| stack[stack.len() - 1 - depth];
+        match *op {
+            Operator::MemoryFill { mem: 0 } | Operator::MemoryInit { mem: 0, .. } => {
+                let len = self.sanitizer_len(builder, operand(0));
+                self.sanitizer_check(builder, operand(2), 0, len, true);
+            }
+            Operator::MemoryCopy { dst_mem, src_mem } => {
+                if src_mem == 0 {
+                    let len = self.sanitizer_len(builder, operand(0));
+                    self.sanitizer_check(builder, operand(1), 0, len, false);
+                }
+                if dst_mem == 0 {
+                    let len = self.sanitizer_len(builder, operand(0));
+                    self.sanitizer_check(builder, operand(2), 0, len, true);
+                }
+            }
+            _ => {
+                if let Some((memarg, depth, len, store)) = memory_access(op) {
+                    if memarg.memory == 0 {
+                        let len = builder.ins().iconst(I64, i64::from(len));
+                        self.sanitizer_check(builder, operand(depth), memarg.offset, len, store);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts the length operand of a bulk memory instruction on the
+    /// default memory to the `i64` expected by the sanitizer.
+    fn sanitizer_len(&self, builder: &mut FunctionBuilder<'_>, len: ir::Value) -> ir::Value {
+        self.cast_memory_index_to_i64(&mut builder.cursor(), len, MemoryIndex::from_u32(0))
+    }
+
+    /// Calls `sanitizer_allocator_entry` on entry to an allocator function,
+    /// passing its parameters.
+    fn sanitizer_allocator_entry(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        function: AllocatorFunction,
+    ) {
+        let mut args = [None, None];
+        let num_params = match function {
+            AllocatorFunction::Malloc | AllocatorFunction::Free => 1,
+            AllocatorFunction::Calloc | AllocatorFunction::Realloc => 2,
+        };
+        for (i, arg) in args.iter_mut().take(num_params).enumerate() {
+            let param = builder.use_var(Variable::new(i));
+            *arg = Some(self.cast_memory_index_to_i64(
+                &mut builder.cursor(),
+                param,
+                MemoryIndex::from_u32(0),
+            ));
+        }
+        let args = args.map(|arg| arg.unwrap_or_else(|| builder.ins().iconst(I64, 0)));
+        let function = builder.ins().iconst(I32, function as i64);
+        let sig = self
+            .builtin_function_signatures
+            .sanitizer_allocator_entry(builder.func);
+        let (vmctx, entry) = self.translate_load_builtin_function_address(
+            &mut builder.cursor(),
+            BuiltinFunctionIndex::sanitizer_allocator_entry(),
+        );
+        builder
+            .ins()
+            .call_indirect(sig, entry, &[vmctx, function, args[0], args[1]]);
+    }
+
+    /// Calls `sanitizer_allocator_exit` as an allocator function returns,
+    /// with its results at the top of `stack`.
+    fn sanitizer_allocator_exit(
+        &mut self,
+        builder: &mut FunctionBuilder<'_>,
+        function: AllocatorFunction,
+        stack: &[ir::Value],
+    ) {
+        let result = match function {
+            AllocatorFunction::Free => builder.ins().iconst(I64, 0),
+            _ => self.cast_memory_index_to_i64(
+                &mut builder.cursor(),
+                *stack.last().unwrap(),
+                MemoryIndex::from_u32(0),
+            ),
+        };
+        let sig = self
+            .builtin_function_signatures
+            .sanitizer_allocator_exit(builder.func);
+        let (vmctx, exit) = self.translate_load_builtin_function_address(
+            &mut builder.cursor(),
+            BuiltinFunctionIndex::sanitizer_allocator_exit(),
+        );
+        builder.ins().call_indirect(sig, exit, &[vmctx, result]);
+    }
+
     fn memory_index_type(&self, index: MemoryIndex) -> ir::Type {
         if self.module.memory_plans[index].memory.memory64 {
             I64
@@ -813,6 +1058,154 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         builder.switch_to_block(continuation_block);
         result_param
     }
+
+    /// Prepare an indirect call through the table element `callee`, returning
+    /// the address of the function to call and the arguments to pass to it.
+    fn prepare_call_indirect(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        table_index: TableIndex,
+        table: ir::Table,
+        ty_index: TypeIndex,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> (ir::Value, Vec<ir::Value>) {
+        let pointer_type = self.pointer_type();
+
+        // Get the funcref pointer from the table.
+        let funcref_ptr = self.get_or_init_funcref_table_elem(builder, table_index, table, callee);
+
+        // Check for whether the table element is null, and trap if so.
+        builder
+            .ins()
+            .trapz(funcref_ptr, ir::TrapCode::IndirectCallToNull);
+
+        // Dereference the funcref pointer to get the function address.
+        let mem_flags = ir::MemFlags::trusted();
+        let func_addr = builder.ins().load(
+            pointer_type,
+            mem_flags,
+            funcref_ptr,
+            i32::from(self.offsets.ptr.vmcaller_checked_func_ref_func_ptr()),
+        );
+
+        // If necessary, check the signature.
+        match self.module.table_plans[table_index].style {
+            TableStyle::CallerChecksSignature => {
+                let sig_id_size = self.offsets.size_of_vmshared_signature_index();
+                let sig_id_type = Type::int(u16::from(sig_id_size) * 8).unwrap();
+                let vmctx = self.vmctx(builder.func);
+                let base = builder.ins().global_value(pointer_type, vmctx);
+
+                // Load the caller ID. This requires loading the
+                // `*mut VMCallerCheckedFuncRef` base pointer from `VMContext`
+                // and then loading, based on `SignatureIndex`, the
+                // corresponding entry.
+                let mem_flags = ir::MemFlags::trusted().with_readonly();
+                let signatures = builder.ins().load(
+                    pointer_type,
+                    mem_flags,
+                    base,
+                    i32::try_from(self.offsets.vmctx_signature_ids_array()).unwrap(),
+                );
+                let sig_index = self.module.types[ty_index].unwrap_function();
+                let offset =
+                    i32::try_from(sig_index.as_u32().checked_mul(sig_id_type.bytes()).unwrap())
+                        .unwrap();
+                let caller_sig_id = builder
+                    .ins()
+                    .load(sig_id_type, mem_flags, signatures, offset);
+
+                // Load the callee ID.
+                let mem_flags = ir::MemFlags::trusted();
+                let callee_sig_id = builder.ins().load(
+                    sig_id_type,
+                    mem_flags,
+                    funcref_ptr,
+                    i32::from(self.offsets.ptr.vmcaller_checked_func_ref_type_index()),
+                );
+
+                // Check that they match.
+                let cmp = builder
+                    .ins()
+                    .icmp(IntCC::Equal, callee_sig_id, caller_sig_id);
+                builder.ins().trapz(cmp, ir::TrapCode::BadSignature);
+            }
+        }
+
+        let mut real_call_args = Vec::with_capacity(call_args.len() + 2);
+        let caller_vmctx = builder
+            .func
+            .special_param(ArgumentPurpose::VMContext)
+            .unwrap();
+
+        // First append the callee vmctx address.
+        let vmctx = builder.ins().load(
+            pointer_type,
+            mem_flags,
+            funcref_ptr,
+            i32::from(self.offsets.ptr.vmcaller_checked_func_ref_vmctx()),
+        );
+        real_call_args.push(vmctx);
+        real_call_args.push(caller_vmctx);
+
+        // Then append the regular call arguments.
+        real_call_args.extend_from_slice(call_args);
+
+        (func_addr, real_call_args)
+    }
+
+    /// Prepare a direct call to `callee_index`, returning the arguments to pass
+    /// to it and, if the function is imported, the address to call it through.
+    fn prepare_call(
+        &mut self,
+        pos: &mut FuncCursor<'_>,
+        callee_index: FuncIndex,
+        call_args: &[ir::Value],
+    ) -> (Option<ir::Value>, Vec<ir::Value>) {
+        let mut real_call_args = Vec::with_capacity(call_args.len() + 2);
+        let caller_vmctx = pos.func.special_param(ArgumentPurpose::VMContext).unwrap();
+
+        // Handle direct calls to locally-defined functions.
+        if !self.module.is_imported_function(callee_index) {
+            // First append the callee vmctx address, which is the same as the caller vmctx in
+            // this case.
+            real_call_args.push(caller_vmctx);
+
+            // Then append the caller vmctx address.
+            real_call_args.push(caller_vmctx);
+
+            // Then append the regular call arguments.
+            real_call_args.extend_from_slice(call_args);
+
+            return (None, real_call_args);
+        }
+
+        // Handle direct calls to imported functions. We use an indirect call
+        // so that we don't have to patch the code at runtime.
+        let pointer_type = self.pointer_type();
+        let vmctx = self.vmctx(&mut pos.func);
+        let base = pos.ins().global_value(pointer_type, vmctx);
+
+        let mem_flags = ir::MemFlags::trusted();
+
+        // Load the callee address.
+        let body_offset =
+            i32::try_from(self.offsets.vmctx_vmfunction_import_body(callee_index)).unwrap();
+        let func_addr = pos.ins().load(pointer_type, mem_flags, base, body_offset);
+
+        // First append the callee vmctx address.
+        let vmctx_offset =
+            i32::try_from(self.offsets.vmctx_vmfunction_import_vmctx(callee_index)).unwrap();
+        let vmctx = pos.ins().load(pointer_type, mem_flags, base, vmctx_offset);
+        real_call_args.push(vmctx);
+        real_call_args.push(caller_vmctx);
+
+        // Then append the regular call arguments.
+        real_call_args.extend_from_slice(call_args);
+
+        (Some(func_addr), real_call_args)
+    }
 }
 
 impl<'module_environment> TargetEnvironment for FuncEnvironment<'module_environment> {
@@ -841,6 +1234,7 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
     }
 
     fn after_locals(&mut self, num_locals: usize) {
+        self.num_locals = num_locals;
         self.vmruntime_limits_ptr = Variable::new(num_locals);
         self.fuel_var = Variable::new(num_locals + 1);
         self.epoch_deadline_var = Variable::new(num_locals + 2);
@@ -1536,7 +1930,7 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         index: TypeIndex,
     ) -> WasmResult<ir::SigRef> {
         let index = self.module.types[index].unwrap_function();
-        let sig = crate::indirect_signature(self.isa, &self.types[index]);
+        let sig = crate::indirect_signature(self.isa, self.tail_calls, &self.types[index]);
         Ok(func.import_signature(sig))
     }
 
@@ -1545,7 +1939,13 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         func: &mut ir::Function,
         index: FuncIndex,
     ) -> WasmResult<ir::FuncRef> {
-        let sig = crate::func_signature(self.isa, self.translation, self.types, index);
+        let sig = crate::func_signature(
+            self.isa,
+            self.tail_calls,
+            self.translation,
+            self.types,
+            index,
+        );
         let signature = func.import_signature(sig);
         let name =
             ir::ExternalName::User(func.declare_imported_user_function(ir::UserExternalName {
@@ -1584,88 +1984,8 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         callee: ir::Value,
         call_args: &[ir::Value],
     ) -> WasmResult<ir::Inst> {
-        let pointer_type = self.pointer_type();
-
-        // Get the funcref pointer from the table.
-        let funcref_ptr = self.get_or_init_funcref_table_elem(builder, table_index, table, callee);
-
-        // Check for whether the table element is null, and trap if so.
-        builder
-            .ins()
-            .trapz(funcref_ptr, ir::TrapCode::IndirectCallToNull);
-
-        // Dereference the funcref pointer to get the function address.
-        let mem_flags = ir::MemFlags::trusted();
-        let func_addr = builder.ins().load(
-            pointer_type,
-            mem_flags,
-            funcref_ptr,
-            i32::from(self.offsets.ptr.vmcaller_checked_func_ref_func_ptr()),
-        );
-
-        // If necessary, check the signature.
-        match self.module.table_plans[table_index].style {
-            TableStyle::CallerChecksSignature => {
-                let sig_id_size = self.offsets.size_of_vmshared_signature_index();
-                let sig_id_type = Type::int(u16::from(sig_id_size) * 8).unwrap();
-                let vmctx = self.vmctx(builder.func);
-                let base = builder.ins().global_value(pointer_type, vmctx);
-
-                // Load the caller ID. This requires loading the
-                // `*mut VMCallerCheckedFuncRef` base pointer from `VMContext`
-                // and then loading, based on `SignatureIndex`, the
-                // corresponding entry.
-                let mem_flags = ir::MemFlags::trusted().with_readonly();
-                let signatures = builder.ins().load(
-                    pointer_type,
-                    mem_flags,
-                    base,
-                    i32::try_from(self.offsets.vmctx_signature_ids_array()).unwrap(),
-                );
-                let sig_index = self.module.types[ty_index].unwrap_function();
-                let offset =
-                    i32::try_from(sig_index.as_u32().checked_mul(sig_id_type.bytes()).unwrap())
-                        .unwrap();
-                let caller_sig_id = builder
-                    .ins()
-                    .load(sig_id_type, mem_flags, signatures, offset);
-
-                // Load the callee ID.
-                let mem_flags = ir::MemFlags::trusted();
-                let callee_sig_id = builder.ins().load(
-                    sig_id_type,
-                    mem_flags,
-                    funcref_ptr,
-                    i32::from(self.offsets.ptr.vmcaller_checked_func_ref_type_index()),
-                );
-
-                // Check that they match.
-                let cmp = builder
-                    .ins()
-                    .icmp(IntCC::Equal, callee_sig_id, caller_sig_id);
-                builder.ins().trapz(cmp, ir::TrapCode::BadSignature);
-            }
-        }
-
-        let mut real_call_args = Vec::with_capacity(call_args.len() + 2);
-        let caller_vmctx = builder
-            .func
-            .special_param(ArgumentPurpose::VMContext)
-            .unwrap();
-
-        // First append the callee vmctx address.
-        let vmctx = builder.ins().load(
-            pointer_type,
-            mem_flags,
-            funcref_ptr,
-            i32::from(self.offsets.ptr.vmcaller_checked_func_ref_vmctx()),
-        );
-        real_call_args.push(vmctx);
-        real_call_args.push(caller_vmctx);
-
-        // Then append the regular call arguments.
-        real_call_args.extend_from_slice(call_args);
-
+        let (func_addr, real_call_args) =
+            self.prepare_call_indirect(builder, table_index, table, ty_index, callee, call_args);
         Ok(builder
             .ins()
             .call_indirect(sig_ref, func_addr, &real_call_args))
@@ -1678,49 +1998,52 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         callee: ir::FuncRef,
         call_args: &[ir::Value],
     ) -> WasmResult<ir::Inst> {
-        let mut real_call_args = Vec::with_capacity(call_args.len() + 2);
-        let caller_vmctx = pos.func.special_param(ArgumentPurpose::VMContext).unwrap();
-
-        // Handle direct calls to locally-defined functions.
-        if !self.module.is_imported_function(callee_index) {
-            // First append the callee vmctx address, which is the same as the caller vmctx in
-            // this case.
-            real_call_args.push(caller_vmctx);
-
-            // Then append the caller vmctx address.
-            real_call_args.push(caller_vmctx);
+        Ok(match self.prepare_call(&mut pos, callee_index, call_args) {
+            (None, real_call_args) => pos.ins().call(callee, &real_call_args),
+            (Some(func_addr), real_call_args) => {
+                let sig_ref = pos.func.dfg.ext_funcs[callee].signature;
+                pos.ins().call_indirect(sig_ref, func_addr, &real_call_args)
+            }
+        })
+    }
 
-            // Then append the regular call arguments.
-            real_call_args.extend_from_slice(call_args);
+    fn translate_return_call_indirect(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        table_index: TableIndex,
+        table: ir::Table,
+        ty_index: TypeIndex,
+        sig_ref: ir::SigRef,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        let (func_addr, real_call_args) =
+            self.prepare_call_indirect(builder, table_index, table, ty_index, callee, call_args);
+        builder
+            .ins()
+            .return_call_indirect(sig_ref, func_addr, &real_call_args);
+        Ok(())
+    }
 
-            return Ok(pos.ins().call(callee, &real_call_args));
+    fn translate_return_call(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        callee_index: FuncIndex,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        match self.prepare_call(&mut builder.cursor(), callee_index, call_args) {
+            (None, real_call_args) => {
+                builder.ins().return_call(callee, &real_call_args);
+            }
+            (Some(func_addr), real_call_args) => {
+                let sig_ref = builder.func.dfg.ext_funcs[callee].signature;
+                builder
+                    .ins()
+                    .return_call_indirect(sig_ref, func_addr, &real_call_args);
+            }
         }
-
-        // Handle direct calls to imported functions. We use an indirect call
-        // so that we don't have to patch the code at runtime.
-        let pointer_type = self.pointer_type();
-        let sig_ref = pos.func.dfg.ext_funcs[callee].signature;
-        let vmctx = self.vmctx(&mut pos.func);
-        let base = pos.ins().global_value(pointer_type, vmctx);
-
-        let mem_flags = ir::MemFlags::trusted();
-
-        // Load the callee address.
-        let body_offset =
-            i32::try_from(self.offsets.vmctx_vmfunction_import_body(callee_index)).unwrap();
-        let func_addr = pos.ins().load(pointer_type, mem_flags, base, body_offset);
-
-        // First append the callee vmctx address.
-        let vmctx_offset =
-            i32::try_from(self.offsets.vmctx_vmfunction_import_vmctx(callee_index)).unwrap();
-        let vmctx = pos.ins().load(pointer_type, mem_flags, base, vmctx_offset);
-        real_call_args.push(vmctx);
-        real_call_args.push(caller_vmctx);
-
-        // Then append the regular call arguments.
-        real_call_args.extend_from_slice(call_args);
-
-        Ok(pos.ins().call_indirect(sig_ref, func_addr, &real_call_args))
+        Ok(())
     }
 
     fn translate_memory_grow(
@@ -2092,6 +2415,23 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         if self.tunables.consume_fuel {
             self.fuel_before_op(op, builder, state.reachable());
         }
+        if self.tunables.guest_debug && state.reachable() {
+            self.debug_hook(builder, DEBUG_HOOK_STEP, state.value_stack());
+            match op {
+                Operator::Return
+                | Operator::ReturnCall { .. }
+                | Operator::ReturnCallIndirect { .. } => {
+                    self.debug_hook(builder, DEBUG_HOOK_EXIT, &[]);
+                }
+                _ => {}
+            }
+        }
+        if self.tunables.memory_sanitizer && state.reachable() {
+            self.sanitizer_before_op(builder, op, state.value_stack());
+            if let (Some(function), Operator::Return) = (self.sanitizer_allocator, op) {
+                self.sanitizer_allocator_exit(builder, function, state.value_stack());
+            }
+        }
         Ok(())
     }
 
@@ -2136,6 +2476,20 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         if self.tunables.epoch_interruption {
             self.epoch_function_entry(builder);
         }
+        if self.tunables.guest_debug {
+            self.debug_function_entry(builder);
+        }
+        if self.tunables.memory_sanitizer {
+            let func_index = match &builder.func.name {
+                ir::UserFuncName::User(name) => FuncIndex::from_u32(name.index),
+                ir::UserFuncName::Testcase(_) => unreachable!("wasm functions are named by index"),
+            };
+            self.sanitizer_allocator =
+                AllocatorFunction::for_function(self.module, self.types, func_index);
+            if let Some(function) = self.sanitizer_allocator {
+                self.sanitizer_allocator_entry(builder, function);
+            }
+        }
         Ok(())
     }
 
@@ -2147,6 +2501,17 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_function_exit(builder);
         }
+        if self.tunables.guest_debug {
+            if state.reachable() {
+                self.debug_hook(builder, DEBUG_HOOK_EXIT, &[]);
+            }
+            self.debug_function_exit(builder);
+        }
+        if let Some(function) = self.sanitizer_allocator {
+            if state.reachable() {
+                self.sanitizer_allocator_exit(builder, function, state.value_stack());
+            }
+        }
         Ok(())
     }
 
@@ -2165,4 +2530,132 @@ impl<'module_environment> cranelift_wasm::FuncEnvironment for FuncEnvironment<'m
     fn is_x86(&self) -> bool {
         self.isa.triple().architecture == target_lexicon::Architecture::X86_64
     }
+
+    fn eliminate_redundant_bounds_checks(&self) -> bool {
+        self.isa.flags().opt_level() != settings::OptLevel::None
+    }
+}
+
+/// Returns the memory access that the load, store or atomic `op` performs:
+/// its immediate, the depth of its address on the value stack, the number of
+/// bytes it accesses and whether it's a store. Read-modify-write operations
+/// count as stores.
+fn memory_access(op: &Operator) -> Option<(MemArg, usize, u32, bool)> {
+    Some(match *op {
+        Operator::I32Load8S { memarg }
+        | Operator::I32Load8U { memarg }
+        | Operator::I64Load8S { memarg }
+        | Operator::I64Load8U { memarg }
+        | Operator::I32AtomicLoad8U { memarg }
+        | Operator::I64AtomicLoad8U { memarg }
+        | Operator::V128Load8Splat { memarg } => (memarg, 0, 1, false),
+        Operator::I32Load16S { memarg }
+        | Operator::I32Load16U { memarg }
+        | Operator::I64Load16S { memarg }
+        | Operator::I64Load16U { memarg }
+        | Operator::I32AtomicLoad16U { memarg }
+        | Operator::I64AtomicLoad16U { memarg }
+        | Operator::V128Load16Splat { memarg } => (memarg, 0, 2, false),
+        Operator::I32Load { memarg }
+        | Operator::F32Load { memarg }
+        | Operator::I64Load32S { memarg }
+        | Operator::I64Load32U { memarg }
+        | Operator::I32AtomicLoad { memarg }
+        | Operator::I64AtomicLoad32U { memarg }
+        | Operator::V128Load32Splat { memarg }
+        | Operator::V128Load32Zero { memarg } => (memarg, 0, 4, false),
+        Operator::I64Load { memarg }
+        | Operator::F64Load { memarg }
+        | Operator::I64AtomicLoad { memarg }
+        | Operator::V128Load8x8S { memarg }
+        | Operator::V128Load8x8U { memarg }
+        | Operator::V128Load16x4S { memarg }
+        | Operator::V128Load16x4U { memarg }
+        | Operator::V128Load32x2S { memarg }
+        | Operator::V128Load32x2U { memarg }
+        | Operator::V128Load64Splat { memarg }
+        | Operator::V128Load64Zero { memarg } => (memarg, 0, 8, false),
+        Operator::V128Load { memarg } => (memarg, 0, 16, false),
+        Operator::V128Load8Lane { memarg, .. } => (memarg, 1, 1, false),
+        Operator::V128Load16Lane { memarg, .. } => (memarg, 1, 2, false),
+        Operator::V128Load32Lane { memarg, .. } => (memarg, 1, 4, false),
+        Operator::V128Load64Lane { memarg, .. } => (memarg, 1, 8, false),
+        Operator::I32Store8 { memarg }
+        | Operator::I64Store8 { memarg }
+        | Operator::I32AtomicStore8 { memarg }
+        | Operator::I64AtomicStore8 { memarg }
+        | Operator::V128Store8Lane { memarg, .. } => (memarg, 1, 1, true),
+        Operator::I32Store16 { memarg }
+        | Operator::I64Store16 { memarg }
+        | Operator::I32AtomicStore16 { memarg }
+        | Operator::I64AtomicStore16 { memarg }
+        | Operator::V128Store16Lane { memarg, .. } => (memarg, 1, 2, true),
+        Operator::I32Store { memarg }
+        | Operator::F32Store { memarg }
+        | Operator::I64Store32 { memarg }
+        | Operator::I32AtomicStore { memarg }
+        | Operator::I64AtomicStore32 { memarg }
+        | Operator::V128Store32Lane { memarg, .. } => (memarg, 1, 4, true),
+        Operator::I64Store { memarg }
+        | Operator::F64Store { memarg }
+        | Operator::I64AtomicStore { memarg }
+        | Operator::V128Store64Lane { memarg, .. } => (memarg, 1, 8, true),
+        Operator::V128Store { memarg } => (memarg, 1, 16, true),
+        Operator::I32AtomicRmw8AddU { memarg }
+        | Operator::I64AtomicRmw8AddU { memarg }
+        | Operator::I32AtomicRmw8SubU { memarg }
+        | Operator::I64AtomicRmw8SubU { memarg }
+        | Operator::I32AtomicRmw8AndU { memarg }
+        | Operator::I64AtomicRmw8AndU { memarg }
+        | Operator::I32AtomicRmw8OrU { memarg }
+        | Operator::I64AtomicRmw8OrU { memarg }
+        | Operator::I32AtomicRmw8XorU { memarg }
+        | Operator::I64AtomicRmw8XorU { memarg }
+        | Operator::I32AtomicRmw8XchgU { memarg }
+        | Operator::I64AtomicRmw8XchgU { memarg } => (memarg, 1, 1, true),
+        Operator::I32AtomicRmw16AddU { memarg }
+        | Operator::I64AtomicRmw16AddU { memarg }
+        | Operator::I32AtomicRmw16SubU { memarg }
+        | Operator::I64AtomicRmw16SubU { memarg }
+        | Operator::I32AtomicRmw16AndU { memarg }
+        | Operator::I64AtomicRmw16AndU { memarg }
+        | Operator::I32AtomicRmw16OrU { memarg }
+        | Operator::I64AtomicRmw16OrU { memarg }
+        | Operator::I32AtomicRmw16XorU { memarg }
+        | Operator::I64AtomicRmw16XorU { memarg }
+        | Operator::I32AtomicRmw16XchgU { memarg }
+        | Operator::I64AtomicRmw16XchgU { memarg } => (memarg, 1, 2, true),
+        Operator::I32AtomicRmwAdd { memarg }
+        | Operator::I64AtomicRmw32AddU { memarg }
+        | Operator::I32AtomicRmwSub { memarg }
+        | Operator::I64AtomicRmw32SubU { memarg }
+        | Operator::I32AtomicRmwAnd { memarg }
+        | Operator::I64AtomicRmw32AndU { memarg }
+        | Operator::I32AtomicRmwOr { memarg }
+        | Operator::I64AtomicRmw32OrU { memarg }
+        | Operator::I32AtomicRmwXor { memarg }
+        | Operator::I64AtomicRmw32XorU { memarg }
+        | Operator::I32AtomicRmwXchg { memarg }
+        | Operator::I64AtomicRmw32XchgU { memarg } => (memarg, 1, 4, true),
+        Operator::I64AtomicRmwAdd { memarg }
+        | Operator::I64AtomicRmwSub { memarg }
+        | Operator::I64AtomicRmwAnd { memarg }
+        | Operator::I64AtomicRmwOr { memarg }
+        | Operator::I64AtomicRmwXor { memarg }
+        | Operator::I64AtomicRmwXchg { memarg } => (memarg, 1, 8, true),
+        Operator::I32AtomicRmw8CmpxchgU { memarg } | Operator::I64AtomicRmw8CmpxchgU { memarg } => {
+            (memarg, 2, 1, true)
+        }
+        Operator::I32AtomicRmw16CmpxchgU { memarg }
+        | Operator::I64AtomicRmw16CmpxchgU { memarg } => (memarg, 2, 2, true),
+        Operator::I32AtomicRmwCmpxchg { memarg } | Operator::I64AtomicRmw32CmpxchgU { memarg } => {
+            (memarg, 2, 4, true)
+        }
+        Operator::I64AtomicRmwCmpxchg { memarg } => (memarg, 2, 8, true),
+        // `memory.atomic.notify` doesn't access the memory, but the waits
+        // load the value they compare with.
+        Operator::MemoryAtomicWait32 { memarg } => (memarg, 2, 4, false),
+        Operator::MemoryAtomicWait64 { memarg } => (memarg, 2, 8, false),
+        _ => return None,
+    })
 }
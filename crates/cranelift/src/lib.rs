@@ -99,6 +99,20 @@ fn wasmtime_call_conv(isa: &dyn TargetIsa) -> CallConv {
     }
 }
 
+/// Returns the calling convention used for wasm functions which may be called
+/// from outside of their module.
+///
+/// When the tail calls proposal is enabled this is the `tail` calling
+/// convention, whose functions pop their own stack arguments, so that a tail
+/// call can pass more stack arguments than its caller received.
+fn wasm_call_conv(isa: &dyn TargetIsa, tail_calls: bool) -> CallConv {
+    if tail_calls {
+        CallConv::Tail
+    } else {
+        wasmtime_call_conv(isa)
+    }
+}
+
 /// Appends the types of the `wasm` function signature into the `sig` signature
 /// provided.
 ///
@@ -129,8 +143,8 @@ fn value_type(isa: &dyn TargetIsa, ty: WasmType) -> ir::types::Type {
 /// indirectly call a wasm function it must be possibly exported somehow (e.g.
 /// this assumes the function target to call doesn't use the "fast" calling
 /// convention).
-fn indirect_signature(isa: &dyn TargetIsa, wasm: &WasmFuncType) -> ir::Signature {
-    let mut sig = blank_sig(isa, wasmtime_call_conv(isa));
+fn indirect_signature(isa: &dyn TargetIsa, tail_calls: bool, wasm: &WasmFuncType) -> ir::Signature {
+    let mut sig = blank_sig(isa, wasm_call_conv(isa, tail_calls));
     push_types(isa, &mut sig, wasm);
     return sig;
 }
@@ -142,12 +156,18 @@ fn indirect_signature(isa: &dyn TargetIsa, wasm: &WasmFuncType) -> ir::Signature
 /// use a custom theoretically faster calling convention instead of the default.
 fn func_signature(
     isa: &dyn TargetIsa,
+    tail_calls: bool,
     translation: &ModuleTranslation,
     types: &ModuleTypes,
     index: FuncIndex,
 ) -> ir::Signature {
     let func = &translation.module.functions[index];
     let call_conv = match translation.module.defined_func_index(index) {
+        // A tail call's callee must use the same calling convention as its
+        // caller, so all wasm functions use the same one when tail calls are
+        // enabled.
+        _ if tail_calls => wasm_call_conv(isa, tail_calls),
+
         // If this is a defined function in the module and it doesn't escape
         // then we can optimize this function to use the fastest calling
         // convention since it's purely an internal implementation detail of
@@ -0,0 +1,103 @@
+//! Block profiles of wasm functions, which record how many times the blocks
+//! of their compiled code were executed and are used to guide the layout of
+//! that code.
+//!
+//! Profiles are collected by compiling a module with
+//! `Tunables::block_profiling`, which makes each defined function count its
+//! block executions and taken branches in an array of `u64` counters. The
+//! `VMContext` of an instance points to a table, indexed by
+//! `DefinedFuncIndex`, of pointers to the counter array of each function, and
+//! `BlockProfileCounter` describes what each counter of an array counts.
+//!
+//! Blocks are numbered as in the Cranelift IR that the function is translated
+//! to, so a profile only applies to the same module compiled with the same
+//! translation-affecting settings.
+
+use crate::DefinedFuncIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a block profiling counter counts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlockProfileCounter {
+    /// The number of times the given block was executed.
+    Block(u32),
+    /// The number of times the edge from the given block to its successor
+    /// with the given index was taken.
+    Edge(u32, u32),
+}
+
+/// The block and edge execution counts of a function.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionBlockProfile {
+    /// `(block, count)` pairs.
+    pub blocks: Vec<(u32, u64)>,
+    /// `(block, successor index, count)` triples.
+    pub edges: Vec<(u32, u32, u64)>,
+}
+
+impl FunctionBlockProfile {
+    /// Builds the profile of a function from the values of its counters,
+    /// where `counters` describes what each of them counts.
+    ///
+    /// Counts are sorted by block, so that equal profiles compare equal.
+    pub fn from_counters(counters: &[BlockProfileCounter], values: &[u64]) -> Self {
+        let mut profile = FunctionBlockProfile::default();
+        for (counter, value) in counters.iter().zip(values) {
+            match *counter {
+                BlockProfileCounter::Block(block) => profile.blocks.push((block, *value)),
+                BlockProfileCounter::Edge(block, succ) => profile.edges.push((block, succ, *value)),
+            }
+        }
+        profile.blocks.sort_unstable();
+        profile.edges.sort_unstable();
+        profile
+    }
+
+    /// Adds the counts of `other` to this profile.
+    pub fn merge(&mut self, other: &FunctionBlockProfile) {
+        let mut blocks: BTreeMap<_, _> = self.blocks.iter().copied().collect();
+        for (block, count) in &other.blocks {
+            let total = blocks.entry(*block).or_insert(0u64);
+            *total = total.saturating_add(*count);
+        }
+        let mut edges: BTreeMap<_, _> = self
+            .edges
+            .iter()
+            .map(|(block, succ, count)| ((*block, *succ), *count))
+            .collect();
+        for (block, succ, count) in &other.edges {
+            let total = edges.entry((*block, *succ)).or_insert(0u64);
+            *total = total.saturating_add(*count);
+        }
+        self.blocks = blocks.into_iter().collect();
+        self.edges = edges
+            .into_iter()
+            .map(|((block, succ), count)| (block, succ, count))
+            .collect();
+    }
+}
+
+/// The block profiles of the defined functions of a module.
+///
+/// Functions without a profile are compiled as if profiling wasn't used.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModuleBlockProfile {
+    /// The profile of each profiled function.
+    pub funcs: BTreeMap<DefinedFuncIndex, FunctionBlockProfile>,
+}
+
+impl ModuleBlockProfile {
+    /// Returns whether this profile has no functions.
+    pub fn is_empty(&self) -> bool {
+        self.funcs.is_empty()
+    }
+
+    /// Adds the counts of `other` to this profile, e.g. to combine the
+    /// profiles of several runs.
+    pub fn merge(&mut self, other: &ModuleBlockProfile) {
+        for (index, func) in &other.funcs {
+            self.funcs.entry(*index).or_default().merge(func);
+        }
+    }
+}
@@ -51,6 +51,18 @@ macro_rules! foreach_builtin_function {
             out_of_gas(vmctx: vmctx);
             /// Invoked when we reach a new epoch.
             new_epoch(vmctx: vmctx) -> i64;
+            /// Invoked on function entry and exit and before each instruction
+            /// when guest debugging is enabled.
+            debug_hook(vmctx: vmctx, kind: i32, func: i32, offset: i32, frame: pointer, locals: i32, stack: i32);
+            /// Invoked before each access to the default linear memory when
+            /// the memory sanitizer is enabled.
+            sanitizer_check(vmctx: vmctx, addr: i64, len: i64, store: i32);
+            /// Invoked on entry to a function of the guest's allocator when
+            /// the memory sanitizer is enabled.
+            sanitizer_allocator_entry(vmctx: vmctx, function: i32, arg0: i64, arg1: i64);
+            /// Invoked when a function of the guest's allocator returns when
+            /// the memory sanitizer is enabled.
+            sanitizer_allocator_exit(vmctx: vmctx, result: i64);
         }
     };
 }
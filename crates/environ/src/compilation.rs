@@ -3,8 +3,8 @@
 
 use crate::obj;
 use crate::{
-    DefinedFuncIndex, FilePos, FuncIndex, FunctionBodyData, ModuleTranslation, ModuleTypes,
-    PrimaryMap, StackMap, Tunables, WasmError, WasmFuncType,
+    BlockProfileCounter, DefinedFuncIndex, FilePos, FuncIndex, FunctionBodyData, ModuleTranslation,
+    ModuleTypes, PrimaryMap, StackMap, Tunables, WasmError, WasmFuncType,
 };
 use anyhow::Result;
 use object::write::{Object, SymbolId};
@@ -14,6 +14,8 @@ use std::any::Any;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -24,6 +26,28 @@ use thiserror::Error;
 pub struct WasmFunctionInfo {
     pub start_srcloc: FilePos,
     pub stack_maps: Box<[StackMapInformation]>,
+    /// What each block profiling counter of the function counts, empty
+    /// unless `Tunables::block_profiling` is enabled.
+    pub block_profile_counters: Box<[BlockProfileCounter]>,
+    /// The calls which were inlined into the function, empty unless
+    /// `Tunables::inlining` is enabled.
+    pub inlined_calls: Box<[InlinedCall]>,
+}
+
+/// A call to another function of the module which was inlined into the
+/// function containing it.
+///
+/// The inlined code keeps the wasm offsets of the callee, so a function with
+/// several copies of the same callee tells them apart by their code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlinedCall {
+    /// The function which was called.
+    pub callee: FuncIndex,
+    /// The offset of the call instruction in the wasm module.
+    pub call_site: FilePos,
+    /// The ranges of code, relative to the start of the function containing
+    /// the call, of this copy of the callee.
+    pub code: Box<[Range<u32>]>,
 }
 
 /// Description of where a function is located in the text section of a
@@ -116,6 +140,24 @@ pub trait CompilerBuilder: Send + Sync + fmt::Debug {
     /// This will return an error if the compiler does not support incremental compilation.
     fn enable_incremental_compilation(&mut self, cache_store: Arc<dyn CacheStore>) -> Result<()>;
 
+    /// Enables writing the textual form of each compiled function at various
+    /// stages of compilation, such as CLIF, into files in the `path`
+    /// directory.
+    ///
+    /// This will return an error if the compiler does not support this.
+    fn clif_dir(&mut self, _path: &Path) -> Result<()> {
+        anyhow::bail!("clif output not supported");
+    }
+
+    /// Compiles WebAssembly functions with a calling convention in which the
+    /// callee pops its own stack arguments, so that WebAssembly tail calls can
+    /// pass more stack arguments than the calling function received.
+    ///
+    /// This will return an error if the compiler does not support tail calls.
+    fn enable_tail_calls(&mut self) -> Result<()> {
+        anyhow::bail!("tail calls not supported");
+    }
+
     /// Builds a new [`Compiler`] object from this configuration.
     fn build(&self) -> Result<Box<dyn Compiler>>;
 }
@@ -172,6 +214,35 @@ pub trait Compiler: Send + Sync {
         types: &ModuleTypes,
     ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError>;
 
+    /// Translates the function `index` within `translation` to the compiler's
+    /// intermediate representation, without compiling it.
+    ///
+    /// This is used instead of `compile_function` when `Tunables::inlining`
+    /// is enabled, so that all the functions of a module are translated
+    /// before any of them is compiled. The result is passed back to
+    /// `compile_translated_function`.
+    fn translate_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        index: DefinedFuncIndex,
+        data: FunctionBodyData<'_>,
+        tunables: &Tunables,
+        types: &ModuleTypes,
+    ) -> Result<Box<dyn Any + Send + Sync>, CompileError>;
+
+    /// Compiles the function `index` within `translation`, which was
+    /// translated with `translate_function`.
+    ///
+    /// The translations of all the defined functions of the module are in
+    /// `translated`, to inline calls to them into the function.
+    fn compile_translated_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        index: DefinedFuncIndex,
+        translated: &PrimaryMap<DefinedFuncIndex, Box<dyn Any + Send + Sync>>,
+        tunables: &Tunables,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError>;
+
     /// Creates a function of type `VMTrampoline` which will then call the
     /// function pointer argument which has the `ty` type provided.
     fn compile_host_to_wasm_trampoline(
@@ -372,6 +372,12 @@ impl<'a, 'data> Translator<'a, 'data> {
             &self.static_components,
         )?;
         self.partition_adapter_modules(&mut component);
+
+        // Now that the list of core wasm modules, including adapter modules, is
+        // final each module learns its index within the component.
+        for (index, translation) in self.static_modules.iter_mut() {
+            translation.component_module_index = Some(index.as_u32());
+        }
         Ok((component.finish(), self.static_modules))
     }
 
@@ -0,0 +1,60 @@
+//! The protocol between compiled code and the runtime for the `debug_hook`
+//! builtin, which is called when guest debugging is enabled.
+//!
+//! Each compiled function reserves a stack slot, its "debug frame", in which
+//! the current values of its locals followed by its wasm value stack are
+//! stored before each call to the hook. Every value occupies
+//! `DEBUG_FRAME_VALUE_SIZE` bytes: a `DebugValueKind` as a little-endian
+//! `u32` followed, at `DEBUG_FRAME_VALUE_OFFSET`, by the value itself.
+
+/// The `kind` passed to `debug_hook` on entry to a function, before its first
+/// instruction.
+pub const DEBUG_HOOK_ENTER: u32 = 0;
+
+/// The `kind` passed to `debug_hook` before each instruction of a function.
+pub const DEBUG_HOOK_STEP: u32 = 1;
+
+/// The `kind` passed to `debug_hook` when a function returns normally,
+/// including through a tail call.
+pub const DEBUG_HOOK_EXIT: u32 = 2;
+
+/// The number of bytes each value occupies in a debug frame.
+pub const DEBUG_FRAME_VALUE_SIZE: u32 = 24;
+
+/// The offset of a value's contents relative to the start of its entry in a
+/// debug frame.
+pub const DEBUG_FRAME_VALUE_OFFSET: u32 = 8;
+
+/// The kind of a value stored in a debug frame, as it's represented in
+/// compiled code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DebugValueKind {
+    /// A 32-bit integer.
+    I32 = 0,
+    /// A 64-bit integer, which is also how `funcref`s are represented.
+    I64 = 1,
+    /// The bits of a 32-bit float.
+    F32 = 2,
+    /// The bits of a 64-bit float.
+    F64 = 3,
+    /// A 128-bit vector.
+    V128 = 4,
+    /// An `externref`, stored as its raw pointer.
+    Ref = 5,
+}
+
+impl DebugValueKind {
+    /// Decodes a kind previously stored in a debug frame.
+    pub fn from_u32(kind: u32) -> Option<DebugValueKind> {
+        Some(match kind {
+            0 => DebugValueKind::I32,
+            1 => DebugValueKind::I64,
+            2 => DebugValueKind::F32,
+            3 => DebugValueKind::F64,
+            4 => DebugValueKind::V128,
+            5 => DebugValueKind::Ref,
+            _ => return None,
+        })
+    }
+}
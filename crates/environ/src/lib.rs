@@ -24,8 +24,11 @@
 )]
 
 mod address_map;
+mod block_profile;
 mod builtin;
 mod compilation;
+mod debug_hook;
+mod memory_sanitizer;
 mod module;
 mod module_environ;
 mod module_types;
@@ -38,8 +41,11 @@ mod tunables;
 mod vmoffsets;
 
 pub use crate::address_map::*;
+pub use crate::block_profile::*;
 pub use crate::builtin::*;
 pub use crate::compilation::*;
+pub use crate::debug_hook::*;
+pub use crate::memory_sanitizer::*;
 pub use crate::module::*;
 pub use crate::module_environ::*;
 pub use crate::module_types::*;
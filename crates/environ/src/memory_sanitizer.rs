@@ -0,0 +1,82 @@
+//! The protocol between compiled code and the runtime for the memory
+//! sanitizer, which is enabled with `Tunables::memory_sanitizer`.
+//!
+//! Every access to a module's default linear memory calls the
+//! `sanitizer_check` builtin first, and the functions that the module exports
+//! as its allocator call `sanitizer_allocator_entry` on entry and
+//! `sanitizer_allocator_exit` when they return, so the runtime can track
+//! which parts of the heap are allocated.
+
+use crate::{EntityIndex, FuncIndex, MemoryIndex, Module, ModuleTypes, WasmType};
+
+/// A function of a guest's allocator, identified by the name that the module
+/// exports it as.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AllocatorFunction {
+    /// `malloc(size) -> ptr`
+    Malloc = 0,
+    /// `calloc(count, size) -> ptr`
+    Calloc = 1,
+    /// `realloc(ptr, size) -> ptr`
+    Realloc = 2,
+    /// `free(ptr)`
+    Free = 3,
+}
+
+impl AllocatorFunction {
+    /// Returns the allocator function that `func` implements, if any.
+    ///
+    /// A function is only recognized when it's exported under the name of an
+    /// allocator function and has the matching signature, with pointers and
+    /// sizes typed as addresses of the module's default memory.
+    pub fn for_function(
+        module: &Module,
+        types: &ModuleTypes,
+        func: FuncIndex,
+    ) -> Option<AllocatorFunction> {
+        let plan = module.memory_plans.get(MemoryIndex::from_u32(0))?;
+        let address = if plan.memory.memory64 {
+            WasmType::I64
+        } else {
+            WasmType::I32
+        };
+        let ty = &types[module.functions[func].signature];
+        module
+            .exports
+            .iter()
+            .filter(|(_, index)| **index == EntityIndex::Function(func))
+            .find_map(|(name, _)| {
+                let (function, params, returns) = match name.as_str() {
+                    "malloc" => (AllocatorFunction::Malloc, 1, 1),
+                    "calloc" => (AllocatorFunction::Calloc, 2, 1),
+                    "realloc" => (AllocatorFunction::Realloc, 2, 1),
+                    "free" => (AllocatorFunction::Free, 1, 0),
+                    _ => return None,
+                };
+                let matches = ty.params().len() == params
+                    && ty.returns().len() == returns
+                    && ty
+                        .params()
+                        .iter()
+                        .chain(ty.returns())
+                        .all(|t| *t == address);
+                if matches {
+                    Some(function)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Decodes a function previously passed to `sanitizer_allocator_entry`.
+    pub fn from_u32(function: u32) -> Option<AllocatorFunction> {
+        Some(match function {
+            0 => AllocatorFunction::Malloc,
+            1 => AllocatorFunction::Calloc,
+            2 => AllocatorFunction::Realloc,
+            3 => AllocatorFunction::Free,
+            _ => return None,
+        })
+    }
+}
@@ -4,8 +4,9 @@ use crate::module::{
 };
 use crate::{
     DataIndex, DefinedFuncIndex, ElemIndex, EntityIndex, EntityType, FuncIndex, Global,
-    GlobalIndex, GlobalInit, MemoryIndex, ModuleTypesBuilder, PrimaryMap, SignatureIndex,
-    TableIndex, TableInitialization, Tunables, TypeIndex, WasmError, WasmFuncType, WasmResult,
+    GlobalIndex, GlobalInit, MemoryIndex, ModuleBlockProfile, ModuleTypesBuilder, PrimaryMap,
+    SignatureIndex, TableIndex, TableInitialization, Tunables, TypeIndex, WasmError, WasmFuncType,
+    WasmResult,
 };
 use cranelift_entity::packed_option::ReservedValue;
 use std::borrow::Cow;
@@ -47,9 +48,20 @@ pub struct ModuleTranslation<'data> {
     /// themselves.
     pub wasm: &'data [u8],
 
+    /// For modules parsed from a component, the index of this module within
+    /// the list of core wasm modules of the component.
+    ///
+    /// This is used to distinguish the functions of different modules which
+    /// are compiled together.
+    pub component_module_index: Option<u32>,
+
     /// References to the function bodies.
     pub function_body_inputs: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
 
+    /// The block profile used to guide the layout of the code of the
+    /// functions, if any.
+    pub block_profile: ModuleBlockProfile,
+
     /// A list of type signatures which are considered exported from this
     /// module, or those that can possibly be called. This list is sorted, and
     /// trampolines for each of these signatures are required.
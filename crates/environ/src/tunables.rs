@@ -31,6 +31,24 @@ pub struct Tunables {
     /// Whether or not we use epoch-based interruption.
     pub epoch_interruption: bool,
 
+    /// Whether or not generated code calls the debug hook on function entry
+    /// and exit and before each instruction, exposing its locals and value
+    /// stack to a debugger.
+    pub guest_debug: bool,
+
+    /// Whether or not generated code reports accesses to the default linear
+    /// memory and calls to the module's exported allocator to the memory
+    /// sanitizer.
+    pub memory_sanitizer: bool,
+
+    /// Whether or not generated code counts how many times its blocks are
+    /// executed, to collect a block profile of the module.
+    pub block_profiling: bool,
+
+    /// Whether or not small functions are inlined into their callers in the
+    /// same module.
+    pub inlining: bool,
+
     /// Whether or not to treat the static memory bound as the maximum for unbounded heaps.
     pub static_memory_bound_is_maximum: bool,
 
@@ -91,6 +109,10 @@ impl Default for Tunables {
             parse_wasm_debuginfo: true,
             consume_fuel: false,
             epoch_interruption: false,
+            guest_debug: false,
+            memory_sanitizer: false,
+            block_profiling: false,
+            inlining: false,
             static_memory_bound_is_maximum: false,
             guard_before_linear_memory: true,
             generate_address_map: true,
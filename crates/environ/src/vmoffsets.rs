@@ -12,6 +12,7 @@
 //      store: *mut dyn Store,
 //      builtins: *mut VMBuiltinFunctionsArray,
 //      signature_ids: *const VMSharedSignatureIndex,
+//      block_profile_counters: *const *mut u64,
 //      imported_functions: [VMFunctionImport; module.num_imported_functions],
 //      imported_tables: [VMTableImport; module.num_imported_tables],
 //      imported_memories: [VMMemoryImport; module.num_imported_memories],
@@ -81,6 +82,7 @@ pub struct VMOffsets<P> {
     store: u32,
     builtin_functions: u32,
     signature_ids: u32,
+    block_profile_counters: u32,
     imported_functions: u32,
     imported_tables: u32,
     imported_memories: u32,
@@ -325,6 +327,7 @@ impl<P: PtrSize> VMOffsets<P> {
             imported_memories: "imported memories",
             imported_tables: "imported tables",
             imported_functions: "imported functions",
+            block_profile_counters: "block profiling counters",
             signature_ids: "module types",
             builtin_functions: "jit builtin functions state",
             store: "jit store state",
@@ -358,6 +361,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             store: 0,
             builtin_functions: 0,
             signature_ids: 0,
+            block_profile_counters: 0,
             imported_functions: 0,
             imported_tables: 0,
             imported_memories: 0,
@@ -409,6 +413,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             size(store) = ret.ptr.size() * 2,
             size(builtin_functions) = ret.pointer_size(),
             size(signature_ids) = ret.ptr.size(),
+            size(block_profile_counters) = ret.ptr.size(),
             size(imported_functions)
                 = cmul(ret.num_imported_functions, ret.size_of_vmfunction_import()),
             size(imported_tables)
@@ -618,6 +623,13 @@ impl<P: PtrSize> VMOffsets<P> {
         self.signature_ids
     }
 
+    /// The offset of the pointer to the table of block profiling counter
+    /// arrays, indexed by `DefinedFuncIndex`.
+    #[inline]
+    pub fn vmctx_block_profile_counters(&self) -> u32 {
+        self.block_profile_counters
+    }
+
     /// The offset of the `tables` array.
     #[allow(clippy::erasing_op)]
     #[inline]
@@ -1,13 +1,14 @@
 /*** State *********************************************************************/
 
 class State {
-  constructor(wat, asm) {
+  constructor(wat, asm, clif) {
     this.wat = wat;
     this.asm = asm;
+    this.clif = clif;
   }
 }
 
-const state = window.STATE = new State(window.WAT, window.ASM);
+const state = window.STATE = new State(window.WAT, window.ASM, window.CLIF);
 
 /*** Hues for Offsets **********************************************************/
 
@@ -67,7 +68,10 @@ const watByOffset = new Map();
 // Get asm instruction elements by Wasm offset.
 const asmByOffset = new Map();
 
-// Get all (WAT chunk or asm instruction) elements by offset.
+// Get CLIF and VCode line elements by Wasm offset.
+const clifByOffset = new Map();
+
+// Get all (WAT chunk, CLIF line, or asm instruction) elements by offset.
 const anyByOffset = new Map();
 
 const addWatElem = (offset, elem) => {
@@ -94,44 +98,61 @@ const addAsmElem = (offset, elem) => {
   anyByOffset.get(offset).push(elem);
 };
 
-/*** Event Handlers ************************************************************/
+const addClifElem = (offset, elem) => {
+  if (!clifByOffset.has(offset)) {
+    clifByOffset.set(offset, []);
+  }
+  clifByOffset.get(offset).push(elem);
 
-const watElem = document.getElementById("wat");
-watElem.addEventListener("click", event => {
-  if (event.target.dataset.wasmOffset == null) {
-    return;
+  if (!anyByOffset.has(offset)) {
+    anyByOffset.set(offset, []);
   }
+  anyByOffset.get(offset).push(elem);
+};
 
-  const offset = parseInt(event.target.dataset.wasmOffset);
-  if (!asmByOffset.get(offset)) {
+/*** Event Handlers ************************************************************/
+
+// Scroll the first of the given elements which is currently displayed into
+// view. Lines of CLIF stages which aren't selected are hidden.
+const scrollToFirstVisible = elems => {
+  const elem = (elems || []).find(elem => elem.offsetParent != null);
+  if (!elem) {
     return;
   }
-
-  const firstAsmElem = asmByOffset.get(offset)[0];
-  firstAsmElem.scrollIntoView({
+  elem.scrollIntoView({
     behavior: "smooth",
     block: "center",
     inline: "nearest",
   });
-}, { passive: true });
+};
 
-const asmElem = document.getElementById("asm");
-asmElem.addEventListener("click", event => {
+// Clicking on an element with a Wasm offset scrolls the other panes to the
+// elements with the same offset.
+const onClick = others => event => {
   if (event.target.dataset.wasmOffset == null) {
     return;
   }
 
   const offset = parseInt(event.target.dataset.wasmOffset);
-  if (!watByOffset.get(offset)) {
-    return;
+  for (const byOffset of others) {
+    scrollToFirstVisible(byOffset.get(offset));
   }
+};
 
-  const firstWatElem = watByOffset.get(offset)[0];
-  firstWatElem.scrollIntoView({
-    behavior: "smooth",
-    block: "center",
-    inline: "nearest",
-  });
+const watElem = document.getElementById("wat");
+watElem.addEventListener("click", onClick([clifByOffset, asmByOffset]), { passive: true });
+
+const clifElem = document.getElementById("clif-functions");
+clifElem.addEventListener("click", onClick([watByOffset, asmByOffset]), { passive: true });
+
+const asmElem = document.getElementById("asm");
+asmElem.addEventListener("click", onClick([watByOffset, clifByOffset]), { passive: true });
+
+const clifStageElem = document.getElementById("clif-stage");
+clifStageElem.addEventListener("change", () => {
+  for (const elem of clifElem.querySelectorAll("[data-stage]")) {
+    elem.style.display = elem.dataset.stage == clifStageElem.value ? "" : "none";
+  }
 }, { passive: true });
 
 const onMouseEnter = event => {
@@ -191,12 +212,11 @@ const renderInst = (mnemonic, operands) => {
 
 // Render the ASM.
 
-let nthFunc = 0;
 for (const func of state.asm.functions) {
   const funcElem = document.createElement("div");
 
   const funcHeader = document.createElement("h3");
-  funcHeader.textContent = `Defined Function ${nthFunc}`;
+  funcHeader.textContent = func.title;
   funcElem.appendChild(funcHeader);
 
   const bodyElem = document.createElement("pre");
@@ -216,7 +236,55 @@ for (const func of state.asm.functions) {
   funcElem.appendChild(bodyElem);
 
   asmElem.appendChild(funcElem);
-  nthFunc++;
+}
+
+// Render the CLIF and VCode, with one body per stage of compilation of which
+// only the selected one is displayed.
+
+for (const stage of state.clif.stages) {
+  const optionElem = document.createElement("option");
+  optionElem.value = stage.name;
+  optionElem.textContent = state.clif.diff ? `${stage.title} (diff)` : stage.title;
+  clifStageElem.appendChild(optionElem);
+}
+
+for (const func of state.clif.functions) {
+  const funcElem = document.createElement("div");
+
+  const funcHeader = document.createElement("h3");
+  funcHeader.textContent = func.title;
+  funcElem.appendChild(funcHeader);
+
+  for (let i = 0; i < state.clif.stages.length; i++) {
+    const bodyElem = document.createElement("pre");
+    bodyElem.dataset.stage = state.clif.stages[i].name;
+    if (i != 0) {
+      bodyElem.style.display = "none";
+    }
+    for (const line of func.stages[i]) {
+      const lineElem = document.createElement("span");
+      lineElem.textContent = state.clif.diff
+        ? `${line.change || " "} ${line.text}\n`
+        : `${line.text}\n`;
+      if (line.change == "+") {
+        lineElem.className = "diff-insert";
+      } else if (line.change == "-") {
+        lineElem.className = "diff-delete";
+      }
+      if (line.wasm_offset != null) {
+        lineElem.dataset.wasmOffset = line.wasm_offset;
+        const hue = hueForOffset(line.wasm_offset);
+        lineElem.style.backgroundColor = `hsl(${hue} 50% 90%)`;
+        lineElem.addEventListener("mouseenter", onMouseEnter);
+        lineElem.addEventListener("mouseleave", onMouseLeave);
+        addClifElem(line.wasm_offset, lineElem);
+      }
+      bodyElem.appendChild(lineElem);
+    }
+    funcElem.appendChild(bodyElem);
+  }
+
+  clifElem.appendChild(funcElem);
 }
 
 // Render the WAT.
@@ -1,10 +1,23 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use capstone::arch::BuildsCapstone;
 use serde::Serialize;
-use std::{io::Write, str::FromStr};
+use std::{io::Write, path::Path, str::FromStr};
 
+/// Generates the HTML of the compiler explorer for `wasm` into `dest`.
+///
+/// The `wasm` may be either a core wasm module or a component. For components
+/// each of their core modules is shown, followed by the adapter modules and
+/// string transcoders that Wasmtime generates for calls between components.
+///
+/// Note that the cache must be disabled in `config` for the Cranelift IR and
+/// VCode of each function to be available.
+///
+/// If `diff_config` is provided then the module is additionally compiled with
+/// it, and the Cranelift IR and VCode panes show a diff from the output of
+/// `config` to the output of `diff_config`.
 pub fn generate(
     config: &wasmtime::Config,
+    diff_config: Option<&wasmtime::Config>,
     target: Option<&str>,
     wasm: &[u8],
     dest: &mut dyn Write,
@@ -14,10 +27,90 @@ pub fn generate(
         Some(target) => target_lexicon::Triple::from_str(target)?,
     };
 
-    let wat = annotate_wat(wasm)?;
+    let mut wat = annotate_wat(wasm, 0)?;
+    let (compiled, clif_dir) = compile(config, wasm)?;
+    let diff_dir = match diff_config {
+        Some(config) => Some(compile(config, wasm)?.1),
+        None => None,
+    };
+    let diff_dir = diff_dir.as_ref().map(|dir| dir.path());
+
+    let mut asm = AnnotatedAsm {
+        functions: Vec::new(),
+    };
+    let mut clif = AnnotatedClif {
+        stages: CLIF_STAGES
+            .iter()
+            .map(|(name, title)| ClifStage { name, title })
+            .collect(),
+        diff: diff_dir.is_some(),
+        functions: Vec::new(),
+    };
+    match &compiled {
+        Compiled::Module(module) => {
+            let module = CoreModule {
+                module,
+                clif_prefix: "wasm".to_string(),
+                offset_base: 0,
+                title: String::new(),
+            };
+            asm.functions = annotate_asm(&module, &target)?;
+            clif.functions = annotate_clif(&module, clif_dir.path(), diff_dir)?;
+        }
+        Compiled::Component(component) => {
+            // The core modules defined within the component come first, and
+            // their offsets are already relative to the component's binary.
+            // They are followed by the adapter modules generated by Wasmtime,
+            // which are appended to the WAT with their offsets moved past the
+            // end of the component's binary.
+            let num_defined = count_core_modules(wasm)?;
+            let mut next_base = u32::try_from(wasm.len()).unwrap();
+            for (index, module) in component.core_modules().enumerate() {
+                let (offset_base, title) = if index < num_defined {
+                    (0, format!("Module {index} "))
+                } else {
+                    let path = clif_dir.path().join(format!("wasm_module_{index}.wasm"));
+                    let adapter = std::fs::read(&path)
+                        .with_context(|| format!("failed to read `{}`", path.display()))?;
+                    wat.chunks.push(AnnotatedWatChunk {
+                        wasm_offset: None,
+                        wat: format!("\n\n;; adapter module {index}\n"),
+                    });
+                    wat.chunks.extend(annotate_wat(&adapter, next_base)?.chunks);
+                    let offset_base = next_base;
+                    next_base += u32::try_from(adapter.len()).unwrap();
+                    (offset_base, format!("Adapter Module {index} "))
+                };
+                let module = CoreModule {
+                    module,
+                    clif_prefix: format!("wasm_module_{index}"),
+                    offset_base,
+                    title,
+                };
+                asm.functions.extend(annotate_asm(&module, &target)?);
+                clif.functions
+                    .extend(annotate_clif(&module, clif_dir.path(), diff_dir)?);
+            }
+
+            // String transcoders are generated directly as Cranelift IR and
+            // don't correspond to any wasm.
+            let transcoders = component
+                .transcoder_locations()
+                .enumerate()
+                .map(|(i, (start, len))| (format!("String Transcoder {i}"), start, len));
+            asm.functions.extend(disassemble(
+                component.text(),
+                Vec::new(),
+                transcoders,
+                0,
+                &target,
+            )?);
+        }
+    }
+
     let wat_json = serde_json::to_string(&wat)?;
-    let asm = annotate_asm(config, &target, wasm)?;
     let asm_json = serde_json::to_string(&asm)?;
+    let clif_json = serde_json::to_string(&clif)?;
 
     let index_css = include_str!("./index.css");
     let index_js = include_str!("./index.js");
@@ -35,10 +128,15 @@ pub fn generate(
   </head>
   <body class="hbox">
     <pre id="wat"></pre>
+    <div id="clif">
+      <select id="clif-stage"></select>
+      <div id="clif-functions"></div>
+    </div>
     <div id="asm"></div>
     <script>
       window.WAT = {wat_json};
       window.ASM = {asm_json};
+      window.CLIF = {clif_json};
     </script>
     <script>
       {index_js}
@@ -64,18 +162,41 @@ struct AnnotatedWatChunk {
     wat: String,
 }
 
-fn annotate_wat(wasm: &[u8]) -> Result<AnnotatedWat> {
+fn annotate_wat(wasm: &[u8], offset_base: u32) -> Result<AnnotatedWat> {
     let mut printer = wasmprinter::Printer::new();
     let chunks = printer
         .offsets_and_lines(wasm)?
         .map(|(offset, wat)| AnnotatedWatChunk {
-            wasm_offset: offset.map(|o| WasmOffset(u32::try_from(o).unwrap())),
+            wasm_offset: offset.map(|o| WasmOffset(offset_base + u32::try_from(o).unwrap())),
             wat: wat.to_string(),
         })
         .collect();
     Ok(AnnotatedWat { chunks })
 }
 
+/// Returns whether `wasm` is a component rather than a core wasm module.
+fn is_component(wasm: &[u8]) -> Result<bool> {
+    match wasmparser::Parser::new(0).parse(wasm, true)? {
+        wasmparser::Chunk::Parsed {
+            payload: wasmparser::Payload::Version { encoding, .. },
+            ..
+        } => Ok(encoding == wasmparser::Encoding::Component),
+        _ => unreachable!(),
+    }
+}
+
+/// Returns the number of core wasm modules defined within the component
+/// `wasm`, including those of nested components.
+fn count_core_modules(wasm: &[u8]) -> Result<usize> {
+    let mut count = 0;
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let wasmparser::Payload::ModuleSection { .. } = payload? {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
 #[derive(Serialize, Debug)]
 struct AnnotatedAsm {
     functions: Vec<AnnotatedFunction>,
@@ -83,6 +204,7 @@ struct AnnotatedAsm {
 
 #[derive(Serialize, Debug)]
 struct AnnotatedFunction {
+    title: String,
     instructions: Vec<AnnotatedInstruction>,
 }
 
@@ -95,20 +217,75 @@ struct AnnotatedInstruction {
     operands: Option<String>,
 }
 
+enum Compiled {
+    Module(wasmtime::Module),
+    Component(wasmtime::component::Component),
+}
+
+/// Compiles `wasm`, either a module or a component, with `config`,
+/// additionally writing the textual form of each stage of compilation into a
+/// new temporary directory.
+fn compile(config: &wasmtime::Config, wasm: &[u8]) -> Result<(Compiled, tempfile::TempDir)> {
+    let clif_dir = tempfile::tempdir().context("failed to create temporary directory")?;
+    let mut config = config.clone();
+    config.emit_clif(clif_dir.path());
+    let compiled = if is_component(wasm)? {
+        config.wasm_component_model(true);
+        let engine = wasmtime::Engine::new(&config)?;
+        Compiled::Component(wasmtime::component::Component::new(&engine, wasm)?)
+    } else {
+        let engine = wasmtime::Engine::new(&config)?;
+        Compiled::Module(wasmtime::Module::new(&engine, wasm)?)
+    };
+    Ok((compiled, clif_dir))
+}
+
+/// A core wasm module shown in the explorer, which may be one of the modules
+/// of a component.
+struct CoreModule<'a> {
+    module: &'a wasmtime::Module,
+    /// The prefix of the names of the files written for this module's
+    /// functions by `Config::emit_clif`.
+    clif_prefix: String,
+    /// Added to the wasm offsets of this module, so that they refer to its
+    /// position in the WAT pane.
+    offset_base: u32,
+    /// Prefixed to the title of each of this module's functions.
+    title: String,
+}
+
 fn annotate_asm(
-    config: &wasmtime::Config,
+    module: &CoreModule<'_>,
     target: &target_lexicon::Triple,
-    wasm: &[u8],
-) -> Result<AnnotatedAsm> {
-    let engine = wasmtime::Engine::new(config)?;
-    let module = wasmtime::Module::new(&engine, wasm)?;
-
-    let text = module.text();
+) -> Result<Vec<AnnotatedFunction>> {
     let address_map: Vec<_> = module
+        .module
         .address_map()
         .ok_or_else(|| anyhow::anyhow!("address maps must be enabled in the config"))?
         .collect();
+    let functions = module
+        .module
+        .function_locations()
+        .enumerate()
+        .map(|(i, (start, len))| (format!("{}Defined Function {i}", module.title), start, len));
+    disassemble(
+        module.module.text(),
+        address_map,
+        functions,
+        module.offset_base,
+        target,
+    )
+}
 
+/// Disassembles each of the `functions`, given as a title and a location within
+/// `text`, annotating instructions with their wasm offset from `address_map`.
+fn disassemble(
+    text: &[u8],
+    address_map: Vec<(usize, Option<u32>)>,
+    functions: impl Iterator<Item = (String, usize, usize)>,
+    offset_base: u32,
+    target: &target_lexicon::Triple,
+) -> Result<Vec<AnnotatedFunction>> {
     let mut address_map_iter = address_map.into_iter().peekable();
     let mut current_entry = address_map_iter.next();
     let mut wasm_offset_for_address = |start: usize, address: u32| -> Option<WasmOffset> {
@@ -125,13 +302,11 @@ fn annotate_asm(
         }) {
             current_entry = address_map_iter.next();
         }
-        current_entry.and_then(|entry| entry.1.map(WasmOffset))
+        current_entry.and_then(|entry| entry.1.map(|offset| WasmOffset(offset_base + offset)))
     };
 
-    let functions = module
-        .function_locations()
-        .into_iter()
-        .map(|(start, len)| {
+    functions
+        .map(|(title, start, len)| {
             let body = &text[start..][..len];
 
             let mut cs = match target.architecture {
@@ -181,9 +356,125 @@ fn annotate_asm(
                     })
                 })
                 .collect::<Result<Vec<_>>>()?;
-            Ok(AnnotatedFunction { instructions })
+            Ok(AnnotatedFunction {
+                title,
+                instructions,
+            })
+        })
+        .collect()
+}
+
+/// The stages of compilation shown in the explorer, as the file extension used
+/// by `Config::emit_clif` and a human-readable title.
+const CLIF_STAGES: &[(&str, &str)] = &[
+    ("clif", "CLIF"),
+    ("opt.clif", "Optimized CLIF"),
+    ("lowered.vcode", "Lowered VCode"),
+    ("vcode", "Register-allocated VCode"),
+];
+
+#[derive(Serialize, Debug)]
+struct AnnotatedClif {
+    stages: Vec<ClifStage>,
+    diff: bool,
+    functions: Vec<ClifFunction>,
+}
+
+#[derive(Serialize, Debug)]
+struct ClifStage {
+    name: &'static str,
+    title: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+struct ClifFunction {
+    title: String,
+    stages: Vec<Vec<ClifLine>>,
+}
+
+#[derive(Serialize, Debug)]
+struct ClifLine {
+    wasm_offset: Option<WasmOffset>,
+    text: String,
+    /// When diffing, whether this line was only present in the baseline
+    /// (`"-"`) or in the comparison (`"+"`).
+    change: Option<&'static str>,
+}
+
+fn annotate_clif(
+    module: &CoreModule<'_>,
+    dir: &Path,
+    diff_dir: Option<&Path>,
+) -> Result<Vec<ClifFunction>> {
+    let num_imported_funcs = module
+        .module
+        .imports()
+        .filter(|import| matches!(import.ty(), wasmtime::ExternType::Func(_)))
+        .count();
+    let num_defined_funcs = module.module.function_locations().len();
+
+    let mut functions = Vec::with_capacity(num_defined_funcs);
+    for index in num_imported_funcs..num_imported_funcs + num_defined_funcs {
+        let mut stages = Vec::with_capacity(CLIF_STAGES.len());
+        for (stage, _) in CLIF_STAGES {
+            let file = format!("{}_func_{index}.{stage}", module.clif_prefix);
+            let old = read_clif_stage(&dir.join(&file))?;
+            let lines = match diff_dir {
+                Some(diff_dir) => {
+                    let new = read_clif_stage(&diff_dir.join(&file))?;
+                    diff_lines(&old, &new, module.offset_base)
+                }
+                None => old
+                    .lines()
+                    .map(|line| annotate_line(line, module.offset_base, None))
+                    .collect(),
+            };
+            stages.push(lines);
+        }
+        functions.push(ClifFunction {
+            title: format!("{}Function {index}", module.title),
+            stages,
+        });
+    }
+    Ok(functions)
+}
+
+fn read_clif_stage(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        // Stages may be missing if they weren't produced by the compiler, for
+        // example if the module was loaded from the cache.
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).with_context(|| format!("failed to read `{}`", path.display())),
+    }
+}
+
+fn diff_lines(old: &str, new: &str, offset_base: u32) -> Vec<ClifLine> {
+    similar::TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| {
+            let tag = match change.tag() {
+                similar::ChangeTag::Equal => None,
+                similar::ChangeTag::Delete => Some("-"),
+                similar::ChangeTag::Insert => Some("+"),
+            };
+            annotate_line(change.value().trim_end_matches('\n'), offset_base, tag)
         })
-        .collect::<Result<Vec<_>>>()?;
+        .collect()
+}
 
-    Ok(AnnotatedAsm { functions })
+/// Both CLIF and VCode instructions are prefixed by the wasm offset they were
+/// generated from, as in `@002a`.
+fn annotate_line(line: &str, offset_base: u32, change: Option<&'static str>) -> ClifLine {
+    let wasm_offset = line
+        .trim_start()
+        .strip_prefix('@')
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|offset| u32::from_str_radix(offset, 16).ok())
+        .map(|offset| WasmOffset(offset_base + offset));
+    ClifLine {
+        wasm_offset,
+        text: line.to_string(),
+        change,
+    }
 }
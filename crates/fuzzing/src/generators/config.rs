@@ -312,7 +312,8 @@ impl<'a> Arbitrary<'a> for Config {
         };
 
         // This is pulled from `u` by default via `wasm-smith`, but Wasmtime
-        // doesn't implement this yet, so forcibly always disable it.
+        // only implements this for x86_64 outside of Windows and not in
+        // Winch, so forcibly always disable it.
         config.module_config.config.tail_call_enabled = false;
 
         config
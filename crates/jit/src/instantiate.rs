@@ -46,6 +46,15 @@ pub struct CompiledModuleInfo {
     meta: Metadata,
 }
 
+impl CompiledModuleInfo {
+    /// Returns the type information about the compiled module, for example to
+    /// update its initializers before writing it into a new artifact with
+    /// `ObjectBuilder::from_artifact`.
+    pub fn module_mut(&mut self) -> &mut Module {
+        &mut self.module
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct FunctionName {
     idx: FuncIndex,
@@ -75,6 +84,10 @@ struct Metadata {
     /// Dwarf sections and the offsets at which they're stored in the
     /// ELF_WASMTIME_DWARF
     dwarf: Vec<(u8, Range<u64>)>,
+
+    /// The range of the ELF_WASM_DATA section holding the original wasm
+    /// binary, which is only retained for guest debugging.
+    wasm_binary: Option<Range<u64>>,
 }
 
 /// Helper structure to create an ELF file as a compilation artifact.
@@ -123,6 +136,132 @@ impl<'a> ObjectBuilder<'a> {
         }
     }
 
+    /// Creates a new builder which starts out as a copy of `image`, an artifact
+    /// previously produced by `ObjectBuilder::finish`.
+    ///
+    /// Every section of `image` is copied over verbatim, including the text
+    /// section and its relocations, except for the wasm data section and the
+    /// `ELF_WASMTIME_INFO` section. The caller is expected to fill those back
+    /// in with `append_data` and `serialize_info`. This enables creating a new
+    /// module with different initial state, such as a snapshot of an instance,
+    /// without recompiling any code.
+    pub fn from_artifact(image: &'a [u8], tunables: &'a Tunables) -> Result<ObjectBuilder<'a>> {
+        use object::read::{Object as _, ObjectSection, ObjectSymbol};
+        use object::write::{Relocation, Symbol, SymbolSection};
+        use object::{RelocationTarget, SymbolFlags, SymbolKind, SymbolScope};
+        use std::collections::HashMap;
+
+        let file = object::read::File::parse(image)
+            .context("failed to parse internal compilation artifact")?;
+        let endian = if file.is_little_endian() {
+            object::Endianness::Little
+        } else {
+            object::Endianness::Big
+        };
+        let mut obj = Object::new(file.format(), file.architecture(), endian);
+        obj.flags = file.flags();
+
+        let mut sections = HashMap::new();
+        for section in file.sections() {
+            let name = section.name()?;
+            if name.is_empty()
+                || name == obj::ELF_WASM_DATA
+                || name == obj::ELF_WASMTIME_INFO
+                || section.kind() == SectionKind::Metadata
+            {
+                continue;
+            }
+            let segment = section.segment_name_bytes()?.unwrap_or(&[]).to_vec();
+            let id = obj.add_section(segment, name.as_bytes().to_vec(), section.kind());
+            obj.section_mut(id).flags = section.flags();
+            sections.insert(section.index(), id);
+
+            let data = section.data()?;
+            if section.relocations().next().is_none() {
+                obj.set_section_data(id, data, section.align());
+                continue;
+            }
+
+            // Only the text section has relocations, namely for libcalls. Note
+            // that these relocations may have already been applied in-place
+            // if `image` was published, so the relocated bytes are reset here
+            // to what the compiler originally emitted.
+            if name != ".text" {
+                bail!("unexpected relocations in section `{name}`");
+            }
+            let mut data = data.to_vec();
+            let mut relocs = Vec::new();
+            for (offset, reloc) in section.relocations() {
+                let sym = match reloc.target() {
+                    RelocationTarget::Symbol(sym) => file.symbol_by_index(sym)?,
+                    other => bail!("unknown relocation target {other:?}"),
+                };
+                let start = usize::try_from(offset).unwrap();
+                let size = usize::from(reloc.size() / 8);
+                data[start..][..size].fill(0);
+                relocs.push((offset, reloc, sym.name()?.as_bytes().to_vec()));
+            }
+            obj.set_section_data(id, data, section.align());
+            for (offset, reloc, name) in relocs {
+                let symbol = match obj.symbol_id(&name) {
+                    Some(symbol) => symbol,
+                    None => obj.add_symbol(Symbol {
+                        name,
+                        value: 0,
+                        size: 0,
+                        kind: SymbolKind::Text,
+                        scope: SymbolScope::Linkage,
+                        weak: false,
+                        section: SymbolSection::Undefined,
+                        flags: SymbolFlags::None,
+                    }),
+                };
+                obj.add_relocation(
+                    id,
+                    Relocation {
+                        offset,
+                        size: reloc.size(),
+                        kind: reloc.kind(),
+                        encoding: reloc.encoding(),
+                        symbol,
+                        addend: reloc.addend(),
+                    },
+                )?;
+            }
+        }
+
+        // Carry over symbols for functions and such defined in the copied
+        // sections to keep the new artifact just as inspectable as the
+        // original.
+        for sym in file.symbols() {
+            if matches!(sym.kind(), SymbolKind::Section | SymbolKind::File) {
+                continue;
+            }
+            let section = match sym.section_index().and_then(|i| sections.get(&i)) {
+                Some(id) => *id,
+                None => continue,
+            };
+            obj.add_symbol(Symbol {
+                name: sym.name()?.as_bytes().to_vec(),
+                value: sym.address(),
+                size: sym.size(),
+                kind: sym.kind(),
+                scope: sym.scope(),
+                weak: sym.is_weak(),
+                section: SymbolSection::Section(section),
+                flags: SymbolFlags::None,
+            });
+        }
+
+        Ok(ObjectBuilder::new(obj, tunables))
+    }
+
+    /// Appends `data` to the wasm data section of this object, returning the
+    /// offset within the section at which it was placed.
+    pub fn append_data(&mut self, data: &[u8], align: u64) -> u64 {
+        self.obj.append_section_data(self.data, data, align)
+    }
+
     /// Completes compilation of the `translation` specified, inserting
     /// everything necessary into the `Object` being built.
     ///
@@ -159,6 +298,7 @@ impl<'a> ObjectBuilder<'a> {
     ) -> Result<CompiledModuleInfo> {
         let ModuleTranslation {
             mut module,
+            wasm,
             debuginfo,
             has_unparsed_debuginfo,
             data,
@@ -186,6 +326,16 @@ impl<'a> ObjectBuilder<'a> {
             self.obj.append_section_data(self.data, data, 1);
         }
 
+        // Debuggers load the module, and its debug information, from the
+        // runtime so the original binary is retained for them when guest
+        // debugging is enabled.
+        let wasm_binary = if self.tunables.guest_debug {
+            let offset = self.obj.append_section_data(self.data, wasm, 1);
+            Some(offset..offset + wasm.len() as u64)
+        } else {
+            None
+        };
+
         // If any names are present in the module then the `ELF_NAME_DATA` section
         // is create and appended.
         let mut func_names = Vec::new();
@@ -277,6 +427,7 @@ impl<'a> ObjectBuilder<'a> {
                 code_section_offset: debuginfo.wasm_file.code_section_offset,
                 has_wasm_debuginfo: self.tunables.parse_wasm_debuginfo,
                 dwarf,
+                wasm_binary,
             },
         })
     }
@@ -633,6 +784,13 @@ impl CompiledModule {
         }))
     }
 
+    /// Returns the original wasm binary of this module, which is only retained
+    /// when guest debugging is enabled.
+    pub fn wasm_binary(&self) -> Option<&[u8]> {
+        let range = self.meta.wasm_binary.as_ref()?;
+        Some(&self.code_memory().wasm_data()[range.start as usize..range.end as usize])
+    }
+
     /// Returns whether the original wasm module had unparsed debug information
     /// based on the tunables configuration.
     pub fn has_unparsed_debuginfo(&self) -> bool {
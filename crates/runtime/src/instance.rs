@@ -353,7 +353,7 @@ impl Instance {
         }
     }
 
-    fn get_exported_memory(&mut self, index: MemoryIndex) -> ExportMemory {
+    pub(crate) fn get_exported_memory(&mut self, index: MemoryIndex) -> ExportMemory {
         let (definition, vmctx, def_index) =
             if let Some(def_index) = self.module().defined_memory_index(index) {
                 (self.memory_ptr(def_index), self.vmctx_ptr(), def_index)
@@ -931,6 +931,10 @@ impl Instance {
         let signatures = self.runtime_info.signature_ids();
         *self.vmctx_plus_offset(offsets.vmctx_signature_ids_array()) = signatures.as_ptr();
 
+        // Initialize the block profiling counters
+        *self.vmctx_plus_offset(offsets.vmctx_block_profile_counters()) =
+            self.runtime_info.block_profile_counters();
+
         // Initialize the built-in functions
         *self.vmctx_plus_offset(offsets.vmctx_builtin_functions()) = &VMBuiltinFunctionsArray::INIT;
 
@@ -1202,6 +1206,18 @@ impl InstanceHandle {
         self.instance_mut().get_table_with_lazy_init(index, range)
     }
 
+    /// Returns whether the passive data segment `index` has been dropped with
+    /// `data.drop`.
+    pub fn is_data_dropped(&self, index: DataIndex) -> bool {
+        self.instance().dropped_data.contains(index)
+    }
+
+    /// Returns whether the passive element segment `index` has been dropped
+    /// with `elem.drop`.
+    pub fn is_elem_dropped(&self, index: ElemIndex) -> bool {
+        self.instance().dropped_elements.contains(index)
+    }
+
     /// Return a reference to the contained `Instance`.
     #[inline]
     pub(crate) fn instance(&self) -> &Instance {
@@ -974,6 +974,9 @@ mod test {
             fn signature_ids(&self) -> &[VMSharedSignatureIndex] {
                 &[]
             }
+            fn block_profile_counters(&self) -> *const *mut u64 {
+                std::ptr::null()
+            }
             fn offsets(&self) -> &VMOffsets<HostPtr> {
                 &self.1
             }
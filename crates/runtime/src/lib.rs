@@ -24,7 +24,7 @@ use anyhow::Error;
 use std::fmt;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use wasmtime_environ::{DefinedFuncIndex, DefinedMemoryIndex, HostPtr, VMOffsets};
+use wasmtime_environ::{DefinedFuncIndex, DefinedMemoryIndex, FuncIndex, HostPtr, VMOffsets};
 
 #[macro_use]
 mod trampolines;
@@ -39,6 +39,7 @@ mod memory;
 mod mmap;
 mod mmap_vec;
 mod parking_spot;
+mod sanitizer;
 mod table;
 mod traphandlers;
 mod vmcontext;
@@ -64,6 +65,7 @@ pub use crate::memory::{
 };
 pub use crate::mmap::Mmap;
 pub use crate::mmap_vec::MmapVec;
+pub use crate::sanitizer::MemorySanitizer;
 pub use crate::table::{Table, TableElement};
 pub use crate::trampolines::prepare_host_to_wasm_trampoline;
 pub use crate::traphandlers::{
@@ -150,6 +152,35 @@ pub unsafe trait Store {
     /// number. Cannot fail; cooperative epoch-based yielding is
     /// completely semantically transparent. Returns the new deadline.
     fn new_epoch(&mut self) -> Result<u64, Error>;
+    /// Callback invoked by code compiled with guest debugging enabled on
+    /// function entry and exit and before each instruction. If an error is
+    /// returned that's raised as a trap.
+    fn debug_hook(&mut self, hook: DebugHook) -> Result<(), Error>;
+    /// Returns the state of the memory sanitizer, used by code compiled with
+    /// the memory sanitizer enabled.
+    fn memory_sanitizer(&mut self) -> &mut MemorySanitizer;
+}
+
+/// A call to the `debug_hook` builtin made by code compiled with guest
+/// debugging enabled.
+#[derive(Copy, Clone, Debug)]
+pub struct DebugHook {
+    /// One of `DEBUG_HOOK_ENTER`, `DEBUG_HOOK_STEP` or `DEBUG_HOOK_EXIT`.
+    pub kind: u32,
+    /// The vmctx of the instance which defines the calling function.
+    pub vmctx: *mut VMContext,
+    /// The index of the calling function within its module.
+    pub func: FuncIndex,
+    /// The offset within the original wasm module of the instruction about
+    /// to execute.
+    pub offset: u32,
+    /// The debug frame of the calling function, which stays valid until the
+    /// function exits.
+    pub frame: *const u8,
+    /// The number of locals stored at the start of `frame`.
+    pub locals: u32,
+    /// The number of value stack entries stored in `frame` after the locals.
+    pub stack: u32,
 }
 
 /// Functionality required by this crate for a particular module. This
@@ -189,6 +220,11 @@ pub trait ModuleRuntimeInfo: Send + Sync + 'static {
     /// `VMSharedSignatureIndex` entries corresponding to the `SignatureIndex`.
     fn signature_ids(&self) -> &[VMSharedSignatureIndex];
 
+    /// Returns the table, indexed by `DefinedFuncIndex`, of pointers to the
+    /// block profiling counters of each function, or null if the module
+    /// wasn't compiled with block profiling.
+    fn block_profile_counters(&self) -> *const *mut u64;
+
     /// Offset information for the current host.
     fn offsets(&self) -> &VMOffsets<HostPtr>;
 }
@@ -56,14 +56,14 @@
 
 use crate::externref::VMExternRef;
 use crate::table::{Table, TableElementType};
-use crate::vmcontext::{VMCallerCheckedFuncRef, VMContext};
-use crate::TrapReason;
+use crate::vmcontext::{VMCallerCheckedFuncRef, VMContext, VMMemoryDefinition};
+use crate::{DebugHook, TrapReason};
 use anyhow::Result;
 use std::mem;
 use std::ptr::{self, NonNull};
 use std::time::{Duration, Instant};
 use wasmtime_environ::{
-    DataIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, Trap,
+    AllocatorFunction, DataIndex, ElemIndex, FuncIndex, GlobalIndex, MemoryIndex, TableIndex, Trap,
 };
 
 /// Actually public trampolines which are used by the runtime as the entrypoint
@@ -504,6 +504,68 @@ unsafe fn new_epoch(vmctx: *mut VMContext) -> Result<u64> {
     (*(*vmctx).instance().store()).new_epoch()
 }
 
+// Hook for debugging guests, called by instrumented code.
+unsafe fn debug_hook(
+    vmctx: *mut VMContext,
+    kind: u32,
+    func: u32,
+    offset: u32,
+    frame: *mut u8,
+    locals: u32,
+    stack: u32,
+) -> Result<()> {
+    (*(*vmctx).instance().store()).debug_hook(DebugHook {
+        kind,
+        vmctx,
+        func: FuncIndex::from_u32(func),
+        offset,
+        frame,
+        locals,
+        stack,
+    })
+}
+
+// Returns the key of the default memory of the instance of `vmctx` for the
+// memory sanitizer.
+unsafe fn sanitizer_memory(vmctx: *mut VMContext) -> usize {
+    let instance = (*vmctx).instance_mut();
+    instance
+        .get_exported_memory(MemoryIndex::from_u32(0))
+        .definition as usize
+}
+
+// Hook for the memory sanitizer, called before each access to the default
+// memory.
+unsafe fn sanitizer_check(vmctx: *mut VMContext, addr: u64, len: u64, store: u32) -> Result<()> {
+    let memory = sanitizer_memory(vmctx);
+    (*(*vmctx).instance().store())
+        .memory_sanitizer()
+        .check(memory, addr, len, store != 0)
+}
+
+// Hook for the memory sanitizer, called on entry to the guest's allocator.
+unsafe fn sanitizer_allocator_entry(
+    vmctx: *mut VMContext,
+    function: u32,
+    arg0: u64,
+    arg1: u64,
+) -> Result<()> {
+    let memory = sanitizer_memory(vmctx);
+    let function = AllocatorFunction::from_u32(function).expect("invalid allocator function");
+    (*(*vmctx).instance().store())
+        .memory_sanitizer()
+        .allocator_entry(memory, function, [arg0, arg1])
+}
+
+// Hook for the memory sanitizer, called when the guest's allocator returns.
+unsafe fn sanitizer_allocator_exit(vmctx: *mut VMContext, result: u64) -> Result<()> {
+    let memory = sanitizer_memory(vmctx) as *const VMMemoryDefinition;
+    let memory_len = (*memory).current_length() as u64;
+    (*(*vmctx).instance().store())
+        .memory_sanitizer()
+        .allocator_exit(result, memory_len)
+}
+
 /// This module contains functions which are used for resolving relocations at
 /// runtime if necessary.
 ///
@@ -0,0 +1,225 @@
+//! Shadow memory for the memory sanitizer, see `Tunables::memory_sanitizer`.
+
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use wasmtime_environ::AllocatorFunction;
+
+/// The state of a byte of the heap which isn't part of any block.
+const UNALLOCATED: u8 = 0;
+/// The state of a byte of a block that's currently allocated.
+const ALLOCATED: u8 = 1;
+/// The state of a byte of a block that has been freed.
+const FREED: u8 = 2;
+
+/// The allocation state of the heaps in the linear memories of a store, as
+/// reported by the guests' allocators.
+#[derive(Default)]
+pub struct MemorySanitizer {
+    /// The shadow of each linear memory, keyed by the address of its
+    /// `VMMemoryDefinition` so that instances importing the same memory
+    /// share it.
+    shadows: HashMap<usize, Shadow>,
+    /// The calls into the allocator that are executing, innermost last.
+    calls: Vec<AllocatorCall>,
+}
+
+struct AllocatorCall {
+    memory: usize,
+    function: AllocatorFunction,
+    args: [u64; 2],
+}
+
+#[derive(Default)]
+struct Shadow {
+    /// The state of each byte of the memory, up to the end of the heap.
+    bytes: Vec<u8>,
+    /// The range between the lowest and the highest block ever allocated.
+    heap: Range<u64>,
+    /// The size of each allocated block, by its address.
+    allocated: BTreeMap<u64, u64>,
+    /// The size of each freed block, by its address, until some of it is
+    /// allocated again.
+    freed: BTreeMap<u64, u64>,
+}
+
+impl MemorySanitizer {
+    /// Checks an access of `len` bytes at `addr` of the memory identified by
+    /// `memory`, returning a diagnostic if it's an invalid heap access.
+    pub fn check(&self, memory: usize, addr: u64, len: u64, store: bool) -> Result<()> {
+        // The allocator is free to access its own data structures.
+        if !self.calls.is_empty() {
+            return Ok(());
+        }
+        let shadow = match self.shadows.get(&memory) {
+            Some(shadow) => shadow,
+            None => return Ok(()),
+        };
+        let start = addr.max(shadow.heap.start);
+        let end = addr.saturating_add(len).min(shadow.heap.end);
+        let bad = (start..end).find(|a| shadow.bytes[*a as usize] != ALLOCATED);
+        let bad = match bad {
+            Some(bad) => bad,
+            None => return Ok(()),
+        };
+        let access = format!(
+            "{}-byte {} at {:#x}",
+            len,
+            if store { "store" } else { "load" },
+            addr
+        );
+        if shadow.bytes[bad as usize] == FREED {
+            match find_block(&shadow.freed, bad) {
+                Some((block, size)) => bail!(
+                    "memory sanitizer: heap use-after-free: {access}, {} bytes inside \
+                     the {size}-byte block at {block:#x} which was freed",
+                    bad - block
+                ),
+                None => bail!("memory sanitizer: heap use-after-free: {access} of freed memory"),
+            }
+        }
+        match shadow.allocated.range(..=bad).next_back() {
+            Some((block, size)) => bail!(
+                "memory sanitizer: heap out-of-bounds access: {access}, {} bytes past the \
+                 end of the {size}-byte block at {block:#x}",
+                bad - (block + size)
+            ),
+            None => bail!(
+                "memory sanitizer: heap out-of-bounds access: {access}, outside of any \
+                 allocated block"
+            ),
+        }
+    }
+
+    /// Records a call to the allocator `function` of the memory identified by
+    /// `memory`, checking its arguments first.
+    pub fn allocator_entry(
+        &mut self,
+        memory: usize,
+        function: AllocatorFunction,
+        args: [u64; 2],
+    ) -> Result<()> {
+        // Only the outermost call is checked, as the allocator may implement
+        // some of its functions with the others.
+        if self.calls.is_empty() {
+            let name = match function {
+                AllocatorFunction::Free => Some("free"),
+                AllocatorFunction::Realloc => Some("realloc"),
+                AllocatorFunction::Malloc | AllocatorFunction::Calloc => None,
+            };
+            let ptr = args[0];
+            if let (Some(name), Some(shadow)) = (name, self.shadows.get(&memory)) {
+                if ptr != 0 && !shadow.allocated.contains_key(&ptr) {
+                    if shadow.freed.contains_key(&ptr) {
+                        bail!(
+                            "memory sanitizer: double free: `{name}` of {ptr:#x}, which was \
+                             already freed"
+                        );
+                    }
+                    bail!(
+                        "memory sanitizer: invalid free: `{name}` of {ptr:#x}, which isn't the \
+                         start of an allocated block"
+                    );
+                }
+            }
+        }
+        self.calls.push(AllocatorCall {
+            memory,
+            function,
+            args,
+        });
+        Ok(())
+    }
+
+    /// Records the return of the innermost call to the allocator, which
+    /// returned `result` while its memory was `memory_len` bytes long.
+    ///
+    /// Fails if the allocator returned a block that doesn't fit in the memory.
+    pub fn allocator_exit(&mut self, result: u64, memory_len: u64) -> Result<()> {
+        let call = self.calls.pop().expect("allocator exit without an entry");
+        if !self.calls.is_empty() {
+            return Ok(());
+        }
+        let shadow = self.shadows.entry(call.memory).or_default();
+        let [arg0, arg1] = call.args;
+        match call.function {
+            AllocatorFunction::Malloc => shadow.allocate(result, arg0, memory_len),
+            AllocatorFunction::Calloc => {
+                shadow.allocate(result, arg0.saturating_mul(arg1), memory_len)
+            }
+            AllocatorFunction::Realloc => {
+                // A failed `realloc` leaves the original block alone.
+                if result != 0 {
+                    shadow.free(arg0);
+                }
+                shadow.allocate(result, arg1, memory_len)
+            }
+            AllocatorFunction::Free => {
+                shadow.free(arg0);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the number of calls to the allocator that are executing.
+    pub fn allocator_depth(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Forgets about the calls to the allocator beyond the first `depth`,
+    /// which were unwound by a trap.
+    pub fn truncate_allocator_calls(&mut self, depth: usize) {
+        self.calls.truncate(depth);
+    }
+}
+
+impl Shadow {
+    fn mark(&mut self, block: Range<u64>, state: u8) {
+        if self.bytes.len() < block.end as usize {
+            self.bytes.resize(block.end as usize, UNALLOCATED);
+        }
+        self.bytes[block.start as usize..block.end as usize].fill(state);
+    }
+
+    /// Records the allocation of `size` bytes at `ptr`, which must fit in the
+    /// `memory_len` bytes of the memory; the shadow never grows past them.
+    fn allocate(&mut self, ptr: u64, size: u64, memory_len: u64) -> Result<()> {
+        if ptr == 0 {
+            return Ok(());
+        }
+        let end = ptr.saturating_add(size);
+        if end > memory_len {
+            bail!(
+                "memory sanitizer: invalid allocation: the allocator returned the {size}-byte \
+                 block at {ptr:#x}, which ends past the {memory_len}-byte memory"
+            );
+        }
+        if self.heap.is_empty() {
+            self.heap = ptr..end;
+        } else {
+            self.heap = self.heap.start.min(ptr)..self.heap.end.max(end);
+        }
+        self.mark(ptr..end, ALLOCATED);
+        self.freed
+            .retain(|block, len| *block >= end || *block + *len <= ptr);
+        self.allocated.insert(ptr, size);
+        Ok(())
+    }
+
+    fn free(&mut self, ptr: u64) {
+        if let Some(size) = self.allocated.remove(&ptr) {
+            self.mark(ptr..ptr + size, FREED);
+            self.freed.insert(ptr, size);
+        }
+    }
+}
+
+/// Returns the block of `blocks` which contains `addr`.
+fn find_block(blocks: &BTreeMap<u64, u64>, addr: u64) -> Option<(u64, u64)> {
+    let (block, size) = blocks.range(..=addr).next_back()?;
+    if addr < block + size {
+        Some((*block, *size))
+    } else {
+        None
+    }
+}
@@ -144,6 +144,7 @@ impl Backtrace {
             last_wasm_exit_pc,
             last_wasm_exit_fp,
             *(*state.limits).last_wasm_entry_sp.get(),
+            state.tail_calls,
             &mut f,
         ) {
             log::trace!("====== Done Capturing Backtrace ======");
@@ -169,6 +170,7 @@ impl Backtrace {
                 state.old_last_wasm_exit_pc(),
                 state.old_last_wasm_exit_fp(),
                 state.old_last_wasm_entry_sp(),
+                state.tail_calls,
                 &mut f,
             ) {
                 log::trace!("====== Done Capturing Backtrace ======");
@@ -185,6 +187,7 @@ impl Backtrace {
         mut pc: usize,
         mut fp: usize,
         first_wasm_sp: usize,
+        tail_calls: bool,
         mut f: impl FnMut(Frame) -> ControlFlow<()>,
     ) -> ControlFlow<()> {
         log::trace!("=== Tracing through contiguous sequence of Wasm frames ===");
@@ -227,6 +230,17 @@ impl Backtrace {
         assert_ne!(fp, 0);
         assert_ne!(first_wasm_sp, 0);
 
+        // If the first Wasm function in this sequence left it through a
+        // tail call to a host function, then its frame was torn down
+        // before the Wasm-to-host trampoline saved the exit FP and PC.
+        // These then belong to the host code that called into Wasm,
+        // whose frame is above the entry SP, and this is another empty
+        // sequence of Wasm frames.
+        if tail_calls && fp >= first_wasm_sp {
+            log::trace!("=== Done tracing (Wasm frames were tail called away) ===");
+            return ControlFlow::Continue(());
+        }
+
         // The stack grows down, and therefore any frame pointer we are
         // dealing with should be less than the stack pointer on entry
         // to Wasm.
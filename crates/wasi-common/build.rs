@@ -7,6 +7,11 @@ fn main() {
     println!("cargo:wasi={}", wasi.display());
     // and available to our own crate as WASI_ROOT:
     println!("cargo:rustc-env=WASI_ROOT={}", wasi.display());
+    // Our own witx documents are exported the same way, as
+    // DEP_WASI_COMMON_19_WITX and WASI_COMMON_WITX:
+    let witx = cwd.join("witx");
+    println!("cargo:witx={}", witx.display());
+    println!("cargo:rustc-env=WASI_COMMON_WITX={}", witx.display());
     // and this build.rs script doesn't depend on any files.
     println!("cargo:rerun-if-changed=build.rs");
 }
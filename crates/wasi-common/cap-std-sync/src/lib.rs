@@ -40,16 +40,20 @@ pub mod net;
 pub mod sched;
 pub mod stdio;
 
-pub use cap_std::ambient_authority;
 pub use cap_std::fs::Dir;
 pub use cap_std::net::TcpListener;
+pub use cap_std::{ambient_authority, AmbientAuthority};
 pub use clocks::clocks_ctx;
 pub use sched::sched_ctx;
 
 use crate::net::Socket;
 use cap_rand::{Rng, RngCore, SeedableRng};
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
 use std::path::Path;
-use wasi_common::{file::FileCaps, table::Table, Error, WasiCtx, WasiFile};
+use wasi_common::{
+    file::FileCaps, table::Table, Error, SocketUse, Trace, WasiCtx, WasiFile, WasiNetwork,
+};
 
 pub struct WasiCtxBuilder(WasiCtx);
 
@@ -135,6 +139,63 @@ impl WasiCtxBuilder {
         self.0.insert_file(fd, file, caps);
         Ok(self)
     }
+    /// Lets the guest create sockets with the ambient authority of the host
+    /// process, restricted to the addresses allowed with `allow_tcp_connect`,
+    /// `allow_udp_bind` and `allow_udp_send`.
+    pub fn inherit_network(self, ambient_authority: AmbientAuthority) -> Self {
+        self.network(Box::new(crate::net::Network::new(ambient_authority)))
+    }
+    /// Lets the guest create sockets with `network`, restricted to the
+    /// addresses allowed with `allow_tcp_connect`, `allow_udp_bind` and
+    /// `allow_udp_send`.
+    pub fn network(mut self, network: Box<dyn WasiNetwork>) -> Self {
+        self.0.set_network(network);
+        self
+    }
+    /// Allows connecting TCP streams to the addresses whose first
+    /// `prefix_len` bits match `net` and whose port is in `ports`.
+    pub fn allow_tcp_connect(
+        self,
+        net: IpAddr,
+        prefix_len: u8,
+        ports: RangeInclusive<u16>,
+    ) -> Self {
+        self.allow(SocketUse::TcpConnect, net, prefix_len, ports)
+    }
+    /// Allows binding UDP sockets to the local addresses whose first
+    /// `prefix_len` bits match `net` and whose port is in `ports`, with port
+    /// 0 standing for any port picked by the host.
+    pub fn allow_udp_bind(self, net: IpAddr, prefix_len: u8, ports: RangeInclusive<u16>) -> Self {
+        self.allow(SocketUse::UdpBind, net, prefix_len, ports)
+    }
+    /// Allows exchanging UDP datagrams with the addresses whose first
+    /// `prefix_len` bits match `net` and whose port is in `ports`.
+    pub fn allow_udp_send(self, net: IpAddr, prefix_len: u8, ports: RangeInclusive<u16>) -> Self {
+        self.allow(SocketUse::UdpSend, net, prefix_len, ports)
+    }
+    fn allow(
+        mut self,
+        use_: SocketUse,
+        net: IpAddr,
+        prefix_len: u8,
+        ports: RangeInclusive<u16>,
+    ) -> Self {
+        self.0
+            .network_policy_mut()
+            .allow(use_, net, prefix_len, ports);
+        self
+    }
+    /// Allows resolving names to addresses.
+    pub fn allow_name_lookup(mut self) -> Self {
+        self.0.network_policy_mut().allow_name_lookup();
+        self
+    }
+    /// Records the results of host calls into `trace`, or replays them
+    /// from it, see `wasi_common::trace` for details.
+    pub fn trace(mut self, trace: Trace) -> Self {
+        self.0.set_trace(trace);
+        self
+    }
     pub fn build(self) -> WasiCtx {
         self.0
     }
@@ -1,3 +1,4 @@
+use cap_std::AmbientAuthority;
 #[cfg(windows)]
 use io_extras::os::windows::{AsRawHandleOrSocket, RawHandleOrSocket};
 use io_lifetimes::AsSocketlike;
@@ -8,6 +9,7 @@ use io_lifetimes::{AsSocket, BorrowedSocket};
 use std::any::Any;
 use std::convert::TryInto;
 use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
 #[cfg(unix)]
 use system_interface::fs::GetSetFdFlags;
 use system_interface::io::IoExt;
@@ -15,7 +17,7 @@ use system_interface::io::IsReadWrite;
 use system_interface::io::ReadReady;
 use wasi_common::{
     file::{FdFlags, FileType, RiFlags, RoFlags, SdFlags, SiFlags, WasiFile},
-    Error, ErrorExt,
+    Error, ErrorExt, WasiNetwork,
 };
 
 pub enum Socket {
@@ -344,6 +346,153 @@ impl UnixStream {
 #[cfg(unix)]
 wasi_stream_write_impl!(UnixStream, std::os::unix::net::UnixStream);
 
+pub struct UdpSocket(cap_std::net::UdpSocket);
+
+impl UdpSocket {
+    pub fn from_cap_std(socket: cap_std::net::UdpSocket) -> Self {
+        UdpSocket(socket)
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiFile for UdpSocket {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    #[cfg(unix)]
+    fn pollable(&self) -> Option<rustix::fd::BorrowedFd> {
+        Some(self.0.as_fd())
+    }
+    #[cfg(windows)]
+    fn pollable(&self) -> Option<io_extras::os::windows::RawHandleOrSocket> {
+        Some(self.0.as_raw_handle_or_socket())
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::SocketDgram)
+    }
+    #[cfg(unix)]
+    async fn get_fdflags(&self) -> Result<FdFlags, Error> {
+        let fdflags = get_fd_flags(&self.0)?;
+        Ok(fdflags)
+    }
+    async fn set_fdflags(&mut self, fdflags: FdFlags) -> Result<(), Error> {
+        if fdflags == wasi_common::file::FdFlags::NONBLOCK {
+            self.0.set_nonblocking(true)?;
+        } else if fdflags.is_empty() {
+            self.0.set_nonblocking(false)?;
+        } else {
+            return Err(Error::invalid_argument().context("cannot set anything else than NONBLOCK"));
+        }
+        Ok(())
+    }
+    async fn sock_recv_from<'a>(
+        &self,
+        ri_data: &mut [std::io::IoSliceMut<'a>],
+        ri_flags: RiFlags,
+    ) -> Result<(u64, RoFlags, SocketAddr), Error> {
+        if (ri_flags & !RiFlags::RECV_PEEK) != RiFlags::empty() {
+            return Err(Error::not_supported());
+        }
+
+        // Datagrams can't be received into several buffers, so receive into
+        // one that is a byte longer than them all to detect truncation.
+        let len: usize = ri_data.iter().map(|buf| buf.len()).sum();
+        let mut buf = vec![0; len + 1];
+        let (n, addr) = if ri_flags.contains(RiFlags::RECV_PEEK) {
+            self.0.peek_from(&mut buf)?
+        } else {
+            self.0.recv_from(&mut buf)?
+        };
+
+        let mut ro_flags = RoFlags::empty();
+        let mut data = &buf[..n];
+        if n > len {
+            ro_flags |= RoFlags::RECV_DATA_TRUNCATED;
+            data = &data[..len];
+        }
+        let n = data.len();
+        for dst in ri_data.iter_mut() {
+            let (head, rest) = data.split_at(dst.len().min(data.len()));
+            dst[..head.len()].copy_from_slice(head);
+            data = rest;
+        }
+        Ok((n as u64, ro_flags, addr))
+    }
+    async fn sock_send_to<'a>(
+        &self,
+        si_data: &[std::io::IoSlice<'a>],
+        si_flags: SiFlags,
+        addr: SocketAddr,
+    ) -> Result<u64, Error> {
+        if si_flags != SiFlags::empty() {
+            return Err(Error::not_supported());
+        }
+
+        let mut buf = Vec::new();
+        for data in si_data {
+            buf.extend_from_slice(data);
+        }
+        let n = self
+            .as_socketlike_view::<std::net::UdpSocket>()
+            .send_to(&buf, addr)?;
+        Ok(n as u64)
+    }
+}
+
+#[cfg(unix)]
+impl AsFd for UdpSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+#[cfg(windows)]
+impl AsSocket for UdpSocket {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        self.0.as_socket()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawHandleOrSocket for UdpSocket {
+    #[inline]
+    fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
+        self.0.as_raw_handle_or_socket()
+    }
+}
+
+/// A `WasiNetwork` which creates sockets with the ambient authority of the
+/// host process. The addresses that a guest may use are restricted by the
+/// `NetworkPolicy` of its `WasiCtx`.
+pub struct Network(());
+
+impl Network {
+    pub fn new(ambient_authority: AmbientAuthority) -> Self {
+        let _ = ambient_authority;
+        Network(())
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiNetwork for Network {
+    async fn connect_tcp(&self, addr: SocketAddr) -> Result<Box<dyn WasiFile>, Error> {
+        let stream = std::net::TcpStream::connect(addr)?;
+        let stream = cap_std::net::TcpStream::from_std(stream);
+        Ok(Box::new(TcpStream::from_cap_std(stream)))
+    }
+    async fn bind_udp(&self, addr: SocketAddr) -> Result<Box<dyn WasiFile>, Error> {
+        let socket = std::net::UdpSocket::bind(addr)?;
+        let socket = cap_std::net::UdpSocket::from_std(socket);
+        Ok(Box::new(UdpSocket::from_cap_std(socket)))
+    }
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, Error> {
+        let addrs = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| Error::not_found().context(format!("failed to resolve {host}: {e}")))?;
+        Ok(addrs.collect())
+    }
+}
+
 pub fn filetype_from(ft: &cap_std::fs::FileType) -> FileType {
     use cap_fs_ext::FileTypeExt;
     if ft.is_block_device() {
@@ -1,11 +1,17 @@
 use crate::clocks::WasiClocks;
 use crate::dir::{DirCaps, DirEntry, WasiDir};
-use crate::file::{FileCaps, FileEntry, WasiFile};
+use crate::file::{
+    FileCaps, FileEntry, FileEntryExt, RiFlags, RoFlags, SiFlags, TableFileExt, WasiFile,
+};
+use crate::net::{NetworkPolicy, SocketUse, WasiNetwork};
 use crate::sched::WasiSched;
 use crate::string_array::StringArray;
 use crate::table::Table;
-use crate::{Error, StringArrayError};
+use crate::trace::Trace;
+use crate::{Error, ErrorExt, StringArrayError};
 use cap_rand::RngCore;
+use std::io::{IoSlice, IoSliceMut};
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -28,6 +34,9 @@ pub struct WasiCtxInner {
     pub clocks: WasiClocks,
     pub sched: Box<dyn WasiSched>,
     pub table: Table,
+    pub trace: Option<Arc<Trace>>,
+    pub network: Option<Box<dyn WasiNetwork>>,
+    pub network_policy: NetworkPolicy,
 }
 
 impl WasiCtx {
@@ -44,6 +53,9 @@ impl WasiCtx {
             clocks,
             sched,
             table,
+            trace: None,
+            network: None,
+            network_policy: NetworkPolicy::default(),
         }));
         s.set_stdin(Box::new(crate::pipe::ReadPipe::new(std::io::empty())));
         s.set_stdout(Box::new(crate::pipe::WritePipe::new(std::io::sink())));
@@ -52,14 +64,23 @@ impl WasiCtx {
     }
 
     pub fn insert_file(&self, fd: u32, file: Box<dyn WasiFile>, caps: FileCaps) {
+        let file = self.traced(file);
         self.table()
             .insert_at(fd, Arc::new(FileEntry::new(caps, file)));
     }
 
     pub fn push_file(&self, file: Box<dyn WasiFile>, caps: FileCaps) -> Result<u32, Error> {
+        let file = self.traced(file);
         self.table().push(Arc::new(FileEntry::new(caps, file)))
     }
 
+    fn traced(&self, file: Box<dyn WasiFile>) -> Box<dyn WasiFile> {
+        match &self.trace {
+            Some(trace) => crate::trace::wrap_file(file, trace),
+            None => file,
+        }
+    }
+
     pub fn insert_dir(
         &self,
         fd: u32,
@@ -108,6 +129,116 @@ impl WasiCtx {
         Ok(())
     }
 
+    /// Records the results of host calls made through this context into
+    /// `trace`, or replays them from it, see the [`trace`](crate::trace)
+    /// module for details.
+    ///
+    /// This wraps the clocks, randomness and files currently in the context,
+    /// as well as any files added later.
+    pub fn set_trace(&mut self, trace: Trace) {
+        let s = Arc::get_mut(&mut self.0).expect(
+            "`set_trace` should only be used during initialization before the context is cloned",
+        );
+        assert!(s.trace.is_none(), "a trace has already been set");
+        let trace = Arc::new(trace);
+        crate::trace::install(s, &trace);
+        s.trace = Some(trace);
+    }
+
+    /// Lets the guest create sockets with `network`, restricted to the
+    /// addresses allowed by [`WasiCtx::network_policy_mut`].
+    pub fn set_network(&mut self, network: Box<dyn WasiNetwork>) {
+        let s = Arc::get_mut(&mut self.0).expect(
+            "`set_network` should only be used during initialization before the context is cloned",
+        );
+        s.network = Some(network);
+    }
+
+    /// Returns the policy deciding which addresses the guest may use, which
+    /// denies everything until it's told otherwise.
+    pub fn network_policy_mut(&mut self) -> &mut NetworkPolicy {
+        let s = Arc::get_mut(&mut self.0).expect(
+            "`network_policy_mut` should only be used during initialization before the context is cloned",
+        );
+        &mut s.network_policy
+    }
+
+    fn network(&self) -> Result<&dyn WasiNetwork, Error> {
+        match &self.network {
+            Some(network) => Ok(&**network),
+            None => Err(Error::not_supported().context("no network is available")),
+        }
+    }
+
+    /// Connects a TCP stream to `addr` and returns its descriptor.
+    pub async fn sock_connect(&self, addr: SocketAddr) -> Result<u32, Error> {
+        self.network_policy.check(SocketUse::TcpConnect, addr)?;
+        let file = self.network()?.connect_tcp(addr).await?;
+        self.push_file(file, Self::socket_rights())
+    }
+
+    /// Binds a UDP socket to the local address `addr` and returns its
+    /// descriptor.
+    pub async fn sock_bind_udp(&self, addr: SocketAddr) -> Result<u32, Error> {
+        self.network_policy.check(SocketUse::UdpBind, addr)?;
+        let file = self.network()?.bind_udp(addr).await?;
+        self.push_file(file, Self::socket_rights())
+    }
+
+    /// Sends a datagram to `addr` on the socket `fd`.
+    pub async fn sock_send_to<'a>(
+        &self,
+        fd: u32,
+        si_data: &[IoSlice<'a>],
+        si_flags: SiFlags,
+        addr: SocketAddr,
+    ) -> Result<u64, Error> {
+        self.network_policy.check(SocketUse::UdpSend, addr)?;
+        let f = self.table().get_file(fd)?;
+        let f = f.get_cap(FileCaps::WRITE)?;
+        f.sock_send_to(si_data, si_flags, addr).await
+    }
+
+    /// Receives a datagram on the socket `fd`, returning its length and its
+    /// sender.
+    ///
+    /// Datagrams from addresses that the guest may not send to are dropped.
+    pub async fn sock_recv_from<'a>(
+        &self,
+        fd: u32,
+        ri_data: &mut [IoSliceMut<'a>],
+        ri_flags: RiFlags,
+    ) -> Result<(u64, RoFlags, SocketAddr), Error> {
+        let f = self.table().get_file(fd)?;
+        let f = f.get_cap(FileCaps::READ)?;
+        loop {
+            let (n, ro_flags, addr) = f.sock_recv_from(ri_data, ri_flags).await?;
+            if self.network_policy.is_allowed(SocketUse::UdpSend, addr) {
+                return Ok((n, ro_flags, addr));
+            }
+            if ri_flags.contains(RiFlags::RECV_PEEK) {
+                // Consume the datagram that was only peeked at.
+                f.sock_recv_from(&mut [], RiFlags::empty()).await?;
+            }
+        }
+    }
+
+    /// Resolves `host` to the addresses it names, with `port` as their port.
+    pub async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, Error> {
+        if !self.network_policy.is_name_lookup_allowed() {
+            return Err(Error::perm().context("name lookups are not allowed"));
+        }
+        self.network()?.resolve(host, port).await
+    }
+
+    fn socket_rights() -> FileCaps {
+        FileCaps::READ
+            | FileCaps::WRITE
+            | FileCaps::FDSTAT_SET_FLAGS
+            | FileCaps::POLL_READWRITE
+            | FileCaps::FILESTAT_GET
+    }
+
     pub fn set_stdin(&self, mut f: Box<dyn WasiFile>) {
         let rights = Self::stdio_rights(&mut *f);
         self.insert_file(0, f, rights);
@@ -1,6 +1,7 @@
 use crate::{Error, ErrorExt, SystemTimeSpec};
 use bitflags::bitflags;
 use std::any::Any;
+use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
 
 #[wiggle::async_trait]
@@ -46,6 +47,23 @@ pub trait WasiFile: Send + Sync {
         Err(Error::badf())
     }
 
+    async fn sock_recv_from<'a>(
+        &self,
+        _ri_data: &mut [std::io::IoSliceMut<'a>],
+        _ri_flags: RiFlags,
+    ) -> Result<(u64, RoFlags, SocketAddr), Error> {
+        Err(Error::badf())
+    }
+
+    async fn sock_send_to<'a>(
+        &self,
+        _si_data: &[std::io::IoSlice<'a>],
+        _si_flags: SiFlags,
+        _addr: SocketAddr,
+    ) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+
     async fn datasync(&self) -> Result<(), Error> {
         Ok(())
     }
@@ -235,6 +253,10 @@ impl FileEntry {
         }
     }
 
+    pub(crate) fn into_parts(self) -> (FileCaps, Box<dyn WasiFile>) {
+        (self.caps.into_inner().unwrap(), self.file)
+    }
+
     pub fn capable_of(&self, caps: FileCaps) -> Result<(), Error> {
         if self.caps.read().unwrap().contains(caps) {
             Ok(())
@@ -27,8 +27,9 @@
 //! This design makes it possible for `wasi-common` embedders to statically
 //! reason about access to the local filesystem by examining what impls are
 //! linked into an application. We found that this separation of concerns also
-//! makes it pretty enjoyable to write alternative implementations, e.g. a
-//! virtual filesystem (which will land in a future PR).
+//! makes it pretty enjoyable to write alternative implementations, e.g. the
+//! in-memory virtual filesystem of the `wasi-virtfs` crate found at
+//! `crates/wasi-common/virtfs`.
 //!
 //! ## Traits for the rest of WASI's features
 //!
@@ -44,7 +45,13 @@
 //! `cap_std::time::Instant`.  * Randomness: we re-use the `cap_rand::RngCore`
 //! trait to represent a randomness source. A trivial `Deterministic` impl is
 //! provided.  * Scheduling: The `WasiSched` trait abstracts over the
-//! `sched_yield` and `poll_oneoff` functions.
+//! `sched_yield` and `poll_oneoff` functions.  * Record and replay: a
+//! `Trace` installed with `WasiCtx::set_trace` records the clock readings and
+//! random bytes observed by a program, as well as its reads from files and
+//! sockets, so that its execution can be replayed later.  * Networking: the
+//! `WasiNetwork` trait creates outbound TCP streams and UDP sockets and
+//! resolves names, restricted to the addresses allowed by the context's
+//! `NetworkPolicy`.
 //!
 //! Users can provide implementations of each of these interfaces to the
 //! `WasiCtx::builder(...)` function. The
@@ -55,12 +62,14 @@ mod ctx;
 pub mod dir;
 mod error;
 pub mod file;
+pub mod net;
 pub mod pipe;
 pub mod random;
 pub mod sched;
 pub mod snapshots;
 mod string_array;
 pub mod table;
+pub mod trace;
 
 pub use cap_rand::RngCore;
 pub use clocks::{SystemTimeSpec, WasiClocks, WasiMonotonicClock, WasiSystemClock};
@@ -68,6 +77,8 @@ pub use ctx::WasiCtx;
 pub use dir::WasiDir;
 pub use error::{Error, ErrorExt, I32Exit};
 pub use file::WasiFile;
+pub use net::{NetworkPolicy, SocketUse, WasiNetwork};
 pub use sched::{Poll, WasiSched};
 pub use string_array::StringArrayError;
 pub use table::Table;
+pub use trace::Trace;
@@ -0,0 +1,195 @@
+//! Outbound networking: connecting TCP streams, sending and receiving UDP
+//! datagrams, and resolving names.
+//!
+//! A guest can't reach the network at all unless the embedder installs a
+//! [`WasiNetwork`] with [`WasiCtx::set_network`](crate::WasiCtx::set_network),
+//! and even then it can only reach the addresses allowed by the context's
+//! [`NetworkPolicy`]. The socket operations of [`WasiCtx`](crate::WasiCtx)
+//! check the policy before calling into the `WasiNetwork` or the sockets it
+//! created, so implementations of `WasiNetwork` and `WasiFile` don't need to
+//! enforce anything themselves.
+//!
+//! Guests call these operations through the `wasi_experimental_sockets`
+//! module, see [`crate::snapshots::experimental_sockets`].
+
+use crate::file::WasiFile;
+use crate::{Error, ErrorExt};
+use std::net::{IpAddr, SocketAddr};
+use std::ops::RangeInclusive;
+
+/// Creates sockets on behalf of a guest.
+#[wiggle::async_trait]
+pub trait WasiNetwork: Send + Sync {
+    /// Opens a TCP connection to `addr`.
+    async fn connect_tcp(&self, addr: SocketAddr) -> Result<Box<dyn WasiFile>, Error>;
+    /// Creates a UDP socket bound to the local address `addr`.
+    async fn bind_udp(&self, addr: SocketAddr) -> Result<Box<dyn WasiFile>, Error>;
+    /// Resolves `host` to the addresses it names, with `port` as their port.
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, Error>;
+}
+
+/// What a guest wants to use an address for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SocketUse {
+    /// Connecting a TCP stream to a remote address.
+    TcpConnect,
+    /// Binding a UDP socket to a local address.
+    UdpBind,
+    /// Sending datagrams to, and receiving them from, a remote address.
+    UdpSend,
+}
+
+/// The addresses that a guest may use, by default none.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkPolicy {
+    rules: Vec<Rule>,
+    name_lookup: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    use_: SocketUse,
+    net: IpAddr,
+    prefix_len: u8,
+    ports: RangeInclusive<u16>,
+}
+
+impl NetworkPolicy {
+    /// Allows `use_` of the addresses whose first `prefix_len` bits match
+    /// `net` and whose port is in `ports`.
+    ///
+    /// IPv4 addresses used through IPv6 sockets, as IPv4-mapped IPv6
+    /// addresses, are matched against IPv4 rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is longer than the address.
+    pub fn allow(
+        &mut self,
+        use_: SocketUse,
+        net: IpAddr,
+        prefix_len: u8,
+        ports: RangeInclusive<u16>,
+    ) {
+        let bits = match net {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        assert!(
+            prefix_len <= bits,
+            "prefix length {prefix_len} is too long for {net}"
+        );
+        self.rules.push(Rule {
+            use_,
+            net: canonical(net),
+            prefix_len,
+            ports,
+        });
+    }
+
+    /// Allows resolving names with [`WasiNetwork::resolve`].
+    ///
+    /// This doesn't allow using the resolved addresses for anything.
+    pub fn allow_name_lookup(&mut self) {
+        self.name_lookup = true;
+    }
+
+    /// Returns whether `use_` of `addr` is allowed.
+    pub fn is_allowed(&self, use_: SocketUse, addr: SocketAddr) -> bool {
+        let ip = canonical(addr.ip());
+        self.rules.iter().any(|rule| {
+            rule.use_ == use_
+                && rule.ports.contains(&addr.port())
+                && prefix_matches(rule.net, rule.prefix_len, ip)
+        })
+    }
+
+    /// Returns whether resolving names is allowed.
+    pub fn is_name_lookup_allowed(&self) -> bool {
+        self.name_lookup
+    }
+
+    pub(crate) fn check(&self, use_: SocketUse, addr: SocketAddr) -> Result<(), Error> {
+        if self.is_allowed(use_, addr) {
+            Ok(())
+        } else {
+            Err(Error::perm().context(format!("{use_:?} of {addr} is not allowed")))
+        }
+    }
+}
+
+/// Unwraps IPv4-mapped IPv6 addresses so that they match IPv4 rules.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => ip,
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+fn prefix_matches(net: IpAddr, prefix_len: u8, ip: IpAddr) -> bool {
+    let (net, ip, bits) = match (net, ip) {
+        (IpAddr::V4(net), IpAddr::V4(ip)) => {
+            (u128::from(u32::from(net)), u128::from(u32::from(ip)), 32)
+        }
+        (IpAddr::V6(net), IpAddr::V6(ip)) => (u128::from(net), u128::from(ip), 128),
+        _ => return false,
+    };
+    let shift = bits - u32::from(prefix_len);
+    shift == bits || (net >> shift) == (ip >> shift)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn denies_by_default() {
+        let policy = NetworkPolicy::default();
+        assert!(!policy.is_allowed(SocketUse::TcpConnect, addr("127.0.0.1:80")));
+        assert!(!policy.is_name_lookup_allowed());
+    }
+
+    #[test]
+    fn matches_prefixes_and_ports() {
+        let mut policy = NetworkPolicy::default();
+        policy.allow(
+            SocketUse::TcpConnect,
+            "10.1.0.0".parse().unwrap(),
+            16,
+            80..=443,
+        );
+        policy.allow(SocketUse::UdpSend, "fe80::".parse().unwrap(), 10, 0..=65535);
+
+        assert!(policy.is_allowed(SocketUse::TcpConnect, addr("10.1.2.3:80")));
+        assert!(policy.is_allowed(SocketUse::TcpConnect, addr("10.1.255.255:443")));
+        assert!(!policy.is_allowed(SocketUse::TcpConnect, addr("10.2.0.1:80")));
+        assert!(!policy.is_allowed(SocketUse::TcpConnect, addr("10.1.2.3:8080")));
+        assert!(!policy.is_allowed(SocketUse::UdpSend, addr("10.1.2.3:80")));
+        assert!(policy.is_allowed(SocketUse::UdpSend, addr("[fe80::1]:53")));
+        assert!(!policy.is_allowed(SocketUse::UdpSend, addr("[fec0::1]:53")));
+    }
+
+    #[test]
+    fn matches_mapped_addresses_and_whole_ranges() {
+        let mut policy = NetworkPolicy::default();
+        policy.allow(
+            SocketUse::TcpConnect,
+            "127.0.0.1".parse().unwrap(),
+            32,
+            8000..=8000,
+        );
+        policy.allow(SocketUse::UdpBind, "::".parse().unwrap(), 0, 0..=0);
+
+        assert!(policy.is_allowed(SocketUse::TcpConnect, addr("[::ffff:127.0.0.1]:8000")));
+        assert!(!policy.is_allowed(SocketUse::TcpConnect, addr("127.0.0.2:8000")));
+        assert!(policy.is_allowed(SocketUse::UdpBind, addr("[::1]:0")));
+        assert!(!policy.is_allowed(SocketUse::UdpBind, addr("0.0.0.0:0")));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::file::{RiFlags, RoFlags, SiFlags};
+use crate::snapshots::preview_1::types as snapshot1_types;
+use crate::snapshots::preview_1::MAX_SHARED_BUFFER_SIZE;
+use crate::WasiCtx;
+use std::convert::{TryFrom, TryInto};
+use std::io::{IoSlice, IoSliceMut};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::Deref;
+use wiggle::GuestPtr;
+
+wiggle::from_witx!({
+    witx: ["$WASI_COMMON_WITX/wasi_experimental_sockets.witx"],
+    errors: { errno => trappable Error },
+    async: *,
+    wasmtime: false,
+});
+
+use types::Error;
+
+impl wiggle::GuestErrorType for types::Errno {
+    fn success() -> Self {
+        Self::Success
+    }
+}
+
+impl From<wiggle::GuestError> for Error {
+    fn from(err: wiggle::GuestError) -> Error {
+        snapshot1_types::Error::from(err).into()
+    }
+}
+
+impl From<snapshot1_types::Error> for Error {
+    fn from(error: snapshot1_types::Error) -> Error {
+        match error.downcast() {
+            Ok(errno) => Error::from(types::Errno::from(errno)),
+            Err(trap) => Error::trap(trap),
+        }
+    }
+}
+
+impl From<std::num::TryFromIntError> for Error {
+    fn from(_err: std::num::TryFromIntError) -> Error {
+        types::Errno::Overflow.into()
+    }
+}
+
+/// Both snapshot 1 and this module take their `errno` from the same witx
+/// document, so every value has a counterpart.
+impl From<snapshot1_types::Errno> for types::Errno {
+    fn from(errno: snapshot1_types::Errno) -> types::Errno {
+        types::Errno::try_from(u16::from(errno)).expect("errno is shared with snapshot 1")
+    }
+}
+
+#[wiggle::async_trait]
+impl wasi_experimental_sockets::WasiExperimentalSockets for WasiCtx {
+    async fn sock_connect<'a>(
+        &mut self,
+        addr: &GuestPtr<'a, types::SocketAddress<'a>>,
+    ) -> Result<types::Fd, Error> {
+        let addr = read_socket_address(addr)?;
+        Ok(WasiCtx::sock_connect(self, addr).await?.into())
+    }
+
+    async fn sock_bind_udp<'a>(
+        &mut self,
+        addr: &GuestPtr<'a, types::SocketAddress<'a>>,
+    ) -> Result<types::Fd, Error> {
+        let addr = read_socket_address(addr)?;
+        Ok(WasiCtx::sock_bind_udp(self, addr).await?.into())
+    }
+
+    async fn sock_send_to<'a>(
+        &mut self,
+        fd: types::Fd,
+        si_data: &types::CiovecArray<'a>,
+        _si_flags: types::Siflags,
+        addr: &GuestPtr<'a, types::SocketAddress<'a>>,
+    ) -> Result<types::Size, Error> {
+        let addr = read_socket_address(addr)?;
+
+        let guest_slices: Vec<wiggle::GuestCow<u8>> = si_data
+            .iter()
+            .map(|iov_ptr| {
+                let iov_ptr = iov_ptr?;
+                let iov: types::Ciovec = iov_ptr.read()?;
+                Ok(iov.buf.as_array(iov.buf_len).as_cow()?)
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let ioslices: Vec<IoSlice> = guest_slices
+            .iter()
+            .map(|s| IoSlice::new(s.deref()))
+            .collect();
+        let bytes_written =
+            WasiCtx::sock_send_to(self, u32::from(fd), &ioslices, SiFlags::empty(), addr).await?;
+
+        Ok(types::Size::try_from(bytes_written)?)
+    }
+
+    async fn sock_recv_from<'a>(
+        &mut self,
+        fd: types::Fd,
+        ri_data: &types::IovecArray<'a>,
+        ri_flags: types::Riflags,
+        addr: &GuestPtr<'a, types::SocketAddress<'a>>,
+    ) -> Result<(types::Size, types::Roflags), Error> {
+        let iovs: Vec<wiggle::GuestPtr<[u8]>> = ri_data
+            .iter()
+            .map(|iov_ptr| {
+                let iov_ptr = iov_ptr?;
+                let iov: types::Iovec = iov_ptr.read()?;
+                Ok(iov.buf.as_array(iov.buf_len))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        // A datagram has to be received in a single call, so it goes into an
+        // intermediate buffer which is then scattered over the iovs. This also
+        // keeps us from holding a `&mut [u8]` of shared memory. No datagram is
+        // larger than `MAX_SHARED_BUFFER_SIZE`.
+        let len = iovs.iter().map(|iov| iov.len() as usize).sum::<usize>();
+        let mut buffer = vec![0; len.min(MAX_SHARED_BUFFER_SIZE)];
+        let (bytes_read, ro_flags, sender) = WasiCtx::sock_recv_from(
+            self,
+            u32::from(fd),
+            &mut [IoSliceMut::new(&mut buffer)],
+            RiFlags::from(ri_flags),
+        )
+        .await?;
+
+        let mut rest = &buffer[..bytes_read.try_into()?];
+        for iov in iovs {
+            if rest.is_empty() {
+                break;
+            }
+            let n = rest.len().min(iov.len() as usize);
+            iov.get_range(0..n.try_into()?)
+                .expect("it should always be possible to slice the iov smaller")
+                .copy_from_slice(&rest[..n])?;
+            rest = &rest[n..];
+        }
+        write_socket_address(addr, sender)?;
+
+        Ok((types::Size::try_from(bytes_read)?, ro_flags.into()))
+    }
+
+    async fn resolve<'a>(
+        &mut self,
+        host: &GuestPtr<'a, str>,
+        port: u16,
+        addrs: &GuestPtr<'a, types::SocketAddress<'a>>,
+        addrs_len: types::Size,
+    ) -> Result<types::Size, Error> {
+        let host = host.as_cow()?;
+        let resolved = WasiCtx::resolve(self, &host, port).await?;
+
+        let mut stored = 0;
+        for (addr, resolved) in addrs.as_array(addrs_len).iter().zip(resolved) {
+            write_socket_address(&addr?, resolved)?;
+            stored += 1;
+        }
+        Ok(stored)
+    }
+}
+
+fn read_socket_address<'a>(
+    addr: &GuestPtr<'a, types::SocketAddress<'a>>,
+) -> Result<SocketAddr, Error> {
+    let addr = addr.read()?;
+    let ip = match addr.family {
+        types::AddressFamily::Inet4 => {
+            let mut octets = [0; 4];
+            octets.copy_from_slice(&addr.octets.as_array(4).to_vec()?);
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        types::AddressFamily::Inet6 => {
+            let mut octets = [0; 16];
+            octets.copy_from_slice(&addr.octets.as_array(16).to_vec()?);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+    };
+    Ok(SocketAddr::new(ip, addr.port))
+}
+
+fn write_socket_address<'a>(
+    ptr: &GuestPtr<'a, types::SocketAddress<'a>>,
+    addr: SocketAddr,
+) -> Result<(), Error> {
+    let octets = ptr.read()?.octets;
+    let family = match addr.ip() {
+        IpAddr::V4(ip) => {
+            octets.as_array(4).copy_from_slice(&ip.octets())?;
+            types::AddressFamily::Inet4
+        }
+        IpAddr::V6(ip) => {
+            octets.as_array(16).copy_from_slice(&ip.octets())?;
+            types::AddressFamily::Inet6
+        }
+    };
+    ptr.write(types::SocketAddress {
+        family,
+        port: addr.port(),
+        octets,
+    })?;
+    Ok(())
+}
+
+impl From<types::Riflags> for RiFlags {
+    fn from(flags: types::Riflags) -> RiFlags {
+        let mut out = RiFlags::empty();
+        if flags.contains(types::Riflags::RECV_PEEK) {
+            out |= RiFlags::RECV_PEEK;
+        }
+        if flags.contains(types::Riflags::RECV_WAITALL) {
+            out |= RiFlags::RECV_WAITALL;
+        }
+        out
+    }
+}
+
+impl From<RoFlags> for types::Roflags {
+    fn from(flags: RoFlags) -> types::Roflags {
+        let mut out = types::Roflags::empty();
+        if flags.contains(RoFlags::RECV_DATA_TRUNCATED) {
+            out |= types::Roflags::RECV_DATA_TRUNCATED;
+        }
+        out
+    }
+}
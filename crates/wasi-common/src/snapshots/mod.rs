@@ -22,6 +22,11 @@
 //! * Snapshots can be implemented in terms of the `Wasi*` traits given by
 //! `WasiCtx`. No further downcasting via the `as_any` escape hatch is
 //! permitted.
+//!
+//! Besides the snapshots, `experimental_sockets` gives Preview 1 guests the
+//! outbound networking of `crate::net`. It is defined by a witx document of
+//! our own and shares its types with snapshot 1.
 
+pub mod experimental_sockets;
 pub mod preview_0;
 pub mod preview_1;
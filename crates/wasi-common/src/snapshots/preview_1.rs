@@ -773,7 +773,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for WasiCtx {
                 .open_file(symlink_follow, path.deref(), oflags, read, write, fdflags)
                 .await?;
             drop(dir);
-            let fd = table.push(Arc::new(FileEntry::new(file_caps, file)))?;
+            let fd = self.push_file(file, file_caps)?;
             Ok(types::Fd::from(fd))
         }
     }
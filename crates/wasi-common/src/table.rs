@@ -50,6 +50,11 @@ impl Table {
         }
     }
 
+    /// Returns the indices of all resources in the table.
+    pub(crate) fn keys(&self) -> Vec<u32> {
+        self.0.read().unwrap().map.keys().copied().collect()
+    }
+
     /// Check if the table has a resource at the given index.
     pub fn contains_key(&self, key: u32) -> bool {
         self.0.read().unwrap().map.contains_key(&key)
@@ -0,0 +1,774 @@
+//! Recording and replaying the nondeterministic results of host calls.
+//!
+//! A [`Trace`] installed in a [`WasiCtx`](crate::WasiCtx) with
+//! [`WasiCtx::set_trace`](crate::WasiCtx::set_trace) either records or
+//! replays the result of every host call whose outcome isn't determined by
+//! the guest's own state:
+//!
+//! * readings of the system and monotonic clocks,
+//! * bytes produced by the random number generator,
+//! * data read from files, pipes, stdin and sockets, along with any errors,
+//! * the outcome of accepting connections on a socket.
+//!
+//! When replaying, these results are served from the trace instead of the
+//! host, so a guest that runs on an engine configured with
+//! `Config::deterministic_execution` executes exactly as it did when the
+//! trace was recorded. Other operations, such as writes, seeks and metadata
+//! queries, are still performed against the host, and the readiness of
+//! subscriptions in `poll_oneoff` is not recorded, nor are the connections
+//! made, the datagrams received and the names resolved through a
+//! [`WasiNetwork`](crate::net::WasiNetwork). If the guest makes a
+//! different sequence of host calls than the one that was recorded then the
+//! replay has diverged, and the next fallible host call traps.
+//!
+//! Events are recorded in the order they happen, which is only meaningful if
+//! the guest is single-threaded.
+
+use crate::clocks::{WasiMonotonicClock, WasiSystemClock};
+use crate::error::Errno;
+use crate::file::{
+    Advice, FdFlags, FileEntry, FileType, Filestat, RiFlags, RoFlags, SdFlags, SiFlags, WasiFile,
+};
+use crate::{Error, ErrorExt, SystemTimeSpec};
+use cap_rand::RngCore;
+use cap_std::time::{Duration, Instant, SystemTime};
+use std::any::Any;
+use std::io::{self, IoSlice, IoSliceMut, Read, SeekFrom, Write};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+const MAGIC: &[u8; 8] = b"\0wasitrc";
+const VERSION: u32 = 1;
+
+/// A trace of host call results, either being recorded or replayed.
+pub struct Trace(Mutex<State>);
+
+struct State {
+    mode: Mode,
+    /// Description of the first failure to record or replay the trace, after
+    /// which the trace is no longer used.
+    failure: Option<String>,
+}
+
+enum Mode {
+    Record(Box<dyn Write + Send>),
+    Replay(Box<dyn Read + Send>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    SystemClock = 0,
+    MonotonicClock = 1,
+    Random = 2,
+    Read = 3,
+    Recv = 4,
+    Accept = 5,
+}
+
+impl Kind {
+    fn from_u8(kind: u8) -> Option<Kind> {
+        Some(match kind {
+            0 => Kind::SystemClock,
+            1 => Kind::MonotonicClock,
+            2 => Kind::Random,
+            3 => Kind::Read,
+            4 => Kind::Recv,
+            5 => Kind::Accept,
+            _ => return None,
+        })
+    }
+}
+
+/// The result of a fallible host call: the bytes it produced along with an
+/// extra word of flags.
+type Outcome = Result<(Vec<u8>, u32), Error>;
+
+const OUTCOME_OK: u8 = 0;
+const OUTCOME_ERRNO: u8 = 1;
+const OUTCOME_TRAP: u8 = 2;
+
+impl Trace {
+    /// Creates a trace which records host call results into `writer`.
+    ///
+    /// Each event is flushed to `writer` as soon as it happens, so the trace
+    /// is complete up to the point of a crash.
+    pub fn record(writer: impl Write + Send + 'static) -> Result<Trace, Error> {
+        let mut writer = Box::new(writer);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.flush()?;
+        Ok(Trace::new(Mode::Record(writer)))
+    }
+
+    /// Creates a trace which replays host call results previously recorded
+    /// with [`Trace::record`] from `reader`.
+    pub fn replay(reader: impl Read + Send + 'static) -> Result<Trace, Error> {
+        let mut reader = Box::new(reader);
+        let mut header = [0; 12];
+        reader.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(Error::invalid_argument().context("not a WASI trace"));
+        }
+        let version = u32::from_le_bytes(header[8..].try_into().unwrap());
+        if version != VERSION {
+            return Err(Error::invalid_argument()
+                .context(format!("unsupported WASI trace version {version}")));
+        }
+        Ok(Trace::new(Mode::Replay(reader)))
+    }
+
+    fn new(mode: Mode) -> Trace {
+        Trace(Mutex::new(State {
+            mode,
+            failure: None,
+        }))
+    }
+
+    /// Returns whether this trace is replaying a previous execution.
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.0.lock().unwrap().mode, Mode::Replay(_))
+    }
+
+    /// Completes the trace once the guest has finished executing.
+    ///
+    /// Returns an error if the trace could not be written, if the replayed
+    /// execution diverged from the recorded one, or if the replayed execution
+    /// ended before consuming every recorded event.
+    pub fn finish(&self) -> Result<(), Error> {
+        let mut state = self.0.lock().unwrap();
+        if let Some(failure) = &state.failure {
+            return Err(Error::trap(anyhow::Error::msg(failure.clone())));
+        }
+        match &mut state.mode {
+            Mode::Record(writer) => writer.flush()?,
+            Mode::Replay(reader) => {
+                if reader.read(&mut [0])? != 0 {
+                    return Err(Error::trap(anyhow::Error::msg(
+                        "execution ended before replaying every event of the trace",
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records or replays the reading of a clock, represented as a
+    /// `Duration` from some fixed point in time. Clock readings are
+    /// infallible, so the host clock is used if the trace is unusable.
+    fn duration(&self, kind: Kind, host: impl FnOnce() -> Duration) -> Duration {
+        let mut state = self.0.lock().unwrap();
+        if !state.is_replaying() {
+            let d = host();
+            let mut payload = d.as_secs().to_le_bytes().to_vec();
+            payload.extend_from_slice(&d.subsec_nanos().to_le_bytes());
+            state.write(kind, &payload);
+            return d;
+        }
+        let event = state.read(kind).and_then(|r| {
+            let secs = read_u64(r)?;
+            let nanos = read_u32(r)?;
+            Ok(Duration::new(secs, nanos))
+        });
+        match event {
+            Ok(d) => d,
+            Err(e) => {
+                state.fail(e);
+                host()
+            }
+        }
+    }
+
+    fn diverged(&self, what: &str) -> Error {
+        self.0
+            .lock()
+            .unwrap()
+            .fail(format!("execution diverged from WASI trace: {what}"))
+    }
+
+    fn record_outcome(&self, kind: Kind, outcome: Result<(&[u8], u32), &Error>) {
+        let mut payload = Vec::new();
+        match outcome {
+            Ok((data, extra)) => {
+                payload.push(OUTCOME_OK);
+                payload.extend_from_slice(&extra.to_le_bytes());
+                payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                payload.extend_from_slice(data);
+            }
+            Err(err) => match err.downcast_ref() {
+                Some(errno) => {
+                    payload.push(OUTCOME_ERRNO);
+                    payload.extend_from_slice(&u16::from(*errno).to_le_bytes());
+                }
+                None => {
+                    let msg = err.to_string();
+                    payload.push(OUTCOME_TRAP);
+                    payload.extend_from_slice(&(msg.len() as u32).to_le_bytes());
+                    payload.extend_from_slice(msg.as_bytes());
+                }
+            },
+        }
+        self.0.lock().unwrap().write(kind, &payload);
+    }
+
+    fn replay_outcome(&self, kind: Kind) -> Outcome {
+        let mut state = self.0.lock().unwrap();
+        let event = state.read(kind).and_then(|r| match read_u8(r)? {
+            OUTCOME_OK => {
+                let extra = read_u32(r)?;
+                let len = read_u32(r)?;
+                Ok(Ok((read_bytes(r, len)?, extra)))
+            }
+            OUTCOME_ERRNO => {
+                let errno = Errno::try_from(read_u16(r)?)
+                    .map_err(|_| format!("invalid errno in {kind:?} event"))?;
+                Ok(Err(Error::from(errno)))
+            }
+            OUTCOME_TRAP => {
+                let len = read_u32(r)?;
+                let msg = String::from_utf8(read_bytes(r, len)?)
+                    .map_err(|_| format!("invalid trap message in {kind:?} event"))?;
+                Ok(Err(Error::trap(anyhow::Error::msg(msg))))
+            }
+            status => Err(format!("invalid status {status} in {kind:?} event")),
+        });
+        match event {
+            Ok(outcome) => outcome,
+            Err(e) => Err(state.fail(e)),
+        }
+    }
+}
+
+impl State {
+    fn is_replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    fn write(&mut self, kind: Kind, payload: &[u8]) {
+        if self.failure.is_some() {
+            return;
+        }
+        if let Mode::Record(writer) = &mut self.mode {
+            let result = writer
+                .write_all(&[kind as u8])
+                .and_then(|()| writer.write_all(payload))
+                .and_then(|()| writer.flush());
+            if let Err(e) = result {
+                self.fail(format!("failed to write WASI trace: {e}"));
+            }
+        }
+    }
+
+    /// Reads the header of the next event, which must be of type `kind`, and
+    /// returns the reader positioned at the event's payload.
+    fn read(&mut self, kind: Kind) -> Result<&mut dyn Read, String> {
+        if let Some(failure) = &self.failure {
+            return Err(failure.clone());
+        }
+        let reader = match &mut self.mode {
+            Mode::Replay(reader) => reader,
+            Mode::Record(_) => unreachable!(),
+        };
+        let mut found = [0];
+        if reader.read(&mut found).map_err(|e| e.to_string())? == 0 {
+            return Err(format!(
+                "execution diverged from WASI trace: expected {kind:?} event, found end of trace"
+            ));
+        }
+        match Kind::from_u8(found[0]) {
+            Some(found) if found == kind => Ok(&mut **reader),
+            Some(found) => Err(format!(
+                "execution diverged from WASI trace: expected {kind:?} event, found {found:?}"
+            )),
+            None => Err(format!("invalid event type {} in WASI trace", found[0])),
+        }
+    }
+
+    fn fail(&mut self, msg: String) -> Error {
+        let msg = self.failure.get_or_insert(msg).clone();
+        Error::trap(anyhow::Error::msg(msg))
+    }
+}
+
+fn read_bytes(reader: &mut dyn Read, len: u32) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    reader
+        .take(u64::from(len))
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    if bytes.len() != len as usize {
+        return Err("unexpected end of WASI trace".to_string());
+    }
+    Ok(bytes)
+}
+
+macro_rules! read_int {
+    ($name:ident, $ty:ident) => {
+        fn $name(reader: &mut dyn Read) -> Result<$ty, String> {
+            let bytes = read_bytes(reader, std::mem::size_of::<$ty>() as u32)?;
+            Ok($ty::from_le_bytes(bytes.try_into().unwrap()))
+        }
+    };
+}
+
+read_int!(read_u8, u8);
+read_int!(read_u16, u16);
+read_int!(read_u32, u32);
+read_int!(read_u64, u64);
+
+/// Installs `trace` into the clocks, random number generator and files of a
+/// context.
+pub(crate) fn install(ctx: &mut crate::ctx::WasiCtxInner, trace: &Arc<Trace>) {
+    let random = ctx.random.get_mut().unwrap();
+    let inner = std::mem::replace(random, Box::new(crate::random::Deterministic::new(vec![0])));
+    *random = Box::new(TracedRandom {
+        inner,
+        trace: trace.clone(),
+    });
+
+    ctx.clocks.system = ctx.clocks.system.take().map(|inner| {
+        Box::new(TracedSystemClock {
+            inner,
+            trace: trace.clone(),
+        }) as Box<dyn WasiSystemClock>
+    });
+    if let Some(clock) = &mut ctx.clocks.monotonic {
+        let inner = std::mem::replace(&mut clock.abs_clock, Box::new(NoClock));
+        clock.abs_clock = Box::new(TracedMonotonicClock {
+            inner,
+            base: clock.creation_time,
+            trace: trace.clone(),
+        });
+    }
+
+    for fd in ctx.table.keys() {
+        if !ctx.table.is::<FileEntry>(fd) {
+            continue;
+        }
+        let entry = ctx.table.delete::<FileEntry>(fd).unwrap();
+        let (caps, file) = Arc::try_unwrap(entry)
+            .ok()
+            .expect("files should not be in use while installing a trace")
+            .into_parts();
+        ctx.table
+            .insert_at(fd, Arc::new(FileEntry::new(caps, wrap_file(file, trace))));
+    }
+}
+
+/// Wraps `file` so that the results of reading from it are traced.
+pub(crate) fn wrap_file(inner: Box<dyn WasiFile>, trace: &Arc<Trace>) -> Box<dyn WasiFile> {
+    Box::new(TracedFile {
+        inner,
+        trace: trace.clone(),
+    })
+}
+
+struct TracedRandom {
+    inner: Box<dyn RngCore + Send + Sync>,
+    trace: Arc<Trace>,
+}
+
+impl RngCore for TracedRandom {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        if self.try_fill_bytes(buf).is_err() {
+            self.inner.fill_bytes(buf);
+        }
+    }
+    fn try_fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), cap_rand::Error> {
+        if !self.trace.is_replaying() {
+            let result = self.inner.try_fill_bytes(buf);
+            match &result {
+                Ok(()) => self.trace.record_outcome(Kind::Random, Ok((&*buf, 0))),
+                Err(e) => self.trace.record_outcome(
+                    Kind::Random,
+                    Err(&Error::trap(anyhow::Error::msg(e.to_string()))),
+                ),
+            }
+            return result;
+        }
+        let err = match self.trace.replay_outcome(Kind::Random) {
+            Ok((data, _)) if data.len() == buf.len() => {
+                buf.copy_from_slice(&data);
+                return Ok(());
+            }
+            Ok(_) => self.trace.diverged("different amount of random bytes"),
+            Err(e) => e,
+        };
+        Err(cap_rand::Error::new(io::Error::new(
+            io::ErrorKind::Other,
+            err.to_string(),
+        )))
+    }
+}
+
+struct TracedSystemClock {
+    inner: Box<dyn WasiSystemClock>,
+    trace: Arc<Trace>,
+}
+
+impl WasiSystemClock for TracedSystemClock {
+    fn resolution(&self) -> Duration {
+        self.inner.resolution()
+    }
+    fn now(&self, precision: Duration) -> SystemTime {
+        // Times before the epoch can't be represented by WASI and are
+        // recorded as the epoch itself.
+        let d = self.trace.duration(Kind::SystemClock, || {
+            self.inner
+                .now(precision)
+                .into_std()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+        });
+        SystemTime::from_std(std::time::UNIX_EPOCH + d)
+    }
+}
+
+/// A monotonic clock whose readings are traced relative to `base`, the
+/// creation time of the context's clock, as `Instant`s themselves are opaque.
+struct TracedMonotonicClock {
+    inner: Box<dyn WasiMonotonicClock>,
+    base: Instant,
+    trace: Arc<Trace>,
+}
+
+impl WasiMonotonicClock for TracedMonotonicClock {
+    fn resolution(&self) -> Duration {
+        self.inner.resolution()
+    }
+    fn now(&self, precision: Duration) -> Instant {
+        self.base
+            + self.trace.duration(Kind::MonotonicClock, || {
+                self.inner.now(precision).duration_since(self.base)
+            })
+    }
+}
+
+/// Placeholder used while moving a clock into its traced wrapper.
+struct NoClock;
+
+impl WasiMonotonicClock for NoClock {
+    fn resolution(&self) -> Duration {
+        unreachable!()
+    }
+    fn now(&self, _precision: Duration) -> Instant {
+        unreachable!()
+    }
+}
+
+fn gather(bufs: &[IoSliceMut<'_>], len: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(len as usize);
+    for buf in bufs {
+        let n = buf.len().min(len as usize - data.len());
+        data.extend_from_slice(&buf[..n]);
+    }
+    data
+}
+
+fn scatter(trace: &Trace, bufs: &mut [IoSliceMut<'_>], mut data: &[u8]) -> Result<u64, Error> {
+    let len = data.len() as u64;
+    for buf in bufs.iter_mut() {
+        let n = buf.len().min(data.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        data = &data[n..];
+    }
+    if !data.is_empty() {
+        return Err(trace.diverged("read into a smaller buffer"));
+    }
+    Ok(len)
+}
+
+struct TracedFile {
+    inner: Box<dyn WasiFile>,
+    trace: Arc<Trace>,
+}
+
+impl TracedFile {
+    fn record_read(&self, kind: Kind, bufs: &[IoSliceMut<'_>], result: &Result<u64, Error>) {
+        let data = result.as_ref().map(|n| gather(bufs, *n));
+        let outcome = data.as_ref().map(|data| (&data[..], 0)).map_err(|e| *e);
+        self.trace.record_outcome(kind, outcome);
+    }
+}
+
+#[wiggle::async_trait]
+impl WasiFile for TracedFile {
+    fn as_any(&self) -> &dyn Any {
+        self.inner.as_any()
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        self.inner.get_filetype().await
+    }
+    #[cfg(unix)]
+    fn pollable(&self) -> Option<rustix::fd::BorrowedFd> {
+        self.inner.pollable()
+    }
+    #[cfg(windows)]
+    fn pollable(&self) -> Option<io_extras::os::windows::RawHandleOrSocket> {
+        self.inner.pollable()
+    }
+    fn isatty(&self) -> bool {
+        self.inner.isatty()
+    }
+    async fn sock_accept(&self, fdflags: FdFlags) -> Result<Box<dyn WasiFile>, Error> {
+        if self.trace.is_replaying() {
+            self.trace.replay_outcome(Kind::Accept)?;
+            return Ok(wrap_file(Box::new(ReplayedSocket), &self.trace));
+        }
+        let result = self.inner.sock_accept(fdflags).await;
+        self.trace
+            .record_outcome(Kind::Accept, result.as_ref().map(|_| (&[][..], 0)));
+        Ok(wrap_file(result?, &self.trace))
+    }
+    async fn sock_recv<'a>(
+        &self,
+        ri_data: &mut [IoSliceMut<'a>],
+        ri_flags: RiFlags,
+    ) -> Result<(u64, RoFlags), Error> {
+        if self.trace.is_replaying() {
+            let (data, flags) = self.trace.replay_outcome(Kind::Recv)?;
+            let n = scatter(&self.trace, ri_data, &data)?;
+            return Ok((n, RoFlags::from_bits_truncate(flags)));
+        }
+        let result = self.inner.sock_recv(ri_data, ri_flags).await;
+        let data = result
+            .as_ref()
+            .map(|(n, flags)| (gather(ri_data, *n), flags.bits()));
+        let outcome = data
+            .as_ref()
+            .map(|(data, flags)| (&data[..], *flags))
+            .map_err(|e| *e);
+        self.trace.record_outcome(Kind::Recv, outcome);
+        result
+    }
+    async fn sock_send<'a>(
+        &self,
+        si_data: &[IoSlice<'a>],
+        si_flags: SiFlags,
+    ) -> Result<u64, Error> {
+        self.inner.sock_send(si_data, si_flags).await
+    }
+    async fn sock_shutdown(&self, how: SdFlags) -> Result<(), Error> {
+        self.inner.sock_shutdown(how).await
+    }
+    async fn sock_recv_from<'a>(
+        &self,
+        ri_data: &mut [IoSliceMut<'a>],
+        ri_flags: RiFlags,
+    ) -> Result<(u64, RoFlags, SocketAddr), Error> {
+        self.inner.sock_recv_from(ri_data, ri_flags).await
+    }
+    async fn sock_send_to<'a>(
+        &self,
+        si_data: &[IoSlice<'a>],
+        si_flags: SiFlags,
+        addr: SocketAddr,
+    ) -> Result<u64, Error> {
+        self.inner.sock_send_to(si_data, si_flags, addr).await
+    }
+    async fn datasync(&self) -> Result<(), Error> {
+        self.inner.datasync().await
+    }
+    async fn sync(&self) -> Result<(), Error> {
+        self.inner.sync().await
+    }
+    async fn get_fdflags(&self) -> Result<FdFlags, Error> {
+        self.inner.get_fdflags().await
+    }
+    async fn set_fdflags(&mut self, flags: FdFlags) -> Result<(), Error> {
+        self.inner.set_fdflags(flags).await
+    }
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        self.inner.get_filestat().await
+    }
+    async fn set_filestat_size(&self, size: u64) -> Result<(), Error> {
+        self.inner.set_filestat_size(size).await
+    }
+    async fn advise(&self, offset: u64, len: u64, advice: Advice) -> Result<(), Error> {
+        self.inner.advise(offset, len, advice).await
+    }
+    async fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        self.inner.allocate(offset, len).await
+    }
+    async fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> Result<(), Error> {
+        self.inner.set_times(atime, mtime).await
+    }
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        if self.trace.is_replaying() {
+            let (data, _) = self.trace.replay_outcome(Kind::Read)?;
+            let n = scatter(&self.trace, bufs, &data)?;
+            // Keep the position of seekable files in sync with the recorded
+            // execution, for anything that isn't seekable this fails and
+            // is ignored.
+            let _ = self.inner.seek(SeekFrom::Current(n as i64)).await;
+            return Ok(n);
+        }
+        let result = self.inner.read_vectored(bufs).await;
+        self.record_read(Kind::Read, bufs, &result);
+        result
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        bufs: &mut [IoSliceMut<'a>],
+        offset: u64,
+    ) -> Result<u64, Error> {
+        if self.trace.is_replaying() {
+            let (data, _) = self.trace.replay_outcome(Kind::Read)?;
+            return scatter(&self.trace, bufs, &data);
+        }
+        let result = self.inner.read_vectored_at(bufs, offset).await;
+        self.record_read(Kind::Read, bufs, &result);
+        result
+    }
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        self.inner.write_vectored(bufs).await
+    }
+    async fn write_vectored_at<'a>(&self, bufs: &[IoSlice<'a>], offset: u64) -> Result<u64, Error> {
+        self.inner.write_vectored_at(bufs, offset).await
+    }
+    async fn seek(&self, pos: SeekFrom) -> Result<u64, Error> {
+        self.inner.seek(pos).await
+    }
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error> {
+        let bufs = &mut [IoSliceMut::new(buf)];
+        if self.trace.is_replaying() {
+            let (data, _) = self.trace.replay_outcome(Kind::Read)?;
+            return scatter(&self.trace, bufs, &data);
+        }
+        let result = self.inner.peek(&mut bufs[0]).await;
+        self.record_read(Kind::Read, bufs, &result);
+        result
+    }
+    fn num_ready_bytes(&self) -> Result<u64, Error> {
+        self.inner.num_ready_bytes()
+    }
+    async fn readable(&self) -> Result<(), Error> {
+        self.inner.readable().await
+    }
+    async fn writable(&self) -> Result<(), Error> {
+        self.inner.writable().await
+    }
+}
+
+/// A connection accepted during a replay, all data received on it comes
+/// from the trace and all data sent to it is discarded.
+struct ReplayedSocket;
+
+#[wiggle::async_trait]
+impl WasiFile for ReplayedSocket {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::SocketStream)
+    }
+    async fn set_fdflags(&mut self, _flags: FdFlags) -> Result<(), Error> {
+        Ok(())
+    }
+    async fn sock_send<'a>(
+        &self,
+        si_data: &[IoSlice<'a>],
+        _si_flags: SiFlags,
+    ) -> Result<u64, Error> {
+        self.write_vectored(si_data).await
+    }
+    async fn sock_shutdown(&self, _how: SdFlags) -> Result<(), Error> {
+        Ok(())
+    }
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        Ok(bufs.iter().map(|b| b.len() as u64).sum())
+    }
+    async fn readable(&self) -> Result<(), Error> {
+        Ok(())
+    }
+    async fn writable(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pipe::ReadPipe;
+
+    struct Clock(u64);
+
+    impl WasiSystemClock for Clock {
+        fn resolution(&self) -> Duration {
+            Duration::from_nanos(1)
+        }
+        fn now(&self, _precision: Duration) -> SystemTime {
+            SystemTime::from_std(std::time::UNIX_EPOCH + Duration::from_secs(self.0))
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Runs a clock reading, random fill and file read against `trace` with
+    // the given host state, returning what the guest would observe.
+    fn run(trace: &Arc<Trace>, time: u64, seed: u8, contents: &str) -> (SystemTime, u32, Vec<u8>) {
+        let clock = TracedSystemClock {
+            inner: Box::new(Clock(time)),
+            trace: trace.clone(),
+        };
+        let mut random = TracedRandom {
+            inner: Box::new(crate::random::Deterministic::new(vec![seed])),
+            trace: trace.clone(),
+        };
+        let file = wrap_file(Box::new(ReadPipe::from(contents)), trace);
+        let mut buf = [0; 8];
+        let n = wiggle::run_in_dummy_executor(file.read_vectored(&mut [IoSliceMut::new(&mut buf)]))
+            .unwrap()
+            .unwrap();
+        (
+            clock.now(Duration::from_nanos(1)),
+            random.next_u32(),
+            buf[..n as usize].to_vec(),
+        )
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let buffer = Buffer::default();
+        let trace = Arc::new(Trace::record(buffer.clone()).unwrap());
+        let recorded = run(&trace, 1, 7, "hello");
+        trace.finish().unwrap();
+
+        // Replaying against different host state observes the recorded one.
+        let bytes = buffer.0.lock().unwrap().clone();
+        let trace = Arc::new(Trace::replay(io::Cursor::new(bytes)).unwrap());
+        assert_eq!(run(&trace, 2, 8, "bye"), recorded);
+        trace.finish().unwrap();
+
+        // Any further events diverge from the trace.
+        let mut random = TracedRandom {
+            inner: Box::new(crate::random::Deterministic::new(vec![0])),
+            trace: trace.clone(),
+        };
+        assert!(random.try_fill_bytes(&mut [0; 4]).is_err());
+        assert!(trace.finish().is_err());
+    }
+}
@@ -6,8 +6,9 @@ use io_lifetimes::AsFd;
 use std::any::Any;
 use std::borrow::Borrow;
 use std::io;
+use std::net::SocketAddr;
 use wasi_common::{
-    file::{Advice, FdFlags, FileType, Filestat, WasiFile},
+    file::{Advice, FdFlags, FileType, Filestat, RiFlags, RoFlags, SiFlags, WasiFile},
     Error,
 };
 
@@ -44,6 +45,17 @@ impl TcpStream {
     }
 }
 
+pub struct UdpSocket(wasi_cap_std_sync::net::UdpSocket);
+
+impl UdpSocket {
+    pub(crate) fn from_inner(socket: wasi_cap_std_sync::net::UdpSocket) -> Self {
+        UdpSocket(socket)
+    }
+    pub fn from_cap_std(socket: cap_std::net::UdpSocket) -> Self {
+        Self::from_inner(wasi_cap_std_sync::net::UdpSocket::from_cap_std(socket))
+    }
+}
+
 #[cfg(unix)]
 pub struct UnixListener(wasi_cap_std_sync::net::UnixListener);
 
@@ -224,6 +236,21 @@ macro_rules! wasi_file_impl {
             async fn sock_accept(&self, fdflags: FdFlags) -> Result<Box<dyn WasiFile>, Error> {
                 block_on_dummy_executor(|| self.0.sock_accept(fdflags))
             }
+            async fn sock_recv_from<'a>(
+                &self,
+                ri_data: &mut [io::IoSliceMut<'a>],
+                ri_flags: RiFlags,
+            ) -> Result<(u64, RoFlags, SocketAddr), Error> {
+                block_on_dummy_executor(move || self.0.sock_recv_from(ri_data, ri_flags))
+            }
+            async fn sock_send_to<'a>(
+                &self,
+                si_data: &[io::IoSlice<'a>],
+                si_flags: SiFlags,
+                addr: SocketAddr,
+            ) -> Result<u64, Error> {
+                block_on_dummy_executor(move || self.0.sock_send_to(si_data, si_flags, addr))
+            }
         }
         #[cfg(windows)]
         impl AsRawHandleOrSocket for $ty {
@@ -238,6 +265,7 @@ macro_rules! wasi_file_impl {
 wasi_file_impl!(File);
 wasi_file_impl!(TcpListener);
 wasi_file_impl!(TcpStream);
+wasi_file_impl!(UdpSocket);
 #[cfg(unix)]
 wasi_file_impl!(UnixListener);
 #[cfg(unix)]
@@ -1,6 +1,47 @@
 pub use crate::file::TcpListener;
 pub use crate::file::TcpStream;
+pub use crate::file::UdpSocket;
 #[cfg(unix)]
 pub use crate::file::UnixListener;
 #[cfg(unix)]
 pub use crate::file::UnixStream;
+
+use cap_std::AmbientAuthority;
+use std::net::SocketAddr;
+use wasi_common::{Error, ErrorExt, WasiFile, WasiNetwork};
+
+/// A `WasiNetwork` which creates sockets with the ambient authority of the
+/// host process, connecting and resolving names without blocking the
+/// executor. The addresses that a guest may use are restricted by the
+/// `NetworkPolicy` of its `WasiCtx`.
+pub struct Network(());
+
+impl Network {
+    pub fn new(ambient_authority: AmbientAuthority) -> Self {
+        let _ = ambient_authority;
+        Network(())
+    }
+}
+
+#[wiggle::async_trait]
+impl WasiNetwork for Network {
+    async fn connect_tcp(&self, addr: SocketAddr) -> Result<Box<dyn WasiFile>, Error> {
+        let stream = tokio::net::TcpStream::connect(addr).await?.into_std()?;
+        // The `WasiFile` impls perform blocking syscalls unless the guest
+        // asks for `NONBLOCK`.
+        stream.set_nonblocking(false)?;
+        let stream = cap_std::net::TcpStream::from_std(stream);
+        Ok(Box::new(TcpStream::from_cap_std(stream)))
+    }
+    async fn bind_udp(&self, addr: SocketAddr) -> Result<Box<dyn WasiFile>, Error> {
+        let socket = std::net::UdpSocket::bind(addr)?;
+        let socket = cap_std::net::UdpSocket::from_std(socket);
+        Ok(Box::new(UdpSocket::from_cap_std(socket)))
+    }
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, Error> {
+        let addrs = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| Error::not_found().context(format!("failed to resolve {host}: {e}")))?;
+        Ok(addrs.collect())
+    }
+}
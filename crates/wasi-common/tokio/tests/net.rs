@@ -0,0 +1,134 @@
+use anyhow::{Context, Error};
+use cap_std::ambient_authority;
+use std::io::{IoSlice, IoSliceMut};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, UdpSocket};
+use wasi_common::file::{RiFlags, SiFlags};
+use wasi_common::snapshots::preview_1::types::Errno;
+use wasi_tokio::WasiCtxBuilder;
+
+const LOCALHOST: Ipv4Addr = Ipv4Addr::LOCALHOST;
+
+fn errno(err: wasi_common::Error) -> Errno {
+    err.downcast().expect("an errno")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tcp_connect_is_allowed_by_policy() -> Result<(), Error> {
+    let listener = TcpListener::bind((LOCALHOST, 0))?;
+    let addr = listener.local_addr()?;
+    let ctx = WasiCtxBuilder::new()
+        .inherit_network(ambient_authority())
+        .allow_tcp_connect(LOCALHOST.into(), 32, addr.port()..=addr.port())
+        .build();
+
+    let fd = ctx.sock_connect(addr).await.context("connect")?;
+    assert!(ctx.table().contains_key(fd));
+    let (_stream, peer) = listener.accept()?;
+    assert_eq!(peer.ip(), LOCALHOST);
+
+    // Another port, or another address in the same network, is denied
+    // without attempting to connect.
+    let denied = SocketAddr::from((LOCALHOST, addr.port().wrapping_add(1)));
+    let err = ctx.sock_connect(denied).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Perm);
+    let denied = SocketAddr::from(([127, 0, 0, 2], addr.port()));
+    let err = ctx.sock_connect(denied).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Perm);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_is_unavailable_by_default() -> Result<(), Error> {
+    let listener = TcpListener::bind((LOCALHOST, 0))?;
+    let addr = listener.local_addr()?;
+
+    // Without a network nothing can be reached, even if the policy allows it.
+    let ctx = WasiCtxBuilder::new()
+        .allow_tcp_connect(LOCALHOST.into(), 8, 0..=65535)
+        .allow_name_lookup()
+        .build();
+    let err = ctx.sock_connect(addr).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Notsup);
+    let err = ctx.resolve("localhost", 80).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Notsup);
+
+    // And with a network nothing is allowed until the policy says so.
+    let ctx = WasiCtxBuilder::new()
+        .inherit_network(ambient_authority())
+        .build();
+    let err = ctx.sock_connect(addr).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Perm);
+    let err = ctx.sock_bind_udp((LOCALHOST, 0).into()).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Perm);
+    let err = ctx.resolve("localhost", 80).await.unwrap_err();
+    assert_eq!(errno(err), Errno::Perm);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn udp_exchanges_datagrams_with_allowed_peers() -> Result<(), Error> {
+    let peer = UdpSocket::bind((LOCALHOST, 0))?;
+    let peer_addr = peer.local_addr()?;
+    let stranger = UdpSocket::bind((LOCALHOST, 0))?;
+    let ctx = WasiCtxBuilder::new()
+        .inherit_network(ambient_authority())
+        .allow_udp_bind(LOCALHOST.into(), 32, 0..=0)
+        .allow_udp_send(LOCALHOST.into(), 32, peer_addr.port()..=peer_addr.port())
+        .build();
+
+    let fd = ctx.sock_bind_udp((LOCALHOST, 0).into()).await?;
+    let sent = ctx
+        .sock_send_to(
+            fd,
+            &[IoSlice::new(b"hello, "), IoSlice::new(b"peer")],
+            SiFlags::empty(),
+            peer_addr,
+        )
+        .await?;
+    assert_eq!(sent, 11);
+    let mut buf = [0; 32];
+    let (n, addr) = peer.recv_from(&mut buf)?;
+    assert_eq!(&buf[..n], b"hello, peer");
+
+    let err = ctx
+        .sock_send_to(
+            fd,
+            &[IoSlice::new(b"hi")],
+            SiFlags::empty(),
+            stranger.local_addr()?,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(errno(err), Errno::Perm);
+
+    // Datagrams from addresses the guest can't send to are dropped.
+    stranger.send_to(b"intruder", addr)?;
+    peer.send_to(b"hello, guest", addr)?;
+    let mut head = [0; 5];
+    let mut tail = [0; 32];
+    let (n, _, from) = ctx
+        .sock_recv_from(
+            fd,
+            &mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)],
+            RiFlags::empty(),
+        )
+        .await?;
+    assert_eq!(from, peer_addr);
+    assert_eq!(n, 12);
+    assert_eq!(&head, b"hello");
+    assert_eq!(&tail[..7], b", guest");
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn name_lookup_is_allowed_by_policy() -> Result<(), Error> {
+    let ctx = WasiCtxBuilder::new()
+        .inherit_network(ambient_authority())
+        .allow_name_lookup()
+        .build();
+    let addrs = ctx.resolve("localhost", 8080).await?;
+    assert!(addrs
+        .iter()
+        .any(|addr| addr.ip().is_loopback() && addr.port() == 8080));
+    Ok(())
+}
@@ -0,0 +1,343 @@
+use crate::file::File;
+use crate::fs::{Kind, Lookup, State, VirtualFs};
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use wasi_common::{
+    dir::{ReaddirCursor, ReaddirEntity, WasiDir},
+    file::{FdFlags, FileType, Filestat, OFlags, WasiFile},
+    snapshots::preview_1::types::Errno,
+    Error, ErrorExt, SystemTimeSpec,
+};
+
+/// A handle to a directory of a [`VirtualFs`].
+///
+/// A directory which is removed while a handle refers to it stays empty, and
+/// nothing can be created in it.
+pub struct Dir {
+    fs: VirtualFs,
+    ino: u64,
+}
+
+impl Dir {
+    pub(crate) fn new(fs: VirtualFs, ino: u64) -> Self {
+        fs.lock().inode_mut(ino).handles += 1;
+        Dir { fs, ino }
+    }
+
+    /// Returns the filesystem this directory belongs to.
+    pub fn fs(&self) -> &VirtualFs {
+        &self.fs
+    }
+
+    pub fn open_file_(
+        &self,
+        symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        read: bool,
+        write: bool,
+        fdflags: FdFlags,
+    ) -> Result<File, Error> {
+        let mut state = self.fs.lock();
+        let lookup = state.resolve(self.ino, path, symlink_follow)?;
+        let ino = match lookup {
+            Lookup { ino: Some(_), .. } if oflags.contains(OFlags::CREATE | OFlags::EXCLUSIVE) => {
+                return Err(Error::exist());
+            }
+            Lookup { ino: Some(ino), .. } => ino,
+            Lookup {
+                parent,
+                name: Some(name),
+                ino: None,
+            } if oflags.contains(OFlags::CREATE) => {
+                state.dir_entries(parent)?;
+                let ino = state.alloc(Kind::File(Arc::new(Vec::new())));
+                state.link(parent, &name, ino);
+                ino
+            }
+            Lookup { ino: None, .. } => return Err(Error::not_found()),
+        };
+        match &mut state.inode_mut(ino).kind {
+            Kind::File(data) => {
+                if oflags.contains(OFlags::TRUNCATE) {
+                    if !write {
+                        return Err(Error::perm().context("truncating a read-only file"));
+                    }
+                    Arc::make_mut(data).clear();
+                }
+            }
+            Kind::Dir { .. } => return Err(Errno::Isdir.into()),
+            Kind::Symlink(_) => return Err(Errno::Loop.into()),
+        }
+        drop(state);
+        Ok(File::new(self.fs.clone(), ino, read, write, fdflags))
+    }
+
+    pub fn open_dir_(&self, symlink_follow: bool, path: &str) -> Result<Self, Error> {
+        let state = self.fs.lock();
+        let ino = state.resolve_existing(self.ino, path, symlink_follow)?;
+        match state.inode(ino).kind {
+            Kind::Dir { .. } => {}
+            Kind::Symlink(_) if !symlink_follow => return Err(Errno::Loop.into()),
+            _ => return Err(Error::not_dir()),
+        }
+        drop(state);
+        Ok(Dir::new(self.fs.clone(), ino))
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        self.fs.lock().release_handle(self.ino);
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiDir for Dir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn open_file(
+        &self,
+        symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        read: bool,
+        write: bool,
+        fdflags: FdFlags,
+    ) -> Result<Box<dyn WasiFile>, Error> {
+        let f = self.open_file_(symlink_follow, path, oflags, read, write, fdflags)?;
+        Ok(Box::new(f))
+    }
+
+    async fn open_dir(&self, symlink_follow: bool, path: &str) -> Result<Box<dyn WasiDir>, Error> {
+        let d = self.open_dir_(symlink_follow, path)?;
+        Ok(Box::new(d))
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), Error> {
+        let mut state = self.fs.lock();
+        let (parent, name) = resolve_new(&state, self.ino, path)?;
+        let ino = state.alloc(Kind::Dir {
+            entries: BTreeMap::new(),
+            parent,
+        });
+        state.link(parent, &name, ino);
+        Ok(())
+    }
+
+    async fn readdir(
+        &self,
+        cursor: ReaddirCursor,
+    ) -> Result<Box<dyn Iterator<Item = Result<ReaddirEntity, Error>> + Send>, Error> {
+        let state = self.fs.lock();
+        let entries = match &state.inode(self.ino).kind {
+            Kind::Dir { entries, .. } => entries,
+            _ => unreachable!("a `Dir` always refers to a directory"),
+        };
+        let mut list = vec![
+            (".".to_owned(), self.ino, FileType::Directory),
+            (
+                "..".to_owned(),
+                state.dir_parent(self.ino),
+                FileType::Directory,
+            ),
+        ];
+        for (name, ino) in entries {
+            list.push((name.clone(), *ino, state.inode(*ino).filetype()));
+        }
+        let entries = list
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, inode, filetype))| {
+                Ok(ReaddirEntity {
+                    next: ReaddirCursor::from(i as u64 + 1),
+                    inode,
+                    name,
+                    filetype,
+                })
+            })
+            .skip(u64::from(cursor) as usize);
+        Ok(Box::new(entries))
+    }
+
+    async fn symlink(&self, src_path: &str, dest_path: &str) -> Result<(), Error> {
+        let mut state = self.fs.lock();
+        let (parent, name) = resolve_new(&state, self.ino, dest_path)?;
+        let ino = state.alloc(Kind::Symlink(src_path.to_owned()));
+        state.link(parent, &name, ino);
+        Ok(())
+    }
+
+    async fn remove_dir(&self, path: &str) -> Result<(), Error> {
+        let mut state = self.fs.lock();
+        let (parent, name, ino) = resolve_entry(&state, self.ino, path.trim_end_matches('/'))?;
+        match &state.inode(ino).kind {
+            Kind::Dir { entries, .. } if !entries.is_empty() => return Err(Errno::Notempty.into()),
+            Kind::Dir { .. } => {}
+            _ => return Err(Error::not_dir()),
+        }
+        state.unlink(parent, &name);
+        Ok(())
+    }
+
+    async fn unlink_file(&self, path: &str) -> Result<(), Error> {
+        let mut state = self.fs.lock();
+        let (parent, name, ino) = resolve_entry(&state, self.ino, path)?;
+        if let Kind::Dir { .. } = state.inode(ino).kind {
+            return Err(Errno::Isdir.into());
+        }
+        state.unlink(parent, &name);
+        Ok(())
+    }
+
+    async fn read_link(&self, path: &str) -> Result<PathBuf, Error> {
+        let state = self.fs.lock();
+        let ino = state.resolve_existing(self.ino, path, false)?;
+        match &state.inode(ino).kind {
+            Kind::Symlink(target) => Ok(PathBuf::from(target)),
+            _ => Err(Error::invalid_argument().context("not a symbolic link")),
+        }
+    }
+
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        Ok(self.fs.lock().filestat(self.ino))
+    }
+
+    async fn get_path_filestat(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+    ) -> Result<Filestat, Error> {
+        let state = self.fs.lock();
+        let ino = state.resolve_existing(self.ino, path, follow_symlinks)?;
+        Ok(state.filestat(ino))
+    }
+
+    async fn rename(
+        &self,
+        src_path: &str,
+        dest_dir: &dyn WasiDir,
+        dest_path: &str,
+    ) -> Result<(), Error> {
+        let dest_dir = dest_dir
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or(Error::badf().context("failed downcast to virtual filesystem Dir"))?;
+        if !self.fs.same_fs(&dest_dir.fs) {
+            return Err(Errno::Xdev.into());
+        }
+        let mut state = self.fs.lock();
+        let (src_parent, src_name, ino) =
+            resolve_entry(&state, self.ino, src_path.trim_end_matches('/'))?;
+        let dest = state.resolve(dest_dir.ino, dest_path.trim_end_matches('/'), false)?;
+        let dest_name = dest
+            .name
+            .ok_or_else(|| Error::perm().context("path ends with `.` or `..`"))?;
+        state.dir_entries(dest.parent)?;
+
+        let is_dir = matches!(state.inode(ino).kind, Kind::Dir { .. });
+        if is_dir {
+            // A directory can't be moved into itself.
+            let mut dir = dest.parent;
+            loop {
+                if dir == ino {
+                    return Err(Error::invalid_argument().context("moving a directory into itself"));
+                }
+                let parent = state.dir_parent(dir);
+                if parent == dir {
+                    break;
+                }
+                dir = parent;
+            }
+        }
+        if let Some(existing) = dest.ino {
+            if existing == ino {
+                return Ok(());
+            }
+            match (&state.inode(existing).kind, is_dir) {
+                (Kind::Dir { entries, .. }, true) if !entries.is_empty() => {
+                    return Err(Errno::Notempty.into())
+                }
+                (Kind::Dir { .. }, true) => {}
+                (Kind::Dir { .. }, false) => return Err(Errno::Isdir.into()),
+                (_, true) => return Err(Error::not_dir()),
+                (_, false) => {}
+            }
+            state.unlink(dest.parent, &dest_name);
+        }
+        // Link the new name first so that the inode isn't freed.
+        state.link(dest.parent, &dest_name, ino);
+        state.unlink(src_parent, &src_name);
+        Ok(())
+    }
+
+    async fn hard_link(
+        &self,
+        src_path: &str,
+        target_dir: &dyn WasiDir,
+        target_path: &str,
+    ) -> Result<(), Error> {
+        let target_dir = target_dir
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or(Error::badf().context("failed downcast to virtual filesystem Dir"))?;
+        if !self.fs.same_fs(&target_dir.fs) {
+            return Err(Errno::Xdev.into());
+        }
+        let mut state = self.fs.lock();
+        let (_, _, ino) = resolve_entry(&state, self.ino, src_path)?;
+        let (parent, name) = resolve_new(&state, target_dir.ino, target_path)?;
+        if let Kind::Dir { .. } = state.inode(ino).kind {
+            return Err(Error::perm().context("hard link to a directory"));
+        }
+        state.link(parent, &name, ino);
+        Ok(())
+    }
+
+    async fn set_times(
+        &self,
+        path: &str,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+        follow_symlinks: bool,
+    ) -> Result<(), Error> {
+        let mut state = self.fs.lock();
+        let ino = state.resolve_existing(self.ino, path, follow_symlinks)?;
+        state.inode_mut(ino).set_times(atime, mtime);
+        Ok(())
+    }
+}
+
+/// Resolves `path` relative to `dir` to an entry which must not exist yet,
+/// returning its parent directory and name.
+fn resolve_new(state: &State, dir: u64, path: &str) -> Result<(u64, String), Error> {
+    match state.resolve(dir, path.trim_end_matches('/'), false)? {
+        Lookup { ino: Some(_), .. } | Lookup { name: None, .. } => Err(Error::exist()),
+        Lookup {
+            parent,
+            name: Some(name),
+            ..
+        } => {
+            state.dir_entries(parent)?;
+            Ok((parent, name))
+        }
+    }
+}
+
+/// Resolves `path` relative to `dir` to an existing entry, without following
+/// a final symbolic link, returning its parent directory, name and inode.
+fn resolve_entry(state: &State, dir: u64, path: &str) -> Result<(u64, String, u64), Error> {
+    match state.resolve(dir, path, false)? {
+        Lookup {
+            parent,
+            name: Some(name),
+            ino: Some(ino),
+        } => Ok((parent, name, ino)),
+        Lookup { name: None, .. } => Err(Error::perm().context("path ends with `.` or `..`")),
+        Lookup { ino: None, .. } => Err(Error::not_found()),
+    }
+}
@@ -0,0 +1,235 @@
+use crate::fs::{Kind, VirtualFs};
+use std::any::Any;
+use std::io::{self, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use wasi_common::{
+    file::{Advice, FdFlags, FileType, Filestat, WasiFile},
+    Error, ErrorExt, SystemTimeSpec,
+};
+
+/// A handle to a regular file of a [`VirtualFs`], with its own position.
+///
+/// A file which is unlinked while a handle refers to it keeps its contents
+/// until the last handle is dropped.
+pub struct File {
+    fs: VirtualFs,
+    ino: u64,
+    /// The position of the handle, only updated while the filesystem is
+    /// locked.
+    position: AtomicU64,
+    fdflags: FdFlags,
+    read: bool,
+    write: bool,
+}
+
+impl File {
+    pub(crate) fn new(fs: VirtualFs, ino: u64, read: bool, write: bool, fdflags: FdFlags) -> Self {
+        fs.lock().inode_mut(ino).handles += 1;
+        File {
+            fs,
+            ino,
+            position: AtomicU64::new(0),
+            fdflags,
+            read,
+            write,
+        }
+    }
+
+    fn check_read(&self) -> Result<(), Error> {
+        if self.read {
+            Ok(())
+        } else {
+            Err(Error::badf().context("file not opened for reading"))
+        }
+    }
+
+    fn check_write(&self) -> Result<(), Error> {
+        if self.write {
+            Ok(())
+        } else {
+            Err(Error::badf().context("file not opened for writing"))
+        }
+    }
+
+    /// Runs `f` on the contents of the file, with the filesystem locked.
+    fn with_data<T>(&self, f: impl FnOnce(&Arc<Vec<u8>>) -> T) -> T {
+        let state = self.fs.lock();
+        match &state.inode(self.ino).kind {
+            Kind::File(data) => f(data),
+            _ => unreachable!("a `File` always refers to a regular file"),
+        }
+    }
+
+    /// Runs `f` on the contents of the file, copying them first if they're
+    /// shared, with the filesystem locked.
+    fn with_data_mut<T>(&self, f: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+        let mut state = self.fs.lock();
+        match &mut state.inode_mut(self.ino).kind {
+            Kind::File(data) => f(Arc::make_mut(data)),
+            _ => unreachable!("a `File` always refers to a regular file"),
+        }
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        self.fs.lock().release_handle(self.ino);
+    }
+}
+
+fn read_at(data: &[u8], bufs: &mut [io::IoSliceMut], offset: u64) -> u64 {
+    let mut pos = usize::try_from(offset)
+        .unwrap_or(usize::MAX)
+        .min(data.len());
+    let start = pos;
+    for buf in bufs {
+        let n = buf.len().min(data.len() - pos);
+        buf[..n].copy_from_slice(&data[pos..pos + n]);
+        pos += n;
+    }
+    (pos - start) as u64
+}
+
+fn write_at(data: &mut Vec<u8>, bufs: &[io::IoSlice], offset: u64) -> Result<u64, Error> {
+    let len: usize = bufs.iter().map(|buf| buf.len()).sum();
+    let end = usize::try_from(offset)
+        .ok()
+        .and_then(|offset| offset.checked_add(len))
+        .ok_or_else(Error::too_big)?;
+    if data.len() < end {
+        data.resize(end, 0);
+    }
+    let mut pos = end - len;
+    for buf in bufs {
+        data[pos..pos + buf.len()].copy_from_slice(buf);
+        pos += buf.len();
+    }
+    Ok(len as u64)
+}
+
+#[async_trait::async_trait]
+impl WasiFile for File {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::RegularFile)
+    }
+    async fn get_fdflags(&self) -> Result<FdFlags, Error> {
+        Ok(self.fdflags)
+    }
+    async fn set_fdflags(&mut self, fdflags: FdFlags) -> Result<(), Error> {
+        if fdflags.intersects(FdFlags::DSYNC | FdFlags::SYNC | FdFlags::RSYNC) {
+            return Err(Error::invalid_argument().context("cannot set DSYNC, SYNC, or RSYNC flag"));
+        }
+        self.fdflags = fdflags;
+        Ok(())
+    }
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        Ok(self.fs.lock().filestat(self.ino))
+    }
+    async fn set_filestat_size(&self, size: u64) -> Result<(), Error> {
+        self.check_write()?;
+        let size = usize::try_from(size).map_err(|_| Error::too_big())?;
+        self.with_data_mut(|data| data.resize(size, 0));
+        Ok(())
+    }
+    async fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> Result<(), Error> {
+        Ok(())
+    }
+    async fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        self.check_write()?;
+        let end = offset
+            .checked_add(len)
+            .and_then(|end| usize::try_from(end).ok())
+            .ok_or_else(Error::too_big)?;
+        self.with_data_mut(|data| {
+            if data.len() < end {
+                data.resize(end, 0);
+            }
+        });
+        Ok(())
+    }
+    async fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> Result<(), Error> {
+        self.fs.lock().inode_mut(self.ino).set_times(atime, mtime);
+        Ok(())
+    }
+    async fn read_vectored<'a>(&self, bufs: &mut [io::IoSliceMut<'a>]) -> Result<u64, Error> {
+        self.check_read()?;
+        Ok(self.with_data(|data| {
+            let n = read_at(data, bufs, self.position.load(Ordering::Relaxed));
+            self.position.fetch_add(n, Ordering::Relaxed);
+            n
+        }))
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        bufs: &mut [io::IoSliceMut<'a>],
+        offset: u64,
+    ) -> Result<u64, Error> {
+        self.check_read()?;
+        Ok(self.with_data(|data| read_at(data, bufs, offset)))
+    }
+    async fn write_vectored<'a>(&self, bufs: &[io::IoSlice<'a>]) -> Result<u64, Error> {
+        self.check_write()?;
+        self.with_data_mut(|data| {
+            let offset = if self.fdflags.contains(FdFlags::APPEND) {
+                data.len() as u64
+            } else {
+                self.position.load(Ordering::Relaxed)
+            };
+            let n = write_at(data, bufs, offset)?;
+            self.position.store(offset + n, Ordering::Relaxed);
+            Ok(n)
+        })
+    }
+    async fn write_vectored_at<'a>(
+        &self,
+        bufs: &[io::IoSlice<'a>],
+        offset: u64,
+    ) -> Result<u64, Error> {
+        self.check_write()?;
+        self.with_data_mut(|data| write_at(data, bufs, offset))
+    }
+    async fn seek(&self, pos: SeekFrom) -> Result<u64, Error> {
+        self.with_data(|data| {
+            let position = match pos {
+                SeekFrom::Start(offset) => Some(offset),
+                SeekFrom::Current(offset) => self
+                    .position
+                    .load(Ordering::Relaxed)
+                    .checked_add_signed(offset),
+                SeekFrom::End(offset) => (data.len() as u64).checked_add_signed(offset),
+            };
+            let position = position
+                .ok_or_else(|| Error::invalid_argument().context("seek to a negative offset"))?;
+            self.position.store(position, Ordering::Relaxed);
+            Ok(position)
+        })
+    }
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error> {
+        self.check_read()?;
+        Ok(self.with_data(|data| {
+            read_at(
+                data,
+                &mut [io::IoSliceMut::new(buf)],
+                self.position.load(Ordering::Relaxed),
+            )
+        }))
+    }
+    fn num_ready_bytes(&self) -> Result<u64, Error> {
+        let position = self.position.load(Ordering::Relaxed);
+        Ok(self.with_data(|data| (data.len() as u64).saturating_sub(position)))
+    }
+    async fn readable(&self) -> Result<(), Error> {
+        Ok(())
+    }
+    async fn writable(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,487 @@
+use crate::dir::Dir;
+use crate::tree::{components, Entry, Tree, OPAQUE_WHITEOUT, WHITEOUT_PREFIX};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+use wasi_common::file::{FileType, Filestat};
+use wasi_common::snapshots::preview_1::types::Errno;
+use wasi_common::{Error, ErrorExt, SystemTimeSpec};
+
+/// The inode number of the root directory.
+pub(crate) const ROOT: u64 = 1;
+
+/// The maximum number of symbolic links followed while resolving a path.
+const MAX_SYMLINKS: usize = 40;
+
+/// An in-memory filesystem.
+///
+/// A `VirtualFs` is a handle to the filesystem, and its clones share the
+/// same contents. Directories are exposed to guests through [`Dir`], e.g.
+/// with [`VirtualFs::root`].
+#[derive(Clone)]
+pub struct VirtualFs {
+    state: Arc<Mutex<State>>,
+}
+
+pub(crate) struct State {
+    /// A number unique to this filesystem, reported as the device of its
+    /// files.
+    pub(crate) device_id: u64,
+    inodes: HashMap<u64, Inode>,
+    next_ino: u64,
+    /// The trees mounted in the filesystem, overlaid on one another.
+    base: Tree,
+}
+
+pub(crate) struct Inode {
+    pub(crate) kind: Kind,
+    /// The number of directory entries which refer to this inode.
+    pub(crate) nlink: u64,
+    /// The number of `Dir` and `File` handles which refer to this inode,
+    /// which keep it alive after it's unlinked.
+    pub(crate) handles: u64,
+    pub(crate) atim: Option<SystemTime>,
+    pub(crate) mtim: Option<SystemTime>,
+    pub(crate) ctim: Option<SystemTime>,
+}
+
+pub(crate) enum Kind {
+    File(Arc<Vec<u8>>),
+    Dir {
+        entries: BTreeMap<String, u64>,
+        parent: u64,
+    },
+    Symlink(String),
+}
+
+/// The result of resolving a path.
+pub(crate) struct Lookup {
+    /// The directory containing the entry.
+    pub(crate) parent: u64,
+    /// The name of the entry in `parent`, or `None` if the path ended with
+    /// `.` or `..`.
+    pub(crate) name: Option<String>,
+    /// The inode of the entry, or `None` if it doesn't exist.
+    pub(crate) ino: Option<u64>,
+}
+
+impl VirtualFs {
+    /// Creates an empty filesystem.
+    pub fn new() -> VirtualFs {
+        static NEXT_DEVICE_ID: AtomicU64 = AtomicU64::new(1);
+        let mut root = Inode::new(Kind::Dir {
+            entries: BTreeMap::new(),
+            parent: ROOT,
+        });
+        // The root is never unlinked.
+        root.nlink = 1;
+        let mut inodes = HashMap::new();
+        inodes.insert(ROOT, root);
+        VirtualFs {
+            state: Arc::new(Mutex::new(State {
+                device_id: NEXT_DEVICE_ID.fetch_add(1, Ordering::Relaxed),
+                inodes,
+                next_ino: ROOT + 1,
+                base: Tree::new(),
+            })),
+        }
+    }
+
+    /// Creates a filesystem with the contents of `tree`.
+    pub fn from_tree(tree: &Tree) -> VirtualFs {
+        let fs = VirtualFs::new();
+        fs.mount("", tree)
+            .expect("mounting at the root of a new filesystem cannot fail");
+        fs
+    }
+
+    /// Overlays `tree` on top of the directory at `path`, creating it if
+    /// needed, as a union mount, see [`Tree::overlay`].
+    ///
+    /// Entries of the tree replace any entries of the same name, except for
+    /// directories which are merged, and whiteouts in the tree delete
+    /// entries. Mounting several layers of a container image in order
+    /// reproduces its filesystem.
+    ///
+    /// The contents of the tree's files are shared with the filesystem until
+    /// they're written to. Files which are hard links to each other in the
+    /// tree are mounted as separate files.
+    ///
+    /// Symbolic links aren't followed in `path`, and `..` components aren't
+    /// allowed.
+    pub fn mount(&self, path: &str, tree: &Tree) -> Result<(), Error> {
+        let components =
+            components(path).ok_or_else(|| Error::perm().context("`..` in mount path"))?;
+        let mut state = self.lock();
+        let mut dir = ROOT;
+        for name in &components {
+            dir = match state.dir_entries(dir)?.get(*name) {
+                Some(ino) => *ino,
+                None => {
+                    let ino = state.alloc(Kind::Dir {
+                        entries: BTreeMap::new(),
+                        parent: dir,
+                    });
+                    state.link(dir, name, ino);
+                    ino
+                }
+            };
+        }
+        state.dir_entries(dir)?;
+        state.overlay(dir, tree);
+        state.base.overlay_at(path, tree);
+        Ok(())
+    }
+
+    /// Returns a handle to the root directory of the filesystem, to be
+    /// preopened in a `WasiCtx`.
+    pub fn root(&self) -> Dir {
+        Dir::new(self.clone(), ROOT)
+    }
+
+    /// Returns the current contents of the filesystem.
+    ///
+    /// Files which are hard links to each other share their contents in the
+    /// returned tree.
+    pub fn snapshot(&self) -> Tree {
+        let state = self.lock();
+        state.tree(ROOT)
+    }
+
+    /// Returns the changes made to the filesystem since the trees were
+    /// mounted in it, as a layer which reproduces the current contents when
+    /// it's overlaid on those trees, see [`Tree::changes_from`].
+    pub fn changes(&self) -> Tree {
+        let state = self.lock();
+        state.tree(ROOT).changes_from(&state.base)
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    pub(crate) fn same_fs(&self, other: &VirtualFs) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Default for VirtualFs {
+    fn default() -> VirtualFs {
+        VirtualFs::new()
+    }
+}
+
+impl Inode {
+    fn new(kind: Kind) -> Inode {
+        Inode {
+            kind,
+            nlink: 0,
+            handles: 0,
+            atim: None,
+            mtim: None,
+            ctim: None,
+        }
+    }
+
+    pub(crate) fn filetype(&self) -> FileType {
+        match self.kind {
+            Kind::File(_) => FileType::RegularFile,
+            Kind::Dir { .. } => FileType::Directory,
+            Kind::Symlink(_) => FileType::SymbolicLink,
+        }
+    }
+
+    pub(crate) fn set_times(
+        &mut self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) {
+        let time = |spec: SystemTimeSpec| match spec {
+            SystemTimeSpec::SymbolicNow => SystemTime::now(),
+            SystemTimeSpec::Absolute(time) => time.into_std(),
+        };
+        if let Some(atime) = atime {
+            self.atim = Some(time(atime));
+        }
+        if let Some(mtime) = mtime {
+            self.mtim = Some(time(mtime));
+        }
+    }
+}
+
+impl State {
+    pub(crate) fn inode(&self, ino: u64) -> &Inode {
+        &self.inodes[&ino]
+    }
+
+    pub(crate) fn inode_mut(&mut self, ino: u64) -> &mut Inode {
+        self.inodes.get_mut(&ino).unwrap()
+    }
+
+    pub(crate) fn filestat(&self, ino: u64) -> Filestat {
+        let inode = self.inode(ino);
+        let size = match &inode.kind {
+            Kind::File(data) => data.len() as u64,
+            Kind::Dir { entries, .. } => entries.len() as u64,
+            Kind::Symlink(target) => target.len() as u64,
+        };
+        Filestat {
+            device_id: self.device_id,
+            inode: ino,
+            filetype: inode.filetype(),
+            nlink: inode.nlink,
+            size,
+            atim: inode.atim,
+            mtim: inode.mtim,
+            ctim: inode.ctim,
+        }
+    }
+
+    /// Returns the entries of the directory `ino`, or fails if it isn't a
+    /// directory or has been removed.
+    pub(crate) fn dir_entries(&self, ino: u64) -> Result<&BTreeMap<String, u64>, Error> {
+        let inode = self.inode(ino);
+        match &inode.kind {
+            Kind::Dir { .. } if inode.nlink == 0 => {
+                Err(Error::not_found().context("directory was removed"))
+            }
+            Kind::Dir { entries, .. } => Ok(entries),
+            _ => Err(Error::not_dir()),
+        }
+    }
+
+    pub(crate) fn dir_parent(&self, ino: u64) -> u64 {
+        match self.inode(ino).kind {
+            Kind::Dir { parent, .. } => parent,
+            _ => unreachable!("not a directory"),
+        }
+    }
+
+    /// Resolves `path` relative to the directory `start`.
+    ///
+    /// Like the paths of `cap-std`, paths can't escape `start`: absolute
+    /// paths, `..` components beyond `start` and symbolic links to absolute
+    /// paths fail with `EPERM`. The last component of the path is only
+    /// followed if it's a symbolic link and `follow` is set, or if the path
+    /// ends with a slash.
+    pub(crate) fn resolve(&self, start: u64, path: &str, follow: bool) -> Result<Lookup, Error> {
+        if path.is_empty() {
+            return Err(Error::not_found().context("empty path"));
+        }
+        if path.starts_with('/') {
+            return Err(Error::perm().context("absolute path"));
+        }
+        let dir_only = path.ends_with('/');
+        let follow = follow || dir_only;
+        let mut pending: VecDeque<String> = path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .collect();
+        let mut stack = vec![start];
+        let mut symlinks = 0;
+        self.dir_entries(start)?;
+        loop {
+            let name = match pending.pop_front() {
+                Some(name) => name,
+                None => {
+                    // The path only had `.` and `..` components.
+                    let dir = *stack.last().unwrap();
+                    return Ok(Lookup {
+                        parent: dir,
+                        name: None,
+                        ino: Some(dir),
+                    });
+                }
+            };
+            let dir = *stack.last().unwrap();
+            match name.as_str() {
+                "." => continue,
+                ".." => {
+                    if stack.len() == 1 {
+                        return Err(Error::perm().context("path escapes its directory"));
+                    }
+                    stack.pop();
+                    continue;
+                }
+                _ => {}
+            }
+            let last = pending.is_empty();
+            let ino = match self.dir_entries(dir)?.get(&name) {
+                Some(ino) => *ino,
+                None if last => {
+                    return Ok(Lookup {
+                        parent: dir,
+                        name: Some(name),
+                        ino: None,
+                    })
+                }
+                None => return Err(Error::not_found()),
+            };
+            match &self.inode(ino).kind {
+                Kind::Symlink(target) if !last || follow => {
+                    symlinks += 1;
+                    if symlinks > MAX_SYMLINKS {
+                        return Err(Error::from(Errno::Loop));
+                    }
+                    if target.starts_with('/') {
+                        return Err(Error::perm().context("absolute symbolic link"));
+                    }
+                    if target.is_empty() {
+                        return Err(Error::not_found());
+                    }
+                    for name in target.rsplit('/').filter(|name| !name.is_empty()) {
+                        pending.push_front(name.to_owned());
+                    }
+                }
+                Kind::Dir { .. } if !last => stack.push(ino),
+                _ if !last => return Err(Error::not_dir()),
+                kind => {
+                    if dir_only && !matches!(kind, Kind::Dir { .. }) {
+                        return Err(Error::not_dir());
+                    }
+                    return Ok(Lookup {
+                        parent: dir,
+                        name: Some(name),
+                        ino: Some(ino),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Resolves `path` relative to `start`, failing if it doesn't exist.
+    pub(crate) fn resolve_existing(
+        &self,
+        start: u64,
+        path: &str,
+        follow: bool,
+    ) -> Result<u64, Error> {
+        self.resolve(start, path, follow)?
+            .ino
+            .ok_or_else(Error::not_found)
+    }
+
+    pub(crate) fn alloc(&mut self, kind: Kind) -> u64 {
+        let ino = self.next_ino;
+        self.next_ino += 1;
+        self.inodes.insert(ino, Inode::new(kind));
+        ino
+    }
+
+    /// Adds an entry `name` for `ino` to the directory `dir`.
+    pub(crate) fn link(&mut self, dir: u64, name: &str, ino: u64) {
+        if let Kind::Dir { entries, .. } = &mut self.inode_mut(dir).kind {
+            entries.insert(name.to_owned(), ino);
+        }
+        if let Kind::Dir { parent, .. } = &mut self.inode_mut(ino).kind {
+            *parent = dir;
+        }
+        self.inode_mut(ino).nlink += 1;
+    }
+
+    /// Removes the entry `name` from the directory `dir`, freeing its inode
+    /// if nothing else refers to it.
+    pub(crate) fn unlink(&mut self, dir: u64, name: &str) {
+        let ino = match &mut self.inode_mut(dir).kind {
+            Kind::Dir { entries, .. } => entries.remove(name),
+            _ => None,
+        };
+        if let Some(ino) = ino {
+            self.release_link(ino);
+        }
+    }
+
+    fn release_link(&mut self, ino: u64) {
+        let inode = self.inode_mut(ino);
+        inode.nlink -= 1;
+        if inode.nlink > 0 {
+            return;
+        }
+        // A directory which is no longer linked can't be reached by new
+        // paths, so its contents are released as well, even if a handle
+        // keeps the directory itself alive.
+        if let Kind::Dir { entries, .. } = &mut inode.kind {
+            for child in std::mem::take(entries).into_values() {
+                self.release_link(child);
+            }
+        }
+        self.free_if_unused(ino);
+    }
+
+    /// Releases a handle to `ino`.
+    pub(crate) fn release_handle(&mut self, ino: u64) {
+        self.inode_mut(ino).handles -= 1;
+        self.free_if_unused(ino);
+    }
+
+    fn free_if_unused(&mut self, ino: u64) {
+        let inode = self.inode(ino);
+        if inode.nlink == 0 && inode.handles == 0 && ino != ROOT {
+            self.inodes.remove(&ino);
+        }
+    }
+
+    /// Overlays `tree` on the directory `dir`, like [`Tree::overlay`].
+    fn overlay(&mut self, dir: u64, tree: &Tree) {
+        if tree.get(OPAQUE_WHITEOUT).is_some() {
+            let names: Vec<String> = match &self.inode(dir).kind {
+                Kind::Dir { entries, .. } => entries.keys().cloned().collect(),
+                _ => Vec::new(),
+            };
+            for name in names {
+                self.unlink(dir, &name);
+            }
+        }
+        for (name, entry) in tree.entries() {
+            if name == OPAQUE_WHITEOUT {
+                continue;
+            }
+            if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+                self.unlink(dir, hidden);
+                continue;
+            }
+            let existing = match &self.inode(dir).kind {
+                Kind::Dir { entries, .. } => entries.get(name).copied(),
+                _ => None,
+            };
+            if let (Some(ino), Entry::Dir(subtree)) = (existing, entry) {
+                if let Kind::Dir { .. } = self.inode(ino).kind {
+                    self.overlay(ino, subtree);
+                    continue;
+                }
+            }
+            self.unlink(dir, name);
+            let ino = match entry {
+                Entry::File(data) => self.alloc(Kind::File(data.clone())),
+                Entry::Symlink(target) => self.alloc(Kind::Symlink(target.clone())),
+                Entry::Dir(subtree) => {
+                    let ino = self.alloc(Kind::Dir {
+                        entries: BTreeMap::new(),
+                        parent: dir,
+                    });
+                    self.overlay(ino, subtree);
+                    ino
+                }
+            };
+            self.link(dir, name, ino);
+        }
+    }
+
+    /// Returns the contents of the directory `dir` as a tree.
+    fn tree(&self, dir: u64) -> Tree {
+        let mut tree = Tree::new();
+        if let Kind::Dir { entries, .. } = &self.inode(dir).kind {
+            for (name, ino) in entries {
+                let entry = match &self.inode(*ino).kind {
+                    Kind::File(data) => Entry::File(data.clone()),
+                    Kind::Dir { .. } => Entry::Dir(self.tree(*ino)),
+                    Kind::Symlink(target) => Entry::Symlink(target.clone()),
+                };
+                tree.insert(name, entry);
+            }
+        }
+        tree
+    }
+}
@@ -0,0 +1,39 @@
+//! The `wasi-virtfs` crate provides impls of `WasiFile` and `WasiDir` backed
+//! by an in-memory filesystem, so that guests can be given a realistic
+//! filesystem without any access to the host's disk.
+//!
+//! A [`VirtualFs`] is built from [`Tree`]s, which can be read from tar
+//! archives, and which are mounted on top of one another as a union, so that
+//! e.g. the layers of a container image can be mounted in order. Whiteouts
+//! in a tree delete entries of the trees below, following the convention of
+//! container images.
+//!
+//! Once the guest has run, [`VirtualFs::snapshot`] reads back the whole
+//! filesystem, and [`VirtualFs::changes`] only what the guest changed, both
+//! as a `Tree` which can be written out as a tar archive.
+//!
+//! ```ignore
+//! let fs = VirtualFs::from_tree(&Tree::from_tar(File::open("rootfs.tar")?)?);
+//! wasi.push_preopened_dir(Box::new(fs.root()), "/")?;
+//! // ... run the guest ...
+//! fs.changes().write_tar(File::create("changes.tar")?)?;
+//! ```
+//!
+//! Paths are resolved like those of `cap-std`: they can't escape the
+//! directory they're resolved from, and symbolic links to absolute paths
+//! aren't followed. Timestamps are only ever changed explicitly, with
+//! `set_times`, so that the contents of the filesystem are deterministic.
+//!
+//! Files of a `VirtualFs` aren't backed by a host file descriptor, so the
+//! synchronous scheduler of `wasi-cap-std-sync` can't poll them.
+
+mod dir;
+mod file;
+mod fs;
+mod tar;
+mod tree;
+
+pub use dir::Dir;
+pub use file::File;
+pub use fs::VirtualFs;
+pub use tree::{Entry, Tree, OPAQUE_WHITEOUT, WHITEOUT_PREFIX};
@@ -0,0 +1,334 @@
+//! Reading and writing [`Tree`]s as tar archives.
+//!
+//! This supports the subset of the ustar, GNU and pax formats needed for
+//! directories, regular files, symbolic links and hard links with arbitrarily
+//! long names. Ownership, permissions and timestamps aren't represented in a
+//! tree, so they're ignored when reading, and written as fixed defaults.
+
+use crate::tree::{components, Entry, Tree};
+use std::io::{self, Read, Write};
+
+const BLOCK: usize = 512;
+
+impl Tree {
+    /// Reads a tree from the tar archive `archive`.
+    ///
+    /// Later entries of the archive replace earlier ones of the same path,
+    /// and device files, FIFOs and global headers are skipped.
+    pub fn from_tar(mut archive: impl Read) -> io::Result<Tree> {
+        let mut tree = Tree::new();
+        let mut long_name = None;
+        let mut long_link = None;
+        let mut block = [0; BLOCK];
+        loop {
+            if !read_block(&mut archive, &mut block)? || block.iter().all(|b| *b == 0) {
+                break;
+            }
+            let header = Header::parse(&block)?;
+            let data = read_data(&mut archive, header.size)?;
+            match header.typeflag {
+                b'L' => {
+                    long_name = Some(nul_terminated(&data));
+                    continue;
+                }
+                b'K' => {
+                    long_link = Some(nul_terminated(&data));
+                    continue;
+                }
+                b'x' => {
+                    for (key, value) in pax_records(&data)? {
+                        match key.as_str() {
+                            "path" => long_name = Some(value),
+                            "linkpath" => long_link = Some(value),
+                            _ => {}
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            let path = long_name.take().unwrap_or(header.name);
+            let link = long_link.take().unwrap_or(header.linkname);
+            let entry = match header.typeflag {
+                b'0' | b'\0' | b'7' => Entry::file(data),
+                b'1' => match tree.get(&check_path(&link)?) {
+                    Some(entry @ Entry::File(_)) => entry.clone(),
+                    _ => return Err(invalid(format!("bad hard link target `{link}`"))),
+                },
+                b'2' => Entry::Symlink(link),
+                b'5' => {
+                    // Don't replace the contents of a directory which was
+                    // already created by an earlier entry.
+                    let path = check_path(&path)?;
+                    if path.is_empty() {
+                        continue;
+                    }
+                    if let Some(Entry::Dir(_)) = tree.get(&path) {
+                        continue;
+                    }
+                    Entry::Dir(Tree::new())
+                }
+                _ => continue,
+            };
+            let path = check_path(&path)?;
+            if path.is_empty() {
+                return Err(invalid("archive entry at the root".to_string()));
+            }
+            tree.insert(&path, entry);
+        }
+        Ok(tree)
+    }
+
+    /// Writes this tree as a tar archive to `archive`.
+    ///
+    /// Files whose contents are shared, e.g. because they were read from hard
+    /// links, are written separately.
+    pub fn write_tar(&self, mut archive: impl Write) -> io::Result<()> {
+        self.walk(&mut |path, entry| {
+            let (typeflag, mode, data, link) = match entry {
+                Entry::File(contents) => (b'0', 0o644, &contents[..], ""),
+                Entry::Dir(_) => (b'5', 0o755, &[][..], ""),
+                Entry::Symlink(target) => (b'2', 0o777, &[][..], target.as_str()),
+            };
+            let mut path = path.to_string();
+            if let Entry::Dir(_) = entry {
+                path.push('/');
+            }
+            if path.len() > 100 {
+                write_entry(
+                    &mut archive,
+                    "././@LongLink",
+                    b'L',
+                    0o644,
+                    path.as_bytes(),
+                    "",
+                )?;
+            }
+            if link.len() > 100 {
+                write_entry(
+                    &mut archive,
+                    "././@LongLink",
+                    b'K',
+                    0o644,
+                    link.as_bytes(),
+                    "",
+                )?;
+            }
+            write_entry(&mut archive, &path, typeflag, mode, data, link)
+        })?;
+        archive.write_all(&[0; 2 * BLOCK])
+    }
+}
+
+struct Header {
+    name: String,
+    linkname: String,
+    size: u64,
+    typeflag: u8,
+}
+
+impl Header {
+    fn parse(block: &[u8; BLOCK]) -> io::Result<Header> {
+        let checksum = parse_octal(&block[148..156])?;
+        let sum = block
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if (148..156).contains(&i) {
+                    32
+                } else {
+                    u64::from(*b)
+                }
+            })
+            .sum::<u64>();
+        if checksum != sum {
+            return Err(invalid("bad tar header checksum".to_string()));
+        }
+
+        let mut name = nul_terminated(&block[0..100]);
+        if &block[257..263] == b"ustar\0" {
+            let prefix = nul_terminated(&block[345..500]);
+            if !prefix.is_empty() {
+                name = format!("{prefix}/{name}");
+            }
+        }
+        let size = if block[124] & 0x80 != 0 {
+            // GNU base-256 encoding, for sizes which don't fit in octal.
+            block[125..136]
+                .iter()
+                .try_fold(u64::from(block[124] & 0x7f), |size, b| {
+                    size.checked_mul(256).map(|size| size + u64::from(*b))
+                })
+                .ok_or_else(|| invalid("tar entry is too large".to_string()))?
+        } else {
+            parse_octal(&block[124..136])?
+        };
+        Ok(Header {
+            name,
+            linkname: nul_terminated(&block[157..257]),
+            size,
+            typeflag: block[156],
+        })
+    }
+}
+
+/// Reads a whole block, returning `false` at the end of the archive.
+fn read_block(archive: &mut impl Read, block: &mut [u8; BLOCK]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < BLOCK {
+        match archive.read(&mut block[read..])? {
+            0 if read == 0 => return Ok(false),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            n => read += n,
+        }
+    }
+    Ok(true)
+}
+
+/// Reads the `size` bytes of data of an entry, and the padding after them.
+fn read_data(archive: &mut impl Read, size: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    archive.by_ref().take(size).read_to_end(&mut data)?;
+    if (data.len() as u64) < size {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+    io::copy(&mut archive.by_ref().take(padding as u64), &mut io::sink())?;
+    Ok(data)
+}
+
+fn write_entry(
+    archive: &mut impl Write,
+    name: &str,
+    typeflag: u8,
+    mode: u32,
+    data: &[u8],
+    linkname: &str,
+) -> io::Result<()> {
+    let mut block = [0; BLOCK];
+    copy_truncated(&mut block[0..100], name.as_bytes());
+    write_octal(&mut block[100..108], mode.into());
+    write_octal(&mut block[108..116], 0);
+    write_octal(&mut block[116..124], 0);
+    write_octal(&mut block[124..136], data.len() as u64);
+    write_octal(&mut block[136..148], 0);
+    block[148..156].fill(b' ');
+    block[156] = typeflag;
+    copy_truncated(&mut block[157..257], linkname.as_bytes());
+    // The GNU magic, as long names use GNU extensions.
+    block[257..265].copy_from_slice(b"ustar  \0");
+    let sum = block.iter().map(|b| u64::from(*b)).sum::<u64>();
+    write_octal(&mut block[148..155], sum);
+
+    archive.write_all(&block)?;
+    archive.write_all(data)?;
+    archive.write_all(&[0; BLOCK][..(BLOCK - data.len() % BLOCK) % BLOCK])
+}
+
+fn copy_truncated(field: &mut [u8], value: &[u8]) {
+    let len = value.len().min(field.len());
+    field[..len].copy_from_slice(&value[..len]);
+}
+
+/// Writes `value` as a NUL-terminated octal number filling `field`.
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let s = format!("{value:0digits$o}");
+    field[..digits].copy_from_slice(&s.as_bytes()[s.len() - digits..]);
+    field[digits] = 0;
+}
+
+fn parse_octal(field: &[u8]) -> io::Result<u64> {
+    let s = std::str::from_utf8(field)
+        .map_err(|_| invalid("bad number in tar header".to_string()))?
+        .trim_matches(|c| c == ' ' || c == '\0');
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|_| invalid(format!("bad number `{s}` in tar header")))
+}
+
+fn nul_terminated(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Parses the `<len> <key>=<value>\n` records of a pax extended header.
+fn pax_records(mut data: &[u8]) -> io::Result<Vec<(String, String)>> {
+    let bad = || invalid("bad pax extended header".to_string());
+    let mut records = Vec::new();
+    while !data.is_empty() {
+        let space = data.iter().position(|b| *b == b' ').ok_or_else(bad)?;
+        let len: usize = std::str::from_utf8(&data[..space])
+            .ok()
+            .and_then(|len| len.parse().ok())
+            .ok_or_else(bad)?;
+        if len <= space || len > data.len() || data[len - 1] != b'\n' {
+            return Err(bad());
+        }
+        let record = std::str::from_utf8(&data[space + 1..len - 1]).map_err(|_| bad())?;
+        let (key, value) = record.split_once('=').ok_or_else(bad)?;
+        records.push((key.to_string(), value.to_string()));
+        data = &data[len..];
+    }
+    Ok(records)
+}
+
+/// Normalizes the path of an archive entry, rejecting `..` components, which
+/// could otherwise escape the root of the tree.
+fn check_path(path: &str) -> io::Result<String> {
+    match components(path) {
+        Some(components) => Ok(components.join("/")),
+        None => Err(invalid(format!(
+            "archive entry `{path}` is outside of the root"
+        ))),
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let long = "d".repeat(80);
+        let mut tree = Tree::new();
+        tree.insert("empty", Entry::Dir(Tree::new()));
+        tree.insert("etc/hosts", Entry::file("127.0.0.1 localhost\n"));
+        tree.insert("big", Entry::file(vec![7; 3 * BLOCK + 1]));
+        tree.insert(&format!("{long}/{long}/file"), Entry::file("deep"));
+        tree.insert("link", Entry::Symlink(format!("{long}/{long}/file")));
+
+        let mut archive = Vec::new();
+        tree.write_tar(&mut archive).unwrap();
+        assert_eq!(archive.len() % BLOCK, 0);
+        assert_eq!(Tree::from_tar(&archive[..]).unwrap(), tree);
+    }
+
+    #[test]
+    fn reads_hard_links_and_pax_names() {
+        let mut archive = Vec::new();
+        write_entry(&mut archive, "./a/file", b'0', 0o644, b"data", "").unwrap();
+        write_entry(&mut archive, "a/hard", b'1', 0o644, b"", "a/file").unwrap();
+        write_entry(&mut archive, "pax", b'x', 0o644, b"16 path=b/named\n", "").unwrap();
+        write_entry(&mut archive, "ignored", b'0', 0o644, b"pax", "").unwrap();
+        archive.extend_from_slice(&[0; 2 * BLOCK]);
+
+        let tree = Tree::from_tar(&archive[..]).unwrap();
+        assert_eq!(tree.read_file("a/hard"), Some(&b"data"[..]));
+        assert_eq!(tree.read_file("b/named"), Some(&b"pax"[..]));
+        assert_eq!(tree.get("ignored"), None);
+    }
+
+    #[test]
+    fn rejects_escaping_paths() {
+        let mut archive = Vec::new();
+        write_entry(&mut archive, "a/../../etc", b'0', 0o644, b"", "").unwrap();
+        let err = Tree::from_tar(&archive[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
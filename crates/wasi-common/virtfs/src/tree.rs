@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// The prefix of the name of a whiteout, an entry of a layer which deletes
+/// the entry of the same name, without the prefix, from the layers below.
+pub const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// The name of an opaque whiteout, an entry of a layer which hides all of the
+/// contents of its directory in the layers below.
+pub const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+/// An immutable directory tree, used to seed a [`VirtualFs`](crate::VirtualFs)
+/// and to read back its contents.
+///
+/// Paths in a tree are relative to its root and separated by `/`. Leading
+/// slashes and `.` components are ignored, and `..` components aren't
+/// allowed.
+///
+/// Cloning a tree and mounting it in a filesystem share the contents of its
+/// files, which are only copied when they're written to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tree {
+    entries: BTreeMap<String, Entry>,
+}
+
+/// An entry of a [`Tree`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    /// A regular file with the given contents.
+    File(Arc<Vec<u8>>),
+    /// A directory.
+    Dir(Tree),
+    /// A symbolic link to the given path.
+    Symlink(String),
+}
+
+impl Entry {
+    /// Creates a regular file entry.
+    pub fn file(contents: impl Into<Vec<u8>>) -> Entry {
+        Entry::File(Arc::new(contents.into()))
+    }
+}
+
+impl Tree {
+    /// Creates an empty tree.
+    pub fn new() -> Tree {
+        Tree::default()
+    }
+
+    /// Returns the entries of the root of the tree, by name.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Returns whether the tree has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entry at `path`, without following symbolic links.
+    pub fn get(&self, path: &str) -> Option<&Entry> {
+        let components = components(path)?;
+        let (last, dirs) = components.split_last()?;
+        let mut tree = self;
+        for name in dirs {
+            match tree.entries.get(*name)? {
+                Entry::Dir(dir) => tree = dir,
+                _ => return None,
+            }
+        }
+        tree.entries.get(*last)
+    }
+
+    /// Returns the contents of the regular file at `path`.
+    pub fn read_file(&self, path: &str) -> Option<&[u8]> {
+        match self.get(path)? {
+            Entry::File(contents) => Some(contents),
+            _ => None,
+        }
+    }
+
+    /// Returns the directory at `path`, which is the tree itself for an
+    /// empty path.
+    pub fn dir(&self, path: &str) -> Option<&Tree> {
+        let components = components(path)?;
+        if components.is_empty() {
+            return Some(self);
+        }
+        match self.get(path)? {
+            Entry::Dir(dir) => Some(dir),
+            _ => None,
+        }
+    }
+
+    /// Inserts `entry` at `path`, creating its parent directories and
+    /// replacing anything in the way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is empty or has `..` components.
+    pub fn insert(&mut self, path: &str, entry: Entry) {
+        let components = components(path).expect("`..` isn't allowed in tree paths");
+        let (last, dirs) = components
+            .split_last()
+            .expect("cannot insert at the root of a tree");
+        self.dir_mut(dirs).entries.insert((*last).to_owned(), entry);
+    }
+
+    /// Removes the entry at `path`, returning it.
+    pub fn remove(&mut self, path: &str) -> Option<Entry> {
+        let components = components(path)?;
+        let (last, dirs) = components.split_last()?;
+        let mut tree = self;
+        for name in dirs {
+            match tree.entries.get_mut(*name)? {
+                Entry::Dir(dir) => tree = dir,
+                _ => return None,
+            }
+        }
+        tree.entries.remove(*last)
+    }
+
+    /// Overlays `upper` on top of this tree, as a union mount: directories
+    /// present in both trees are merged, and any other entry of `upper`
+    /// replaces the one of the same name in this tree.
+    ///
+    /// Whiteouts in `upper` aren't copied, instead they delete entries from
+    /// this tree: `.wh.<name>` deletes `<name>`, and `.wh..wh..opq` deletes
+    /// everything in its directory that `upper` doesn't itself provide. This
+    /// is the convention used by the layers of container images.
+    pub fn overlay(&mut self, upper: &Tree) {
+        if upper.entries.contains_key(OPAQUE_WHITEOUT) {
+            self.entries.clear();
+        }
+        for (name, entry) in &upper.entries {
+            if name == OPAQUE_WHITEOUT {
+                continue;
+            }
+            if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+                self.entries.remove(hidden);
+                continue;
+            }
+            match (self.entries.get_mut(name), entry) {
+                (Some(Entry::Dir(lower)), Entry::Dir(upper)) => lower.overlay(upper),
+                (_, Entry::Dir(upper)) => {
+                    // Overlay onto an empty directory so that the whiteouts
+                    // of `upper` are dropped.
+                    let mut dir = Tree::new();
+                    dir.overlay(upper);
+                    self.entries.insert(name.clone(), Entry::Dir(dir));
+                }
+                (_, entry) => {
+                    self.entries.insert(name.clone(), entry.clone());
+                }
+            }
+        }
+    }
+
+    /// Overlays `upper` on top of the directory at `path`, creating it if
+    /// needed, see [`Tree::overlay`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` has `..` components.
+    pub fn overlay_at(&mut self, path: &str, upper: &Tree) {
+        let components = components(path).expect("`..` isn't allowed in tree paths");
+        self.dir_mut(&components).overlay(upper);
+    }
+
+    /// Returns the changes that turn `base` into `self`, as a tree which
+    /// reproduces `self` when it's overlaid on `base`.
+    ///
+    /// The changes include every entry which is new or different, with
+    /// whiteouts for the entries which were removed.
+    pub fn changes_from(&self, base: &Tree) -> Tree {
+        let mut changes = Tree::new();
+        for (name, entry) in &self.entries {
+            match (base.entries.get(name), entry) {
+                (Some(Entry::Dir(base)), Entry::Dir(dir)) => {
+                    let dir = dir.changes_from(base);
+                    if !dir.is_empty() {
+                        changes.entries.insert(name.clone(), Entry::Dir(dir));
+                    }
+                }
+                (Some(base), entry) if base == entry => {}
+                (_, entry) => {
+                    changes.entries.insert(name.clone(), entry.clone());
+                }
+            }
+        }
+        for name in base.entries.keys() {
+            if !self.entries.contains_key(name) {
+                changes
+                    .entries
+                    .insert(format!("{WHITEOUT_PREFIX}{name}"), Entry::file(Vec::new()));
+            }
+        }
+        changes
+    }
+
+    /// Returns the directory at `components`, creating it, and replacing
+    /// anything in the way, if needed.
+    fn dir_mut(&mut self, components: &[&str]) -> &mut Tree {
+        let mut tree = self;
+        for name in components {
+            let entry = tree
+                .entries
+                .entry((*name).to_owned())
+                .or_insert_with(|| Entry::Dir(Tree::new()));
+            if !matches!(entry, Entry::Dir(_)) {
+                *entry = Entry::Dir(Tree::new());
+            }
+            tree = match entry {
+                Entry::Dir(dir) => dir,
+                _ => unreachable!(),
+            };
+        }
+        tree
+    }
+
+    /// Walks the tree depth-first, calling `f` with the path of each entry
+    /// before visiting its contents.
+    pub(crate) fn walk<E>(
+        &self,
+        f: &mut impl FnMut(&str, &Entry) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.walk_in("", f)
+    }
+
+    fn walk_in<E>(
+        &self,
+        prefix: &str,
+        f: &mut impl FnMut(&str, &Entry) -> Result<(), E>,
+    ) -> Result<(), E> {
+        for (name, entry) in &self.entries {
+            let path = format!("{prefix}{name}");
+            f(&path, entry)?;
+            if let Entry::Dir(dir) = entry {
+                dir.walk_in(&format!("{path}/"), f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a tree path into its components, or returns `None` if it has `..`
+/// components.
+pub(crate) fn components(path: &str) -> Option<Vec<&str>> {
+    let mut components = Vec::new();
+    for name in path.split('/') {
+        match name {
+            "" | "." => {}
+            ".." => return None,
+            name => components.push(name),
+        }
+    }
+    Some(components)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tree(entries: &[(&str, Entry)]) -> Tree {
+        let mut tree = Tree::new();
+        for (path, entry) in entries {
+            tree.insert(path, entry.clone());
+        }
+        tree
+    }
+
+    #[test]
+    fn overlay_merges_and_whiteouts() {
+        let mut lower = tree(&[
+            ("etc/hosts", Entry::file("lower")),
+            ("etc/passwd", Entry::file("root")),
+            ("usr/lib/a", Entry::file("a")),
+            ("usr/lib/b", Entry::file("b")),
+            ("var/log", Entry::Dir(Tree::new())),
+        ]);
+        let upper = tree(&[
+            ("etc/hosts", Entry::file("upper")),
+            ("etc/.wh.passwd", Entry::file("")),
+            ("usr/lib/.wh..wh..opq", Entry::file("")),
+            ("usr/lib/c", Entry::file("c")),
+            ("var/log", Entry::Symlink("/dev/null".to_string())),
+            ("new/.wh.nothing", Entry::file("")),
+        ]);
+        lower.overlay(&upper);
+
+        assert_eq!(lower.read_file("etc/hosts"), Some(&b"upper"[..]));
+        assert_eq!(lower.get("etc/passwd"), None);
+        assert_eq!(lower.get("/usr/lib/a"), None);
+        assert_eq!(lower.read_file("./usr/lib/c"), Some(&b"c"[..]));
+        assert_eq!(
+            lower.get("var/log"),
+            Some(&Entry::Symlink("/dev/null".to_string()))
+        );
+        assert_eq!(lower.dir("new"), Some(&Tree::new()));
+    }
+
+    #[test]
+    fn changes_reproduce_the_tree() {
+        let base = tree(&[
+            ("a", Entry::file("a")),
+            ("d/b", Entry::file("b")),
+            ("d/c", Entry::file("c")),
+            ("e/f", Entry::file("f")),
+        ]);
+        let mut new = base.clone();
+        new.insert("a", Entry::file("changed"));
+        new.remove("d/b");
+        new.insert("d/g", Entry::Symlink("c".to_string()));
+        new.remove("e");
+        new.insert("e", Entry::file("not a directory"));
+
+        let changes = new.changes_from(&base);
+        assert_eq!(changes.read_file("a"), Some(&b"changed"[..]));
+        assert_eq!(changes.read_file("d/.wh.b"), Some(&b""[..]));
+        assert_eq!(changes.get("d/c"), None);
+
+        let mut replayed = base.clone();
+        replayed.overlay(&changes);
+        assert_eq!(replayed, new);
+        assert!(new.changes_from(&new).is_empty());
+    }
+}
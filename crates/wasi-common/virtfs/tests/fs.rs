@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::io::{IoSlice, IoSliceMut, SeekFrom};
+use wasi_common::dir::{ReaddirCursor, WasiDir};
+use wasi_common::file::{FdFlags, FileType, OFlags, WasiFile};
+use wasi_common::snapshots::preview_1::types::Errno;
+use wasi_virtfs::{Entry, Tree, VirtualFs};
+
+fn base() -> Tree {
+    let mut tree = Tree::new();
+    tree.insert("etc/hosts", Entry::file("127.0.0.1 localhost\n"));
+    tree.insert("etc/motd", Entry::file("hello\n"));
+    tree.insert("usr/bin/sh", Entry::file("#!"));
+    tree.insert("bin", Entry::Symlink("usr/bin".to_string()));
+    tree
+}
+
+fn errno(err: wasi_common::Error) -> Errno {
+    err.downcast().expect("error is an errno")
+}
+
+fn open(dir: &dyn WasiDir, path: &str, oflags: OFlags, fdflags: FdFlags) -> Box<dyn WasiFile> {
+    run(dir.open_file(true, path, oflags, true, true, fdflags)).expect("open file")
+}
+
+fn read_to_string(file: &dyn WasiFile) -> String {
+    let mut buf = vec![0; 256];
+    let n = run(file.read_vectored_at(&mut [IoSliceMut::new(&mut buf)], 0)).expect("read");
+    buf.truncate(n as usize);
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn reads_writes_and_snapshots() {
+    let fs = VirtualFs::from_tree(&base());
+    let root = fs.root();
+
+    let motd = open(&root, "etc/motd", OFlags::empty(), FdFlags::APPEND);
+    run(motd.write_vectored(&[IoSlice::new(b"world\n")])).expect("append");
+    assert_eq!(read_to_string(&*motd), "hello\nworld\n");
+
+    let sh = open(&root, "bin/sh", OFlags::TRUNCATE, FdFlags::empty());
+    run(sh.write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b"cd")])).expect("write");
+    assert_eq!(run(sh.seek(SeekFrom::Current(-1))).unwrap(), 3);
+    run(sh.write_vectored(&[IoSlice::new(b"EF")])).expect("overwrite");
+    assert_eq!(read_to_string(&*sh), "abcEF");
+
+    let err = run(root.open_file(
+        false,
+        "tmp/new",
+        OFlags::CREATE,
+        true,
+        true,
+        FdFlags::empty(),
+    ));
+    assert_eq!(errno(err.err().unwrap()), Errno::Noent);
+    run(root.create_dir("tmp")).expect("create dir");
+    open(&root, "tmp/new", OFlags::CREATE, FdFlags::empty());
+    run(root.unlink_file("etc/hosts")).expect("unlink");
+
+    let snapshot = fs.snapshot();
+    assert_eq!(snapshot.read_file("etc/motd"), Some(&b"hello\nworld\n"[..]));
+    assert_eq!(snapshot.read_file("usr/bin/sh"), Some(&b"abcEF"[..]));
+    assert_eq!(snapshot.read_file("tmp/new"), Some(&b""[..]));
+    assert_eq!(snapshot.get("etc/hosts"), None);
+
+    let changes = fs.changes();
+    assert!(changes.get("etc/.wh.hosts").is_some());
+    assert_eq!(changes.get("bin"), None);
+    let mut replayed = base();
+    replayed.overlay(&changes);
+    assert_eq!(replayed, snapshot);
+
+    let mut archive = Vec::new();
+    changes.write_tar(&mut archive).expect("write tar");
+    assert_eq!(Tree::from_tar(&archive[..]).expect("read tar"), changes);
+}
+
+#[test]
+fn union_mounts() {
+    let fs = VirtualFs::from_tree(&base());
+    let mut layer = Tree::new();
+    layer.insert("etc/.wh.motd", Entry::file(""));
+    layer.insert("etc/hostname", Entry::file("guest\n"));
+    layer.insert("usr/.wh..wh..opq", Entry::file(""));
+    layer.insert("usr/lib/libc.so", Entry::file("elf"));
+    fs.mount("", &layer).expect("mount layer");
+    fs.mount("data/input", &base()).expect("mount at a path");
+
+    let root = fs.root();
+    assert_eq!(
+        read_to_string(&*open(
+            &root,
+            "etc/hostname",
+            OFlags::empty(),
+            FdFlags::empty()
+        )),
+        "guest\n"
+    );
+    let err = run(root.get_path_filestat("etc/motd", true)).err().unwrap();
+    assert_eq!(errno(err), Errno::Noent);
+    let err = run(root.get_path_filestat("bin/sh", true)).err().unwrap();
+    assert_eq!(errno(err), Errno::Noent);
+    assert_eq!(
+        run(root.get_path_filestat("data/input/bin/sh", true))
+            .unwrap()
+            .filetype,
+        FileType::RegularFile
+    );
+
+    // Mounted layers aren't changes.
+    assert!(fs.changes().is_empty());
+}
+
+#[test]
+fn paths_stay_inside_the_filesystem() {
+    let fs = VirtualFs::from_tree(&base());
+    let root = fs.root();
+    run(root.symlink("/etc", "abs")).expect("symlink");
+    run(root.symlink("../..", "up")).expect("symlink");
+    run(root.symlink("loop", "loop")).expect("symlink");
+
+    for path in ["/etc/hosts", "../etc", "abs/hosts", "up/x", "etc/../../etc"] {
+        let err = run(root.get_path_filestat(path, true)).err().unwrap();
+        assert_eq!(errno(err), Errno::Perm, "{path}");
+    }
+    let err = run(root.get_path_filestat("loop", true)).err().unwrap();
+    assert_eq!(errno(err), Errno::Loop);
+
+    let etc = run(root.open_dir(false, "etc")).expect("open dir");
+    let err = run(etc.get_path_filestat("../usr", true)).err().unwrap();
+    assert_eq!(errno(err), Errno::Perm);
+    assert_eq!(
+        run(etc.read_link("../bin")).err().map(errno),
+        Some(Errno::Perm)
+    );
+    assert_eq!(
+        run(root.read_link("bin")).unwrap().to_str(),
+        Some("usr/bin")
+    );
+}
+
+#[test]
+fn directories() {
+    let fs = VirtualFs::from_tree(&base());
+    let root = fs.root();
+
+    let entries: HashMap<String, FileType> = run(root.readdir(ReaddirCursor::from(0)))
+        .expect("readdir")
+        .map(|entity| {
+            let entity = entity.unwrap();
+            (entity.name, entity.filetype)
+        })
+        .collect();
+    assert_eq!(entries.len(), 5);
+    assert_eq!(entries["."], FileType::Directory);
+    assert_eq!(entries["bin"], FileType::SymbolicLink);
+    assert_eq!(
+        run(root.readdir(ReaddirCursor::from(4))).unwrap().count(),
+        1
+    );
+
+    let err = run(root.remove_dir("etc")).err().unwrap();
+    assert_eq!(errno(err), Errno::Notempty);
+    let err = run(root.unlink_file("etc")).err().unwrap();
+    assert_eq!(errno(err), Errno::Isdir);
+    let err = run(root.rename("etc", &root, "etc/sub")).err().unwrap();
+    assert_eq!(errno(err), Errno::Inval);
+    let other = VirtualFs::new().root();
+    let err = run(root.rename("etc", &other, "etc")).err().unwrap();
+    assert_eq!(errno(err), Errno::Xdev);
+
+    run(root.rename("etc", &root, "usr/etc")).expect("rename");
+    run(root.hard_link("usr/etc/motd", &root, "motd")).expect("link");
+    let motd = open(&root, "motd", OFlags::empty(), FdFlags::empty());
+    run(root.unlink_file("usr/etc/motd")).expect("unlink");
+    run(motd.write_vectored_at(&[IoSlice::new(b"J")], 0)).expect("write");
+    assert_eq!(run(motd.get_filestat()).unwrap().nlink, 1);
+    assert_eq!(fs.snapshot().read_file("motd"), Some(&b"Jello\n"[..]));
+
+    let etc = run(root.open_dir(true, "usr/etc")).expect("open dir");
+    run(root.unlink_file("usr/etc/hosts")).expect("unlink");
+    run(root.remove_dir("usr/etc")).expect("remove dir");
+    let err = run(etc.create_dir("new")).err().unwrap();
+    assert_eq!(errno(err), Errno::Noent);
+}
+
+fn run<F: std::future::Future>(future: F) -> F::Output {
+    use std::pin::Pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    let mut f = Pin::from(Box::new(future));
+    let waker = dummy_waker();
+    let mut cx = Context::from_waker(&waker);
+    match f.as_mut().poll(&mut cx) {
+        Poll::Ready(val) => return val,
+        Poll::Pending => {
+            panic!("Cannot wait on pending future: the virtual filesystem never blocks")
+        }
+    }
+
+    fn dummy_waker() -> Waker {
+        return unsafe { Waker::from_raw(clone(5 as *const _)) };
+
+        unsafe fn clone(ptr: *const ()) -> RawWaker {
+            assert_eq!(ptr as usize, 5);
+            const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop);
+            RawWaker::new(ptr, &VTABLE)
+        }
+
+        unsafe fn wake(ptr: *const ()) {
+            assert_eq!(ptr as usize, 5);
+        }
+
+        unsafe fn wake_by_ref(ptr: *const ()) {
+            assert_eq!(ptr as usize, 5);
+        }
+
+        unsafe fn drop(ptr: *const ()) {
+            assert_eq!(ptr as usize, 5);
+        }
+    }
+}
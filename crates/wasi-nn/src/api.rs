@@ -1,35 +1,79 @@
 //! Define the Rust interface a backend must implement in order to be used by
 //! this crate. the `Box<dyn ...>` types returned by these interfaces allow
 //! implementations to maintain backend-specific state between calls.
+//!
+//! Backends are registered with a [crate::WasiNnCtx] for a particular
+//! [GraphEncoding]; embedders can implement these traits to plug in their own
+//! inference engines.
 
-use crate::witx::types::{ExecutionTarget, GraphBuilderArray, Tensor};
+use crate::witx::types::{ExecutionTarget, GraphEncoding, TensorType};
+use std::sync::Arc;
 use thiserror::Error;
 use wiggle::GuestError;
 
 /// A [Backend] contains the necessary state to load [BackendGraph]s.
-pub(crate) trait Backend: Send + Sync {
+pub trait Backend: Send + Sync {
+    /// The name of this backend, used in diagnostics.
     fn name(&self) -> &str;
+
+    /// Load a graph from the `builders` buffers passed by the guest to
+    /// `load`; what each buffer contains is specific to the graph encoding
+    /// this backend was registered for.
     fn load(
         &mut self,
-        builders: &GraphBuilderArray<'_>,
+        builders: &[&[u8]],
         target: ExecutionTarget,
     ) -> Result<Box<dyn BackendGraph>, BackendError>;
 }
 
 /// A [BackendGraph] can create [BackendExecutionContext]s; this is the backing
 /// implementation for a [crate::witx::types::Graph].
-pub(crate) trait BackendGraph: Send + Sync {
-    fn init_execution_context(&mut self) -> Result<Box<dyn BackendExecutionContext>, BackendError>;
+///
+/// A graph may be shared by many guests at once (see [SharedGraph]), so
+/// creating an execution context only requires shared access.
+pub trait BackendGraph: Send + Sync {
+    fn init_execution_context(&self) -> Result<Box<dyn BackendExecutionContext>, BackendError>;
+}
+
+/// A reference-counted [BackendGraph]; a graph loaded once can be cheaply
+/// shared between many [crate::WasiNnCtx]s, e.g., to register a preloaded
+/// model with every guest instance.
+#[derive(Clone)]
+pub struct SharedGraph(Arc<dyn BackendGraph>);
+
+impl SharedGraph {
+    /// Wrap a graph loaded by a [Backend] for sharing.
+    pub fn new(graph: Box<dyn BackendGraph>) -> Self {
+        Self(Arc::from(graph))
+    }
+
+    pub(crate) fn init_execution_context(
+        &self,
+    ) -> Result<Box<dyn BackendExecutionContext>, BackendError> {
+        self.0.init_execution_context()
+    }
 }
 
 /// A [BackendExecutionContext] performs the actual inference; this is the
 /// backing implementation for a [crate::witx::types::GraphExecutionContext].
-pub(crate) trait BackendExecutionContext: Send + Sync {
+pub trait BackendExecutionContext: Send + Sync {
     fn set_input(&mut self, index: u32, tensor: &Tensor<'_>) -> Result<(), BackendError>;
     fn compute(&mut self) -> Result<(), BackendError>;
     fn get_output(&mut self, index: u32, destination: &mut [u8]) -> Result<u32, BackendError>;
 }
 
+/// A tensor passed by the guest to `set_input`, already read out of guest
+/// memory.
+#[derive(Debug)]
+pub struct Tensor<'a> {
+    /// The size of each dimension of the tensor.
+    pub dimensions: &'a [u32],
+    /// The type of each element of the tensor.
+    pub tensor_type: TensorType,
+    /// The raw, little-endian bytes of the tensor's elements.
+    pub data: &'a [u8],
+}
+
 /// Errors returned by a backend; [BackendError::BackendAccess] is a catch-all
 /// for failures interacting with the ML library.
 #[derive(Debug, Error)]
@@ -40,6 +84,30 @@ pub enum BackendError {
     GuestAccess(#[from] GuestError),
     #[error("The backend expects {0} buffers, passed {1}")]
     InvalidNumberOfBuilders(u32, u32),
+    #[error("The model has no input at index: {0}")]
+    InvalidInput(u32),
     #[error("Not enough memory to copy tensor data of size: {0}")]
     NotEnoughMemory(usize),
+    #[error("The backend does not support the execution target: {0:?}")]
+    UnsupportedTarget(ExecutionTarget),
+    #[error("The backend does not support the tensor type: {0:?}")]
+    UnsupportedTensorType(TensorType),
+}
+
+/// Return the backends built into this crate, along with the graph encoding
+/// each one is registered for by default.
+pub fn default_backends() -> Vec<(GraphEncoding, Box<dyn Backend>)> {
+    #[allow(unused_mut)]
+    let mut backends: Vec<(GraphEncoding, Box<dyn Backend>)> = Vec::new();
+    #[cfg(feature = "openvino")]
+    backends.push((
+        GraphEncoding::Openvino,
+        Box::new(crate::openvino::OpenvinoBackend::default()),
+    ));
+    #[cfg(feature = "onnx")]
+    backends.push((
+        GraphEncoding::Onnx,
+        Box::new(crate::onnx::OnnxBackend::default()),
+    ));
+    backends
 }
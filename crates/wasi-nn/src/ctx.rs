@@ -1,9 +1,8 @@
 //! Implements the base structure (i.e. [WasiNnCtx]) that will provide the
 //! implementation of the wasi-nn API.
-use crate::api::{Backend, BackendError, BackendExecutionContext, BackendGraph};
-use crate::openvino::OpenvinoBackend;
+use crate::api::{self, Backend, BackendError, BackendExecutionContext, SharedGraph};
 use crate::r#impl::UsageError;
-use crate::witx::types::{Graph, GraphEncoding, GraphExecutionContext};
+use crate::witx::types::{ExecutionTarget, Graph, GraphEncoding, GraphExecutionContext};
 use std::collections::HashMap;
 use std::hash::Hash;
 use thiserror::Error;
@@ -12,25 +11,70 @@ use wiggle::GuestError;
 /// Capture the state necessary for calling into the backend ML libraries.
 pub struct WasiNnCtx {
     pub(crate) backends: HashMap<u8, Box<dyn Backend>>,
-    pub(crate) graphs: Table<Graph, Box<dyn BackendGraph>>,
+    pub(crate) graphs: Table<Graph, SharedGraph>,
+    pub(crate) named_graphs: HashMap<String, SharedGraph>,
     pub(crate) executions: Table<GraphExecutionContext, Box<dyn BackendExecutionContext>>,
 }
 
 impl WasiNnCtx {
-    /// Make a new context from the default state.
+    /// Make a new context from the default state, with all of the backends
+    /// built into this crate registered.
     pub fn new() -> WasiNnResult<Self> {
-        let mut backends = HashMap::new();
-        backends.insert(
-            // This is necessary because Wiggle's variant types do not derive
-            // `Hash` and `Eq`.
-            GraphEncoding::Openvino.into(),
-            Box::new(OpenvinoBackend::default()) as Box<dyn Backend>,
-        );
-        Ok(Self {
-            backends,
+        let mut ctx = Self::empty();
+        for (encoding, backend) in api::default_backends() {
+            ctx.register_backend(encoding, backend);
+        }
+        Ok(ctx)
+    }
+
+    /// Make a new context with no backends registered; use
+    /// [WasiNnCtx::register_backend] to add some.
+    pub fn empty() -> Self {
+        Self {
+            backends: HashMap::new(),
             graphs: Table::default(),
+            named_graphs: HashMap::new(),
             executions: Table::default(),
-        })
+        }
+    }
+
+    /// Register `backend` to load graphs passed with the given `encoding`,
+    /// replacing any backend previously registered for that encoding.
+    pub fn register_backend(
+        &mut self,
+        encoding: GraphEncoding,
+        backend: Box<dyn Backend>,
+    ) -> &mut Self {
+        // This is necessary because Wiggle's variant types do not derive
+        // `Hash` and `Eq`.
+        self.backends.insert(encoding.into(), backend);
+        self
+    }
+
+    /// Load a graph from `builders` with the backend registered for
+    /// `encoding`.
+    ///
+    /// The returned graph can be registered by name with any number of
+    /// contexts using [WasiNnCtx::register_graph].
+    pub fn load_graph(
+        &mut self,
+        builders: &[&[u8]],
+        encoding: GraphEncoding,
+        target: ExecutionTarget,
+    ) -> WasiNnResult<SharedGraph> {
+        let encoding_id: u8 = encoding.into();
+        match self.backends.get_mut(&encoding_id) {
+            Some(backend) => Ok(SharedGraph::new(backend.load(builders, target)?)),
+            None => Err(UsageError::InvalidEncoding(encoding).into()),
+        }
+    }
+
+    /// Register `graph` under `name` so that guests can retrieve it with
+    /// `load_by_name` instead of passing the model's bytes to `load`,
+    /// replacing any graph previously registered with that name.
+    pub fn register_graph(&mut self, name: impl Into<String>, graph: SharedGraph) -> &mut Self {
+        self.named_graphs.insert(name.into(), graph);
+        self
     }
 }
 
@@ -72,6 +116,10 @@ where
         key
     }
 
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.entries.get(&key)
+    }
+
     pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
         self.entries.get_mut(&key)
     }
@@ -86,9 +134,57 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::api::BackendGraph;
 
     #[test]
     fn instantiate() {
         WasiNnCtx::new().unwrap();
     }
+
+    struct Dummy;
+
+    impl Backend for Dummy {
+        fn name(&self) -> &str {
+            "dummy"
+        }
+        fn load(
+            &mut self,
+            _: &[&[u8]],
+            _: ExecutionTarget,
+        ) -> Result<Box<dyn BackendGraph>, BackendError> {
+            Ok(Box::new(DummyGraph))
+        }
+    }
+
+    struct DummyGraph;
+
+    impl BackendGraph for DummyGraph {
+        fn init_execution_context(&self) -> Result<Box<dyn BackendExecutionContext>, BackendError> {
+            Err(BackendError::InvalidNumberOfBuilders(0, 0))
+        }
+    }
+
+    #[test]
+    fn register_backend() {
+        let mut ctx = WasiNnCtx::empty();
+        assert!(ctx.backends.is_empty());
+        ctx.register_backend(GraphEncoding::Tensorflow, Box::new(Dummy));
+        let encoding: u8 = GraphEncoding::Tensorflow.into();
+        assert_eq!(ctx.backends[&encoding].name(), "dummy");
+    }
+
+    #[test]
+    fn register_graph() {
+        let mut ctx = WasiNnCtx::empty();
+        assert!(ctx
+            .load_graph(&[], GraphEncoding::Tensorflow, ExecutionTarget::Cpu)
+            .is_err());
+
+        ctx.register_backend(GraphEncoding::Tensorflow, Box::new(Dummy));
+        let graph = ctx
+            .load_graph(&[], GraphEncoding::Tensorflow, ExecutionTarget::Cpu)
+            .unwrap();
+        ctx.register_graph("model", graph);
+        assert!(ctx.named_graphs.contains_key("model"));
+    }
 }
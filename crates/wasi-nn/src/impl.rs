@@ -12,9 +12,9 @@ use wiggle::GuestPtr;
 pub enum UsageError {
     #[error("Invalid context; has the load function been called?")]
     InvalidContext,
-    #[error("Only OpenVINO's IR is currently supported, passed encoding: {0:?}")]
+    #[error("No backend is registered for the graph encoding: {0:?}")]
     InvalidEncoding(GraphEncoding),
-    #[error("OpenVINO expects only two buffers (i.e. [ir, weights]), passed: {0}")]
+    #[error("Invalid number of graph builders passed: {0}")]
     InvalidNumberOfBuilders(u32),
     #[error("Invalid graph handle; has it been loaded?")]
     InvalidGraphHandle,
@@ -22,6 +22,8 @@ pub enum UsageError {
     InvalidExecutionContextHandle,
     #[error("Not enough memory to copy tensor data of size: {0}")]
     NotEnoughMemory(u32),
+    #[error("No graph is registered with the name: {0}")]
+    NotFound(String),
 }
 
 impl<'a> WasiEphemeralNn for WasiNnCtx {
@@ -31,18 +33,35 @@ impl<'a> WasiEphemeralNn for WasiNnCtx {
         encoding: GraphEncoding,
         target: ExecutionTarget,
     ) -> Result<Graph> {
-        let encoding_id: u8 = encoding.into();
-        let graph = if let Some(backend) = self.backends.get_mut(&encoding_id) {
-            backend.load(builders, target)?
-        } else {
-            return Err(UsageError::InvalidEncoding(encoding).into());
+        // Read the guest array.
+        let mut slices = Vec::with_capacity(builders.len() as usize);
+        for builder in builders.iter() {
+            let slice = builder?
+                .read()?
+                .as_slice()?
+                .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
+            slices.push(slice);
+        }
+        let slices = slices.iter().map(|s| &**s).collect::<Vec<&[u8]>>();
+        let graph = self.load_graph(&slices, encoding, target)?;
+        let graph_id = self.graphs.insert(graph);
+        Ok(graph_id)
+    }
+
+    fn load_by_name<'b>(&mut self, name: &GuestPtr<'b, str>) -> Result<Graph> {
+        let name = name
+            .as_str()?
+            .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
+        let graph = match self.named_graphs.get(&*name) {
+            Some(graph) => graph.clone(),
+            None => return Err(UsageError::NotFound(name.to_string()).into()),
         };
         let graph_id = self.graphs.insert(graph);
         Ok(graph_id)
     }
 
     fn init_execution_context(&mut self, graph_id: Graph) -> Result<GraphExecutionContext> {
-        let exec_context = if let Some(graph) = self.graphs.get_mut(graph_id) {
+        let exec_context = if let Some(graph) = self.graphs.get(graph_id) {
             graph.init_execution_context()?
         } else {
             return Err(UsageError::InvalidGraphHandle.into());
@@ -59,7 +78,20 @@ impl<'a> WasiEphemeralNn for WasiNnCtx {
         tensor: &Tensor<'b>,
     ) -> Result<()> {
         if let Some(exec_context) = self.executions.get_mut(exec_context_id) {
-            Ok(exec_context.set_input(index, tensor)?)
+            let dimensions = tensor
+                .dimensions
+                .as_slice()?
+                .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
+            let data = tensor
+                .data
+                .as_slice()?
+                .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
+            let tensor = crate::api::Tensor {
+                dimensions: &dimensions,
+                tensor_type: tensor.type_,
+                data: &data,
+            };
+            Ok(exec_context.set_input(index, &tensor)?)
         } else {
             Err(UsageError::InvalidGraphHandle.into())
         }
@@ -1,8 +1,20 @@
 mod api;
 mod ctx;
 mod r#impl;
+#[cfg(feature = "onnx")]
+mod onnx;
+#[cfg(feature = "openvino")]
 mod openvino;
 mod witx;
 
+pub use api::{
+    default_backends, Backend, BackendError, BackendExecutionContext, BackendGraph, SharedGraph,
+    Tensor,
+};
 pub use ctx::WasiNnCtx;
+#[cfg(feature = "onnx")]
+pub use onnx::OnnxBackend;
+#[cfg(feature = "openvino")]
+pub use openvino::OpenvinoBackend;
+pub use witx::types::{ExecutionTarget, GraphEncoding, TensorType};
 pub use witx::wasi_ephemeral_nn::add_to_linker;
@@ -0,0 +1,225 @@
+//! Implements a wasi-nn [`Backend`] for ONNX models using [tract], a pure-Rust
+//! inference engine which needs no native libraries.
+//!
+//! [tract]: https://github.com/sonos/tract
+
+use crate::api::{Backend, BackendError, BackendExecutionContext, BackendGraph, Tensor};
+use crate::witx::types::{ExecutionTarget, TensorType};
+use anyhow::anyhow;
+use std::sync::Arc;
+use tract_onnx::prelude::{
+    tvec, Framework, InferenceModelExt, IntoTensor, TValue, TVec, Tensor as TractTensor,
+    TypedModel, TypedRunnableModel,
+};
+
+/// A [`Backend`] for ONNX models, which expects a single builder containing
+/// the serialized model. Only the CPU execution target is supported.
+#[derive(Default)]
+pub struct OnnxBackend(());
+
+impl Backend for OnnxBackend {
+    fn name(&self) -> &str {
+        "onnx"
+    }
+
+    fn load(
+        &mut self,
+        builders: &[&[u8]],
+        target: ExecutionTarget,
+    ) -> Result<Box<dyn BackendGraph>, BackendError> {
+        if builders.len() != 1 {
+            return Err(BackendError::InvalidNumberOfBuilders(
+                1,
+                builders.len() as u32,
+            ));
+        }
+        if !matches!(target, ExecutionTarget::Cpu) {
+            return Err(BackendError::UnsupportedTarget(target));
+        }
+
+        let model = tract_onnx::onnx()
+            .model_for_read(&mut &builders[0][..])?
+            .into_optimized()?
+            .into_runnable()?;
+        Ok(Box::new(OnnxGraph(Arc::new(model))))
+    }
+}
+
+struct OnnxGraph(Arc<TypedRunnableModel<TypedModel>>);
+
+impl BackendGraph for OnnxGraph {
+    fn init_execution_context(&self) -> Result<Box<dyn BackendExecutionContext>, BackendError> {
+        Ok(Box::new(OnnxExecutionContext {
+            model: self.0.clone(),
+            inputs: vec![None; self.0.model().inputs.len()],
+            outputs: Vec::new(),
+        }))
+    }
+}
+
+struct OnnxExecutionContext {
+    model: Arc<TypedRunnableModel<TypedModel>>,
+    inputs: Vec<Option<TractTensor>>,
+    outputs: Vec<TractTensor>,
+}
+
+impl BackendExecutionContext for OnnxExecutionContext {
+    fn set_input(&mut self, index: u32, tensor: &Tensor<'_>) -> Result<(), BackendError> {
+        let slot = self
+            .inputs
+            .get_mut(index as usize)
+            .ok_or(BackendError::InvalidInput(index))?;
+        let shape = tensor
+            .dimensions
+            .iter()
+            .map(|d| *d as usize)
+            .collect::<Vec<_>>();
+        let input = match tensor.tensor_type {
+            TensorType::F32 => {
+                TractTensor::from_shape(&shape, &from_le_bytes(tensor.data, f32::from_le_bytes))?
+            }
+            TensorType::I32 => {
+                TractTensor::from_shape(&shape, &from_le_bytes(tensor.data, i32::from_le_bytes))?
+            }
+            TensorType::U8 => TractTensor::from_shape(&shape, tensor.data)?,
+            TensorType::F16 => return Err(BackendError::UnsupportedTensorType(tensor.tensor_type)),
+        };
+        *slot = Some(input);
+        Ok(())
+    }
+
+    fn compute(&mut self) -> Result<(), BackendError> {
+        let mut inputs: TVec<TValue> = tvec![];
+        for (i, input) in self.inputs.iter().enumerate() {
+            match input {
+                Some(input) => inputs.push(input.clone().into()),
+                None => return Err(anyhow!("input {} has not been set", i).into()),
+            }
+        }
+        let outputs = self.model.run(inputs)?;
+        self.outputs = outputs.into_iter().map(|o| o.into_tensor()).collect();
+        Ok(())
+    }
+
+    fn get_output(&mut self, index: u32, destination: &mut [u8]) -> Result<u32, BackendError> {
+        let output = self.outputs.get(index as usize).ok_or_else(|| {
+            anyhow!(
+                "output {} is not available; has compute been called?",
+                index
+            )
+        })?;
+        if !output.datum_type().is_copy() {
+            return Err(anyhow!(
+                "output {} has type {:?}, which has no raw bytes",
+                index,
+                output.datum_type()
+            )
+            .into());
+        }
+        // Tensors of plain data types are contiguous buffers of their values.
+        let bytes = unsafe { output.as_bytes() };
+        if bytes.len() > destination.len() {
+            return Err(BackendError::NotEnoughMemory(bytes.len()));
+        }
+
+        // Copy the tensor data into the destination buffer.
+        destination[..bytes.len()].copy_from_slice(bytes);
+        Ok(bytes.len() as u32)
+    }
+}
+
+/// Decode little-endian tensor data into a vector of 4-byte elements.
+fn from_le_bytes<T>(data: &[u8], decode: fn([u8; 4]) -> T) -> Vec<T> {
+    data.chunks_exact(4)
+        .map(|chunk| decode(chunk.try_into().unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::witx::types::GraphEncoding;
+    use crate::WasiNnCtx;
+    use tract_onnx::prelude::TypedFact;
+
+    /// A serialized ONNX model, with IR version 7 and opset 13, whose graph
+    /// computes `y = Relu(x)` for tensors `x` and `y` of four `f32`s.
+    const RELU_MODEL: &[u8] = b"\
+        \x08\x07:6\
+        \x0a\x0c\x0a\x01x\x12\x01y\x22\x04Relu\
+        \x12\x04relu\
+        Z\x0f\x0a\x01x\x12\x0a\x0a\x08\x08\x01\x12\x04\x0a\x02\x08\x04\
+        b\x0f\x0a\x01y\x12\x0a\x0a\x08\x08\x01\x12\x04\x0a\x02\x08\x04\
+        B\x04\x0a\x00\x10\x0d";
+
+    fn f32_bytes(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// Build a graph whose output is its single input, two `f32`s.
+    fn identity_graph() -> OnnxGraph {
+        let mut model = TypedModel::default();
+        let input = model
+            .add_source("input", TypedFact::shape::<f32, _>([2usize]))
+            .unwrap();
+        model.set_output_outlets(&[input]).unwrap();
+        OnnxGraph(Arc::new(model.into_runnable().unwrap()))
+    }
+
+    #[test]
+    fn set_input() {
+        let mut ctx = identity_graph().init_execution_context().unwrap();
+        let data = [1.0f32.to_le_bytes(), 2.0f32.to_le_bytes()].concat();
+        let tensor = Tensor {
+            dimensions: &[2],
+            tensor_type: TensorType::F32,
+            data: &data,
+        };
+
+        // The model only has one input.
+        assert!(matches!(
+            ctx.set_input(1, &tensor),
+            Err(BackendError::InvalidInput(1))
+        ));
+        assert!(matches!(
+            ctx.set_input(u32::MAX, &tensor),
+            Err(BackendError::InvalidInput(u32::MAX))
+        ));
+        assert!(ctx.compute().is_err());
+
+        ctx.set_input(0, &tensor).unwrap();
+        ctx.compute().unwrap();
+        let mut output = [0; 8];
+        assert_eq!(ctx.get_output(0, &mut output).unwrap(), 8);
+        assert_eq!(&output[..], &data[..]);
+    }
+
+    #[test]
+    fn load_onnx_model() {
+        let mut ctx = WasiNnCtx::empty();
+        ctx.register_backend(GraphEncoding::Onnx, Box::new(OnnxBackend::default()));
+        assert!(ctx
+            .load_graph(
+                &[RELU_MODEL, RELU_MODEL],
+                GraphEncoding::Onnx,
+                ExecutionTarget::Cpu
+            )
+            .is_err());
+        let graph = ctx
+            .load_graph(&[RELU_MODEL], GraphEncoding::Onnx, ExecutionTarget::Cpu)
+            .unwrap();
+
+        let mut exec = graph.init_execution_context().unwrap();
+        let input = f32_bytes(&[-1.0, 2.0, -3.0, 4.0]);
+        let tensor = Tensor {
+            dimensions: &[4],
+            tensor_type: TensorType::F32,
+            data: &input,
+        };
+        exec.set_input(0, &tensor).unwrap();
+        exec.compute().unwrap();
+        let mut output = [0; 16];
+        assert_eq!(exec.get_output(0, &mut output).unwrap(), 16);
+        assert_eq!(&output[..], &f32_bytes(&[0.0, 2.0, 0.0, 4.0])[..]);
+    }
+}
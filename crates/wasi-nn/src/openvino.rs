@@ -1,12 +1,17 @@
-//! Implements the wasi-nn API.
+//! Implements a wasi-nn [`Backend`] using OpenVINO.
 
-use crate::api::{Backend, BackendError, BackendExecutionContext, BackendGraph};
-use crate::witx::types::{ExecutionTarget, GraphBuilderArray, Tensor, TensorType};
+use crate::api::{Backend, BackendError, BackendExecutionContext, BackendGraph, Tensor};
+use crate::witx::types::{ExecutionTarget, TensorType};
 use openvino::{InferenceError, Layout, Precision, SetupError, TensorDesc};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+/// A [`Backend`] for OpenVINO IR models, which expects two builders: the
+/// model's XML description and its weights.
+///
+/// The OpenVINO libraries are located and loaded at runtime, the first time a
+/// model is loaded.
 #[derive(Default)]
-pub(crate) struct OpenvinoBackend(Option<openvino::Core>);
+pub struct OpenvinoBackend(Option<openvino::Core>);
 
 unsafe impl Send for OpenvinoBackend {}
 unsafe impl Sync for OpenvinoBackend {}
@@ -18,11 +23,14 @@ impl Backend for OpenvinoBackend {
 
     fn load(
         &mut self,
-        builders: &GraphBuilderArray<'_>,
+        builders: &[&[u8]],
         target: ExecutionTarget,
     ) -> Result<Box<dyn BackendGraph>, BackendError> {
         if builders.len() != 2 {
-            return Err(BackendError::InvalidNumberOfBuilders(2, builders.len()).into());
+            return Err(BackendError::InvalidNumberOfBuilders(
+                2,
+                builders.len() as u32,
+            ));
         }
 
         // Construct the context if none is present; this is done lazily (i.e.
@@ -33,17 +41,8 @@ impl Backend for OpenvinoBackend {
             self.0.replace(openvino::Core::new(None)?);
         }
 
-        // Read the guest array.
-        let builders = builders.as_ptr();
-        let xml = builders
-            .read()?
-            .as_slice()?
-            .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
-        let weights = builders
-            .add(1)?
-            .read()?
-            .as_slice()?
-            .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
+        let xml = builders[0];
+        let weights = builders[1];
 
         // Construct OpenVINO graph structures: `cnn_network` contains the graph
         // structure, `exec_network` can perform inference.
@@ -51,7 +50,7 @@ impl Backend for OpenvinoBackend {
             .0
             .as_mut()
             .expect("openvino::Core was previously constructed");
-        let mut cnn_network = core.read_network_from_buffer(&xml, &weights)?;
+        let mut cnn_network = core.read_network_from_buffer(xml, weights)?;
 
         // TODO this is a temporary workaround. We need a more eligant way to specify the layout in the long run.
         // However, without this newer versions of OpenVINO will fail due to parameter mismatch.
@@ -63,18 +62,24 @@ impl Backend for OpenvinoBackend {
         let exec_network =
             core.load_network(&cnn_network, map_execution_target_to_string(target))?;
 
-        Ok(Box::new(OpenvinoGraph(Arc::new(cnn_network), exec_network)))
+        Ok(Box::new(OpenvinoGraph(
+            Arc::new(cnn_network),
+            Mutex::new(exec_network),
+        )))
     }
 }
 
-struct OpenvinoGraph(Arc<openvino::CNNNetwork>, openvino::ExecutableNetwork);
+struct OpenvinoGraph(
+    Arc<openvino::CNNNetwork>,
+    Mutex<openvino::ExecutableNetwork>,
+);
 
 unsafe impl Send for OpenvinoGraph {}
 unsafe impl Sync for OpenvinoGraph {}
 
 impl BackendGraph for OpenvinoGraph {
-    fn init_execution_context(&mut self) -> Result<Box<dyn BackendExecutionContext>, BackendError> {
-        let infer_request = self.1.create_infer_request()?;
+    fn init_execution_context(&self) -> Result<Box<dyn BackendExecutionContext>, BackendError> {
+        let infer_request = self.1.lock().unwrap().create_infer_request()?;
         Ok(Box::new(OpenvinoExecutionContext(
             self.0.clone(),
             infer_request,
@@ -91,21 +96,15 @@ impl BackendExecutionContext for OpenvinoExecutionContext {
         // Construct the blob structure.
         let dimensions = tensor
             .dimensions
-            .as_slice()?
-            .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)")
             .iter()
             .map(|d| *d as usize)
             .collect::<Vec<_>>();
-        let precision = map_tensor_type_to_precision(tensor.type_);
+        let precision = map_tensor_type_to_precision(tensor.tensor_type);
 
         // TODO There must be some good way to discover the layout here; this
         // should not have to default to NHWC.
         let desc = TensorDesc::new(Layout::NHWC, &dimensions, precision);
-        let data = tensor
-            .data
-            .as_slice()?
-            .expect("cannot use with shared memories; see https://github.com/bytecodealliance/wasmtime/issues/5235 (TODO)");
-        let blob = openvino::Blob::new(&desc, &data)?;
+        let blob = openvino::Blob::new(&desc, tensor.data)?;
 
         // Actually assign the blob to the request.
         self.1.set_blob(&input_name, &blob)?;
@@ -1,11 +1,13 @@
 //! Contains the macro-generated implementation of wasi-nn from the its witx definition file.
+use crate::api::BackendError;
 use crate::ctx::WasiNnCtx;
 use crate::ctx::WasiNnError;
+use crate::r#impl::UsageError;
 use anyhow::Result;
 
 // Generate the traits and types of wasi-nn in several Rust modules (e.g. `types`).
 wiggle::from_witx!({
-    witx: ["$WASI_ROOT/phases/ephemeral/witx/wasi_ephemeral_nn.witx"],
+    witx: ["$CARGO_MANIFEST_DIR/witx/wasi-nn.witx"],
     errors: { nn_errno => WasiNnError }
 });
 
@@ -14,10 +16,15 @@ use types::NnErrno;
 impl<'a> types::UserErrorConversion for WasiNnCtx {
     fn nn_errno_from_wasi_nn_error(&mut self, e: WasiNnError) -> Result<NnErrno> {
         eprintln!("Host error: {:?}", e);
+        // Guest memory that can't be accessed traps, like it does in the other
+        // WASI APIs; everything else is reported to the guest.
         match e {
-            WasiNnError::BackendError(_) => unimplemented!(),
-            WasiNnError::GuestError(_) => unimplemented!(),
-            WasiNnError::UsageError(_) => unimplemented!(),
+            WasiNnError::GuestError(e)
+            | WasiNnError::BackendError(BackendError::GuestAccess(e)) => Err(e.into()),
+            WasiNnError::BackendError(BackendError::BackendAccess(_)) => Ok(NnErrno::RuntimeError),
+            WasiNnError::BackendError(_) => Ok(NnErrno::InvalidArgument),
+            WasiNnError::UsageError(UsageError::InvalidEncoding(_)) => Ok(NnErrno::InvalidEncoding),
+            WasiNnError::UsageError(_) => Ok(NnErrno::InvalidArgument),
         }
     }
 }
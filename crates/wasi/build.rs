@@ -3,4 +3,7 @@ fn main() {
     let wasi_root = std::env::var("DEP_WASI_COMMON_19_WASI").unwrap();
     // Make it available as WASI_ROOT:
     println!("cargo:rustc-env=WASI_ROOT={}", wasi_root);
+    // And the same for wasi-common's own witx documents:
+    let wasi_common_witx = std::env::var("DEP_WASI_COMMON_19_WITX").unwrap();
+    println!("cargo:rustc-env=WASI_COMMON_WITX={}", wasi_common_witx);
 }
@@ -7,7 +7,7 @@
 //! Individual snapshots are available through
 //! `wasmtime_wasi::snapshots::preview_{0, 1}::Wasi::new(&Store, Rc<RefCell<WasiCtx>>)`.
 
-pub use wasi_common::{Error, I32Exit, WasiCtx, WasiDir, WasiFile};
+pub use wasi_common::{Error, I32Exit, Trace, WasiCtx, WasiDir, WasiFile, WasiNetwork};
 
 /// Re-export the commonly used wasi-cap-std-sync crate here. This saves
 /// consumers of this library from having to keep additional dependencies
@@ -48,11 +48,13 @@ pub fn add_to_linker<T, U>(
 ) -> anyhow::Result<()>
     where U: Send
             + wasi_common::snapshots::preview_0::wasi_unstable::WasiUnstable
-            + wasi_common::snapshots::preview_1::wasi_snapshot_preview1::WasiSnapshotPreview1,
+            + wasi_common::snapshots::preview_1::wasi_snapshot_preview1::WasiSnapshotPreview1
+            + wasi_common::snapshots::experimental_sockets::wasi_experimental_sockets::WasiExperimentalSockets,
         $($bounds)*
 {
     snapshots::preview_1::add_wasi_snapshot_preview1_to_linker(linker, get_cx)?;
     snapshots::preview_0::add_wasi_unstable_to_linker(linker, get_cx)?;
+    snapshots::experimental_sockets::add_wasi_experimental_sockets_to_linker(linker, get_cx)?;
     Ok(())
 }
 
@@ -68,6 +70,17 @@ pub mod snapshots {
             $async_mode: *
         });
     }
+    pub mod experimental_sockets {
+        wiggle::wasmtime_integration!({
+            // The wiggle code to integrate with lives here:
+            target: wasi_common::snapshots::experimental_sockets,
+            // This must be the same witx document as used above. This should be ensured by
+            // the `WASI_COMMON_WITX` env variable, which is set in wasi-common's `build.rs`.
+            witx: ["$WASI_COMMON_WITX/wasi_experimental_sockets.witx"],
+            errors: { errno => trappable Error },
+            $async_mode: *
+        });
+    }
     pub mod preview_0 {
         wiggle::wasmtime_integration!({
             // The wiggle code to integrate with lives here:
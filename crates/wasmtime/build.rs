@@ -2,8 +2,8 @@ fn main() {
     // Code in the `wasmtime` crate will use #[cfg(compiler)] conditional
     // compilation when runtime compilation is supported or not. This #[cfg] is
     // defined by this build script here, and is guarded with a conditional.
-    // Currently this conditional is #[cfg(feature = "cranelift")] since that's
-    // the only supported compiler.
+    // Currently this conditional is #[cfg(any(feature = "cranelift", feature =
+    // "winch"))] since those are the supported compilers.
     //
     // Note that #[doc(cfg)] throughout the `wasmtime` crate points here. We
     // want the rustdoc documentation to accurately reflect the requirements for
@@ -12,7 +12,7 @@ fn main() {
     // frequently then all rustdoc attributes also need to be updated with the
     // new condition to ensure the documentation accurately reflects when an API
     // is available.
-    if cfg!(feature = "cranelift") {
+    if cfg!(feature = "cranelift") || cfg!(feature = "winch") {
         println!("cargo:rustc-cfg=compiler");
     }
 
@@ -0,0 +1,91 @@
+use anyhow::{bail, Context, Result};
+use wasmtime_environ::ModuleBlockProfile;
+
+/// The magic bytes at the start of a serialized block profile.
+const MAGIC: &[u8] = b"\0wasmtime-block-profile\0";
+
+/// Execution counts of the blocks of the compiled code of a module's
+/// functions, used to guide the layout of that code.
+///
+/// A block profile is collected by running a module compiled with
+/// [`Config::block_profiling`](crate::Config::block_profiling) and reading it
+/// back with [`Module::block_profile`](crate::Module::block_profile). It can
+/// then be saved with [`BlockProfile::serialize`] and passed to
+/// [`Module::new_with_block_profile`](crate::Module::new_with_block_profile)
+/// to compile the same module again with hot code laid out along fallthrough
+/// paths and code which never ran moved to the end of its function.
+///
+/// A profile only applies to the module it was collected from, compiled by the
+/// same version of Wasmtime with the same configuration apart from
+/// `Config::block_profiling` itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlockProfile {
+    inner: ModuleBlockProfile,
+}
+
+impl BlockProfile {
+    /// Creates an empty block profile, with which modules are compiled as if
+    /// no profile was used.
+    pub fn new() -> BlockProfile {
+        BlockProfile::default()
+    }
+
+    pub(crate) fn from_inner(inner: ModuleBlockProfile) -> BlockProfile {
+        BlockProfile { inner }
+    }
+
+    pub(crate) fn inner(&self) -> &ModuleBlockProfile {
+        &self.inner
+    }
+
+    /// Returns whether this profile has no counts.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Adds the counts of `other`, a profile of the same module, to this
+    /// profile, e.g. to combine the profiles of several runs.
+    pub fn merge(&mut self, other: &BlockProfile) {
+        self.inner.merge(&other.inner);
+    }
+
+    /// Serializes this profile to bytes, to be read back with
+    /// [`BlockProfile::deserialize`].
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let version = env!("CARGO_PKG_VERSION");
+        let mut data = MAGIC.to_vec();
+        data.push(version.len() as u8);
+        data.extend_from_slice(version.as_bytes());
+        bincode::serialize_into(&mut data, &self.inner)?;
+        Ok(data)
+    }
+
+    /// Deserializes a profile serialized with [`BlockProfile::serialize`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` isn't a serialized block profile, or if it
+    /// was serialized by a different version of Wasmtime.
+    pub fn deserialize(bytes: &[u8]) -> Result<BlockProfile> {
+        let data = bytes
+            .strip_prefix(MAGIC)
+            .context("bytes are not a serialized block profile")?;
+        let (len, data) = data
+            .split_first()
+            .context("serialized block profile is truncated")?;
+        let len = usize::from(*len);
+        if data.len() < len {
+            bail!("serialized block profile is truncated");
+        }
+        let (version, data) = data.split_at(len);
+        let version = std::str::from_utf8(version)?;
+        if version != env!("CARGO_PKG_VERSION") {
+            bail!(
+                "block profile was collected with Wasmtime {version}, not {}",
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+        let inner = bincode::deserialize(data).context("failed to decode block profile")?;
+        Ok(BlockProfile { inner })
+    }
+}
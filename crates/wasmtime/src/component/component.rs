@@ -1,4 +1,7 @@
 use crate::code::CodeObject;
+use crate::component::types::Type;
+#[cfg(all(feature = "cache", compiler))]
+use crate::module::HashedEngineCompileEnv;
 use crate::signatures::SignatureCollection;
 use crate::{Engine, Module};
 use anyhow::{bail, Context, Result};
@@ -11,7 +14,7 @@ use std::ptr::NonNull;
 use std::sync::Arc;
 use wasmtime_environ::component::{
     ComponentTypes, GlobalInitializer, LoweredIndex, RuntimeAlwaysTrapIndex,
-    RuntimeTranscoderIndex, StaticModuleIndex, Translator,
+    RuntimeTranscoderIndex, StaticModuleIndex, Translator, TypeDef,
 };
 use wasmtime_environ::{EntityRef, FunctionLoc, ObjectKind, PrimaryMap, ScopeVec, SignatureIndex};
 use wasmtime_jit::{CodeMemory, CompiledModuleInfo};
@@ -85,7 +88,7 @@ impl Component {
     //
     // FIXME: need to write more docs here.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn new(engine: &Engine, bytes: impl AsRef<[u8]>) -> Result<Component> {
         let bytes = bytes.as_ref();
         #[cfg(feature = "wat")]
@@ -98,7 +101,7 @@ impl Component {
     //
     // FIXME: need to write more docs here.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn from_file(engine: &Engine, file: impl AsRef<Path>) -> Result<Component> {
         match Self::new(
             engine,
@@ -124,16 +127,58 @@ impl Component {
     //
     // FIXME: need to write more docs here.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn from_binary(engine: &Engine, binary: &[u8]) -> Result<Component> {
         engine
             .check_compatible_with_native_host()
             .context("compilation settings are not compatible with the native host")?;
 
-        let (mmap, artifacts) = Component::build_artifacts(engine, binary)?;
-        let mut code_memory = CodeMemory::new(mmap)?;
-        code_memory.publish()?;
-        Component::from_parts(engine, Arc::new(code_memory), Some(artifacts))
+        // Components go through the same on-disk cache as core modules, keyed
+        // on the engine's compilation settings and the component's bytes. The
+        // cached artifact contains all nested core modules as well as the
+        // trampolines and adapters generated for the component.
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "cache")] {
+                let state = (HashedEngineCompileEnv(engine), binary);
+                let (code, artifacts) = wasmtime_cache::ModuleCacheEntry::new(
+                    "wasmtime",
+                    engine.cache_config(),
+                )
+                .get_data_raw(
+                    &state,
+
+                    // Cache miss, compute the actual artifacts
+                    |(engine, wasm)| -> Result<_> {
+                        let (mmap, artifacts) = Component::build_artifacts(engine.0, wasm)?;
+                        let code = publish_mmap(mmap)?;
+                        Ok((code, Some(artifacts)))
+                    },
+
+                    // Implementation of how to serialize artifacts
+                    |(_engine, _wasm), (code, _artifacts)| {
+                        Some(code.mmap().to_vec())
+                    },
+
+                    // Cache hit, deserialize the provided artifacts
+                    |(engine, _wasm), serialized_bytes| {
+                        let code = engine.0.load_code_bytes(&serialized_bytes, ObjectKind::Component).ok()?;
+                        Some((code, None))
+                    },
+                )?;
+            } else {
+                let (mmap, artifacts) = Component::build_artifacts(engine, binary)?;
+                let code = publish_mmap(mmap)?;
+                let artifacts = Some(artifacts);
+            }
+        };
+
+        return Component::from_parts(engine, code, artifacts);
+
+        fn publish_mmap(mmap: MmapVec) -> Result<Arc<CodeMemory>> {
+            let mut code = CodeMemory::new(mmap)?;
+            code.publish()?;
+            Ok(Arc::new(code))
+        }
     }
 
     /// Same as [`Module::deserialize`], but for components.
@@ -186,6 +231,18 @@ impl Component {
                 .context("failed to parse WebAssembly module")?;
         let types = types.finish();
 
+        // When the textual form of each function is being written out the
+        // binary of each core wasm module is written as well, notably
+        // including the adapter modules generated during translation which
+        // otherwise don't exist anywhere.
+        if let Some(dir) = engine.config().clif_dir() {
+            for (index, module) in modules.iter() {
+                let path = dir.join(format!("wasm_module_{}.wasm", index.as_u32()));
+                fs::write(&path, module.wasm)
+                    .with_context(|| format!("failed to write `{}`", path.display()))?;
+            }
+        }
+
         // Compile all core wasm modules, in parallel, which will internally
         // compile all their functions in parallel as well.
         let module_funcs = engine.run_maybe_parallel(modules.values_mut().collect(), |module| {
@@ -470,6 +527,30 @@ impl Component {
         &self.inner.info.component
     }
 
+    /// Returns the parameter and result types of the function this component
+    /// imports as `name`, or `None` if it doesn't import a function with that
+    /// name.
+    ///
+    /// This is primarily useful when defining imports with
+    /// [`LinkerInstance::func_new`](crate::component::LinkerInstance::func_new)
+    /// since compound result values must be created from their types.
+    pub fn import_func_type(&self, name: &str) -> Option<(Box<[Type]>, Box<[Type]>)> {
+        let index =
+            self.env_component()
+                .import_types
+                .values()
+                .find_map(|(import, ty)| match ty {
+                    TypeDef::ComponentFunc(index) if import == name => Some(*index),
+                    _ => None,
+                })?;
+        let types = self.types();
+        let ty = &types[index];
+        Some((
+            ty.params.iter().map(|ty| Type::from(ty, types)).collect(),
+            ty.results.iter().map(|ty| Type::from(ty, types)).collect(),
+        ))
+    }
+
     pub(crate) fn static_module(&self, idx: StaticModuleIndex) -> &Module {
         &self.inner.static_modules[idx]
     }
@@ -487,7 +568,9 @@ impl Component {
         self.inner.code.signatures()
     }
 
-    pub(crate) fn text(&self) -> &[u8] {
+    /// Get this component's code object's `.text` section, containing its
+    /// compiled executable code.
+    pub fn text(&self) -> &[u8] {
         self.inner.code.code_memory().text()
     }
 
@@ -527,4 +610,32 @@ impl Component {
     pub fn serialize(&self) -> Result<Vec<u8>> {
         Ok(self.code_object().code_memory().mmap().to_vec())
     }
+
+    /// Returns the core wasm modules of this component, including the adapter
+    /// modules generated by Wasmtime to implement calls from one component to
+    /// another.
+    ///
+    /// The modules defined within the component come first, in the order that
+    /// they appear in its binary, followed by the adapter modules. The `M`th
+    /// module is the one whose files are named `wasm_module_M` by
+    /// [`Config::emit_clif`](crate::Config::emit_clif).
+    ///
+    /// This is intended for inspecting the compiled code of a component, for
+    /// example with [`Module::function_locations`]. Note that all of these
+    /// modules share the `.text` section of this component.
+    pub fn core_modules(&self) -> impl ExactSizeIterator<Item = &Module> + '_ {
+        self.inner.static_modules.values()
+    }
+
+    /// Get the locations of the string transcoding functions in this
+    /// component's `.text` section, which are called from adapter modules.
+    ///
+    /// Each function's location is a (`.text` section offset, length) pair.
+    pub fn transcoder_locations(&self) -> impl ExactSizeIterator<Item = (usize, usize)> + '_ {
+        self.inner
+            .info
+            .transcoders
+            .values()
+            .map(|loc| (loc.start as usize, loc.length as usize))
+    }
 }
@@ -88,6 +88,7 @@ union ParamsAndResults<Params: Copy, Return: Copy> {
 //
 // FIXME: write more docs here
 #[derive(Copy, Clone, Debug)]
+#[repr(transparent)] // here for the C API
 pub struct Func(Stored<FuncData>);
 
 #[doc(hidden)]
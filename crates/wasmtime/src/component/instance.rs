@@ -24,6 +24,7 @@ use wasmtime_runtime::component::{ComponentInstance, OwnedComponentInstance};
 //
 // FIXME: need to write more docs here.
 #[derive(Copy, Clone)]
+#[repr(transparent)] // here for the C API
 pub struct Instance(pub(crate) Stored<Option<Box<InstanceData>>>);
 
 pub(crate) struct InstanceData {
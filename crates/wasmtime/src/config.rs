@@ -4,8 +4,10 @@ use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-#[cfg(feature = "cache")]
+#[cfg(any(feature = "cache", compiler))]
 use std::path::Path;
+#[cfg(compiler)]
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use target_lexicon::Architecture;
@@ -92,6 +94,7 @@ pub struct Config {
     pub(crate) wasm_backtrace: bool,
     pub(crate) wasm_backtrace_details_env_used: bool,
     pub(crate) native_unwind_info: bool,
+    pub(crate) coredump_on_trap: bool,
     #[cfg(feature = "async")]
     pub(crate) async_stack_size: usize,
     pub(crate) async_support: bool,
@@ -100,6 +103,7 @@ pub struct Config {
     pub(crate) memory_init_cow: bool,
     pub(crate) memory_guaranteed_dense_image_size: u64,
     pub(crate) force_memory_init_memfd: bool,
+    pub(crate) deterministic_execution: bool,
 }
 
 /// User-provided configuration for the compiler.
@@ -112,6 +116,7 @@ struct CompilerConfig {
     flags: HashSet<String>,
     #[cfg(compiler)]
     cache_store: Option<Arc<dyn CacheStore>>,
+    clif_dir: Option<PathBuf>,
 }
 
 #[cfg(compiler)]
@@ -123,6 +128,7 @@ impl CompilerConfig {
             settings: HashMap::new(),
             flags: HashSet::new(),
             cache_store: None,
+            clif_dir: None,
         }
     }
 
@@ -178,6 +184,7 @@ impl Config {
             wasm_backtrace: true,
             wasm_backtrace_details_env_used: false,
             native_unwind_info: true,
+            coredump_on_trap: false,
             features: WasmFeatures::default(),
             #[cfg(feature = "async")]
             async_stack_size: 2 << 20,
@@ -187,6 +194,7 @@ impl Config {
             memory_init_cow: true,
             memory_guaranteed_dense_image_size: 16 << 20,
             force_memory_init_memfd: false,
+            deterministic_execution: false,
         };
         #[cfg(compiler)]
         {
@@ -201,7 +209,7 @@ impl Config {
         ret.wasm_backtrace_details(WasmBacktraceDetails::Environment);
 
         // This is on-by-default in `wasmparser` since it's a stage 4+ proposal
-        // but it's not implemented in Wasmtime yet so disable it.
+        // but it's only implemented for some targets in Wasmtime so disable it.
         ret.features.tail_call = false;
 
         ret
@@ -220,7 +228,7 @@ impl Config {
     ///
     /// This method will error if the given target triple is not supported.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn target(&mut self, target: &str) -> Result<&mut Self> {
         self.compiler_config.target =
             Some(target_lexicon::Triple::from_str(target).map_err(|e| anyhow::anyhow!(e))?);
@@ -239,6 +247,38 @@ impl Config {
         Ok(self)
     }
 
+    /// Writes the textual form of each compiled function at various stages of
+    /// compilation into the directory `path`, which must already exist.
+    ///
+    /// This is intended for debugging the code generated for WebAssembly. For
+    /// a defined function with index `N` the files written are:
+    ///
+    /// * `wasm_func_N.clif` - the Cranelift IR translated from WebAssembly.
+    /// * `wasm_func_N.opt.clif` - the Cranelift IR after optimizations.
+    /// * `wasm_func_N.lowered.vcode` - machine instructions using virtual
+    ///   registers, as lowered from the optimized IR.
+    /// * `wasm_func_N.vcode` - machine instructions after register
+    ///   allocation.
+    ///
+    /// Instructions are prefixed with the offset, in the original wasm
+    /// binary, of the WebAssembly instruction that they were generated from.
+    ///
+    /// When compiling a component the functions of its `M`th core wasm module
+    /// are instead written to files named `wasm_module_M_func_N.*`, and the
+    /// binary of each core wasm module is written to `wasm_module_M.wasm`.
+    /// This includes the adapter modules generated for the component, whose
+    /// offsets are relative to their own binary rather than the component's.
+    ///
+    /// Note that no files are written for modules loaded from the cache
+    /// configured with [`Config::cache_config_load`], so the cache should be
+    /// disabled when using this option.
+    #[cfg(compiler)]
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
+    pub fn emit_clif(&mut self, path: &Path) -> &mut Self {
+        self.compiler_config.clif_dir = Some(path.to_path_buf());
+        self
+    }
+
     /// Whether or not to enable support for asynchronous functions in Wasmtime.
     ///
     /// When enabled, the config can optionally define host functions with `async`.
@@ -375,6 +415,30 @@ impl Config {
         self
     }
 
+    /// Configures whether a [`WasmCoreDump`] is captured when WebAssembly
+    /// traps.
+    ///
+    /// When enabled, errors returned from wasm which trapped will have a
+    /// [`WasmCoreDump`] attached via [`anyhow::Error::context`]. The coredump
+    /// holds on to every linear memory and global in the store, along with the
+    /// wasm frames on the stack at the time of the trap, and can be acquired
+    /// via [`anyhow::Error::downcast_ref`] and then serialized with
+    /// [`WasmCoreDump::serialize`].
+    ///
+    /// Frames in the coredump are only available when
+    /// [`Config::wasm_backtrace`] is also enabled, and their locals and
+    /// operand stacks are only recorded for code compiled with
+    /// [`Config::guest_debug`].
+    ///
+    /// This option is `false` by default.
+    ///
+    /// [`WasmCoreDump`]: crate::WasmCoreDump
+    /// [`WasmCoreDump::serialize`]: crate::WasmCoreDump::serialize
+    pub fn coredump_on_trap(&mut self, enable: bool) -> &mut Self {
+        self.coredump_on_trap = enable;
+        self
+    }
+
     /// Configures whether backtraces in `Trap` will parse debug info in the wasm file to
     /// have filename/line number information.
     ///
@@ -536,6 +600,103 @@ impl Config {
         self
     }
 
+    /// Enables instrumentation of compiled code for debugging guests at the
+    /// WebAssembly level.
+    ///
+    /// Unlike [`Config::debug_info`], which describes the compiled code to a
+    /// native debugger, this makes compiled code call back into the runtime
+    /// on function entry and exit and before each WebAssembly instruction.
+    /// Those calls make the current location, the locals and the value stack
+    /// of every WebAssembly frame available through
+    /// [`Store::debug_frames`](crate::Store::debug_frames), and invoke the
+    /// callback configured with
+    /// [`Store::debug_hook`](crate::Store::debug_hook), which is how
+    /// breakpoints and single-stepping are implemented by debuggers such as
+    /// [`GdbStub`](crate::GdbStub). The original binary of each module is
+    /// additionally retained so that debuggers can load it from the runtime.
+    ///
+    /// This instrumentation makes execution many times slower and is meant
+    /// for debugging only.
+    ///
+    /// By default this option is `false`.
+    pub fn guest_debug(&mut self, enable: bool) -> &mut Self {
+        self.tunables.guest_debug = enable;
+        self
+    }
+
+    /// Enables a memory sanitizer which checks the guest's use of its heap.
+    ///
+    /// When a module exports its allocator as `malloc` and `free`, and
+    /// optionally `calloc` and `realloc`, the runtime tracks which bytes of
+    /// the module's default linear memory are allocated by watching calls to
+    /// those functions, in a shadow memory kept outside of the guest. Every
+    /// load, store and atomic operation of the default memory, as well as
+    /// every range read or written by `memory.fill`, `memory.copy` and
+    /// `memory.init`, is then checked against it, and execution traps with a
+    /// diagnostic describing the access and the affected block on:
+    ///
+    /// * a use-after-free, an access to a block which was freed,
+    /// * a double free, or a free of a pointer which isn't a block,
+    /// * a heap out-of-bounds access, an access to the heap outside of any
+    ///   allocated block, such as just past the end of one, and
+    /// * an invalid allocation, a block returned by the allocator which
+    ///   doesn't fit in the memory.
+    ///
+    /// The heap is the region between the lowest and highest blocks that the
+    /// allocator has ever returned. Accesses outside of it, such as to the
+    /// guest's stack and static data, as well as accesses made while the
+    /// allocator itself runs, aren't checked. Neither are memories whose
+    /// module doesn't export an allocator.
+    ///
+    /// Like [`Config::guest_debug`], this instrumentation makes execution many
+    /// times slower and is meant for debugging only.
+    ///
+    /// By default this option is `false`.
+    pub fn memory_sanitizer(&mut self, enable: bool) -> &mut Self {
+        self.tunables.memory_sanitizer = enable;
+        self
+    }
+
+    /// Instruments compiled code to count how many times each of its blocks
+    /// is executed, to collect a [`BlockProfile`](crate::BlockProfile).
+    ///
+    /// The counts are shared by all instances of a [`Module`](crate::Module)
+    /// and are read back with
+    /// [`Module::block_profile`](crate::Module::block_profile). A profile
+    /// collected on a representative workload can then be passed to
+    /// [`Module::new_with_block_profile`](crate::Module::new_with_block_profile)
+    /// with this option disabled, to lay out the hot paths of the module's
+    /// functions as straight-line code and move the code which never ran to
+    /// the end of each function.
+    ///
+    /// Counting slows down execution, and counts may be lost when instances
+    /// of the same module run on several threads at once.
+    ///
+    /// By default this option is `false`.
+    pub fn block_profiling(&mut self, enable: bool) -> &mut Self {
+        self.tunables.block_profiling = enable;
+        self
+    }
+
+    /// Configures whether small functions are inlined into the functions of
+    /// the same module which call them.
+    ///
+    /// When enabled, all the functions of a module are translated before any
+    /// of them is compiled, and direct calls to functions whose code is small
+    /// enough are replaced with a copy of that code, which saves the cost of
+    /// the call and lets it be optimized together with the caller. This uses
+    /// more memory while compiling a module.
+    ///
+    /// Traps and backtraces still report the function that the inlined code
+    /// came from, followed by a frame for the function it was inlined into.
+    /// Inlining can't be combined with [`Config::debug_info`].
+    ///
+    /// By default this option is `false`.
+    pub fn inlining(&mut self, enable: bool) -> &mut Self {
+        self.tunables.inlining = enable;
+        self
+    }
+
     /// Configures the maximum amount of stack space available for
     /// executing WebAssembly code.
     ///
@@ -732,6 +893,42 @@ impl Config {
         self
     }
 
+    /// Configures whether all sources of nondeterminism within WebAssembly
+    /// execution itself are removed.
+    ///
+    /// WebAssembly is almost entirely deterministic, with the exception of a
+    /// few well-defined sources of nondeterminism. When this option is
+    /// enabled:
+    ///
+    /// * NaNs produced by floating-point instructions are canonicalized, see
+    ///   [`Config::cranelift_nan_canonicalization`].
+    /// * Relaxed SIMD instructions use their deterministic lowering, see
+    ///   [`Config::relaxed_simd_deterministic`].
+    /// * The [threads proposal][threads] is disabled, as the interleaving of
+    ///   threads is up to the host.
+    ///
+    /// Changing any of these settings back afterwards causes `Engine::new` to
+    /// fail. Disabling this option again leaves them as they are.
+    ///
+    /// Together with recording the results of host calls, for example with
+    /// `wasi_common::Trace`, this makes it possible to reproduce an execution
+    /// exactly. Note that resource exhaustion, such as running out of stack
+    /// or failing to grow a memory, still depends on the host.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [threads]: https://github.com/webassembly/threads
+    pub fn deterministic_execution(&mut self, enable: bool) -> &mut Self {
+        self.deterministic_execution = enable;
+        if enable {
+            self.relaxed_simd_deterministic(true);
+            #[cfg(compiler)]
+            self.cranelift_nan_canonicalization(true);
+            self.features.threads = false;
+        }
+        self
+    }
+
     /// Configures whether the [WebAssembly bulk memory operations
     /// proposal][proposal] will be enabled for compilation.
     ///
@@ -769,6 +966,28 @@ impl Config {
         self
     }
 
+    /// Configures whether the WebAssembly tail calls [proposal] will be enabled
+    /// for compilation.
+    ///
+    /// This feature gates the `return_call` and `return_call_indirect`
+    /// instructions. It is currently only supported when compiling for
+    /// x86_64 on platforms other than Windows, and not with Winch.
+    ///
+    /// When this is enabled wasm functions are compiled with Cranelift's
+    /// `tail` calling convention, in which functions pop their own stack
+    /// arguments, so that a tail call may pass more arguments than its caller
+    /// received. Calls into wasm from the host then always go through a
+    /// compiled trampoline, which makes [`TypedFunc::call`] slightly slower.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/webassembly/tail-call
+    /// [`TypedFunc::call`]: crate::TypedFunc::call
+    pub fn wasm_tail_call(&mut self, enable: bool) -> &mut Self {
+        self.features.tail_call = enable;
+        self
+    }
+
     /// Configures whether the WebAssembly multi-memory [proposal] will
     /// be enabled for compilation.
     ///
@@ -820,7 +1039,7 @@ impl Config {
     ///
     /// The default value for this is `Strategy::Auto`.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn strategy(&mut self, strategy: Strategy) -> &mut Self {
         self.compiler_config.strategy = strategy;
         self
@@ -854,7 +1073,7 @@ impl Config {
     ///
     /// The default value for this is `false`
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn cranelift_debug_verifier(&mut self, enable: bool) -> &mut Self {
         let val = if enable { "true" } else { "false" };
         self.compiler_config
@@ -871,7 +1090,7 @@ impl Config {
     ///
     /// The default value for this is `OptLevel::None`.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn cranelift_opt_level(&mut self, level: OptLevel) -> &mut Self {
         let val = match level {
             OptLevel::None => "none",
@@ -895,7 +1114,7 @@ impl Config {
     ///
     /// The default value for this is `true`.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     #[deprecated(
         since = "5.0.0",
         note = "egraphs will be the default and this method will be removed in a future version."
@@ -917,7 +1136,7 @@ impl Config {
     ///
     /// The default value for this is `false`
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn cranelift_nan_canonicalization(&mut self, enable: bool) -> &mut Self {
         let val = if enable { "true" } else { "false" };
         self.compiler_config
@@ -943,7 +1162,7 @@ impl Config {
     /// cause `Engine::new` fail if the flag's name does not exist, or the value is not appropriate
     /// for the flag type.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub unsafe fn cranelift_flag_enable(&mut self, flag: &str) -> &mut Self {
         self.compiler_config.flags.insert(flag.to_string());
         self
@@ -969,7 +1188,7 @@ impl Config {
     /// For example, feature `wasm_backtrace` will set `unwind_info` to `true`, but if it's
     /// manually set to false then it will fail.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub unsafe fn cranelift_flag_set(&mut self, name: &str, value: &str) -> &mut Self {
         self.compiler_config
             .settings
@@ -1494,6 +1713,24 @@ impl Config {
         if self.features.threads && !self.features.bulk_memory {
             bail!("feature 'threads' requires 'bulk_memory' to be enabled");
         }
+        if self.deterministic_execution {
+            if self.features.threads {
+                bail!("feature 'threads' cannot be enabled with deterministic execution");
+            }
+            if !self.tunables.relaxed_simd_deterministic {
+                bail!("deterministic execution requires deterministic relaxed SIMD");
+            }
+            #[cfg(compiler)]
+            if self
+                .compiler_config
+                .settings
+                .get("enable_nan_canonicalization")
+                .map(String::as_str)
+                != Some("true")
+            {
+                bail!("deterministic execution requires NaN canonicalization");
+            }
+        }
         #[cfg(feature = "async")]
         if self.max_wasm_stack > self.async_stack_size {
             bail!("max_wasm_stack size cannot exceed the async_stack_size");
@@ -1506,6 +1743,9 @@ impl Config {
         {
             bail!("static memory guard size cannot be smaller than dynamic memory guard size");
         }
+        if self.tunables.inlining && self.tunables.generate_native_debuginfo {
+            bail!("inlining cannot be enabled with native debug information");
+        }
 
         Ok(())
     }
@@ -1543,10 +1783,23 @@ impl Config {
         })
     }
 
+    /// Returns the directory configured with [`Config::emit_clif`], if any.
+    #[cfg(compiler)]
+    pub(crate) fn clif_dir(&self) -> Option<&Path> {
+        self.compiler_config.clif_dir.as_deref()
+    }
+
     #[cfg(compiler)]
     pub(crate) fn build_compiler(&mut self) -> Result<Box<dyn wasmtime_environ::Compiler>> {
         let mut compiler = match self.compiler_config.strategy {
+            #[cfg(feature = "cranelift")]
             Strategy::Auto | Strategy::Cranelift => wasmtime_cranelift::builder(),
+            #[cfg(not(feature = "cranelift"))]
+            Strategy::Auto | Strategy::Cranelift => bail!("cranelift support not compiled in"),
+            #[cfg(feature = "winch")]
+            Strategy::Winch => wasmtime_winch::builder(),
+            #[cfg(not(feature = "winch"))]
+            Strategy::Winch => bail!("winch support not compiled in"),
         };
 
         if let Some(target) = &self.compiler_config.target {
@@ -1574,6 +1827,49 @@ impl Config {
                 .insert("enable_probestack".into());
         }
 
+        // Tail calls are only implemented in the x86_64 backend so far. Wasm
+        // functions then use the `tail` calling convention, which is based on
+        // System V and so doesn't match how host functions are called on
+        // Windows.
+        if self.features.tail_call
+            && (target.architecture != Architecture::X86_64
+                || target.operating_system == target_lexicon::OperatingSystem::Windows)
+        {
+            bail!("the tail calls proposal is not supported on {}", target);
+        }
+
+        if let Strategy::Winch = self.compiler_config.strategy {
+            let supported = match target.architecture {
+                Architecture::X86_64 => {
+                    target.operating_system != target_lexicon::OperatingSystem::Windows
+                }
+                // Winch lays out stack arguments in 8-byte slots, which
+                // doesn't match Apple's calling convention.
+                Architecture::Aarch64(_) => {
+                    target.operating_system == target_lexicon::OperatingSystem::Linux
+                }
+                _ => false,
+            };
+            if !supported {
+                bail!("Winch is not supported on {}", target);
+            }
+            if self.tunables.consume_fuel || self.tunables.epoch_interruption {
+                bail!("fuel and epoch interruption are not supported by Winch");
+            }
+            if self.tunables.guest_debug
+                || self.tunables.memory_sanitizer
+                || self.tunables.block_profiling
+            {
+                bail!("guest debugging, the memory sanitizer and block profiling are not supported by Winch");
+            }
+            if self.tunables.inlining || self.tunables.generate_native_debuginfo {
+                bail!("function inlining and native debug information are not supported by Winch");
+            }
+            if self.features.tail_call {
+                bail!("the tail calls proposal is not supported by Winch");
+            }
+        }
+
         if self.native_unwind_info ||
              // Windows always needs unwind info, since it is part of the ABI.
              target.operating_system == target_lexicon::OperatingSystem::Windows
@@ -1627,6 +1923,14 @@ impl Config {
             compiler.enable_incremental_compilation(cache_store.clone())?;
         }
 
+        if let Some(path) = &self.compiler_config.clif_dir {
+            compiler.clif_dir(path)?;
+        }
+
+        if self.features.tail_call {
+            compiler.enable_tail_calls()?;
+        }
+
         compiler.build()
     }
 
@@ -1664,6 +1968,7 @@ impl fmt::Debug for Config {
             .field("wasm_bulk_memory", &self.features.bulk_memory)
             .field("wasm_simd", &self.features.simd)
             .field("wasm_relaxed_simd", &self.features.relaxed_simd)
+            .field("deterministic_execution", &self.deterministic_execution)
             .field("wasm_multi_value", &self.features.multi_value)
             .field(
                 "static_memory_maximum_size",
@@ -1711,6 +2016,15 @@ pub enum Strategy {
     /// Currently the default backend, Cranelift aims to be a reasonably fast
     /// code generator which generates high quality machine code.
     Cranelift,
+
+    /// A baseline compiler for WebAssembly, currently under active development
+    /// and not ready for production applications.
+    ///
+    /// Winch only supports x86_64 System V and aarch64 Linux targets and
+    /// doesn't support fuel, epochs, guest debugging, the memory sanitizer,
+    /// block profiling, function inlining or native debug information.
+    /// Trampolines are still compiled with Cranelift.
+    Winch,
 }
 
 /// Possible optimization levels for the Cranelift codegen backend.
@@ -0,0 +1,494 @@
+use crate::store::StoreOpaque;
+use crate::{
+    AsContextMut, DebugValue, FrameInfo, Global, Instance, Memory, Module, Mutability, Val,
+    ValType, WasmBacktrace,
+};
+use std::fmt;
+
+/// The maximum size of a data segment, whose length is encoded as a `u32`.
+const MAX_SEGMENT_SIZE: usize = u32::MAX as usize;
+
+/// Representation of a core dump of a WebAssembly store, captured when
+/// WebAssembly traps.
+///
+/// When the [`Config::coredump_on_trap`] option is enabled this structure is
+/// attached to the [`anyhow::Error`] returned from many Wasmtime functions that
+/// execute WebAssembly such as [`Instance::new`] or [`Func::call`]. This can be
+/// acquired with the [`anyhow::Error::downcast`] family of methods and then
+/// written out in the [wasm coredump format] with
+/// [`WasmCoreDump::serialize`], which debuggers can load afterwards. A
+/// coredump can also be created after the fact with [`WasmCoreDump::new`]
+/// from the [`WasmBacktrace`] attached to the error of a trap.
+///
+/// A coredump holds handles to every instance, linear memory and global
+/// within the store at the time of the trap. The contents of memories and
+/// globals are read when the coredump is serialized rather than when it's
+/// captured, so serialize the coredump before running more wasm in the same
+/// store to get an accurate picture of the trap.
+///
+/// The locals and operand stack of each frame are only recorded for code
+/// compiled with [`Config::guest_debug`] enabled, which keeps them in memory,
+/// and only in coredumps captured on trap. Otherwise they're written out as
+/// empty. Values which can't be represented in the coredump format, such as
+/// vectors and references, are written out as missing.
+///
+/// [`Config::coredump_on_trap`]: crate::Config::coredump_on_trap
+/// [`Config::guest_debug`]: crate::Config::guest_debug
+/// [`Func::call`]: crate::Func::call
+/// [`Instance::new`]: crate::Instance::new
+/// [wasm coredump format]: https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
+///
+/// # Examples
+///
+/// ```
+/// # use wasmtime::*;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut config = Config::new();
+/// config.coredump_on_trap(true);
+/// let engine = Engine::new(&config)?;
+/// let module = Module::new(
+///     &engine,
+///     r#"
+///         (module
+///             (memory 1)
+///             (global (mut i32) (i32.const 42))
+///             (func (export "run")
+///                 unreachable)
+///         )
+///     "#,
+/// )?;
+/// let mut store = Store::new(&engine, ());
+/// let instance = Instance::new(&mut store, &module, &[])?;
+/// let func = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+/// let error = func.call(&mut store, ()).unwrap_err();
+/// let coredump = error.downcast_ref::<WasmCoreDump>().unwrap();
+/// assert_eq!(coredump.frames().len(), 1);
+/// assert_eq!(coredump.instances().len(), 1);
+/// assert_eq!(coredump.memories().len(), 1);
+/// assert_eq!(coredump.globals().len(), 1);
+/// let bytes = coredump.serialize(&mut store, "example.wasm");
+/// # drop(bytes);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WasmCoreDump {
+    frames: Vec<FrameInfo>,
+    instances: Vec<Instance>,
+    modules: Vec<Module>,
+    memories: Vec<Memory>,
+    globals: Vec<Global>,
+    core_instances: Vec<CoreInstance>,
+    frame_instances: Vec<u32>,
+    frame_values: Vec<Option<(Vec<DebugValue>, Vec<DebugValue>)>>,
+}
+
+/// The layout of an instance within a coredump, in terms of indices into the
+/// coredump's lists of modules, memories and globals.
+#[derive(Debug)]
+struct CoreInstance {
+    module: u32,
+    memories: Vec<u32>,
+    globals: Vec<u32>,
+}
+
+impl WasmCoreDump {
+    /// Captures a coredump of `store` with the frames of `backtrace`.
+    ///
+    /// The `backtrace` is typically acquired from the error of a trap with
+    /// [`anyhow::Error::downcast_ref`], in which case this should be called
+    /// before any more WebAssembly runs in `store`.
+    pub fn new(mut store: impl AsContextMut, backtrace: &WasmBacktrace) -> WasmCoreDump {
+        WasmCoreDump::capture(store.as_context_mut().0, backtrace.frames().to_vec())
+    }
+
+    pub(crate) fn capture(store: &mut StoreOpaque, frames: Vec<FrameInfo>) -> WasmCoreDump {
+        let instances = store.all_instances();
+        let memories = store.all_memories();
+        let globals = store.all_globals();
+        let memory_definitions = memories
+            .iter()
+            .map(|m| m.vmimport(store).from)
+            .collect::<Vec<_>>();
+        let global_definitions = globals
+            .iter()
+            .map(|g| g.vmimport(store).from)
+            .collect::<Vec<_>>();
+
+        let mut modules: Vec<Module> = Vec::new();
+        let mut core_instances = Vec::new();
+        for instance in instances.iter() {
+            let module = instance.module(store).clone();
+            let module_index = match modules.iter().position(|m| Module::same(m, &module)) {
+                Some(i) => i,
+                None => {
+                    modules.push(module.clone());
+                    modules.len() - 1
+                }
+            };
+
+            // Memories and globals, including imported ones, are described in
+            // terms of where they are in the store-wide lists above. Shared
+            // memories aren't captured so they're omitted here too.
+            let id = instance.id(store);
+            let handle = store.instance_mut(id);
+            let env_module = module.env_module();
+            let instance_memories = env_module
+                .memory_plans
+                .keys()
+                .filter_map(|index| {
+                    let definition = handle.get_exported_memory(index).definition;
+                    memory_definitions.iter().position(|d| *d == definition)
+                })
+                .map(|i| i as u32)
+                .collect();
+            let instance_globals = env_module
+                .globals
+                .keys()
+                .filter_map(|index| {
+                    let definition = handle.get_exported_global(index).definition;
+                    global_definitions.iter().position(|d| *d == definition)
+                })
+                .map(|i| i as u32)
+                .collect();
+            core_instances.push(CoreInstance {
+                module: module_index as u32,
+                memories: instance_memories,
+                globals: instance_globals,
+            });
+        }
+
+        // Frames don't record which instance they were executing in, so they
+        // are attributed to the first instance of their module.
+        let frame_instances = frames
+            .iter()
+            .map(|frame| {
+                instances
+                    .iter()
+                    .position(|i| Module::same(i.module(store), frame.module()))
+                    .unwrap_or(0) as u32
+            })
+            .collect();
+
+        // The locals and operand stacks of frames compiled with guest
+        // debugging were saved by their records, which are matched with the
+        // frames from the innermost one outwards.
+        let mut records = store.debug_frame_records().iter().rev().peekable();
+        let frame_values = frames
+            .iter()
+            .map(|frame| {
+                let record = records.next_if(|r| {
+                    r.func_index() == frame.func_index() && Module::same(r.module(), frame.module())
+                })?;
+                record.saved().cloned()
+            })
+            .collect();
+
+        WasmCoreDump {
+            frames,
+            instances,
+            modules,
+            memories,
+            globals,
+            core_instances,
+            frame_instances,
+            frame_values,
+        }
+    }
+
+    /// Returns the wasm frames that were on the stack when the trap happened,
+    /// with the most recently called function first.
+    ///
+    /// This is empty if [`Config::wasm_backtrace`](crate::Config::wasm_backtrace)
+    /// is disabled.
+    pub fn frames(&self) -> &[FrameInfo] {
+        &self.frames
+    }
+
+    /// Returns all instances within the store when the trap happened, in the
+    /// order they were created.
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Returns the modules that the instances in this coredump were
+    /// instantiated from, without duplicates.
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Returns all linear memories defined within the store when the trap
+    /// happened.
+    ///
+    /// Shared memories are not included.
+    pub fn memories(&self) -> &[Memory] {
+        &self.memories
+    }
+
+    /// Returns all globals defined within the store when the trap happened.
+    pub fn globals(&self) -> &[Global] {
+        &self.globals
+    }
+
+    /// Serializes this coredump into the binary wasm coredump format.
+    ///
+    /// The `name` is recorded as the name of the executable which was
+    /// running, typically the name of the wasm file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` isn't the store this coredump was captured from.
+    pub fn serialize(&self, mut store: impl AsContextMut, name: &str) -> Vec<u8> {
+        let mut store = store.as_context_mut();
+        let mut module = Vec::new();
+        module.extend_from_slice(b"\0asm");
+        module.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]);
+
+        let mut core = vec![0x00];
+        write_name(&mut core, name);
+        write_custom_section(&mut module, "core", &core);
+
+        let mut coremodules = Vec::new();
+        write_u32(&mut coremodules, self.modules.len() as u32);
+        for m in self.modules.iter() {
+            coremodules.push(0x00);
+            write_name(&mut coremodules, m.name().unwrap_or("<anonymous-module>"));
+        }
+        write_custom_section(&mut module, "coremodules", &coremodules);
+
+        let mut coreinstances = Vec::new();
+        write_u32(&mut coreinstances, self.core_instances.len() as u32);
+        for instance in self.core_instances.iter() {
+            coreinstances.push(0x00);
+            write_u32(&mut coreinstances, instance.module);
+            write_u32(&mut coreinstances, instance.memories.len() as u32);
+            for index in instance.memories.iter() {
+                write_u32(&mut coreinstances, *index);
+            }
+            write_u32(&mut coreinstances, instance.globals.len() as u32);
+            for index in instance.globals.iter() {
+                write_u32(&mut coreinstances, *index);
+            }
+        }
+        write_custom_section(&mut module, "coreinstances", &coreinstances);
+
+        let mut corestack = vec![0x00];
+        write_name(&mut corestack, "main");
+        write_u32(&mut corestack, self.frames.len() as u32);
+        let frames = self
+            .frames
+            .iter()
+            .zip(&self.frame_instances)
+            .zip(&self.frame_values);
+        for ((frame, instance), values) in frames {
+            corestack.push(0x00);
+            write_u32(&mut corestack, *instance);
+            write_u32(&mut corestack, frame.func_index());
+            write_u32(&mut corestack, frame.func_offset().unwrap_or(0) as u32);
+            let (locals, stack) = match values {
+                Some((locals, stack)) => (&locals[..], &stack[..]),
+                None => (&[][..], &[][..]),
+            };
+            write_values(&mut corestack, locals);
+            write_values(&mut corestack, stack);
+        }
+        write_custom_section(&mut module, "corestack", &corestack);
+
+        let mut memories = Vec::new();
+        let mut data = Vec::new();
+        write_u32(&mut memories, self.memories.len() as u32);
+        let mut segments = 0;
+        for (index, memory) in self.memories.iter().enumerate() {
+            let ty = memory.ty(&store);
+            let mut flags = 0x00;
+            if ty.maximum().is_some() {
+                flags |= 0x01;
+            }
+            if ty.is_64() {
+                flags |= 0x04;
+            }
+            memories.push(flags);
+            // The minimum size is the current size so that all of the data
+            // captured below fits within the memory.
+            write_u64(&mut memories, memory.size(&store));
+            if let Some(max) = ty.maximum() {
+                write_u64(&mut memories, max);
+            }
+
+            // Trailing zeros are already provided by the memory being
+            // zero-initialized, so only the prefix up to the last non-zero
+            // byte is written out as an active data segment.
+            let bytes = memory.data(&store);
+            let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+            if len == 0 {
+                continue;
+            }
+            // The length of a data segment is a `u32`, so the contents of
+            // large memories are split into several segments.
+            for (i, chunk) in bytes[..len].chunks(MAX_SEGMENT_SIZE).enumerate() {
+                let offset = (i * MAX_SEGMENT_SIZE) as u64;
+                segments += 1;
+                if index == 0 {
+                    data.push(0x00);
+                } else {
+                    data.push(0x02);
+                    write_u32(&mut data, index as u32);
+                }
+                if ty.is_64() {
+                    data.push(0x42);
+                    write_i64(&mut data, offset as i64);
+                } else {
+                    // Offsets into 32-bit memories are `i32` constants.
+                    data.push(0x41);
+                    write_i64(&mut data, i64::from(offset as u32 as i32));
+                }
+                data.push(0x0b);
+                write_u32(&mut data, chunk.len() as u32);
+                data.extend_from_slice(chunk);
+            }
+        }
+        write_section(&mut module, 5, &memories);
+
+        let mut globals = Vec::new();
+        write_u32(&mut globals, self.globals.len() as u32);
+        for global in self.globals.iter() {
+            let ty = global.ty(&store);
+            globals.push(match ty.content() {
+                ValType::I32 => 0x7f,
+                ValType::I64 => 0x7e,
+                ValType::F32 => 0x7d,
+                ValType::F64 => 0x7c,
+                ValType::V128 => 0x7b,
+                ValType::FuncRef => 0x70,
+                ValType::ExternRef => 0x6f,
+            });
+            globals.push(match ty.mutability() {
+                Mutability::Const => 0x00,
+                Mutability::Var => 0x01,
+            });
+            match global.get(&mut store) {
+                Val::I32(i) => {
+                    globals.push(0x41);
+                    write_i64(&mut globals, i.into());
+                }
+                Val::I64(i) => {
+                    globals.push(0x42);
+                    write_i64(&mut globals, i);
+                }
+                Val::F32(bits) => {
+                    globals.push(0x43);
+                    globals.extend_from_slice(&bits.to_le_bytes());
+                }
+                Val::F64(bits) => {
+                    globals.push(0x44);
+                    globals.extend_from_slice(&bits.to_le_bytes());
+                }
+                Val::V128(bits) => {
+                    globals.extend_from_slice(&[0xfd, 0x0c]);
+                    globals.extend_from_slice(&bits.to_le_bytes());
+                }
+                // References can't be meaningfully serialized, so they're
+                // always recorded as null.
+                Val::FuncRef(_) => globals.extend_from_slice(&[0xd0, 0x70]),
+                Val::ExternRef(_) => globals.extend_from_slice(&[0xd0, 0x6f]),
+            }
+            globals.push(0x0b);
+        }
+        write_section(&mut module, 6, &globals);
+
+        let mut data_section = Vec::new();
+        write_u32(&mut data_section, segments);
+        data_section.extend_from_slice(&data);
+        write_section(&mut module, 11, &data_section);
+
+        module
+    }
+}
+
+impl fmt::Display for WasmCoreDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wasm coredump captured with {} frame(s), {} instances, {} memories and {} globals",
+            self.frames.len(),
+            self.instances.len(),
+            self.memories.len(),
+            self.globals.len()
+        )
+    }
+}
+
+fn write_values(dst: &mut Vec<u8>, values: &[DebugValue]) {
+    write_u32(dst, values.len() as u32);
+    for value in values {
+        match *value {
+            DebugValue::I32(i) => {
+                dst.push(0x7f);
+                write_i64(dst, i.into());
+            }
+            DebugValue::I64(i) => {
+                dst.push(0x7e);
+                write_i64(dst, i);
+            }
+            DebugValue::F32(bits) => {
+                dst.push(0x7d);
+                dst.extend_from_slice(&bits.to_le_bytes());
+            }
+            DebugValue::F64(bits) => {
+                dst.push(0x7c);
+                dst.extend_from_slice(&bits.to_le_bytes());
+            }
+            // Vectors and references have no representation in the coredump
+            // format, so they're recorded as missing values.
+            DebugValue::V128(_) | DebugValue::Ref(_) => dst.push(0x01),
+        }
+    }
+}
+
+fn write_section(dst: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    dst.push(id);
+    write_u32(dst, contents.len() as u32);
+    dst.extend_from_slice(contents);
+}
+
+fn write_custom_section(dst: &mut Vec<u8>, name: &str, contents: &[u8]) {
+    let mut section = Vec::new();
+    write_name(&mut section, name);
+    section.extend_from_slice(contents);
+    write_section(dst, 0, &section);
+}
+
+fn write_name(dst: &mut Vec<u8>, name: &str) {
+    write_u32(dst, name.len() as u32);
+    dst.extend_from_slice(name.as_bytes());
+}
+
+fn write_u32(dst: &mut Vec<u8>, val: u32) {
+    write_u64(dst, val.into())
+}
+
+fn write_u64(dst: &mut Vec<u8>, mut val: u64) {
+    loop {
+        let byte = (val & 0x7f) as u8;
+        val >>= 7;
+        if val == 0 {
+            dst.push(byte);
+            return;
+        }
+        dst.push(byte | 0x80);
+    }
+}
+
+fn write_i64(dst: &mut Vec<u8>, mut val: i64) {
+    loop {
+        let byte = (val & 0x7f) as u8;
+        val >>= 7;
+        let done = (val == 0 && byte & 0x40 == 0) || (val == -1 && byte & 0x40 != 0);
+        if done {
+            dst.push(byte);
+            return;
+        }
+        dst.push(byte | 0x80);
+    }
+}
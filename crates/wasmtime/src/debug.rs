@@ -0,0 +1,329 @@
+//! Debugging of guests at the WebAssembly level, see
+//! [`Config::guest_debug`](crate::Config::guest_debug).
+
+use crate::store::StoreId;
+use crate::{Module, StoreContext};
+use std::ptr;
+use wasmtime_environ::{
+    DebugValueKind, EntityRef, GlobalIndex, MemoryIndex, WasmType, DEBUG_FRAME_VALUE_OFFSET,
+    DEBUG_FRAME_VALUE_SIZE,
+};
+use wasmtime_runtime::{DebugHook, InstanceHandle, VMContext};
+
+mod gdbstub;
+
+pub use self::gdbstub::GdbStub;
+
+/// The instruction about to be executed, as passed to the callback configured
+/// with [`Store::debug_hook`](crate::Store::debug_hook).
+#[derive(Clone)]
+pub struct DebugLocation {
+    pub(crate) module: Module,
+    pub(crate) func_index: u32,
+    pub(crate) module_offset: usize,
+    pub(crate) depth: usize,
+}
+
+impl DebugLocation {
+    /// Returns the module which defines the executing function.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the index of the executing function within its module.
+    pub fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// Returns the offset of the instruction within the original wasm
+    /// module.
+    pub fn module_offset(&self) -> usize {
+        self.module_offset
+    }
+
+    /// Returns the number of WebAssembly frames on the stack, including the
+    /// executing one, which can be used to step over or out of calls.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// The value of a local, value stack entry or global of a [`DebugFrame`].
+///
+/// Values are reported as they are represented in compiled code, which means
+/// that floats are their raw bits, `funcref`s are 64-bit integers and
+/// `externref`s are their raw pointers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugValue {
+    /// A 32-bit integer.
+    I32(i32),
+    /// A 64-bit integer.
+    I64(i64),
+    /// The bits of a 32-bit float.
+    F32(u32),
+    /// The bits of a 64-bit float.
+    F64(u64),
+    /// A 128-bit vector.
+    V128(u128),
+    /// A reference's raw pointer.
+    Ref(usize),
+}
+
+impl DebugValue {
+    /// Returns the little-endian bytes of this value.
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        match *self {
+            DebugValue::I32(v) => v.to_le_bytes().to_vec(),
+            DebugValue::I64(v) => v.to_le_bytes().to_vec(),
+            DebugValue::F32(v) => v.to_le_bytes().to_vec(),
+            DebugValue::F64(v) => v.to_le_bytes().to_vec(),
+            DebugValue::V128(v) => v.to_le_bytes().to_vec(),
+            DebugValue::Ref(v) => v.to_le_bytes().to_vec(),
+        }
+    }
+
+    /// Reads the value of the debug frame `entry`.
+    unsafe fn read(entry: *const u8) -> DebugValue {
+        let kind = ptr::read_unaligned(entry.cast::<u32>());
+        let value = entry.add(DEBUG_FRAME_VALUE_OFFSET as usize);
+        match DebugValueKind::from_u32(kind).expect("invalid debug frame value") {
+            DebugValueKind::I32 => DebugValue::I32(ptr::read_unaligned(value.cast())),
+            DebugValueKind::I64 => DebugValue::I64(ptr::read_unaligned(value.cast())),
+            DebugValueKind::F32 => DebugValue::F32(ptr::read_unaligned(value.cast())),
+            DebugValueKind::F64 => DebugValue::F64(ptr::read_unaligned(value.cast())),
+            DebugValueKind::V128 => DebugValue::V128(ptr::read_unaligned(value.cast())),
+            DebugValueKind::Ref => DebugValue::Ref(ptr::read_unaligned(value.cast())),
+        }
+    }
+}
+
+/// A snapshot of a WebAssembly frame on the stack of a store, as returned by
+/// [`Store::debug_frames`](crate::Store::debug_frames).
+///
+/// The locals and value stack of the frame are copied when the snapshot is
+/// taken, while its instance's memories and globals are read from the store
+/// on demand.
+pub struct DebugFrame {
+    store: StoreId,
+    module: Module,
+    vmctx: *mut VMContext,
+    func_index: u32,
+    module_offset: usize,
+    locals: Vec<DebugValue>,
+    stack: Vec<DebugValue>,
+}
+
+// The `vmctx` pointer stays valid for as long as the store that the frame
+// came from, and it's only used with that store.
+unsafe impl Send for DebugFrame {}
+unsafe impl Sync for DebugFrame {}
+
+impl DebugFrame {
+    /// Returns the module which defines this frame's function.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the index of this frame's function within its module.
+    pub fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// Returns the offset within the original wasm module of the instruction
+    /// that this frame is executing, which for frames other than the
+    /// innermost one is the call to the next frame.
+    pub fn module_offset(&self) -> usize {
+        self.module_offset
+    }
+
+    /// Returns the values of the locals of this frame, starting with its
+    /// parameters.
+    pub fn locals(&self) -> &[DebugValue] {
+        &self.locals
+    }
+
+    /// Returns the values on the value stack of this frame, from its bottom
+    /// to its top.
+    pub fn stack(&self) -> &[DebugValue] {
+        &self.stack
+    }
+
+    /// Returns the contents of the linear memory `index` of this frame's
+    /// instance, or `None` if there's no such memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` isn't the store that this frame came from.
+    pub fn memory<'a, T: 'a>(
+        &self,
+        store: impl Into<StoreContext<'a, T>>,
+        index: u32,
+    ) -> Option<&'a [u8]> {
+        let store = store.into();
+        assert!(
+            store.0.id() == self.store,
+            "frame used with the wrong store"
+        );
+        let mut handle = unsafe { InstanceHandle::from_vmctx(self.vmctx) };
+        let index = MemoryIndex::from_u32(index);
+        if index.index() >= handle.module().memory_plans.len() {
+            return None;
+        }
+        let memory = handle.get_exported_memory(index);
+        unsafe {
+            let definition = &*memory.definition;
+            Some(std::slice::from_raw_parts(
+                definition.base,
+                definition.current_length(),
+            ))
+        }
+    }
+
+    /// Returns the value of the global `index` of this frame's instance, or
+    /// `None` if there's no such global.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` isn't the store that this frame came from.
+    pub fn global<'a, T: 'a>(
+        &self,
+        store: impl Into<StoreContext<'a, T>>,
+        index: u32,
+    ) -> Option<DebugValue> {
+        let store = store.into();
+        assert!(
+            store.0.id() == self.store,
+            "frame used with the wrong store"
+        );
+        let mut handle = unsafe { InstanceHandle::from_vmctx(self.vmctx) };
+        let index = GlobalIndex::from_u32(index);
+        if index.index() >= handle.module().globals.len() {
+            return None;
+        }
+        let global = handle.get_exported_global(index);
+        unsafe {
+            let definition = &*global.definition;
+            Some(match global.global.wasm_ty {
+                WasmType::I32 => DebugValue::I32(*definition.as_i32()),
+                WasmType::I64 => DebugValue::I64(*definition.as_i64()),
+                WasmType::F32 => DebugValue::F32(*definition.as_f32_bits()),
+                WasmType::F64 => DebugValue::F64(*definition.as_f64_bits()),
+                WasmType::V128 => DebugValue::V128(*definition.as_u128()),
+                WasmType::FuncRef => DebugValue::Ref(definition.as_anyfunc() as usize),
+                WasmType::ExternRef => DebugValue::Ref(
+                    definition
+                        .as_externref()
+                        .as_ref()
+                        .map_or(0, |r| r.as_raw() as usize),
+                ),
+            })
+        }
+    }
+}
+
+/// A WebAssembly frame executing code compiled with guest debugging, as
+/// tracked by its store from the function's entry to its exit.
+pub(crate) struct DebugFrameRecord {
+    module: Module,
+    vmctx: *mut VMContext,
+    func_index: u32,
+    frame: *const u8,
+    module_offset: u32,
+    locals: u32,
+    stack: u32,
+    /// Copies of the locals and value stack as of the last step, which are
+    /// only kept when they must outlive the frame itself, see `step`.
+    saved: Option<(Vec<DebugValue>, Vec<DebugValue>)>,
+}
+
+// The pointers are only dereferenced while the frame is on the stack of the
+// store which owns this record.
+unsafe impl Send for DebugFrameRecord {}
+unsafe impl Sync for DebugFrameRecord {}
+
+impl DebugFrameRecord {
+    /// Creates the record of the function which `hook`, the entry hook, was
+    /// called for.
+    pub(crate) fn new(module: Module, hook: &DebugHook) -> DebugFrameRecord {
+        DebugFrameRecord {
+            module,
+            vmctx: hook.vmctx,
+            func_index: hook.func.as_u32(),
+            frame: hook.frame,
+            module_offset: hook.offset,
+            locals: 0,
+            stack: 0,
+            saved: None,
+        }
+    }
+
+    /// Updates this record from the hook called before an instruction.
+    ///
+    /// When `save` is true the locals and value stack are copied out of the
+    /// frame as well, so that they're still available after a trap unwinds
+    /// the frame, for example to be written to a coredump.
+    pub(crate) fn step(&mut self, hook: &DebugHook, save: bool) {
+        debug_assert_eq!(self.frame, hook.frame);
+        self.module_offset = hook.offset;
+        self.locals = hook.locals;
+        self.stack = hook.stack;
+        if save {
+            // Safety: the frame is on the stack while its hook is called.
+            self.saved = Some(unsafe { self.values() });
+        }
+    }
+
+    pub(crate) fn module(&self) -> &Module {
+        &self.module
+    }
+
+    pub(crate) fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// Returns the locals and value stack saved by the last `step`, if any.
+    pub(crate) fn saved(&self) -> Option<&(Vec<DebugValue>, Vec<DebugValue>)> {
+        self.saved.as_ref()
+    }
+
+    pub(crate) fn location(&self, depth: usize) -> DebugLocation {
+        DebugLocation {
+            module: self.module.clone(),
+            func_index: self.func_index,
+            module_offset: self.module_offset as usize,
+            depth,
+        }
+    }
+
+    /// Copies the state of this frame into a `DebugFrame`.
+    ///
+    /// # Unsafety
+    ///
+    /// This frame must still be on the stack.
+    pub(crate) unsafe fn snapshot(&self, store: StoreId) -> DebugFrame {
+        let (locals, stack) = self.values();
+        DebugFrame {
+            store,
+            module: self.module.clone(),
+            vmctx: self.vmctx,
+            func_index: self.func_index,
+            module_offset: self.module_offset as usize,
+            locals,
+            stack,
+        }
+    }
+
+    /// Reads the locals and value stack of this frame.
+    ///
+    /// # Unsafety
+    ///
+    /// This frame must still be on the stack.
+    unsafe fn values(&self) -> (Vec<DebugValue>, Vec<DebugValue>) {
+        let value =
+            |i: u32| DebugValue::read(self.frame.add((i * DEBUG_FRAME_VALUE_SIZE) as usize));
+        (
+            (0..self.locals).map(value).collect(),
+            (self.locals..self.locals + self.stack).map(value).collect(),
+        )
+    }
+}
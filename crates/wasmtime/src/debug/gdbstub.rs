@@ -0,0 +1,584 @@
+//! A debugger server speaking the GDB remote serial protocol.
+//!
+//! WebAssembly isn't a target that GDB itself knows about, so this implements
+//! the dialect that LLDB uses for WebAssembly targets, which is also what
+//! other WebAssembly runtimes implement: code addresses identify a module and
+//! an offset within its original binary, the only register is the pc, and the
+//! `qWasmCallStack`, `qWasmLocal`, `qWasmGlobal`, `qWasmStackValue` and
+//! `qWasmMem` packets inspect the state of the guest frame by frame.
+
+use super::{DebugFrame, DebugLocation};
+use crate::{AsContext, Module, Store};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+
+/// The bits of an address which hold its kind: memory addresses are kind 0
+/// and code addresses, within the binary of a module, are kind 1.
+const ADDRESS_KIND_SHIFT: u32 = 62;
+const ADDRESS_KIND_CODE: u64 = 1;
+
+/// How many times the debug hook is called between checks for an interrupt
+/// request from the debugger while the guest is running.
+const INTERRUPT_POLL_INTERVAL: u32 = 4096;
+
+/// A debugger server for guests which speaks the GDB remote serial protocol
+/// over TCP.
+///
+/// The server exposes breakpoints, single-stepping, the call stack, the
+/// locals and value stack of each frame, globals and linear memory of guests
+/// at the WebAssembly level, independently of any native debugger. It
+/// requires [`Config::guest_debug`](crate::Config::guest_debug) to be enabled
+/// and is attached to a store with [`GdbStub::attach`].
+///
+/// Guests are stopped before their first instruction until a debugger has
+/// connected, for example with LLDB's `process connect connect://ADDR`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use wasmtime::*;
+/// # fn main() -> anyhow::Result<()> {
+/// let mut config = Config::new();
+/// config.guest_debug(true);
+/// let engine = Engine::new(&config)?;
+/// let module = Module::from_file(&engine, "guest.wasm")?;
+///
+/// let mut store = Store::new(&engine, ());
+/// let stub = GdbStub::listen("127.0.0.1:1234")?;
+/// stub.attach(&mut store);
+///
+/// let instance = Instance::new(&mut store, &module, &[])?;
+/// let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+/// let result = run.call(&mut store, ());
+/// stub.finish(if result.is_ok() { 0 } else { 1 })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GdbStub {
+    session: Arc<Mutex<Session>>,
+}
+
+impl GdbStub {
+    /// Creates a server listening for a debugger on `addr`.
+    pub fn listen(addr: impl ToSocketAddrs) -> Result<GdbStub> {
+        let listener = TcpListener::bind(addr).context("failed to bind debugger address")?;
+        Ok(GdbStub {
+            session: Arc::new(Mutex::new(Session {
+                listener,
+                conn: None,
+                modules: Vec::new(),
+                breakpoints: HashSet::new(),
+                resume: Resume::Continue,
+                hooks_until_poll: INTERRUPT_POLL_INTERVAL,
+            })),
+        })
+    }
+
+    /// Returns the address that the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.session.lock().unwrap().listener.local_addr()?)
+    }
+
+    /// Debugs the guests executing in `store` with this server.
+    ///
+    /// This replaces any hook previously configured with
+    /// [`Store::debug_hook`].
+    pub fn attach<T>(&self, store: &mut Store<T>) {
+        let session = self.session.clone();
+        store.debug_hook(move |store, location| session.lock().unwrap().hook(store, location));
+    }
+
+    /// Reports to the debugger that the guest exited with `exit_code`.
+    ///
+    /// This does nothing if no debugger is connected.
+    pub fn finish(&self, exit_code: i32) -> Result<()> {
+        let mut session = self.session.lock().unwrap();
+        if let Some(conn) = &mut session.conn {
+            conn.send(&format!("W{:02x}", exit_code as u8))?;
+        }
+        session.conn = None;
+        Ok(())
+    }
+}
+
+/// How the guest resumes executing after a stop.
+#[derive(Copy, Clone, PartialEq)]
+enum Resume {
+    Continue,
+    Step,
+    /// The debugger went away, so the guest runs without stopping again.
+    Detached,
+}
+
+/// Why the guest stopped.
+#[derive(Copy, Clone)]
+enum StopReason {
+    Attached,
+    Breakpoint,
+    Step,
+    Interrupt,
+}
+
+struct Session {
+    listener: TcpListener,
+    conn: Option<Connection>,
+    /// The modules that have executed code so far, indexed by the ids used in
+    /// code addresses.
+    modules: Vec<Module>,
+    /// The code addresses of the breakpoints.
+    breakpoints: HashSet<u64>,
+    resume: Resume,
+    hooks_until_poll: u32,
+}
+
+impl Session {
+    fn hook(&mut self, store: impl AsContext, location: DebugLocation) -> Result<()> {
+        if self.resume == Resume::Detached {
+            return Ok(());
+        }
+        let module = self.module_id(&location.module);
+        let pc = code_address(module, location.module_offset);
+
+        let reason = if self.conn.is_none() {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .context("failed to accept debugger connection")?;
+            self.conn = Some(Connection::new(stream)?);
+            StopReason::Attached
+        } else if self.resume == Resume::Step {
+            StopReason::Step
+        } else if self.breakpoints.contains(&pc) {
+            StopReason::Breakpoint
+        } else if self.interrupted()? {
+            StopReason::Interrupt
+        } else {
+            return Ok(());
+        };
+        self.stopped(store, reason)
+    }
+
+    /// Returns the id of `module`, assigning it one when it's first seen.
+    fn module_id(&mut self, module: &Module) -> usize {
+        match self.modules.iter().position(|m| Module::same(m, module)) {
+            Some(id) => id,
+            None => {
+                self.modules.push(module.clone());
+                self.modules.len() - 1
+            }
+        }
+    }
+
+    /// Checks, every so often, whether the debugger asked to interrupt the
+    /// running guest.
+    fn interrupted(&mut self) -> Result<bool> {
+        self.hooks_until_poll -= 1;
+        if self.hooks_until_poll > 0 {
+            return Ok(false);
+        }
+        self.hooks_until_poll = INTERRUPT_POLL_INTERVAL;
+        let conn = self.conn.as_mut().unwrap();
+        conn.stream.set_nonblocking(true)?;
+        let mut byte = [0];
+        let result = conn.stream.read(&mut byte);
+        conn.stream.set_nonblocking(false)?;
+        match result {
+            Ok(0) => {
+                self.resume = Resume::Detached;
+                Ok(false)
+            }
+            Ok(_) => Ok(byte[0] == 0x03),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Serves the debugger's requests while the guest is stopped, returning
+    /// once it resumes the guest.
+    fn stopped(&mut self, store: impl AsContext, reason: StopReason) -> Result<()> {
+        let frames = store.as_context().debug_frames();
+        let mut conn = self.conn.take().unwrap();
+        // The debugger asks for the reason of the initial stop itself.
+        if !matches!(reason, StopReason::Attached) {
+            conn.send(&stop_reply(reason))?;
+        }
+        let result = self.serve(&mut conn, &store, &frames, reason);
+        // The connection is closed once the debugger kills the guest.
+        if result.is_ok() {
+            self.conn = Some(conn);
+        }
+        result
+    }
+
+    fn serve(
+        &mut self,
+        conn: &mut Connection,
+        store: &impl AsContext,
+        frames: &[DebugFrame],
+        reason: StopReason,
+    ) -> Result<()> {
+        loop {
+            let packet = match conn.recv()? {
+                Some(packet) => packet,
+                None => {
+                    self.resume = Resume::Detached;
+                    return Ok(());
+                }
+            };
+            let (command, args) = split_command(&packet);
+            let reply = match command {
+                "?" => stop_reply(reason),
+                "qSupported" => {
+                    "PacketSize=4000;QStartNoAckMode+;qXfer:libraries:read+;vContSupported+"
+                        .to_string()
+                }
+                "QStartNoAckMode" => {
+                    conn.send("OK")?;
+                    conn.ack = false;
+                    continue;
+                }
+                "qHostInfo" => format!(
+                    "triple:{};ptrsize:4;endian:little;",
+                    hex(b"wasm32-unknown-unknown-wasm")
+                ),
+                "qProcessInfo" => format!(
+                    "pid:1;ppid:1;uid:0;gid:0;euid:0;egid:0;name:{};triple:{};ptrsize:4;endian:little;",
+                    hex(b"wasmtime"),
+                    hex(b"wasm32-unknown-unknown-wasm")
+                ),
+                "qRegisterInfo0" => "name:pc;alt-name:pc;bitsize:64;offset:0;encoding:uint;\
+                    format:hex;set:General Purpose Registers;gcc:16;dwarf:16;generic:pc;"
+                    .to_string(),
+                "qRegisterInfo1" => "E45".to_string(),
+                "qfThreadInfo" => "m1".to_string(),
+                "qsThreadInfo" => "l".to_string(),
+                "qC" => "QC1".to_string(),
+                "qAttached" => "1".to_string(),
+                "H" | "T" => "OK".to_string(),
+                "g" | "p0" => hex(&self.pc(frames, 0).unwrap_or(0).to_le_bytes()),
+                "qWasmCallStack" => {
+                    let mut pcs = Vec::new();
+                    for depth in 0..frames.len() {
+                        pcs.extend_from_slice(&self.pc(frames, depth).unwrap().to_le_bytes());
+                    }
+                    hex(&pcs)
+                }
+                "qWasmLocal" | "qWasmStackValue" | "qWasmGlobal" => {
+                    let value = parse_frame_index(args, frames).and_then(|(frame, index)| {
+                        let index = index.parse::<u32>().ok()?;
+                        match command {
+                            "qWasmLocal" => frame.locals().get(index as usize).copied(),
+                            "qWasmStackValue" => frame.stack().get(index as usize).copied(),
+                            _ => frame.global(store, index),
+                        }
+                    });
+                    match value {
+                        Some(value) => hex(&value.to_le_bytes()),
+                        None => "E03".to_string(),
+                    }
+                }
+                "qWasmMem" => {
+                    let memory = parse_frame_index(args, frames).and_then(|(frame, rest)| {
+                        let (addr, len) = rest.split_once(';')?;
+                        let addr = u64::from_str_radix(addr, 16).ok()?;
+                        let len = usize::from_str_radix(len, 16).ok()?;
+                        read_memory(frame, store, addr, len)
+                    });
+                    match memory {
+                        Some(bytes) => hex(bytes),
+                        None => "E03".to_string(),
+                    }
+                }
+                "m" => {
+                    let bytes = args.split_once(',').and_then(|(addr, len)| {
+                        let addr = u64::from_str_radix(addr, 16).ok()?;
+                        let len = usize::from_str_radix(len, 16).ok()?;
+                        self.read(frames.first(), store, addr, len)
+                    });
+                    match bytes {
+                        Some(bytes) => hex(bytes),
+                        None => "E03".to_string(),
+                    }
+                }
+                "qXfer" => {
+                    // The requested range may split a character of a module
+                    // name, so this reply is binary data.
+                    let reply = match args.strip_prefix("libraries:read::") {
+                        Some(range) => self.libraries(range),
+                        None => Vec::new(),
+                    };
+                    conn.send(&reply)?;
+                    continue;
+                }
+                "Z0" | "Z1" | "z0" | "z1" => {
+                    let addr = args
+                        .trim_start_matches(',')
+                        .split(',')
+                        .next()
+                        .and_then(|addr| u64::from_str_radix(addr, 16).ok());
+                    match addr {
+                        Some(addr) => {
+                            if command.starts_with('Z') {
+                                self.breakpoints.insert(addr);
+                            } else {
+                                self.breakpoints.remove(&addr);
+                            }
+                            "OK".to_string()
+                        }
+                        None => "E03".to_string(),
+                    }
+                }
+                "vCont?" => "vCont;c;C;s;S".to_string(),
+                "c" | "C" => return self.resume(Resume::Continue),
+                "s" | "S" => return self.resume(Resume::Step),
+                "vCont" => match args.trim_start_matches(';').chars().next() {
+                    Some('c') | Some('C') => return self.resume(Resume::Continue),
+                    Some('s') | Some('S') => return self.resume(Resume::Step),
+                    _ => "E03".to_string(),
+                },
+                "D" => {
+                    conn.send("OK")?;
+                    self.breakpoints.clear();
+                    return self.resume(Resume::Detached);
+                }
+                "k" => {
+                    self.resume = Resume::Detached;
+                    bail!("guest killed by the debugger")
+                }
+                _ => String::new(),
+            };
+            conn.send(&reply)?;
+        }
+    }
+
+    fn resume(&mut self, resume: Resume) -> Result<()> {
+        self.resume = resume;
+        self.hooks_until_poll = INTERRUPT_POLL_INTERVAL;
+        Ok(())
+    }
+
+    /// Returns the code address that the frame at `depth` is executing.
+    fn pc(&self, frames: &[DebugFrame], depth: usize) -> Option<u64> {
+        let frame = frames.get(depth)?;
+        let module = self
+            .modules
+            .iter()
+            .position(|m| Module::same(m, frame.module()))?;
+        Some(code_address(module, frame.module_offset()))
+    }
+
+    /// Reads from a code address, or from the default memory of the innermost
+    /// frame's instance for a memory address.
+    fn read<'a>(
+        &'a self,
+        frame: Option<&DebugFrame>,
+        store: &'a impl AsContext,
+        addr: u64,
+        len: usize,
+    ) -> Option<&'a [u8]> {
+        if addr >> ADDRESS_KIND_SHIFT == ADDRESS_KIND_CODE {
+            let module = ((addr >> 32) & ((1 << (ADDRESS_KIND_SHIFT - 32)) - 1)) as usize;
+            let binary = self.modules.get(module)?.compiled_module().wasm_binary()?;
+            let offset = (addr & 0xffff_ffff) as usize;
+            let bytes = binary.get(offset..)?;
+            Some(&bytes[..len.min(bytes.len())])
+        } else {
+            read_memory(frame?, store, addr, len)
+        }
+    }
+
+    /// Answers a `qXfer:libraries:read` request for the given `offset,length`
+    /// of the list of modules.
+    fn libraries(&self, range: &str) -> Vec<u8> {
+        let mut list = String::from("<library-list>");
+        for (id, module) in self.modules.iter().enumerate() {
+            let name = match module.name() {
+                Some(name) => format!("{name}.wasm"),
+                None => format!("module{id}.wasm"),
+            };
+            list.push_str(&format!(
+                "<library name=\"{}\"><section address=\"0x{:x}\"/></library>",
+                xml_escape(&name),
+                code_address(id, 0)
+            ));
+        }
+        list.push_str("</library-list>");
+
+        let (offset, length) = match range.split_once(',').and_then(|(offset, length)| {
+            Some((
+                usize::from_str_radix(offset, 16).ok()?,
+                usize::from_str_radix(length, 16).ok()?,
+            ))
+        }) {
+            Some(range) => range,
+            None => return b"E03".to_vec(),
+        };
+        let rest = list.as_bytes().get(offset..).unwrap_or(&[]);
+        let mut reply = Vec::with_capacity(rest.len().min(length) + 1);
+        if rest.len() > length {
+            reply.push(b'm');
+            reply.extend_from_slice(&rest[..length]);
+        } else {
+            reply.push(b'l');
+            reply.extend_from_slice(rest);
+        }
+        reply
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    /// Whether packets are acknowledged, until the debugger turns that off.
+    ack: bool,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Connection> {
+        stream.set_nodelay(true)?;
+        Ok(Connection { stream, ack: true })
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        let mut byte = [0];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Receives the next packet, or `None` if the debugger disconnected.
+    fn recv(&mut self) -> Result<Option<String>> {
+        loop {
+            // Skip acknowledgements and interrupt requests, which don't mean
+            // anything while the guest is stopped, until the next packet.
+            match self.read_byte()? {
+                Some(b'$') => {}
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+            let mut packet = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    Some(b'#') => break,
+                    Some(byte) => packet.push(byte),
+                    None => return Ok(None),
+                }
+            }
+            let mut checksum = [0; 2];
+            self.stream.read_exact(&mut checksum)?;
+            let expected = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|c| u8::from_str_radix(c, 16).ok());
+            let valid = expected == Some(checksum_of(&packet));
+            if self.ack {
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&packet).into_owned()));
+            }
+        }
+    }
+
+    fn send(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        let data = data.as_ref();
+        let mut escaped = Vec::with_capacity(data.len());
+        for &byte in data {
+            match byte {
+                b'$' | b'#' | b'}' | b'*' => escaped.extend_from_slice(&[b'}', byte ^ 0x20]),
+                _ => escaped.push(byte),
+            }
+        }
+        let mut packet = Vec::with_capacity(escaped.len() + 4);
+        packet.push(b'$');
+        packet.extend_from_slice(&escaped);
+        packet.extend_from_slice(format!("#{:02x}", checksum_of(&escaped)).as_bytes());
+        loop {
+            self.stream.write_all(&packet)?;
+            if !self.ack {
+                return Ok(());
+            }
+            match self.read_byte()? {
+                Some(b'-') => continue,
+                Some(_) => return Ok(()),
+                None => return Err(anyhow!("debugger disconnected")),
+            }
+        }
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn code_address(module: usize, offset: usize) -> u64 {
+    (ADDRESS_KIND_CODE << ADDRESS_KIND_SHIFT) | ((module as u64) << 32) | offset as u64
+}
+
+fn stop_reply(reason: StopReason) -> String {
+    match reason {
+        StopReason::Attached => "T05thread:1;".to_string(),
+        StopReason::Breakpoint => "T05thread:1;reason:breakpoint;".to_string(),
+        StopReason::Step => "T05thread:1;reason:trace;".to_string(),
+        StopReason::Interrupt => "T02thread:1;reason:signal;".to_string(),
+    }
+}
+
+/// Splits a packet into its command and its arguments.
+fn split_command(packet: &str) -> (&str, &str) {
+    // Named commands are separated from their arguments by the first `:` or
+    // `;`.
+    if packet.starts_with('q') || packet.starts_with('Q') || packet.starts_with('v') {
+        return match packet.find(|c| c == ':' || c == ';') {
+            Some(i) => {
+                let (command, args) = packet.split_at(i);
+                (command, args.strip_prefix(':').unwrap_or(args))
+            }
+            None => (packet, ""),
+        };
+    }
+    // Breakpoint packets are `Z<type>,<addr>,<kind>`, thread selection is
+    // `H<op><thread>` and reading the pc is `p0`.
+    if packet.starts_with('Z') || packet.starts_with('z') {
+        return packet.split_at(packet.len().min(2));
+    }
+    if packet == "p0" {
+        return (packet, "");
+    }
+    packet.split_at(packet.len().min(1))
+}
+
+/// Parses the `<frame>;<rest>` arguments of the `qWasm*` packets.
+fn parse_frame_index<'a, 'b>(
+    args: &'b str,
+    frames: &'a [DebugFrame],
+) -> Option<(&'a DebugFrame, &'b str)> {
+    let (frame, rest) = args.split_once(';')?;
+    let frame = frames.get(frame.parse::<usize>().ok()?)?;
+    Some((frame, rest))
+}
+
+fn read_memory<'a>(
+    frame: &DebugFrame,
+    store: &'a impl AsContext,
+    addr: u64,
+    len: usize,
+) -> Option<&'a [u8]> {
+    let memory = frame.memory(store, 0)?;
+    let bytes = memory.get(usize::try_from(addr).ok()?..)?;
+    Some(&bytes[..len.min(bytes.len())])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
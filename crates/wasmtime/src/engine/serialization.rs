@@ -21,7 +21,7 @@
 //! other random ELF files, as well as provide better error messages for
 //! using wasmtime artifacts across versions.
 
-use crate::{Engine, ModuleVersionStrategy};
+use crate::{Engine, ModuleVersionStrategy, Precompiled};
 use anyhow::{anyhow, bail, Context, Result};
 use object::write::{Object, StandardSegment};
 use object::{File, FileFlags, Object as _, ObjectSection, SectionKind};
@@ -65,6 +65,29 @@ pub fn append_compiler_info(engine: &Engine, obj: &mut Object<'_>) {
     obj.set_section_data(section, data, 1);
 }
 
+/// Classifies `bytes` as a precompiled module or component based on the ELF
+/// header written by `append_compiler_info` and friends, returning `None` if
+/// the bytes don't look like a Wasmtime-produced artifact at all.
+pub fn detect_precompiled(bytes: &[u8]) -> Option<Precompiled> {
+    if !bytes.starts_with(b"\x7fELF") {
+        return None;
+    }
+    let obj = File::parse(bytes).ok()?;
+    match obj.flags() {
+        FileFlags::Elf {
+            os_abi: obj::ELFOSABI_WASMTIME,
+            abi_version: 0,
+            e_flags: obj::EF_WASMTIME_MODULE,
+        } => Some(Precompiled::Module),
+        FileFlags::Elf {
+            os_abi: obj::ELFOSABI_WASMTIME,
+            abi_version: 0,
+            e_flags: obj::EF_WASMTIME_COMPONENT,
+        } => Some(Precompiled::Component),
+        _ => None,
+    }
+}
+
 /// Verifies that the serialized engine in `mmap` is compatible with the
 /// `engine` provided.
 ///
@@ -163,6 +186,7 @@ struct WasmFeatures {
     component_model: bool,
     simd: bool,
     threads: bool,
+    tail_call: bool,
     multi_memory: bool,
     exceptions: bool,
     memory64: bool,
@@ -197,7 +221,6 @@ impl Metadata {
         } = engine.config().features;
 
         assert!(!memory_control);
-        assert!(!tail_call);
         assert!(!function_references);
 
         Metadata {
@@ -212,6 +235,7 @@ impl Metadata {
                 component_model,
                 simd,
                 threads,
+                tail_call,
                 multi_memory,
                 exceptions,
                 memory64,
@@ -307,6 +331,9 @@ impl Metadata {
             parse_wasm_debuginfo,
             consume_fuel,
             epoch_interruption,
+            guest_debug,
+            memory_sanitizer,
+            block_profiling,
             static_memory_bound_is_maximum,
             guard_before_linear_memory,
             relaxed_simd_deterministic,
@@ -314,6 +341,11 @@ impl Metadata {
             // This doesn't affect compilation, it's just a runtime setting.
             dynamic_memory_growth_reserve: _,
 
+            // Inlining changes the compiled code of functions but not how
+            // they're called, so modules compiled with or without it can be
+            // loaded into engines with the opposite setting.
+            inlining: _,
+
             // This does technically affect compilation but modules with/without
             // trap information can be loaded into engines with the opposite
             // setting just fine (it's just a section in the compiled file and
@@ -355,6 +387,9 @@ impl Metadata {
             other.epoch_interruption,
             "epoch interruption",
         )?;
+        Self::check_bool(guest_debug, other.guest_debug, "guest debugging")?;
+        Self::check_bool(memory_sanitizer, other.memory_sanitizer, "memory sanitizer")?;
+        Self::check_bool(block_profiling, other.block_profiling, "block profiling")?;
         Self::check_bool(
             static_memory_bound_is_maximum,
             other.static_memory_bound_is_maximum,
@@ -382,6 +417,7 @@ impl Metadata {
             component_model,
             simd,
             threads,
+            tail_call,
             multi_memory,
             exceptions,
             memory64,
@@ -411,6 +447,7 @@ impl Metadata {
         )?;
         Self::check_bool(simd, other.simd, "WebAssembly SIMD support")?;
         Self::check_bool(threads, other.threads, "WebAssembly threads support")?;
+        Self::check_bool(tail_call, other.tail_call, "WebAssembly tail-call support")?;
         Self::check_bool(
             multi_memory,
             other.multi_memory,
@@ -346,7 +346,7 @@ impl Func {
     ///
     /// [`Trap`]: crate::Trap
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn new<T>(
         store: impl AsContextMut<Data = T>,
         ty: FuncType,
@@ -384,7 +384,7 @@ impl Func {
     /// the `func` provided correctly interprets the argument types provided to
     /// it, or that the results it produces will be of the correct type.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub unsafe fn new_unchecked<T>(
         mut store: impl AsContextMut<Data = T>,
         ty: FuncType,
@@ -463,8 +463,11 @@ impl Func {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(all(feature = "async", feature = "cranelift"))]
-    #[cfg_attr(nightlydoc, doc(cfg(all(feature = "async", feature = "cranelift"))))]
+    #[cfg(all(feature = "async", any(feature = "cranelift", feature = "winch")))]
+    #[cfg_attr(
+        nightlydoc,
+        doc(cfg(all(feature = "async", any(feature = "cranelift", feature = "winch"))))
+    )]
     pub fn new_async<T, F>(store: impl AsContextMut<Data = T>, ty: FuncType, func: F) -> Func
     where
         F: for<'a> Fn(
@@ -1303,15 +1306,26 @@ pub(crate) fn invoke_wasm_and_catch_traps<T>(
             exit_wasm(store, exit);
             return Err(trap);
         }
+        let debug_frame_depth = store.0.debug_frame_depth();
+        let allocator_depth = store.0.memory_sanitizer_mut().allocator_depth();
         let result = wasmtime_runtime::catch_traps(
             store.0.signal_handler(),
             store.0.engine().config().wasm_backtrace,
+            store.0.engine().config().features.tail_call,
             store.0.default_caller(),
             closure,
         );
+        // Traps are converted before forgetting about the unwound debug
+        // frames, since coredumps include the values saved in their records.
+        let result = result.map_err(|t| crate::trap::from_runtime_box(store.0, t));
+        store.0.truncate_debug_frames(debug_frame_depth);
+        store
+            .0
+            .memory_sanitizer_mut()
+            .truncate_allocator_calls(allocator_depth);
         exit_wasm(store, exit);
         store.0.call_hook(CallHook::ReturningFromWasm)?;
-        result.map_err(|t| crate::trap::from_runtime_box(store.0, t))
+        result
     }
 }
 
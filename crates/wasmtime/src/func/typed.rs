@@ -167,6 +167,20 @@ where
             }
         };
 
+        // When tail calls are enabled wasm functions pop their own stack
+        // arguments, which native code calling them directly doesn't expect,
+        // so the call goes through the function's compiled trampoline
+        // instead. There are at most 16 params and results, see
+        // `for_each_function_signature`.
+        if store.0.engine().config().features.tail_call {
+            let trampoline = store.0.lookup_trampoline(func.as_ref());
+            let mut values = [ValRaw::i32(0); 16];
+            Params::store_raw(params, values.as_mut_ptr());
+            Func::call_unchecked_raw(store, func, trampoline, values.as_mut_ptr())?;
+            let ret = Results::load_raw(values.as_mut_ptr());
+            return Ok(Results::from_abi(store.0, ret));
+        }
+
         // Try to capture only a single variable (a tuple) in the closure below.
         // This means the size of the closure is one pointer and is much more
         // efficient to move in memory. This closure is actually invoked on the
@@ -480,6 +494,9 @@ pub unsafe trait WasmParams: Send {
         vmctx2: *mut VMContext,
         abi: Self::Abi,
     ) -> R::ResultAbi;
+
+    #[doc(hidden)]
+    unsafe fn store_raw(abi: Self::Abi, raw: *mut ValRaw);
 }
 
 // Forward an impl from `T` to `(T,)` for convenience if there's only one
@@ -512,6 +529,10 @@ where
     ) -> R::ResultAbi {
         <(T,) as WasmParams>::invoke::<R>(func, vmctx1, vmctx2, abi)
     }
+
+    unsafe fn store_raw(abi: Self::Abi, raw: *mut ValRaw) {
+        <(T,) as WasmParams>::store_raw(abi, raw)
+    }
 }
 
 macro_rules! impl_wasm_params {
@@ -592,6 +613,15 @@ macro_rules! impl_wasm_params {
                     fnptr(vmctx1, vmctx2, $($t,)* retptr)
                 })
             }
+
+            unsafe fn store_raw(abi: Self::Abi, _raw: *mut ValRaw) {
+                let ($($t,)*) = abi;
+                let mut _i = 0;
+                $(
+                    $t::abi_into_raw($t, _raw.add(_i));
+                    _i += 1;
+                )*
+            }
         }
     };
 }
@@ -609,6 +639,8 @@ pub unsafe trait WasmResults: WasmParams {
     type ResultAbi: HostAbi;
     #[doc(hidden)]
     unsafe fn from_abi(store: &mut StoreOpaque, abi: Self::ResultAbi) -> Self;
+    #[doc(hidden)]
+    unsafe fn load_raw(raw: *mut ValRaw) -> Self::ResultAbi;
 }
 
 // Forwards from a bare type `T` to the 1-tuple type `(T,)`
@@ -621,6 +653,10 @@ where
     unsafe fn from_abi(store: &mut StoreOpaque, abi: Self::ResultAbi) -> Self {
         <(T,) as WasmResults>::from_abi(store, abi).0
     }
+
+    unsafe fn load_raw(raw: *mut ValRaw) -> Self::ResultAbi {
+        <(T,) as WasmResults>::load_raw(raw)
+    }
 }
 
 macro_rules! impl_wasm_results {
@@ -636,6 +672,15 @@ macro_rules! impl_wasm_results {
                 let ($($t,)*) = abi;
                 ($($t::from_abi($t, store),)*)
             }
+
+            unsafe fn load_raw(raw: *mut ValRaw) -> Self::ResultAbi {
+                let mut _i = 0;
+                ($({
+                    let abi = $t::abi_from_raw(raw.add(_i));
+                    _i += 1;
+                    abi
+                },)*)
+            }
         }
     };
 }
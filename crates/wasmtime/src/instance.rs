@@ -35,6 +35,8 @@ pub(crate) struct InstanceData {
     /// The id of the instance within the store, used to find the original
     /// `InstanceHandle`.
     id: InstanceId,
+    /// The module that this instance was instantiated from.
+    module: Module,
     /// A lazily-populated list of exports of this instance. The order of
     /// exports here matches the order of the exports in the the original
     /// module.
@@ -302,7 +304,11 @@ impl Instance {
         // those here.
         let instance = {
             let exports = vec![None; compiled_module.module().exports.len()];
-            let data = InstanceData { id, exports };
+            let data = InstanceData {
+                id,
+                module: module.clone(),
+                exports,
+            };
             Instance::from_wasmtime(data, store)
         };
 
@@ -332,6 +338,19 @@ impl Instance {
         Instance(store.store_data_mut().insert(handle))
     }
 
+    pub(crate) fn from_stored(stored: Stored<InstanceData>) -> Instance {
+        Instance(stored)
+    }
+
+    /// Returns the module that this instance was instantiated from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub(crate) fn module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
+        &store[self.0].module
+    }
+
     fn start_raw<T>(&self, store: &mut StoreContextMut<'_, T>, start: FuncIndex) -> Result<()> {
         let id = store.0.store_data()[self.0].id;
         // If a start function is present, invoke it. Make sure we use all the
@@ -522,7 +541,82 @@ impl Instance {
         self.get_export(store, name)?.into_global()
     }
 
-    #[cfg(feature = "component-model")]
+    /// Captures the current state of this instance into a new [`Module`].
+    ///
+    /// The returned module shares its compiled code with the module this
+    /// instance was created from, but instances of it start out with the
+    /// current contents of this instance's linear memories, globals and tables
+    /// rather than the ones described in the original wasm module. The start
+    /// function, if any, is not run again. This is similar to tools like
+    /// [Wizer]: a module's expensive initialization routine can be run once,
+    /// snapshotted, and then subsequent instantiations skip it entirely.
+    ///
+    /// Memory snapshots are stored as page-aligned images, so when
+    /// [copy-on-write initialization](crate::Config::memory_init_cow) is
+    /// enabled they're mapped into new instances lazily. The returned module
+    /// can additionally be saved with [`Module::serialize`] and loaded later
+    /// with [`Module::deserialize`].
+    ///
+    /// Note that state outside of this instance isn't captured, for example
+    /// host state in the [`Store`](crate::Store) or the contents of imported
+    /// globals.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the state of this instance can't be captured:
+    ///
+    /// * The module imports a memory or a table.
+    /// * The instance has a shared memory.
+    /// * A table or global contains a non-null `externref`, or a reference to
+    ///   a function which wasn't defined or imported by this instance.
+    /// * The module was defined within a component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(
+    ///     &engine,
+    ///     r#"
+    ///         (module
+    ///             (memory (export "memory") 1)
+    ///             (global $g (export "g") (mut i32) (i32.const 0))
+    ///             (func (export "init")
+    ///                 (i32.store (i32.const 100) (i32.const 42))
+    ///                 (global.set $g (i32.const 1)))
+    ///         )
+    ///     "#,
+    /// )?;
+    ///
+    /// // Run the initialization routine once and capture the result...
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Instance::new(&mut store, &module, &[])?;
+    /// let init = instance.get_typed_func::<(), ()>(&mut store, "init")?;
+    /// init.call(&mut store, ())?;
+    /// let snapshot = instance.snapshot(&mut store)?;
+    ///
+    /// // ... and new instances start out initialized.
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Instance::new(&mut store, &snapshot, &[])?;
+    /// let memory = instance.get_memory(&mut store, "memory").unwrap();
+    /// assert_eq!(memory.data(&store)[100], 42);
+    /// let g = instance.get_global(&mut store, "g").unwrap();
+    /// assert_eq!(g.get(&mut store).i32(), Some(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [Wizer]: https://github.com/bytecodealliance/wizer
+    pub fn snapshot(&self, mut store: impl AsContextMut) -> Result<Module> {
+        Module::from_snapshot(store.as_context_mut(), self)
+    }
+
     pub(crate) fn id(&self, store: &StoreOpaque) -> InstanceId {
         store[self.0].id
     }
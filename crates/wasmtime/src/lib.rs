@@ -390,8 +390,11 @@
 #[macro_use]
 mod func;
 
+mod block_profile;
 mod code;
 mod config;
+mod coredump;
+mod debug;
 mod engine;
 mod externals;
 mod instance;
@@ -399,6 +402,7 @@ mod limits;
 mod linker;
 mod memory;
 mod module;
+mod profiling;
 mod r#ref;
 mod signatures;
 mod store;
@@ -407,7 +411,10 @@ mod trap;
 mod types;
 mod values;
 
+pub use crate::block_profile::BlockProfile;
 pub use crate::config::*;
+pub use crate::coredump::*;
+pub use crate::debug::{DebugFrame, DebugLocation, DebugValue, GdbStub};
 pub use crate::engine::*;
 pub use crate::externals::*;
 pub use crate::func::*;
@@ -416,6 +423,7 @@ pub use crate::limits::*;
 pub use crate::linker::*;
 pub use crate::memory::*;
 pub use crate::module::Module;
+pub use crate::profiling::GuestProfiler;
 pub use crate::r#ref::ExternRef;
 #[cfg(feature = "async")]
 pub use crate::store::CallHookHandler;
@@ -2,20 +2,23 @@ use crate::code::CodeObject;
 use crate::{
     signatures::SignatureCollection,
     types::{ExportType, ExternType, ImportType},
-    Engine,
+    BlockProfile, Engine,
 };
 use anyhow::{bail, Context, Result};
 use once_cell::sync::OnceCell;
 use std::any::Any;
+use std::fmt;
 use std::fs;
 use std::mem;
 use std::ops::Range;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use wasmparser::{Parser, ValidPayload, Validator};
 use wasmtime_environ::{
-    DefinedFuncIndex, DefinedMemoryIndex, HostPtr, ModuleEnvironment, ModuleTranslation,
-    ModuleTypes, ObjectKind, PrimaryMap, VMOffsets, WasmFunctionInfo,
+    DefinedFuncIndex, DefinedMemoryIndex, FunctionBlockProfile, HostPtr, ModuleBlockProfile,
+    ModuleEnvironment, ModuleTranslation, ModuleTypes, ObjectKind, PrimaryMap, VMOffsets,
+    WasmFunctionInfo,
 };
 use wasmtime_jit::{CodeMemory, CompiledModule, CompiledModuleInfo};
 use wasmtime_runtime::{
@@ -24,6 +27,7 @@ use wasmtime_runtime::{
 };
 
 mod registry;
+mod snapshot;
 
 pub use registry::{is_wasm_trap_pc, register_code, unregister_code, ModuleRegistry};
 
@@ -127,6 +131,52 @@ struct ModuleInner {
 
     /// Runtime offset information for `VMContext`.
     offsets: VMOffsets<HostPtr>,
+
+    /// The block profiling counters of this module's functions, if it was
+    /// compiled with `Config::block_profiling`.
+    block_profile_counters: Option<BlockProfileCounters>,
+}
+
+/// The block profiling counters of the functions of a module, which are
+/// shared by all of its instances.
+struct BlockProfileCounters {
+    /// The counters of each defined function.
+    funcs: PrimaryMap<DefinedFuncIndex, Box<[AtomicU64]>>,
+    /// Pointers to the counters of each defined function, which is the table
+    /// that the `VMContext` of each instance points to.
+    table: Box<[*mut u64]>,
+}
+
+// Safety: the pointers in `table` point to `funcs`, which is only accessed
+// with atomics on the host, while compiled code increments the counters
+// without synchronization and may lose updates across threads.
+unsafe impl Send for BlockProfileCounters {}
+unsafe impl Sync for BlockProfileCounters {}
+
+impl BlockProfileCounters {
+    /// Allocates the counters described by the functions of `module`, or
+    /// returns `None` if it wasn't compiled with block profiling.
+    fn new(module: &CompiledModule) -> Option<BlockProfileCounters> {
+        let num_defined_funcs =
+            module.module().functions.len() - module.module().num_imported_funcs;
+        let funcs: PrimaryMap<DefinedFuncIndex, Box<[AtomicU64]>> = (0..num_defined_funcs)
+            .map(|i| {
+                let info = module.wasm_func_info(DefinedFuncIndex::from_u32(i as u32));
+                info.block_profile_counters
+                    .iter()
+                    .map(|_| AtomicU64::new(0))
+                    .collect()
+            })
+            .collect();
+        if funcs.values().all(|counters| counters.is_empty()) {
+            return None;
+        }
+        let table = funcs
+            .values()
+            .map(|counters| counters.as_ptr() as *mut u64)
+            .collect();
+        Some(BlockProfileCounters { funcs, table })
+    }
 }
 
 impl Module {
@@ -196,7 +246,7 @@ impl Module {
     /// # }
     /// ```
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn new(engine: &Engine, bytes: impl AsRef<[u8]>) -> Result<Module> {
         let bytes = bytes.as_ref();
         #[cfg(feature = "wat")]
@@ -233,7 +283,7 @@ impl Module {
     /// # }
     /// ```
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn from_file(engine: &Engine, file: impl AsRef<Path>) -> Result<Module> {
         match Self::new(
             engine,
@@ -286,15 +336,62 @@ impl Module {
     /// # }
     /// ```
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn from_binary(engine: &Engine, binary: &[u8]) -> Result<Module> {
+        Self::from_binary_with_block_profile(engine, binary, &ModuleBlockProfile::default())
+    }
+
+    /// Creates a new WebAssembly `Module` from the given in-memory `bytes`,
+    /// like [`Module::new`], using a block profile collected from a previous
+    /// run of the same module to guide the layout of its compiled code.
+    ///
+    /// Functions which the profile doesn't cover are compiled as usual. See
+    /// [`BlockProfile`] for how to collect a profile.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let wasm_bytes: Vec<u8> = Vec::new();
+    /// let mut config = Config::new();
+    /// config.block_profiling(true);
+    /// let engine = Engine::new(&config)?;
+    /// let module = Module::new(&engine, &wasm_bytes)?;
+    /// // ... run the module on a representative workload ...
+    /// let profile = module.block_profile().unwrap();
+    ///
+    /// let engine = Engine::default();
+    /// let module = Module::new_with_block_profile(&engine, &wasm_bytes, &profile)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(compiler)]
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
+    pub fn new_with_block_profile(
+        engine: &Engine,
+        bytes: impl AsRef<[u8]>,
+        profile: &BlockProfile,
+    ) -> Result<Module> {
+        let bytes = bytes.as_ref();
+        #[cfg(feature = "wat")]
+        let bytes = wat::parse_bytes(bytes)?;
+        Self::from_binary_with_block_profile(engine, &bytes, profile.inner())
+    }
+
+    #[cfg(compiler)]
+    fn from_binary_with_block_profile(
+        engine: &Engine,
+        binary: &[u8],
+        profile: &ModuleBlockProfile,
+    ) -> Result<Module> {
         engine
             .check_compatible_with_native_host()
             .context("compilation settings are not compatible with the native host")?;
 
         cfg_if::cfg_if! {
             if #[cfg(feature = "cache")] {
-                let state = (HashedEngineCompileEnv(engine), binary);
+                let state = (HashedEngineCompileEnv(engine), binary, profile);
                 let (code, info_and_types) = wasmtime_cache::ModuleCacheEntry::new(
                     "wasmtime",
                     engine.cache_config(),
@@ -303,25 +400,25 @@ impl Module {
                     &state,
 
                     // Cache miss, compute the actual artifacts
-                    |(engine, wasm)| -> Result<_> {
-                        let (mmap, info) = Module::build_artifacts(engine.0, wasm)?;
+                    |(engine, wasm, profile)| -> Result<_> {
+                        let (mmap, info) = Module::build_artifacts(engine.0, wasm, profile)?;
                         let code = publish_mmap(mmap)?;
                         Ok((code, info))
                     },
 
                     // Implementation of how to serialize artifacts
-                    |(_engine, _wasm), (code, _info_and_types)| {
+                    |(_engine, _wasm, _profile), (code, _info_and_types)| {
                         Some(code.mmap().to_vec())
                     },
 
                     // Cache hit, deserialize the provided artifacts
-                    |(engine, _wasm), serialized_bytes| {
+                    |(engine, _wasm, _profile), serialized_bytes| {
                         let code = engine.0.load_code_bytes(&serialized_bytes, ObjectKind::Module).ok()?;
                         Some((code, None))
                     },
                 )?;
             } else {
-                let (mmap, info_and_types) = Module::build_artifacts(engine, binary)?;
+                let (mmap, info_and_types) = Module::build_artifacts(engine, binary, profile)?;
                 let code = publish_mmap(mmap)?;
             }
         };
@@ -358,7 +455,7 @@ impl Module {
     /// reflect the current state of the file, not necessarily the origianl
     /// state of the file.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub unsafe fn from_trusted_file(engine: &Engine, file: impl AsRef<Path>) -> Result<Module> {
         let mmap = MmapVec::from_file(file.as_ref())?;
         if &mmap[0..4] == b"\x7fELF" {
@@ -381,10 +478,14 @@ impl Module {
     /// Additionally compilation returns an `Option` here which is always
     /// `Some`, notably compiled metadata about the module in addition to the
     /// type information found within.
+    ///
+    /// The code of the module's functions is laid out according to
+    /// `profile`, which is empty if no block profile is used.
     #[cfg(compiler)]
     pub(crate) fn build_artifacts(
         engine: &Engine,
         wasm: &[u8],
+        profile: &ModuleBlockProfile,
     ) -> Result<(MmapVec, Option<(CompiledModuleInfo, ModuleTypes)>)> {
         let tunables = &engine.config().tunables;
         let compiler = engine.compiler();
@@ -401,6 +502,7 @@ impl Module {
             .translate(parser, wasm)
             .context("failed to parse WebAssembly module")?;
         let types = types.finish();
+        translation.block_profile = profile.clone();
 
         // Afterwards compile all functions and trampolines required by the
         // module.
@@ -505,19 +607,44 @@ impl Module {
         let functions = mem::take(&mut translation.function_body_inputs);
         let functions = functions.into_iter().collect::<Vec<_>>();
         let compiler = engine.compiler();
-        let funcs = engine.run_maybe_parallel(functions, |(index, func)| {
-            let offset = func.body.range().start;
-            let result = compiler.compile_function(&translation, index, func, tunables, types);
-            result.with_context(|| {
-                let index = translation.module.func_index(index);
-                let name = match translation.debuginfo.name_section.func_names.get(&index) {
-                    Some(name) => format!(" (`{}`)", name),
-                    None => String::new(),
-                };
-                let index = index.as_u32();
-                format!("failed to compile wasm function {index}{name} at offset {offset:#x}")
-            })
-        })?;
+        let context = |index: DefinedFuncIndex, offset: usize| {
+            let index = translation.module.func_index(index);
+            let name = match translation.debuginfo.name_section.func_names.get(&index) {
+                Some(name) => format!(" (`{}`)", name),
+                None => String::new(),
+            };
+            let index = index.as_u32();
+            format!("failed to compile wasm function {index}{name} at offset {offset:#x}")
+        };
+        let funcs = if tunables.inlining {
+            // Translate all the functions first, so that each function can
+            // then be compiled with the bodies of its callees inlined.
+            let offsets = functions
+                .iter()
+                .map(|(_, func)| func.body.range().start)
+                .collect::<PrimaryMap<DefinedFuncIndex, _>>();
+            let translated = engine.run_maybe_parallel(functions, |(index, func)| {
+                compiler
+                    .translate_function(&translation, index, func, tunables, types)
+                    .with_context(|| context(index, offsets[index]))
+            })?;
+            let translated = translated
+                .into_iter()
+                .collect::<PrimaryMap<DefinedFuncIndex, _>>();
+            let indices = translated.keys().collect::<Vec<_>>();
+            engine.run_maybe_parallel(indices, |index| {
+                compiler
+                    .compile_translated_function(&translation, index, &translated, tunables)
+                    .with_context(|| context(index, offsets[index]))
+            })?
+        } else {
+            engine.run_maybe_parallel(functions, |(index, func)| {
+                let offset = func.body.range().start;
+                compiler
+                    .compile_function(&translation, index, func, tunables, types)
+                    .with_context(|| context(index, offset))
+            })?
+        };
 
         // If configured attempt to use static memory initialization which
         // can either at runtime be implemented as a single memcpy to
@@ -671,6 +798,8 @@ impl Module {
         let offsets = VMOffsets::new(HostPtr, module.module());
         engine.allocator().validate(module.module(), &offsets)?;
 
+        let block_profile_counters = BlockProfileCounters::new(&module);
+
         Ok(Self {
             inner: Arc::new(ModuleInner {
                 engine: engine.clone(),
@@ -679,6 +808,7 @@ impl Module {
                 module,
                 serializable,
                 offsets,
+                block_profile_counters,
             }),
         })
     }
@@ -738,7 +868,7 @@ impl Module {
     /// this method can be useful to get the serialized version without
     /// compiling twice.
     #[cfg(compiler)]
-    #[cfg_attr(nightlydoc, doc(cfg(feature = "cranelift")))] // see build.rs
+    #[cfg_attr(nightlydoc, doc(cfg(any(feature = "cranelift", feature = "winch"))))] // see build.rs
     pub fn serialize(&self) -> Result<Vec<u8>> {
         // The current representation of compiled modules within a compiled
         // component means that it cannot be serialized. The mmap returned here
@@ -775,6 +905,11 @@ impl Module {
         self.compiled_module().module()
     }
 
+    /// Returns whether `a` and `b` refer to the same compiled module.
+    pub(crate) fn same(a: &Module, b: &Module) -> bool {
+        Arc::ptr_eq(&a.inner, &b.inner)
+    }
+
     pub(crate) fn types(&self) -> &ModuleTypes {
         self.inner.code.module_types()
     }
@@ -991,6 +1126,28 @@ impl Module {
         &self.inner.engine
     }
 
+    /// Returns the block profile collected so far by all instances of this
+    /// module, or `None` if it wasn't compiled with
+    /// [`Config::block_profiling`](crate::Config::block_profiling) enabled.
+    ///
+    /// Only functions which have run are part of the profile.
+    pub fn block_profile(&self) -> Option<BlockProfile> {
+        let counters = self.inner.block_profile_counters.as_ref()?;
+        let mut profile = ModuleBlockProfile::default();
+        for (index, values) in counters.funcs.iter() {
+            let values: Vec<u64> = values.iter().map(|v| v.load(Ordering::Relaxed)).collect();
+            if values.iter().all(|v| *v == 0) {
+                continue;
+            }
+            let info = self.inner.module.wasm_func_info(index);
+            profile.funcs.insert(
+                index,
+                FunctionBlockProfile::from_counters(&info.block_profile_counters, &values),
+            );
+        }
+        Some(BlockProfile::from_inner(profile))
+    }
+
     /// Returns the `ModuleInner` cast as `ModuleRuntimeInfo` for use
     /// by the runtime.
     pub(crate) fn runtime_info(&self) -> Arc<dyn wasmtime_runtime::ModuleRuntimeInfo> {
@@ -1111,6 +1268,14 @@ impl Module {
     }
 }
 
+impl fmt::Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Module")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
 impl ModuleInner {
     fn memory_images(&self) -> Result<Option<&ModuleMemoryImages>> {
         let images = self
@@ -1139,13 +1304,13 @@ fn _assert_send_sync() {
 }
 
 /// This is a helper struct used when caching to hash the state of an `Engine`
-/// used for module compilation.
+/// used for module and component compilation.
 ///
 /// The hash computed for this structure is used to key the global wasmtime
 /// cache and dictates whether artifacts are reused. Consequently the contents
 /// of this hash dictate when artifacts are or aren't re-used.
 #[cfg(all(feature = "cache", compiler))]
-struct HashedEngineCompileEnv<'a>(&'a Engine);
+pub(crate) struct HashedEngineCompileEnv<'a>(pub(crate) &'a Engine);
 
 #[cfg(all(feature = "cache", compiler))]
 impl std::hash::Hash for HashedEngineCompileEnv<'_> {
@@ -1196,6 +1361,13 @@ impl wasmtime_runtime::ModuleRuntimeInfo for ModuleInner {
         self.code.signatures().as_module_map().values().as_slice()
     }
 
+    fn block_profile_counters(&self) -> *const *mut u64 {
+        match &self.block_profile_counters {
+            Some(counters) => counters.table.as_ptr(),
+            None => std::ptr::null(),
+        }
+    }
+
     fn offsets(&self) -> &VMOffsets<HostPtr> {
         &self.offsets
     }
@@ -1286,6 +1458,10 @@ impl wasmtime_runtime::ModuleRuntimeInfo for BareModuleInfo {
         }
     }
 
+    fn block_profile_counters(&self) -> *const *mut u64 {
+        std::ptr::null()
+    }
+
     fn offsets(&self) -> &VMOffsets<HostPtr> {
         &self.offsets
     }
@@ -57,7 +57,9 @@ impl ModuleRegistry {
         Some((code, pc - *start))
     }
 
-    fn module(&self, pc: usize) -> Option<(&Module, usize)> {
+    /// Fetches the module containing `pc` and the offset of `pc` within its
+    /// code.
+    pub(crate) fn module(&self, pc: usize) -> Option<(&Module, usize)> {
         let (code, offset) = self.code(pc)?;
         Some((code.module(pc)?, offset))
     }
@@ -138,16 +140,17 @@ impl ModuleRegistry {
 
     /// Fetches frame information about a program counter in a backtrace.
     ///
-    /// Returns an object if this `pc` is known to some previously registered
-    /// module, or returns `None` if no information can be found. The first
-    /// boolean returned indicates whether the original module has unparsed
-    /// debug information due to the compiler's configuration. The second
-    /// boolean indicates whether the engine used to compile this module is
-    /// using environment variables to control debuginfo parsing.
-    pub(crate) fn lookup_frame_info(&self, pc: usize) -> Option<(FrameInfo, &Module)> {
+    /// Returns the frames for this `pc`, innermost first, if it's known to some
+    /// previously registered module, or returns `None` if no information can
+    /// be found. The first boolean returned indicates whether the original
+    /// module has unparsed debug information due to the compiler's
+    /// configuration. The second boolean indicates whether the engine used to
+    /// compile this module is using environment variables to control debuginfo
+    /// parsing.
+    pub(crate) fn lookup_frame_info(&self, pc: usize) -> Option<(Vec<FrameInfo>, &Module)> {
         let (module, offset) = self.module(pc)?;
-        let info = FrameInfo::new(module, offset)?;
-        Some((info, module))
+        let frames = FrameInfo::new(module, offset)?;
+        Some((frames, module))
     }
 }
 
@@ -285,12 +288,13 @@ fn test_frame_info() -> Result<(), anyhow::Error> {
             (ptr as usize, ptr as usize + len)
         };
         for pc in start..end {
-            let (frame, _) = store
+            let (frames, _) = store
                 .as_context()
                 .0
                 .modules()
                 .lookup_frame_info(pc)
                 .unwrap();
+            let frame = frames.last().unwrap();
             assert!(
                 frame.func_index() == i.as_u32(),
                 "lookup of {:#x} returned {}, expected {}",
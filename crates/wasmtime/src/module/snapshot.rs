@@ -0,0 +1,216 @@
+//! Creation of new modules from the state of a live instance, see
+//! `Instance::snapshot` for more information.
+
+use crate::store::{InstanceId, StoreOpaque};
+use crate::{Extern, Instance, Module, StoreContextMut, Val};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use wasmtime_environ::{
+    packed_option::ReservedValue, EntityIndex, FuncIndex, GlobalInit, MemoryInitialization,
+    ModuleTypes, PrimaryMap, StaticMemoryInitializer, TableInitialization,
+};
+use wasmtime_jit::{CodeMemory, CompiledModuleInfo, ObjectBuilder};
+use wasmtime_runtime::TableElement;
+
+impl Module {
+    /// Creates a new `Module` whose instances start out with the current state
+    /// of `instance`.
+    ///
+    /// The new module reuses the compiled code of the original module and only
+    /// its initializers are replaced:
+    ///
+    /// * Each linear memory is initialized with a static image of its current
+    ///   contents, which is then eligible for copy-on-write initialization.
+    /// * Each global is initialized with a constant of its current value.
+    /// * Each table is initialized with its current list of functions.
+    /// * Passive segments dropped by `instance` are dropped in the new module.
+    /// * The start function is removed as it has already been executed.
+    pub(crate) fn from_snapshot<T>(
+        mut store: StoreContextMut<'_, T>,
+        instance: &Instance,
+    ) -> Result<Module> {
+        let module = instance.module(store.0).clone();
+        if !module.inner.serializable {
+            bail!("cannot snapshot an instance of a module defined in a component");
+        }
+        let env = module.env_module();
+        if env.num_imported_memories > 0 {
+            bail!("cannot snapshot an instance of a module which imports memories");
+        }
+        if env.num_imported_tables > 0 {
+            bail!("cannot snapshot an instance of a module which imports tables");
+        }
+
+        // Start from a fresh copy of the compilation metadata stored in the
+        // original artifact, then update all initializers within it to reflect
+        // the current state of the instance.
+        let code_memory = module.compiled_module().code_memory();
+        let (mut info, types): (CompiledModuleInfo, ModuleTypes) =
+            bincode::deserialize(code_memory.wasmtime_info())
+                .context("failed to deserialize module metadata")?;
+        let id = instance.id(store.0);
+
+        // Build a map of all functions that may be referenced from tables and
+        // globals back to their index in the module. Functions from other
+        // instances, or host functions, can't be represented in a snapshot.
+        let mut funcs = HashMap::new();
+        for (index, func) in env.functions.iter() {
+            if func.is_escaping() {
+                let anyfunc = store.0.instance_mut(id).get_exported_func(index).anyfunc;
+                funcs.insert(anyfunc.as_ptr() as usize, index);
+            }
+        }
+        let func_index = |anyfunc: usize| -> Result<FuncIndex> {
+            funcs.get(&anyfunc).copied().ok_or_else(|| {
+                anyhow!("cannot snapshot a reference to a function not defined by this instance")
+            })
+        };
+
+        // First up build the new contents of the wasm data section. Each
+        // memory gets one image of its non-zero contents, page-aligned to be
+        // mmap-able, followed by the still-live passive data segments.
+        let page_size = u64::try_from(wasmtime_runtime::page_size()).unwrap();
+        let mut data = Vec::new();
+        let mut map = PrimaryMap::with_capacity(env.memory_plans.len());
+        for index in env.memory_plans.keys() {
+            let memory = match export(store.0, id, EntityIndex::Memory(index)) {
+                Extern::Memory(memory) => memory,
+                Extern::SharedMemory(_) => {
+                    bail!("cannot snapshot an instance with a shared memory")
+                }
+                _ => unreachable!(),
+            };
+            info.module_mut().memory_plans[index].memory.minimum = memory.size(&store);
+            let contents = memory.data(&store);
+
+            let (start, end) = match (
+                contents.iter().position(|b| *b != 0),
+                contents.iter().rposition(|b| *b != 0),
+            ) {
+                (Some(start), Some(end)) => (start as u64, end as u64 + 1),
+                _ => {
+                    map.push(None);
+                    continue;
+                }
+            };
+            let start = start - start % page_size;
+            let len = (end - start + page_size - 1) / page_size * page_size;
+            let offset = data.len();
+            let image = &contents[start as usize..end as usize];
+            data.extend_from_slice(image);
+            data.resize(offset + len as usize, 0);
+            map.push(Some(StaticMemoryInitializer {
+                offset: start,
+                data: data_range(offset, data.len())?,
+            }));
+        }
+
+        let wasm_data = code_memory.wasm_data();
+        let handle = store.0.instance(id);
+        let mut passive_data_map = env.passive_data_map.clone();
+        passive_data_map.retain(|index, _| !handle.is_data_dropped(*index));
+        for range in passive_data_map.values_mut() {
+            let offset = data.len();
+            data.extend_from_slice(&wasm_data[range.start as usize..range.end as usize]);
+            *range = data_range(offset, data.len())?;
+        }
+        let mut passive_elements_map = env.passive_elements_map.clone();
+        passive_elements_map.retain(|index, _| !handle.is_elem_dropped(*index));
+
+        // Next the globals of the instance are recorded as constants.
+        for index in env.globals.keys() {
+            let def_index = match env.defined_global_index(index) {
+                Some(i) => i,
+                None => continue,
+            };
+            let global = match export(store.0, id, EntityIndex::Global(index)) {
+                Extern::Global(g) => g,
+                _ => unreachable!(),
+            };
+            let init = match global.get(&mut store) {
+                Val::I32(i) => GlobalInit::I32Const(i),
+                Val::I64(i) => GlobalInit::I64Const(i),
+                Val::F32(f) => GlobalInit::F32Const(f),
+                Val::F64(f) => GlobalInit::F64Const(f),
+                Val::V128(v) => GlobalInit::V128Const(v),
+                Val::FuncRef(None) | Val::ExternRef(None) => GlobalInit::RefNullConst,
+                Val::FuncRef(Some(f)) => GlobalInit::RefFunc(func_index(
+                    f.caller_checked_anyfunc(store.0).as_ptr() as usize,
+                )?),
+                Val::ExternRef(Some(_)) => {
+                    bail!(
+                        "cannot snapshot global {} containing an `externref`",
+                        def_index.as_u32()
+                    )
+                }
+            };
+            info.module_mut().globals[index].initializer = init;
+        }
+
+        // And finally tables are recorded as a list of functions per table
+        // which is lazily initialized when the new module is instantiated.
+        let mut tables = PrimaryMap::with_capacity(env.table_plans.len());
+        for index in env.table_plans.keys() {
+            // Force lazy initialization of the whole table to read its
+            // contents, the iteration here stops at the end of the table.
+            let def_index = env.defined_table_index(index).unwrap();
+            let table = store
+                .0
+                .instance_mut(id)
+                .get_defined_table_with_lazy_init(def_index, 0..);
+            let table = unsafe { &*table };
+            let size = table.size();
+            let mut elements = Vec::new();
+            for i in 0..size {
+                let anyfunc = match table.get(i).unwrap() {
+                    TableElement::FuncRef(anyfunc) if !anyfunc.is_null() => anyfunc,
+                    TableElement::FuncRef(_) | TableElement::ExternRef(None) => continue,
+                    TableElement::ExternRef(Some(_)) => bail!(
+                        "cannot snapshot table {} containing an `externref`",
+                        def_index.as_u32()
+                    ),
+                    TableElement::UninitFunc => unreachable!(),
+                };
+                elements.resize(i as usize, FuncIndex::reserved_value());
+                elements.push(func_index(anyfunc as usize)?);
+            }
+            tables.push(elements);
+            info.module_mut().table_plans[index].table.minimum = size;
+        }
+
+        let env = info.module_mut();
+        env.start_func = None;
+        env.memory_initialization = MemoryInitialization::Static { map };
+        env.table_initialization = TableInitialization::FuncTable {
+            tables,
+            segments: Vec::new(),
+        };
+        env.passive_data_map = passive_data_map;
+        env.passive_elements_map = passive_elements_map;
+
+        // With all the new metadata in place the new artifact is created from
+        // the original one, and loaded as a module like any other.
+        let engine = module.engine();
+        let mut obj = ObjectBuilder::from_artifact(code_memory.mmap(), &engine.config().tunables)?;
+        let offset = obj.append_data(&data, page_size);
+        assert_eq!(offset, 0);
+        obj.serialize_info(&(&info, &types));
+        let mut code = CodeMemory::new(obj.finish()?)?;
+        code.publish()?;
+        Module::from_parts(engine, Arc::new(code), Some((info, types)))
+    }
+}
+
+fn export(store: &mut StoreOpaque, id: InstanceId, index: EntityIndex) -> Extern {
+    let export = store.instance_mut(id).get_export_by_index(index);
+    unsafe { Extern::from_wasmtime_export(export, store) }
+}
+
+fn data_range(start: usize, end: usize) -> Result<Range<u32>> {
+    match (u32::try_from(start), u32::try_from(end)) {
+        (Ok(start), Ok(end)) => Ok(start..end),
+        _ => bail!("snapshot data is too large (> 4gb)"),
+    }
+}
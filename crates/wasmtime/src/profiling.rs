@@ -0,0 +1,172 @@
+use crate::{AsContext, FrameInfo, WasmBacktrace};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use wasmtime_jit::{demangle_function_name, demangle_function_name_or_index};
+
+/// A sampling profiler for the WebAssembly code executing within a store.
+///
+/// Unlike the profilers selected with [`Config::profiler`], which describe
+/// the compiled code to external native tools, this profiler is built into
+/// Wasmtime. Each call to [`GuestProfiler::sample`] walks the WebAssembly
+/// frames currently on the stack of a store, the same way a
+/// [`WasmBacktrace`] is captured, and attributes the sample to the functions
+/// in those frames. When the modules were compiled with DWARF debugging
+/// information and [`Config::wasm_backtrace_details`] is enabled the samples
+/// are further attributed to source functions, including inlined ones, and
+/// their lines.
+///
+/// Samples are typically taken at regular intervals by enabling
+/// [`Config::epoch_interruption`], incrementing the epoch from a timer
+/// thread, and sampling in a [`Store::epoch_deadline_callback`]. Once
+/// execution has finished the profile is written in the collapsed stack
+/// format with [`GuestProfiler::finish`], which can be turned into a flame
+/// graph by tools such as [inferno] or loaded into [speedscope].
+///
+/// [`Config::profiler`]: crate::Config::profiler
+/// [`Config::wasm_backtrace_details`]: crate::Config::wasm_backtrace_details
+/// [`Config::epoch_interruption`]: crate::Config::epoch_interruption
+/// [`Store::epoch_deadline_callback`]: crate::Store::epoch_deadline_callback
+/// [inferno]: https://github.com/jonhoo/inferno
+/// [speedscope]: https://www.speedscope.app
+///
+/// # Examples
+///
+/// ```
+/// # use wasmtime::*;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut config = Config::new();
+/// config.epoch_interruption(true);
+/// let engine = Engine::new(&config)?;
+/// let module = Module::new(&engine, r#"(module (func (export "run") (loop br 0)))"#)?;
+///
+/// // Take a sample on every epoch tick, and stop after ten of them.
+/// let mut store = Store::new(&engine, GuestProfiler::new());
+/// store.set_epoch_deadline(1);
+/// store.epoch_deadline_callback(|mut store| {
+///     let mut profiler = std::mem::take(store.data_mut());
+///     profiler.sample(&store);
+///     *store.data_mut() = profiler;
+///     if store.data().samples() == 10 {
+///         anyhow::bail!("done");
+///     }
+///     Ok(1)
+/// });
+/// let ticker = engine.clone();
+/// std::thread::spawn(move || loop {
+///     std::thread::sleep(std::time::Duration::from_millis(1));
+///     ticker.increment_epoch();
+/// });
+///
+/// let instance = Instance::new(&mut store, &module, &[])?;
+/// let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+/// assert!(run.call(&mut store, ()).is_err());
+///
+/// let mut profile = Vec::new();
+/// store.data().finish(&mut profile)?;
+/// assert_eq!(
+///     String::from_utf8(profile)?,
+///     "<unknown>!<wasm function 0> 10\n",
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GuestProfiler {
+    /// The number of samples of each distinct stack, where the key is the
+    /// stack in the collapsed format: the name of each frame, outermost
+    /// first, separated by `;`.
+    stacks: BTreeMap<String, u64>,
+}
+
+impl GuestProfiler {
+    /// Creates a new profiler without any samples.
+    pub fn new() -> GuestProfiler {
+        GuestProfiler::default()
+    }
+
+    /// Takes a sample of the WebAssembly frames currently on the stack of
+    /// `store`.
+    ///
+    /// This is intended to be called while WebAssembly is executing, for
+    /// example from an epoch deadline callback or from a host function.
+    /// Samples are taken regardless of the [`Config::wasm_backtrace`]
+    /// setting, and a sample without any WebAssembly frames on the stack is
+    /// ignored.
+    ///
+    /// [`Config::wasm_backtrace`]: crate::Config::wasm_backtrace
+    pub fn sample(&mut self, store: impl AsContext) {
+        let backtrace = WasmBacktrace::force_capture(store);
+        let mut stack = String::new();
+        for frame in backtrace.frames().iter().rev() {
+            push_frame(&mut stack, frame);
+        }
+        if !stack.is_empty() {
+            *self.stacks.entry(stack).or_insert(0) += 1;
+        }
+    }
+
+    /// Returns the number of samples taken so far.
+    pub fn samples(&self) -> u64 {
+        self.stacks.values().sum()
+    }
+
+    /// Writes the profile to `output` in the collapsed stack format.
+    ///
+    /// Each line of the output is one distinct stack that was sampled: the
+    /// frames from outermost to innermost separated by `;`, followed by a
+    /// space and the number of samples of that stack.
+    pub fn finish(&self, mut output: impl Write) -> io::Result<()> {
+        for (stack, count) in &self.stacks {
+            writeln!(output, "{stack} {count}")?;
+        }
+        output.flush()
+    }
+}
+
+/// Appends the names of `frame` to the collapsed `stack`, which are multiple
+/// names if `frame` has inlined functions in its debugging information.
+fn push_frame(stack: &mut String, frame: &FrameInfo) {
+    let mut raw_name = format!("{}!", frame.module_name().unwrap_or("<unknown>"));
+    demangle_function_name_or_index(
+        &mut raw_name,
+        frame.func_name(),
+        frame.func_index() as usize,
+    )
+    .unwrap();
+    if frame.symbols().is_empty() {
+        push_name(stack, &raw_name);
+        return;
+    }
+
+    // Like frames themselves the symbols of a frame are listed innermost
+    // first, so the outermost function of the frame is pushed first.
+    for symbol in frame.symbols().iter().rev() {
+        let mut name = String::new();
+        match symbol.name() {
+            Some(symbol) => demangle_function_name(&mut name, symbol).unwrap(),
+            None => name.push_str(&raw_name),
+        }
+        if let Some(file) = symbol.file() {
+            name.push_str(&format!(" ({file}"));
+            if let Some(line) = symbol.line() {
+                name.push_str(&format!(":{line}"));
+            }
+            name.push(')');
+        }
+        push_name(stack, &name);
+    }
+}
+
+fn push_name(stack: &mut String, name: &str) {
+    if !stack.is_empty() {
+        stack.push(';');
+    }
+    // `;` separates frames and a newline separates stacks, so neither may
+    // appear within a frame's name.
+    stack.extend(name.chars().map(|c| match c {
+        ';' => ':',
+        '\n' => ' ',
+        c => c,
+    }));
+}
@@ -76,9 +76,10 @@
 //! contents of `StoreOpaque`. This is an invariant that we, as the authors of
 //! `wasmtime`, must uphold for the public interface to be safe.
 
+use crate::debug::{DebugFrame, DebugFrameRecord, DebugLocation};
 use crate::linker::Definition;
 use crate::module::BareModuleInfo;
-use crate::{module::ModuleRegistry, Engine, Module, Trap, Val, ValRaw};
+use crate::{module::ModuleRegistry, Engine, Global, Instance, Memory, Module, Trap, Val, ValRaw};
 use anyhow::{anyhow, bail, Result};
 use std::cell::UnsafeCell;
 use std::collections::HashMap;
@@ -94,7 +95,7 @@ use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use wasmtime_runtime::{
-    InstanceAllocationRequest, InstanceAllocator, InstanceHandle, ModuleInfo,
+    InstanceAllocationRequest, InstanceAllocator, InstanceHandle, MemorySanitizer, ModuleInfo,
     OnDemandInstanceAllocator, SignalHandler, StorePtr, VMCallerCheckedFuncRef, VMContext,
     VMExternRef, VMExternRefActivationsTable, VMRuntimeLimits, VMSharedSignatureIndex,
     VMTrampoline, WasmFault,
@@ -203,6 +204,7 @@ pub struct StoreInner<T> {
     limiter: Option<ResourceLimiterInner<T>>,
     call_hook: Option<CallHookInner<T>>,
     epoch_deadline_behavior: EpochDeadline<T>,
+    debug_hook: Option<DebugHookInner<T>>,
     // for comments about `ManuallyDrop`, see `Store::into_data`
     data: ManuallyDrop<T>,
 }
@@ -222,6 +224,9 @@ pub trait CallHookHandler<T>: Send {
     async fn handle_call_event(&self, t: &mut T, ch: CallHook) -> Result<()>;
 }
 
+type DebugHookInner<T> =
+    Box<dyn FnMut(StoreContextMut<'_, T>, DebugLocation) -> Result<()> + Send + Sync>;
+
 enum CallHookInner<T> {
     Sync(Box<dyn FnMut(&mut T, CallHook) -> Result<()> + Send + Sync>),
     #[cfg(feature = "async")]
@@ -327,6 +332,13 @@ pub struct StoreOpaque {
     /// Note that this is `ManuallyDrop` as it must be dropped after
     /// `store_data` above, where the function pointers are stored.
     rooted_host_funcs: ManuallyDrop<Vec<Arc<[Definition]>>>,
+
+    /// The frames of functions compiled with guest debugging that are
+    /// currently on the stack, from the outermost to the innermost.
+    debug_frames: Vec<DebugFrameRecord>,
+
+    /// The heap state tracked for code compiled with the memory sanitizer.
+    memory_sanitizer: MemorySanitizer,
 }
 
 #[cfg(feature = "async")]
@@ -498,10 +510,13 @@ impl<T> Store<T> {
                 hostcall_val_storage: Vec::new(),
                 wasm_val_raw_storage: Vec::new(),
                 rooted_host_funcs: ManuallyDrop::new(Vec::new()),
+                debug_frames: Vec::new(),
+                memory_sanitizer: MemorySanitizer::default(),
             },
             limiter: None,
             call_hook: None,
             epoch_deadline_behavior: EpochDeadline::Trap,
+            debug_hook: None,
             data: ManuallyDrop::new(data),
         });
 
@@ -728,6 +743,34 @@ impl<T> Store<T> {
         self.inner.call_hook = Some(CallHookInner::Sync(Box::new(hook)));
     }
 
+    /// Configures a function that runs before each WebAssembly instruction
+    /// which is executed by code compiled with
+    /// [`Config::guest_debug`](crate::Config::guest_debug) enabled.
+    ///
+    /// The function is passed the location of the instruction about to be
+    /// executed, and may inspect the state of the guest with
+    /// [`StoreContextMut::debug_frames`]. It's how debuggers implement
+    /// breakpoints and single-stepping, for example by blocking until the
+    /// user resumes execution. If the function returns an error it's raised
+    /// as a trap.
+    pub fn debug_hook(
+        &mut self,
+        hook: impl FnMut(StoreContextMut<'_, T>, DebugLocation) -> Result<()> + Send + Sync + 'static,
+    ) {
+        self.inner.debug_hook = Some(Box::new(hook));
+    }
+
+    /// Returns the WebAssembly frames on the stack of this store which are
+    /// executing code compiled with
+    /// [`Config::guest_debug`](crate::Config::guest_debug) enabled, starting
+    /// with the innermost one.
+    ///
+    /// This is empty when no such code is executing. Frames of code compiled
+    /// without guest debugging aren't included.
+    pub fn debug_frames(&self) -> Vec<DebugFrame> {
+        self.inner.debug_frames()
+    }
+
     /// Returns the [`Engine`] that this store is associated with.
     pub fn engine(&self) -> &Engine {
         self.inner.engine()
@@ -992,6 +1035,11 @@ impl<'a, T> StoreContext<'a, T> {
     pub fn fuel_consumed(&self) -> Option<u64> {
         self.0.fuel_consumed()
     }
+
+    /// Same as [`Store::debug_frames`].
+    pub fn debug_frames(&self) -> Vec<DebugFrame> {
+        self.0.debug_frames()
+    }
 }
 
 impl<'a, T> StoreContextMut<'a, T> {
@@ -1028,6 +1076,11 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.fuel_consumed()
     }
 
+    /// Same as [`Store::debug_frames`].
+    pub fn debug_frames(&self) -> Vec<DebugFrame> {
+        self.0.debug_frames()
+    }
+
     /// Inject more fuel into this store to be consumed when executing wasm code.
     ///
     /// For more information see [`Store::add_fuel`]
@@ -1119,6 +1172,37 @@ impl StoreOpaque {
         self.store_data.id()
     }
 
+    pub(crate) fn debug_frames(&self) -> Vec<DebugFrame> {
+        // Records are only kept for frames still on the stack, see
+        // `truncate_debug_frames`.
+        self.debug_frames
+            .iter()
+            .rev()
+            .map(|record| unsafe { record.snapshot(self.id()) })
+            .collect()
+    }
+
+    /// Returns the records of the frames compiled with guest debugging which
+    /// are on the stack, or were unwound by a trap which is being handled,
+    /// starting with the outermost one.
+    pub(crate) fn debug_frame_records(&self) -> &[DebugFrameRecord] {
+        &self.debug_frames
+    }
+
+    pub(crate) fn debug_frame_depth(&self) -> usize {
+        self.debug_frames.len()
+    }
+
+    /// Forgets about the frames above `depth`, which were unwound by a trap
+    /// and so never reported their exit.
+    pub(crate) fn truncate_debug_frames(&mut self, depth: usize) {
+        self.debug_frames.truncate(depth);
+    }
+
+    pub(crate) fn memory_sanitizer_mut(&mut self) -> &mut MemorySanitizer {
+        &mut self.memory_sanitizer
+    }
+
     pub fn bump_resource_counts(&mut self, module: &Module) -> Result<()> {
         fn bump(slot: &mut usize, max: usize, amt: usize, desc: &str) -> Result<()> {
             let new = slot.saturating_add(amt);
@@ -1195,6 +1279,58 @@ impl StoreOpaque {
         &mut self.instances[id.0].handle
     }
 
+    /// Returns handles to all instances within this store, in the order they
+    /// were created.
+    pub(crate) fn all_instances(&self) -> Vec<Instance> {
+        self.store_data()
+            .iter()
+            .map(Instance::from_stored)
+            .collect()
+    }
+
+    /// Returns handles to all linear memories defined by instances within
+    /// this store, in the order the instances were created.
+    ///
+    /// Imported memories are skipped since they're reported by the instance
+    /// that defines them. Shared memories are skipped as well since they may
+    /// be concurrently modified by other threads.
+    pub(crate) fn all_memories(&mut self) -> Vec<Memory> {
+        let mut exports = Vec::new();
+        for instance in self.instances.iter_mut() {
+            let module = instance.handle.module().clone();
+            for (index, plan) in module
+                .memory_plans
+                .iter()
+                .skip(module.num_imported_memories)
+            {
+                if plan.memory.shared {
+                    continue;
+                }
+                exports.push(instance.handle.get_exported_memory(index));
+            }
+        }
+        exports
+            .into_iter()
+            .map(|export| unsafe { Memory::from_wasmtime_memory(export, self) })
+            .collect()
+    }
+
+    /// Returns handles to all globals defined by instances within this store,
+    /// in the order the instances were created.
+    pub(crate) fn all_globals(&mut self) -> Vec<Global> {
+        let mut exports = Vec::new();
+        for instance in self.instances.iter_mut() {
+            let module = instance.handle.module().clone();
+            for (index, _) in module.globals.iter().skip(module.num_imported_globals) {
+                exports.push(instance.handle.get_exported_global(index));
+            }
+        }
+        exports
+            .into_iter()
+            .map(|export| unsafe { Global::from_wasmtime_global(export, self) })
+            .collect()
+    }
+
     #[cfg_attr(not(target_os = "linux"), allow(dead_code))] // not used on all platforms
     pub fn set_signal_handler(&mut self, handler: Option<Box<SignalHandler<'static>>>) {
         self.signal_handler = handler;
@@ -2008,6 +2144,47 @@ unsafe impl<T> wasmtime_runtime::Store for StoreInner<T> {
         self.epoch_deadline_behavior = behavior;
         delta_result
     }
+
+    fn debug_hook(&mut self, hook: wasmtime_runtime::DebugHook) -> Result<(), anyhow::Error> {
+        match hook.kind {
+            wasmtime_environ::DEBUG_HOOK_ENTER => {
+                // The hook was called from the entered function, whose module
+                // is found through the pc recorded on the way out of wasm.
+                let pc = unsafe { *self.runtime_limits.last_wasm_exit_pc.get() };
+                let (module, _) = self
+                    .modules()
+                    .module(pc)
+                    .expect("debug hook called from unknown module");
+                let record = DebugFrameRecord::new(module.clone(), &hook);
+                self.debug_frames.push(record);
+                Ok(())
+            }
+            wasmtime_environ::DEBUG_HOOK_EXIT => {
+                self.debug_frames.pop();
+                Ok(())
+            }
+            _ => {
+                let depth = self.debug_frames.len();
+                let save = self.engine().config().coredump_on_trap;
+                let record = self.debug_frames.last_mut().unwrap();
+                record.step(&hook, save);
+                let location = record.location(depth);
+                // Temporarily take the hook to avoid mutably borrowing
+                // multiple times.
+                let mut callback = match self.debug_hook.take() {
+                    Some(callback) => callback,
+                    None => return Ok(()),
+                };
+                let result = callback((&mut *self).as_context_mut(), location);
+                self.debug_hook = Some(callback);
+                result
+            }
+        }
+    }
+
+    fn memory_sanitizer(&mut self) -> &mut MemorySanitizer {
+        &mut self.memory_sanitizer
+    }
 }
 
 impl<T> StoreInner<T> {
@@ -1,8 +1,8 @@
 use crate::store::StoreOpaque;
-use crate::{AsContext, Module};
+use crate::{AsContext, Module, WasmCoreDump};
 use anyhow::Error;
 use std::fmt;
-use wasmtime_environ::{EntityRef, FilePos};
+use wasmtime_environ::{EntityRef, FilePos, FuncIndex};
 use wasmtime_jit::{demangle_function_name, demangle_function_name_or_index};
 
 /// Representation of a WebAssembly trap and what caused it to occur.
@@ -76,7 +76,7 @@ pub(crate) unsafe fn raise(error: anyhow::Error) -> ! {
 
 #[cold] // traps are exceptional, this helps move handling off the main path
 pub(crate) fn from_runtime_box(
-    store: &StoreOpaque,
+    store: &mut StoreOpaque,
     runtime_trap: Box<wasmtime_runtime::Trap>,
 ) -> Error {
     let wasmtime_runtime::Trap { reason, backtrace } = *runtime_trap;
@@ -121,16 +121,17 @@ pub(crate) fn from_runtime_box(
         }
         wasmtime_runtime::TrapReason::Wasm(trap_code) => (trap_code.into(), None),
     };
-    match backtrace {
-        Some(bt) => {
-            let bt = WasmBacktrace::from_captured(store, bt, pc);
-            if bt.wasm_trace.is_empty() {
-                error
-            } else {
-                error.context(bt)
-            }
-        }
-        None => error,
+    let bt = backtrace.map(|bt| WasmBacktrace::from_captured(store, bt, pc));
+    let error = if store.engine().config().coredump_on_trap {
+        let frames = bt.as_ref().map_or(&[][..], |bt| bt.frames());
+        let coredump = WasmCoreDump::capture(store, frames.to_vec());
+        error.context(coredump)
+    } else {
+        error
+    };
+    match bt {
+        Some(bt) if !bt.wasm_trace.is_empty() => error.context(bt),
+        _ => error,
     }
 }
 
@@ -319,8 +320,8 @@ impl WasmBacktrace {
             // Some(..)` instead of the `unwrap` you might otherwise expect and
             // we ignore frames from modules that were not registered in this
             // store's module registry.
-            if let Some((info, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
-                wasm_trace.push(info);
+            if let Some((frames, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
+                wasm_trace.extend(frames);
 
                 // If this frame has unparsed debug information and the
                 // store's configuration indicates that we were
@@ -411,8 +412,9 @@ impl fmt::Display for WasmBacktrace {
 /// Whenever an error happens while WebAssembly is executing a
 /// [`WasmBacktrace`] will be attached to the error returned which can be used
 /// to acquire this `FrameInfo`. For more information see [`WasmBacktrace`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct FrameInfo {
+    module: Module,
     module_name: Option<String>,
     func_index: u32,
     func_name: Option<String>,
@@ -424,14 +426,19 @@ pub struct FrameInfo {
 impl FrameInfo {
     /// Fetches frame information about a program counter in a backtrace.
     ///
-    /// Returns an object if this `pc` is known to this module, or returns `None`
-    /// if no information can be found.
-    pub(crate) fn new(module: &Module, text_offset: usize) -> Option<FrameInfo> {
-        let module = module.compiled_module();
-        let (index, _func_offset) = module.func_by_text_offset(text_offset)?;
-        let info = module.wasm_func_info(index);
-        let instr =
-            wasmtime_environ::lookup_file_pos(module.code_memory().address_map_data(), text_offset);
+    /// Returns the frames for this `pc`, innermost first, if it's known to
+    /// this module, or returns `None` if no information can be found. More
+    /// than one frame is returned when the `pc` is within code that was
+    /// inlined from another function: the inlined function is reported first,
+    /// followed by the function it was inlined into.
+    pub(crate) fn new(module: &Module, text_offset: usize) -> Option<Vec<FrameInfo>> {
+        let compiled = module.compiled_module();
+        let (index, func_offset) = compiled.func_by_text_offset(text_offset)?;
+        let info = compiled.wasm_func_info(index);
+        let instr = wasmtime_environ::lookup_file_pos(
+            compiled.code_memory().address_map_data(),
+            text_offset,
+        );
 
         // In debug mode for now assert that we found a mapping for `pc` within
         // the function, because otherwise something is buggy along the way and
@@ -441,11 +448,45 @@ impl FrameInfo {
         // Note that if the module doesn't even have an address map due to
         // compilation settings then it's expected that `instr` is `None`.
         debug_assert!(
-            instr.is_some() || !module.has_address_map(),
+            instr.is_some() || !compiled.has_address_map(),
             "failed to find instruction for {:#x}",
             text_offset
         );
 
+        let func_index = compiled.module().func_index(index);
+        let call = info
+            .inlined_calls
+            .iter()
+            .find(|call| call.code.iter().any(|code| code.contains(&func_offset)));
+        let call = match call {
+            Some(call) => call,
+            None => {
+                return Some(vec![FrameInfo::for_func(
+                    module,
+                    func_index,
+                    instr,
+                    info.start_srcloc,
+                )])
+            }
+        };
+
+        let callee_info =
+            compiled.wasm_func_info(compiled.module().defined_func_index(call.callee)?);
+        Some(vec![
+            FrameInfo::for_func(module, call.callee, instr, callee_info.start_srcloc),
+            FrameInfo::for_func(module, func_index, Some(call.call_site), info.start_srcloc),
+        ])
+    }
+
+    fn for_func(
+        module: &Module,
+        index: FuncIndex,
+        instr: Option<FilePos>,
+        func_start: FilePos,
+    ) -> FrameInfo {
+        let handle = module.clone();
+        let module = module.compiled_module();
+
         // Use our wasm-relative pc to symbolize this frame. If there's a
         // symbolication context (dwarf debug info) available then we can try to
         // look this up there.
@@ -480,16 +521,20 @@ impl FrameInfo {
             }
         }
 
-        let index = module.module().func_index(index);
-
-        Some(FrameInfo {
+        FrameInfo {
+            module: handle,
             module_name: module.module().name.clone(),
             func_index: index.index() as u32,
             func_name: module.func_name(index).map(|s| s.to_string()),
             instr,
-            func_start: info.start_srcloc,
+            func_start,
             symbols,
-        })
+        }
+    }
+
+    /// Returns the module which this frame's function is defined in.
+    pub fn module(&self) -> &Module {
+        &self.module
     }
 
     /// Returns the WebAssembly function index for this frame.
@@ -580,7 +625,7 @@ impl FrameInfo {
 /// When DWARF debug information is present in a wasm file then this structure
 /// can be found on a [`FrameInfo`] and can be used to learn about filenames,
 /// line numbers, etc, which are the origin of a function in a stack trace.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct FrameSymbol {
     name: Option<String>,
     file: Option<String>,
@@ -3,11 +3,13 @@ use crate::component;
 use crate::core;
 use crate::spectest::*;
 use anyhow::{anyhow, bail, Context as _, Error, Result};
+use std::collections::HashSet;
 use std::path::Path;
 use std::str;
 use wasmtime::*;
 use wast::lexer::Lexer;
 use wast::parser::{self, ParseBuffer};
+use wast::token::Id;
 use wast::{QuoteWat, Wast, WastArg, WastDirective, WastExecute, WastInvoke, WastRet, Wat};
 
 /// The wast test script language allows modules to be defined and actions
@@ -20,6 +22,13 @@ pub struct WastContext<T> {
     #[cfg(feature = "component-model")]
     component_linker: component::Linker<T>,
     store: Store<T>,
+    /// Whether an error means that a module uses features that the compiler
+    /// doesn't support, if such modules are skipped.
+    is_unsupported: Option<fn(&Error) -> bool>,
+    /// The names of the modules which were skipped, and whether the current
+    /// module was.
+    unsupported: HashSet<String>,
+    current_unsupported: bool,
 }
 
 enum Outcome<T = Results> {
@@ -80,9 +89,22 @@ impl<T> WastContext<T> {
                 linker
             },
             store,
+            is_unsupported: None,
+            unsupported: HashSet::new(),
+            current_unsupported: false,
         }
     }
 
+    /// Skip the modules which fail to compile with an error that
+    /// `is_unsupported` accepts, instead of failing, along with the directives
+    /// using those modules.
+    ///
+    /// This lets a compiler which doesn't support some features run the rest
+    /// of a script.
+    pub fn skip_unsupported(&mut self, is_unsupported: fn(&Error) -> bool) {
+        self.is_unsupported = Some(is_unsupported);
+    }
+
     fn get_export(&mut self, module: Option<&str>, name: &str) -> Result<Export> {
         if let Some(module) = module {
             return Ok(Export::Core(
@@ -365,19 +387,106 @@ impl<T> WastContext<T> {
                 let (line, col) = sp.linecol_in(wast);
                 log::debug!("running directive on {}:{}:{}", filename, line + 1, col);
             }
-            self.run_directive(directive)
-                .map_err(|e| match e.downcast() {
-                    Ok(err) => adjust_wast(err).into(),
-                    Err(e) => e,
-                })
-                .with_context(|| {
-                    let (line, col) = sp.linecol_in(wast);
-                    format!("failed directive on {}:{}:{}", filename, line + 1, col)
-                })?;
+            let skip = |reason: &dyn std::fmt::Display| {
+                let (line, col) = sp.linecol_in(wast);
+                log::info!(
+                    "skipping directive on {}:{}:{}: {}",
+                    filename,
+                    line + 1,
+                    col,
+                    reason
+                );
+            };
+            if let Some(module) = self.unsupported_use(&directive) {
+                if let WastDirective::Register { name, .. } = directive {
+                    self.unsupported.insert(name.to_string());
+                }
+                skip(&format_args!("uses the skipped module {}", module));
+                continue;
+            }
+
+            // The name of the module that the directive defines, if any.
+            let defines = match &directive {
+                WastDirective::Wat(QuoteWat::Wat(Wat::Module(m))) => Some(m.id),
+                WastDirective::Wat(QuoteWat::Wat(Wat::Component(c))) => Some(c.id),
+                WastDirective::Wat(_) => Some(None),
+                _ => None,
+            };
+            let defines = defines.map(|name| name.map(|id| id.name().to_string()));
+            match self.run_directive(directive) {
+                Ok(()) => {
+                    if let Some(name) = defines {
+                        self.current_unsupported = false;
+                        if let Some(name) = name {
+                            self.unsupported.remove(&name);
+                        }
+                    }
+                }
+                Err(e) if self.is_unsupported(&e) => {
+                    if let Some(name) = defines {
+                        self.current = None;
+                        self.current_unsupported = true;
+                        if let Some(name) = name {
+                            self.unsupported.insert(name);
+                        }
+                    }
+                    skip(&format_args!("{:#}", e));
+                }
+                Err(e) => {
+                    return Err(match e.downcast() {
+                        Ok(err) => adjust_wast(err).into(),
+                        Err(e) => e,
+                    })
+                    .with_context(|| {
+                        let (line, col) = sp.linecol_in(wast);
+                        format!("failed directive on {}:{}:{}", filename, line + 1, col)
+                    })
+                }
+            }
         }
         Ok(())
     }
 
+    /// Returns the name of the skipped module that `directive` uses, if any.
+    fn unsupported_use(&self, directive: &WastDirective<'_>) -> Option<String> {
+        let unsupported = |module: Option<Id<'_>>| match module {
+            Some(id) if self.unsupported.contains(id.name()) => Some(format!("`{}`", id.name())),
+            Some(_) => None,
+            None if self.current_unsupported => Some("which is current".to_string()),
+            None => None,
+        };
+        match directive {
+            WastDirective::Register { module, .. } => unsupported(*module),
+            WastDirective::Invoke(call) | WastDirective::AssertExhaustion { call, .. } => {
+                unsupported(call.module)
+            }
+            WastDirective::AssertReturn { exec, .. } | WastDirective::AssertTrap { exec, .. } => {
+                match exec {
+                    WastExecute::Invoke(call) => unsupported(call.module),
+                    WastExecute::Get { module, .. } => unsupported(*module),
+                    WastExecute::Wat(_) => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `error` means that a module uses unsupported features, or
+    /// imports from a module which was skipped because it does.
+    fn is_unsupported(&self, error: &Error) -> bool {
+        let is_unsupported = match self.is_unsupported {
+            Some(is_unsupported) => is_unsupported,
+            None => return false,
+        };
+        if is_unsupported(error) {
+            return true;
+        }
+        let error = format!("{:?}", error);
+        self.unsupported
+            .iter()
+            .any(|module| error.contains(&format!("unknown import: `{}::", module)))
+    }
+
     fn run_directive(&mut self, directive: WastDirective) -> Result<()> {
         use wast::WastDirective::*;
 
@@ -8,11 +8,15 @@ use winch_codegen::{isa, TargetIsa};
 /// Compiler builder.
 struct Builder {
     inner: IsaBuilder<Result<Box<dyn TargetIsa>>>,
+    /// Builder of the Cranelift compiler used to compile trampolines,
+    /// which is configured along with the Winch ISA.
+    cranelift: Box<dyn CompilerBuilder>,
 }
 
 pub fn builder() -> Box<dyn CompilerBuilder> {
     Box::new(Builder {
         inner: IsaBuilder::new(|triple| isa::lookup(triple).map_err(|e| e.into())),
+        cranelift: wasmtime_cranelift::builder(),
     })
 }
 
@@ -22,16 +26,19 @@ impl CompilerBuilder for Builder {
     }
 
     fn target(&mut self, target: target_lexicon::Triple) -> Result<()> {
-        self.inner.target(target)?;
+        self.inner.target(target.clone())?;
+        self.cranelift.target(target)?;
         Ok(())
     }
 
     fn set(&mut self, name: &str, value: &str) -> Result<()> {
-        self.inner.set(name, value)
+        self.inner.set(name, value)?;
+        self.cranelift.set(name, value)
     }
 
     fn enable(&mut self, name: &str) -> Result<()> {
-        self.inner.enable(name)
+        self.inner.enable(name)?;
+        self.cranelift.enable(name)
     }
 
     fn settings(&self) -> Vec<Setting> {
@@ -40,8 +47,9 @@ impl CompilerBuilder for Builder {
 
     fn build(&self) -> Result<Box<dyn wasmtime_environ::Compiler>> {
         let isa = self.inner.build()?;
+        let trampolines = self.cranelift.build()?;
 
-        Ok(Box::new(Compiler::new(isa)))
+        Ok(Box::new(Compiler::new(isa, trampolines)))
     }
 
     fn enable_incremental_compilation(
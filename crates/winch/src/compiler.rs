@@ -1,59 +1,121 @@
 use anyhow::Result;
 use object::write::{Object, SymbolId};
 use std::any::Any;
+use wasmtime_cranelift::CompiledFunction;
 use wasmtime_environ::{
-    CompileError, DefinedFuncIndex, FuncIndex, FunctionBodyData, FunctionLoc, ModuleTranslation,
-    ModuleTypes, PrimaryMap, Tunables, WasmFunctionInfo,
+    CompileError, DefinedFuncIndex, FilePos, FuncIndex, FunctionBodyData, FunctionLoc,
+    ModuleTranslation, ModuleTypes, PrimaryMap, Tunables, WasmFunctionInfo,
 };
 use winch_codegen::TargetIsa;
+use winch_environ::FuncEnv;
 
 pub(crate) struct Compiler {
     isa: Box<dyn TargetIsa>,
+    /// The Cranelift compiler used to compile trampolines, which is
+    /// also in charge of emitting the compiled functions, since Winch
+    /// functions are represented as Cranelift's `CompiledFunction`.
+    trampolines: Box<dyn wasmtime_environ::Compiler>,
 }
 
 impl Compiler {
-    pub fn new(isa: Box<dyn TargetIsa>) -> Self {
-        Self { isa }
+    pub fn new(isa: Box<dyn TargetIsa>, trampolines: Box<dyn wasmtime_environ::Compiler>) -> Self {
+        Self { isa, trampolines }
     }
 }
 
 impl wasmtime_environ::Compiler for Compiler {
     fn compile_function(
+        &self,
+        translation: &ModuleTranslation<'_>,
+        index: DefinedFuncIndex,
+        data: FunctionBodyData<'_>,
+        tunables: &Tunables,
+        _types: &ModuleTypes,
+    ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
+        let index = translation.module.func_index(index);
+        let types = translation.get_types();
+        let sig = types.function_at(index.as_u32()).ok_or_else(|| {
+            CompileError::Codegen(format!(
+                "missing function type at index: {}",
+                index.as_u32()
+            ))
+        })?;
+        let FunctionBodyData { body, validator } = data;
+        let range = body.range();
+        let validator = validator.into_validator(Default::default());
+        let env = FuncEnv::new(&translation.module, types, &*self.isa);
+        let buffer = self
+            .isa
+            .compile_function(sig, &body, &env, validator)
+            .map_err(|e| CompileError::Codegen(format!("{:?}", e)))?;
+        let compiled_function = CompiledFunction::from_mach_buffer(
+            &buffer,
+            self.isa.function_alignment(),
+            &range,
+            tunables,
+        );
+
+        Ok((
+            WasmFunctionInfo {
+                start_srcloc: FilePos::new(u32::try_from(range.start).unwrap()),
+                stack_maps: Default::default(),
+                block_profile_counters: Default::default(),
+                inlined_calls: Default::default(),
+            },
+            Box::new(compiled_function),
+        ))
+    }
+
+    fn translate_function(
         &self,
         _translation: &ModuleTranslation<'_>,
         _index: DefinedFuncIndex,
         _data: FunctionBodyData<'_>,
         _tunables: &Tunables,
         _types: &ModuleTypes,
+    ) -> Result<Box<dyn Any + Send + Sync>, CompileError> {
+        Err(CompileError::Codegen(
+            "function inlining is not supported by Winch".to_string(),
+        ))
+    }
+
+    fn compile_translated_function(
+        &self,
+        _translation: &ModuleTranslation<'_>,
+        _index: DefinedFuncIndex,
+        _translated: &PrimaryMap<DefinedFuncIndex, Box<dyn Any + Send + Sync>>,
+        _tunables: &Tunables,
     ) -> Result<(WasmFunctionInfo, Box<dyn Any + Send>), CompileError> {
-        todo!()
+        Err(CompileError::Codegen(
+            "function inlining is not supported by Winch".to_string(),
+        ))
     }
 
     fn compile_host_to_wasm_trampoline(
         &self,
-        _ty: &wasmtime_environ::WasmFuncType,
+        ty: &wasmtime_environ::WasmFuncType,
     ) -> Result<Box<dyn Any + Send>, CompileError> {
-        todo!()
+        self.trampolines.compile_host_to_wasm_trampoline(ty)
     }
 
     fn append_code(
         &self,
-        _obj: &mut Object<'static>,
-        _funcs: &[(String, Box<dyn Any + Send>)],
-        _tunables: &Tunables,
-        _resolve_reloc: &dyn Fn(usize, FuncIndex) -> usize,
+        obj: &mut Object<'static>,
+        funcs: &[(String, Box<dyn Any + Send>)],
+        tunables: &Tunables,
+        resolve_reloc: &dyn Fn(usize, FuncIndex) -> usize,
     ) -> Result<Vec<(SymbolId, FunctionLoc)>> {
-        assert!(_funcs.is_empty());
-        Ok(Vec::new())
+        self.trampolines
+            .append_code(obj, funcs, tunables, resolve_reloc)
     }
 
     fn emit_trampoline_obj(
         &self,
-        _ty: &wasmtime_environ::WasmFuncType,
-        _host_fn: usize,
-        _obj: &mut wasmtime_environ::object::write::Object<'static>,
+        ty: &wasmtime_environ::WasmFuncType,
+        host_fn: usize,
+        obj: &mut wasmtime_environ::object::write::Object<'static>,
     ) -> Result<(FunctionLoc, FunctionLoc)> {
-        todo!()
+        self.trampolines.emit_trampoline_obj(ty, host_fn, obj)
     }
 
     fn triple(&self) -> &target_lexicon::Triple {
@@ -74,7 +136,7 @@ impl wasmtime_environ::Compiler for Compiler {
 
     #[cfg(feature = "component-model")]
     fn component_compiler(&self) -> &dyn wasmtime_environ::component::ComponentCompiler {
-        todo!()
+        self.trampolines.component_compiler()
     }
 
     fn append_dwarf(
@@ -83,7 +145,7 @@ impl wasmtime_environ::Compiler for Compiler {
         _translation: &ModuleTranslation<'_>,
         _funcs: &PrimaryMap<DefinedFuncIndex, (SymbolId, &(dyn Any + Send))>,
     ) -> Result<()> {
-        todo!()
+        anyhow::bail!("native debug information is not supported by Winch")
     }
 
     fn function_alignment(&self) -> u32 {
@@ -0,0 +1,192 @@
+/*
+Example of instantiating a component which imports a host function, passing
+records, enums and options back and forth.
+
+You can compile and run this example on Linux with:
+
+   cargo build --release -p wasmtime-c-api
+   cc examples/component.c \
+       -I crates/c-api/include \
+       -I crates/c-api/wasm-c-api/include \
+       target/release/libwasmtime.a \
+       -lpthread -ldl -lm \
+       -o component
+   ./component
+
+Note that on Windows and macOS the command will be similar, but you'll need
+to tweak the `-lpthread` and such annotations as well as the name of the
+`libwasmtime.a` file on Windows.
+
+You can also build using cmake:
+
+mkdir build && cd build && cmake .. && cmake --build . --target wasmtime-component
+*/
+
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <wasm.h>
+#include <wasmtime.h>
+
+static void exit_with_error(const char *message, wasmtime_error_t *error, wasm_trap_t *trap);
+static void read_wat_file(wasm_engine_t *engine, wasm_byte_vec_t *bytes, const char *file);
+
+static bool name_is(const wasm_name_t *name, const char *expected) {
+  return name->size == strlen(expected) && memcmp(name->data, expected, name->size) == 0;
+}
+
+static wasm_trap_t* combine_callback(
+    void *env,
+    wasmtime_context_t *context,
+    const wasmtime_component_val_t *args,
+    size_t nargs,
+    wasmtime_component_val_t *results,
+    size_t nresults
+) {
+  // The arguments are borrowed and have the types of the `combine` import.
+  assert(nargs == 3 && nresults == 1);
+  const wasmtime_component_valrecord_t *point = &args[0].of.record;
+  assert(args[0].kind == WASMTIME_COMPONENT_RECORD && point->size == 2);
+  assert(name_is(&point->data[0].name, "x") && name_is(&point->data[1].name, "y"));
+  int32_t x = point->data[0].val.of.s32;
+  int32_t y = point->data[1].val.of.s32;
+  printf("Calling back with (%d, %d)...\n", x, y);
+
+  assert(args[1].kind == WASMTIME_COMPONENT_ENUM);
+  int32_t combined = name_is(&args[1].of.enumeration, "add") ? x + y : x * y;
+
+  assert(args[2].kind == WASMTIME_COMPONENT_OPTION);
+  int32_t factor = args[2].of.option != NULL ? args[2].of.option->of.s32 : 1;
+
+  // Each result starts out as `false` and is overwritten with an owned value.
+  results[0].kind = WASMTIME_COMPONENT_S32;
+  results[0].of.s32 = combined * factor;
+  return NULL;
+}
+
+int main() {
+  // Components have to be enabled in the configuration of the engine.
+  printf("Initializing...\n");
+  wasm_config_t *config = wasm_config_new();
+  assert(config != NULL);
+  wasmtime_config_wasm_component_model_set(config, true);
+  wasm_engine_t *engine = wasm_engine_new_with_config(config);
+  assert(engine != NULL);
+  wasmtime_store_t *store = wasmtime_store_new(engine, NULL, NULL);
+  assert(store != NULL);
+  wasmtime_context_t *context = wasmtime_store_context(store);
+
+  wasm_byte_vec_t wasm;
+  read_wat_file(engine, &wasm, "examples/component.wat");
+
+  printf("Compiling component...\n");
+  wasmtime_component_t *component = NULL;
+  wasmtime_error_t *error = wasmtime_component_new(engine, (uint8_t*) wasm.data, wasm.size, &component);
+  wasm_byte_vec_delete(&wasm);
+  if (error != NULL)
+    exit_with_error("failed to compile component", error, NULL);
+
+  // Define the `combine` import, whose types are taken from the component.
+  wasmtime_component_linker_t *linker = wasmtime_component_linker_new(engine);
+  error = wasmtime_component_linker_define_func(linker, component, "combine", strlen("combine"),
+                                                combine_callback, NULL, NULL);
+  if (error != NULL)
+    exit_with_error("failed to define combine", error, NULL);
+
+  printf("Instantiating component...\n");
+  wasmtime_component_instance_t instance;
+  wasm_trap_t *trap = NULL;
+  error = wasmtime_component_linker_instantiate(linker, context, component, &instance, &trap);
+  if (error != NULL || trap != NULL)
+    exit_with_error("failed to instantiate", error, trap);
+
+  wasmtime_component_func_t run;
+  bool ok = wasmtime_component_instance_get_func(&instance, context, "run", strlen("run"), &run);
+  assert(ok);
+
+  // Build the arguments. Everything they point to is allocated by Wasmtime so
+  // that it can be deallocated with `wasmtime_component_val_delete`.
+  wasmtime_component_val_t args[3];
+  wasmtime_component_valrecord_field_t fields[2];
+  wasm_name_new(&fields[0].name, 1, "x");
+  fields[0].val.kind = WASMTIME_COMPONENT_S32;
+  fields[0].val.of.s32 = 3;
+  wasm_name_new(&fields[1].name, 1, "y");
+  fields[1].val.kind = WASMTIME_COMPONENT_S32;
+  fields[1].val.of.s32 = 4;
+  args[0].kind = WASMTIME_COMPONENT_RECORD;
+  wasmtime_component_valrecord_new(&args[0].of.record, 2, fields);
+
+  args[1].kind = WASMTIME_COMPONENT_ENUM;
+  wasm_name_new(&args[1].of.enumeration, 3, "mul");
+
+  args[2].kind = WASMTIME_COMPONENT_OPTION;
+  args[2].of.option = wasmtime_component_val_new();
+  args[2].of.option->kind = WASMTIME_COMPONENT_S32;
+  args[2].of.option->of.s32 = 10;
+
+  printf("Calling export...\n");
+  wasmtime_component_val_t result;
+  error = wasmtime_component_func_call(context, &run, args, 3, &result, 1, &trap);
+  if (error != NULL || trap != NULL)
+    exit_with_error("failed to call run", error, trap);
+  assert(result.kind == WASMTIME_COMPONENT_S32);
+  printf("> %d\n", result.of.s32);
+  assert(result.of.s32 == 480);
+
+  // Clean up after ourselves at this point
+  printf("All finished!\n");
+  for (int i = 0; i < 3; i++)
+    wasmtime_component_val_delete(&args[i]);
+  wasmtime_component_val_delete(&result);
+  wasmtime_component_linker_delete(linker);
+  wasmtime_component_delete(component);
+  wasmtime_store_delete(store);
+  wasm_engine_delete(engine);
+  return 0;
+}
+
+static void read_wat_file(
+  wasm_engine_t *engine,
+  wasm_byte_vec_t *bytes,
+  const char *filename
+) {
+  wasm_byte_vec_t wat;
+  // Load our input file to parse it next
+  FILE* file = fopen(filename, "r");
+  if (!file) {
+    printf("> Error loading file!\n");
+    exit(1);
+  }
+  fseek(file, 0L, SEEK_END);
+  size_t file_size = ftell(file);
+  wasm_byte_vec_new_uninitialized(&wat, file_size);
+  fseek(file, 0L, SEEK_SET);
+  if (fread(wat.data, file_size, 1, file) != 1) {
+    printf("> Error loading component!\n");
+    exit(1);
+  }
+  fclose(file);
+
+  // Parse the wat into the binary wasm format
+  wasmtime_error_t *error = wasmtime_wat2wasm(wat.data, wat.size, bytes);
+  if (error != NULL)
+    exit_with_error("failed to parse wat", error, NULL);
+  wasm_byte_vec_delete(&wat);
+}
+
+static void exit_with_error(const char *message, wasmtime_error_t *error, wasm_trap_t *trap) {
+  fprintf(stderr, "error: %s\n", message);
+  wasm_byte_vec_t error_message;
+  if (error != NULL) {
+    wasmtime_error_message(error, &error_message);
+    wasmtime_error_delete(error);
+  } else {
+    wasm_trap_message(trap, &error_message);
+    wasm_trap_delete(trap);
+  }
+  fprintf(stderr, "%.*s\n", (int) error_message.size, error_message.data);
+  wasm_byte_vec_delete(&error_message);
+  exit(1);
+}
@@ -0,0 +1,66 @@
+//! Small example of how to instantiate a component which imports a host
+//! function, passing records, enums and options back and forth.
+
+// You can execute this example with `cargo run --example component`
+
+use anyhow::Result;
+use wasmtime::component::{Component, Linker, Val};
+use wasmtime::{Config, Engine, Store};
+
+fn main() -> Result<()> {
+    // Components have to be enabled in the configuration of the engine.
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config)?;
+    let component = Component::from_file(&engine, "examples/component.wat")?;
+
+    // Define the `combine` import with dynamically typed values.
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_new(&component, "combine", |_store, params, results| {
+            let (x, y) = match &params[0] {
+                Val::Record(point) => match point.fields().collect::<Vec<_>>()[..] {
+                    [("x", Val::S32(x)), ("y", Val::S32(y))] => (*x, *y),
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            };
+            let combined = match &params[1] {
+                Val::Enum(op) if op.discriminant() == "add" => x + y,
+                Val::Enum(_) => x * y,
+                _ => unreachable!(),
+            };
+            let factor = match &params[2] {
+                Val::Option(factor) => match factor.value() {
+                    Some(Val::S32(factor)) => *factor,
+                    _ => 1,
+                },
+                _ => unreachable!(),
+            };
+            println!("Calling back with ({x}, {y})...");
+            results[0] = Val::S32(combined * factor);
+            Ok(())
+        })?;
+
+    println!("Instantiating component...");
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let run = instance.get_func(&mut store, "run").unwrap();
+
+    // Build the arguments from the types of the parameters.
+    let types = run.params(&store);
+    let point = types[0]
+        .unwrap_record()
+        .new_val([("x", Val::S32(3)), ("y", Val::S32(4))])?;
+    let op = types[1].unwrap_enum().new_val("mul")?;
+    let factor = types[2].unwrap_option().new_val(Some(Val::S32(10)))?;
+
+    println!("Calling export...");
+    let mut results = [Val::Bool(false)];
+    run.call(&mut store, &[point, op, factor], &mut results)?;
+    run.post_return(&mut store)?;
+    println!("> {:?}", results[0]);
+    assert_eq!(results[0], Val::S32(480));
+    Ok(())
+}
@@ -0,0 +1,28 @@
+(component
+  (type $point (record (field "x" s32) (field "y" s32)))
+  (type $op (enum "add" "mul"))
+
+  (import "combine" (func $combine
+    (param "p" $point) (param "op" $op) (param "factor" (option s32))
+    (result s32)))
+
+  (core func $combine_lowered (canon lower (func $combine)))
+  (core module $m
+    (import "host" "combine" (func $combine (param i32 i32 i32 i32 i32) (result i32)))
+    ;; Forwards its arguments to the host, doubling the point first.
+    (func (export "run") (param i32 i32 i32 i32 i32) (result i32)
+      (call $combine
+        (i32.mul (local.get 0) (i32.const 2))
+        (i32.mul (local.get 1) (i32.const 2))
+        (local.get 2)
+        (local.get 3)
+        (local.get 4)))
+  )
+  (core instance $i (instantiate $m
+    (with "host" (instance (export "combine" (func $combine_lowered))))))
+
+  (func (export "run")
+    (param "p" $point) (param "op" $op) (param "factor" (option s32))
+    (result s32)
+    (canon lift (core func $i "run")))
+)
@@ -9,6 +9,9 @@ use wasmtime_cli::commands::{
     CompileCommand, ConfigCommand, ExploreCommand, RunCommand, SettingsCommand, WastCommand,
 };
 
+#[cfg(feature = "pooling-allocator")]
+use wasmtime_cli::commands::ServeCommand;
+
 /// Wasmtime WebAssembly Runtime
 #[derive(Parser)]
 #[clap(
@@ -39,6 +42,9 @@ enum Wasmtime {
     Explore(ExploreCommand),
     /// Runs a WebAssembly module
     Run(RunCommand),
+    /// Serves HTTP requests with a WebAssembly module
+    #[cfg(feature = "pooling-allocator")]
+    Serve(ServeCommand),
     /// Displays available Cranelift settings for a target.
     Settings(SettingsCommand),
     /// Runs a WebAssembly test script file
@@ -53,6 +59,8 @@ impl Wasmtime {
             Self::Compile(c) => c.execute(),
             Self::Explore(c) => c.execute(),
             Self::Run(c) => c.execute(),
+            #[cfg(feature = "pooling-allocator")]
+            Self::Serve(c) => c.execute(),
             Self::Settings(c) => c.execute(),
             Self::Wast(c) => c.execute(),
         }
@@ -4,7 +4,12 @@ mod compile;
 mod config;
 mod explore;
 mod run;
+#[cfg(feature = "pooling-allocator")]
+mod serve;
 mod settings;
 mod wast;
 
 pub use self::{compile::*, config::*, explore::*, run::*, settings::*, wast::*};
+
+#[cfg(feature = "pooling-allocator")]
+pub use self::serve::*;
@@ -3,9 +3,9 @@
 use anyhow::{Context, Result};
 use clap::Parser;
 use std::path::PathBuf;
-use wasmtime_cli_flags::CommonOptions;
+use wasmtime_cli_flags::{parse_cranelift_flag, parse_opt_level, CommonOptions};
 
-/// Explore the compilation of a WebAssembly module to native code.
+/// Explore the compilation of a WebAssembly module or component to native code.
 #[derive(Parser)]
 #[clap(name = "explore")]
 pub struct ExploreCommand {
@@ -16,7 +16,7 @@ pub struct ExploreCommand {
     #[clap(long, value_name = "TARGET")]
     target: Option<String>,
 
-    /// The path of the WebAssembly module to compile
+    /// The path of the WebAssembly module or component to compile
     #[clap(required = true, value_name = "MODULE")]
     module: PathBuf,
 
@@ -24,6 +24,21 @@ pub struct ExploreCommand {
     /// provided)
     #[clap(short, long)]
     output: Option<PathBuf>,
+
+    /// Compare the Cranelift IR and VCode of each function with the output
+    /// of compiling with this optimization level instead
+    #[clap(long, value_name = "LEVEL", parse(try_from_str = parse_opt_level))]
+    diff_opt_level: Option<wasmtime::OptLevel>,
+
+    /// Compare the Cranelift IR and VCode of each function with the output
+    /// of compiling with this Cranelift setting additionally set
+    #[clap(
+        long = "diff-cranelift-set",
+        value_name = "NAME=VALUE",
+        number_of_values = 1,
+        parse(try_from_str = parse_cranelift_flag)
+    )]
+    diff_cranelift_set: Vec<(String, String)>,
 }
 
 impl ExploreCommand {
@@ -31,7 +46,25 @@ impl ExploreCommand {
     pub fn execute(&self) -> Result<()> {
         self.common.init_logging();
 
-        let config = self.common.config(self.target.as_deref())?;
+        // The cache is disabled so that each function is compiled and the
+        // Cranelift IR and VCode of each stage of compilation is available.
+        let mut config = self.common.config(self.target.as_deref())?;
+        config.disable_cache();
+
+        let diff_config = if self.diff_opt_level.is_some() || !self.diff_cranelift_set.is_empty() {
+            let mut diff_config = config.clone();
+            if let Some(level) = &self.diff_opt_level {
+                diff_config.cranelift_opt_level(level.clone());
+            }
+            for (name, value) in &self.diff_cranelift_set {
+                unsafe {
+                    diff_config.cranelift_flag_set(name, value);
+                }
+            }
+            Some(diff_config)
+        } else {
+            None
+        };
 
         let wasm = std::fs::read(&self.module)
             .with_context(|| format!("failed to read Wasm module: {}", self.module.display()))?;
@@ -44,7 +77,13 @@ impl ExploreCommand {
             .with_context(|| format!("failed to create file: {}", output.display()))?;
         let mut output_file = std::io::BufWriter::new(output_file);
 
-        wasmtime_explorer::generate(&config, self.target.as_deref(), &wasm, &mut output_file)?;
+        wasmtime_explorer::generate(
+            &config,
+            diff_config.as_ref(),
+            self.target.as_deref(),
+            &wasm,
+            &mut output_file,
+        )?;
         println!("Exploration written to {}", output.display());
         Ok(())
     }
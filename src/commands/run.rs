@@ -5,20 +5,23 @@ use clap::Parser;
 use once_cell::sync::Lazy;
 use std::ffi::OsStr;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::{Component, Path, PathBuf};
 use std::thread;
-use std::time::Duration;
-use wasmtime::{Engine, Func, Linker, Module, Store, Val, ValType};
-use wasmtime_cli_flags::{CommonOptions, WasiModules};
-use wasmtime_wasi::maybe_exit_on_error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use wasmtime::{
+    BlockProfile, Engine, Func, GdbStub, GuestProfiler, Linker, Module, Precompiled, Store, Val,
+    ValType,
+};
+use wasmtime_cli_flags::{CommonOptions, Profile, WasiModules};
 use wasmtime_wasi::sync::{ambient_authority, Dir, TcpListener, WasiCtxBuilder};
+use wasmtime_wasi::{maybe_exit_on_error, I32Exit, Trace};
 
 #[cfg(any(feature = "wasi-crypto", feature = "wasi-nn", feature = "wasi-threads"))]
 use std::sync::Arc;
 
 #[cfg(feature = "wasi-nn")]
-use wasmtime_wasi_nn::WasiNnCtx;
+use wasmtime_wasi_nn::{ExecutionTarget, GraphEncoding, SharedGraph, WasiNnCtx};
 
 #[cfg(feature = "wasi-crypto")]
 use wasmtime_wasi_crypto::WasiCryptoCtx;
@@ -26,10 +29,14 @@ use wasmtime_wasi_crypto::WasiCryptoCtx;
 #[cfg(feature = "wasi-threads")]
 use wasmtime_wasi_threads::WasiThreadsCtx;
 
+#[cfg(feature = "component-model")]
+use wasmtime::component::{self, Component};
+
 fn parse_module(s: &OsStr) -> anyhow::Result<PathBuf> {
     // Do not accept wasmtime subcommand names as the module name
     match s.to_str() {
-        Some("help") | Some("config") | Some("run") | Some("wast") | Some("compile") => {
+        Some("help") | Some("config") | Some("run") | Some("wast") | Some("compile")
+        | Some("serve") => {
             bail!("module name cannot be the same as a subcommand")
         }
         #[cfg(unix)]
@@ -54,7 +61,15 @@ fn parse_map_dirs(s: &str) -> Result<(String, String)> {
     Ok((parts[0].into(), parts[1].into()))
 }
 
-fn parse_dur(s: &str) -> Result<Duration> {
+fn parse_graphs(s: &str) -> Result<(String, PathBuf)> {
+    let parts: Vec<&str> = s.split("::").collect();
+    if parts.len() != 2 {
+        bail!("must contain exactly one double colon ('::')");
+    }
+    Ok((parts[0].into(), parts[1].into()))
+}
+
+pub(crate) fn parse_dur(s: &str) -> Result<Duration> {
     // assume an integer without a unit specified is a number of seconds ...
     if let Ok(val) = s.parse() {
         return Ok(Duration::from_secs(val));
@@ -74,7 +89,7 @@ fn parse_preloads(s: &str) -> Result<(String, PathBuf)> {
 
 static AFTER_HELP: Lazy<String> = Lazy::new(|| crate::FLAG_EXPLANATIONS.to_string());
 
-/// Runs a WebAssembly module
+/// Runs a WebAssembly module or component
 #[derive(Parser)]
 #[structopt(name = "run", trailing_var_arg = true, after_help = AFTER_HELP.as_str())]
 pub struct RunCommand {
@@ -95,7 +110,8 @@ pub struct RunCommand {
     #[clap(long = "default-values-unknown-imports")]
     default_values_unknown_imports: bool,
 
-    /// Allow executing precompiled WebAssembly modules as `*.cwasm` files.
+    /// Allow executing precompiled WebAssembly modules and components as
+    /// `*.cwasm` files.
     ///
     /// Note that this option is not safe to pass if the module being passed in
     /// is arbitrary user input. Only `wasmtime`-precompiled modules generated
@@ -126,6 +142,9 @@ pub struct RunCommand {
     vars: Vec<(String, String)>,
 
     /// The name of the function to run
+    ///
+    /// This is required when running a component, which has no default
+    /// export to call.
     #[clap(long, value_name = "FUNCTION")]
     invoke: Option<String>,
 
@@ -133,7 +152,7 @@ pub struct RunCommand {
     #[clap(long = "mapdir", number_of_values = 1, value_name = "GUEST_DIR::HOST_DIR", parse(try_from_str = parse_map_dirs))]
     map_dirs: Vec<(String, String)>,
 
-    /// The path of the WebAssembly module to run
+    /// The path of the WebAssembly module or component to run
     #[clap(
         required = true,
         value_name = "MODULE",
@@ -150,6 +169,20 @@ pub struct RunCommand {
     )]
     preloads: Vec<(String, PathBuf)>,
 
+    /// Preload a wasi-nn graph so that guests can load it with
+    /// `load_by_name`.
+    ///
+    /// The path may be an OpenVINO `.xml` file (with its weights in a `.bin`
+    /// file alongside it), an ONNX `.onnx` file, or a directory containing
+    /// either `model.xml` and `model.bin` or `model.onnx`.
+    #[clap(
+        long = "wasi-nn-graph",
+        number_of_values = 1,
+        value_name = "NAME::PATH",
+        parse(try_from_str = parse_graphs)
+    )]
+    wasi_nn_graphs: Vec<(String, PathBuf)>,
+
     /// Maximum execution time of wasm code before timing out (1, 2s, 100ms, etc)
     #[clap(
         long = "wasm-timeout",
@@ -159,9 +192,50 @@ pub struct RunCommand {
     wasm_timeout: Option<Duration>,
 
     /// Enable coredump generation after a WebAssembly trap.
+    ///
+    /// The path may contain `%p` (process ID), `%t` (seconds since the Unix
+    /// epoch), `%m` (module name) and `%%` (a literal `%`) patterns.
     #[clap(long = "coredump-on-trap", value_name = "PATH")]
     coredump_on_trap: Option<String>,
 
+    /// Record the results of all WASI calls, such as clock readings, random
+    /// bytes and reads, into a trace file.
+    ///
+    /// Combined with `--deterministic` the execution can then be reproduced
+    /// exactly with `--replay-trace`.
+    #[clap(long = "record-trace", value_name = "PATH")]
+    record_trace: Option<PathBuf>,
+
+    /// Replay the results of all WASI calls from a trace file previously
+    /// created with `--record-trace`.
+    #[clap(
+        long = "replay-trace",
+        value_name = "PATH",
+        conflicts_with = "record-trace"
+    )]
+    replay_trace: Option<PathBuf>,
+
+    /// Wait for a debugger, such as LLDB, to connect to a GDB remote protocol
+    /// server on the given address before running, and let it debug the
+    /// WebAssembly code.
+    #[clap(long = "gdbstub", value_name = "SOCKET ADDRESS")]
+    gdbstub: Option<String>,
+
+    /// Compile the main module with the block profile in the given file,
+    /// previously collected with `--block-profile-out`, to lay out its code
+    /// for the execution paths that were hot in that profile.
+    #[clap(long = "block-profile", value_name = "PATH")]
+    block_profile: Option<PathBuf>,
+
+    /// Count the executions of the blocks of the main module's code, and
+    /// write them as a block profile to the given file once it has finished
+    /// running.
+    ///
+    /// If the file already contains a profile of the module, the new counts
+    /// are added to it.
+    #[clap(long = "block-profile-out", value_name = "PATH")]
+    block_profile_out: Option<PathBuf>,
+
     // NOTE: this must come last for trailing varargs
     /// The arguments to pass to the module
     #[clap(value_name = "ARGS")]
@@ -177,40 +251,81 @@ impl RunCommand {
         if self.wasm_timeout.is_some() {
             config.epoch_interruption(true);
         }
-        let engine = Engine::new(&config)?;
-
-        let preopen_sockets = self.compute_preopen_sockets()?;
+        if let Some(Profile::Guest { .. }) = &self.common.profile {
+            if self.wasm_timeout.is_some() {
+                bail!("`--profile=guest` cannot be combined with `--wasm-timeout`");
+            }
+            config.epoch_interruption(true);
+        }
+        if self.gdbstub.is_some() {
+            config.guest_debug(true);
+        }
 
         // Validate coredump-on-trap argument
         if let Some(coredump_path) = self.coredump_on_trap.as_ref() {
-            if coredump_path.contains("%") {
-                bail!("the coredump-on-trap path does not support patterns yet.")
-            }
+            expand_coredump_path(coredump_path, &self.module)?;
+            config.coredump_on_trap(true);
         }
+        if self.block_profile_out.is_some() {
+            config.block_profiling(true);
+        }
+        let engine = Engine::new(&config)?;
+
+        let preopen_sockets = self.compute_preopen_sockets()?;
+        let trace = self.compute_trace()?;
 
         // Make wasi available by default.
         let preopen_dirs = self.compute_preopen_dirs()?;
         let argv = self.compute_argv();
 
-        let mut linker = Linker::new(&engine);
-        linker.allow_unknown_exports(self.allow_unknown_exports);
+        let block_profile = match &self.block_profile {
+            Some(path) => Some(read_block_profile(path)?),
+            None => None,
+        };
 
         // Read the wasm module binary either as `*.wat` or a raw binary.
-        let module = self.load_module(linker.engine(), &self.module)?;
+        let main = self.load_module(&engine, &self.module, block_profile.as_ref())?;
+        let profiled_module = match (&self.block_profile_out, &main) {
+            (None, _) => None,
+            (Some(_), CliModule::Core(module)) => Some(module.clone()),
+            #[cfg(feature = "component-model")]
+            (Some(_), CliModule::Component(_)) => {
+                bail!("`--block-profile-out` is not supported for components")
+            }
+        };
+
+        let mut linker = match &main {
+            CliModule::Core(_) => {
+                let mut linker = Linker::new(&engine);
+                linker.allow_unknown_exports(self.allow_unknown_exports);
+                CliLinker::Core(linker)
+            }
+            #[cfg(feature = "component-model")]
+            CliModule::Component(_) => CliLinker::Component(component::Linker::new(&engine)),
+        };
 
         let host = Host::default();
         let mut store = Store::new(&engine, host);
-        populate_with_wasi(
-            &mut linker,
-            &mut store,
-            module.clone(),
-            preopen_dirs,
-            &argv,
-            &self.vars,
-            &self.common.wasi_modules.unwrap_or(WasiModules::default()),
-            self.listenfd,
-            preopen_sockets,
-        )?;
+
+        // WASI is only available to core modules at this time, components
+        // which import WASI interfaces will fail to instantiate.
+        match (&mut linker, &main) {
+            (CliLinker::Core(linker), CliModule::Core(module)) => populate_with_wasi(
+                linker,
+                &mut store,
+                module.clone(),
+                preopen_dirs,
+                &argv,
+                &self.vars,
+                &self.common.wasi_modules.unwrap_or(WasiModules::default()),
+                self.listenfd,
+                preopen_sockets,
+                &self.wasi_nn_graphs,
+                trace,
+            )?,
+            #[cfg(feature = "component-model")]
+            _ => {}
+        }
 
         // If fuel has been configured, we want to add the configured
         // fuel amount to this store.
@@ -221,21 +336,55 @@ impl RunCommand {
         // Load the preload wasm modules.
         for (name, path) in self.preloads.iter() {
             // Read the wasm module binary either as `*.wat` or a raw binary
-            let module = self.load_module(&engine, path)?;
+            let module = match self.load_module(&engine, path, None)? {
+                CliModule::Core(module) => module,
+                #[cfg(feature = "component-model")]
+                CliModule::Component(_) => bail!("components cannot be loaded with `--preload`"),
+            };
 
             // Add the module's functions to the linker.
-            linker.module(&mut store, name, &module).context(format!(
+            match &mut linker {
+                CliLinker::Core(linker) => linker.module(&mut store, name, &module),
+                #[cfg(feature = "component-model")]
+                CliLinker::Component(linker) => linker.root().module(name, &module),
+            }
+            .context(format!(
                 "failed to process preload `{}` at `{}`",
                 name,
                 path.display()
             ))?;
         }
 
+        let gdbstub = match &self.gdbstub {
+            Some(addr) => {
+                let stub = GdbStub::listen(addr.as_str())
+                    .with_context(|| format!("failed to listen for a debugger on `{}`", addr))?;
+                eprintln!("waiting for a debugger on {}", stub.local_addr()?);
+                stub.attach(&mut store);
+                Some(stub)
+            }
+            None => None,
+        };
+
         // Load the main wasm module.
-        match self
-            .load_main_module(&mut store, &mut linker, module)
-            .with_context(|| format!("failed to run main module `{}`", self.module.display()))
-        {
+        let result = self
+            .load_main_module(&mut store, &mut linker, main)
+            .with_context(|| format!("failed to run main module `{}`", self.module.display()));
+        if let Some(stub) = &gdbstub {
+            let exit_code = match &result {
+                Ok(()) => 0,
+                Err(e) => e.downcast_ref::<I32Exit>().map_or(1, |exit| exit.0),
+            };
+            stub.finish(exit_code)?;
+        }
+
+        // Write out the block profile even if the module trapped, as a
+        // partial run still says which code is hot.
+        if let (Some(path), Some(module)) = (&self.block_profile_out, &profiled_module) {
+            write_block_profile(path, module)?;
+        }
+
+        match result {
             Ok(()) => (),
             Err(e) => {
                 // Exit the process if Wasmtime understands the error;
@@ -245,9 +394,31 @@ impl RunCommand {
             }
         }
 
+        // Report whether a replay diverged from its trace, or didn't consume
+        // all of it.
+        if let Some(trace) = store.data().wasi.as_ref().and_then(|w| w.trace.as_ref()) {
+            trace.finish().context("failed to complete WASI trace")?;
+        }
+
         Ok(())
     }
 
+    fn compute_trace(&self) -> Result<Option<Trace>> {
+        if let Some(path) = &self.record_trace {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create trace '{}'", path.display()))?;
+            return Ok(Some(Trace::record(file)?));
+        }
+        if let Some(path) = &self.replay_trace {
+            let file = File::open(path)
+                .with_context(|| format!("failed to open trace '{}'", path.display()))?;
+            let trace = Trace::replay(std::io::BufReader::new(file))
+                .with_context(|| format!("failed to read trace '{}'", path.display()))?;
+            return Ok(Some(trace));
+        }
+        Ok(None)
+    }
+
     fn compute_preopen_dirs(&self) -> Result<Vec<(String, Dir)>> {
         let mut preopen_dirs = Vec::new();
 
@@ -310,8 +481,8 @@ impl RunCommand {
     fn load_main_module(
         &self,
         store: &mut Store<Host>,
-        linker: &mut Linker<Host>,
-        module: Module,
+        linker: &mut CliLinker,
+        main: CliModule,
     ) -> Result<()> {
         if let Some(timeout) = self.wasm_timeout {
             store.set_epoch_deadline(1);
@@ -322,6 +493,62 @@ impl RunCommand {
             });
         }
 
+        // Sample the guest's stack on every epoch tick, and write out the
+        // profile once it has finished running, even if it trapped.
+        if let Some(Profile::Guest { path, interval }) = &self.common.profile {
+            store.data_mut().guest_profiler = Some(GuestProfiler::new());
+            store.set_epoch_deadline(1);
+            store.epoch_deadline_callback(|mut store| {
+                let mut profiler = store.data_mut().guest_profiler.take().unwrap();
+                profiler.sample(&store);
+                store.data_mut().guest_profiler = Some(profiler);
+                Ok(1)
+            });
+            let engine = store.engine().clone();
+            let interval = *interval;
+            thread::spawn(move || loop {
+                thread::sleep(interval);
+                engine.increment_epoch();
+            });
+
+            let result = self.load_main_module_kind(store, linker, main);
+            let profiler = store.data_mut().guest_profiler.take().unwrap();
+            let output = File::create(path)
+                .with_context(|| format!("failed to create guest profile `{}`", path))?;
+            profiler
+                .finish(BufWriter::new(output))
+                .with_context(|| format!("failed to write guest profile `{}`", path))?;
+            return result;
+        }
+
+        self.load_main_module_kind(store, linker, main)
+    }
+
+    fn load_main_module_kind(
+        &self,
+        store: &mut Store<Host>,
+        linker: &mut CliLinker,
+        main: CliModule,
+    ) -> Result<()> {
+        match (linker, main) {
+            (CliLinker::Core(linker), CliModule::Core(module)) => {
+                self.load_main_core_module(store, linker, module)
+            }
+            #[cfg(feature = "component-model")]
+            (CliLinker::Component(linker), CliModule::Component(component)) => {
+                self.load_main_component(store, linker, component)
+            }
+            #[cfg(feature = "component-model")]
+            _ => unreachable!("linker and main module kinds are always paired"),
+        }
+    }
+
+    fn load_main_core_module(
+        &self,
+        store: &mut Store<Host>,
+        linker: &mut Linker<Host>,
+        module: Module,
+    ) -> Result<()> {
         // The main module might be allowed to have unknown imports, which
         // should be defined as traps:
         if self.trap_unknown_imports {
@@ -400,33 +627,18 @@ impl RunCommand {
         // Invoke the function and then afterwards print all the results that came
         // out, if there are any.
         let mut results = vec![Val::null(); ty.results().len()];
-        let invoke_res = func.call(store, &values, &mut results).with_context(|| {
-            if let Some(name) = name {
-                format!("failed to invoke `{}`", name)
-            } else {
-                format!("failed to invoke command default")
-            }
-        });
-
-        if let Err(err) = invoke_res {
-            let err = if err.is::<wasmtime::Trap>() {
-                if let Some(coredump_path) = self.coredump_on_trap.as_ref() {
-                    let source_name = self.module.to_str().unwrap_or_else(|| "unknown");
-
-                    if let Err(coredump_err) = generate_coredump(&err, &source_name, coredump_path)
-                    {
-                        eprintln!("warning: coredump failed to generate: {}", coredump_err);
-                        err
-                    } else {
-                        err.context(format!("core dumped at {}", coredump_path))
-                    }
+        let invoke_res = func
+            .call(&mut *store, &values, &mut results)
+            .with_context(|| {
+                if let Some(name) = name {
+                    format!("failed to invoke `{}`", name)
                 } else {
-                    err
+                    format!("failed to invoke command default")
                 }
-            } else {
-                err
-            };
-            return Err(err);
+            });
+
+        if let Err(err) = invoke_res {
+            return Err(self.handle_trap(store, err));
         }
 
         if !results.is_empty() {
@@ -451,16 +663,233 @@ impl RunCommand {
         Ok(())
     }
 
-    fn load_module(&self, engine: &Engine, path: &Path) -> Result<Module> {
+    #[cfg(feature = "component-model")]
+    fn load_main_component(
+        &self,
+        store: &mut Store<Host>,
+        linker: &mut component::Linker<Host>,
+        component: Component,
+    ) -> Result<()> {
+        if self.trap_unknown_imports || self.default_values_unknown_imports {
+            bail!("stubbing unknown imports is not supported for components");
+        }
+
+        let instance = linker
+            .instantiate(&mut *store, &component)
+            .context(format!("failed to instantiate {:?}", self.module))?;
+
+        // Components have no equivalent of a command's default export, so the
+        // function to call must always be named.
+        let name = match self.invoke.as_ref() {
+            Some(name) => name,
+            None => bail!("running a component requires naming an export with `--invoke`"),
+        };
+        let func = instance
+            .get_func(&mut *store, name)
+            .ok_or_else(|| anyhow!("no export named `{}` found", name))?;
+        self.invoke_component_func(store, func, name)
+    }
+
+    #[cfg(feature = "component-model")]
+    fn invoke_component_func(
+        &self,
+        store: &mut Store<Host>,
+        func: component::Func,
+        name: &str,
+    ) -> Result<()> {
+        use wasmtime::component::{Type, Val};
+
+        let params = func.params(&*store);
+        let mut args = self.module_args.iter();
+        let mut values = Vec::new();
+        for ty in params.iter() {
+            let val = match args.next() {
+                Some(s) => s,
+                None => bail!("not enough arguments for `{}`", name),
+            };
+            values.push(match ty {
+                Type::Bool => Val::Bool(val.parse()?),
+                Type::S8 => Val::S8(val.parse()?),
+                Type::U8 => Val::U8(val.parse()?),
+                Type::S16 => Val::S16(val.parse()?),
+                Type::U16 => Val::U16(val.parse()?),
+                Type::S32 => Val::S32(val.parse()?),
+                Type::U32 => Val::U32(val.parse()?),
+                Type::S64 => Val::S64(val.parse()?),
+                Type::U64 => Val::U64(val.parse()?),
+                Type::Float32 => Val::Float32(val.parse()?),
+                Type::Float64 => Val::Float64(val.parse()?),
+                Type::Char => Val::Char(val.parse()?),
+                Type::String => Val::String(val.as_str().into()),
+                t => bail!("unsupported argument type {:?}", t),
+            });
+        }
+
+        // Invoke the function and then afterwards print all the results that
+        // came out, if there are any. The placeholder values here are
+        // overwritten by the call.
+        let mut results = vec![Val::Bool(false); func.results(&*store).len()];
+        let invoke_res = func
+            .call(&mut *store, &values, &mut results)
+            .with_context(|| format!("failed to invoke `{}`", name));
+        if let Err(err) = invoke_res {
+            return Err(self.handle_trap(store, err));
+        }
+        func.post_return(&mut *store)?;
+
+        for result in results {
+            match result {
+                Val::Bool(b) => println!("{}", b),
+                Val::S8(i) => println!("{}", i),
+                Val::U8(i) => println!("{}", i),
+                Val::S16(i) => println!("{}", i),
+                Val::U16(i) => println!("{}", i),
+                Val::S32(i) => println!("{}", i),
+                Val::U32(i) => println!("{}", i),
+                Val::S64(i) => println!("{}", i),
+                Val::U64(i) => println!("{}", i),
+                Val::Float32(f) => println!("{}", f),
+                Val::Float64(f) => println!("{}", f),
+                Val::Char(c) => println!("{}", c),
+                Val::String(s) => println!("{}", s),
+                other => println!("{:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generates a coredump for `err`, if one was requested and `err` is a
+    /// trap, returning the error to report to the user.
+    fn handle_trap(&self, store: &mut Store<Host>, err: anyhow::Error) -> anyhow::Error {
+        if !err.is::<wasmtime::Trap>() {
+            return err;
+        }
+        let coredump_pattern = match self.coredump_on_trap.as_ref() {
+            Some(pattern) => pattern,
+            None => return err,
+        };
+        let source_name = self.module.to_str().unwrap_or_else(|| "unknown");
+
+        let result = expand_coredump_path(coredump_pattern, &self.module).and_then(|path| {
+            generate_coredump(store, &err, source_name, &path)?;
+            Ok(path)
+        });
+        match result {
+            Ok(coredump_path) => err.context(format!("core dumped at {}", coredump_path)),
+            Err(coredump_err) => {
+                eprintln!("warning: coredump failed to generate: {}", coredump_err);
+                err
+            }
+        }
+    }
+
+    fn load_module(
+        &self,
+        engine: &Engine,
+        path: &Path,
+        block_profile: Option<&BlockProfile>,
+    ) -> Result<CliModule> {
+        // Precompiled artifacts are only loaded through a file mapping, which
+        // means that they can't be read from a pipe such as stdin.
         if self.allow_precompiled {
-            unsafe { Module::from_trusted_file(engine, path) }
-        } else {
-            Module::from_file(engine, path)
-                .context("if you're trying to run a precompiled module, pass --allow-precompiled")
+            match engine.detect_precompiled_file(path)? {
+                Some(Precompiled::Module) => {
+                    if block_profile.is_some() {
+                        bail!("a block profile cannot be applied to a precompiled module");
+                    }
+                    let module = unsafe { Module::deserialize_file(engine, path)? };
+                    return Ok(CliModule::Core(module));
+                }
+                #[cfg(feature = "component-model")]
+                Some(Precompiled::Component) => {
+                    let component = unsafe { Component::deserialize_file(engine, path)? };
+                    return Ok(CliModule::Component(component));
+                }
+                #[cfg(not(feature = "component-model"))]
+                Some(Precompiled::Component) => {
+                    bail!("support for components was not enabled at compile time")
+                }
+                None => {}
+            }
+        }
+
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read input file `{}`", path.display()))?;
+        if engine.detect_precompiled(&bytes).is_some() {
+            bail!("if you're trying to run a precompiled module, pass --allow-precompiled");
+        }
+        let wasm = wat::parse_bytes(&bytes).map_err(|mut e| {
+            e.set_path(path);
+            e
+        })?;
+
+        #[cfg(feature = "component-model")]
+        if is_component(&wasm) {
+            return Ok(CliModule::Component(Component::new(engine, &wasm)?));
         }
+
+        let module = match block_profile {
+            Some(profile) => Module::new_with_block_profile(engine, &wasm, profile)?,
+            None => Module::new(engine, &wasm)?,
+        };
+        Ok(CliModule::Core(module))
     }
 }
 
+/// The main wasm binary being run, either a core module or a component.
+enum CliModule {
+    Core(Module),
+    #[cfg(feature = "component-model")]
+    Component(Component),
+}
+
+/// The linker used to instantiate a `CliModule`, whose kind always matches
+/// the kind of the main module.
+enum CliLinker {
+    Core(Linker<Host>),
+    #[cfg(feature = "component-model")]
+    Component(component::Linker<Host>),
+}
+
+/// Tests whether `wasm` looks like a component by sniffing its header with
+/// the current component model proposal encoding.
+#[cfg(feature = "component-model")]
+fn is_component(wasm: &[u8]) -> bool {
+    matches!(
+        wasmparser::Parser::new(0).parse(wasm, true),
+        Ok(wasmparser::Chunk::Parsed {
+            payload: wasmparser::Payload::Version {
+                encoding: wasmparser::Encoding::Component,
+                ..
+            },
+            ..
+        })
+    )
+}
+
+fn read_block_profile(path: &Path) -> Result<BlockProfile> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("failed to read block profile `{}`", path.display()))?;
+    BlockProfile::deserialize(&bytes)
+        .with_context(|| format!("failed to read block profile `{}`", path.display()))
+}
+
+/// Writes the block profile collected by `module` to `path`, adding it to the
+/// profile already in that file, if any.
+fn write_block_profile(path: &Path, module: &Module) -> Result<()> {
+    let mut profile = if path.exists() {
+        read_block_profile(path)?
+    } else {
+        BlockProfile::new()
+    };
+    if let Some(collected) = module.block_profile() {
+        profile.merge(&collected);
+    }
+    std::fs::write(path, profile.serialize()?)
+        .with_context(|| format!("failed to write block profile `{}`", path.display()))
+}
+
 #[derive(Default, Clone)]
 struct Host {
     wasi: Option<wasmtime_wasi::WasiCtx>,
@@ -470,6 +899,7 @@ struct Host {
     wasi_nn: Option<Arc<WasiNnCtx>>,
     #[cfg(feature = "wasi-threads")]
     wasi_threads: Option<Arc<WasiThreadsCtx<Host>>>,
+    guest_profiler: Option<GuestProfiler>,
 }
 
 /// Populates the given `Linker` with WASI APIs.
@@ -483,6 +913,8 @@ fn populate_with_wasi(
     wasi_modules: &WasiModules,
     listenfd: bool,
     mut tcplisten: Vec<TcpListener>,
+    wasi_nn_graphs: &[(String, PathBuf)],
+    trace: Option<Trace>,
 ) -> Result<()> {
     if wasi_modules.wasi_common {
         wasmtime_wasi::add_to_linker(linker, |host| host.wasi.as_mut().unwrap())?;
@@ -507,6 +939,10 @@ fn populate_with_wasi(
             builder = builder.preopened_dir(dir, name)?;
         }
 
+        if let Some(trace) = trace {
+            builder = builder.trace(trace);
+        }
+
         store.data_mut().wasi = Some(builder.build());
     }
 
@@ -544,8 +980,21 @@ fn populate_with_wasi(
                 Arc::get_mut(host.wasi_nn.as_mut().unwrap())
                     .expect("wasi-nn is not implemented with multi-threading support")
             })?;
-            store.data_mut().wasi_nn = Some(Arc::new(WasiNnCtx::new()?));
+            let mut ctx = WasiNnCtx::new()?;
+            for (name, path) in wasi_nn_graphs {
+                let graph = load_wasi_nn_graph(&mut ctx, path).with_context(|| {
+                    format!(
+                        "failed to load wasi-nn graph `{}` from `{}`",
+                        name,
+                        path.display()
+                    )
+                })?;
+                ctx.register_graph(name.as_str(), graph);
+            }
+            store.data_mut().wasi_nn = Some(Arc::new(ctx));
         }
+    } else if !wasi_nn_graphs.is_empty() {
+        bail!("`--wasi-nn-graph` requires wasi-nn to be enabled (`--wasi-modules experimental-wasi-nn`)");
     }
 
     if wasi_modules.wasi_threads {
@@ -572,6 +1021,42 @@ fn populate_with_wasi(
     Ok(())
 }
 
+/// Loads the model files at `path` with the wasi-nn backend matching their
+/// format.
+#[cfg(feature = "wasi-nn")]
+fn load_wasi_nn_graph(ctx: &mut WasiNnCtx, path: &Path) -> Result<SharedGraph> {
+    let (encoding, files) = if path.is_dir() {
+        if path.join("model.xml").exists() {
+            (
+                GraphEncoding::Openvino,
+                vec![path.join("model.xml"), path.join("model.bin")],
+            )
+        } else if path.join("model.onnx").exists() {
+            (GraphEncoding::Onnx, vec![path.join("model.onnx")])
+        } else {
+            bail!("directory does not contain `model.xml` or `model.onnx`");
+        }
+    } else {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("xml") => (
+                GraphEncoding::Openvino,
+                vec![path.to_path_buf(), path.with_extension("bin")],
+            ),
+            Some("onnx") => (GraphEncoding::Onnx, vec![path.to_path_buf()]),
+            _ => bail!("unknown model format; expected an `.xml` or `.onnx` file"),
+        }
+    };
+
+    let mut builders = Vec::with_capacity(files.len());
+    for file in files.iter() {
+        let bytes =
+            std::fs::read(file).with_context(|| format!("failed to read `{}`", file.display()))?;
+        builders.push(bytes);
+    }
+    let builders = builders.iter().map(|b| b.as_slice()).collect::<Vec<_>>();
+    Ok(ctx.load_graph(&builders, encoding, ExecutionTarget::Cpu)?)
+}
+
 #[cfg(not(unix))]
 fn ctx_set_listenfd(num_fd: usize, builder: WasiCtxBuilder) -> Result<(usize, WasiCtxBuilder)> {
     Ok((num_fd, builder))
@@ -604,31 +1089,49 @@ fn ctx_set_listenfd(num_fd: usize, builder: WasiCtxBuilder) -> Result<(usize, Wa
     Ok((num_fd, builder))
 }
 
-fn generate_coredump(err: &anyhow::Error, source_name: &str, coredump_path: &str) -> Result<()> {
-    let bt = err
-        .downcast_ref::<wasmtime::WasmBacktrace>()
-        .ok_or_else(|| anyhow!("no wasm backtrace found to generate coredump with"))?;
-
-    let mut coredump_builder =
-        wasm_coredump_builder::CoredumpBuilder::new().executable_name(source_name);
-
-    {
-        let mut thread_builder = wasm_coredump_builder::ThreadBuilder::new().thread_name("main");
-
-        for frame in bt.frames() {
-            let coredump_frame = wasm_coredump_builder::FrameBuilder::new()
-                .codeoffset(frame.func_offset().unwrap_or(0) as u32)
-                .funcidx(frame.func_index())
-                .build();
-            thread_builder.add_frame(coredump_frame);
+/// Expands the `%`-patterns within a `--coredump-on-trap` path.
+///
+/// Supported patterns are `%p` for the process ID, `%t` for the number of
+/// seconds since the Unix epoch, `%m` for the name of the module being run
+/// without its extension, and `%%` for a literal `%`.
+fn expand_coredump_path(pattern: &str, module: &Path) -> Result<String> {
+    let mut path = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            path.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => path.push('%'),
+            Some('p') => path.push_str(&std::process::id().to_string()),
+            Some('t') => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                path.push_str(&now.as_secs().to_string());
+            }
+            Some('m') => match module.file_stem() {
+                Some(stem) => path.push_str(&stem.to_string_lossy()),
+                None => path.push_str("unknown"),
+            },
+            Some(c) => bail!("unknown pattern `%{}` in coredump-on-trap path", c),
+            None => bail!("the coredump-on-trap path cannot end with a lone `%`"),
         }
-
-        coredump_builder.add_thread(thread_builder.build());
     }
+    Ok(path)
+}
 
-    let coredump = coredump_builder
-        .serialize()
-        .map_err(|err| anyhow!("failed to serialize coredump: {}", err))?;
+fn generate_coredump(
+    store: &mut Store<Host>,
+    err: &anyhow::Error,
+    source_name: &str,
+    coredump_path: &str,
+) -> Result<()> {
+    let coredump = err
+        .downcast_ref::<wasmtime::WasmCoreDump>()
+        .ok_or_else(|| anyhow!("no wasm coredump found in the error"))?
+        .serialize(store, source_name);
 
     let mut f = File::create(coredump_path)
         .context(format!("failed to create file at `{}`", coredump_path))?;
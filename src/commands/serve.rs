@@ -0,0 +1,573 @@
+//! The module that implements the `wasmtime serve` command.
+
+use super::run::parse_dur;
+use anyhow::{anyhow, bail, Context as _, Result};
+use clap::Parser;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use wasmtime::{
+    AsContextMut, Caller, Engine, Extern, InstanceAllocationStrategy, InstancePre, Linker, Memory,
+    Module, PoolingAllocationConfig, Store,
+};
+use wasmtime_cli_flags::CommonOptions;
+use wasmtime_wasi::{I32Exit, WasiCtx, WasiCtxBuilder};
+
+/// The name of the module from which guests import the HTTP interface.
+const HTTP_MODULE: &str = "wasi_http";
+
+/// The name of the function that guests export to handle a request.
+const HANDLER_EXPORT: &str = "handle";
+
+/// Limits on incoming requests and outgoing responses, to keep a misbehaving
+/// client or guest from exhausting host memory.
+const MAX_LINE_LEN: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+const MAX_BODY_LEN: usize = 16 << 20;
+
+fn parse_env_var(s: &str) -> Result<(String, String)> {
+    let parts: Vec<_> = s.splitn(2, '=').collect();
+    if parts.len() != 2 {
+        bail!("must be of the form `key=value`");
+    }
+    Ok((parts[0].to_owned(), parts[1].to_owned()))
+}
+
+/// Serves HTTP requests with a WebAssembly module, creating a fresh instance
+/// for every request.
+#[derive(Parser)]
+#[clap(name = "serve")]
+pub struct ServeCommand {
+    #[clap(flatten)]
+    common: CommonOptions,
+
+    /// The socket address to listen on
+    #[clap(long, value_name = "SOCKET ADDRESS", default_value = "127.0.0.1:8080")]
+    addr: String,
+
+    /// The maximum number of requests handled concurrently, which is also the
+    /// number of instance slots reserved in the pooling allocator; further
+    /// connections wait until a request is done
+    #[clap(long, value_name = "N", default_value = "100")]
+    max_instances: u32,
+
+    /// How long a client may take to send or receive each part of a request
+    /// or response before its connection is dropped (1, 2s, 100ms, etc)
+    #[clap(
+        long,
+        value_name = "TIME",
+        default_value = "30",
+        parse(try_from_str = parse_dur)
+    )]
+    io_timeout: Duration,
+
+    /// Pass an environment variable to the handler
+    #[clap(long = "env", number_of_values = 1, value_name = "NAME=VAL", parse(try_from_str = parse_env_var))]
+    vars: Vec<(String, String)>,
+
+    /// The path of the WebAssembly module handling requests
+    #[clap(required = true, value_name = "MODULE")]
+    module: PathBuf,
+}
+
+impl ServeCommand {
+    /// Executes the command.
+    pub fn execute(&self) -> Result<()> {
+        self.common.init_logging();
+
+        if self.max_instances == 0 {
+            bail!("--max-instances must be at least 1");
+        }
+        if self.io_timeout.is_zero() {
+            bail!("--io-timeout must not be zero");
+        }
+
+        let mut config = self.common.config(None)?;
+        let mut pooling = PoolingAllocationConfig::default();
+        pooling.instance_count(self.max_instances);
+        config.allocation_strategy(InstanceAllocationStrategy::Pooling(pooling));
+        let engine = Engine::new(&config)?;
+
+        let module = Module::from_file(&engine, &self.module)
+            .with_context(|| format!("failed to load module `{}`", self.module.display()))?;
+        match module.get_export(HANDLER_EXPORT) {
+            Some(ty) => {
+                let ty = ty
+                    .func()
+                    .ok_or_else(|| anyhow!("export `{}` is not a function", HANDLER_EXPORT))?;
+                if ty.params().len() != 0 || ty.results().len() != 0 {
+                    bail!("export `{}` must have type `[] -> []`", HANDLER_EXPORT);
+                }
+            }
+            None => bail!("module does not export a `{}` function", HANDLER_EXPORT),
+        }
+
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::add_to_linker(&mut linker, |host: &mut ServeHost| &mut host.wasi)?;
+        add_http_to_linker(&mut linker)?;
+        let server = Arc::new(Server {
+            pre: linker.instantiate_pre(&module)?,
+            vars: self.vars.clone(),
+            io_timeout: self.io_timeout,
+        });
+
+        // Each connection is handled by one of a fixed number of workers, so
+        // that no more than `max_instances` requests are in flight. The
+        // channel has no buffer, which leaves connections that arrive while
+        // all workers are busy in the listener's backlog.
+        let (sender, receiver) = mpsc::sync_channel::<TcpStream>(0);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..self.max_instances {
+            let server = server.clone();
+            let receiver = receiver.clone();
+            thread::spawn(move || loop {
+                let stream = receiver.lock().unwrap().recv();
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => return,
+                };
+                // A panic only loses the connection that caused it, not the
+                // worker.
+                match panic::catch_unwind(AssertUnwindSafe(|| server.handle_connection(stream))) {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => eprintln!("error: {:?}", e),
+                    Err(_) => eprintln!("error: panicked while handling a connection"),
+                }
+            });
+        }
+
+        let listener = TcpListener::bind(&self.addr)
+            .with_context(|| format!("failed to bind to address '{}'", self.addr))?;
+        eprintln!("Serving HTTP on http://{}/", listener.local_addr()?);
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("error: failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            sender.send(stream)?;
+        }
+        Ok(())
+    }
+}
+
+/// State shared by all connections.
+struct Server {
+    pre: InstancePre<ServeHost>,
+    vars: Vec<(String, String)>,
+    io_timeout: Duration,
+}
+
+impl Server {
+    fn handle_connection(&self, stream: TcpStream) -> Result<()> {
+        // Keep a client that stops sending or receiving from holding on to a
+        // worker forever.
+        stream.set_read_timeout(Some(self.io_timeout))?;
+        stream.set_write_timeout(Some(self.io_timeout))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let response = match read_request(&mut reader) {
+            Ok(request) => self.handle_request(request).unwrap_or_else(|e| {
+                eprintln!("error: failed to handle request: {:?}", e);
+                OutgoingResponse::error(500)
+            }),
+            Err(e) => {
+                eprintln!("error: malformed request: {:?}", e);
+                OutgoingResponse::error(400)
+            }
+        };
+        write_response(stream, &response)
+    }
+
+    fn handle_request(&self, request: IncomingRequest) -> Result<OutgoingResponse> {
+        let wasi = WasiCtxBuilder::new()
+            .inherit_stdout()
+            .inherit_stderr()
+            .envs(&self.vars)?
+            .build();
+        let mut store = Store::new(
+            self.pre.module().engine(),
+            ServeHost {
+                wasi,
+                request,
+                response: OutgoingResponse::default(),
+            },
+        );
+
+        let instance = self.pre.instantiate(&mut store)?;
+        if let Some(init) = instance.get_func(&mut store, "_initialize") {
+            init.typed::<(), ()>(&store)?.call(&mut store, ())?;
+        }
+        let handler = instance.get_typed_func::<(), ()>(&mut store, HANDLER_EXPORT)?;
+        match handler.call(&mut store, ()) {
+            Ok(()) => {}
+            // A handler which exits successfully still produces a response.
+            Err(e) if matches!(e.downcast_ref::<I32Exit>(), Some(I32Exit(0))) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(store.into_data().response)
+    }
+}
+
+/// The per-request host state.
+struct ServeHost {
+    wasi: WasiCtx,
+    request: IncomingRequest,
+    response: OutgoingResponse,
+}
+
+#[derive(Debug, Default)]
+struct IncomingRequest {
+    method: String,
+    uri: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    body_pos: usize,
+}
+
+impl IncomingRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct OutgoingResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Default for OutgoingResponse {
+    fn default() -> Self {
+        OutgoingResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+impl OutgoingResponse {
+    fn error(status: u16) -> Self {
+        OutgoingResponse {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+/// Defines the `wasi_http` functions guests use to inspect the incoming
+/// request and build up the outgoing response.
+///
+/// Functions that return data to the guest copy as much as fits in the
+/// provided buffer and return the full length, so a guest can retry with a
+/// larger buffer if needed.
+fn add_http_to_linker(linker: &mut Linker<ServeHost>) -> Result<()> {
+    linker.func_wrap(
+        HTTP_MODULE,
+        "request_method",
+        |mut caller: Caller<'_, ServeHost>, ptr: u32, len: u32| -> Result<u32> {
+            let method = caller.data().request.method.clone();
+            copy_out(&mut caller, method.as_bytes(), ptr, len)
+        },
+    )?;
+    linker.func_wrap(
+        HTTP_MODULE,
+        "request_uri",
+        |mut caller: Caller<'_, ServeHost>, ptr: u32, len: u32| -> Result<u32> {
+            let uri = caller.data().request.uri.clone();
+            copy_out(&mut caller, uri.as_bytes(), ptr, len)
+        },
+    )?;
+    linker.func_wrap(
+        HTTP_MODULE,
+        "request_header",
+        |mut caller: Caller<'_, ServeHost>,
+         name_ptr: u32,
+         name_len: u32,
+         ptr: u32,
+         len: u32|
+         -> Result<i32> {
+            let name = read_string(&mut caller, name_ptr, name_len)?;
+            let value = match caller.data().request.header(&name) {
+                Some(value) => value.to_string(),
+                None => return Ok(-1),
+            };
+            Ok(copy_out(&mut caller, value.as_bytes(), ptr, len)? as i32)
+        },
+    )?;
+    linker.func_wrap(
+        HTTP_MODULE,
+        "request_body",
+        |mut caller: Caller<'_, ServeHost>, ptr: u32, len: u32| -> Result<u32> {
+            let request = &caller.data().request;
+            let end = request.body.len().min(request.body_pos + len as usize);
+            let chunk = request.body[request.body_pos..end].to_vec();
+            memory(&mut caller)?.write(&mut caller, ptr as usize, &chunk)?;
+            caller.data_mut().request.body_pos = end;
+            Ok(chunk.len() as u32)
+        },
+    )?;
+    linker.func_wrap(
+        HTTP_MODULE,
+        "response_status",
+        |mut caller: Caller<'_, ServeHost>, status: u32| -> Result<()> {
+            if !(100..1000).contains(&status) {
+                bail!("invalid HTTP status code {}", status);
+            }
+            caller.data_mut().response.status = status as u16;
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HTTP_MODULE,
+        "response_header",
+        |mut caller: Caller<'_, ServeHost>,
+         name_ptr: u32,
+         name_len: u32,
+         value_ptr: u32,
+         value_len: u32|
+         -> Result<()> {
+            let name = read_string(&mut caller, name_ptr, name_len)?;
+            let value = read_string(&mut caller, value_ptr, value_len)?;
+            if name.is_empty() || name.bytes().any(|b| b <= b' ' || b == b':' || b >= 0x7f) {
+                bail!("invalid HTTP header name {:?}", name);
+            }
+            if value.bytes().any(|b| b == b'\r' || b == b'\n') {
+                bail!("invalid HTTP header value {:?}", value);
+            }
+            caller.data_mut().response.headers.push((name, value));
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HTTP_MODULE,
+        "response_body",
+        |mut caller: Caller<'_, ServeHost>, ptr: u32, len: u32| -> Result<()> {
+            let memory = memory(&mut caller)?;
+            let (data, host) = memory.data_and_store_mut(&mut caller);
+            let chunk = guest_slice(data, ptr, len)?;
+            if chunk.len() > MAX_BODY_LEN - host.response.body.len() {
+                bail!("response body too large");
+            }
+            host.response.body.extend_from_slice(chunk);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn memory(caller: &mut Caller<'_, ServeHost>) -> Result<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => bail!("module does not export a `memory`"),
+    }
+}
+
+fn copy_out(caller: &mut Caller<'_, ServeHost>, data: &[u8], ptr: u32, len: u32) -> Result<u32> {
+    let n = data.len().min(len as usize);
+    memory(caller)?.write(caller.as_context_mut(), ptr as usize, &data[..n])?;
+    Ok(u32::try_from(data.len())?)
+}
+
+fn read_string(caller: &mut Caller<'_, ServeHost>, ptr: u32, len: u32) -> Result<String> {
+    let memory = memory(caller)?;
+    let bytes = guest_slice(memory.data(&*caller), ptr, len)?;
+    let s = std::str::from_utf8(bytes).context("string is not valid UTF-8")?;
+    Ok(s.to_string())
+}
+
+/// Borrows `len` bytes at `ptr` from a guest's memory, without allocating
+/// anything for lengths the guest made up.
+fn guest_slice(data: &[u8], ptr: u32, len: u32) -> Result<&[u8]> {
+    let start = ptr as usize;
+    data.get(start..start + len as usize)
+        .ok_or_else(|| anyhow!("out of bounds memory access"))
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if line.len() > MAX_LINE_LEN {
+        bail!("line too long");
+    }
+    if line.last() != Some(&b'\n') {
+        bail!("unexpected end of request");
+    }
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).context("request is not valid UTF-8")
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<IncomingRequest> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split(' ');
+    let (method, uri) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(uri), Some(version), None) if version.starts_with("HTTP/1.") => {
+            (method.to_string(), uri.to_string())
+        }
+        _ => bail!("invalid request line {:?}", request_line),
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            bail!("too many headers");
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid header line {:?}", line))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = IncomingRequest {
+        method,
+        uri,
+        headers,
+        ..IncomingRequest::default()
+    };
+    if let Some(encoding) = request.header("transfer-encoding") {
+        if !encoding.eq_ignore_ascii_case("chunked") {
+            bail!("unsupported transfer encoding {:?}", encoding);
+        }
+        request.body = read_chunked_body(reader)?;
+    } else if let Some(len) = request.header("content-length") {
+        let len: usize = len.parse().context("invalid content length")?;
+        if len > MAX_BODY_LEN {
+            bail!("request body too large");
+        }
+        request.body = vec![0; len];
+        reader.read_exact(&mut request.body)?;
+    }
+    Ok(request)
+}
+
+fn read_chunked_body(reader: &mut impl BufRead) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        let size = line.split(';').next().unwrap().trim();
+        let size = usize::from_str_radix(size, 16).context("invalid chunk size")?;
+        if size == 0 {
+            break;
+        }
+        if size > MAX_BODY_LEN - body.len() {
+            bail!("request body too large");
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        if !read_line(reader)?.is_empty() {
+            bail!("missing chunk terminator");
+        }
+    }
+    // Skip any trailers.
+    while !read_line(reader)?.is_empty() {}
+    Ok(body)
+}
+
+fn write_response(stream: TcpStream, response: &OutgoingResponse) -> Result<()> {
+    let mut stream = std::io::BufWriter::new(stream);
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        reason_phrase(response.status)
+    )?;
+    for (name, value) in response.headers.iter() {
+        // These are managed by the host, since the body is buffered and the
+        // connection is closed after every response.
+        if ["content-length", "transfer-encoding", "connection"]
+            .iter()
+            .any(|h| name.eq_ignore_ascii_case(h))
+        {
+            continue;
+        }
+        write!(stream, "{}: {}\r\n", name, value)?;
+    }
+    write!(stream, "content-length: {}\r\n", response.body.len())?;
+    write!(stream, "connection: close\r\n\r\n")?;
+    stream.write_all(&response.body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_request() -> Result<()> {
+        let mut input: &[u8] =
+            b"POST /echo?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello";
+        let request = read_request(&mut input)?;
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.uri, "/echo?x=1");
+        assert_eq!(request.header("host"), Some("localhost"));
+        assert_eq!(request.body, b"hello");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_chunked_request() -> Result<()> {
+        let mut input: &[u8] = b"PUT / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+            5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\n\r\n";
+        let request = read_request(&mut input)?;
+        assert_eq!(request.body, b"hello world");
+        Ok(())
+    }
+
+    #[test]
+    fn reject_malformed_request() {
+        let mut input: &[u8] = b"GET /\r\n\r\n";
+        assert!(read_request(&mut input).is_err());
+        let mut input: &[u8] = b"GET / HTTP/1.1\r\nno-colon\r\n\r\n";
+        assert!(read_request(&mut input).is_err());
+    }
+
+    #[test]
+    fn reject_oversized_chunk() {
+        let mut input: &[u8] = b"PUT / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+            1\r\na\r\nffffffffffffffff\r\n";
+        let err = read_request(&mut input).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+}
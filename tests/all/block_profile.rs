@@ -0,0 +1,113 @@
+use anyhow::Result;
+use wasmtime::*;
+
+// Sums the odd numbers below `n`, with a branch which is taken on every other
+// iteration and a `br_table` whose default arm never runs.
+const GUEST: &str = r#"
+    (module
+        (func (export "sum_odd") (param $n i32) (result i32)
+            (local $i i32)
+            (local $sum i32)
+            (block $done
+                (loop $loop
+                    (br_if $done (i32.ge_u (local.get $i) (local.get $n)))
+                    (if (i32.and (local.get $i) (i32.const 1))
+                        (then
+                            (local.set $sum (i32.add (local.get $sum) (local.get $i)))))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br $loop)))
+            (local.get $sum))
+        (func (export "classify") (param i32) (result i32)
+            (block $default
+                (block $one
+                    (block $zero
+                        (br_table $zero $one $default (i32.and (local.get 0) (i32.const 1))))
+                    (return (i32.const 10)))
+                (return (i32.const 11)))
+            (i32.const 12))
+        (func (export "unused") (result i32)
+            (i32.const 0))
+    )
+"#;
+
+fn profiling_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.block_profiling(true);
+    Engine::new(&config)
+}
+
+fn run(engine: &Engine, module: &Module) -> Result<()> {
+    let mut store = Store::new(engine, ());
+    let instance = Instance::new(&mut store, module, &[])?;
+    let sum_odd = instance.get_typed_func::<i32, i32>(&mut store, "sum_odd")?;
+    let classify = instance.get_typed_func::<i32, i32>(&mut store, "classify")?;
+    assert_eq!(sum_odd.call(&mut store, 10)?, 25);
+    for i in 0..5 {
+        assert_eq!(classify.call(&mut store, i)?, 10 + (i & 1));
+    }
+    Ok(())
+}
+
+#[test]
+fn disabled_by_default() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, GUEST)?;
+    run(&engine, &module)?;
+    assert!(module.block_profile().is_none());
+    Ok(())
+}
+
+#[test]
+fn collect_and_apply() -> Result<()> {
+    let engine = profiling_engine()?;
+    let module = Module::new(&engine, GUEST)?;
+    assert!(module.block_profile().unwrap().is_empty());
+
+    run(&engine, &module)?;
+    let profile = module.block_profile().unwrap();
+    assert!(!profile.is_empty());
+
+    // The counts of all instances of a module are shared.
+    run(&engine, &module)?;
+    let mut twice = profile.clone();
+    twice.merge(&profile);
+    assert_eq!(module.block_profile().unwrap(), twice);
+
+    // A profile survives serialization, and the code compiled with it behaves
+    // the same.
+    let profile = module.block_profile().unwrap();
+    assert_eq!(BlockProfile::deserialize(&profile.serialize()?)?, profile);
+    let engine = Engine::default();
+    let module = Module::new_with_block_profile(&engine, GUEST, &profile)?;
+    run(&engine, &module)?;
+    Ok(())
+}
+
+#[test]
+fn profiles_of_profiled_modules_match() -> Result<()> {
+    // Block numbers don't depend on the profile a module was compiled with,
+    // so profiles collected from a module compiled with one apply as well.
+    let engine = profiling_engine()?;
+    let module = Module::new(&engine, GUEST)?;
+    run(&engine, &module)?;
+    let profile = module.block_profile().unwrap();
+
+    let module = Module::new_with_block_profile(&engine, GUEST, &profile)?;
+    run(&engine, &module)?;
+    assert_eq!(module.block_profile().unwrap(), profile);
+    Ok(())
+}
+
+#[test]
+fn deserialize_invalid() {
+    assert!(BlockProfile::deserialize(b"").is_err());
+    assert!(BlockProfile::deserialize(b"\0asm\x01\0\0\0").is_err());
+
+    let mut bytes = BlockProfile::new().serialize().unwrap();
+    assert_eq!(
+        BlockProfile::deserialize(&bytes).unwrap(),
+        BlockProfile::new()
+    );
+    bytes.truncate(bytes.len() - 1);
+    assert!(BlockProfile::deserialize(&bytes).is_err());
+}
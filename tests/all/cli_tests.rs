@@ -5,9 +5,9 @@ use std::path::Path;
 use std::process::{Command, Output, Stdio};
 use tempfile::{NamedTempFile, TempDir};
 
-// Run the wasmtime CLI with the provided args and return the `Output`.
-// If the `stdin` is `Some`, opens the file and redirects to the child's stdin.
-pub fn run_wasmtime_for_output(args: &[&str], stdin: Option<&Path>) -> Result<Output> {
+// Get a `Command` for the wasmtime CLI, wrapped in the configured cargo
+// target runner if there is one.
+fn get_wasmtime_command() -> Result<Command> {
     let runner = std::env::vars()
         .filter(|(k, _v)| k.starts_with("CARGO_TARGET") && k.ends_with("RUNNER"))
         .next();
@@ -16,15 +16,10 @@ pub fn run_wasmtime_for_output(args: &[&str], stdin: Option<&Path>) -> Result<Ou
     me.pop(); // chop off `deps`
     me.push("wasmtime");
 
-    let stdin = stdin
-        .map(File::open)
-        .transpose()
-        .context("Cannot open a file to use as stdin")?;
-
     // If we're running tests with a "runner" then we might be doing something
     // like cross-emulation, so spin up the emulator rather than the tests
     // itself, which may not be natively executable.
-    let mut cmd = if let Some((_, runner)) = runner {
+    let cmd = if let Some((_, runner)) = runner {
         let mut parts = runner.split_whitespace();
         let mut cmd = Command::new(parts.next().unwrap());
         for arg in parts {
@@ -35,6 +30,18 @@ pub fn run_wasmtime_for_output(args: &[&str], stdin: Option<&Path>) -> Result<Ou
     } else {
         Command::new(&me)
     };
+    Ok(cmd)
+}
+
+// Run the wasmtime CLI with the provided args and return the `Output`.
+// If the `stdin` is `Some`, opens the file and redirects to the child's stdin.
+pub fn run_wasmtime_for_output(args: &[&str], stdin: Option<&Path>) -> Result<Output> {
+    let stdin = stdin
+        .map(File::open)
+        .transpose()
+        .context("Cannot open a file to use as stdin")?;
+
+    let mut cmd = get_wasmtime_command()?;
 
     if let Some(mut f) = stdin {
         let mut buf = Vec::new();
@@ -171,6 +178,48 @@ fn run_wasmtime_unreachable_wat() -> Result<()> {
     Ok(())
 }
 
+// Writing a coredump for a trap to a path with patterns in it.
+#[test]
+fn run_wasmtime_unreachable_coredump() -> Result<()> {
+    let td = TempDir::new()?;
+    let wasm = td.path().join("unreachable.wasm");
+    std::fs::write(
+        &wasm,
+        wat::parse_file("tests/all/cli_tests/unreachable.wat")?,
+    )?;
+    let pattern = td.path().join("%m-%%.coredump");
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--disable-cache",
+            "--coredump-on-trap",
+            pattern.to_str().unwrap(),
+            wasm.to_str().unwrap(),
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+
+    let coredump = std::fs::read(td.path().join("unreachable-%.coredump"))?;
+    wasmparser::validate(&coredump)?;
+
+    // Unknown patterns are rejected up front.
+    let pattern = td.path().join("%x.coredump");
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--disable-cache",
+            "--coredump-on-trap",
+            pattern.to_str().unwrap(),
+            wasm.to_str().unwrap(),
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown pattern `%x`"));
+    Ok(())
+}
+
 // Run a simple WASI hello world, snapshot0 edition.
 #[test]
 fn hello_wasi_snapshot0() -> Result<()> {
@@ -431,6 +480,64 @@ fn run_cwasm() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "component-model")]
+#[test]
+fn run_component() -> Result<()> {
+    let wasm = build_wasm("tests/all/cli_tests/component-add.wat")?;
+    let stdout = run_wasmtime(&[
+        "run",
+        "--wasm-features",
+        "component-model",
+        "--disable-cache",
+        "--invoke",
+        "add",
+        wasm.path().to_str().unwrap(),
+        "1",
+        "2",
+    ])?;
+    assert_eq!(stdout, "3\n");
+
+    // Components have no default export so `--invoke` is required.
+    assert!(run_wasmtime(&[
+        "run",
+        "--wasm-features",
+        "component-model",
+        "--disable-cache",
+        wasm.path().to_str().unwrap(),
+    ])
+    .is_err());
+    Ok(())
+}
+
+#[cfg(feature = "component-model")]
+#[test]
+fn run_component_cwasm() -> Result<()> {
+    let td = TempDir::new()?;
+    let cwasm = td.path().join("component.cwasm");
+    let stdout = run_wasmtime(&[
+        "compile",
+        "--wasm-features",
+        "component-model",
+        "tests/all/cli_tests/component-add.wat",
+        "-o",
+        cwasm.to_str().unwrap(),
+    ])?;
+    assert_eq!(stdout, "");
+    let stdout = run_wasmtime(&[
+        "run",
+        "--wasm-features",
+        "component-model",
+        "--allow-precompiled",
+        "--invoke",
+        "add",
+        cwasm.to_str().unwrap(),
+        "40",
+        "2",
+    ])?;
+    assert_eq!(stdout, "42\n");
+    Ok(())
+}
+
 #[cfg(unix)]
 #[test]
 fn hello_wasi_snapshot0_from_stdin() -> Result<()> {
@@ -498,3 +605,180 @@ fn run_threads() -> Result<()> {
     );
     Ok(())
 }
+
+#[cfg(feature = "pooling-allocator")]
+#[test]
+fn serve_echo() -> Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpStream;
+
+    let mut child = get_wasmtime_command()?
+        .args(&[
+            "serve",
+            "--disable-cache",
+            "--addr",
+            "127.0.0.1:0",
+            "tests/all/cli_tests/http-echo.wat",
+        ])
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let result = (|| -> Result<String> {
+        // The server reports the address it's listening on once it's ready.
+        let mut line = String::new();
+        BufReader::new(child.stderr.take().unwrap()).read_line(&mut line)?;
+        let addr = line
+            .trim()
+            .strip_prefix("Serving HTTP on http://")
+            .and_then(|s| s.strip_suffix('/'))
+            .with_context(|| format!("unexpected output: {:?}", line))?;
+
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(
+            b"POST /greet?name=wasm HTTP/1.1\r\n\
+              host: localhost\r\n\
+              content-length: 11\r\n\
+              \r\n\
+              hello world",
+        )?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    })();
+    child.kill()?;
+    child.wait()?;
+
+    assert_eq!(
+        result?,
+        "HTTP/1.1 201 Created\r\n\
+         x-method: POST\r\n\
+         content-length: 27\r\n\
+         connection: close\r\n\
+         \r\n\
+         /greet?name=wasmhello world"
+    );
+    Ok(())
+}
+
+#[cfg(feature = "pooling-allocator")]
+#[test]
+fn serve_drops_idle_connections() -> Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpStream;
+
+    // With a single worker, a request can only be handled once the idle
+    // connection ahead of it has timed out.
+    let mut child = get_wasmtime_command()?
+        .args(&[
+            "serve",
+            "--disable-cache",
+            "--addr",
+            "127.0.0.1:0",
+            "--max-instances",
+            "1",
+            "--io-timeout",
+            "500ms",
+            "tests/all/cli_tests/http-echo.wat",
+        ])
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Keep the server's output open, since it reports the dropped connection.
+    let mut stderr = BufReader::new(child.stderr.take().unwrap());
+    let result = (|| -> Result<(String, String)> {
+        let mut line = String::new();
+        stderr.read_line(&mut line)?;
+        let addr = line
+            .trim()
+            .strip_prefix("Serving HTTP on http://")
+            .and_then(|s| s.strip_suffix('/'))
+            .with_context(|| format!("unexpected output: {:?}", line))?;
+
+        let mut idle = TcpStream::connect(addr)?;
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(b"GET /hi HTTP/1.1\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let mut idle_response = String::new();
+        idle.read_to_string(&mut idle_response)?;
+        Ok((idle_response, response))
+    })();
+    child.kill()?;
+    child.wait()?;
+
+    let (idle_response, response) = result?;
+    assert!(
+        idle_response.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{idle_response}"
+    );
+    assert!(
+        response.starts_with("HTTP/1.1 201 Created\r\n"),
+        "{response}"
+    );
+    assert!(response.ends_with("/hi"), "{response}");
+    Ok(())
+}
+
+#[cfg(feature = "pooling-allocator")]
+#[test]
+fn serve_survives_oversized_requests_and_responses() -> Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpStream;
+
+    // A single worker has to handle every request in turn.
+    let mut child = get_wasmtime_command()?
+        .args(&[
+            "serve",
+            "--disable-cache",
+            "--addr",
+            "127.0.0.1:0",
+            "--max-instances",
+            "1",
+            "tests/all/cli_tests/http-oversized.wat",
+        ])
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stderr = BufReader::new(child.stderr.take().unwrap());
+    let result = (|| -> Result<Vec<String>> {
+        let mut line = String::new();
+        stderr.read_line(&mut line)?;
+        let addr = line
+            .trim()
+            .strip_prefix("Serving HTTP on http://")
+            .and_then(|s| s.strip_suffix('/'))
+            .with_context(|| format!("unexpected output: {:?}", line))?;
+
+        let requests: [&[u8]; 3] = [
+            b"PUT / HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n\
+              1\r\na\r\nffffffffffffffff\r\n",
+            b"GET /oob HTTP/1.1\r\n\r\n",
+            b"GET /big HTTP/1.1\r\n\r\n",
+        ];
+        let mut responses = Vec::new();
+        for request in requests {
+            let mut stream = TcpStream::connect(addr)?;
+            stream.write_all(request)?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            responses.push(response);
+        }
+        Ok(responses)
+    })();
+    child.kill()?;
+    child.wait()?;
+
+    let responses = result?;
+    assert!(
+        responses[0].starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{}",
+        responses[0]
+    );
+    for response in &responses[1..] {
+        assert!(
+            response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"),
+            "{response}"
+        );
+    }
+    Ok(())
+}
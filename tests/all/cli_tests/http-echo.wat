@@ -0,0 +1,32 @@
+;; Responds to every request with a `x-method` header holding the request
+;; method and a body holding the request URI followed by the request body.
+(module
+  (import "wasi_http" "request_method" (func $request_method (param i32 i32) (result i32)))
+  (import "wasi_http" "request_uri" (func $request_uri (param i32 i32) (result i32)))
+  (import "wasi_http" "request_body" (func $request_body (param i32 i32) (result i32)))
+  (import "wasi_http" "response_status" (func $response_status (param i32)))
+  (import "wasi_http" "response_header" (func $response_header (param i32 i32 i32 i32)))
+  (import "wasi_http" "response_body" (func $response_body (param i32 i32)))
+
+  (memory (export "memory") 1)
+  (data (i32.const 0) "x-method")
+
+  (func (export "handle")
+    (local $len i32)
+
+    (call $response_status (i32.const 201))
+
+    (local.set $len (call $request_method (i32.const 64) (i32.const 64)))
+    (call $response_header (i32.const 0) (i32.const 8) (i32.const 64) (local.get $len))
+
+    (local.set $len (call $request_uri (i32.const 128) (i32.const 1024)))
+    (call $response_body (i32.const 128) (local.get $len))
+
+    (loop $read
+      (local.set $len (call $request_body (i32.const 2048) (i32.const 4)))
+      (if (local.get $len)
+        (then
+          (call $response_body (i32.const 2048) (local.get $len))
+          (br $read))))
+  )
+)
@@ -0,0 +1,21 @@
+;; Responds to `/oob` by passing an out-of-bounds buffer to `response_body`,
+;; and to any other request with a body larger than the host allows.
+(module
+  (import "wasi_http" "request_uri" (func $request_uri (param i32 i32) (result i32)))
+  (import "wasi_http" "response_body" (func $response_body (param i32 i32)))
+
+  (memory (export "memory") 1)
+
+  (func (export "handle")
+    (local $i i32)
+    (drop (call $request_uri (i32.const 0) (i32.const 16)))
+    (if (i32.eq (i32.load8_u (i32.const 1)) (i32.const 0x6f))
+      (then
+        (call $response_body (i32.const 0) (i32.const -1))
+        (return)))
+    (loop $write
+      (call $response_body (i32.const 0) (i32.const 65536))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $write (i32.lt_u (local.get $i) (i32.const 512))))
+  )
+)
@@ -76,6 +76,72 @@ fn components_importing_modules() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn emit_clif() -> Result<()> {
+    let tmpdir = tempfile::TempDir::new()?;
+    let mut config = component_test_util::config();
+    config.emit_clif(tmpdir.path());
+    let engine = wasmtime::Engine::new(&config)?;
+    let component = Component::new(
+        &engine,
+        r#"
+        (component
+            (component $c
+                (core module $m
+                    (func (export "") (param i32 i32))
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32) i32.const 0)
+                    (memory (export "memory") 1)
+                )
+                (core instance $m (instantiate $m))
+                (func (export "a") (param "a" string)
+                    (canon lift (core func $m "") (realloc (func $m "realloc")) (memory $m "memory"))
+                )
+            )
+            (component $c2
+                (import "a" (func $f (param "a" string)))
+                (core module $libc
+                    (memory (export "memory") 1)
+                )
+                (core instance $libc (instantiate $libc))
+                (core func $f (canon lower (func $f) string-encoding=utf16 (memory $libc "memory")))
+                (core module $m
+                    (import "" "" (func $f (param i32 i32)))
+                    (func (export "b") (call $f (i32.const 0) (i32.const 0)))
+                )
+                (core instance (instantiate $m (with "" (instance (export "" (func $f))))))
+            )
+            (instance $c (instantiate $c))
+            (instance $c2 (instantiate $c2 (with "a" (func $c "a"))))
+        )
+        "#,
+    )?;
+
+    // The three modules defined in the component are followed by the adapter
+    // module for calls from `$c2` to `$c`, which transcodes strings from utf16
+    // to utf8.
+    assert_eq!(component.core_modules().len(), 4);
+    assert!(component.transcoder_locations().len() > 0);
+    for index in 0..4 {
+        let path = tmpdir.path().join(format!("wasm_module_{index}.wasm"));
+        assert!(std::fs::read(&path)?.starts_with(b"\0asm"));
+    }
+
+    // Functions are named by the index of their module within the component.
+    assert!(tmpdir.path().join("wasm_module_0_func_0.clif").exists());
+    assert!(tmpdir.path().join("wasm_module_2_func_1.clif").exists());
+    assert!(!tmpdir.path().join("wasm_func_0.clif").exists());
+    let adapter = component.core_modules().nth(3).unwrap();
+    let num_imported_funcs = adapter
+        .imports()
+        .filter(|import| import.ty().func().is_some())
+        .count();
+    let path = tmpdir
+        .path()
+        .join(format!("wasm_module_3_func_{num_imported_funcs}.clif"));
+    assert!(std::fs::read_to_string(&path)?.contains("@0"));
+    Ok(())
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum Type {
     S8,
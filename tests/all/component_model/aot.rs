@@ -1,6 +1,6 @@
 use anyhow::Result;
 use wasmtime::component::{Component, Linker};
-use wasmtime::{Module, Store};
+use wasmtime::{Module, Precompiled, Store};
 
 #[test]
 fn module_component_mismatch() -> Result<()> {
@@ -16,6 +16,26 @@ fn module_component_mismatch() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn detect_precompiled() -> Result<()> {
+    let engine = super::engine();
+    let module = Module::new(&engine, "(module)")?.serialize()?;
+    let component = Component::new(&engine, "(component)")?.serialize()?;
+
+    assert_eq!(
+        engine.detect_precompiled(&module),
+        Some(Precompiled::Module)
+    );
+    assert_eq!(
+        engine.detect_precompiled(&component),
+        Some(Precompiled::Component)
+    );
+    assert_eq!(engine.detect_precompiled(b"(component)"), None);
+    assert_eq!(engine.detect_precompiled(&[]), None);
+
+    Ok(())
+}
+
 #[test]
 fn bare_bones() -> Result<()> {
     let engine = super::engine();
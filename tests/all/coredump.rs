@@ -0,0 +1,199 @@
+use anyhow::Result;
+use wasmtime::*;
+
+fn coredump_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.coredump_on_trap(true);
+    Engine::new(&config)
+}
+
+#[test]
+fn coredump_disabled_by_default() -> Result<()> {
+    let mut store = Store::<()>::default();
+    let module = Module::new(
+        store.engine(),
+        r#"(module (func (export "run") unreachable))"#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let e = run.call(&mut store, ()).unwrap_err();
+    assert!(e.downcast_ref::<WasmCoreDump>().is_none());
+    Ok(())
+}
+
+#[test]
+fn coredump_attached_to_trap() -> Result<()> {
+    let engine = coredump_engine()?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module $a
+                (memory (export "m") 1)
+                (global (export "g") (mut i32) (i32.const 1))
+                (data (i32.const 8) "hello")
+                (func (export "run") call $trap)
+                (func $trap unreachable)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let e = run.call(&mut store, ()).unwrap_err();
+    assert_eq!(
+        *e.downcast_ref::<Trap>().unwrap(),
+        Trap::UnreachableCodeReached
+    );
+
+    let coredump = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(coredump.frames().len(), 2);
+    assert_eq!(coredump.frames()[0].func_name(), Some("trap"));
+    assert_eq!(coredump.instances().len(), 1);
+    assert_eq!(coredump.modules().len(), 1);
+    assert_eq!(coredump.modules()[0].name(), Some("a"));
+    assert_eq!(coredump.memories().len(), 1);
+    assert_eq!(coredump.globals().len(), 1);
+    assert_eq!(&coredump.memories()[0].data(&store)[8..13], b"hello");
+    assert_eq!(coredump.globals()[0].get(&mut store).i32(), Some(1));
+
+    let bytes = coredump.serialize(&mut store, "a.wasm");
+    wasmparser::validate(&bytes)?;
+    Ok(())
+}
+
+#[test]
+fn coredump_includes_imported_items_once() -> Result<()> {
+    let engine = coredump_engine()?;
+    let mut store = Store::new(&engine, ());
+    let memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    let global = Global::new(
+        &mut store,
+        GlobalType::new(ValType::I64, Mutability::Var),
+        Val::I64(-1),
+    )?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "m" (memory 1))
+                (import "" "g" (global (mut i64)))
+                (global f64 (f64.const 1.5))
+                (func (export "run") unreachable)
+            )
+        "#,
+    )?;
+    Instance::new(&mut store, &module, &[memory.into(), global.into()])?;
+    let instance = Instance::new(&mut store, &module, &[memory.into(), global.into()])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let e = run.call(&mut store, ()).unwrap_err();
+
+    let coredump = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(coredump.instances().len(), 2);
+    assert_eq!(coredump.modules().len(), 1);
+    assert_eq!(coredump.memories().len(), 1);
+    assert_eq!(coredump.globals().len(), 3);
+
+    let bytes = coredump.serialize(&mut store, "imports.wasm");
+    wasmparser::validate(&bytes)?;
+    Ok(())
+}
+
+#[test]
+fn coredump_records_locals_with_guest_debug() -> Result<()> {
+    let mut config = Config::new();
+    config.coredump_on_trap(true).guest_debug(true);
+    let engine = Engine::new(&config)?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "run") (param i32 i64) (local f32 f64)
+                    f32.const 1.5
+                    local.set 2
+                    f64.const -2
+                    local.set 3
+                    i32.const 7
+                    unreachable)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(i32, i64), ()>(&mut store, "run")?;
+    let e = run.call(&mut store, (-3, 1 << 40)).unwrap_err();
+    let coredump = e.downcast_ref::<WasmCoreDump>().unwrap();
+    let bytes = coredump.serialize(&mut store, "locals.wasm");
+    wasmparser::validate(&bytes)?;
+
+    let corestack = wasmparser::Parser::new(0)
+        .parse_all(&bytes)
+        .find_map(|payload| match payload {
+            Ok(wasmparser::Payload::CustomSection(s)) if s.name() == "corestack" => Some(s),
+            _ => None,
+        })
+        .unwrap();
+    let mut reader = wasmparser::BinaryReader::new(corestack.data());
+    assert_eq!(reader.read_u8()?, 0x00);
+    assert_eq!(reader.read_string()?, "main");
+    assert_eq!(reader.read_var_u32()?, 1);
+    assert_eq!(reader.read_u8()?, 0x00);
+    assert_eq!(reader.read_var_u32()?, 0); // instance
+    assert_eq!(reader.read_var_u32()?, 0); // function
+    reader.read_var_u32()?; // code offset
+
+    assert_eq!(reader.read_var_u32()?, 4);
+    assert_eq!(reader.read_u8()?, 0x7f);
+    assert_eq!(reader.read_var_i32()?, -3);
+    assert_eq!(reader.read_u8()?, 0x7e);
+    assert_eq!(reader.read_var_i64()?, 1 << 40);
+    assert_eq!(reader.read_u8()?, 0x7d);
+    assert_eq!(reader.read_f32()?.bits(), 1.5f32.to_bits());
+    assert_eq!(reader.read_u8()?, 0x7c);
+    assert_eq!(reader.read_f64()?.bits(), (-2.0f64).to_bits());
+
+    assert_eq!(reader.read_var_u32()?, 1);
+    assert_eq!(reader.read_u8()?, 0x7f);
+    assert_eq!(reader.read_var_i32()?, 7);
+    assert!(reader.eof());
+    Ok(())
+}
+
+#[test]
+fn coredump_memory64() -> Result<()> {
+    let mut config = Config::new();
+    config.coredump_on_trap(true).wasm_memory64(true);
+    let engine = Engine::new(&config)?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory i64 1)
+                (data (i64.const 16) "abc")
+                (func (export "run") unreachable)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let e = run.call(&mut store, ()).unwrap_err();
+    let coredump = e.downcast_ref::<WasmCoreDump>().unwrap();
+    let bytes = coredump.serialize(&mut store, "memory64.wasm");
+
+    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+        memory64: true,
+        ..Default::default()
+    });
+    validator.validate_all(&bytes)?;
+    let data = wasmparser::Parser::new(0)
+        .parse_all(&bytes)
+        .find_map(|payload| match payload {
+            Ok(wasmparser::Payload::DataSection(s)) => Some(s),
+            _ => None,
+        })
+        .unwrap();
+    let segments = data.into_iter().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].data.len(), 19);
+    Ok(())
+}
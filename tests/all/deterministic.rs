@@ -0,0 +1,146 @@
+use anyhow::Result;
+use wasi_common::pipe::{ReadPipe, WritePipe};
+use wasmtime::*;
+use wasmtime_wasi::sync::WasiCtxBuilder;
+
+const NANS: &str = r#"
+    (module
+        (func (export "div") (param f32 f32) (result i32)
+            (i32.reinterpret_f32 (f32.div (local.get 0) (local.get 1))))
+        (func (export "add") (param i32) (result i32)
+            (i32.reinterpret_f32 (f32.add (f32.reinterpret_i32 (local.get 0)) (f32.const 0))))
+        (func (export "min") (param i64) (result i64)
+            (i64.reinterpret_f64
+                (f64.min (f64.reinterpret_i64 (local.get 0)) (f64.const 1))))
+    )
+"#;
+
+#[test]
+fn deterministic_nans() -> Result<()> {
+    let mut config = Config::new();
+    config.deterministic_execution(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, NANS)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let div = instance.get_typed_func::<(f32, f32), i32>(&mut store, "div")?;
+    assert_eq!(div.call(&mut store, (0.0, 0.0))?, 0x7fc00000);
+    let add = instance.get_typed_func::<i32, i32>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, 0x7fc00001)?, 0x7fc00000);
+    assert_eq!(add.call(&mut store, 0xffc00000u32 as i32)?, 0x7fc00000);
+    let min = instance.get_typed_func::<i64, i64>(&mut store, "min")?;
+    assert_eq!(
+        min.call(&mut store, 0x7ff0000000000001)?,
+        0x7ff8000000000000
+    );
+    Ok(())
+}
+
+#[test]
+fn deterministic_rejects_threads() -> Result<()> {
+    let mut config = Config::new();
+    config.deterministic_execution(true);
+    config.wasm_threads(true);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("threads"), "{err}");
+
+    // Threads are disabled when deterministic execution is enabled.
+    let mut config = Config::new();
+    config.wasm_threads(true);
+    config.deterministic_execution(true);
+    let engine = Engine::new(&config)?;
+    assert!(Module::new(&engine, r#"(module (memory 1 1 shared))"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn deterministic_rejects_nondeterministic_settings() -> Result<()> {
+    let mut config = Config::new();
+    config.deterministic_execution(true);
+    config.cranelift_nan_canonicalization(false);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("NaN"), "{err}");
+
+    let mut config = Config::new();
+    config.deterministic_execution(true);
+    config.relaxed_simd_deterministic(false);
+    let err = Engine::new(&config).err().unwrap();
+    assert!(err.to_string().contains("relaxed SIMD"), "{err}");
+
+    // Disabling deterministic execution leaves the other settings alone.
+    let mut config = Config::new();
+    config.cranelift_nan_canonicalization(true);
+    config.deterministic_execution(false);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, NANS)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let div = instance.get_typed_func::<(f32, f32), i32>(&mut store, "div")?;
+    assert_eq!(div.call(&mut store, (0.0, 0.0))?, 0x7fc00000);
+    Ok(())
+}
+
+// Writes the wall clock, 8 random bytes and up to 16 bytes of stdin to
+// stdout.
+const WASI_GUEST: &str = r#"
+    (module
+        (import "wasi_snapshot_preview1" "clock_time_get"
+            (func $clock_time_get (param i32 i64 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "random_get"
+            (func $random_get (param i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_read"
+            (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+            (func $fd_write (param i32 i32 i32 i32) (result i32)))
+
+        (memory (export "memory") 1)
+        (data (i32.const 0) "\50\00\00\00\10\00\00\00")
+        (data (i32.const 8) "\40\00\00\00\20\00\00\00")
+
+        (func (export "_start")
+            (drop (call $clock_time_get (i32.const 0) (i64.const 1) (i32.const 64)))
+            (drop (call $random_get (i32.const 72) (i32.const 8)))
+            (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 96)))
+            (drop (call $fd_write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 96))))
+    )
+"#;
+
+/// Runs `WASI_GUEST` with `trace` and `stdin`, returning what it wrote.
+fn run_traced(trace: wasmtime_wasi::Trace, stdin: &str) -> Result<Vec<u8>> {
+    let mut config = Config::new();
+    config.deterministic_execution(true);
+    let engine = Engine::new(&config)?;
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::add_to_linker(&mut linker, |s| s)?;
+    let module = Module::new(&engine, WASI_GUEST)?;
+
+    let stdout = WritePipe::new_in_memory();
+    let wasi = WasiCtxBuilder::new()
+        .stdin(Box::new(ReadPipe::from(stdin)))
+        .stdout(Box::new(stdout.clone()))
+        .trace(trace)
+        .build();
+    let mut store = Store::new(&engine, wasi);
+    let instance = linker.instantiate(&mut store, &module)?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "_start")?
+        .call(&mut store, ())?;
+    store.data().trace.as_ref().unwrap().finish()?;
+    drop(store);
+
+    Ok(stdout.try_into_inner().unwrap().into_inner())
+}
+
+#[test]
+fn deterministic_wasi_record_replay() -> Result<()> {
+    let trace = tempfile::NamedTempFile::new()?;
+    let recorded = run_traced(wasmtime_wasi::Trace::record(trace.reopen()?)?, "hello")?;
+    assert_eq!(recorded.len(), 32);
+    assert_eq!(&recorded[16..21], b"hello");
+
+    // The clock, random bytes and stdin all come from the trace on replay.
+    let replayed = run_traced(wasmtime_wasi::Trace::replay(trace.reopen()?)?, "goodbye")?;
+    assert_eq!(replayed, recorded);
+    Ok(())
+}
@@ -0,0 +1,342 @@
+use anyhow::Result;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use wasmtime::*;
+
+fn debug_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.guest_debug(true);
+    Engine::new(&config)
+}
+
+#[test]
+fn debug_hook_sees_locals_and_stack() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32) (local i64)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, Vec::new());
+    store.debug_hook(|mut store, location| {
+        assert_eq!(location.depth(), 1);
+        assert_eq!(location.func_index(), 0);
+        let frames = store.debug_frames();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].module_offset(), location.module_offset());
+        let state = (frames[0].locals().to_vec(), frames[0].stack().to_vec());
+        store.data_mut().push((location.module_offset(), state));
+        Ok(())
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_typed_func::<(i32, i32), i32>(&mut store, "add")?;
+    assert_eq!(add.call(&mut store, (1, 2))?, 3);
+    assert!(store.debug_frames().is_empty());
+
+    let locals = vec![DebugValue::I32(1), DebugValue::I32(2), DebugValue::I64(0)];
+    let steps = store.data();
+    assert_eq!(steps.len(), 4);
+    assert!(steps.windows(2).all(|w| w[0].0 < w[1].0));
+    assert_eq!(steps[0].1, (locals.clone(), vec![]));
+    assert_eq!(steps[1].1, (locals.clone(), vec![DebugValue::I32(1)]));
+    assert_eq!(
+        steps[2].1,
+        (locals.clone(), vec![DebugValue::I32(1), DebugValue::I32(2)])
+    );
+    assert_eq!(steps[3].1, (locals, vec![DebugValue::I32(3)]));
+    Ok(())
+}
+
+#[test]
+fn debug_frames_follow_calls_and_traps() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (global $g (mut i64) (i64.const 7))
+                (memory 1)
+                (data (i32.const 16) "hello")
+                (func (export "run") (param i32)
+                    local.get 0
+                    call $callee)
+                (func $callee (param i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                    drop
+                    unreachable)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, 0);
+    store.debug_hook(|mut store, location| {
+        if location.depth() == 2 && *store.data() == 0 {
+            let frames = store.debug_frames();
+            assert_eq!(frames.len(), 2);
+            assert_eq!(frames[0].func_index(), 1);
+            assert_eq!(frames[0].locals(), &[DebugValue::I32(42)]);
+            assert_eq!(frames[1].func_index(), 0);
+            assert_eq!(frames[1].locals(), &[DebugValue::I32(42)]);
+            assert_eq!(frames[0].global(&store, 0), Some(DebugValue::I64(7)));
+            assert_eq!(frames[0].global(&store, 1), None);
+            let memory = frames[0].memory(&store, 0).unwrap();
+            assert_eq!(memory.len(), 65536);
+            assert_eq!(&memory[16..21], b"hello");
+            assert!(frames[0].memory(&store, 1).is_none());
+            *store.data_mut() += 1;
+        }
+        Ok(())
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, ()>(&mut store, "run")?;
+    let trap = run.call(&mut store, 42).unwrap_err();
+    assert_eq!(trap.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+    assert_eq!(*store.data(), 1);
+    assert!(store.debug_frames().is_empty());
+
+    // The store keeps working after the trap unwound the frames.
+    *store.data_mut() = 0;
+    assert!(run.call(&mut store, 42).is_err());
+    assert_eq!(*store.data(), 1);
+    Ok(())
+}
+
+#[test]
+fn debug_hook_errors_trap() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(&engine, r#"(module (func (export "run") nop))"#)?;
+    let mut store = Store::new(&engine, ());
+    store.debug_hook(|_, _| anyhow::bail!("stopped by the debugger"));
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    assert!(format!("{err:?}").contains("stopped by the debugger"));
+    assert!(store.debug_frames().is_empty());
+    Ok(())
+}
+
+#[test]
+fn debug_hook_ignored_without_guest_debug() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, r#"(module (func (export "run") nop))"#)?;
+    let mut store = Store::new(&engine, ());
+    store.debug_hook(|_, _| panic!("hook called without guest debugging"));
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    run.call(&mut store, ())?;
+    Ok(())
+}
+
+/// A minimal GDB remote protocol client.
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn send(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        write!(self.stream, "${packet}#{checksum:02x}").unwrap();
+    }
+
+    fn recv(&mut self) -> String {
+        String::from_utf8(self.recv_bytes()).unwrap()
+    }
+
+    fn recv_bytes(&mut self) -> Vec<u8> {
+        let mut byte = [0];
+        loop {
+            self.stream.read_exact(&mut byte).unwrap();
+            if byte[0] == b'$' {
+                break;
+            }
+        }
+        let mut packet = Vec::new();
+        loop {
+            self.stream.read_exact(&mut byte).unwrap();
+            if byte[0] == b'#' {
+                break;
+            }
+            packet.push(byte[0]);
+        }
+        let mut checksum = [0; 2];
+        self.stream.read_exact(&mut checksum).unwrap();
+        packet
+    }
+
+    fn request(&mut self, packet: &str) -> String {
+        self.send(packet);
+        self.recv()
+    }
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len() / 2)
+        .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn gdbstub_session() -> Result<()> {
+    let engine = debug_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module $guest
+                (memory 1)
+                (data (i32.const 8) "\01\02\03")
+                (func (export "run") (result i32) (local i32)
+                    i32.const 5
+                    local.set 0
+                    local.get 0
+                    call $double)
+                (func $double (param i32) (result i32)
+                    local.get 0
+                    local.get 0
+                    i32.add)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let stub = GdbStub::listen("127.0.0.1:0")?;
+    stub.attach(&mut store);
+    let addr = stub.local_addr()?;
+
+    let transcript = Arc::new(Mutex::new(Vec::new()));
+    let client = std::thread::spawn({
+        let transcript = transcript.clone();
+        move || {
+            let mut client = Client {
+                stream: TcpStream::connect(addr).unwrap(),
+            };
+            client.send("QStartNoAckMode");
+            let mut ack = [0];
+            client.stream.read_exact(&mut ack).unwrap();
+            assert_eq!(&ack, b"+");
+            assert_eq!(client.recv(), "OK");
+            client.stream.write_all(b"+").unwrap();
+
+            let mut log = |s: String| transcript.lock().unwrap().push(s);
+            log(client.request("?"));
+            log(client.request("qXfer:libraries:read::0,1000"));
+
+            // The guest stopped before the first instruction of `run`; put
+            // a breakpoint on the `i32.add` of `$double` and continue to it.
+            let stack = client.request("qWasmCallStack");
+            let pc = u64::from_le_bytes(unhex(&stack[..16]).try_into().unwrap());
+            log(format!("{:x}", pc >> 32));
+            let binary = unhex(&client.request("m4000000000000000,1000"));
+            assert_eq!(&binary[..4], b"\0asm");
+            let add = binary.iter().rposition(|&b| b == 0x6a).unwrap();
+            log(client.request(&format!("Z0,{:x},1", (1u64 << 62) | add as u64)));
+            log(client.request("c"));
+
+            log(client.request("qWasmCallStack").len().to_string());
+            log(client.request("qWasmLocal:0;0"));
+            log(client.request("qWasmStackValue:0;1"));
+            log(client.request("qWasmLocal:1;0"));
+            log(client.request("qWasmLocal:5;0"));
+            log(client.request("qWasmMem:0;8;3"));
+
+            // Step onto the end of `$double`, then detach.
+            log(client.request("s"));
+            log(client.request("qWasmStackValue:0;0"));
+            log(client.request("D"));
+        }
+    });
+
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, ())?, 10);
+    stub.finish(0)?;
+    client.join().unwrap();
+
+    let transcript = transcript.lock().unwrap();
+    assert_eq!(
+        *transcript,
+        [
+            "T05thread:1;",
+            "l<library-list><library name=\"guest.wasm\">\
+             <section address=\"0x4000000000000000\"/></library></library-list>",
+            "40000000",
+            "OK",
+            "T05thread:1;reason:breakpoint;",
+            "32",
+            "05000000",
+            "05000000",
+            "05000000",
+            "E03",
+            "010203",
+            "T05thread:1;reason:trace;",
+            "0a000000",
+            "OK",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn gdbstub_libraries_split_characters() -> Result<()> {
+    let engine = debug_engine()?;
+    // A module named "gäst", whose "ä" spans bytes 30 and 31 of the library
+    // list.
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (@custom "name" "\00\06\05g\c3\a4st")
+                (func (export "run"))
+            )
+        "#,
+    )?;
+    assert_eq!(module.name(), Some("gäst"));
+    let mut store = Store::new(&engine, ());
+    let stub = GdbStub::listen("127.0.0.1:0")?;
+    stub.attach(&mut store);
+    let addr = stub.local_addr()?;
+
+    let client = std::thread::spawn(move || {
+        let mut client = Client {
+            stream: TcpStream::connect(addr).unwrap(),
+        };
+        client.send("QStartNoAckMode");
+        let mut ack = [0];
+        client.stream.read_exact(&mut ack).unwrap();
+        assert_eq!(client.recv(), "OK");
+        client.stream.write_all(b"+").unwrap();
+
+        // Read the list 31 bytes at a time, so the first chunk ends in the
+        // middle of the "ä".
+        let mut list = Vec::new();
+        loop {
+            client.send(&format!("qXfer:libraries:read::{:x},1f", list.len()));
+            let chunk = client.recv_bytes();
+            list.extend_from_slice(&chunk[1..]);
+            if chunk[0] == b'l' {
+                break;
+            }
+            assert_eq!(chunk[0], b'm');
+        }
+        client.request("D");
+        String::from_utf8(list).unwrap()
+    });
+
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    run.call(&mut store, ())?;
+    stub.finish(0)?;
+
+    assert_eq!(
+        client.join().unwrap(),
+        "<library-list><library name=\"gäst.wasm\">\
+         <section address=\"0x4000000000000000\"/></library></library-list>"
+    );
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use wasmtime::*;
+
+#[test]
+fn guest_profiler_samples_wasm_frames() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module $m
+                (import "" "sample" (func $sample))
+                (func (export "run")
+                    call $inner
+                    call $outer
+                    call $outer)
+                (func $inner call $sample)
+                (func $outer call $inner)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, GuestProfiler::new());
+    let sample = Func::wrap(&mut store, |mut caller: Caller<'_, GuestProfiler>| {
+        let mut profiler = std::mem::take(caller.data_mut());
+        profiler.sample(&caller);
+        *caller.data_mut() = profiler;
+    });
+    let instance = Instance::new(&mut store, &module, &[sample.into()])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    run.call(&mut store, ())?;
+
+    // Outside of wasm there's nothing to sample.
+    let mut profiler = std::mem::take(store.data_mut());
+    profiler.sample(&store);
+    assert_eq!(profiler.samples(), 3);
+
+    let mut profile = Vec::new();
+    profiler.finish(&mut profile)?;
+    assert_eq!(
+        String::from_utf8(profile)?,
+        "m!<wasm function 1>;m!inner 1\n\
+         m!<wasm function 1>;m!outer;m!inner 2\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn guest_profiler_with_epochs() -> Result<()> {
+    let mut config = Config::new();
+    config.epoch_interruption(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, r#"(module (func (export "spin") (loop br 0)))"#)?;
+    let mut store = Store::new(&engine, GuestProfiler::new());
+    store.set_epoch_deadline(1);
+    store.epoch_deadline_callback(|mut store| {
+        let mut profiler = std::mem::take(store.data_mut());
+        profiler.sample(&store);
+        *store.data_mut() = profiler;
+        if store.data().samples() == 3 {
+            anyhow::bail!("done sampling");
+        }
+        Ok(1)
+    });
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let spin = instance.get_typed_func::<(), ()>(&mut store, "spin")?;
+    let ticker = engine.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        ticker.increment_epoch();
+    });
+    let err = spin.call(&mut store, ()).unwrap_err();
+    assert!(format!("{err:?}").contains("done sampling"), "{err:?}");
+
+    let mut profile = Vec::new();
+    store.data().finish(&mut profile)?;
+    assert_eq!(
+        String::from_utf8(profile)?,
+        "<unknown>!<wasm function 0> 3\n"
+    );
+    Ok(())
+}
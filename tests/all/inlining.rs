@@ -0,0 +1,133 @@
+use anyhow::Result;
+use wasmtime::*;
+
+const GUEST: &str = r#"
+    (module
+        (global $count (mut i32) (i32.const 0))
+        (memory 1)
+
+        (func $min (param i32 i32) (result i32)
+            (if (result i32) (i32.lt_s (local.get 0) (local.get 1))
+                (then (local.get 0))
+                (else (local.get 1))))
+        (func $divmod (param i32 i32) (result i32 i32)
+            (global.set $count (i32.add (global.get $count) (i32.const 1)))
+            (i32.div_u (local.get 0) (local.get 1))
+            (i32.rem_u (local.get 0) (local.get 1)))
+        (func $store (param i32 i32)
+            (i32.store (local.get 0) (local.get 1)))
+
+        (func (export "min") (param i32 i32) (result i32)
+            (call $min (local.get 0) (local.get 1)))
+        (func (export "divmod") (param i32 i32) (result i32)
+            (call $divmod (local.get 0) (local.get 1))
+            i32.add)
+        (func (export "divmod_twice") (param i32 i32) (result i32)
+            (call $divmod (local.get 0) (local.get 1))
+            (call $divmod)
+            i32.add)
+        (func (export "store_load") (param i32 i32) (result i32)
+            (call $store (local.get 0) (local.get 1))
+            (i32.load (local.get 0)))
+        (func (export "count") (result i32)
+            (global.get $count))
+    )
+"#;
+
+fn engine(inlining: bool) -> Result<Engine> {
+    let mut config = Config::new();
+    config.inlining(inlining);
+    Engine::new(&config)
+}
+
+fn instantiate(inlining: bool) -> Result<(Store<()>, Instance)> {
+    let engine = engine(inlining)?;
+    let module = Module::new(&engine, GUEST)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Ok((store, instance))
+}
+
+fn frames(err: &Error) -> Vec<(u32, Option<usize>)> {
+    err.downcast_ref::<WasmBacktrace>()
+        .unwrap()
+        .frames()
+        .iter()
+        .map(|frame| (frame.func_index(), frame.module_offset()))
+        .collect()
+}
+
+#[test]
+fn results_match() -> Result<()> {
+    for inlining in [false, true] {
+        let (mut store, instance) = instantiate(inlining)?;
+        let min = instance.get_typed_func::<(i32, i32), i32>(&mut store, "min")?;
+        let divmod = instance.get_typed_func::<(i32, i32), i32>(&mut store, "divmod")?;
+        let divmod_twice =
+            instance.get_typed_func::<(i32, i32), i32>(&mut store, "divmod_twice")?;
+        let store_load = instance.get_typed_func::<(i32, i32), i32>(&mut store, "store_load")?;
+        let count = instance.get_typed_func::<(), i32>(&mut store, "count")?;
+
+        assert_eq!(min.call(&mut store, (3, -4))?, -4);
+        assert_eq!(min.call(&mut store, (3, 4))?, 3);
+        assert_eq!(divmod.call(&mut store, (17, 5))?, 5);
+        assert_eq!(divmod_twice.call(&mut store, (17, 5))?, 2);
+        assert_eq!(store_load.call(&mut store, (8, 42))?, 42);
+        assert_eq!(count.call(&mut store, ())?, 3);
+    }
+    Ok(())
+}
+
+#[test]
+fn trap_in_inlined_callee() -> Result<()> {
+    let mut traces = Vec::new();
+    for inlining in [false, true] {
+        let (mut store, instance) = instantiate(inlining)?;
+        let divmod = instance.get_typed_func::<(i32, i32), i32>(&mut store, "divmod")?;
+        let err = divmod.call(&mut store, (17, 0)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Trap>(),
+            Some(&Trap::IntegerDivisionByZero)
+        );
+        traces.push(frames(&err));
+    }
+
+    // The inlined callee is reported in its own frame, followed by its caller
+    // at the call site, exactly as if it hadn't been inlined.
+    assert_eq!(traces[0].len(), 2);
+    assert_eq!(traces[0][0].0, 1);
+    assert_eq!(traces[0][1].0, 4);
+    assert_eq!(traces[0], traces[1]);
+    Ok(())
+}
+
+#[test]
+fn trap_in_callee_inlined_twice() -> Result<()> {
+    // The first call divides by 0 for `(17, 0)`, and the second call divides
+    // `4 / 2` by `4 % 2` for `(4, 2)`.
+    let mut traces = Vec::new();
+    for inlining in [false, true] {
+        let (mut store, instance) = instantiate(inlining)?;
+        let divmod_twice =
+            instance.get_typed_func::<(i32, i32), i32>(&mut store, "divmod_twice")?;
+        for args in [(17, 0), (4, 2)] {
+            let err = divmod_twice.call(&mut store, args).unwrap_err();
+            traces.push(frames(&err));
+        }
+    }
+
+    // Each copy of the callee reports the offset of its own call site, the
+    // two `call $divmod` instructions at offsets 185 and 187.
+    assert_eq!(traces[0], [(1, Some(143)), (5, Some(185))]);
+    assert_eq!(traces[1], [(1, Some(143)), (5, Some(187))]);
+    assert_eq!(traces[0], traces[2]);
+    assert_eq!(traces[1], traces[3]);
+    Ok(())
+}
+
+#[test]
+fn not_with_native_debuginfo() {
+    let mut config = Config::new();
+    config.inlining(true).debug_info(true);
+    assert!(Engine::new(&config).is_err());
+}
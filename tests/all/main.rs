@@ -1,9 +1,12 @@
 mod async_functions;
+mod block_profile;
 mod call_hook;
 mod cli_tests;
 mod component_model;
+mod coredump;
 mod custom_signal_handler;
 mod debug;
+mod deterministic;
 mod epoch_interruption;
 mod externals;
 mod fuel;
@@ -11,27 +14,35 @@ mod func;
 mod funcref;
 mod gc;
 mod globals;
+mod guest_debug;
+mod guest_profiler;
 mod host_funcs;
 mod iloop;
 mod import_calling_export;
 mod import_indexes;
+mod inlining;
 mod instance;
 mod invoke_func_via_table;
 mod limits;
 mod linker;
 mod memory;
 mod memory_creator;
+mod memory_sanitizer;
 mod module;
 mod module_serialize;
 mod name;
 mod pooling_allocator;
 mod relocs;
+mod snapshot;
 mod stack_overflow;
 mod store;
 mod table;
+mod tail_call;
 mod threads;
 mod traps;
 mod wait_notify;
+mod wasi_nn;
+mod wasi_sockets;
 mod wasi_testsuite;
 mod wast;
 
@@ -465,6 +465,99 @@ fn memory64_maximum_minimum() -> Result<()> {
     Ok(())
 }
 
+// Checks that removing redundant bounds checks from 64-bit memories doesn't
+// change which accesses trap, whether the checks are removed because they are
+// covered by a dominating check, by a dominating check and the guard region,
+// or by a loop's bounds.
+#[test]
+fn memory64_redundant_bounds_checks() -> Result<()> {
+    const WAT: &str = r#"
+        (module
+            (memory (export "memory") i64 1)
+            (data (i64.const 0) "\01\00\00\00\02\00\00\00\03\00\00\00")
+            (func (export "fields") (param i64) (result i32)
+                (i32.add
+                    (i32.add
+                        (i32.load offset=8 (local.get 0))
+                        (i32.load (local.get 0)))
+                    (i32.load offset=4 (local.get 0))))
+            (func (export "guarded") (param i64) (result i32)
+                (i32.add
+                    (i32.load (local.get 0))
+                    (i32.load offset=8 (local.get 0))))
+            (func (export "grow") (param i64) (result i32)
+                (drop (i32.load offset=4 (local.get 0)))
+                (drop (memory.grow (i64.const 1)))
+                (i32.load (local.get 0)))
+            (func (export "sum") (param i64) (result i32)
+                (local $i i64) (local $sum i32)
+                (loop $l
+                    (local.set $sum
+                        (i32.add
+                            (local.get $sum)
+                            (i32.load (i64.shl (local.get $i) (i64.const 2)))))
+                    (local.set $i (i64.add (local.get $i) (i64.const 1)))
+                    (br_if $l (i64.lt_u (local.get $i) (i64.const 1024))))
+                (local.get $sum))
+        )
+    "#;
+    const ADDRS: &[u64] = &[
+        0,
+        4,
+        65536 - 4096,
+        65536 - 4097,
+        65536 - 12,
+        65536 - 11,
+        65536 - 8,
+        65536 - 4,
+        65536,
+        65536 + 65536 - 4,
+        u64::MAX - 8,
+        u64::MAX,
+    ];
+
+    let mut outcomes = Vec::new();
+    for opt_level in [OptLevel::None, OptLevel::Speed] {
+        for guard_size in [0, 65536] {
+            for spectre in ["false", "true"] {
+                let mut config = Config::new();
+                config
+                    .wasm_memory64(true)
+                    .static_memory_maximum_size(0)
+                    .dynamic_memory_guard_size(guard_size)
+                    .cranelift_opt_level(opt_level.clone());
+                unsafe {
+                    config.cranelift_flag_set("enable_heap_access_spectre_mitigation", spectre);
+                }
+                let engine = Engine::new(&config)?;
+                let module = Module::new(&engine, WAT)?;
+                let mut store = Store::new(&engine, ());
+                let instance = Instance::new(&mut store, &module, &[])?;
+
+                let mut outcome = Vec::new();
+                for name in ["fields", "guarded", "sum", "grow"] {
+                    let func = instance.get_typed_func::<u64, i32>(&mut store, name)?;
+                    for addr in ADDRS {
+                        outcome.push(
+                            func.call(&mut store, *addr)
+                                .map_err(|e| e.downcast::<Trap>().unwrap()),
+                        );
+                    }
+                }
+                outcomes.push(outcome);
+            }
+        }
+    }
+
+    assert_eq!(outcomes[0][0], Ok(6));
+    assert_eq!(outcomes[0][4], Ok(0));
+    assert_eq!(outcomes[0][5], Err(Trap::MemoryOutOfBounds));
+    for outcome in &outcomes[1..] {
+        assert_eq!(*outcome, outcomes[0]);
+    }
+    Ok(())
+}
+
 #[test]
 fn shared_memory_basics() -> Result<()> {
     let engine = Engine::default();
@@ -0,0 +1,307 @@
+use anyhow::Result;
+use wasmtime::*;
+
+// A bump allocator which puts an 8-byte header before each block and never
+// reuses memory, driven through the exported helpers below.
+const GUEST: &str = r#"
+    (module
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func $malloc (export "malloc") (param $size i32) (result i32)
+            (local $block i32)
+            global.get $next
+            local.get $size
+            i32.store
+            global.get $next
+            i32.const 8
+            i32.add
+            local.tee $block
+            local.get $size
+            i32.const 7
+            i32.add
+            i32.const -8
+            i32.and
+            i32.add
+            global.set $next
+            local.get $block)
+        (func $free (export "free") (param $block i32)
+            ;; Poison the freed block.
+            local.get $block
+            i32.const 0xdd
+            i32.const 4
+            memory.fill)
+        (func (export "load") (param i32) (result i32)
+            local.get 0
+            i32.load)
+        (func (export "store8") (param i32 i32)
+            local.get 0
+            local.get 1
+            i32.store8)
+        (func (export "fill") (param i32 i32)
+            local.get 0
+            i32.const 0
+            local.get 1
+            memory.fill)
+        (func (export "rmw") (param i32) (result i32)
+            local.get 0
+            i32.const 1
+            i32.atomic.rmw.add)
+        (func (export "cmpxchg") (param i32) (result i32)
+            local.get 0
+            i32.const 0
+            i32.const 1
+            i32.atomic.rmw.cmpxchg)
+        (data $data "0123456789abcdef")
+        (func (export "init") (param i32 i32)
+            local.get 0
+            i32.const 0
+            local.get 1
+            memory.init $data)
+        (func (export "use_after_free") (result i32)
+            (local $block i32)
+            i32.const 16
+            call $malloc
+            local.tee $block
+            call $free
+            local.get $block
+            i32.load offset=4)
+    )
+"#;
+
+struct Guest {
+    store: Store<()>,
+    instance: Instance,
+}
+
+impl Guest {
+    fn new(sanitize: bool) -> Result<Guest> {
+        let mut config = Config::new();
+        config.memory_sanitizer(sanitize);
+        config.wasm_threads(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, GUEST)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        Ok(Guest { store, instance })
+    }
+
+    fn malloc(&mut self, size: i32) -> Result<i32> {
+        let malloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "malloc")?;
+        malloc.call(&mut self.store, size)
+    }
+
+    fn free(&mut self, ptr: i32) -> Result<()> {
+        let free = self
+            .instance
+            .get_typed_func::<i32, ()>(&mut self.store, "free")?;
+        free.call(&mut self.store, ptr)
+    }
+
+    fn load(&mut self, ptr: i32) -> Result<i32> {
+        let load = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "load")?;
+        load.call(&mut self.store, ptr)
+    }
+
+    fn store8(&mut self, ptr: i32, value: i32) -> Result<()> {
+        let store8 = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, "store8")?;
+        store8.call(&mut self.store, (ptr, value))
+    }
+
+    fn fill(&mut self, ptr: i32, len: i32) -> Result<()> {
+        let fill = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, "fill")?;
+        fill.call(&mut self.store, (ptr, len))
+    }
+
+    fn call_i32(&mut self, name: &str, ptr: i32) -> Result<i32> {
+        let func = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, name)?;
+        func.call(&mut self.store, ptr)
+    }
+
+    fn init(&mut self, ptr: i32, len: i32) -> Result<()> {
+        let init = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, "init")?;
+        init.call(&mut self.store, (ptr, len))
+    }
+}
+
+fn assert_diagnostic(result: Result<impl std::fmt::Debug>, expected: &str) {
+    let err = result.unwrap_err();
+    let message = format!("{err:?}");
+    assert!(
+        message.contains(expected),
+        "expected `{expected}` in:\n{message}"
+    );
+}
+
+#[test]
+fn valid_heap_accesses() -> Result<()> {
+    let mut guest = Guest::new(true)?;
+    let a = guest.malloc(12)?;
+    let b = guest.malloc(4)?;
+    guest.store8(a + 11, 1)?;
+    guest.fill(a, 12)?;
+    assert_eq!(guest.load(a + 8)?, 0);
+    assert_eq!(guest.load(b)?, 0);
+    guest.free(a)?;
+    guest.free(b)?;
+
+    // Memory outside of the heap isn't checked.
+    assert_eq!(guest.load(0)?, 0);
+    guest.store8(60000, 1)?;
+    Ok(())
+}
+
+#[test]
+fn use_after_free() -> Result<()> {
+    let mut guest = Guest::new(true)?;
+    let block = guest.malloc(16)?;
+    guest.free(block)?;
+    assert_diagnostic(
+        guest.load(block + 4),
+        &format!(
+            "memory sanitizer: heap use-after-free: 4-byte load at {:#x}, 4 bytes inside \
+             the 16-byte block at {:#x} which was freed",
+            block + 4,
+            block
+        ),
+    );
+
+    let use_after_free = guest
+        .instance
+        .get_typed_func::<(), i32>(&mut guest.store, "use_after_free")?;
+    assert_diagnostic(
+        use_after_free.call(&mut guest.store, ()),
+        "heap use-after-free",
+    );
+    Ok(())
+}
+
+#[test]
+fn double_free() -> Result<()> {
+    let mut guest = Guest::new(true)?;
+    let block = guest.malloc(8)?;
+    guest.free(block)?;
+    assert_diagnostic(
+        guest.free(block),
+        &format!("memory sanitizer: double free: `free` of {block:#x}, which was already freed"),
+    );
+    assert_diagnostic(guest.free(block + 1), "memory sanitizer: invalid free");
+    Ok(())
+}
+
+#[test]
+fn heap_out_of_bounds() -> Result<()> {
+    let mut guest = Guest::new(true)?;
+    let a = guest.malloc(10)?;
+    let _b = guest.malloc(10)?;
+    assert_diagnostic(
+        guest.store8(a + 10, 1),
+        &format!(
+            "memory sanitizer: heap out-of-bounds access: 1-byte store at {:#x}, 0 bytes \
+             past the end of the 10-byte block at {a:#x}",
+            a + 10
+        ),
+    );
+    assert_diagnostic(guest.load(a + 8), "heap out-of-bounds access");
+    assert_diagnostic(guest.fill(a, 11), "11-byte store");
+    Ok(())
+}
+
+#[test]
+fn atomics_and_memory_init() -> Result<()> {
+    let mut guest = Guest::new(true)?;
+    let a = guest.malloc(10)?;
+    let _b = guest.malloc(10)?;
+    assert_eq!(guest.call_i32("rmw", a)?, 0);
+    assert_eq!(guest.call_i32("cmpxchg", a + 4)?, 0);
+    guest.init(a, 10)?;
+    assert_diagnostic(
+        guest.call_i32("rmw", a + 8),
+        &format!(
+            "memory sanitizer: heap out-of-bounds access: 4-byte store at {:#x}, 0 bytes \
+             past the end of the 10-byte block at {a:#x}",
+            a + 8
+        ),
+    );
+    assert_diagnostic(
+        guest.call_i32("cmpxchg", a + 8),
+        "heap out-of-bounds access",
+    );
+    assert_diagnostic(guest.init(a, 11), "11-byte store");
+    Ok(())
+}
+
+#[test]
+fn allocation_past_the_memory() -> Result<()> {
+    let mut guest = Guest::new(true)?;
+    assert_diagnostic(
+        guest.malloc(1 << 16),
+        "memory sanitizer: invalid allocation: the allocator returned the 65536-byte block \
+         at 0x408, which ends past the 65536-byte memory",
+    );
+    Ok(())
+}
+
+#[test]
+fn disabled_by_default() -> Result<()> {
+    let mut guest = Guest::new(false)?;
+    let block = guest.malloc(16)?;
+    guest.free(block)?;
+    guest.free(block)?;
+    guest.load(block + 16)?;
+    Ok(())
+}
+
+#[test]
+fn allocator_trap_unwinds() -> Result<()> {
+    let mut config = Config::new();
+    config.memory_sanitizer(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "hook" (func $hook))
+                (memory 1)
+                (func (export "malloc") (param i32) (result i32)
+                    call $hook
+                    i32.const 1024)
+                (func (export "free") (param i32))
+                (func (export "load") (param i32) (result i32)
+                    local.get 0
+                    i32.load)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, true);
+    let hook = Func::wrap(&mut store, |caller: Caller<'_, bool>| {
+        if *caller.data() {
+            anyhow::bail!("failed allocation");
+        }
+        Ok(())
+    });
+    let instance = Instance::new(&mut store, &module, &[hook.into()])?;
+    let malloc = instance.get_typed_func::<i32, i32>(&mut store, "malloc")?;
+    let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+    assert!(malloc.call(&mut store, 4).is_err());
+
+    // The failed call doesn't leave the allocator looking busy, which would
+    // disable checking.
+    *store.data_mut() = false;
+    assert_eq!(malloc.call(&mut store, 4)?, 1024);
+    let free = instance.get_typed_func::<i32, ()>(&mut store, "free")?;
+    free.call(&mut store, 1024)?;
+    assert_diagnostic(load.call(&mut store, 1024), "heap use-after-free");
+    Ok(())
+}
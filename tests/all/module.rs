@@ -214,3 +214,33 @@ fn missing_sse_and_floats_still_works() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn emit_clif() -> Result<()> {
+    let tmpdir = tempfile::TempDir::new()?;
+    let mut config = Config::new();
+    config.emit_clif(tmpdir.path());
+    let engine = Engine::new(&config)?;
+    Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "" (func))
+                (func (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add)
+            )
+        "#,
+    )?;
+
+    // The defined function is the second one in the index space, and each
+    // stage annotates the `i32.add` with its offset in the wasm binary.
+    for stage in ["clif", "opt.clif", "lowered.vcode", "vcode"] {
+        let path = tmpdir.path().join(format!("wasm_func_1.{stage}"));
+        let contents = std::fs::read_to_string(&path)?;
+        assert!(contents.contains("@002"), "{stage}:\n{contents}");
+    }
+    assert!(!tmpdir.path().join("wasm_func_0.clif").exists());
+    Ok(())
+}
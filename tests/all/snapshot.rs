@@ -0,0 +1,236 @@
+use anyhow::Result;
+use wasmtime::*;
+
+const INIT: &str = r#"
+    (module
+        (memory (export "memory") 1)
+        (global $g (export "g") (mut i32) (i32.const 0))
+        (global $counter (mut i32) (i32.const 0))
+        (table $t (export "table") 2 funcref)
+        (func $one (result i32) i32.const 1)
+        (func $two (result i32) i32.const 2)
+        (elem declare func $two)
+        (elem (i32.const 0) $one)
+
+        (func $start
+            (global.set $counter (i32.add (global.get $counter) (i32.const 1))))
+        (start $start)
+
+        (func (export "init")
+            (i32.store (i32.const 100) (i32.const 42))
+            (drop (memory.grow (i32.const 1)))
+            (i32.store (i32.const 70000) (i32.const 43))
+            (global.set $g (i32.const 1))
+            (table.set $t (i32.const 1) (ref.func $two)))
+
+        (func (export "counter") (result i32) global.get $counter)
+        (func (export "call") (param i32) (result i32)
+            local.get 0
+            call_indirect $t (result i32))
+    )
+"#;
+
+fn check_initialized(store: &mut Store<()>, instance: Instance) -> Result<()> {
+    let memory = instance.get_memory(&mut *store, "memory").unwrap();
+    assert_eq!(memory.size(&*store), 2);
+    assert_eq!(memory.data(&*store)[100], 42);
+    assert_eq!(memory.data(&*store)[70000], 43);
+    let g = instance.get_global(&mut *store, "g").unwrap();
+    assert_eq!(g.get(&mut *store).i32(), Some(1));
+    let counter = instance.get_typed_func::<(), i32>(&mut *store, "counter")?;
+    assert_eq!(counter.call(&mut *store, ())?, 1);
+    let call = instance.get_typed_func::<i32, i32>(&mut *store, "call")?;
+    assert_eq!(call.call(&mut *store, 0)?, 1);
+    assert_eq!(call.call(&mut *store, 1)?, 2);
+    Ok(())
+}
+
+fn initialized_snapshot(engine: &Engine) -> Result<Module> {
+    let module = Module::new(engine, INIT)?;
+    let mut store = Store::new(engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let init = instance.get_typed_func::<(), ()>(&mut store, "init")?;
+    init.call(&mut store, ())?;
+    instance.snapshot(&mut store)
+}
+
+#[test]
+fn snapshot_memories_globals_and_tables() -> Result<()> {
+    let engine = Engine::default();
+    let snapshot = initialized_snapshot(&engine)?;
+
+    // The memory growth during initialization is reflected in the type of the
+    // new module.
+    let ty = snapshot.get_export("memory").unwrap();
+    assert_eq!(ty.memory().unwrap().minimum(), 2);
+
+    for _ in 0..2 {
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &snapshot, &[])?;
+        check_initialized(&mut store, instance)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn snapshot_serialize() -> Result<()> {
+    let engine = Engine::default();
+    let bytes = initialized_snapshot(&engine)?.serialize()?;
+    let snapshot = unsafe { Module::deserialize(&engine, &bytes)? };
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &snapshot, &[])?;
+    check_initialized(&mut store, instance)
+}
+
+#[test]
+fn snapshot_with_pooling_and_cow() -> Result<()> {
+    let mut config = Config::new();
+    config.memory_init_cow(true);
+    config.allocation_strategy(InstanceAllocationStrategy::Pooling(
+        PoolingAllocationConfig::default(),
+    ));
+    let engine = Engine::new(&config)?;
+    let snapshot = initialized_snapshot(&engine)?;
+    snapshot.initialize_copy_on_write_image()?;
+    for _ in 0..3 {
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &snapshot, &[])?;
+        check_initialized(&mut store, instance)?;
+
+        // Dirty the memory, which must not leak into the next instance.
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+        memory.data_mut(&mut store)[100] = 0;
+        memory.data_mut(&mut store)[200] = 1;
+    }
+    Ok(())
+}
+
+#[test]
+fn snapshot_of_snapshot() -> Result<()> {
+    let engine = Engine::default();
+    let snapshot = initialized_snapshot(&engine)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &snapshot, &[])?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    memory.data_mut(&mut store)[200] = 7;
+    let snapshot = instance.snapshot(&mut store)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &snapshot, &[])?;
+    check_initialized(&mut store, instance)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.data(&store)[200], 7);
+    Ok(())
+}
+
+#[test]
+fn snapshot_dropped_segments() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (data $a "abc")
+                (data $b "def")
+                (func (export "init") (data.drop $a))
+                (func (export "copy") (param i32)
+                    (memory.init $b (local.get 0) (i32.const 0) (i32.const 3)))
+                (func (export "copy-dropped") (param i32)
+                    (memory.init $a (local.get 0) (i32.const 0) (i32.const 3)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "init")?
+        .call(&mut store, ())?;
+    let snapshot = instance.snapshot(&mut store)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &snapshot, &[])?;
+    instance
+        .get_typed_func::<i32, ()>(&mut store, "copy")?
+        .call(&mut store, 10)?;
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(&memory.data(&store)[10..13], b"def");
+    let err = instance
+        .get_typed_func::<i32, ()>(&mut store, "copy-dropped")?
+        .call(&mut store, 10)
+        .unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::MemoryOutOfBounds);
+    Ok(())
+}
+
+#[test]
+fn snapshot_unsupported() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+
+    let module = Module::new(&engine, r#"(module (import "" "" (memory 1)))"#)?;
+    let memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    let instance = Instance::new(&mut store, &module, &[memory.into()])?;
+    let err = instance.snapshot(&mut store).unwrap_err();
+    assert!(err.to_string().contains("imports memories"), "{err}");
+
+    let module = Module::new(
+        &engine,
+        r#"(module (global (export "g") (mut externref) (ref.null extern)))"#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let g = instance.get_global(&mut store, "g").unwrap();
+    g.set(&mut store, Val::ExternRef(Some(ExternRef::new(1))))?;
+    let err = instance.snapshot(&mut store).unwrap_err();
+    assert!(err.to_string().contains("externref"), "{err}");
+
+    let module = Module::new(&engine, r#"(module (table (export "t") 1 funcref))"#)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let t = instance.get_table(&mut store, "t").unwrap();
+    let f = Func::wrap(&mut store, || {});
+    t.set(&mut store, 0, f.into())?;
+    let err = instance.snapshot(&mut store).unwrap_err();
+    assert!(
+        err.to_string().contains("not defined by this instance"),
+        "{err}"
+    );
+    Ok(())
+}
+
+// Libcall relocations in the text section must be carried over into the new
+// artifact, see `missing_sse_and_floats_still_works` in `module.rs`.
+#[test]
+#[cfg_attr(not(target_arch = "x86_64"), ignore)]
+fn snapshot_with_relocations() -> Result<()> {
+    let mut config = Config::new();
+    config.wasm_simd(false);
+    unsafe {
+        config.cranelift_flag_set("has_sse41", "false");
+    }
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (global $g (mut f32) (f32.const 0))
+                (func (export "init") (global.set $g (f32.const 2.5)))
+                (func (export "ceil") (result f32)
+                    global.get $g
+                    f32.ceil)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    instance
+        .get_typed_func::<(), ()>(&mut store, "init")?
+        .call(&mut store, ())?;
+    let bytes = instance.snapshot(&mut store)?.serialize()?;
+    let snapshot = unsafe { Module::deserialize(&engine, &bytes)? };
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &snapshot, &[])?;
+    let ceil = instance.get_typed_func::<(), f32>(&mut store, "ceil")?;
+    assert_eq!(ceil.call(&mut store, ())?, 3.0);
+    Ok(())
+}
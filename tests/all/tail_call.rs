@@ -0,0 +1,167 @@
+#![cfg(target_arch = "x86_64")]
+
+use anyhow::Result;
+use wasmtime::*;
+
+fn tail_call_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.wasm_tail_call(true);
+    Engine::new(&config)
+}
+
+#[test]
+fn tail_call_disabled_by_default() {
+    let engine = Engine::default();
+    let wat = r#"
+        (module
+            (func $f (result i32) i32.const 1)
+            (func (export "run") (result i32) return_call $f)
+        )
+    "#;
+    assert!(Module::new(&engine, wat).is_err());
+}
+
+#[test]
+fn tail_call_host_function() -> Result<()> {
+    let engine = tail_call_engine()?;
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host (param i32 i32 i32 i32 i32) (result i32)))
+                (func (export "run") (param i32 i32 i32 i32 i32) (result i32)
+                    local.get 4
+                    local.get 3
+                    local.get 2
+                    local.get 1
+                    local.get 0
+                    return_call $host)
+            )
+        "#,
+    )?;
+    let host = Func::wrap(&mut store, |a: i32, b: i32, c: i32, d: i32, e: i32| {
+        a * 10000 + b * 1000 + c * 100 + d * 10 + e
+    });
+    let instance = Instance::new(&mut store, &module, &[host.into()])?;
+    let run = instance.get_typed_func::<(i32, i32, i32, i32, i32), i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, (1, 2, 3, 4, 5))?, 54321);
+    Ok(())
+}
+
+#[test]
+fn tail_call_needs_caller_stack_args() -> Result<()> {
+    let engine = tail_call_engine()?;
+    let mut store = Store::new(&engine, ());
+
+    // Some of the callee's arguments are passed on the stack while `run`
+    // receives none, so the tail call grows the stack argument area; `shrink`
+    // does the opposite.
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $callee (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                    local.get 0
+                    i64.const 10
+                    i64.mul
+                    local.get 7
+                    i64.add)
+                (func $run (export "run") (result i64)
+                    i64.const 1
+                    i64.const 2
+                    i64.const 3
+                    i64.const 4
+                    i64.const 5
+                    i64.const 6
+                    i64.const 7
+                    i64.const 8
+                    return_call $callee)
+                (func $id (param i64) (result i64)
+                    local.get 0)
+                (func (export "shrink") (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                    local.get 7
+                    return_call $id)
+                (func (export "call_run") (result i64)
+                    (local i64)
+                    i64.const 100
+                    local.set 0
+                    call $run
+                    local.get 0
+                    i64.add)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let run = instance.get_typed_func::<(), i64>(&mut store, "run")?;
+    for _ in 0..100 {
+        assert_eq!(run.call(&mut store, ())?, 18);
+    }
+    let mut results = [Val::I64(0)];
+    instance
+        .get_func(&mut store, "run")
+        .unwrap()
+        .call(&mut store, &[], &mut results)?;
+    assert_eq!(results[0].unwrap_i64(), 18);
+
+    let call_run = instance.get_typed_func::<(), i64>(&mut store, "call_run")?;
+    assert_eq!(call_run.call(&mut store, ())?, 118);
+
+    let shrink = instance
+        .get_typed_func::<(i64, i64, i64, i64, i64, i64, i64, i64), i64>(&mut store, "shrink")?;
+    for _ in 0..100 {
+        assert_eq!(shrink.call(&mut store, (1, 2, 3, 4, 5, 6, 7, 8))?, 8);
+    }
+    Ok(())
+}
+
+#[test]
+fn tail_call_backtraces() -> Result<()> {
+    let engine = tail_call_engine()?;
+    let mut store = Store::new(&engine, Vec::new());
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "capture" (func $capture))
+                (func $trap unreachable)
+                (func $tail_trap (export "tail_trap") return_call $trap)
+                (func $tail_capture (export "tail_capture") return_call $capture)
+                (func (export "call_tail_trap") call $tail_trap)
+                (func (export "call_tail_capture") call $tail_capture)
+            )
+        "#,
+    )?;
+    let capture = Func::wrap(&mut store, |mut caller: Caller<'_, Vec<u32>>| {
+        let frames = WasmBacktrace::capture(&caller)
+            .frames()
+            .iter()
+            .map(|f| f.func_index())
+            .collect();
+        *caller.data_mut() = frames;
+    });
+    let instance = Instance::new(&mut store, &module, &[capture.into()])?;
+
+    // The frame of the function which made the tail call is gone by the time
+    // the callee traps.
+    for (name, expected) in [("tail_trap", vec![1]), ("call_tail_trap", vec![1, 4])] {
+        let run = instance.get_typed_func::<(), ()>(&mut store, name)?;
+        let e = run.call(&mut store, ()).unwrap_err();
+        let bt = e.downcast_ref::<WasmBacktrace>().unwrap();
+        let indices = bt
+            .frames()
+            .iter()
+            .map(|f| f.func_index())
+            .collect::<Vec<_>>();
+        assert_eq!(indices, expected, "{name}");
+    }
+
+    // The same applies when a host function is tail called.
+    for (name, expected) in [("tail_capture", vec![]), ("call_tail_capture", vec![5])] {
+        let run = instance.get_typed_func::<(), ()>(&mut store, name)?;
+        run.call(&mut store, ())?;
+        assert_eq!(*store.data(), expected, "{name}");
+    }
+    Ok(())
+}
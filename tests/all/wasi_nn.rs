@@ -0,0 +1,101 @@
+#![cfg(feature = "wasi-nn")]
+
+use anyhow::Result;
+use wasmtime::*;
+use wasmtime_wasi_nn::{
+    Backend, BackendError, BackendExecutionContext, BackendGraph, ExecutionTarget, GraphEncoding,
+    Tensor, WasiNnCtx,
+};
+
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_INVALID_ARGUMENT: i32 = 1;
+
+// Loads the graph registered under the name at 16 and stores its handle at 8.
+const GUEST: &str = r#"
+    (module
+        (import "wasi_ephemeral_nn" "load_by_name"
+            (func $load_by_name (param i32 i32 i32) (result i32)))
+        (import "wasi_ephemeral_nn" "init_execution_context"
+            (func $init_execution_context (param i32 i32) (result i32)))
+        (import "wasi_ephemeral_nn" "compute"
+            (func $compute (param i32) (result i32)))
+
+        (memory (export "memory") 1)
+        (data (i32.const 16) "model")
+
+        (func (export "load") (param $len i32) (result i32)
+            (call $load_by_name (i32.const 16) (local.get $len) (i32.const 8)))
+
+        (func (export "run") (result i32)
+            (local $errno i32)
+            (local.set $errno
+                (call $init_execution_context (i32.load (i32.const 8)) (i32.const 12)))
+            (if (local.get $errno) (then (return (local.get $errno))))
+            (call $compute (i32.load (i32.const 12))))
+    )
+"#;
+
+/// A backend whose graphs count how many times they've been run.
+struct Counter;
+
+impl Backend for Counter {
+    fn name(&self) -> &str {
+        "counter"
+    }
+    fn load(
+        &mut self,
+        _: &[&[u8]],
+        _: ExecutionTarget,
+    ) -> Result<Box<dyn BackendGraph>, BackendError> {
+        Ok(Box::new(CounterGraph))
+    }
+}
+
+struct CounterGraph;
+
+impl BackendGraph for CounterGraph {
+    fn init_execution_context(&self) -> Result<Box<dyn BackendExecutionContext>, BackendError> {
+        Ok(Box::new(CounterContext))
+    }
+}
+
+struct CounterContext;
+
+static RUNS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+impl BackendExecutionContext for CounterContext {
+    fn set_input(&mut self, index: u32, _: &Tensor<'_>) -> Result<(), BackendError> {
+        Err(BackendError::InvalidInput(index))
+    }
+    fn compute(&mut self) -> Result<(), BackendError> {
+        RUNS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+    fn get_output(&mut self, index: u32, _: &mut [u8]) -> Result<u32, BackendError> {
+        Err(BackendError::InvalidInput(index))
+    }
+}
+
+#[test]
+fn wasi_nn_load_by_name() -> Result<()> {
+    let mut ctx = WasiNnCtx::empty();
+    ctx.register_backend(GraphEncoding::Tensorflow, Box::new(Counter));
+    let graph = ctx.load_graph(&[], GraphEncoding::Tensorflow, ExecutionTarget::Cpu)?;
+    ctx.register_graph("model", graph);
+
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi_nn::add_to_linker(&mut linker, |ctx| ctx)?;
+    let module = Module::new(&engine, GUEST)?;
+    let mut store = Store::new(&engine, ctx);
+    let instance = linker.instantiate(&mut store, &module)?;
+    let load = instance.get_typed_func::<i32, i32>(&mut store, "load")?;
+    let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+
+    // "mode" was never registered.
+    assert_eq!(load.call(&mut store, 4)?, ERRNO_INVALID_ARGUMENT);
+    assert_eq!(load.call(&mut store, 5)?, ERRNO_SUCCESS);
+    assert_eq!(run.call(&mut store, ())?, ERRNO_SUCCESS);
+    assert_eq!(RUNS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    Ok(())
+}
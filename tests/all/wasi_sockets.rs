@@ -0,0 +1,212 @@
+use anyhow::Result;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpListener, UdpSocket};
+use wasmtime::*;
+use wasmtime_wasi::sync::{ambient_authority, WasiCtxBuilder};
+use wasmtime_wasi::WasiCtx;
+
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_NOTSUP: i32 = 58;
+const ERRNO_PERM: i32 = 63;
+
+// The guest keeps a `socket_address` for 127.0.0.1 at 8, the descriptor it
+// got last at 16, the size it got last at 20, the sender of the last datagram
+// at 32, and what it received at 256.
+const GUEST: &str = r#"
+    (module
+        (import "wasi_experimental_sockets" "sock_connect"
+            (func $sock_connect (param i32 i32) (result i32)))
+        (import "wasi_experimental_sockets" "sock_bind_udp"
+            (func $sock_bind_udp (param i32 i32) (result i32)))
+        (import "wasi_experimental_sockets" "sock_send_to"
+            (func $sock_send_to (param i32 i32 i32 i32 i32 i32) (result i32)))
+        (import "wasi_experimental_sockets" "sock_recv_from"
+            (func $sock_recv_from (param i32 i32 i32 i32 i32 i32 i32) (result i32)))
+        (import "wasi_experimental_sockets" "resolve"
+            (func $resolve (param i32 i32 i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+            (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_read"
+            (func $fd_read (param i32 i32 i32 i32) (result i32)))
+
+        (memory (export "memory") 1)
+        (data (i32.const 0) "\7f\00\00\01")
+        (data (i32.const 64) "\80\00\00\00\04\00\00\00")
+        (data (i32.const 72) "\00\01\00\00\10\00\00\00")
+        (data (i32.const 128) "ping")
+        (data (i32.const 512) "127.0.0.1")
+
+        (func $addr (param $port i32)
+            (i32.store8 (i32.const 8) (i32.const 0))
+            (i32.store16 (i32.const 10) (local.get $port))
+            (i32.store (i32.const 12) (i32.const 0)))
+
+        (func (export "connect") (param $port i32) (result i32)
+            (call $addr (local.get $port))
+            (call $sock_connect (i32.const 8) (i32.const 16)))
+
+        (func (export "exchange") (result i32)
+            (local $errno i32)
+            (local.set $errno
+                (call $fd_write (i32.load (i32.const 16)) (i32.const 64) (i32.const 1) (i32.const 20)))
+            (if (local.get $errno) (then (return (local.get $errno))))
+            (call $fd_read (i32.load (i32.const 16)) (i32.const 72) (i32.const 1) (i32.const 20)))
+
+        (func (export "bind_udp") (param $port i32) (result i32)
+            (call $addr (local.get $port))
+            (call $sock_bind_udp (i32.const 8) (i32.const 16)))
+
+        (func (export "send_to") (param $port i32) (result i32)
+            (call $addr (local.get $port))
+            (call $sock_send_to
+                (i32.load (i32.const 16)) (i32.const 64) (i32.const 1) (i32.const 0)
+                (i32.const 8) (i32.const 20)))
+
+        (func (export "recv_from") (result i32)
+            (i32.store (i32.const 36) (i32.const 48))
+            (call $sock_recv_from
+                (i32.load (i32.const 16)) (i32.const 72) (i32.const 1) (i32.const 0)
+                (i32.const 32) (i32.const 20) (i32.const 24)))
+
+        (func (export "resolve") (param $port i32) (result i32)
+            (i32.store (i32.const 36) (i32.const 48))
+            (call $resolve
+                (i32.const 512) (i32.const 9) (local.get $port) (i32.const 32) (i32.const 1)
+                (i32.const 20)))
+    )
+"#;
+
+struct Guest {
+    store: Store<WasiCtx>,
+    instance: Instance,
+}
+
+impl Guest {
+    fn new(wasi: WasiCtx) -> Result<Guest> {
+        let engine = Engine::default();
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::add_to_linker(&mut linker, |s| s)?;
+        let module = Module::new(&engine, GUEST)?;
+        let mut store = Store::new(&engine, wasi);
+        let instance = linker.instantiate(&mut store, &module)?;
+        Ok(Guest { store, instance })
+    }
+
+    fn call(&mut self, name: &str, port: u16) -> Result<i32> {
+        self.instance
+            .get_typed_func::<i32, i32>(&mut self.store, name)?
+            .call(&mut self.store, port.into())
+    }
+
+    fn call0(&mut self, name: &str) -> Result<i32> {
+        self.instance
+            .get_typed_func::<(), i32>(&mut self.store, name)?
+            .call(&mut self.store, ())
+    }
+
+    fn memory(&mut self, range: std::ops::Range<usize>) -> Vec<u8> {
+        let memory = self.instance.get_memory(&mut self.store, "memory").unwrap();
+        memory.data(&self.store)[range].to_vec()
+    }
+
+    fn size(&mut self) -> u32 {
+        u32::from_le_bytes(self.memory(20..24).try_into().unwrap())
+    }
+
+    /// The family, port and octets of the `socket_address` at 32.
+    fn sender(&mut self) -> (u8, u16, Vec<u8>) {
+        let family = self.memory(32..33)[0];
+        let port = u16::from_le_bytes(self.memory(34..36).try_into().unwrap());
+        (family, port, self.memory(48..52))
+    }
+}
+
+#[test]
+fn tcp_over_loopback() -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    let server = std::thread::spawn(move || -> std::io::Result<()> {
+        let (mut stream, _) = listener.accept()?;
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf)?;
+        assert_eq!(&buf, b"ping");
+        stream.write_all(b"pong")
+    });
+
+    let mut guest = Guest::new(
+        WasiCtxBuilder::new()
+            .inherit_network(ambient_authority())
+            .allow_tcp_connect(Ipv4Addr::LOCALHOST.into(), 32, port..=port)
+            .build(),
+    )?;
+    assert_eq!(guest.call("connect", port ^ 1)?, ERRNO_PERM);
+    assert_eq!(guest.call("connect", port)?, ERRNO_SUCCESS);
+    assert_eq!(guest.call0("exchange")?, ERRNO_SUCCESS);
+    assert_eq!(guest.size(), 4);
+    assert_eq!(guest.memory(256..260), b"pong");
+    server.join().unwrap()?;
+    Ok(())
+}
+
+#[test]
+fn udp_over_loopback() -> Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = socket.local_addr()?.port();
+
+    let mut guest = Guest::new(
+        WasiCtxBuilder::new()
+            .inherit_network(ambient_authority())
+            .allow_udp_bind(Ipv4Addr::LOCALHOST.into(), 32, 0..=0)
+            .allow_udp_send(Ipv4Addr::LOCALHOST.into(), 32, port..=port)
+            .build(),
+    )?;
+    assert_eq!(guest.call("bind_udp", port)?, ERRNO_PERM);
+    assert_eq!(guest.call("bind_udp", 0)?, ERRNO_SUCCESS);
+    assert_eq!(guest.call("send_to", port ^ 1)?, ERRNO_PERM);
+    assert_eq!(guest.call("send_to", port)?, ERRNO_SUCCESS);
+    assert_eq!(guest.size(), 4);
+
+    let mut buf = [0; 16];
+    let (n, from) = socket.recv_from(&mut buf)?;
+    assert_eq!(&buf[..n], b"ping");
+    socket.send_to(b"pong", from)?;
+
+    assert_eq!(guest.call0("recv_from")?, ERRNO_SUCCESS);
+    assert_eq!(guest.size(), 4);
+    assert_eq!(guest.memory(256..260), b"pong");
+    assert_eq!(guest.sender(), (0, port, vec![127, 0, 0, 1]));
+    Ok(())
+}
+
+#[test]
+fn name_lookup() -> Result<()> {
+    let mut guest = Guest::new(
+        WasiCtxBuilder::new()
+            .inherit_network(ambient_authority())
+            .build(),
+    )?;
+    assert_eq!(guest.call("resolve", 80)?, ERRNO_PERM);
+
+    let mut guest = Guest::new(
+        WasiCtxBuilder::new()
+            .inherit_network(ambient_authority())
+            .allow_name_lookup()
+            .build(),
+    )?;
+    assert_eq!(guest.call("resolve", 80)?, ERRNO_SUCCESS);
+    assert_eq!(guest.size(), 1);
+    assert_eq!(guest.sender(), (0, 80, vec![127, 0, 0, 1]));
+    Ok(())
+}
+
+#[test]
+fn no_network() -> Result<()> {
+    // Allowing an address doesn't help without a network to reach it with.
+    let mut guest = Guest::new(
+        WasiCtxBuilder::new()
+            .allow_tcp_connect(Ipv4Addr::LOCALHOST.into(), 32, 0..=65535)
+            .build(),
+    )?;
+    assert_eq!(guest.call("connect", 80)?, ERRNO_NOTSUP);
+    Ok(())
+}
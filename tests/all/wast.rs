@@ -13,14 +13,21 @@ include!(concat!(env!("OUT_DIR"), "/wast_testsuite_tests.rs"));
 
 // Each of the tests included from `wast_testsuite_tests` will call this
 // function which actually executes the `wast` test suite given the `strategy`
-// to compile it.
-fn run_wast(wast: &str, strategy: Strategy, pooling: bool) -> anyhow::Result<()> {
+// to compile it. With `skip_unsupported` the modules which use features that
+// the compiler doesn't support are skipped, along with the directives using
+// them.
+fn run_wast(
+    wast: &str,
+    strategy: Strategy,
+    pooling: bool,
+    skip_unsupported: bool,
+) -> anyhow::Result<()> {
     drop(env_logger::try_init());
 
     let wast_bytes = std::fs::read(wast).with_context(|| format!("failed to read `{}`", wast))?;
 
     match strategy {
-        Strategy::Cranelift => {}
+        Strategy::Cranelift | Strategy::Winch => {}
         _ => unimplemented!(),
     }
 
@@ -31,6 +38,7 @@ fn run_wast(wast: &str, strategy: Strategy, pooling: bool) -> anyhow::Result<()>
     let threads = feature_found(wast, "threads");
     let reference_types = !(threads && feature_found(wast, "proposals"));
     let relaxed_simd = feature_found(wast, "relaxed-simd");
+    let tail_call = feature_found(wast, "tail-call");
     let use_shared_memory = feature_found_src(&wast_bytes, "shared_memory")
         || feature_found_src(&wast_bytes, "shared)");
 
@@ -40,11 +48,13 @@ fn run_wast(wast: &str, strategy: Strategy, pooling: bool) -> anyhow::Result<()>
     }
 
     let mut cfg = Config::new();
-    cfg.wasm_multi_memory(multi_memory)
+    cfg.strategy(strategy)
+        .wasm_multi_memory(multi_memory)
         .wasm_threads(threads)
         .wasm_memory64(memory64)
         .wasm_reference_types(reference_types)
         .wasm_relaxed_simd(relaxed_simd)
+        .wasm_tail_call(tail_call)
         .cranelift_debug_verifier(true);
 
     cfg.wasm_component_model(feature_found(wast, "component-model"));
@@ -145,6 +155,12 @@ fn run_wast(wast: &str, strategy: Strategy, pooling: bool) -> anyhow::Result<()>
         let store = Store::new(&engine, ());
         let mut wast_context = WastContext::new(store);
         wast_context.register_spectest(use_shared_memory)?;
+        if skip_unsupported {
+            wast_context.skip_unsupported(|e| {
+                e.chain()
+                    .any(|e| e.to_string().starts_with("Compilation error: Unsupported"))
+            });
+        }
         wast_context
             .run_buffer(wast.to_str().unwrap(), &wast_bytes)
             .with_context(|| format!("failed to run spec test with {desc} engine"))?;
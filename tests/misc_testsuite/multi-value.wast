@@ -0,0 +1,372 @@
+(module $m
+  (func (export "pair") (param i32 i64) (result i64 i32)
+    (local.get 1)
+    (local.get 0))
+
+  (func (export "many") (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32 i64 f32 f64 i32)
+    (i32.add (local.get 0) (local.get 7))
+    (i64.extend_i32_u (local.get 1))
+    (f32.convert_i32_s (local.get 2))
+    (f64.convert_i32_s (local.get 3))
+    (local.get 6))
+)
+(register "m" $m)
+
+(module
+  (import "m" "pair" (func $imported-pair (param i32 i64) (result i64 i32)))
+  (import "m" "many" (func $imported-many (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32 i64 f32 f64 i32)))
+
+  (type $pair (func (param i32 i64) (result i64 i32)))
+  (table funcref (elem $pair $imported-pair))
+
+  (func $pair (param i32 i64) (result i64 i32)
+    (local.get 1)
+    (local.get 0))
+
+  (func $swap (param f64 f32) (result f32 f64)
+    (local.get 1)
+    (local.get 0))
+
+  (func (export "swap") (param f64 f32) (result f32 f64)
+    (call $swap (local.get 0) (local.get 1)))
+
+  (func (export "call-pair") (param i32 i64) (result i64)
+    (call $pair (local.get 0) (local.get 1))
+    (i64.extend_i32_u)
+    (i64.sub))
+
+  (func (export "call-imported-pair") (param i32 i64) (result i64)
+    (call $imported-pair (local.get 0) (local.get 1))
+    (i64.extend_i32_u)
+    (i64.sub))
+
+  (func (export "call-indirect-pair") (param i32 i64 i32) (result i64 i32)
+    (call_indirect (type $pair) (local.get 0) (local.get 1) (local.get 2)))
+
+  (func (export "call-imported-many") (result f64)
+    (local i32 i64 f32 f64 i32)
+    (call $imported-many
+      (i32.const 1) (i32.const 2) (i32.const 3) (i32.const 4)
+      (i32.const 5) (i32.const 6) (i32.const 7) (i32.const 8))
+    (local.set 4)
+    (local.set 3)
+    (local.set 2)
+    (local.set 1)
+    (local.set 0)
+    (f64.add
+      (f64.add
+        (f64.convert_i32_s (local.get 0))
+        (f64.convert_i64_s (local.get 1)))
+      (f64.add
+        (f64.promote_f32 (local.get 2))
+        (f64.add (local.get 3) (f64.convert_i32_s (local.get 4))))))
+
+  (func $many (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32 i64 f32 f64 i32)
+    (i32.add (local.get 0) (local.get 7))
+    (i64.extend_i32_u (local.get 1))
+    (f32.convert_i32_s (local.get 2))
+    (f64.convert_i32_s (local.get 3))
+    (local.get 6))
+
+  (func (export "call-many-live-values") (param i32) (result i32 i32 i64 f32 f64 i32 i32)
+    (local.get 0)
+    (call $many
+      (local.get 0) (i32.const 2) (i32.const 3) (i32.const 4)
+      (i32.const 5) (i32.const 6) (i32.const 7) (i32.const 8))
+    (i32.mul (local.get 0) (i32.const 3)))
+
+  (func (export "block") (param i32) (result i32 i32)
+    (block (result i32 i32)
+      (local.get 0)
+      (i32.const 1)))
+
+  (func (export "block-params") (param i32 i32) (result i32 i32)
+    (local.get 0)
+    (local.get 1)
+    (block (param i32 i32) (result i32 i32)
+      (i32.add (i32.const 10))))
+
+  (func (export "block-params-single") (param i32) (result i32)
+    (i32.const 3)
+    (local.get 0)
+    (block (param i32 i32) (result i32)
+      (i32.sub)))
+
+  (func (export "block-constants") (result i32 i64 f32 f64)
+    (block (result i32 i64 f32 f64)
+      (i32.const 1)
+      (i64.const 2)
+      (f32.const 3)
+      (f64.const 4)))
+
+  (func (export "br") (param i32) (result i32 i32)
+    (block (result i32 i32)
+      (i32.const 1)
+      (block (result i32 i32)
+        (local.get 0)
+        (i32.const 2)
+        (i32.const 3)
+        (br 1))
+      (drop)
+      (drop)
+      (i32.const 4)))
+
+  (func (export "br-if") (param i32) (result i32 i32)
+    (i32.const 100)
+    (block (param i32) (result i32 i32)
+      (i32.const 7)
+      (i32.const 8)
+      (br_if 0 (local.get 0))
+      (drop)
+      (drop)
+      (i32.const 9)))
+
+  (func (export "br-if-live") (param i32) (result i32)
+    (local i32)
+    (block (result i32 i32)
+      (i32.const 1)
+      (i32.const 2)
+      (i32.const 3)
+      (i32.const 4)
+      (br_if 0 (local.get 0))
+      (local.set 1)
+      (i32.add)
+      (i32.add (local.get 1)))
+    (i32.sub))
+
+  (func (export "br-table") (param i32) (result i32 i32)
+    (block (result i32 i32)
+      (block (result i32 i32)
+        (block (result i32 i32)
+          (i32.const 10)
+          (i32.const 20)
+          (br_table 0 1 2 (local.get 0)))
+        (i32.add)
+        (i32.const 1)
+        (br 1))
+      (i32.sub)
+      (i32.const 2)))
+
+  (func (export "return") (param i32) (result i32 i64)
+    (block
+      (br_if 0 (local.get 0))
+      (i32.const 1)
+      (i64.const 2)
+      (return))
+    (i32.const 3)
+    (i64.const 4))
+
+  (func (export "return-from-loop") (param i32) (result i64 i32)
+    (loop
+      (i64.const 5)
+      (i32.const 6)
+      (br_if 1 (local.get 0))
+      (drop)
+      (drop)
+      (local.set 0 (i32.const 1))
+      (br 0))
+    (unreachable))
+
+  (func (export "loop-params") (param i32) (result i32 i32)
+    (i32.const 0)
+    (i32.const 1)
+    (loop (param i32 i32) (result i32 i32)
+      (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+      ;; [a, b] => [b, a + b]
+      (call $pair (i32.const 0) (i64.const 0))
+      (drop)
+      (drop)
+      (block (param i32 i32) (result i32 i32)
+        (local.tee 0 (local.get 0))
+        (drop))
+      (call $fib-step)
+      (br_if 0 (local.get 0))))
+
+  (func $fib-step (param i32 i32) (result i32 i32)
+    (local.get 1)
+    (i32.add (local.get 0) (local.get 1)))
+
+  (func (export "loop-single-param") (param i32) (result i32)
+    (i32.const 0)
+    (loop (param i32) (result i32)
+      (i32.add (local.get 0))
+      (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+      (br_if 0 (local.get 0))))
+
+  (func (export "if") (param i32) (result i32 i32)
+    (if (result i32 i32) (local.get 0)
+      (then (i32.const 1) (i32.const 2))
+      (else (i32.const 3) (i32.const 4))))
+
+  (func (export "if-params") (param i32 i32 i32) (result i32 i32)
+    (local.get 1)
+    (local.get 2)
+    (if (param i32 i32) (result i32 i32) (local.get 0)
+      (then
+        (i32.add)
+        (i32.const 0))
+      (else
+        (i32.sub)
+        (i32.const 1))))
+
+  (func (export "if-params-single") (param i32 i32) (result i32)
+    (local.get 1)
+    (if (param i32) (result i32) (local.get 0)
+      (then (i32.const 2) (i32.mul))
+      (else (i32.const 3) (i32.mul))))
+
+  (func (export "if-params-no-else") (param i32 i32 i32) (result i32 i32)
+    (local.get 1)
+    (local.get 2)
+    (if (param i32 i32) (result i32 i32) (local.get 0)
+      (then
+        (drop)
+        (drop)
+        (i32.const 7)
+        (i32.const 8))))
+
+  (func (export "if-params-clobber") (param i32) (result i32 i32)
+    (i32.const 1)
+    (i32.const 2)
+    (if (param i32 i32) (result i32 i32) (local.get 0)
+      (then
+        (drop)
+        (drop)
+        (i32.const 10)
+        (i32.const 20)
+        (i32.const 30)
+        (call $fib-step)
+        (call $fib-step)
+        (drop)
+        (call $fib-step))))
+
+  (func (export "if-branch-out") (param i32) (result i32 i32)
+    (block (result i32 i32)
+      (i32.const 5)
+      (i32.const 6)
+      (if (param i32 i32) (result i32 i32) (local.get 0)
+        (then (br 1))
+        (else (i32.add) (i32.const 0)))))
+
+  (func (export "unreachable-end") (result i32 i32)
+    (block (result i32 i32)
+      (i32.const 1)
+      (i32.const 2)
+      (br 0)
+      (i32.add)
+      (unreachable)))
+)
+
+(assert_return (invoke "swap" (f64.const 1.5) (f32.const 2.5)) (f32.const 2.5) (f64.const 1.5))
+(assert_return (invoke "call-pair" (i32.const 3) (i64.const 10)) (i64.const 7))
+(assert_return (invoke "call-imported-pair" (i32.const 3) (i64.const 10)) (i64.const 7))
+(assert_return (invoke "call-indirect-pair" (i32.const 1) (i64.const 2) (i32.const 0)) (i64.const 2) (i32.const 1))
+(assert_return (invoke "call-indirect-pair" (i32.const 1) (i64.const 2) (i32.const 1)) (i64.const 2) (i32.const 1))
+(assert_return (invoke "call-imported-many") (f64.const 25))
+(assert_return (invoke "call-many-live-values" (i32.const 5))
+  (i32.const 5) (i32.const 13) (i64.const 2) (f32.const 3) (f64.const 4) (i32.const 7) (i32.const 15))
+(assert_return (invoke "block" (i32.const 7)) (i32.const 7) (i32.const 1))
+(assert_return (invoke "block-params" (i32.const 1) (i32.const 2)) (i32.const 1) (i32.const 12))
+(assert_return (invoke "block-params-single" (i32.const 1)) (i32.const 2))
+(assert_return (invoke "block-constants") (i32.const 1) (i64.const 2) (f32.const 3) (f64.const 4))
+(assert_return (invoke "br" (i32.const 7)) (i32.const 2) (i32.const 3))
+(assert_return (invoke "br-if" (i32.const 0)) (i32.const 100) (i32.const 9))
+(assert_return (invoke "br-if" (i32.const 1)) (i32.const 7) (i32.const 8))
+(assert_return (invoke "br-if-live" (i32.const 0)) (i32.const -8))
+(assert_return (invoke "br-if-live" (i32.const 1)) (i32.const -1))
+(assert_return (invoke "br-table" (i32.const 0)) (i32.const 30) (i32.const 1))
+(assert_return (invoke "br-table" (i32.const 1)) (i32.const -10) (i32.const 2))
+(assert_return (invoke "br-table" (i32.const 2)) (i32.const 10) (i32.const 20))
+(assert_return (invoke "br-table" (i32.const 3)) (i32.const 10) (i32.const 20))
+(assert_return (invoke "return" (i32.const 0)) (i32.const 1) (i64.const 2))
+(assert_return (invoke "return" (i32.const 1)) (i32.const 3) (i64.const 4))
+(assert_return (invoke "return-from-loop" (i32.const 0)) (i64.const 5) (i32.const 6))
+(assert_return (invoke "loop-params" (i32.const 1)) (i32.const 1) (i32.const 1))
+(assert_return (invoke "loop-params" (i32.const 10)) (i32.const 55) (i32.const 89))
+(assert_return (invoke "loop-single-param" (i32.const 4)) (i32.const 10))
+(assert_return (invoke "if" (i32.const 1)) (i32.const 1) (i32.const 2))
+(assert_return (invoke "if" (i32.const 0)) (i32.const 3) (i32.const 4))
+(assert_return (invoke "if-params" (i32.const 1) (i32.const 5) (i32.const 3)) (i32.const 8) (i32.const 0))
+(assert_return (invoke "if-params" (i32.const 0) (i32.const 5) (i32.const 3)) (i32.const 2) (i32.const 1))
+(assert_return (invoke "if-params-single" (i32.const 1) (i32.const 5)) (i32.const 10))
+(assert_return (invoke "if-params-single" (i32.const 0) (i32.const 5)) (i32.const 15))
+(assert_return (invoke "if-params-no-else" (i32.const 1) (i32.const 5) (i32.const 3)) (i32.const 7) (i32.const 8))
+(assert_return (invoke "if-params-no-else" (i32.const 0) (i32.const 5) (i32.const 3)) (i32.const 5) (i32.const 3))
+(assert_return (invoke "if-params-clobber" (i32.const 1)) (i32.const 50) (i32.const 60))
+(assert_return (invoke "if-params-clobber" (i32.const 0)) (i32.const 1) (i32.const 2))
+(assert_return (invoke "if-branch-out" (i32.const 1)) (i32.const 5) (i32.const 6))
+(assert_return (invoke "if-branch-out" (i32.const 0)) (i32.const 11) (i32.const 0))
+(assert_return (invoke "unreachable-end") (i32.const 1) (i32.const 2))
+
+(module
+  (type $sig (func (param i32 i64) (result i64 i32)))
+  (table funcref (elem $swap $inc-both $f32-pair))
+  (func $swap (param i32 i64) (result i64 i32) (local.get 1) (local.get 0))
+  (func $inc-both (param i32 i64) (result i64 i32)
+    (i64.add (local.get 1) (i64.const 1))
+    (i32.add (local.get 0) (i32.const 1)))
+  (func $f32-pair (param f32) (result f32 f32) (local.get 0) (f32.neg (local.get 0)))
+
+  (func (export "call-indirect") (param i32) (result i64 i32)
+    (call_indirect (type $sig) (i32.const 3) (i64.const 4) (local.get 0)))
+  (func (export "br-table-nested") (param i32) (result i32 i64)
+    (block (result i32 i64)
+      (block (result i32 i64)
+        (block (result i32 i64)
+          (i32.const 5) (i64.const 6)
+          (br_table 0 1 2 0 (local.get 0)))
+        (drop) (i64.const 60) (br 1))
+      (i64.const 1) (i64.add)))
+  (func (export "br-if-many") (param i32) (result i32 i32 i32 i32)
+    (block (result i32 i32 i32 i32)
+      (i32.const 1) (i32.const 2) (i32.const 3) (i32.const 4)
+      (br_if 0 (local.get 0))
+      (i32.add) (i32.add) (i32.add) (i32.const 0) (i32.const 0) (i32.const 0)))
+  (func (export "if-nested") (param i32 i32) (result i32 i32)
+    (i32.const 1) (i32.const 2)
+    (if (param i32 i32) (result i32 i32) (local.get 0)
+      (then
+        (if (param i32 i32) (result i32 i32) (local.get 1)
+          (then (i32.add) (i32.const 100))
+          (else (i32.sub) (i32.const 200))))
+      (else (drop) (i32.const 300))))
+  (func (export "return-nested") (param i32) (result i32 f64)
+    (block
+      (loop
+        (block
+          (if (local.get 0) (then (return (i32.const 1) (f64.const 1.5)))))
+        (br 1)))
+    (i32.const 2) (f64.const 2.5))
+  (func (export "return-in-operand") (result i64 i32)
+    (i32.add (i32.const 1) (return (i64.const 7) (i32.const 8))))
+
+  ;; Factorial threading its state through loop parameters.
+  (func $pick0 (param i64) (result i64 i64) (local.get 0) (local.get 0))
+  (func $pick1 (param i64 i64) (result i64 i64 i64) (local.get 0) (local.get 1) (local.get 0))
+  (func (export "fac-ssa") (param i64) (result i64)
+    (i64.const 1) (local.get 0)
+    (loop $l (param i64 i64) (result i64)
+      (call $pick1) (call $pick1) (i64.mul)
+      (call $pick1) (i64.const 1) (i64.sub)
+      (call $pick0) (i64.const 0) (i64.gt_u)
+      (br_if $l)
+      (drop) (return)))
+)
+
+(assert_return (invoke "call-indirect" (i32.const 0)) (i64.const 4) (i32.const 3))
+(assert_return (invoke "call-indirect" (i32.const 1)) (i64.const 5) (i32.const 4))
+(assert_trap (invoke "call-indirect" (i32.const 2)) "indirect call type mismatch")
+(assert_trap (invoke "call-indirect" (i32.const 3)) "undefined element")
+(assert_return (invoke "br-table-nested" (i32.const 0)) (i32.const 5) (i64.const 60))
+(assert_return (invoke "br-table-nested" (i32.const 1)) (i32.const 5) (i64.const 7))
+(assert_return (invoke "br-table-nested" (i32.const 2)) (i32.const 5) (i64.const 6))
+(assert_return (invoke "br-table-nested" (i32.const 3)) (i32.const 5) (i64.const 60))
+(assert_return (invoke "br-if-many" (i32.const 1)) (i32.const 1) (i32.const 2) (i32.const 3) (i32.const 4))
+(assert_return (invoke "br-if-many" (i32.const 0)) (i32.const 10) (i32.const 0) (i32.const 0) (i32.const 0))
+(assert_return (invoke "if-nested" (i32.const 1) (i32.const 1)) (i32.const 3) (i32.const 100))
+(assert_return (invoke "if-nested" (i32.const 1) (i32.const 0)) (i32.const -1) (i32.const 200))
+(assert_return (invoke "if-nested" (i32.const 0) (i32.const 0)) (i32.const 1) (i32.const 300))
+(assert_return (invoke "return-nested" (i32.const 1)) (i32.const 1) (f64.const 1.5))
+(assert_return (invoke "return-nested" (i32.const 0)) (i32.const 2) (f64.const 2.5))
+(assert_return (invoke "return-in-operand") (i64.const 7) (i32.const 8))
+(assert_return (invoke "fac-ssa" (i64.const 20)) (i64.const 2432902008176640000))
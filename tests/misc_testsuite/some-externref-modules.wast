@@ -0,0 +1,30 @@
+;; Only some of these modules use `externref`s, so that a compiler which
+;; doesn't support them can skip those modules and still run the others.
+
+(module $refs
+  (func (export "is_null") (param externref) (result i32)
+    (ref.is_null (local.get 0)))
+  (func (export "add") (param i32 i32) (result i32)
+    (i32.add (local.get 0) (local.get 1))))
+(register "refs" $refs)
+(assert_return (invoke "is_null" (ref.null extern)) (i32.const 1))
+(assert_return (invoke $refs "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+
+(module
+  (import "refs" "add" (func $add (param i32 i32) (result i32)))
+  (func (export "double") (param i32) (result i32)
+    (call $add (local.get 0) (local.get 0))))
+(assert_return (invoke "double" (i32.const 21)) (i32.const 42))
+
+(module $numbers
+  (func (export "add") (param i32 i32) (result i32)
+    (i32.add (local.get 0) (local.get 1))))
+(register "numbers" $numbers)
+(assert_return (invoke "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+
+(module
+  (import "numbers" "add" (func $add (param i32 i32) (result i32)))
+  (func (export "double") (param i32) (result i32)
+    (call $add (local.get 0) (local.get 0))))
+(assert_return (invoke "double" (i32.const 21)) (i32.const 42))
+(assert_return (invoke $numbers "add" (i32.const 2) (i32.const 3)) (i32.const 5))
@@ -0,0 +1,76 @@
+;; Mutually recursive functions which would exhaust the stack without tail
+;; calls.
+(module
+  (func $even (export "even") (param i64) (result i32)
+    local.get 0
+    i64.eqz
+    if (result i32)
+      i32.const 1
+    else
+      local.get 0
+      i64.const 1
+      i64.sub
+      return_call $odd
+    end)
+
+  (func $odd (export "odd") (param i64) (result i32)
+    local.get 0
+    i64.eqz
+    if (result i32)
+      i32.const 0
+    else
+      local.get 0
+      i64.const 1
+      i64.sub
+      return_call $even
+    end)
+)
+
+(assert_return (invoke "even" (i64.const 0)) (i32.const 1))
+(assert_return (invoke "odd" (i64.const 0)) (i32.const 0))
+(assert_return (invoke "even" (i64.const 1000001)) (i32.const 0))
+(assert_return (invoke "odd" (i64.const 1000001)) (i32.const 1))
+
+;; The same, but through a table.
+(module
+  (type $t (func (param i64 i64) (result i64)))
+  (table funcref (elem $count $done))
+
+  (func $count (type $t)
+    local.get 0
+    i64.eqz
+    if (result i64)
+      local.get 1
+    else
+      local.get 0
+      i64.const 1
+      i64.sub
+      local.get 1
+      i64.const 2
+      i64.add
+      local.get 0
+      i64.const 1
+      i64.eq
+      return_call_indirect (type $t)
+    end)
+
+  (func $done (type $t)
+    local.get 1)
+
+  (func (export "count") (param i64) (result i64)
+    local.get 0
+    i64.const 0
+    i32.const 0
+    return_call_indirect (type $t))
+
+  (func (export "null") (result i64)
+    i64.const 0
+    i64.const 0
+    i32.const 2
+    return_call_indirect (type $t))
+)
+
+(assert_return (invoke "count" (i64.const 0)) (i64.const 0))
+(assert_return (invoke "count" (i64.const 1)) (i64.const 2))
+(assert_return (invoke "count" (i64.const 1000000)) (i64.const 2000000))
+(assert_trap (invoke "null") "undefined element")
@@ -0,0 +1,144 @@
+;; Tail calls between functions which receive different numbers of stack
+;; arguments, so that each call grows or shrinks the stack argument area.
+(module
+  (type $many_t (func (param i64 i64 i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+  (table funcref (elem $many_indirect))
+
+  (func $few (export "few") (param $n i64) (result i64)
+    local.get $n
+    i64.eqz
+    if (result i64)
+      i64.const 0
+    else
+      local.get $n
+      i64.const 1
+      i64.sub
+      i64.const 1
+      i64.const 2
+      i64.const 3
+      i64.const 4
+      i64.const 5
+      i64.const 6
+      i64.const 7
+      i64.const 8
+      i64.const 9
+      return_call $many
+    end)
+
+  (func $many (export "many") (type $many_t)
+    local.get 1
+    local.get 2
+    local.get 3
+    local.get 4
+    local.get 5
+    local.get 6
+    local.get 7
+    local.get 8
+    local.get 9
+    call $check_args
+    local.get 0
+    return_call $few)
+
+  (func $few_indirect (export "few-indirect") (param $n i64) (result i64)
+    local.get $n
+    i64.eqz
+    if (result i64)
+      i64.const 0
+    else
+      local.get $n
+      i64.const 1
+      i64.sub
+      i64.const 1
+      i64.const 2
+      i64.const 3
+      i64.const 4
+      i64.const 5
+      i64.const 6
+      i64.const 7
+      i64.const 8
+      i64.const 9
+      i32.const 0
+      return_call_indirect (type $many_t)
+    end)
+
+  (func $many_indirect (type $many_t)
+    local.get 1
+    local.get 2
+    local.get 3
+    local.get 4
+    local.get 5
+    local.get 6
+    local.get 7
+    local.get 8
+    local.get 9
+    call $check_args
+    local.get 0
+    return_call $few_indirect)
+
+  ;; Traps unless the arguments are 1 through 9, in order.
+  (func $check_args (param i64 i64 i64 i64 i64 i64 i64 i64 i64)
+    local.get 0
+    i64.const 10
+    i64.mul
+    local.get 1
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 2
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 3
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 4
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 5
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 6
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 7
+    i64.add
+    i64.const 10
+    i64.mul
+    local.get 8
+    i64.add
+    i64.const 123456789
+    i64.ne
+    if
+      unreachable
+    end)
+
+  ;; A regular call to a function which makes tail calls must find the
+  ;; caller's frame as it left it.
+  (func (export "call-few") (param i64) (result i64)
+    (local i64)
+    i64.const 42
+    local.set 1
+    local.get 0
+    call $few
+    local.get 1
+    i64.add)
+)
+
+(assert_return (invoke "few" (i64.const 0)) (i64.const 0))
+(assert_return (invoke "few" (i64.const 1000000)) (i64.const 0))
+(assert_return (invoke "few-indirect" (i64.const 1000000)) (i64.const 0))
+(assert_return (invoke "call-few" (i64.const 1001)) (i64.const 42))
+(assert_return
+  (invoke "many"
+    (i64.const 1000) (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+    (i64.const 5) (i64.const 6) (i64.const 7) (i64.const 8) (i64.const 9))
+  (i64.const 0))
+(assert_trap
+  (invoke "many"
+    (i64.const 1000) (i64.const 1) (i64.const 2) (i64.const 3) (i64.const 4)
+    (i64.const 5) (i64.const 6) (i64.const 7) (i64.const 9) (i64.const 8))
+  "unreachable")
@@ -3,19 +3,28 @@
 //!
 //! # Default ABI
 //!
-//! Winch uses a default internal ABI, for all internal functions.
-//! This allows us to push the complexity of system ABI compliance to
-//! the trampolines (not yet implemented).  The default ABI treats all
-//! allocatable registers as caller saved, which means that (i) all
-//! register values in the Wasm value stack (which are normally
-//! referred to as "live"), must be saved onto the machine stack (ii)
-//! function prologues and epilogues don't store/restore other
-//! registers more than the non-allocatable ones (e.g. rsp/rbp in
-//! x86_64).
+//! Winch uses a default internal ABI, for all internal functions,
+//! which matches the system calling convention that Wasmtime uses
+//! for WebAssembly functions; this allows Winch-compiled functions to
+//! be called from, and to call into, the host and the trampolines
+//! generated by Wasmtime. Every function receives two implicit
+//! pointer-sized parameters before any of the WebAssembly parameters:
+//! the callee's `VMContext` and the caller's `VMContext`.
+//!
+//! The default ABI treats all allocatable registers as caller saved,
+//! which means that (i) all register values in the Wasm value stack
+//! (which are normally referred to as "live"), must be saved onto the
+//! machine stack (ii) function prologues and epilogues don't
+//! store/restore other registers more than the non-allocatable ones
+//! (e.g. rsp/rbp in x86_64). Registers that are callee saved in the
+//! system calling convention are never allocated.
 //!
 //! The calling convention in the default ABI, uses registers to a
 //! certain fixed count for arguments and return values, and then the
-//! stack is used for all additional arguments.
+//! stack is used for all additional arguments. Only the first return
+//! value is returned in a register; any other return values are
+//! written to a stack area allocated by the caller, whose address is
+//! passed as an additional argument after all the other arguments.
 //!
 //! Generally the stack layout looks like:
 //! +-------------------------------+
@@ -43,6 +52,7 @@
 //! |                               | ----> Space allocated for calls
 //! |                               |
 use crate::isa::reg::Reg;
+use anyhow::{bail, Result};
 use smallvec::SmallVec;
 use std::ops::{Add, BitAnd, Not, Sub};
 use wasmparser::{FuncType, ValType};
@@ -64,8 +74,21 @@ pub(crate) trait ABI {
     fn arg_base_offset(&self) -> u8;
 
     /// Construct the ABI-specific signature from a WebAssembly
-    /// function type.
-    fn sig(&self, wasm_sig: &FuncType) -> ABISig;
+    /// function type, including the implicit `VMContext` parameters.
+    fn sig(&self, wasm_sig: &FuncType) -> Result<ABISig> {
+        let params: SmallVec<[ValType; 6]> = [ValType::I64, ValType::I64]
+            .into_iter()
+            .chain(wasm_sig.params().iter().copied())
+            .collect();
+        self.sig_from(&params, wasm_sig.results())
+    }
+
+    /// Construct the ABI-specific signature from a list of parameter
+    /// and result types, without any implicit parameters.
+    fn sig_from(&self, params: &[ValType], results: &[ValType]) -> Result<ABISig>;
+
+    /// Returns the register used to hold a result of the given type.
+    fn result_reg(ty: &ValType) -> Reg;
 
     /// Returns the number of bits in a word.
     fn word_bits() -> u32;
@@ -77,6 +100,11 @@ pub(crate) trait ABI {
 
     /// Returns the designated scratch register.
     fn scratch_reg() -> Reg;
+
+    /// Returns the register used to hold the address of the callee
+    /// in indirect calls. This register is neither used to pass
+    /// arguments nor as the scratch register.
+    fn callee_reg() -> Reg;
 }
 
 /// ABI-specific representation of a function argument.
@@ -109,14 +137,6 @@ impl ABIArg {
         Self::Stack { ty, offset }
     }
 
-    /// Is this abi arg in a register.
-    pub fn is_reg(&self) -> bool {
-        match *self {
-            ABIArg::Reg { .. } => true,
-            _ => false,
-        }
-    }
-
     /// Get the register associated to this arg.
     pub fn get_reg(&self) -> Option<Reg> {
         match *self {
@@ -133,34 +153,74 @@ impl ABIArg {
     }
 }
 
-/// ABI-specific representation of the function result.
-pub(crate) enum ABIResult {
-    Reg {
-        /// Type of the result.
-        ty: Option<ValType>,
-        /// Register to hold the result.
-        reg: Reg,
-    },
+/// ABI-specific representation of the function results.
+///
+/// The first result, if any, is returned in a register; any other
+/// results are returned in a stack area, allocated by the caller, whose
+/// address is passed to the callee as an implicit parameter after all
+/// the other parameters.
+pub(crate) struct ABIResult {
+    /// The result types.
+    tys: SmallVec<[ValType; 1]>,
+    /// Register holding the first result.
+    reg: Reg,
+    /// The offsets of the results in the return area; the first
+    /// result, which is returned in a register, has no offset.
+    offsets: SmallVec<[u32; 1]>,
+    /// The size of the return area.
+    ret_area_size: u32,
 }
 
 impl ABIResult {
-    /// Create a register ABI result.
-    pub fn reg(ty: Option<ValType>, reg: Reg) -> Self {
-        Self::Reg { ty, reg }
+    /// Create an ABI result from the given result types, returning
+    /// the first result in the given register. The results in the
+    /// return area are laid out with their natural alignment.
+    pub fn new(tys: &[ValType], reg: Reg) -> Self {
+        let mut ret_area_size = 0;
+        let offsets = tys
+            .iter()
+            .skip(1)
+            .map(|ty| {
+                let size = ty_size(ty);
+                let offset = align_to(ret_area_size, size);
+                ret_area_size = offset + size;
+                offset
+            })
+            .collect();
+
+        Self {
+            tys: tys.iter().copied().collect(),
+            reg,
+            offsets,
+            ret_area_size,
+        }
     }
 
     /// Get the result reg.
     pub fn result_reg(&self) -> Reg {
-        match self {
-            Self::Reg { reg, .. } => *reg,
-        }
+        self.reg
+    }
+
+    /// Get the result types.
+    pub fn tys(&self) -> &[ValType] {
+        &self.tys
     }
 
     /// Checks if the result is void.
     pub fn is_void(&self) -> bool {
-        match self {
-            Self::Reg { ty, .. } => ty.is_none(),
-        }
+        self.tys.is_empty()
+    }
+
+    /// Get the offset in the return area of the result at the given
+    /// index, which must not be the first one.
+    pub fn ret_area_offset(&self, index: usize) -> u32 {
+        self.offsets[index - 1]
+    }
+
+    /// Get the size of the return area; zero if all the results are
+    /// returned in registers.
+    pub fn ret_area_size(&self) -> u32 {
+        self.ret_area_size
     }
 }
 
@@ -172,30 +232,62 @@ pub(crate) struct ABISig {
     pub params: ABIParams,
     /// Function result.
     pub result: ABIResult,
+    /// The implicit parameter holding the address of the return area,
+    /// present only if the function returns more than one value.
+    pub ret_area_ptr: Option<ABIArg>,
     /// Stack space needed for stack arguments.
     pub stack_bytes: u32,
 }
 
 impl ABISig {
     /// Create a new ABI signature.
-    pub fn new(params: ABIParams, result: ABIResult, stack_bytes: u32) -> Self {
+    pub fn new(
+        params: ABIParams,
+        result: ABIResult,
+        ret_area_ptr: Option<ABIArg>,
+        stack_bytes: u32,
+    ) -> Self {
         Self {
             params,
             result,
+            ret_area_ptr,
             stack_bytes,
         }
     }
 }
 
+/// The number of implicit parameters, the callee and caller
+/// `VMContext`, that precede the WebAssembly parameters in the
+/// signature of every function.
+pub(crate) const VMCTX_PARAMS: usize = 2;
+
 /// Returns the size in bytes of a given WebAssembly type.
 pub(crate) fn ty_size(ty: &ValType) -> u32 {
     match *ty {
         ValType::I32 | ValType::F32 => 4,
-        ValType::I64 | ValType::F64 => 8,
+        ValType::I64 | ValType::F64 | ValType::FUNCREF => 8,
         _ => panic!(),
     }
 }
 
+/// Returns an error if values of the given type can't be handled by
+/// the code generation.
+pub(crate) fn ensure_supported_ty(ty: &ValType) -> Result<()> {
+    match *ty {
+        ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 | ValType::FUNCREF => Ok(()),
+        ty => bail!("Unsupported type {:?}", ty),
+    }
+}
+
+/// Returns an error if a signature with the given parameter and
+/// result types can't be handled by the code generation.
+pub(crate) fn ensure_supported_sig(params: &[ValType], results: &[ValType]) -> Result<()> {
+    params
+        .iter()
+        .chain(results)
+        .try_for_each(ensure_supported_ty)
+}
+
 /// Align a value up to the given power-of-two-alignment.
 // See https://sites.google.com/site/theoryofoperatingsystems/labs/malloc/align8
 pub(crate) fn align_to<N>(value: N, alignment: N) -> N
@@ -2,8 +2,8 @@
 //! calling convention, see [ABI].
 use super::CodeGenContext;
 use crate::{
-    abi::{align_to, ABIArg, ABIResult, ABISig, ABI},
-    masm::{MacroAssembler, OperandSize},
+    abi::{ABIArg, ABISig, ABI},
+    masm::{CalleeKind, MacroAssembler, OperandSize},
     reg::Reg,
     stack::Val,
 };
@@ -67,9 +67,10 @@ pub(crate) struct FnCall<'a> {
     arg_stack_space: u32,
     /// The ABI-specific signature of the callee.
     abi_sig: &'a ABISig,
-    /// The stack pointer offset prior to preparing and emitting the
-    /// call. This is tracked to assert the position of the stack
-    /// pointer after the call has finished.
+    /// The stack pointer offset after saving the live registers that
+    /// are not used as arguments to the call. This is tracked to
+    /// assert the position of the stack pointer after the call has
+    /// finished.
     sp_offset_at_callsite: u32,
 }
 
@@ -93,14 +94,12 @@ impl<'a> FnCall<'a> {
         masm: &mut M,
     ) -> Self {
         let stack = &context.stack;
-        let arg_stack_space = callee_sig.stack_bytes;
         let callee_params = &callee_sig.params;
-        let sp_offset_at_callsite = masm.sp_offset();
 
-        let (spilled_regs, memory_values) = match callee_params.len() {
+        let (spilled_regs, memory_values, sp_offset_at_callsite) = match callee_params.len() {
             0 => {
                 let _ = context.spill_regs_and_count_memory_in(masm, ..);
-                (0, 0)
+                (0, 0, masm.sp_offset())
             }
             _ => {
                 // Here we perform a "spill" of the register entries
@@ -131,44 +130,80 @@ impl<'a> FnCall<'a> {
                 assert!(stack.len() >= callee_params.len());
                 let partition = stack.len() - callee_params.len();
                 let _ = context.spill_regs_and_count_memory_in(masm, 0..partition);
-                context.spill_regs_and_count_memory_in(masm, partition..)
+                // The registers spilled above remain in the value
+                // stack as memory entries after the call.
+                let sp_offset = masm.sp_offset();
+                let (spilled_regs, memory_values) =
+                    context.spill_regs_and_count_memory_in(masm, partition..);
+                (spilled_regs, memory_values, sp_offset)
             }
         };
 
+        // The return area, if any, is placed right above the stack
+        // arguments. After the call, all the results are moved to
+        // stack slots at the stack pointer offset at the callsite, so
+        // enough space is reserved for the slots not to overlap with
+        // the return area.
+        let word_bytes = <A as ABI>::word_bytes();
+        let consumed_space = (spilled_regs + memory_values) * word_bytes;
+        let ret_area_space = match callee_sig.result.tys().len() {
+            0 | 1 => 0,
+            n => {
+                let slots_space = n as u32 * word_bytes;
+                callee_sig.result.ret_area_size() + slots_space.saturating_sub(consumed_space)
+            }
+        };
+        let arg_stack_space = callee_sig.stack_bytes + ret_area_space;
+
+        // The frame pointer is aligned to the ABI's call stack
+        // alignment, so the stack pointer at the callsite is aligned
+        // as long as the current stack pointer offset plus the space
+        // reserved for the arguments is.
         let delta = calculate_frame_adjustment(
             masm.sp_offset(),
-            abi.arg_base_offset() as u32,
+            arg_stack_space,
             abi.call_stack_align() as u32,
         );
 
-        let arg_stack_space = align_to(arg_stack_space + delta, abi.call_stack_align() as u32);
+        let arg_stack_space = arg_stack_space + delta;
         Self {
             abi_sig: &callee_sig,
             arg_stack_space,
-            total_stack_space: (spilled_regs * <A as ABI>::word_bytes())
-                + (memory_values * <A as ABI>::word_bytes())
-                + arg_stack_space,
+            total_stack_space: consumed_space + arg_stack_space,
             sp_offset_at_callsite,
         }
     }
 
     /// Emit the function call.
+    ///
+    /// If the callee is an indirect one, the register holding its
+    /// address must not be used to pass any of the arguments.
     pub fn emit<M: MacroAssembler, A: ABI>(
         &self,
         masm: &mut M,
         context: &mut CodeGenContext,
-        callee: u32,
+        callee: CalleeKind,
     ) {
+        let scratch = <A as ABI>::scratch_reg();
         masm.reserve_stack(self.arg_stack_space);
-        self.assign_args(context, masm, <A as ABI>::scratch_reg());
+        self.assign_args(context, masm, scratch);
         masm.call(callee);
-        masm.free_stack(self.total_stack_space);
+
+        let base = masm.sp_offset() - self.total_stack_space;
+        let slots_space = match self.abi_sig.result.tys().len() {
+            0 | 1 => 0,
+            n => {
+                self.move_results_to_slots::<M, A>(masm, base, scratch);
+                n as u32 * <A as ABI>::word_bytes()
+            }
+        };
+        masm.free_stack(self.total_stack_space - slots_space);
         context.drop_last(self.abi_sig.params.len());
         // The stack pointer at the end of the function call
         // cannot be less than what it was when starting the
         // function call.
-        assert!(self.sp_offset_at_callsite >= masm.sp_offset());
-        self.handle_result(context, masm);
+        assert!(self.sp_offset_at_callsite + slots_space >= masm.sp_offset());
+        self.handle_result::<M, A>(context, masm, base);
     }
 
     fn assign_args<M: MacroAssembler>(
@@ -185,31 +220,78 @@ impl<'a> FnCall<'a> {
                 .next()
                 .unwrap_or_else(|| panic!("expected stack value for function argument"));
             match &arg {
-                &ABIArg::Reg { ty, reg } => {
-                    context.move_val_to_reg(&val, *reg, masm, (*ty).into());
+                &ABIArg::Reg { reg, .. } => {
+                    context.move_val_to_reg(&val, *reg, masm);
                 }
                 &ABIArg::Stack { ty, offset } => {
+                    // Stack arguments are moved through the integer
+                    // scratch register, regardless of their type.
                     let addr = masm.address_at_sp(*offset);
                     let size: OperandSize = (*ty).into();
-                    context.move_val_to_reg(val, scratch, masm, size);
+                    context.move_val_to_reg(val, scratch, masm);
                     masm.store(scratch.into(), addr, size);
                 }
             }
         }
+
+        // The return area is located right above the stack arguments.
+        if let Some(arg) = &self.abi_sig.ret_area_ptr {
+            let ret_area = masm.address_at_sp(self.abi_sig.stack_bytes);
+            match arg {
+                ABIArg::Reg { reg, .. } => masm.load_addr(ret_area, *reg, OperandSize::S64),
+                ABIArg::Stack { offset, .. } => {
+                    masm.load_addr(ret_area, scratch, OperandSize::S64);
+                    let addr = masm.address_at_sp(*offset);
+                    masm.store(scratch.into(), addr, OperandSize::S64);
+                }
+            }
+        }
     }
 
-    fn handle_result<M: MacroAssembler>(&self, context: &mut CodeGenContext, masm: &mut M) {
+    /// Moves the results of a function returning more than one value to
+    /// the consecutive stack slots right above the given stack pointer
+    /// offset.
+    fn move_results_to_slots<M: MacroAssembler, A: ABI>(
+        &self,
+        masm: &mut M,
+        base: u32,
+        scratch: Reg,
+    ) {
         let result = &self.abi_sig.result;
-        if result.is_void() {
-            return;
+        let slot = |index: usize| base + (index as u32 + 1) * <A as ABI>::word_bytes();
+
+        let addr = masm.address_from_sp(slot(0));
+        masm.store(result.result_reg().into(), addr, OperandSize::S64);
+        for (index, ty) in result.tys().iter().enumerate().skip(1) {
+            let offset = self.abi_sig.stack_bytes + result.ret_area_offset(index);
+            let addr = masm.address_at_sp(offset);
+            masm.load(addr, scratch, (*ty).into());
+            let addr = masm.address_from_sp(slot(index));
+            masm.store(scratch.into(), addr, OperandSize::S64);
         }
+    }
 
-        match result {
-            &ABIResult::Reg { ty: _, reg } => {
-                assert!(context.regalloc.gpr_available(reg));
-                let result_reg = Val::reg(context.gpr(reg, masm));
+    fn handle_result<M: MacroAssembler, A: ABI>(
+        &self,
+        context: &mut CodeGenContext,
+        masm: &mut M,
+        base: u32,
+    ) {
+        let result = &self.abi_sig.result;
+        match result.tys() {
+            [] => {}
+            [ty] => {
+                let reg = result.result_reg();
+                assert!(context.regalloc.reg_available(reg));
+                let result_reg = Val::reg(context.reg(reg, masm), *ty);
                 context.stack.push(result_reg);
             }
+            tys => {
+                for (index, ty) in tys.iter().enumerate() {
+                    let offset = base + (index as u32 + 1) * <A as ABI>::word_bytes();
+                    context.stack.push(Val::mem(*ty, offset));
+                }
+            }
         }
     }
 }
@@ -3,9 +3,10 @@ use crate::{
     masm::{MacroAssembler, OperandSize, RegImm},
     reg::Reg,
     regalloc::RegAlloc,
-    stack::{Stack, Val},
+    stack::{Stack, TypedReg, Val},
 };
 use std::ops::RangeBounds;
+use wasmparser::ValType;
 
 /// The code generation context.
 /// The code generation context is made up of three
@@ -41,11 +42,11 @@ impl<'a> CodeGenContext<'a> {
         }
     }
 
-    /// Request a specific general purpose register to the register allocator,
+    /// Request a specific register to the register allocator,
     /// spilling if not available.
-    pub fn gpr<M: MacroAssembler>(&mut self, named: Reg, masm: &mut M) -> Reg {
-        self.regalloc.gpr(named, &mut |regalloc| {
-            Self::spill(&mut self.stack, regalloc, &self.frame, masm)
+    pub fn reg<M: MacroAssembler>(&mut self, named: Reg, masm: &mut M) -> Reg {
+        self.regalloc.reg(named, &mut |regalloc| {
+            Self::spill_impl(&mut self.stack, regalloc, &self.frame, masm)
         })
     }
 
@@ -53,77 +54,97 @@ impl<'a> CodeGenContext<'a> {
     /// spilling if no registers are available.
     pub fn any_gpr<M: MacroAssembler>(&mut self, masm: &mut M) -> Reg {
         self.regalloc
-            .any_gpr(&mut |regalloc| Self::spill(&mut self.stack, regalloc, &self.frame, masm))
+            .any_gpr(&mut |regalloc| Self::spill_impl(&mut self.stack, regalloc, &self.frame, masm))
     }
 
-    /// Free the given general purpose register.
-    pub fn free_gpr(&mut self, reg: Reg) {
-        self.regalloc.free_gpr(reg);
+    /// Request the next avaiable floating point register to the register allocator,
+    /// spilling if no registers are available.
+    pub fn any_fpr<M: MacroAssembler>(&mut self, masm: &mut M) -> Reg {
+        self.regalloc
+            .any_fpr(&mut |regalloc| Self::spill_impl(&mut self.stack, regalloc, &self.frame, masm))
+    }
+
+    /// Request the next available register suitable to hold a value
+    /// of the given type, spilling if no registers are available.
+    pub fn reg_for_type<M: MacroAssembler>(&mut self, ty: ValType, masm: &mut M) -> Reg {
+        self.regalloc.reg_for_type(ty, &mut |regalloc| {
+            Self::spill_impl(&mut self.stack, regalloc, &self.frame, masm)
+        })
+    }
+
+    /// Free the given register.
+    pub fn free_reg(&mut self, reg: impl Into<Reg>) {
+        self.regalloc.free(reg.into());
     }
 
     /// Loads the stack top value into the next available register, if
     /// it isn't already one; spilling if there are no registers
     /// available.  Optionally the caller may specify a specific
     /// destination register.
-    pub fn pop_to_reg<M: MacroAssembler>(
-        &mut self,
-        masm: &mut M,
-        named: Option<Reg>,
-        size: OperandSize,
-    ) -> Reg {
-        let (in_stack, dst) = if let Some(dst) = named {
-            self.stack
-                .pop_named_reg(dst)
-                .map(|reg| (true, reg))
-                .unwrap_or_else(|| (false, self.gpr(dst, masm)))
-        } else {
-            self.stack
-                .pop_reg()
-                .map(|reg| (true, reg))
-                .unwrap_or_else(|| (false, self.any_gpr(masm)))
+    pub fn pop_to_reg<M: MacroAssembler>(&mut self, masm: &mut M, named: Option<Reg>) -> TypedReg {
+        let popped = match named {
+            Some(dst) => self.stack.pop_named_reg(dst),
+            None => self.stack.pop_reg(),
         };
 
-        if in_stack {
-            return dst;
+        if let Some(reg) = popped {
+            return reg;
         }
 
+        let ty = self.stack.peek().expect("a value at stack top").ty();
+        let dst = match named {
+            Some(dst) => self.reg(dst, masm),
+            None => self.reg_for_type(ty, masm),
+        };
+
         let val = self.stack.pop().expect("a value at stack top");
-        if val.is_mem() {
-            masm.pop(dst);
-        } else {
-            self.move_val_to_reg(&val, dst, masm, size);
+        match val {
+            Val::Memory { .. } => masm.pop(dst),
+            Val::Reg(src) => {
+                self.move_val_to_reg(&val, dst, masm);
+                self.free_reg(src);
+            }
+            _ => self.move_val_to_reg(&val, dst, masm),
         }
 
-        dst
+        TypedReg::new(ty, dst)
     }
 
     /// Move a stack value to the given register.
-    pub fn move_val_to_reg<M: MacroAssembler>(
-        &self,
-        src: &Val,
-        dst: Reg,
-        masm: &mut M,
-        size: OperandSize,
-    ) {
+    ///
+    /// The value is moved preserving its bit pattern, even if the
+    /// register class of the destination differs from the one of the
+    /// value's type.
+    pub fn move_val_to_reg<M: MacroAssembler>(&self, src: &Val, dst: Reg, masm: &mut M) {
+        let size: OperandSize = src.ty().into();
         match src {
-            Val::Reg(src) => masm.mov(RegImm::reg(*src), RegImm::reg(dst), size),
+            Val::Reg(src) => masm.mov(RegImm::reg(src.reg), RegImm::reg(dst), size),
             Val::I32(imm) => masm.mov(RegImm::imm((*imm).into()), RegImm::reg(dst), size),
             Val::I64(imm) => masm.mov(RegImm::imm(*imm), RegImm::reg(dst), size),
-            Val::Local(index) => {
+            Val::F32(bits) => masm.mov(RegImm::imm((*bits).into()), RegImm::reg(dst), size),
+            Val::F64(bits) => masm.mov(RegImm::imm(*bits as i64), RegImm::reg(dst), size),
+            Val::Local { index, .. } => {
                 let slot = self
                     .frame
                     .get_local(*index)
                     .unwrap_or_else(|| panic!("valid local at index = {}", index));
                 let addr = masm.local_address(&slot);
-                masm.load(addr, dst, slot.ty.into());
+                masm.load(addr, dst, size);
             }
-            Val::Memory(offset) => {
+            Val::Memory { offset, .. } => {
                 let addr = masm.address_from_sp(*offset);
                 masm.load(addr, dst, size);
             }
         };
     }
 
+    /// Loads the `VMContext` of the current function into the given
+    /// register.
+    pub fn load_vmctx<M: MacroAssembler>(&self, dst: Reg, masm: &mut M) {
+        let addr = masm.local_address(&self.frame.vmctx_slot);
+        masm.load(addr, dst, OperandSize::S64);
+    }
+
     /// Prepares arguments for emitting an i32 binary operation.
     pub fn i32_binop<F, M>(&mut self, masm: &mut M, emit: &mut F)
     where
@@ -137,20 +158,20 @@ impl<'a> CodeGenContext<'a> {
                 .stack
                 .pop_i32_const()
                 .expect("i32 const value at stack top");
-            let reg = self.pop_to_reg(masm, None, OperandSize::S32);
+            let reg = self.pop_to_reg(masm, None);
             emit(
                 masm,
-                RegImm::reg(reg),
+                RegImm::reg(reg.reg),
                 RegImm::imm(val as i64),
                 OperandSize::S32,
             );
-            self.stack.push(Val::reg(reg));
+            self.stack.push(Val::reg(reg.reg, ValType::I32));
         } else {
-            let src = self.pop_to_reg(masm, None, OperandSize::S32);
-            let dst = self.pop_to_reg(masm, None, OperandSize::S32);
-            emit(masm, dst.into(), src.into(), OperandSize::S32);
-            self.regalloc.free_gpr(src);
-            self.stack.push(Val::reg(dst));
+            let src = self.pop_to_reg(masm, None);
+            let dst = self.pop_to_reg(masm, None);
+            emit(masm, dst.reg.into(), src.reg.into(), OperandSize::S32);
+            self.free_reg(src);
+            self.stack.push(Val::reg(dst.reg, ValType::I32));
         }
     }
 
@@ -166,18 +187,82 @@ impl<'a> CodeGenContext<'a> {
                 .stack
                 .pop_i64_const()
                 .expect("i64 const value at stack top");
-            let reg = self.pop_to_reg(masm, None, OperandSize::S64);
-            emit(masm, RegImm::reg(reg), RegImm::imm(val), OperandSize::S64);
-            self.stack.push(Val::reg(reg));
+            let reg = self.pop_to_reg(masm, None);
+            emit(
+                masm,
+                RegImm::reg(reg.reg),
+                RegImm::imm(val),
+                OperandSize::S64,
+            );
+            self.stack.push(Val::reg(reg.reg, ValType::I64));
         } else {
-            let src = self.pop_to_reg(masm, None, OperandSize::S64);
-            let dst = self.pop_to_reg(masm, None, OperandSize::S64);
-            emit(masm, dst.into(), src.into(), OperandSize::S64);
-            self.regalloc.free_gpr(src);
-            self.stack.push(Val::reg(dst));
+            let src = self.pop_to_reg(masm, None);
+            let dst = self.pop_to_reg(masm, None);
+            emit(masm, dst.reg.into(), src.reg.into(), OperandSize::S64);
+            self.free_reg(src);
+            self.stack.push(Val::reg(dst.reg, ValType::I64));
         }
     }
 
+    /// Prepares arguments for emitting a floating point binary
+    /// operation. The result is pushed with the type of the
+    /// operands.
+    pub fn float_binop<F, M>(&mut self, masm: &mut M, size: OperandSize, emit: &mut F)
+    where
+        F: FnMut(&mut M, Reg, Reg, OperandSize),
+        M: MacroAssembler,
+    {
+        let src = self.pop_to_reg(masm, None);
+        let dst = self.pop_to_reg(masm, None);
+        emit(masm, dst.reg, src.reg, size);
+        self.free_reg(src);
+        self.stack.push(dst.into());
+    }
+
+    /// Prepares arguments for emitting a floating point comparison,
+    /// which produces an i32.
+    pub fn float_cmp_op<F, M>(&mut self, masm: &mut M, size: OperandSize, emit: &mut F)
+    where
+        F: FnMut(&mut M, Reg, Reg, Reg, OperandSize),
+        M: MacroAssembler,
+    {
+        let rhs = self.pop_to_reg(masm, None);
+        let lhs = self.pop_to_reg(masm, None);
+        let dst = self.any_gpr(masm);
+        emit(masm, dst, lhs.reg, rhs.reg, size);
+        self.free_reg(lhs);
+        self.free_reg(rhs);
+        self.stack.push(Val::reg(dst, ValType::I32));
+    }
+
+    /// Prepares arguments for emitting a unary operation, which
+    /// operates in place on the value at the top of the stack; the
+    /// result is pushed with the given type.
+    pub fn unop<F, M>(&mut self, masm: &mut M, ty: ValType, emit: &mut F)
+    where
+        F: FnMut(&mut M, Reg),
+        M: MacroAssembler,
+    {
+        let reg = self.pop_to_reg(masm, None);
+        emit(masm, reg.reg);
+        self.stack.push(Val::reg(reg.reg, ty));
+    }
+
+    /// Prepares arguments for emitting a conversion between values of
+    /// different register classes; the value at the top of the stack
+    /// is converted to a value of type `ty` in a new register.
+    pub fn convert_op<F, M>(&mut self, masm: &mut M, ty: ValType, emit: &mut F)
+    where
+        F: FnMut(&mut M, Reg, Reg),
+        M: MacroAssembler,
+    {
+        let src = self.pop_to_reg(masm, None);
+        let dst = self.reg_for_type(ty, masm);
+        emit(masm, src.reg, dst);
+        self.free_reg(src);
+        self.stack.push(Val::reg(dst, ty));
+    }
+
     /// Saves any live registers in the value stack in a particular
     /// range defined by the caller.  This is a specialization of the
     /// spill function; made available for cases in which spilling
@@ -197,9 +282,9 @@ impl<'a> CodeGenContext<'a> {
         for i in self.stack.inner_mut().range_mut(range) {
             if i.is_reg() {
                 let reg = i.get_reg();
-                let offset = masm.push(reg);
-                self.regalloc.free_gpr(reg);
-                *i = Val::Memory(offset);
+                let offset = masm.push(reg.reg);
+                self.regalloc.free(reg.reg);
+                *i = Val::mem(reg.ty, offset);
                 spilled += 1;
             } else if i.is_mem() {
                 memory_values += 1;
@@ -218,12 +303,45 @@ impl<'a> CodeGenContext<'a> {
 
         self.stack.inner_mut().range(truncate..).for_each(|v| {
             if v.is_reg() {
-                self.regalloc.free_gpr(v.get_reg());
+                self.regalloc.free(v.get_reg().reg);
             }
         });
         self.stack.inner_mut().truncate(truncate);
     }
 
+    /// Spill locals and registers in the value stack to memory.
+    pub fn spill<M: MacroAssembler>(&mut self, masm: &mut M) {
+        Self::spill_impl(&mut self.stack, &mut self.regalloc, &self.frame, masm);
+    }
+
+    /// Replaces every lazy reference to the local at `index` in the
+    /// value stack with the local's current value, so that it's not
+    /// affected by a subsequent write to the local.
+    pub fn materialize_local<M: MacroAssembler>(&mut self, index: u32, masm: &mut M) {
+        let is_local = |v: &Val| matches!(v, Val::Local { index: i, .. } if *i == index);
+        let slot = self.frame.get_local(index).expect("valid local at slot");
+        while let Some(pos) = self.stack.inner_mut().iter().position(|v| is_local(v)) {
+            // Requesting a register might spill the entire value stack,
+            // materializing the local as a side effect.
+            let reg = self.reg_for_type(slot.ty, masm);
+            let val = &mut self.stack.inner_mut()[pos];
+            if is_local(val) {
+                let addr = masm.local_address(&slot);
+                masm.load(addr, reg, slot.ty.into());
+                *val = Val::reg(reg, slot.ty);
+            } else {
+                self.regalloc.free(reg);
+            }
+        }
+    }
+
+    /// Truncates the value stack to the given length, freeing any
+    /// registers located in the truncated region.
+    pub fn truncate_stack(&mut self, len: usize) {
+        assert!(len <= self.stack.len());
+        self.drop_last(self.stack.len() - len);
+    }
+
     /// Spill locals and registers to memory.
     // TODO optimize the spill range;
     //
@@ -232,7 +350,7 @@ impl<'a> CodeGenContext<'a> {
     // we could effectively ignore that range;
     // only focusing on the range that contains
     // spillable values.
-    fn spill<M: MacroAssembler>(
+    fn spill_impl<M: MacroAssembler>(
         stack: &mut Stack,
         regalloc: &mut RegAlloc,
         frame: &Frame,
@@ -240,20 +358,23 @@ impl<'a> CodeGenContext<'a> {
     ) {
         stack.inner_mut().iter_mut().for_each(|v| match v {
             Val::Reg(r) => {
-                let offset = masm.push(*r);
-                regalloc.free_gpr(*r);
-                *v = Val::Memory(offset);
+                let offset = masm.push(r.reg);
+                regalloc.free(r.reg);
+                *v = Val::mem(r.ty, offset);
             }
-            Val::Local(index) => {
+            Val::Local { index, ty } => {
+                // Locals are moved through the integer scratch
+                // register, regardless of their type, since only
+                // their bit pattern needs to be preserved.
                 let slot = frame.get_local(*index).expect("valid local at slot");
                 let addr = masm.local_address(&slot);
                 masm.load(addr, regalloc.scratch, slot.ty.into());
                 let offset = masm.push(regalloc.scratch);
-                *v = Val::Memory(offset);
-            }
-            v => {
-                println!("trying to spill something unknown {:?}", v);
+                *v = Val::mem(*ty, offset);
             }
+            // Constants are rematerialized when used and memory
+            // values are already in place.
+            Val::I32(_) | Val::I64(_) | Val::F32(_) | Val::F64(_) | Val::Memory { .. } => {}
         });
     }
 }
@@ -0,0 +1,152 @@
+//! Data structures for control flow emission.
+//!
+//! Every WebAssembly block, loop and if is represented by a control
+//! stack frame, which tracks the labels that branches targeting the
+//! frame jump to and the state of the value stack and machine stack at
+//! the frame's entry. Since the value stack is entirely spilled to
+//! memory on entry to a control frame, any branch to the frame only
+//! needs to reset the machine stack pointer to the offset observed at
+//! the entry and place the values carried by the branch, if any, in
+//! their designated location: a single value is placed in the result
+//! register, while multiple values are placed in consecutive stack
+//! slots right above the machine stack pointer offset at the entry.
+use crate::{
+    abi::ensure_supported_ty,
+    codegen::{CodeGenContext, FuncEnv},
+    masm::MacroAssembler,
+};
+use anyhow::Result;
+use cranelift_codegen::MachLabel;
+use smallvec::SmallVec;
+use wasmparser::{BlockType, ValType};
+
+/// The types of the parameters or results of a control frame.
+pub(crate) type BlockTypes = SmallVec<[ValType; 1]>;
+
+/// The state of the value stack and machine stack at the entry of a
+/// control frame.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockState {
+    /// The parameter types of the block.
+    pub params: BlockTypes,
+    /// The result types of the block.
+    pub results: BlockTypes,
+    /// The length of the value stack at the frame's entry, excluding
+    /// the block parameters.
+    pub stack_len: usize,
+    /// The stack pointer offset at the frame's entry, excluding any
+    /// space taken by the block parameters.
+    pub sp_offset: u32,
+}
+
+impl BlockState {
+    /// Create a new block state from a block type and the current
+    /// state of the value stack, which must have been spilled.
+    ///
+    /// Returns an error if the block type is not supported.
+    pub fn new<M: MacroAssembler>(
+        ty: BlockType,
+        env: &dyn FuncEnv,
+        context: &CodeGenContext,
+        masm: &mut M,
+        word_bytes: u32,
+    ) -> Result<Self> {
+        let (params, results): (BlockTypes, BlockTypes) = match ty {
+            BlockType::Empty => Default::default(),
+            BlockType::Type(ty) => (Default::default(), [ty].into_iter().collect()),
+            BlockType::FuncType(index) => {
+                let ty = env.func_type_at(index);
+                (
+                    ty.params().iter().copied().collect(),
+                    ty.results().iter().copied().collect(),
+                )
+            }
+        };
+        params
+            .iter()
+            .chain(&results)
+            .try_for_each(ensure_supported_ty)?;
+
+        // The parameters spilled to memory are at the top of the
+        // machine stack.
+        let stack_len = context.stack.len() - params.len();
+        let memory_params = context
+            .stack
+            .peekn(params.len())
+            .filter(|v| v.is_mem())
+            .count() as u32;
+
+        Ok(Self {
+            params,
+            results,
+            stack_len,
+            sp_offset: masm.sp_offset() - memory_params * word_bytes,
+        })
+    }
+}
+
+/// A control stack frame.
+#[derive(Debug)]
+pub(crate) enum ControlStackFrame {
+    /// The consequent of an if.
+    If {
+        /// The label of the alternative (or of the end, if there's no
+        /// alternative).
+        cont: MachLabel,
+        /// The label of the end of the if.
+        exit: MachLabel,
+        /// The state at the entry of the if.
+        state: BlockState,
+    },
+    /// The alternative of an if.
+    Else {
+        /// The label of the end of the if.
+        exit: MachLabel,
+        /// The state at the entry of the if.
+        state: BlockState,
+    },
+    /// A block; also used to represent the function body.
+    Block {
+        /// The label of the end of the block.
+        exit: MachLabel,
+        /// The state at the entry of the block.
+        state: BlockState,
+    },
+    /// A loop.
+    Loop {
+        /// The label of the loop header.
+        head: MachLabel,
+        /// The state at the entry of the loop.
+        state: BlockState,
+    },
+}
+
+impl ControlStackFrame {
+    /// Returns the state at the entry of the frame.
+    pub fn state(&self) -> &BlockState {
+        match self {
+            Self::If { state, .. }
+            | Self::Else { state, .. }
+            | Self::Block { state, .. }
+            | Self::Loop { state, .. } => state,
+        }
+    }
+
+    /// Returns the label that branches targeting this frame jump to.
+    pub fn branch_target(&self) -> MachLabel {
+        match self {
+            Self::If { exit, .. } | Self::Else { exit, .. } | Self::Block { exit, .. } => *exit,
+            Self::Loop { head, .. } => *head,
+        }
+    }
+
+    /// Returns the types of the values carried by branches targeting
+    /// this frame. Branches to a loop target its header, so they
+    /// carry the loop parameters.
+    pub fn branch_types(&self) -> &[ValType] {
+        match self {
+            Self::Loop { state, .. } => &state.params,
+            _ => &self.state().results,
+        }
+    }
+}
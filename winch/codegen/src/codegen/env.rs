@@ -1,10 +1,46 @@
-use wasmparser::FuncType;
+use wasmparser::{FuncType, ValType};
 
 /// Function environment used the by the code generation to
 /// resolve module and runtime-specific information.
 pub trait FuncEnv {
     /// Get the callee information from a given function index.
     fn callee_from_index(&self, index: u32) -> Callee;
+
+    /// Get the offsets of the fields in the `VMContext` and in the
+    /// runtime structures reachable from it that don't depend on an
+    /// entity index.
+    fn vmctx_offsets(&self) -> VMContextOffsets;
+
+    /// Get the offsets, relative to the `VMContext`, of the function
+    /// pointer and the `VMContext` of the imported function at the
+    /// given index.
+    fn imported_function(&self, index: u32) -> ImportedFunctionData;
+
+    /// Get the information needed to access the global at the given
+    /// index.
+    fn global(&self, index: u32) -> GlobalData;
+
+    /// Get the information needed to access the linear memory at the
+    /// given index.
+    fn heap(&self, index: u32) -> HeapData;
+
+    /// Get the information needed to access the table at the given
+    /// index.
+    fn table(&self, index: u32) -> TableData;
+
+    /// Get the function type at the given index in the type index
+    /// space.
+    fn func_type_at(&self, type_index: u32) -> FuncType;
+
+    /// Get the index of the shared signature of the function type at
+    /// the given index in the type index space; shared signature
+    /// indices are used to check the type of indirect calls at
+    /// runtime.
+    fn signature_index(&self, type_index: u32) -> u32;
+
+    /// Get the index of the given builtin function in the array of
+    /// builtin functions referenced by the `VMContext`.
+    fn builtin_function_index(&self, builtin: BuiltinFunction) -> u32;
 }
 
 /// Metadata about a function callee.  Use by the code generation
@@ -17,3 +53,138 @@ pub struct Callee {
     /// The callee index in the WebAssembly function index space.
     pub index: u32,
 }
+
+/// Offsets of the fields in the `VMContext` and related runtime
+/// structures that don't depend on an entity index.
+#[derive(Debug, Copy, Clone)]
+pub struct VMContextOffsets {
+    /// The offset of the pointer to the `VMRuntimeLimits` in the
+    /// `VMContext`.
+    pub runtime_limits: u32,
+    /// The offset of the stack limit in the `VMRuntimeLimits`.
+    pub stack_limit: u32,
+    /// The offset of the pointer to the array of builtin functions
+    /// in the `VMContext`.
+    pub builtin_functions: u32,
+    /// The offset of the pointer to the array of shared signature
+    /// indices in the `VMContext`.
+    pub signature_ids: u32,
+    /// The size of a shared signature index.
+    pub signature_id_size: u32,
+    /// The offset of the function pointer in a `VMCallerCheckedFuncRef`.
+    pub func_ref_func_ptr: u32,
+    /// The offset of the shared signature index in a
+    /// `VMCallerCheckedFuncRef`.
+    pub func_ref_type_index: u32,
+    /// The offset of the `VMContext` in a `VMCallerCheckedFuncRef`.
+    pub func_ref_vmctx: u32,
+}
+
+/// The location of a runtime data structure.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DataLocation {
+    /// The data structure is inlined in the `VMContext`, at the given
+    /// offset.
+    VMContext(u32),
+    /// A pointer to the data structure is stored in the `VMContext`,
+    /// at the given offset.
+    Pointer(u32),
+}
+
+/// Offsets, relative to the `VMContext`, needed to call an imported
+/// function.
+#[derive(Debug, Copy, Clone)]
+pub struct ImportedFunctionData {
+    /// The offset of the pointer to the function body.
+    pub body: u32,
+    /// The offset of the callee's `VMContext`.
+    pub vmctx: u32,
+}
+
+/// Information about a global.
+#[derive(Debug, Copy, Clone)]
+pub struct GlobalData {
+    /// The type of the global.
+    pub ty: ValType,
+    /// The location of the global's value.
+    pub location: DataLocation,
+}
+
+/// Information about a linear memory.
+#[derive(Debug, Copy, Clone)]
+pub struct HeapData {
+    /// The location of the memory's `VMMemoryDefinition`.
+    pub location: DataLocation,
+    /// The offset of the base pointer in the `VMMemoryDefinition`.
+    pub base_offset: u32,
+    /// The offset of the current length, in bytes, in the
+    /// `VMMemoryDefinition`.
+    pub current_length_offset: u32,
+    /// The type of the memory's index; `I64` for 64-bit memories
+    /// and `I32` otherwise.
+    pub index_type: ValType,
+}
+
+/// Information about a table.
+#[derive(Debug, Copy, Clone)]
+pub struct TableData {
+    /// The location of the table's `VMTableDefinition`.
+    pub location: DataLocation,
+    /// The offset of the base pointer in the `VMTableDefinition`.
+    pub base_offset: u32,
+    /// The offset of the current number of elements in the
+    /// `VMTableDefinition`.
+    pub current_elements_offset: u32,
+    /// The type of the table's elements.
+    pub element_type: ValType,
+}
+
+/// The builtin functions used by the code generation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BuiltinFunction {
+    /// `memory.grow`.
+    MemoryGrow,
+    /// `memory.copy`.
+    MemoryCopy,
+    /// `memory.fill`.
+    MemoryFill,
+    /// `memory.init`.
+    MemoryInit,
+    /// `data.drop`.
+    DataDrop,
+    /// `table.copy`.
+    TableCopy,
+    /// `table.init`.
+    TableInit,
+    /// `elem.drop`.
+    ElemDrop,
+    /// `table.grow` for `funcref` tables.
+    TableGrowFuncRef,
+    /// `table.fill` for `funcref` tables.
+    TableFillFuncRef,
+    /// Lazy initialization of a `funcref` table element.
+    TableGetLazyInitFuncRef,
+    /// `ref.func`.
+    RefFunc,
+}
+
+impl BuiltinFunction {
+    /// The parameter and result types of the builtin function,
+    /// including the leading `VMContext` parameter. Pointers are
+    /// represented as `I64`.
+    pub(crate) fn sig(&self) -> (&'static [ValType], &'static [ValType]) {
+        use ValType::*;
+        match self {
+            Self::MemoryGrow => (&[I64, I64, I32], &[I64]),
+            Self::MemoryCopy => (&[I64, I32, I64, I32, I64, I64], &[]),
+            Self::MemoryFill => (&[I64, I32, I64, I32, I64], &[]),
+            Self::MemoryInit => (&[I64, I32, I32, I64, I32, I32], &[]),
+            Self::DataDrop | Self::ElemDrop => (&[I64, I32], &[]),
+            Self::TableCopy | Self::TableInit => (&[I64, I32, I32, I32, I32, I32], &[]),
+            Self::TableGrowFuncRef => (&[I64, I32, I32, I64], &[I32]),
+            Self::TableFillFuncRef => (&[I64, I32, I32, I64, I32], &[]),
+            Self::TableGetLazyInitFuncRef => (&[I64, I32, I32], &[I64]),
+            Self::RefFunc => (&[I64, I32], &[I64]),
+        }
+    }
+}
@@ -1,16 +1,22 @@
 use crate::{
     abi::{ABISig, ABI},
-    masm::{MacroAssembler, OperandSize},
+    masm::{CalleeKind, CmpKind, ExtendKind, MacroAssembler, OperandSize, RegImm},
+    reg::Reg,
+    stack::{TypedReg, Val},
 };
-use anyhow::Result;
-use call::FnCall;
-use wasmparser::{BinaryReader, FuncValidator, ValType, ValidatorResources, VisitOperator};
+use anyhow::{bail, Result};
+pub(crate) use call::FnCall;
+use cranelift_codegen::ir::TrapCode;
+use smallvec::SmallVec;
+use wasmparser::{BinaryReader, FuncValidator, MemArg, ValType, ValidatorResources, VisitOperator};
 
 mod context;
 pub(crate) use context::*;
 mod env;
 pub use env::*;
 mod call;
+mod control;
+pub(crate) use control::*;
 
 /// The code generation abstraction.
 pub(crate) struct CodeGen<'a, A, M>
@@ -32,6 +38,17 @@ where
 
     /// A reference to the current ABI.
     pub abi: &'a A,
+
+    /// The control stack frames; the outermost frame represents the
+    /// function body.
+    pub control_frames: Vec<ControlStackFrame>,
+
+    /// Whether the code currently being emitted is reachable.
+    pub reachable: bool,
+
+    /// The nesting depth of control frames entered while in
+    /// unreachable code, which are not emitted.
+    unreachable_depth: u32,
 }
 
 impl<'a, A, M> CodeGen<'a, A, M>
@@ -52,6 +69,9 @@ where
             masm,
             abi,
             env,
+            control_frames: Vec::new(),
+            reachable: true,
+            unreachable_depth: 0,
         }
     }
 
@@ -68,13 +88,29 @@ where
         Ok(())
     }
 
-    // TODO stack checks
     fn emit_start(&mut self) -> Result<()> {
         self.masm.prologue();
         self.masm.reserve_stack(self.context.frame.locals_size);
+        self.emit_stack_check();
         Ok(())
     }
 
+    /// Emit a check of the stack pointer against the stack limit
+    /// stored in the `VMRuntimeLimits`, trapping with a stack
+    /// overflow if the limit has been exceeded.
+    fn emit_stack_check(&mut self) {
+        let vmctx = self.sig.params[0]
+            .get_reg()
+            .expect("VMContext argument in a register");
+        let offsets = self.env.vmctx_offsets();
+        let scratch = <A as ABI>::scratch_reg();
+        let addr = self.masm.address_at_reg(vmctx, offsets.runtime_limits);
+        self.masm.load(addr, scratch, OperandSize::S64);
+        let addr = self.masm.address_at_reg(scratch, offsets.stack_limit);
+        self.masm.load(addr, scratch, OperandSize::S64);
+        self.masm.check_stack(scratch);
+    }
+
     fn emit_body(
         &mut self,
         body: &mut BinaryReader<'a>,
@@ -88,51 +124,717 @@ where
             &mut self.context.regalloc,
         );
 
+        let exit = self.masm.get_label();
+        let state = BlockState {
+            params: Default::default(),
+            results: self.sig.result.tys().into(),
+            stack_len: 0,
+            sp_offset: self.masm.sp_offset(),
+        };
+        self.control_frames
+            .push(ControlStackFrame::Block { exit, state });
+
         while !body.eof() {
             let offset = body.original_position();
+            self.masm.start_source_loc(offset as u32);
             body.visit_operator(&mut ValidateThenVisit(validator.visitor(offset), self))??;
+            self.masm.end_source_loc();
         }
         validator.finish(body.original_position())?;
         return Ok(());
 
         struct ValidateThenVisit<'a, T, U>(T, &'a mut U);
 
+        macro_rules! control_op {
+            (Block) => {
+                ControlOp::Enter
+            };
+            (Loop) => {
+                ControlOp::Enter
+            };
+            (If) => {
+                ControlOp::Enter
+            };
+            (Else) => {
+                ControlOp::Else
+            };
+            (End) => {
+                ControlOp::End
+            };
+            ($op:ident) => {
+                ControlOp::Other
+            };
+        }
+
         macro_rules! validate_then_visit {
             ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
                 $(
                     fn $visit(&mut self $($(,$arg: $argty)*)?) -> Self::Output {
                         self.0.$visit($($($arg.clone()),*)?)?;
-                        Ok(self.1.$visit($($($arg),*)?))
+                        if self.1.should_visit(control_op!($op)) {
+                            self.1.$visit($($($arg),*)?)?;
+                        }
+                        Ok(())
                     }
                 )*
             };
         }
 
-        impl<'a, T, U> VisitOperator<'a> for ValidateThenVisit<'_, T, U>
+        impl<'a, T, A, M> VisitOperator<'a> for ValidateThenVisit<'_, T, CodeGen<'a, A, M>>
         where
             T: VisitOperator<'a, Output = wasmparser::Result<()>>,
-            U: VisitOperator<'a>,
+            A: ABI,
+            M: MacroAssembler,
         {
-            type Output = Result<U::Output>;
+            type Output = Result<()>;
 
             wasmparser::for_each_operator!(validate_then_visit);
         }
     }
 
-    /// Emit a direct function call.
-    pub fn emit_call(&mut self, index: u32) {
+    /// Determines whether an operator should be emitted.
+    ///
+    /// Code following an unconditional branch is unreachable until the
+    /// end (or else) of the enclosing control frame; control frames
+    /// opened in unreachable code are skipped entirely.
+    fn should_visit(&mut self, op: ControlOp) -> bool {
+        if self.reachable {
+            return true;
+        }
+
+        match op {
+            ControlOp::Enter => {
+                self.unreachable_depth += 1;
+                false
+            }
+            ControlOp::Else => self.unreachable_depth == 0,
+            ControlOp::End if self.unreachable_depth > 0 => {
+                self.unreachable_depth -= 1;
+                false
+            }
+            ControlOp::End => true,
+            ControlOp::Other => false,
+        }
+    }
+
+    /// Returns the index in the control stack of the frame at the
+    /// given relative depth.
+    pub fn control_index(&self, depth: u32) -> usize {
+        (self.control_frames.len() - 1)
+            .checked_sub(depth as usize)
+            .unwrap_or_else(|| panic!("invalid block depth: {}", depth))
+    }
+
+    /// Returns the stack pointer offset at which values of the given
+    /// types are passed to a control frame whose stack pointer offset
+    /// at the entry is `base`; multiple values are passed in the stack
+    /// slots right below the returned offset.
+    pub fn frame_sp_offset(base: u32, tys: &[ValType]) -> u32 {
+        match tys.len() {
+            0 | 1 => base,
+            n => base + n as u32 * <A as ABI>::word_bytes(),
+        }
+    }
+
+    /// Pops the value carried by a branch that carries a single value
+    /// from the value stack into the result register.
+    pub fn pop_block_result(&mut self, tys: &[ValType]) -> Option<TypedReg> {
+        match tys {
+            [ty] => {
+                let named = <A as ABI>::result_reg(ty);
+                Some(self.context.pop_to_reg(self.masm, Some(named)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Pops the i32 condition or index of a branch into a register
+    /// that doesn't clash with the result register, in case the
+    /// branch carries a single value.
+    pub fn pop_branch_operand(&mut self, tys: &[ValType]) -> Reg {
+        let reg = self.context.pop_to_reg(self.masm, None).reg;
+        match tys {
+            [ty] if reg == <A as ABI>::result_reg(ty) => {
+                let dst = self.context.any_gpr(self.masm);
+                self.masm
+                    .mov(RegImm::reg(reg), RegImm::reg(dst), OperandSize::S32);
+                self.context.free_reg(reg);
+                dst
+            }
+            _ => reg,
+        }
+    }
+
+    /// Copies the given number of values at the top of the value stack
+    /// to the consecutive stack slots right above the stack pointer
+    /// offset `base`, leaving the stack pointer right above the slots.
+    ///
+    /// The copy only uses the scratch register and doesn't modify the
+    /// value stack, so it can be emitted on a conditional path.
+    pub fn emit_copy_to_slots(&mut self, base: u32, count: usize) {
+        let word_bytes = <A as ABI>::word_bytes();
+        let slot = |i: usize| base + (i as u32 + 1) * word_bytes;
+        let target = slot(count - 1);
+        let current = self.masm.sp_offset();
+        let in_place = current == target
+            && self
+                .context
+                .stack
+                .peekn(count)
+                .enumerate()
+                .all(|(i, v)| matches!(v, Val::Memory { offset, .. } if *offset == slot(i)));
+        if in_place {
+            return;
+        }
+
+        if current < target {
+            self.masm.reserve_stack(target - current);
+        }
+
+        // The values are first pushed above the slots, since the
+        // values themselves might be located in the slots.
+        let scratch = <A as ABI>::scratch_reg();
+        let mut temps: SmallVec<[u32; 4]> = SmallVec::new();
+        for val in self.context.stack.peekn(count) {
+            let offset = match val {
+                Val::Reg(reg) => self.masm.push(reg.reg),
+                val => {
+                    self.context.move_val_to_reg(val, scratch, self.masm);
+                    self.masm.push(scratch)
+                }
+            };
+            temps.push(offset);
+        }
+
+        for (i, temp) in temps.into_iter().enumerate() {
+            let src = self.masm.address_from_sp(temp);
+            self.masm.load(src, scratch, OperandSize::S64);
+            let dst = self.masm.address_from_sp(slot(i));
+            self.masm.store(scratch.into(), dst, OperandSize::S64);
+        }
+
+        let current = self.masm.sp_offset();
+        self.masm.free_stack(current - target);
+    }
+
+    /// Emits an unconditional jump to the branch target of the
+    /// control frame at the given index, resetting the machine stack
+    /// pointer to the offset at the entry of the frame and copying the
+    /// values carried by the branch to their stack slots, if there's
+    /// more than one; a single value is expected to have been popped
+    /// into the result register.
+    ///
+    /// The tracked stack pointer offset is preserved, since the code
+    /// following the jump might still be reachable through other
+    /// control flow edges.
+    pub fn emit_jump_to(&mut self, index: usize) {
+        let frame = &self.control_frames[index];
+        let target = frame.branch_target();
+        let count = frame.branch_types().len();
+        let sp_offset = frame.state().sp_offset;
+        let current = self.masm.sp_offset();
+
+        if count > 1 {
+            self.emit_copy_to_slots(sp_offset, count);
+        } else if current > sp_offset {
+            self.masm.free_stack(current - sp_offset);
+        }
+        self.masm.jmp(target);
+        self.masm.reset_stack_pointer(current);
+    }
+
+    /// Moves the values of the given types at the top of the value
+    /// stack to the location in which they are passed to a control
+    /// frame whose stack pointer offset at the entry is `base`, and
+    /// truncates the value stack to the given length.
+    pub fn pop_frame_values(&mut self, base: u32, stack_len: usize, tys: &[ValType]) {
+        if tys.len() > 1 {
+            self.emit_copy_to_slots(base, tys.len());
+        } else if let Some(reg) = self.pop_block_result(tys) {
+            self.context.free_reg(reg);
+        }
+
+        let current = self.masm.sp_offset();
+        let target = Self::frame_sp_offset(base, tys);
+        if current > target {
+            self.masm.free_stack(current - target);
+        }
+        self.context.truncate_stack(stack_len);
+    }
+
+    /// Pushes values of the given types, passed to a control frame
+    /// whose stack pointer offset at the entry is `base`, to the value
+    /// stack.
+    pub fn push_frame_values(&mut self, base: u32, tys: &[ValType]) {
+        match tys {
+            [ty] => {
+                let named = <A as ABI>::result_reg(ty);
+                let reg = self.context.reg(named, self.masm);
+                self.context.stack.push(Val::reg(reg, *ty));
+            }
+            tys => self.push_slot_values(base, tys),
+        }
+    }
+
+    /// Pushes values of the given types, located in the consecutive
+    /// stack slots right above the stack pointer offset `base`, to the
+    /// value stack.
+    pub fn push_slot_values(&mut self, base: u32, tys: &[ValType]) {
+        let word_bytes = <A as ABI>::word_bytes();
+        for (i, ty) in tys.iter().enumerate() {
+            let offset = base + (i as u32 + 1) * word_bytes;
+            self.context.stack.push(Val::mem(*ty, offset));
+        }
+    }
+
+    /// Ends the given control frame, moving its results, if any, to
+    /// their designated location and restoring the value stack and
+    /// machine stack to their state at the entry of the frame.
+    pub fn end_control_frame(&mut self, state: &BlockState) {
+        if self.reachable {
+            self.pop_frame_values(state.sp_offset, state.stack_len, &state.results);
+        } else {
+            self.context.truncate_stack(state.stack_len);
+        }
+
+        let sp_offset = Self::frame_sp_offset(state.sp_offset, &state.results);
+        self.masm.reset_stack_pointer(sp_offset);
+    }
+
+    /// Emit a call to the function at the given index in the
+    /// function index space.
+    pub fn emit_call(&mut self, index: u32) -> Result<()> {
         let callee = self.env.callee_from_index(index);
-        if callee.import {
-            // TODO: Only locally defined functions for now.
-            unreachable!()
+        let sig = self.abi.sig(&callee.ty)?;
+        let param_count = callee.ty.params().len();
+
+        if !callee.import {
+            // Locally defined functions are called with the current
+            // `VMContext` as both the callee and the caller `VMContext`.
+            let callee_vmctx = self.context.any_gpr(self.masm);
+            self.context.load_vmctx(callee_vmctx, self.masm);
+            let caller_vmctx = self.context.any_gpr(self.masm);
+            self.context.load_vmctx(caller_vmctx, self.masm);
+            self.insert_vmctx_args(param_count, callee_vmctx, caller_vmctx);
+
+            let fncall = FnCall::new(self.abi, &sig, &mut self.context, self.masm);
+            fncall.emit::<M, A>(self.masm, &mut self.context, CalleeKind::Direct(index));
+            return Ok(());
+        }
+
+        let import = self.env.imported_function(index);
+        let callee_vmctx = self.context.any_gpr(self.masm);
+        self.context.load_vmctx(callee_vmctx, self.masm);
+        let body = self.context.any_gpr(self.masm);
+        let addr = self.masm.address_at_reg(callee_vmctx, import.body);
+        self.masm.load(addr, body, OperandSize::S64);
+        let addr = self.masm.address_at_reg(callee_vmctx, import.vmctx);
+        self.masm.load(addr, callee_vmctx, OperandSize::S64);
+        let caller_vmctx = self.context.any_gpr(self.masm);
+        self.context.load_vmctx(caller_vmctx, self.masm);
+        self.insert_vmctx_args(param_count, callee_vmctx, caller_vmctx);
+
+        self.emit_indirect_call(&sig, body);
+        Ok(())
+    }
+
+    /// Emit an indirect call through the table at the given index,
+    /// checking that the type of the callee matches the type at the
+    /// given index in the type index space.
+    pub fn emit_call_indirect(&mut self, type_index: u32, table_index: u32) -> Result<()> {
+        let ty = self.env.func_type_at(type_index);
+        let sig = self.abi.sig(&ty)?;
+        let offsets = self.env.vmctx_offsets();
+
+        let funcref = self.emit_table_get_funcref(table_index)?;
+        self.masm.trap_if(
+            CmpKind::Eq,
+            funcref,
+            RegImm::imm(0),
+            OperandSize::S64,
+            TrapCode::IndirectCallToNull,
+        );
+
+        // Compare the shared signature index of the callee against
+        // the expected one.
+        let scratch = <A as ABI>::scratch_reg();
+        let sig_index = self.env.signature_index(type_index);
+        self.context.load_vmctx(scratch, self.masm);
+        let addr = self.masm.address_at_reg(scratch, offsets.signature_ids);
+        self.masm.load(addr, scratch, OperandSize::S64);
+        let addr = self
+            .masm
+            .address_at_reg(scratch, sig_index * offsets.signature_id_size);
+        self.masm.load(addr, scratch, OperandSize::S32);
+        let callee_vmctx = self.context.any_gpr(self.masm);
+        let addr = self
+            .masm
+            .address_at_reg(funcref, offsets.func_ref_type_index);
+        self.masm.load(addr, callee_vmctx, OperandSize::S32);
+        self.masm.trap_if(
+            CmpKind::Ne,
+            callee_vmctx,
+            RegImm::reg(scratch),
+            OperandSize::S32,
+            TrapCode::BadSignature,
+        );
+
+        let addr = self.masm.address_at_reg(funcref, offsets.func_ref_vmctx);
+        self.masm.load(addr, callee_vmctx, OperandSize::S64);
+        let addr = self.masm.address_at_reg(funcref, offsets.func_ref_func_ptr);
+        self.masm.load(addr, funcref, OperandSize::S64);
+        let caller_vmctx = self.context.any_gpr(self.masm);
+        self.context.load_vmctx(caller_vmctx, self.masm);
+        self.insert_vmctx_args(ty.params().len(), callee_vmctx, caller_vmctx);
+
+        self.emit_indirect_call(&sig, funcref);
+        Ok(())
+    }
+
+    /// Emit a call to the given builtin function.
+    ///
+    /// The arguments are described by `args`, in which stack
+    /// arguments refer to the values at the top of the value stack, in
+    /// bottom-most to top-most order. The arguments are consumed and
+    /// the result, if any, is pushed to the value stack.
+    pub fn emit_builtin_call(
+        &mut self,
+        builtin: BuiltinFunction,
+        args: &[BuiltinArg],
+    ) -> Result<()> {
+        let (params, results) = builtin.sig();
+        let sig = self.abi.sig_from(params, results)?;
+        assert_eq!(params.len(), args.len());
+
+        // Request the `VMContext` register before popping any stack
+        // arguments, so that any spill happens before the values
+        // consumed by the call are removed from the value stack.
+        let vmctx = self.context.any_gpr(self.masm);
+        self.context.load_vmctx(vmctx, self.masm);
+
+        let stack_args = args
+            .iter()
+            .filter(|a| matches!(a, BuiltinArg::Stack(_)))
+            .count();
+        let len = self.context.stack.len();
+        let mut stack_vals: Vec<Option<Val>> = self
+            .context
+            .stack
+            .inner_mut()
+            .drain(len - stack_args..)
+            .map(Some)
+            .collect();
+        for arg in args {
+            let val = match arg {
+                BuiltinArg::VMContext => Val::reg(vmctx, ValType::I64),
+                BuiltinArg::I32(imm) => Val::i32(*imm as i32),
+                BuiltinArg::Stack(i) => stack_vals[*i].take().expect("unused stack argument"),
+            };
+            self.context.stack.push(val);
         }
 
-        let sig = self.abi.sig(&callee.ty);
+        let index = self.env.builtin_function_index(builtin);
+        let offsets = self.env.vmctx_offsets();
         let fncall = FnCall::new(self.abi, &sig, &mut self.context, self.masm);
-        fncall.emit::<M, A>(self.masm, &mut self.context, index);
+        let callee = <A as ABI>::callee_reg();
+        self.context.load_vmctx(callee, self.masm);
+        let addr = self.masm.address_at_reg(callee, offsets.builtin_functions);
+        self.masm.load(addr, callee, OperandSize::S64);
+        let addr = self
+            .masm
+            .address_at_reg(callee, index * <A as ABI>::word_bytes());
+        self.masm.load(addr, callee, OperandSize::S64);
+        fncall.emit::<M, A>(self.masm, &mut self.context, CalleeKind::Indirect(callee));
+        Ok(())
+    }
+
+    /// Inserts the callee and caller `VMContext` arguments below the
+    /// given number of WebAssembly arguments in the value stack.
+    fn insert_vmctx_args(&mut self, param_count: usize, callee_vmctx: Reg, caller_vmctx: Reg) {
+        let stack = self.context.stack.inner_mut();
+        let index = stack.len() - param_count;
+        stack.insert(index, Val::reg(caller_vmctx, ValType::I64));
+        stack.insert(index, Val::reg(callee_vmctx, ValType::I64));
+    }
+
+    /// Emit a call to the function whose address is in the given
+    /// register, which must not be part of the value stack.
+    fn emit_indirect_call(&mut self, sig: &ABISig, addr: Reg) {
+        let fncall = FnCall::new(self.abi, sig, &mut self.context, self.masm);
+        // At this point all the registers in the value stack have been
+        // saved, so the only allocated register is the one holding
+        // the callee address, which might be used to pass an argument.
+        let callee = <A as ABI>::callee_reg();
+        if addr != callee {
+            self.masm
+                .mov(RegImm::reg(addr), RegImm::reg(callee), OperandSize::S64);
+        }
+        self.context.free_reg(addr);
+        fncall.emit::<M, A>(self.masm, &mut self.context, CalleeKind::Indirect(callee));
+    }
+
+    /// Emit the computation of the address of an access of the given
+    /// size to the linear memory described by `memarg`, whose index is
+    /// at the top of the value stack. Traps with an out of bounds heap
+    /// access if any byte of the access is outside of the memory.
+    ///
+    /// Returns the register holding the address, which must be freed
+    /// by the caller.
+    pub fn emit_heap_address(&mut self, memarg: &MemArg, access_size: u32) -> Result<Reg> {
+        let heap = self.env.heap(memarg.memory);
+        let index = self.context.pop_to_reg(self.masm, None).reg;
+        if heap.index_type == ValType::I32 {
+            self.masm.extend(index, index, ExtendKind::I64ExtendI32U);
+        }
+
+        // Compute the end of the access; which can only overflow for
+        // 64-bit memories.
+        let end = self.context.any_gpr(self.masm);
+        match memarg.offset.checked_add(access_size.into()) {
+            Some(bound) if heap.index_type == ValType::I32 => {
+                self.masm.mov(index.into(), end.into(), OperandSize::S64);
+                self.masm.add(
+                    end.into(),
+                    end.into(),
+                    RegImm::imm(bound as i64),
+                    OperandSize::S64,
+                );
+            }
+            Some(bound) => {
+                let scratch = <A as ABI>::scratch_reg();
+                self.masm
+                    .mov(RegImm::imm(bound as i64), scratch.into(), OperandSize::S64);
+                self.masm.checked_uadd(
+                    end,
+                    index,
+                    scratch.into(),
+                    OperandSize::S64,
+                    TrapCode::HeapOutOfBounds,
+                );
+            }
+            None => self.masm.trap(TrapCode::HeapOutOfBounds),
+        }
+
+        let base = self.context.any_gpr(self.masm);
+        let def_offset = self.emit_data_location(heap.location, base);
+        let scratch = <A as ABI>::scratch_reg();
+        let addr = self
+            .masm
+            .address_at_reg(base, def_offset + heap.current_length_offset);
+        self.masm.load(addr, scratch, OperandSize::S64);
+        self.masm.trap_if(
+            CmpKind::GtU,
+            end,
+            scratch.into(),
+            OperandSize::S64,
+            TrapCode::HeapOutOfBounds,
+        );
+        self.context.free_reg(end);
+
+        let addr = self
+            .masm
+            .address_at_reg(base, def_offset + heap.base_offset);
+        self.masm.load(addr, base, OperandSize::S64);
+        self.masm
+            .add(base.into(), base.into(), index.into(), OperandSize::S64);
+        self.context.free_reg(index);
+        if memarg.offset > 0 {
+            self.masm.add(
+                base.into(),
+                base.into(),
+                RegImm::imm(memarg.offset as i64),
+                OperandSize::S64,
+            );
+        }
+        Ok(base)
+    }
+
+    /// Loads into the given register the base address of a runtime
+    /// data structure at the given location, returning the offset of
+    /// the structure relative to that address.
+    pub fn emit_data_location(&mut self, location: DataLocation, dst: Reg) -> u32 {
+        self.context.load_vmctx(dst, self.masm);
+        match location {
+            DataLocation::VMContext(offset) => offset,
+            DataLocation::Pointer(offset) => {
+                let addr = self.masm.address_at_reg(dst, offset);
+                self.masm.load(addr, dst, OperandSize::S64);
+                0
+            }
+        }
+    }
+
+    /// Emit a bounds check of the index in the given register against
+    /// the table at the given index, and the computation of the
+    /// address of the element.
+    ///
+    /// Returns the register holding the address, which must be freed
+    /// by the caller.
+    fn emit_table_elem_address(&mut self, table_index: u32, index: Reg) -> Result<Reg> {
+        let table = self.env.table(table_index);
+        if table.element_type != ValType::FUNCREF {
+            bail!("Unsupported table element type {:?}", table.element_type);
+        }
+
+        let base = self.context.any_gpr(self.masm);
+        let def_offset = self.emit_data_location(table.location, base);
+        let scratch = <A as ABI>::scratch_reg();
+        let addr = self
+            .masm
+            .address_at_reg(base, def_offset + table.current_elements_offset);
+        self.masm.load(addr, scratch, OperandSize::S32);
+        self.masm.trap_if(
+            CmpKind::GeU,
+            index,
+            scratch.into(),
+            OperandSize::S32,
+            TrapCode::TableOutOfBounds,
+        );
+
+        let addr = self
+            .masm
+            .address_at_reg(base, def_offset + table.base_offset);
+        self.masm.load(addr, base, OperandSize::S64);
+        self.masm
+            .mov(index.into(), scratch.into(), OperandSize::S32);
+        self.masm.mul(
+            scratch.into(),
+            scratch.into(),
+            RegImm::imm(<A as ABI>::word_bytes().into()),
+            OperandSize::S64,
+        );
+        self.masm
+            .add(base.into(), base.into(), scratch.into(), OperandSize::S64);
+        Ok(base)
+    }
+
+    /// Emit a `funcref` table get, whose index is at the top of the
+    /// value stack, lazily initializing the element if needed.
+    ///
+    /// Returns the register holding the `funcref`, which must be freed
+    /// by the caller.
+    pub fn emit_table_get_funcref(&mut self, table_index: u32) -> Result<Reg> {
+        // The value stack is entirely spilled, so that its state is the
+        // same regardless of whether the lazy initialization is
+        // performed.
+        self.context.spill(self.masm);
+        let index = self.context.pop_to_reg(self.masm, None);
+        let addr = self.emit_table_elem_address(table_index, index.reg)?;
+        let elem = self.context.any_gpr(self.masm);
+        let src = self.masm.address_at_reg(addr, 0);
+        self.masm.load(src, elem, OperandSize::S64);
+        self.masm.and(
+            elem.into(),
+            elem.into(),
+            RegImm::imm(!FUNCREF_INIT_BIT),
+            OperandSize::S64,
+        );
+        let done = self.masm.get_label();
+        self.masm
+            .branch(CmpKind::Ne, elem, RegImm::imm(0), done, OperandSize::S64);
+
+        self.context.free_reg(addr);
+        self.context.free_reg(elem);
+        self.context.stack.push(index.into());
+        self.emit_builtin_call(
+            BuiltinFunction::TableGetLazyInitFuncRef,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(table_index),
+                BuiltinArg::Stack(0),
+            ],
+        )?;
+        let result = self.context.pop_to_reg(self.masm, Some(elem));
+        self.masm.bind(done);
+        Ok(result.reg)
+    }
+
+    /// Emit a `funcref` table set, whose index and value are at the top
+    /// of the value stack.
+    pub fn emit_table_set_funcref(&mut self, table_index: u32) -> Result<()> {
+        let value = self.context.pop_to_reg(self.masm, None).reg;
+        let index = self.context.pop_to_reg(self.masm, None).reg;
+        let addr = self.emit_table_elem_address(table_index, index)?;
+        self.context.free_reg(index);
+        self.masm.or(
+            value.into(),
+            value.into(),
+            RegImm::imm(FUNCREF_INIT_BIT),
+            OperandSize::S64,
+        );
+        let dst = self.masm.address_at_reg(addr, 0);
+        self.masm.store(value.into(), dst, OperandSize::S64);
+        self.context.free_reg(addr);
+        self.context.free_reg(value);
+        Ok(())
+    }
+
+    /// Emit a store of the value at the top of the value stack to the
+    /// local at the given index, returning the register holding the
+    /// value.
+    pub fn emit_set_local(&mut self, index: u32) -> TypedReg {
+        let slot = self
+            .context
+            .frame
+            .get_local(index)
+            .unwrap_or_else(|| panic!("valid local at slot = {}", index));
+        let src = self.context.pop_to_reg(self.masm, None);
+        // Any reference to the local in the value stack must observe
+        // the value it had before this store.
+        self.context.materialize_local(index, self.masm);
+        let addr = self.masm.local_address(&slot);
+        self.masm.store(RegImm::reg(src.reg), addr, slot.ty.into());
+        src
+    }
+
+    /// Emit a load from linear memory of a value of the given size,
+    /// whose index is at the top of the value stack. The loaded value
+    /// is pushed with the given type, sign extended if requested.
+    pub fn emit_wasm_load(
+        &mut self,
+        memarg: &MemArg,
+        ty: ValType,
+        size: OperandSize,
+        sign_extend: bool,
+    ) -> Result<()> {
+        let addr = self.emit_heap_address(memarg, size.bytes())?;
+        let dst = self.context.reg_for_type(ty, self.masm);
+        let src = self.masm.address_at_reg(addr, 0);
+        if sign_extend {
+            self.masm.load_sext(src, dst, size);
+        } else {
+            self.masm.load(src, dst, size);
+        }
+        self.context.free_reg(addr);
+        self.context.stack.push(Val::reg(dst, ty));
+        Ok(())
+    }
+
+    /// Emit a store to linear memory of the low `size` bits of the
+    /// value at the top of the value stack, whose index is right below
+    /// it.
+    pub fn emit_wasm_store(&mut self, memarg: &MemArg, size: OperandSize) -> Result<()> {
+        let src = self.context.pop_to_reg(self.masm, None);
+        let addr = self.emit_heap_address(memarg, size.bytes())?;
+        let dst = self.masm.address_at_reg(addr, 0);
+        self.masm.store(src.reg.into(), dst, size);
+        self.context.free_reg(addr);
+        self.context.free_reg(src);
+        Ok(())
+    }
+
+    /// Replaces the type of the register at the top of the value
+    /// stack.
+    pub fn retype_stack_top(&mut self, ty: ValType) {
+        let reg = self.context.pop_to_reg(self.masm, None);
+        self.context.stack.push(Val::reg(reg.reg, ty));
     }
 
     /// Emit the usual function end instruction sequence.
+    ///
+    /// At this point the control frame representing the function body
+    /// has been ended, leaving the function's result, if any, at the
+    /// top of the value stack.
     fn emit_end(&mut self) -> Result<()> {
         self.handle_abi_result();
         self.masm.epilogue(self.context.frame.locals_size);
@@ -140,43 +842,87 @@ where
     }
 
     fn spill_register_arguments(&mut self) {
-        // TODO
-        // Revisit this once the implicit VMContext argument is introduced;
-        // when that happens the mapping between local slots and abi args
-        // is not going to be symmetric.
-        self.sig
+        let frame = self.context.frame;
+        let slots = self
+            .sig
             .params
             .iter()
             .enumerate()
-            .filter(|(_, a)| a.is_reg())
-            .for_each(|(index, arg)| {
-                let ty = arg.ty();
-                let local = self
-                    .context
-                    .frame
-                    .get_local(index as u32)
-                    .expect("valid local slot at location");
-                let addr = self.masm.local_address(local);
-                let src = arg
-                    .get_reg()
-                    .expect("arg should be associated to a register");
-
-                match &ty {
-                    ValType::I32 => self.masm.store(src.into(), addr, OperandSize::S32),
-                    ValType::I64 => self.masm.store(src.into(), addr, OperandSize::S64),
-                    _ => panic!("Unsupported type {:?}", ty),
-                }
-            });
+            .map(|(index, arg)| (arg, frame.param_slot(index)))
+            .chain(
+                self.sig
+                    .ret_area_ptr
+                    .iter()
+                    .map(|arg| (arg, frame.ret_area_slot.as_ref())),
+            );
+        for (arg, slot) in slots {
+            if let (Some(src), Some(slot)) = (arg.get_reg(), slot) {
+                let addr = self.masm.local_address(slot);
+                self.masm.store(src.into(), addr, arg.ty().into());
+            }
+        }
     }
 
+    /// Moves the function results from the top of the value stack to
+    /// their ABI locations: the first result to the result register and
+    /// any other results to the return area.
     pub fn handle_abi_result(&mut self) {
-        if self.sig.result.is_void() {
-            return;
+        let result = &self.sig.result;
+        let tys = result.tys();
+        if tys.len() > 1 {
+            let slot = self
+                .context
+                .frame
+                .ret_area_slot
+                .as_ref()
+                .expect("return area slot");
+            let ret_area = self.context.any_gpr(self.masm);
+            let addr = self.masm.local_address(slot);
+            self.masm.load(addr, ret_area, OperandSize::S64);
+            for (index, ty) in tys.iter().enumerate().skip(1).rev() {
+                let val = self.context.pop_to_reg(self.masm, None);
+                let dst = self
+                    .masm
+                    .address_at_reg(ret_area, result.ret_area_offset(index));
+                self.masm.store(val.reg.into(), dst, (*ty).into());
+                self.context.free_reg(val);
+            }
+            self.context.free_reg(ret_area);
+        }
+
+        if !result.is_void() {
+            let named_reg = result.result_reg();
+            let reg = self.context.pop_to_reg(self.masm, Some(named_reg));
+            self.context.free_reg(reg);
         }
-        let named_reg = self.sig.result.result_reg();
-        let reg = self
-            .context
-            .pop_to_reg(self.masm, Some(named_reg), OperandSize::S64);
-        self.context.regalloc.free_gpr(reg);
     }
 }
+
+/// The classification of operators needed to handle unreachable
+/// code.
+enum ControlOp {
+    /// An operator which opens a control frame.
+    Enter,
+    /// The else operator.
+    Else,
+    /// The end operator.
+    End,
+    /// Any other operator.
+    Other,
+}
+
+/// The bit set in `funcref` table elements that have been initialized;
+/// uninitialized elements are lazily initialized on access.
+const FUNCREF_INIT_BIT: i64 = 1;
+
+/// An argument of a call to a builtin function.
+pub(crate) enum BuiltinArg {
+    /// The current `VMContext`.
+    VMContext,
+    /// An immediate 32-bit value.
+    I32(u32),
+    /// The value at the given position among the values at the top of
+    /// the value stack that are consumed by the call, in bottom-most
+    /// to top-most order.
+    Stack(usize),
+}
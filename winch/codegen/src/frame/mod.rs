@@ -1,4 +1,6 @@
-use crate::abi::{align_to, ty_size, ABIArg, ABISig, LocalSlot, ABI};
+use crate::abi::{
+    align_to, ensure_supported_ty, ty_size, ABIArg, ABISig, LocalSlot, ABI, VMCTX_PARAMS,
+};
 use anyhow::Result;
 use smallvec::SmallVec;
 use std::ops::Range;
@@ -33,6 +35,13 @@ pub(crate) struct Frame {
     /// Locals get calculated when allocating a frame and are readonly
     /// through the function compilation lifetime.
     pub locals: Locals,
+
+    /// The slot holding the `VMContext` of the current function.
+    pub vmctx_slot: LocalSlot,
+
+    /// The slot holding the address of the return area, if the
+    /// current function returns more than one value.
+    pub ret_area_slot: Option<LocalSlot>,
 }
 
 impl Frame {
@@ -43,7 +52,8 @@ impl Frame {
         validator: &mut FuncValidator<ValidatorResources>,
         abi: &A,
     ) -> Result<Self> {
-        let (mut locals, defined_locals_start) = Self::compute_arg_slots(sig, abi)?;
+        let (vmctx_slot, ret_area_slot, mut locals, defined_locals_start) =
+            Self::compute_arg_slots(sig, abi)?;
         let (defined_slots, defined_locals_end) =
             Self::compute_defined_slots(body, validator, defined_locals_start)?;
         locals.extend(defined_slots);
@@ -53,6 +63,8 @@ impl Frame {
             locals,
             locals_size,
             defined_locals_range: DefinedLocalsRange(defined_locals_start..defined_locals_end),
+            vmctx_slot,
+            ret_area_slot,
         })
     }
 
@@ -61,7 +73,20 @@ impl Frame {
         self.locals.get(index as usize)
     }
 
-    fn compute_arg_slots<A: ABI>(sig: &ABISig, abi: &A) -> Result<(Locals, u32)> {
+    /// Get the local slot in which the given ABI parameter is saved,
+    /// if any. The caller's `VMContext` is not saved.
+    pub fn param_slot(&self, index: usize) -> Option<&LocalSlot> {
+        match index {
+            0 => Some(&self.vmctx_slot),
+            i if i < VMCTX_PARAMS => None,
+            i => self.get_local((i - VMCTX_PARAMS) as u32),
+        }
+    }
+
+    fn compute_arg_slots<A: ABI>(
+        sig: &ABISig,
+        abi: &A,
+    ) -> Result<(LocalSlot, Option<LocalSlot>, Locals, u32)> {
         // Go over the function ABI-signature and
         // calculate the stack slots.
         //
@@ -89,16 +114,29 @@ impl Frame {
         //      Winch appends an addressing mode to each slot, in the end
         //      we want positive addressing from the stack pointer
         //      for both locals and stack arguments.
+        //
+        //  The callee `VMContext` gets its own slot, which is not
+        //  part of the WebAssembly locals; the caller `VMContext` is
+        //  not needed by the function body, so it's not saved. The
+        //  address of the return area, if any, also gets its own slot.
 
         let arg_base_offset = abi.arg_base_offset().into();
         let mut next_stack = 0u32;
-        let slots: Locals = sig
-            .params
-            .iter()
+        let mut params = sig.params.iter();
+        let vmctx_slot = params
+            .next()
+            .map(|arg| Self::abi_arg_slot(&arg, &mut next_stack, arg_base_offset))
+            .expect("vmctx parameter");
+        let slots: Locals = params
+            .skip(VMCTX_PARAMS - 1)
             .map(|arg| Self::abi_arg_slot(&arg, &mut next_stack, arg_base_offset))
             .collect();
+        let ret_area_slot = sig
+            .ret_area_ptr
+            .as_ref()
+            .map(|arg| Self::abi_arg_slot(arg, &mut next_stack, arg_base_offset));
 
-        Ok((slots, next_stack))
+        Ok((vmctx_slot, ret_area_slot, slots, next_stack))
     }
 
     fn abi_arg_slot(arg: &ABIArg, next_stack: &mut u32, arg_base_offset: u32) -> LocalSlot {
@@ -132,6 +170,7 @@ impl Frame {
             validator.define_locals(position, count, ty)?;
 
             let ty: ValType = ty.try_into()?;
+            ensure_supported_ty(&ty)?;
             for _ in 0..count {
                 let ty_size = ty_size(&ty);
                 next_stack = align_to(next_stack, ty_size) + ty_size;
@@ -1,8 +1,9 @@
 use super::regs;
-use crate::abi::{ABIArg, ABIResult, ABISig, ABI};
+use crate::abi::{ensure_supported_sig, ABIArg, ABIResult, ABISig, ABI};
 use crate::isa::reg::Reg;
+use anyhow::Result;
 use smallvec::SmallVec;
-use wasmparser::{FuncType, ValType};
+use wasmparser::ValType;
 
 #[derive(Default)]
 pub(crate) struct Aarch64ABI;
@@ -59,30 +60,44 @@ impl ABI for Aarch64ABI {
         64
     }
 
-    fn sig(&self, wasm_sig: &FuncType) -> ABISig {
-        if wasm_sig.results().len() > 1 {
-            panic!("multi-value not supported");
-        }
+    fn sig_from(&self, params: &[ValType], results: &[ValType]) -> Result<ABISig> {
+        ensure_supported_sig(params, results)?;
 
         let mut stack_offset = 0;
         let mut index_env = RegIndexEnv::default();
 
-        let params: SmallVec<[ABIArg; 6]> = wasm_sig
-            .params()
+        let params: SmallVec<[ABIArg; 6]> = params
             .iter()
             .map(|arg| Self::to_abi_arg(arg, &mut stack_offset, &mut index_env))
             .collect();
 
-        let ty = wasm_sig.results().get(0).map(|e| e.clone());
-        // NOTE temporarily defaulting to x0;
-        let reg = regs::xreg(0);
-        let result = ABIResult::reg(ty, reg);
+        let reg = results
+            .first()
+            .map(Self::result_reg)
+            .unwrap_or_else(|| regs::xreg(0));
+        let result = ABIResult::new(results, reg);
+
+        // The address of the return area is passed after all the
+        // other parameters.
+        let ret_area_ptr = (result.ret_area_size() > 0)
+            .then(|| Self::to_abi_arg(&ValType::I64, &mut stack_offset, &mut index_env));
+
+        Ok(ABISig::new(params, result, ret_area_ptr, stack_offset))
+    }
 
-        ABISig::new(params, result, stack_offset)
+    fn result_reg(ty: &ValType) -> Reg {
+        match ty {
+            ValType::F32 | ValType::F64 => regs::vreg(0),
+            _ => regs::xreg(0),
+        }
     }
 
     fn scratch_reg() -> Reg {
-        todo!()
+        regs::scratch()
+    }
+
+    fn callee_reg() -> Reg {
+        regs::ip1()
     }
 }
 
@@ -92,15 +107,16 @@ impl Aarch64ABI {
         stack_offset: &mut u32,
         index_env: &mut RegIndexEnv,
     ) -> ABIArg {
-        let (reg, ty) = match wasm_arg {
-            ty @ (ValType::I32 | ValType::I64) => (index_env.next_xreg().map(regs::xreg), ty),
+        let (reg, ty) = match *wasm_arg {
+            ty @ (ValType::I32 | ValType::I64 | ValType::FUNCREF) => {
+                (index_env.next_xreg().map(regs::xreg), ty)
+            }
 
             ty @ (ValType::F32 | ValType::F64) => (index_env.next_vreg().map(regs::vreg), ty),
 
             ty => unreachable!("Unsupported argument type {:?}", ty),
         };
 
-        let ty = *ty;
         let default = || {
             let size = Self::word_bytes();
             let arg = ABIArg::stack_offset(*stack_offset, ty);
@@ -140,7 +156,7 @@ mod tests {
         let wasm_sig = FuncType::new([I32, I64, I32, I64, I32, I32, I64, I32, I64], []);
 
         let abi = Aarch64ABI::default();
-        let sig = abi.sig(&wasm_sig);
+        let sig = abi.sig_from(wasm_sig.params(), wasm_sig.results()).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), I32, regs::xreg(0));
@@ -159,7 +175,7 @@ mod tests {
         let wasm_sig = FuncType::new([F32, F64, F32, F64, F32, F32, F64, F32, F64], []);
 
         let abi = Aarch64ABI::default();
-        let sig = abi.sig(&wasm_sig);
+        let sig = abi.sig_from(wasm_sig.params(), wasm_sig.results()).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::vreg(0));
@@ -178,7 +194,7 @@ mod tests {
         let wasm_sig = FuncType::new([F32, I32, I64, F64, I32, F32, F64, F32, F64], []);
 
         let abi = Aarch64ABI::default();
-        let sig = abi.sig(&wasm_sig);
+        let sig = abi.sig_from(wasm_sig.params(), wasm_sig.results()).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::vreg(0));
@@ -192,6 +208,35 @@ mod tests {
         match_reg_arg(params.get(8).unwrap(), F64, regs::vreg(5));
     }
 
+    #[test]
+    fn vmctx_abi_sig() {
+        let wasm_sig = FuncType::new([I32, F32], [F64]);
+
+        let abi = Aarch64ABI::default();
+        let sig = abi.sig(&wasm_sig).unwrap();
+        let params = sig.params;
+
+        match_reg_arg(params.get(0).unwrap(), I64, regs::xreg(0));
+        match_reg_arg(params.get(1).unwrap(), I64, regs::xreg(1));
+        match_reg_arg(params.get(2).unwrap(), I32, regs::xreg(2));
+        match_reg_arg(params.get(3).unwrap(), F32, regs::vreg(0));
+        assert_eq!(sig.result.result_reg(), regs::vreg(0));
+    }
+
+    #[test]
+    fn multi_value_abi_sig() {
+        let wasm_sig = FuncType::new([I32, F32], [F64, I32, I64]);
+
+        let abi = Aarch64ABI::default();
+        let sig = abi.sig(&wasm_sig).unwrap();
+
+        assert_eq!(sig.result.result_reg(), regs::vreg(0));
+        assert_eq!(sig.result.ret_area_offset(1), 0);
+        assert_eq!(sig.result.ret_area_offset(2), 8);
+        assert_eq!(sig.result.ret_area_size(), 16);
+        match_reg_arg(sig.ret_area_ptr.as_ref().unwrap(), I64, regs::xreg(3));
+    }
+
     fn match_reg_arg(abi_arg: &ABIArg, expected_ty: ValType, expected_reg: Reg) {
         match abi_arg {
             &ABIArg::Reg { reg, ty } => {
@@ -1,16 +1,30 @@
 //! Assembler library implementation for Aarch64.
 
 use super::{address::Address, regs};
-use crate::{masm::OperandSize, reg::Reg};
+use crate::{
+    masm::{CalleeKind, CmpKind, ExtendKind, OperandSize, RoundingMode, ShiftKind},
+    reg::Reg,
+};
 use cranelift_codegen::{
-    ir::MemFlags,
-    isa::aarch64::inst::{
-        self,
-        emit::{EmitInfo, EmitState},
-        ALUOp, AMode, ExtendOp, Imm12, Inst, PairAMode,
+    entity::EntityRef,
+    ir::{
+        types, ExternalName, MemFlags, Opcode, RelSourceLoc, SourceLoc, TrapCode, Type,
+        UserExternalNameRef,
+    },
+    isa::{
+        aarch64::inst::{
+            self,
+            emit::{EmitInfo, EmitState},
+            ALUOp, ALUOp3, AMode, BitOp, BranchTarget, CallIndInfo, CallInfo, Cond, CondBrKind,
+            ExtendOp, FPULeftShiftImm, FPUOp1, FPUOp2, FPUOpRI, FPUOpRIMod, FPURightShiftImm,
+            FpuRoundMode, FpuToIntOp, Imm12, ImmLogic, ImmShift, Inst, IntToFpuOp, PairAMode,
+            ScalarSize, UImm5, VecLanesOp, VecMisc2, VectorSize, NZCV,
+        },
+        CallConv,
     },
-    settings, Final, MachBuffer, MachBufferFinalized, MachInstEmit, Writable,
+    settings, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit, MachLabel, Writable,
 };
+use smallvec::smallvec;
 
 /// An Aarch64 instruction operand.
 #[derive(Debug)]
@@ -29,12 +43,67 @@ pub(crate) enum Operand {
 impl From<OperandSize> for inst::OperandSize {
     fn from(size: OperandSize) -> Self {
         match size {
+            // Narrower operations are performed on 32-bit registers.
+            OperandSize::S8 | OperandSize::S16 | OperandSize::S32 => Self::Size32,
+            OperandSize::S64 => Self::Size64,
+        }
+    }
+}
+
+impl From<OperandSize> for ScalarSize {
+    fn from(size: OperandSize) -> Self {
+        match size {
+            OperandSize::S8 => Self::Size8,
+            OperandSize::S16 => Self::Size16,
             OperandSize::S32 => Self::Size32,
             OperandSize::S64 => Self::Size64,
         }
     }
 }
 
+impl From<CmpKind> for Cond {
+    fn from(kind: CmpKind) -> Self {
+        match kind {
+            CmpKind::Eq => Cond::Eq,
+            CmpKind::Ne => Cond::Ne,
+            CmpKind::LtS => Cond::Lt,
+            CmpKind::LtU => Cond::Lo,
+            CmpKind::GtS => Cond::Gt,
+            CmpKind::GtU => Cond::Hi,
+            CmpKind::LeS => Cond::Le,
+            CmpKind::LeU => Cond::Ls,
+            CmpKind::GeS => Cond::Ge,
+            CmpKind::GeU => Cond::Hs,
+        }
+    }
+}
+
+impl From<ShiftKind> for ALUOp {
+    fn from(kind: ShiftKind) -> Self {
+        match kind {
+            ShiftKind::Shl => ALUOp::Lsl,
+            ShiftKind::ShrS => ALUOp::Asr,
+            ShiftKind::ShrU => ALUOp::Lsr,
+            ShiftKind::Rotr => ALUOp::RotR,
+            // Left rotations are emitted as right rotations by the
+            // negated amount.
+            ShiftKind::Rotl => ALUOp::RotR,
+        }
+    }
+}
+
+/// The memory access type of a register of the given size.
+fn access_type(reg: Reg, size: OperandSize) -> Type {
+    match (reg.is_float(), size) {
+        (true, OperandSize::S32) => types::F32,
+        (true, _) => types::F64,
+        (false, OperandSize::S8) => types::I8,
+        (false, OperandSize::S16) => types::I16,
+        (false, OperandSize::S32) => types::I32,
+        (false, OperandSize::S64) => types::I64,
+    }
+}
+
 /// Low level assembler implementation for Aarch64.
 pub(crate) struct Assembler {
     /// The machine instruction buffer.
@@ -58,13 +127,40 @@ impl Assembler {
 
 impl Assembler {
     /// Return the emitted code.
+    ///
+    /// Source locations are recorded as offsets in the module, hence
+    /// the base source location is zero.
     pub fn finalize(self) -> MachBufferFinalized<Final> {
         let stencil = self.buffer.finish();
-        stencil.apply_base_srcloc(Default::default())
+        stencil.apply_base_srcloc(SourceLoc::new(0))
+    }
+
+    /// Mark the start of the code for the given source location.
+    pub fn start_source_loc(&mut self, offset: u32) {
+        self.buffer.start_srcloc(RelSourceLoc::new(offset));
+    }
+
+    /// Mark the end of the code for the current source location.
+    pub fn end_source_loc(&mut self) {
+        self.buffer.end_srcloc();
     }
 
     fn emit(&mut self, inst: Inst) {
         inst.emit(&[], &mut self.buffer, &self.emit_info, &mut self.emit_state);
+
+        // Conditional branches and traps have a limited range; emit
+        // any pending veneers and trap sequences before going out of
+        // range, jumping over them.
+        let distance = Inst::worst_case_size();
+        if self.buffer.island_needed(distance) {
+            let continuation = self.buffer.get_label();
+            Inst::Jump {
+                dest: BranchTarget::Label(continuation),
+            }
+            .emit(&[], &mut self.buffer, &self.emit_info, &mut self.emit_state);
+            self.buffer.emit_island(distance);
+            self.buffer.bind_label(continuation);
+        }
     }
 
     /// Load a constant into a register.
@@ -88,49 +184,92 @@ impl Assembler {
 
     /// Store a register.
     pub fn str(&mut self, reg: Reg, addr: Address, size: OperandSize) {
-        let mem: AMode = addr.try_into().unwrap();
+        let mem = Self::amode(addr, access_type(reg, size));
         let flags = MemFlags::trusted();
+        let rd = reg.into();
 
         use OperandSize::*;
-        let inst = match size {
-            S64 => Inst::Store64 {
-                rd: reg.into(),
-                mem,
-                flags,
-            },
-            S32 => Inst::Store32 {
-                rd: reg.into(),
-                mem,
-                flags,
-            },
+        let inst = match (reg.is_float(), size) {
+            (true, S32) => Inst::FpuStore32 { rd, mem, flags },
+            (true, _) => Inst::FpuStore64 { rd, mem, flags },
+            (false, S64) => Inst::Store64 { rd, mem, flags },
+            (false, S32) => Inst::Store32 { rd, mem, flags },
+            (false, S16) => Inst::Store16 { rd, mem, flags },
+            (false, S8) => Inst::Store8 { rd, mem, flags },
         };
 
         self.emit(inst);
     }
 
-    /// Load a register.
+    /// Load a register, zero extending integer values.
     pub fn ldr(&mut self, addr: Address, rd: Reg, size: OperandSize) {
-        use OperandSize::*;
-        let writable_reg = Writable::from_reg(rd.into());
-        let mem: AMode = addr.try_into().unwrap();
+        let mem = Self::amode(addr, access_type(rd, size));
         let flags = MemFlags::trusted();
+        let writable_reg = Writable::from_reg(rd.into());
 
-        let inst = match size {
-            S64 => Inst::ULoad64 {
+        use OperandSize::*;
+        let inst = match (rd.is_float(), size) {
+            (true, S32) => Inst::FpuLoad32 {
                 rd: writable_reg,
                 mem,
                 flags,
             },
-            S32 => Inst::ULoad32 {
+            (true, _) => Inst::FpuLoad64 {
                 rd: writable_reg,
                 mem,
                 flags,
             },
+            (false, S64) => Inst::ULoad64 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (false, S32) => Inst::ULoad32 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (false, S16) => Inst::ULoad16 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+            (false, S8) => Inst::ULoad8 {
+                rd: writable_reg,
+                mem,
+                flags,
+            },
+        };
+
+        self.emit(inst);
+    }
+
+    /// Load an integer register, sign extending the value to 64 bits.
+    pub fn ldr_sext(&mut self, addr: Address, rd: Reg, size: OperandSize) {
+        let mem = Self::amode(addr, access_type(rd, size));
+        let flags = MemFlags::trusted();
+        let rd = Writable::from_reg(rd.into());
+
+        use OperandSize::*;
+        let inst = match size {
+            S64 => Inst::ULoad64 { rd, mem, flags },
+            S32 => Inst::SLoad32 { rd, mem, flags },
+            S16 => Inst::SLoad16 { rd, mem, flags },
+            S8 => Inst::SLoad8 { rd, mem, flags },
         };
 
         self.emit(inst);
     }
 
+    /// Load the effective address of the given location.
+    pub fn load_addr(&mut self, addr: Address, rd: Reg) {
+        let mem = Self::amode(addr, types::I64);
+        self.emit(Inst::LoadAddr {
+            rd: Writable::from_reg(rd.into()),
+            mem,
+        });
+    }
+
     /// Load a pair of registers.
     pub fn ldp(&mut self, xt1: Reg, xt2: Reg, addr: Address) {
         let writable_xt1 = Writable::from_reg(xt1.into());
@@ -148,11 +287,14 @@ impl Assembler {
     /// Move instruction combinations.
     pub fn mov(&mut self, src: Operand, dst: Operand, size: OperandSize) {
         match &(src, dst) {
-            (Operand::Imm(imm), Operand::Reg(rd)) => {
+            (Operand::Imm(imm), Operand::Reg(rd)) if rd.is_float() => {
                 let scratch = regs::scratch();
                 self.load_constant(*imm as u64, scratch);
                 self.mov_rr(scratch, *rd, size);
             }
+            (Operand::Imm(imm), Operand::Reg(rd)) => {
+                self.load_constant(*imm as u64, *rd);
+            }
             (Operand::Reg(src), Operand::Reg(rd)) => {
                 self.mov_rr(*src, *rd, size);
             }
@@ -164,14 +306,33 @@ impl Assembler {
         }
     }
 
-    /// Register to register move.
+    /// Register to register move. Moves between general purpose and
+    /// floating point registers preserve the bit pattern of the value.
     pub fn mov_rr(&mut self, rm: Reg, rd: Reg, size: OperandSize) {
         let writable_rd = Writable::from_reg(rd.into());
-        self.emit(Inst::Mov {
-            size: size.into(),
-            rd: writable_rd,
-            rm: rm.into(),
-        });
+        let inst = match (rm.is_float(), rd.is_float()) {
+            (false, false) => Inst::Mov {
+                size: size.into(),
+                rd: writable_rd,
+                rm: rm.into(),
+            },
+            (true, true) => Inst::FpuMove64 {
+                rd: writable_rd,
+                rn: rm.into(),
+            },
+            (false, true) => Inst::MovToFpu {
+                rd: writable_rd,
+                rn: rm.into(),
+                size: Self::float_size(size),
+            },
+            (true, false) => Inst::MovFromVec {
+                rd: writable_rd,
+                rn: rm.into(),
+                idx: 0,
+                size: Self::float_size(size),
+            },
+        };
+        self.emit(inst);
     }
 
     /// Add instruction combinations.
@@ -202,6 +363,22 @@ impl Assembler {
         }
     }
 
+    /// Subtract instruction combinations.
+    pub fn sub(&mut self, opm: Operand, opn: Operand, opd: Operand, size: OperandSize) {
+        match &(opm, opn, opd) {
+            (Operand::Imm(imm), Operand::Reg(rn), Operand::Reg(rd)) => {
+                self.sub_ir(*imm as u64, *rn, *rd, size);
+            }
+            (Operand::Reg(rm), Operand::Reg(rn), Operand::Reg(rd)) => {
+                self.emit_alu_rrr_extend(ALUOp::Sub, *rm, *rn, *rd, size);
+            }
+            (rm, rn, rd) => panic!(
+                "Invalid combination for sub: rm = {:?}, rn = {:?}, rd = {:?}",
+                rm, rn, rd
+            ),
+        }
+    }
+
     /// Subtract immediate and register.
     pub fn sub_ir(&mut self, imm: u64, rn: Reg, rd: Reg, size: OperandSize) {
         let alu_op = ALUOp::Sub;
@@ -214,11 +391,519 @@ impl Assembler {
         }
     }
 
+    /// Multiply instruction combinations.
+    pub fn mul(&mut self, opm: Operand, opn: Operand, opd: Operand, size: OperandSize) {
+        match &(opm, opn, opd) {
+            // Multiplying by a power of two doesn't need the scratch
+            // register, which may hold the multiplicand.
+            (Operand::Imm(imm), Operand::Reg(rn), Operand::Reg(rd))
+                if (*imm as u64).is_power_of_two() =>
+            {
+                let amount = (*imm as u64).trailing_zeros() as u64;
+                self.shift_ir(amount, *rn, *rd, ShiftKind::Shl, size);
+            }
+            (Operand::Imm(imm), Operand::Reg(rn), Operand::Reg(rd)) => {
+                let scratch = regs::scratch();
+                assert!(*rn != scratch);
+                self.load_constant(*imm as u64, scratch);
+                self.alu_rrrr(ALUOp3::MAdd, scratch, *rn, regs::zero(), *rd, size);
+            }
+            (Operand::Reg(rm), Operand::Reg(rn), Operand::Reg(rd)) => {
+                self.alu_rrrr(ALUOp3::MAdd, *rm, *rn, regs::zero(), *rd, size);
+            }
+            (rm, rn, rd) => panic!(
+                "Invalid combination for mul: rm = {:?}, rn = {:?}, rd = {:?}",
+                rm, rn, rd
+            ),
+        }
+    }
+
+    /// Logical instruction combinations: `and`, `orr` and `eor`.
+    pub fn logical(
+        &mut self,
+        op: ALUOp,
+        opm: Operand,
+        opn: Operand,
+        opd: Operand,
+        size: OperandSize,
+    ) {
+        match &(opm, opn, opd) {
+            (Operand::Imm(imm), Operand::Reg(rn), Operand::Reg(rd)) => {
+                let ty = match size {
+                    OperandSize::S64 => types::I64,
+                    _ => types::I32,
+                };
+                let bits = match size {
+                    OperandSize::S64 => *imm as u64,
+                    _ => *imm as u32 as u64,
+                };
+                if let Some(imml) = ImmLogic::maybe_from_u64(bits, ty) {
+                    self.emit(Inst::AluRRImmLogic {
+                        alu_op: op,
+                        size: size.into(),
+                        rd: Writable::from_reg((*rd).into()),
+                        rn: (*rn).into(),
+                        imml,
+                    });
+                } else {
+                    let scratch = regs::scratch();
+                    self.load_constant(bits, scratch);
+                    self.alu_rrr(op, scratch, *rn, *rd, size);
+                }
+            }
+            (Operand::Reg(rm), Operand::Reg(rn), Operand::Reg(rd)) => {
+                self.alu_rrr(op, *rm, *rn, *rd, size);
+            }
+            (rm, rn, rd) => panic!(
+                "Invalid combination for {:?}: rm = {:?}, rn = {:?}, rd = {:?}",
+                op, rm, rn, rd
+            ),
+        }
+    }
+
+    /// Add two registers, setting the flags.
+    pub fn adds(&mut self, opm: Operand, rn: Reg, rd: Reg, size: OperandSize) {
+        match opm {
+            Operand::Imm(imm) => match Imm12::maybe_from_u64(imm as u64) {
+                Some(imm12) => self.emit_alu_rri(ALUOp::AddS, imm12, rn, rd, size),
+                None => {
+                    let scratch = regs::scratch();
+                    self.load_constant(imm as u64, scratch);
+                    self.alu_rrr(ALUOp::AddS, scratch, rn, rd, size);
+                }
+            },
+            Operand::Reg(rm) => self.alu_rrr(ALUOp::AddS, rm, rn, rd, size),
+            Operand::Mem(_) => panic!("Invalid memory operand for adds"),
+        }
+    }
+
+    /// Three register ALU operation.
+    pub fn alu_rrr(&mut self, op: ALUOp, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::AluRRR {
+            alu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
+    /// Four register ALU operation; `rd = ra + rn * rm` for `madd` and
+    /// `rd = ra - rn * rm` for `msub`.
+    pub fn alu_rrrr(&mut self, op: ALUOp3, rm: Reg, rn: Reg, ra: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::AluRRRR {
+            alu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            rm: rm.into(),
+            ra: ra.into(),
+        });
+    }
+
+    /// Shift or rotate a register by an immediate amount; the amount
+    /// is masked to the operand width.
+    pub fn shift_ir(&mut self, amount: u64, rn: Reg, rd: Reg, kind: ShiftKind, size: OperandSize) {
+        let mask = u64::from(size.num_bits()) - 1;
+        let amount = match kind {
+            ShiftKind::Rotl => amount.wrapping_neg(),
+            _ => amount,
+        } & mask;
+        self.emit(Inst::AluRRImmShift {
+            alu_op: kind.into(),
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            immshift: ImmShift::maybe_from_u64(amount).expect("valid shift amount"),
+        });
+    }
+
+    /// Shift or rotate a register by the amount in a register; the
+    /// hardware masks the amount to the operand width.
+    pub fn shift_rr(&mut self, rm: Reg, rn: Reg, rd: Reg, kind: ShiftKind, size: OperandSize) {
+        let amount = match kind {
+            ShiftKind::Rotl => {
+                let scratch = regs::scratch();
+                self.alu_rrr(ALUOp::Sub, rm, regs::zero(), scratch, size);
+                scratch
+            }
+            _ => rm,
+        };
+        self.alu_rrr(kind.into(), amount, rn, rd, size);
+    }
+
+    /// Bit manipulation operations: `clz` and `rbit`.
+    pub fn bit_rr(&mut self, op: BitOp, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::BitRR {
+            op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Count the set bits of a general purpose register, using a
+    /// floating point register as temporary.
+    pub fn popcnt(&mut self, rn: Reg, rd: Reg, tmp: Reg, size: OperandSize) {
+        let writable_tmp = Writable::from_reg(tmp.into());
+        self.mov_rr(rn, tmp, size);
+        self.emit(Inst::VecMisc {
+            op: VecMisc2::Cnt,
+            rd: writable_tmp,
+            rn: tmp.into(),
+            size: VectorSize::Size8x8,
+        });
+        self.emit(Inst::VecLanes {
+            op: VecLanesOp::Addv,
+            rd: writable_tmp,
+            rn: tmp.into(),
+            size: VectorSize::Size8x8,
+        });
+        self.emit(Inst::MovFromVec {
+            rd: Writable::from_reg(rd.into()),
+            rn: tmp.into(),
+            idx: 0,
+            size: ScalarSize::Size8,
+        });
+    }
+
+    /// Compare instruction variants; sets the flags according to
+    /// `rn - opm`.
+    pub fn cmp(&mut self, opm: Operand, rn: Reg, size: OperandSize) {
+        match opm {
+            Operand::Imm(imm) => match Imm12::maybe_from_u64(imm as u64) {
+                Some(imm12) => self.emit_alu_rri(ALUOp::SubS, imm12, rn, regs::zero(), size),
+                None => {
+                    let scratch = regs::scratch();
+                    self.load_constant(imm as u64, scratch);
+                    self.alu_rrr(ALUOp::SubS, scratch, rn, regs::zero(), size);
+                }
+            },
+            Operand::Reg(rm) => self.alu_rrr(ALUOp::SubS, rm, rn, regs::zero(), size),
+            Operand::Mem(_) => panic!("Invalid memory operand for cmp"),
+        }
+    }
+
+    /// Conditional compare with an immediate; sets the flags according
+    /// to `rn - imm` if the condition holds, or to `nzcv` otherwise.
+    pub fn ccmp_imm(&mut self, imm: u8, rn: Reg, nzcv: NZCV, cond: Cond, size: OperandSize) {
+        self.emit(Inst::CCmpImm {
+            size: size.into(),
+            rn: rn.into(),
+            imm: UImm5::maybe_from_u8(imm).expect("valid 5-bit immediate"),
+            nzcv,
+            cond,
+        });
+    }
+
+    /// Set the register to 1 if the condition holds, or to 0
+    /// otherwise.
+    pub fn cset(&mut self, rd: Reg, cond: Cond) {
+        self.emit(Inst::CSet {
+            rd: Writable::from_reg(rd.into()),
+            cond,
+        });
+    }
+
+    /// Conditional select; `rd = cond ? rn : rm`.
+    pub fn csel(&mut self, rn: Reg, rm: Reg, rd: Reg, cond: Cond, size: OperandSize) {
+        let float = rd.is_float();
+        let rd = Writable::from_reg(rd.into());
+        let (rn, rm) = (rn.into(), rm.into());
+        let inst = match (float, size) {
+            (true, OperandSize::S32) => Inst::FpuCSel32 { rd, rn, rm, cond },
+            (true, _) => Inst::FpuCSel64 { rd, rn, rm, cond },
+            (false, _) => Inst::CSel { rd, cond, rn, rm },
+        };
+        self.emit(inst);
+    }
+
+    /// Integer extension.
+    pub fn extend(&mut self, rn: Reg, rd: Reg, kind: ExtendKind) {
+        let (signed, from_bits, to_bits) = match kind {
+            ExtendKind::I64ExtendI32S => (true, 32, 64),
+            ExtendKind::I64ExtendI32U => (false, 32, 64),
+            ExtendKind::I32Extend8S => (true, 8, 32),
+            ExtendKind::I32Extend16S => (true, 16, 32),
+            ExtendKind::I64Extend8S => (true, 8, 64),
+            ExtendKind::I64Extend16S => (true, 16, 64),
+        };
+        self.emit(Inst::Extend {
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            signed,
+            from_bits,
+            to_bits,
+        });
+    }
+
+    /// Floating point operation with two operands.
+    pub fn fpu_rrr(&mut self, op: FPUOp2, rm: Reg, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::FpuRRR {
+            fpu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
+    /// Floating point operation with one operand.
+    pub fn fpu_rr(&mut self, op: FPUOp1, rn: Reg, rd: Reg, size: OperandSize) {
+        self.emit(Inst::FpuRR {
+            fpu_op: op,
+            size: size.into(),
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Copy the sign of `rm` to the floating point value in `rd`,
+    /// using `tmp` as a temporary floating point register.
+    pub fn fpu_copysign(&mut self, rm: Reg, rd: Reg, tmp: Reg, size: OperandSize) {
+        let bits = size.num_bits() as u8;
+        let shift = bits - 1;
+        let (ushr, sli) = match size {
+            OperandSize::S32 => (
+                FPUOpRI::UShr32(FPURightShiftImm::maybe_from_u8(shift, bits).unwrap()),
+                FPUOpRIMod::Sli32(FPULeftShiftImm::maybe_from_u8(shift, bits).unwrap()),
+            ),
+            _ => (
+                FPUOpRI::UShr64(FPURightShiftImm::maybe_from_u8(shift, bits).unwrap()),
+                FPUOpRIMod::Sli64(FPULeftShiftImm::maybe_from_u8(shift, bits).unwrap()),
+            ),
+        };
+        // Move the sign bit of `rm` to the least significant bit and
+        // insert it back as the most significant bit of `rd`.
+        self.emit(Inst::FpuRRI {
+            fpu_op: ushr,
+            rd: Writable::from_reg(tmp.into()),
+            rn: rm.into(),
+        });
+        self.emit(Inst::FpuRRIMod {
+            fpu_op: sli,
+            rd: Writable::from_reg(rd.into()),
+            ri: rd.into(),
+            rn: tmp.into(),
+        });
+    }
+
+    /// Round a floating point value to an integral value.
+    pub fn fpu_round(&mut self, mode: RoundingMode, rn: Reg, rd: Reg, size: OperandSize) {
+        let op = match (mode, size) {
+            (RoundingMode::Nearest, OperandSize::S32) => FpuRoundMode::Nearest32,
+            (RoundingMode::Nearest, _) => FpuRoundMode::Nearest64,
+            (RoundingMode::Down, OperandSize::S32) => FpuRoundMode::Minus32,
+            (RoundingMode::Down, _) => FpuRoundMode::Minus64,
+            (RoundingMode::Up, OperandSize::S32) => FpuRoundMode::Plus32,
+            (RoundingMode::Up, _) => FpuRoundMode::Plus64,
+            (RoundingMode::Zero, OperandSize::S32) => FpuRoundMode::Zero32,
+            (RoundingMode::Zero, _) => FpuRoundMode::Zero64,
+        };
+        self.emit(Inst::FpuRound {
+            op,
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Floating point comparison; sets the flags according to
+    /// `rn - rm`, with an unordered result setting the carry and
+    /// overflow flags.
+    pub fn fpu_cmp(&mut self, rn: Reg, rm: Reg, size: OperandSize) {
+        self.emit(Inst::FpuCmp {
+            size: size.into(),
+            rn: rn.into(),
+            rm: rm.into(),
+        });
+    }
+
+    /// Convert an integer to a floating point value.
+    pub fn cvt_int_to_float(
+        &mut self,
+        rn: Reg,
+        rd: Reg,
+        signed: bool,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        use OperandSize::*;
+        let op = match (signed, src_size, dst_size) {
+            (true, S32, S32) => IntToFpuOp::I32ToF32,
+            (true, S32, _) => IntToFpuOp::I32ToF64,
+            (true, _, S32) => IntToFpuOp::I64ToF32,
+            (true, _, _) => IntToFpuOp::I64ToF64,
+            (false, S32, S32) => IntToFpuOp::U32ToF32,
+            (false, S32, _) => IntToFpuOp::U32ToF64,
+            (false, _, S32) => IntToFpuOp::U64ToF32,
+            (false, _, _) => IntToFpuOp::U64ToF64,
+        };
+        self.emit(Inst::IntToFpu {
+            op,
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Convert a floating point value to an integer, rounding towards
+    /// zero and saturating to the range of the integer type; NaN is
+    /// converted to zero.
+    pub fn cvt_float_to_int(
+        &mut self,
+        rn: Reg,
+        rd: Reg,
+        signed: bool,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        use OperandSize::*;
+        let op = match (signed, src_size, dst_size) {
+            (true, S32, S32) => FpuToIntOp::F32ToI32,
+            (true, S32, _) => FpuToIntOp::F32ToI64,
+            (true, _, S32) => FpuToIntOp::F64ToI32,
+            (true, _, _) => FpuToIntOp::F64ToI64,
+            (false, S32, S32) => FpuToIntOp::F32ToU32,
+            (false, S32, _) => FpuToIntOp::F32ToU64,
+            (false, _, S32) => FpuToIntOp::F64ToU32,
+            (false, _, _) => FpuToIntOp::F64ToU64,
+        };
+        self.emit(Inst::FpuToInt {
+            op,
+            rd: Writable::from_reg(rd.into()),
+            rn: rn.into(),
+        });
+    }
+
+    /// Function call.
+    pub fn call(&mut self, callee: CalleeKind) {
+        match callee {
+            CalleeKind::Direct(index) => {
+                let dest = ExternalName::user(UserExternalNameRef::new(index as usize));
+                self.emit(Inst::Call {
+                    info: Box::new(CallInfo {
+                        dest,
+                        uses: smallvec![],
+                        defs: smallvec![],
+                        clobbers: Default::default(),
+                        opcode: Opcode::Call,
+                        caller_callconv: CallConv::SystemV,
+                        callee_callconv: CallConv::SystemV,
+                    }),
+                });
+            }
+            CalleeKind::LibCall(libcall) => {
+                // Library calls are resolved through absolute
+                // relocations, hence the indirect call.
+                let scratch = regs::scratch();
+                self.emit(Inst::LoadExtName {
+                    rd: Writable::from_reg(scratch.into()),
+                    name: Box::new(ExternalName::LibCall(libcall)),
+                    offset: 0,
+                });
+                self.call_indirect(scratch);
+            }
+            CalleeKind::Indirect(reg) => self.call_indirect(reg),
+        }
+    }
+
+    fn call_indirect(&mut self, rn: Reg) {
+        self.emit(Inst::CallInd {
+            info: Box::new(CallIndInfo {
+                rn: rn.into(),
+                uses: smallvec![],
+                defs: smallvec![],
+                clobbers: Default::default(),
+                opcode: Opcode::CallIndirect,
+                caller_callconv: CallConv::SystemV,
+                callee_callconv: CallConv::SystemV,
+            }),
+        });
+    }
+
+    /// Unconditional jump to a label.
+    pub fn jmp(&mut self, target: MachLabel) {
+        self.emit(Inst::Jump {
+            dest: BranchTarget::Label(target),
+        });
+    }
+
+    /// Conditional jump to a label.
+    pub fn jmp_if(&mut self, cond: Cond, taken: MachLabel) {
+        // The not taken target is the next instruction; the
+        // unconditional branch to it is elided when binding the
+        // label.
+        let not_taken = self.get_label();
+        self.emit(Inst::CondBr {
+            taken: BranchTarget::Label(taken),
+            not_taken: BranchTarget::Label(not_taken),
+            kind: CondBrKind::Cond(cond),
+        });
+        self.bind(not_taken);
+    }
+
+    /// Trap instruction.
+    pub fn trap(&mut self, code: TrapCode) {
+        self.emit(Inst::Udf { trap_code: code });
+    }
+
+    /// Conditional trap.
+    pub fn trap_if(&mut self, cond: Cond, code: TrapCode) {
+        self.emit(Inst::TrapIf {
+            kind: CondBrKind::Cond(cond),
+            trap_code: code,
+        });
+    }
+
     /// Return instruction.
     pub fn ret(&mut self) {
         self.emit(Inst::Ret { rets: vec![] });
     }
 
+    /// Compare the stack pointer against the given limit and trap
+    /// with a stack overflow if the stack pointer is below it.
+    pub fn trap_if_sp_below(&mut self, limit: Reg) {
+        self.emit_alu_rrr_extend(
+            ALUOp::SubS,
+            limit,
+            regs::sp(),
+            regs::zero(),
+            OperandSize::S64,
+        );
+        self.emit(Inst::TrapIf {
+            kind: CondBrKind::Cond(Cond::Lo),
+            trap_code: TrapCode::StackOverflow,
+        });
+    }
+
+    /// Allocate a new label.
+    pub fn get_label(&mut self) -> MachLabel {
+        self.buffer.get_label()
+    }
+
+    /// Bind a label to the current offset.
+    pub fn bind(&mut self, label: MachLabel) {
+        self.buffer.bind_label(label);
+    }
+
+    // Helpers for addressing modes and operand sizes.
+
+    /// Convert the address to an addressing mode for an access of the
+    /// given type, which determines the scaling of the offset.
+    fn amode(addr: Address, ty: Type) -> AMode {
+        match addr.try_into().unwrap() {
+            AMode::RegOffset { rn, off, .. } => AMode::RegOffset { rn, off, ty },
+            mem => mem,
+        }
+    }
+
+    /// The scalar size of a floating point operand.
+    fn float_size(size: OperandSize) -> ScalarSize {
+        match size {
+            OperandSize::S32 => ScalarSize::Size32,
+            _ => ScalarSize::Size64,
+        }
+    }
+
     // Helpers for ALU operations.
 
     fn emit_alu_rri(&mut self, op: ALUOp, imm: Imm12, rn: Reg, rd: Reg, size: OperandSize) {
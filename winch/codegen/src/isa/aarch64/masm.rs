@@ -7,9 +7,18 @@ use crate::{
     abi::local::LocalSlot,
     codegen::CodeGenContext,
     isa::reg::Reg,
-    masm::{DivKind, MacroAssembler as Masm, OperandSize, RegImm, RemKind},
+    masm::{
+        CalleeKind, CmpKind, DivKind, ExtendKind, FloatCmpKind, MacroAssembler as Masm,
+        OperandSize, RegImm, RemKind, RoundingMode, ShiftKind, TruncKind,
+    },
+    stack::Val,
 };
-use cranelift_codegen::{settings, Final, MachBufferFinalized};
+use cranelift_codegen::{
+    ir::TrapCode,
+    isa::aarch64::inst::{ALUOp, ALUOp3, BitOp, Cond, FPUOp1, FPUOp2, NZCV},
+    settings, Final, MachBufferFinalized, MachLabel,
+};
+use wasmparser::ValType;
 
 /// Aarch64 MacroAssembler.
 pub(crate) struct MacroAssembler {
@@ -63,6 +72,10 @@ impl Masm for MacroAssembler {
 
         self.asm.stp(fp, lr, addr);
         self.asm.mov_rr(sp, fp, OperandSize::S64);
+        // The shadow stack pointer is callee saved; it's stored in a
+        // 16-byte slot to preserve the alignment of the stack pointer.
+        let addr = Address::pre_indexed_from_sp(-16);
+        self.asm.str(regs::shadow_sp(), addr, OperandSize::S64);
         self.move_sp_to_shadow_sp();
     }
 
@@ -73,9 +86,11 @@ impl Masm for MacroAssembler {
         if locals_size > 0 {
             self.asm
                 .add_ir(locals_size as u64, sp, sp, OperandSize::S64);
-            self.move_sp_to_shadow_sp();
         }
 
+        let addr = Address::post_indexed_from_sp(16);
+        self.asm.ldr(addr, regs::shadow_sp(), OperandSize::S64);
+
         let lr = regs::lr();
         let fp = regs::fp();
         let addr = Address::post_indexed_from_sp(16);
@@ -96,8 +111,16 @@ impl Masm for MacroAssembler {
         self.increment_sp(bytes);
     }
 
-    fn free_stack(&mut self, _bytes: u32) {
-        todo!()
+    fn free_stack(&mut self, bytes: u32) {
+        if bytes == 0 {
+            return;
+        }
+
+        let sp = regs::sp();
+        self.asm.add_ir(bytes as u64, sp, sp, OperandSize::S64);
+        self.move_sp_to_shadow_sp();
+
+        self.decrement_sp(bytes);
     }
 
     fn local_address(&mut self, local: &LocalSlot) -> Address {
@@ -115,12 +138,12 @@ impl Masm for MacroAssembler {
         Address::offset(reg, offset as i64)
     }
 
-    fn address_from_sp(&self, _offset: u32) -> Self::Address {
-        todo!()
+    fn address_from_sp(&self, offset: u32) -> Self::Address {
+        Address::from_shadow_sp((self.sp_offset - offset) as i64)
     }
 
-    fn address_at_sp(&self, _offset: u32) -> Self::Address {
-        todo!()
+    fn address_at_sp(&self, offset: u32) -> Self::Address {
+        Address::from_shadow_sp(offset as i64)
     }
 
     fn store(&mut self, src: RegImm, dst: Address, size: OperandSize) {
@@ -136,16 +159,30 @@ impl Masm for MacroAssembler {
         self.asm.str(src, dst, size);
     }
 
-    fn call(&mut self, _callee: u32) {
-        todo!()
+    fn address_at_reg(&self, reg: Reg, offset: u32) -> Self::Address {
+        Address::offset(reg, offset as i64)
+    }
+
+    fn call(&mut self, callee: CalleeKind) {
+        self.asm.call(callee);
     }
 
     fn load(&mut self, src: Address, dst: Reg, size: OperandSize) {
         self.asm.ldr(src, dst, size);
     }
 
-    fn pop(&mut self, _dst: Reg) {
-        todo!()
+    fn load_sext(&mut self, src: Address, dst: Reg, size: OperandSize) {
+        self.asm.ldr_sext(src, dst, size);
+    }
+
+    fn load_addr(&mut self, src: Address, dst: Reg, _size: OperandSize) {
+        self.asm.load_addr(src, dst);
+    }
+
+    fn pop(&mut self, dst: Reg) {
+        let address = Address::from_shadow_sp(0);
+        self.asm.ldr(address, dst, OperandSize::S64);
+        self.free_stack(8);
     }
 
     fn sp_offset(&mut self) -> u32 {
@@ -156,6 +193,14 @@ impl Masm for MacroAssembler {
         self.asm.finalize()
     }
 
+    fn start_source_loc(&mut self, offset: u32) {
+        self.asm.start_source_loc(offset);
+    }
+
+    fn end_source_loc(&mut self) {
+        self.asm.end_source_loc();
+    }
+
     fn mov(&mut self, src: RegImm, dst: RegImm, size: OperandSize) {
         self.asm.mov(src.into(), dst.into(), size);
     }
@@ -164,20 +209,315 @@ impl Masm for MacroAssembler {
         self.asm.add(rhs.into(), lhs.into(), dst.into(), size);
     }
 
-    fn sub(&mut self, _dst: RegImm, _lhs: RegImm, _rhs: RegImm, _size: OperandSize) {
-        todo!()
+    fn sub(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        self.asm.sub(rhs.into(), lhs.into(), dst.into(), size);
+    }
+
+    fn mul(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        self.asm.mul(rhs.into(), lhs.into(), dst.into(), size);
+    }
+
+    fn div(&mut self, context: &mut CodeGenContext, kind: DivKind, size: OperandSize) {
+        let divisor = context.pop_to_reg(self, None);
+        let dividend = context.pop_to_reg(self, None);
+
+        self.check_division(divisor.reg, dividend.reg, kind == DivKind::Signed, size);
+        self.divide(
+            divisor.reg,
+            dividend.reg,
+            dividend.reg,
+            kind == DivKind::Signed,
+            size,
+        );
+
+        context.free_reg(divisor);
+        context.stack.push(dividend.into());
+    }
+
+    fn rem(&mut self, context: &mut CodeGenContext, kind: RemKind, size: OperandSize) {
+        let divisor = context.pop_to_reg(self, None);
+        let dividend = context.pop_to_reg(self, None);
+
+        // The remainder of the overflowing signed division is zero,
+        // which is the result of the sequence below, so only division
+        // by zero traps.
+        self.check_division(divisor.reg, dividend.reg, false, size);
+        // remainder = dividend - (dividend / divisor) * divisor
+        let quotient = regs::scratch();
+        self.divide(
+            divisor.reg,
+            dividend.reg,
+            quotient,
+            kind == RemKind::Signed,
+            size,
+        );
+        self.asm.alu_rrrr(
+            ALUOp3::MSub,
+            divisor.reg,
+            quotient,
+            dividend.reg,
+            dividend.reg,
+            size,
+        );
+
+        context.free_reg(divisor);
+        context.stack.push(dividend.into());
+    }
+
+    fn checked_uadd(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize, trap: TrapCode) {
+        self.asm.adds(rhs.into(), lhs, dst, size);
+        // The carry flag is set on unsigned overflow.
+        self.asm.trap_if(Cond::Hs, trap);
+    }
+
+    fn and(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        self.asm
+            .logical(ALUOp::And, rhs.into(), lhs.into(), dst.into(), size);
     }
 
-    fn mul(&mut self, _dst: RegImm, _lhs: RegImm, _rhs: RegImm, _size: OperandSize) {
-        todo!()
+    fn or(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        self.asm
+            .logical(ALUOp::Orr, rhs.into(), lhs.into(), dst.into(), size);
     }
 
-    fn div(&mut self, _context: &mut CodeGenContext, _kind: DivKind, _size: OperandSize) {
-        todo!()
+    fn xor(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        self.asm
+            .logical(ALUOp::Eor, rhs.into(), lhs.into(), dst.into(), size);
     }
 
-    fn rem(&mut self, _context: &mut CodeGenContext, _kind: RemKind, _size: OperandSize) {
-        todo!()
+    fn shift(&mut self, context: &mut CodeGenContext, kind: ShiftKind, size: OperandSize) {
+        let top = context.stack.peek().expect("value at stack top");
+
+        if top.is_i32_const() || top.is_i64_const() {
+            let amount = match context.stack.pop().expect("value at stack top") {
+                Val::I32(v) => v as u32 as u64,
+                Val::I64(v) => v as u64,
+                _ => unreachable!(),
+            };
+            let dst = context.pop_to_reg(self, None);
+            self.asm.shift_ir(amount, dst.reg, dst.reg, kind, size);
+            context.stack.push(dst.into());
+        } else {
+            let amount = context.pop_to_reg(self, None);
+            let dst = context.pop_to_reg(self, None);
+            self.asm.shift_rr(amount.reg, dst.reg, dst.reg, kind, size);
+            context.free_reg(amount);
+            context.stack.push(dst.into());
+        }
+    }
+
+    fn clz(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        self.asm.bit_rr(BitOp::Clz, src, dst, size);
+    }
+
+    fn ctz(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        // The trailing zeros are the leading zeros of the value with
+        // its bits reversed.
+        self.asm.bit_rr(BitOp::RBit, src, dst, size);
+        self.asm.bit_rr(BitOp::Clz, dst, dst, size);
+    }
+
+    fn popcnt(&mut self, context: &mut CodeGenContext, size: OperandSize) {
+        let ty = int_type(size);
+        context.unop(self, ty, &mut |masm, reg| {
+            masm.asm.popcnt(reg, reg, regs::float_scratch(), size)
+        });
+    }
+
+    fn cmp_with_set(&mut self, src: RegImm, dst: RegImm, kind: CmpKind, size: OperandSize) {
+        let dst = match dst {
+            RegImm::Reg(reg) => reg,
+            RegImm::Imm(_) => panic!("the destination of a comparison must be a register"),
+        };
+        self.asm.cmp(src.into(), dst, size);
+        self.asm.cset(dst, kind.into());
+    }
+
+    fn float_cmp_with_set(
+        &mut self,
+        lhs: Reg,
+        rhs: Reg,
+        dst: Reg,
+        kind: FloatCmpKind,
+        size: OperandSize,
+    ) {
+        // An unordered comparison sets the carry and overflow flags;
+        // the condition codes are chosen so that comparisons
+        // involving NaN are false, except for `ne`.
+        let cond = match kind {
+            FloatCmpKind::Eq => Cond::Eq,
+            FloatCmpKind::Ne => Cond::Ne,
+            FloatCmpKind::Lt => Cond::Mi,
+            FloatCmpKind::Le => Cond::Ls,
+            FloatCmpKind::Gt => Cond::Gt,
+            FloatCmpKind::Ge => Cond::Ge,
+        };
+        self.asm.fpu_cmp(lhs, rhs, size);
+        self.asm.cset(dst, cond);
+    }
+
+    fn cmov_if_zero(&mut self, cond: Reg, src: Reg, dst: Reg, size: OperandSize) {
+        self.asm.cmp(Operand::Imm(0), cond, OperandSize::S32);
+        self.asm.csel(src, dst, dst, Cond::Eq, size);
+    }
+
+    fn extend(&mut self, src: Reg, dst: Reg, kind: ExtendKind) {
+        self.asm.extend(src, dst, kind);
+    }
+
+    fn float_add(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fpu_rrr(FPUOp2::Add, rhs, lhs, dst, size);
+    }
+
+    fn float_sub(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fpu_rrr(FPUOp2::Sub, rhs, lhs, dst, size);
+    }
+
+    fn float_mul(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fpu_rrr(FPUOp2::Mul, rhs, lhs, dst, size);
+    }
+
+    fn float_div(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fpu_rrr(FPUOp2::Div, rhs, lhs, dst, size);
+    }
+
+    fn float_min(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        // `fmin` and `fmax` propagate NaN and order signed zeros,
+        // matching WebAssembly's semantics.
+        self.asm.fpu_rrr(FPUOp2::Min, rhs, lhs, dst, size);
+    }
+
+    fn float_max(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.asm.fpu_rrr(FPUOp2::Max, rhs, lhs, dst, size);
+    }
+
+    fn float_copysign(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        if dst != lhs {
+            self.asm.mov_rr(lhs, dst, size);
+        }
+        self.asm.fpu_copysign(rhs, dst, regs::float_scratch(), size);
+    }
+
+    fn float_neg(&mut self, dst: Reg, size: OperandSize) {
+        self.asm.fpu_rr(FPUOp1::Neg, dst, dst, size);
+    }
+
+    fn float_abs(&mut self, dst: Reg, size: OperandSize) {
+        self.asm.fpu_rr(FPUOp1::Abs, dst, dst, size);
+    }
+
+    fn float_sqrt(&mut self, dst: Reg, src: Reg, size: OperandSize) {
+        self.asm.fpu_rr(FPUOp1::Sqrt, src, dst, size);
+    }
+
+    fn float_round(&mut self, mode: RoundingMode, context: &mut CodeGenContext, size: OperandSize) {
+        let ty = float_type(size);
+        context.unop(self, ty, &mut |masm, reg| {
+            masm.asm.fpu_round(mode, reg, reg, size)
+        });
+    }
+
+    fn signed_convert(&mut self, src: Reg, dst: Reg, src_size: OperandSize, dst_size: OperandSize) {
+        self.asm
+            .cvt_int_to_float(src, dst, true, src_size, dst_size);
+    }
+
+    fn unsigned_convert(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        _tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        self.asm
+            .cvt_int_to_float(src, dst, false, src_size, dst_size);
+    }
+
+    fn signed_truncate(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    ) {
+        if kind == TruncKind::Checked {
+            self.check_truncation(src, true, src_size, dst_size);
+        }
+        self.asm
+            .cvt_float_to_int(src, dst, true, src_size, dst_size);
+    }
+
+    fn unsigned_truncate(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        _tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    ) {
+        if kind == TruncKind::Checked {
+            self.check_truncation(src, false, src_size, dst_size);
+        }
+        self.asm
+            .cvt_float_to_int(src, dst, false, src_size, dst_size);
+    }
+
+    fn promote(&mut self, src: Reg, dst: Reg) {
+        self.asm
+            .fpu_rr(FPUOp1::Cvt32To64, src, dst, OperandSize::S32);
+    }
+
+    fn demote(&mut self, src: Reg, dst: Reg) {
+        self.asm
+            .fpu_rr(FPUOp1::Cvt64To32, src, dst, OperandSize::S64);
+    }
+
+    fn branch(
+        &mut self,
+        kind: CmpKind,
+        lhs: Reg,
+        rhs: RegImm,
+        taken: MachLabel,
+        size: OperandSize,
+    ) {
+        self.asm.cmp(rhs.into(), lhs, size);
+        self.asm.jmp_if(kind.into(), taken);
+    }
+
+    fn jmp(&mut self, target: MachLabel) {
+        self.asm.jmp(target);
+    }
+
+    fn get_label(&mut self) -> MachLabel {
+        self.asm.get_label()
+    }
+
+    fn bind(&mut self, label: MachLabel) {
+        self.asm.bind(label);
+    }
+
+    fn unreachable(&mut self) {
+        self.asm.trap(TrapCode::UnreachableCodeReached);
+    }
+
+    fn trap(&mut self, code: TrapCode) {
+        self.asm.trap(code);
+    }
+
+    fn trap_if(&mut self, kind: CmpKind, lhs: Reg, rhs: RegImm, size: OperandSize, code: TrapCode) {
+        self.asm.cmp(rhs.into(), lhs, size);
+        self.asm.trap_if(kind.into(), code);
+    }
+
+    fn check_stack(&mut self, limit: Reg) {
+        self.asm.trap_if_sp_below(limit);
+    }
+
+    fn reset_stack_pointer(&mut self, offset: u32) {
+        self.sp_offset = offset;
     }
 
     fn zero(&mut self, reg: Reg) {
@@ -189,7 +529,7 @@ impl Masm for MacroAssembler {
         // 64-bit architectures.
         let size = 8u32;
         self.reserve_stack(size);
-        let address = Address::from_shadow_sp(size as i64);
+        let address = Address::from_shadow_sp(0);
         self.asm.str(reg, address, OperandSize::S64);
 
         self.sp_offset
@@ -201,6 +541,98 @@ impl MacroAssembler {
         self.sp_offset += bytes;
     }
 
+    fn decrement_sp(&mut self, bytes: u32) {
+        assert!(
+            self.sp_offset >= bytes,
+            "sp offset = {}; bytes = {}",
+            self.sp_offset,
+            bytes
+        );
+        self.sp_offset -= bytes;
+    }
+
+    /// Trap if the divisor is zero and, for signed divisions, if the
+    /// division overflows, which the hardware doesn't detect.
+    fn check_division(&mut self, divisor: Reg, dividend: Reg, signed: bool, size: OperandSize) {
+        self.asm.cmp(Operand::Imm(0), divisor, size);
+        self.asm.trap_if(Cond::Eq, TrapCode::IntegerDivisionByZero);
+
+        if signed {
+            // The division overflows if the divisor is -1 and the
+            // dividend is the minimum integer, in which case
+            // subtracting 1 from the dividend sets the overflow flag.
+            self.asm.adds(Operand::Imm(1), divisor, regs::zero(), size);
+            self.asm.ccmp_imm(
+                1,
+                dividend,
+                NZCV::new(false, false, false, false),
+                Cond::Eq,
+                size,
+            );
+            self.asm.trap_if(Cond::Vs, TrapCode::IntegerOverflow);
+        }
+    }
+
+    /// Emit a division; the hardware only divides 64-bit values, so
+    /// 32-bit operands are extended in place first.
+    fn divide(&mut self, divisor: Reg, dividend: Reg, dst: Reg, signed: bool, size: OperandSize) {
+        if size == OperandSize::S32 {
+            let kind = if signed {
+                ExtendKind::I64ExtendI32S
+            } else {
+                ExtendKind::I64ExtendI32U
+            };
+            self.asm.extend(divisor, divisor, kind);
+            self.asm.extend(dividend, dividend, kind);
+        }
+        let op = if signed { ALUOp::SDiv } else { ALUOp::UDiv };
+        self.asm
+            .alu_rrr(op, divisor, dividend, dst, OperandSize::S64);
+    }
+
+    /// Trap if the floating point value in `src` is NaN or out of the
+    /// range of the integer type of the given size.
+    fn check_truncation(
+        &mut self,
+        src: Reg,
+        signed: bool,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        self.asm.fpu_cmp(src, src, src_size);
+        self.asm.trap_if(Cond::Vs, TrapCode::BadConversionToInteger);
+
+        let bits = dst_size.num_bits() as i32;
+        // The bounds are exclusive; the lower bound is inclusive if
+        // it's representable in the integer type.
+        let (low, low_cond, high) = match (signed, src_size, dst_size) {
+            (false, _, _) => (-1.0, Cond::Ls, 2f64.powi(bits)),
+            (true, OperandSize::S64, OperandSize::S32) => {
+                (-2f64.powi(bits - 1) - 1.0, Cond::Ls, 2f64.powi(bits - 1))
+            }
+            (true, _, _) => (-2f64.powi(bits - 1), Cond::Mi, 2f64.powi(bits - 1)),
+        };
+
+        let tmp = regs::float_scratch();
+        self.load_float_const(low, tmp, src_size);
+        self.asm.fpu_cmp(src, tmp, src_size);
+        self.asm.trap_if(low_cond, TrapCode::IntegerOverflow);
+        self.load_float_const(high, tmp, src_size);
+        self.asm.fpu_cmp(src, tmp, src_size);
+        self.asm.trap_if(Cond::Ge, TrapCode::IntegerOverflow);
+    }
+
+    /// Load a floating point constant of the given size, through the
+    /// integer scratch register.
+    fn load_float_const(&mut self, value: f64, dst: Reg, size: OperandSize) {
+        let bits = match size {
+            OperandSize::S32 => (value as f32).to_bits() as u64,
+            _ => value.to_bits(),
+        };
+        self.asm
+            .mov(Operand::Imm(bits as i64), Operand::Reg(dst), size);
+    }
+
     // Copies the value of the stack pointer to the shadow stack
     // pointer: mov x28, sp
 
@@ -215,3 +647,17 @@ impl MacroAssembler {
         self.asm.mov_rr(sp, shadow_sp, OperandSize::S64);
     }
 }
+
+fn int_type(size: OperandSize) -> ValType {
+    match size {
+        OperandSize::S32 => ValType::I32,
+        _ => ValType::I64,
+    }
+}
+
+fn float_type(size: OperandSize) -> ValType {
+    match size {
+        OperandSize::S32 => ValType::F32,
+        _ => ValType::F64,
+    }
+}
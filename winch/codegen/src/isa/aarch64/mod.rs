@@ -1,4 +1,4 @@
-use self::regs::{scratch, ALL_GPR};
+use self::regs::{scratch, ALL_FPR, ALL_GPR};
 use crate::{
     abi::ABI,
     codegen::{CodeGen, CodeGenContext},
@@ -92,10 +92,9 @@ impl TargetIsa for Aarch64 {
         let mut masm = Aarch64Masm::new(self.shared_flags.clone());
         let stack = Stack::new();
         let abi = abi::Aarch64ABI::default();
-        let abi_sig = abi.sig(sig);
+        let abi_sig = abi.sig(sig)?;
         let frame = Frame::new(&abi_sig, &mut body, &mut validator, &abi)?;
-        // TODO: Add floating point bitmask
-        let regalloc = RegAlloc::new(RegSet::new(ALL_GPR, 0), scratch());
+        let regalloc = RegAlloc::new(RegSet::new(ALL_GPR, ALL_FPR), scratch());
         let codegen_context = CodeGenContext::new(regalloc, stack, &frame);
         let mut codegen = CodeGen::new(&mut masm, &abi, codegen_context, env, abi_sig);
 
@@ -26,6 +26,11 @@ pub(crate) const fn scratch() -> Reg {
     ip0()
 }
 
+/// Floating point scratch register.
+pub(crate) const fn float_scratch() -> Reg {
+    vreg(31)
+}
+
 /// Scratch register.
 /// Intra-procedure-call corruptible register.
 pub(crate) const fn ip1() -> Reg {
@@ -125,13 +130,26 @@ pub(crate) const fn shadow_sp() -> Reg {
     xreg(28)
 }
 
+// Callee saved registers in the system calling convention are never
+// allocated, with the exception of the shadow stack pointer, which is
+// saved in the prologue and restored in the epilogue; see the default
+// ABI documentation.
+const CALLEE_SAVED_GPR: u32 = 0x1ff << 19;
 const NON_ALLOCATABLE_GPR: u32 = (1 << ip0().hw_enc())
     | (1 << ip1().hw_enc())
     | (1 << platform().hw_enc())
     | (1 << fp().hw_enc())
     | (1 << lr().hw_enc())
     | (1 << zero().hw_enc())
-    | (1 << shadow_sp().hw_enc());
+    | (1 << shadow_sp().hw_enc())
+    | CALLEE_SAVED_GPR;
 
 /// Bitmask to represent the available general purpose registers.
 pub(crate) const ALL_GPR: u32 = u32::MAX & !NON_ALLOCATABLE_GPR;
+
+// The lower 64 bits of v8-v15 are callee saved.
+const CALLEE_SAVED_FPR: u32 = 0xff << 8;
+const NON_ALLOCATABLE_FPR: u32 = (1 << float_scratch().hw_enc()) | CALLEE_SAVED_FPR;
+
+/// Bitmask to represent the available floating point registers.
+pub(crate) const ALL_FPR: u32 = u32::MAX & !NON_ALLOCATABLE_FPR;
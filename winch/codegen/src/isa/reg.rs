@@ -22,11 +22,25 @@ impl Reg {
     }
 
     /// Create a new floating point register from encoding.
-    #[allow(dead_code)]
     pub fn float(enc: usize) -> Self {
         Self::new(PReg::new(enc, RegClass::Float))
     }
 
+    /// Get the class of the underlying register.
+    pub fn class(self) -> RegClass {
+        self.0.class()
+    }
+
+    /// Returns true if the register is a general purpose register.
+    pub fn is_int(self) -> bool {
+        self.class() == RegClass::Int
+    }
+
+    /// Returns true if the register is a floating point register.
+    pub fn is_float(self) -> bool {
+        self.class() == RegClass::Float
+    }
+
     /// Get the encoding of the underlying register.
     pub const fn hw_enc(self) -> u8 {
         self.0.hw_enc() as u8
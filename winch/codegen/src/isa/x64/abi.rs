@@ -1,10 +1,11 @@
 use super::regs;
 use crate::{
-    abi::{ABIArg, ABIResult, ABISig, ABI},
+    abi::{ensure_supported_sig, ABIArg, ABIResult, ABISig, ABI},
     isa::reg::Reg,
 };
+use anyhow::Result;
 use smallvec::SmallVec;
-use wasmparser::{FuncType, ValType};
+use wasmparser::ValType;
 
 /// Helper environment to track argument-register
 /// assignment in x64.
@@ -25,7 +26,7 @@ impl RegIndexEnv {
 
     fn increment(index: &mut u8) -> u8 {
         let current = *index;
-        *index += 1;
+        *index = index.saturating_add(1);
         current
     }
 }
@@ -60,31 +61,45 @@ impl ABI for X64ABI {
         64
     }
 
-    fn sig(&self, wasm_sig: &FuncType) -> ABISig {
-        if wasm_sig.results().len() > 1 {
-            panic!("multi-value not supported");
-        }
+    fn sig_from(&self, params: &[ValType], results: &[ValType]) -> Result<ABISig> {
+        ensure_supported_sig(params, results)?;
 
         let mut stack_offset = 0;
         let mut index_env = RegIndexEnv::default();
 
-        let params: SmallVec<[ABIArg; 6]> = wasm_sig
-            .params()
+        let params: SmallVec<[ABIArg; 6]> = params
             .iter()
             .map(|arg| Self::to_abi_arg(arg, &mut stack_offset, &mut index_env))
             .collect();
 
-        let ty = wasm_sig.results().get(0).map(|e| e.clone());
-        // NOTE temporarily defaulting to rax.
-        let reg = regs::rax();
-        let result = ABIResult::reg(ty, reg);
+        let reg = results
+            .first()
+            .map(Self::result_reg)
+            .unwrap_or_else(regs::rax);
+        let result = ABIResult::new(results, reg);
+
+        // The address of the return area is passed after all the
+        // other parameters.
+        let ret_area_ptr = (result.ret_area_size() > 0)
+            .then(|| Self::to_abi_arg(&ValType::I64, &mut stack_offset, &mut index_env));
+
+        Ok(ABISig::new(params, result, ret_area_ptr, stack_offset))
+    }
 
-        ABISig::new(params, result, stack_offset)
+    fn result_reg(ty: &ValType) -> Reg {
+        match ty {
+            ValType::F32 | ValType::F64 => regs::xmm0(),
+            _ => regs::rax(),
+        }
     }
 
     fn scratch_reg() -> Reg {
         regs::scratch()
     }
+
+    fn callee_reg() -> Reg {
+        regs::r10()
+    }
 }
 
 impl X64ABI {
@@ -93,8 +108,10 @@ impl X64ABI {
         stack_offset: &mut u32,
         index_env: &mut RegIndexEnv,
     ) -> ABIArg {
-        let (reg, ty) = match wasm_arg {
-            ty @ (ValType::I32 | ValType::I64) => (Self::int_reg_for(index_env.next_gpr()), ty),
+        let (reg, ty) = match *wasm_arg {
+            ty @ (ValType::I32 | ValType::I64 | ValType::FUNCREF) => {
+                (Self::int_reg_for(index_env.next_gpr()), ty)
+            }
 
             ty @ (ValType::F32 | ValType::F64) => (Self::float_reg_for(index_env.next_fpr()), ty),
 
@@ -102,13 +119,13 @@ impl X64ABI {
         };
 
         let default = || {
-            let arg = ABIArg::stack_offset(*stack_offset, *ty);
+            let arg = ABIArg::stack_offset(*stack_offset, ty);
             let size = Self::word_bytes();
             *stack_offset += size;
             arg
         };
 
-        reg.map_or_else(default, |reg| ABIArg::reg(reg, *ty))
+        reg.map_or_else(default, |reg| ABIArg::reg(reg, ty))
     }
 
     fn int_reg_for(index: u8) -> Option<Reg> {
@@ -167,7 +184,7 @@ mod tests {
         let wasm_sig = FuncType::new([I32, I64, I32, I64, I32, I32, I64, I32], []);
 
         let abi = X64ABI::default();
-        let sig = abi.sig(&wasm_sig);
+        let sig = abi.sig_from(wasm_sig.params(), wasm_sig.results()).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), I32, regs::rdi());
@@ -185,7 +202,7 @@ mod tests {
         let wasm_sig = FuncType::new([F32, F64, F32, F64, F32, F32, F64, F32, F64], []);
 
         let abi = X64ABI::default();
-        let sig = abi.sig(&wasm_sig);
+        let sig = abi.sig_from(wasm_sig.params(), wasm_sig.results()).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::xmm0());
@@ -204,7 +221,7 @@ mod tests {
         let wasm_sig = FuncType::new([F32, I32, I64, F64, I32, F32, F64, F32, F64], []);
 
         let abi = X64ABI::default();
-        let sig = abi.sig(&wasm_sig);
+        let sig = abi.sig_from(wasm_sig.params(), wasm_sig.results()).unwrap();
         let params = sig.params;
 
         match_reg_arg(params.get(0).unwrap(), F32, regs::xmm0());
@@ -218,6 +235,56 @@ mod tests {
         match_reg_arg(params.get(8).unwrap(), F64, regs::xmm5());
     }
 
+    #[test]
+    fn vmctx_abi_sig() {
+        let wasm_sig = FuncType::new([I32, F32], [F64]);
+
+        let abi = X64ABI::default();
+        let sig = abi.sig(&wasm_sig).unwrap();
+        let params = sig.params;
+
+        match_reg_arg(params.get(0).unwrap(), I64, regs::rdi());
+        match_reg_arg(params.get(1).unwrap(), I64, regs::rsi());
+        match_reg_arg(params.get(2).unwrap(), I32, regs::rdx());
+        match_reg_arg(params.get(3).unwrap(), F32, regs::xmm0());
+        assert_eq!(sig.result.result_reg(), regs::xmm0());
+    }
+
+    #[test]
+    fn multi_value_abi_sig() {
+        let wasm_sig = FuncType::new([I32, F32], [F64, I32, I64, F32]);
+
+        let abi = X64ABI::default();
+        let sig = abi.sig(&wasm_sig).unwrap();
+
+        assert_eq!(sig.result.result_reg(), regs::xmm0());
+        assert_eq!(sig.result.ret_area_offset(1), 0);
+        assert_eq!(sig.result.ret_area_offset(2), 8);
+        assert_eq!(sig.result.ret_area_offset(3), 16);
+        assert_eq!(sig.result.ret_area_size(), 20);
+        match_reg_arg(sig.ret_area_ptr.as_ref().unwrap(), I64, regs::rcx());
+    }
+
+    #[test]
+    fn multi_value_abi_sig_stack_ret_area_ptr() {
+        let wasm_sig = FuncType::new([I32, I32, I32, I32, I32], [I32, I32]);
+
+        let abi = X64ABI::default();
+        let sig = abi.sig(&wasm_sig).unwrap();
+
+        assert_eq!(sig.result.result_reg(), regs::rax());
+        assert_eq!(sig.result.ret_area_size(), 4);
+        match_stack_arg(sig.ret_area_ptr.as_ref().unwrap(), I64, 8);
+        assert_eq!(sig.stack_bytes, 16);
+    }
+
+    #[test]
+    fn unsupported_abi_sig() {
+        let abi = X64ABI::default();
+        assert!(abi.sig(&FuncType::new([V128], [])).is_err());
+        assert!(abi.sig(&FuncType::new([], [I32, V128])).is_err());
+    }
+
     fn match_reg_arg(abi_arg: &ABIArg, expected_ty: ValType, expected_reg: Reg) {
         match abi_arg {
             &ABIArg::Reg { reg, ty } => {
@@ -2,20 +2,26 @@
 
 use crate::{
     isa::reg::Reg,
-    masm::{DivKind, OperandSize, RemKind},
+    masm::{
+        CalleeKind, CmpKind, DivKind, ExtendKind, OperandSize, RemKind, RoundingMode, ShiftKind,
+    },
 };
 use cranelift_codegen::{
     entity::EntityRef,
-    ir::TrapCode,
-    ir::{ExternalName, Opcode, UserExternalNameRef},
-    isa::x64::{
-        args::{
-            self, AluRmiROpcode, Amode, CmpOpcode, DivSignedness, ExtMode, FromWritableReg, Gpr,
-            GprMem, GprMemImm, RegMem, RegMemImm, SyntheticAmode, WritableGpr, CC,
+    ir::{types, ExternalName, Opcode, RelSourceLoc, SourceLoc, TrapCode, UserExternalNameRef},
+    isa::{
+        x64::{
+            args::{
+                self, AluRmiROpcode, Amode, CmpOpcode, DivSignedness, ExtMode, FromWritableReg,
+                Gpr, GprMem, GprMemImm, Imm8Gpr, Imm8Reg, RegMem, RegMemImm, SseOpcode,
+                SyntheticAmode, UnaryRmROpcode, WritableGpr, WritableXmm, Xmm, XmmMem,
+                XmmMemAligned, CC,
+            },
+            settings as x64_settings, CallInfo, EmitInfo, EmitState, Inst,
         },
-        settings as x64_settings, CallInfo, EmitInfo, EmitState, Inst,
+        CallConv,
     },
-    settings, Final, MachBuffer, MachBufferFinalized, MachInstEmit, Writable,
+    settings, Final, MachBuffer, MachBufferFinalized, MachInstEmit, MachLabel, Writable,
 };
 
 use super::{address::Address, regs};
@@ -59,9 +65,36 @@ impl From<Reg> for GprMemImm {
     }
 }
 
+impl From<Reg> for Xmm {
+    fn from(reg: Reg) -> Self {
+        Xmm::new(reg.into()).expect("valid xmm")
+    }
+}
+
+impl From<Reg> for WritableXmm {
+    fn from(reg: Reg) -> Self {
+        let writable = Writable::from_reg(reg.into());
+        WritableXmm::from_writable_reg(writable).expect("valid writable xmm")
+    }
+}
+
+impl From<Reg> for XmmMem {
+    fn from(reg: Reg) -> Self {
+        XmmMem::new(RegMem::reg(reg.into())).expect("valid xmm")
+    }
+}
+
+impl From<Reg> for XmmMemAligned {
+    fn from(reg: Reg) -> Self {
+        XmmMemAligned::new(RegMem::reg(reg.into())).expect("valid xmm")
+    }
+}
+
 impl From<OperandSize> for args::OperandSize {
     fn from(size: OperandSize) -> Self {
         match size {
+            OperandSize::S8 => Self::Size8,
+            OperandSize::S16 => Self::Size16,
             OperandSize::S32 => Self::Size32,
             OperandSize::S64 => Self::Size64,
         }
@@ -77,6 +110,41 @@ impl From<DivKind> for DivSignedness {
     }
 }
 
+impl From<Reg> for GprMem {
+    fn from(reg: Reg) -> Self {
+        GprMem::new(RegMem::reg(reg.into())).expect("valid gpr")
+    }
+}
+
+impl From<ShiftKind> for args::ShiftKind {
+    fn from(kind: ShiftKind) -> Self {
+        match kind {
+            ShiftKind::Shl => Self::ShiftLeft,
+            ShiftKind::ShrS => Self::ShiftRightArithmetic,
+            ShiftKind::ShrU => Self::ShiftRightLogical,
+            ShiftKind::Rotl => Self::RotateLeft,
+            ShiftKind::Rotr => Self::RotateRight,
+        }
+    }
+}
+
+impl From<CmpKind> for CC {
+    fn from(kind: CmpKind) -> Self {
+        match kind {
+            CmpKind::Eq => CC::Z,
+            CmpKind::Ne => CC::NZ,
+            CmpKind::LtS => CC::L,
+            CmpKind::LtU => CC::B,
+            CmpKind::GtS => CC::NLE,
+            CmpKind::GtU => CC::NBE,
+            CmpKind::LeS => CC::LE,
+            CmpKind::LeU => CC::BE,
+            CmpKind::GeS => CC::NL,
+            CmpKind::GeU => CC::NB,
+        }
+    }
+}
+
 /// Low level assembler implementation for x64.
 pub(crate) struct Assembler {
     /// The machine instruction buffer.
@@ -98,9 +166,22 @@ impl Assembler {
     }
 
     /// Return the emitted code.
+    ///
+    /// Source locations are recorded as offsets in the module, hence
+    /// the base source location is zero.
     pub fn finalize(self) -> MachBufferFinalized<Final> {
         let stencil = self.buffer.finish();
-        stencil.apply_base_srcloc(Default::default())
+        stencil.apply_base_srcloc(SourceLoc::new(0))
+    }
+
+    /// Mark the start of the code for the given source location.
+    pub fn start_source_loc(&mut self, offset: u32) {
+        self.buffer.start_srcloc(RelSourceLoc::new(offset));
+    }
+
+    /// Mark the end of the code for the current source location.
+    pub fn end_source_loc(&mut self) {
+        self.buffer.end_srcloc();
     }
 
     fn emit(&mut self, inst: Inst) {
@@ -121,26 +202,48 @@ impl Assembler {
 
     /// Return instruction.
     pub fn ret(&mut self) {
-        self.emit(Inst::Ret { rets: vec![] });
+        self.emit(Inst::Ret {
+            rets: vec![],
+            stack_bytes_to_pop: 0,
+        });
     }
 
     /// Move instruction variants.
+    ///
+    /// Moves between registers of different classes preserve the bit
+    /// pattern of the value.
     pub fn mov(&mut self, src: Operand, dst: Operand, size: OperandSize) {
         use self::Operand::*;
 
         match &(src, dst) {
-            (Reg(lhs), Reg(rhs)) => self.mov_rr(*lhs, *rhs, size),
+            (Reg(lhs), Reg(rhs)) => match (lhs.is_int(), rhs.is_int()) {
+                (true, true) => self.mov_rr(*lhs, *rhs, size),
+                (false, false) => self.xmm_mov_rr(*lhs, *rhs),
+                (true, false) => self.gpr_to_xmm(*lhs, *rhs, size),
+                (false, true) => self.xmm_to_gpr(*lhs, *rhs, size),
+            },
             (Reg(lhs), Mem(addr)) => match addr {
-                Address::Offset { base, offset: imm } => self.mov_rm(*lhs, *base, *imm, size),
+                Address::Offset { base, offset: imm } if lhs.is_int() => {
+                    self.mov_rm(*lhs, *base, *imm, size)
+                }
+                Address::Offset { base, offset: imm } => self.xmm_mov_rm(*lhs, *base, *imm, size),
             },
             (Imm(imm), Mem(addr)) => match addr {
                 Address::Offset { base, offset: disp } => {
                     self.mov_im(*imm as u64, *base, *disp, size)
                 }
             },
-            (Imm(imm), Reg(reg)) => self.mov_ir(*imm as u64, *reg, size),
+            (Imm(imm), Reg(reg)) if reg.is_int() => self.mov_ir(*imm as u64, *reg, size),
+            (Imm(imm), Reg(reg)) => {
+                let scratch = regs::scratch();
+                self.mov_ir(*imm as u64, scratch, size);
+                self.gpr_to_xmm(scratch, *reg, size);
+            }
             (Mem(addr), Reg(reg)) => match addr {
-                Address::Offset { base, offset: imm } => self.mov_mr(*base, *imm, *reg, size),
+                Address::Offset { base, offset: imm } if reg.is_int() => {
+                    self.mov_mr(*base, *imm, *reg, size)
+                }
+                Address::Offset { base, offset: imm } => self.xmm_mov_mr(*base, *imm, *reg, size),
             },
 
             _ => panic!(
@@ -192,26 +295,121 @@ impl Assembler {
         });
     }
 
-    /// Memory-to-register load.
+    /// Memory-to-register load, zero extending values smaller than 64
+    /// bits.
     pub fn mov_mr(&mut self, base: Reg, disp: u32, dst: Reg, size: OperandSize) {
-        use OperandSize::S64;
-
         let amode = Amode::imm_reg(disp, base.into());
         let src = SyntheticAmode::real(amode);
 
-        if size == S64 {
-            self.emit(Inst::Mov64MR {
-                src,
-                dst: dst.into(),
-            });
-        } else {
-            let reg_mem = RegMem::mem(src);
-            self.emit(Inst::MovzxRmR {
-                ext_mode: ExtMode::LQ,
-                src: GprMem::new(reg_mem).expect("valid memory address"),
-                dst: dst.into(),
-            });
-        }
+        let ext_mode = match size {
+            OperandSize::S8 => ExtMode::BQ,
+            OperandSize::S16 => ExtMode::WQ,
+            OperandSize::S32 => ExtMode::LQ,
+            OperandSize::S64 => {
+                self.emit(Inst::Mov64MR {
+                    src,
+                    dst: dst.into(),
+                });
+                return;
+            }
+        };
+
+        let reg_mem = RegMem::mem(src);
+        self.emit(Inst::MovzxRmR {
+            ext_mode,
+            src: GprMem::new(reg_mem).expect("valid memory address"),
+            dst: dst.into(),
+        });
+    }
+
+    /// Memory-to-register load, sign extending values smaller than 64
+    /// bits.
+    pub fn movsx_mr(&mut self, base: Reg, disp: u32, dst: Reg, size: OperandSize) {
+        let ext_mode = match size {
+            OperandSize::S8 => ExtMode::BQ,
+            OperandSize::S16 => ExtMode::WQ,
+            OperandSize::S32 => ExtMode::LQ,
+            OperandSize::S64 => return self.mov_mr(base, disp, dst, size),
+        };
+
+        let amode = Amode::imm_reg(disp, base.into());
+        let reg_mem = RegMem::mem(SyntheticAmode::real(amode));
+        self.emit(Inst::MovsxRmR {
+            ext_mode,
+            src: GprMem::new(reg_mem).expect("valid memory address"),
+            dst: dst.into(),
+        });
+    }
+
+    /// Load effective address.
+    pub fn lea(&mut self, addr: Address, dst: Reg, size: OperandSize) {
+        let addr = match addr {
+            Address::Offset { base, offset } => Amode::imm_reg(offset, base.into()),
+        };
+
+        self.emit(Inst::LoadEffectiveAddress {
+            addr: SyntheticAmode::real(addr),
+            dst: dst.into(),
+            size: size.into(),
+        });
+    }
+
+    /// Float register-to-register move.
+    pub fn xmm_mov_rr(&mut self, src: Reg, dst: Reg) {
+        self.emit(Inst::XmmUnaryRmR {
+            op: SseOpcode::Movaps,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Float memory-to-register load.
+    pub fn xmm_mov_mr(&mut self, base: Reg, disp: u32, dst: Reg, size: OperandSize) {
+        let amode = Amode::imm_reg(disp, base.into());
+        let src = XmmMem::new(RegMem::mem(SyntheticAmode::real(amode))).expect("valid address");
+        self.emit(Inst::XmmUnaryRmRUnaligned {
+            op: float_mov_opcode(size),
+            src,
+            dst: dst.into(),
+        });
+    }
+
+    /// Float register-to-memory store.
+    pub fn xmm_mov_rm(&mut self, src: Reg, base: Reg, disp: u32, size: OperandSize) {
+        let amode = Amode::imm_reg(disp, base.into());
+        self.emit(Inst::XmmMovRM {
+            op: float_mov_opcode(size),
+            src: src.into(),
+            dst: SyntheticAmode::real(amode),
+        });
+    }
+
+    /// Move the bits of an integer register to a float register.
+    pub fn gpr_to_xmm(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        let op = match size {
+            OperandSize::S64 => SseOpcode::Movq,
+            _ => SseOpcode::Movd,
+        };
+        self.emit(Inst::GprToXmm {
+            op,
+            src: src.into(),
+            dst: dst.into(),
+            src_size: size.into(),
+        });
+    }
+
+    /// Move the bits of a float register to an integer register.
+    pub fn xmm_to_gpr(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        let op = match size {
+            OperandSize::S64 => SseOpcode::Movq,
+            _ => SseOpcode::Movd,
+        };
+        self.emit(Inst::XmmToGpr {
+            op,
+            src: src.into(),
+            dst: dst.into(),
+            dst_size: size.into(),
+        });
     }
 
     /// Subtract instruction variants.
@@ -469,17 +667,421 @@ impl Assembler {
         });
     }
 
-    /// Direct function call to a user defined function.
-    pub fn call(&mut self, callee: u32) {
-        let dest = ExternalName::user(UserExternalNameRef::new(callee as usize));
-        self.emit(Inst::CallKnown {
-            dest,
-            info: Box::new(CallInfo {
-                uses: smallvec![],
-                defs: smallvec![],
-                clobbers: Default::default(),
-                opcode: Opcode::Call,
+    /// Logical and instruction variants.
+    pub fn and(&mut self, src: Operand, dst: Operand, size: OperandSize) {
+        self.alu(AluRmiROpcode::And, src, dst, size);
+    }
+
+    /// Logical or instruction variants.
+    pub fn or(&mut self, src: Operand, dst: Operand, size: OperandSize) {
+        self.alu(AluRmiROpcode::Or, src, dst, size);
+    }
+
+    /// Logical exclusive or instruction variants.
+    pub fn xor(&mut self, src: Operand, dst: Operand, size: OperandSize) {
+        self.alu(AluRmiROpcode::Xor, src, dst, size);
+    }
+
+    /// Emit an ALU operation with an immediate or register source;
+    /// immediates that don't fit in 32 bits are materialized in the
+    /// scratch register.
+    fn alu(&mut self, op: AluRmiROpcode, src: Operand, dst: Operand, size: OperandSize) {
+        match &(src, dst) {
+            (Operand::Imm(imm), Operand::Reg(dst)) => {
+                if let Ok(val) = i32::try_from(*imm) {
+                    self.emit(Inst::AluRmiR {
+                        size: size.into(),
+                        op,
+                        src1: (*dst).into(),
+                        src2: GprMemImm::new(RegMemImm::imm(val as u32)).expect("valid immediate"),
+                        dst: (*dst).into(),
+                    });
+                } else {
+                    let scratch = regs::scratch();
+                    self.mov_ir(*imm as u64, scratch, size);
+                    self.alu(op, scratch.into(), Operand::Reg(*dst), size);
+                }
+            }
+            (Operand::Reg(src), Operand::Reg(dst)) => self.emit(Inst::AluRmiR {
+                size: size.into(),
+                op,
+                src1: (*dst).into(),
+                src2: (*src).into(),
+                dst: (*dst).into(),
             }),
+            _ => panic!(
+                "Invalid operand combination for {:?}; src = {:?} dst = {:?}",
+                op, src, dst
+            ),
+        }
+    }
+
+    /// Shift or rotate a register by the amount in `cl`.
+    pub fn shift_rr(&mut self, dst: Reg, kind: ShiftKind, size: OperandSize) {
+        let num_bits = Imm8Gpr::new(Imm8Reg::Reg {
+            reg: regs::rcx().into(),
+        })
+        .expect("valid gpr");
+
+        self.emit(Inst::ShiftR {
+            size: size.into(),
+            kind: kind.into(),
+            src: dst.into(),
+            num_bits,
+            dst: dst.into(),
         });
     }
+
+    /// Shift or rotate a register by an immediate amount.
+    pub fn shift_ir(&mut self, imm: u8, dst: Reg, kind: ShiftKind, size: OperandSize) {
+        let num_bits = Imm8Gpr::new(Imm8Reg::Imm8 { imm }).expect("valid immediate");
+
+        self.emit(Inst::ShiftR {
+            size: size.into(),
+            kind: kind.into(),
+            src: dst.into(),
+            num_bits,
+            dst: dst.into(),
+        });
+    }
+
+    /// Compare instruction variants; sets the flags according to
+    /// `dst - src`.
+    pub fn cmp(&mut self, src: Operand, dst: Reg, size: OperandSize) {
+        match src {
+            Operand::Imm(imm) => {
+                if let Ok(val) = i32::try_from(imm) {
+                    self.emit(Inst::CmpRmiR {
+                        size: size.into(),
+                        opcode: CmpOpcode::Cmp,
+                        src: GprMemImm::new(RegMemImm::imm(val as u32)).expect("valid immediate"),
+                        dst: dst.into(),
+                    });
+                } else {
+                    let scratch = regs::scratch();
+                    self.mov_ir(imm as u64, scratch, size);
+                    self.cmp(scratch.into(), dst, size);
+                }
+            }
+            Operand::Reg(src) => self.emit(Inst::CmpRmiR {
+                size: size.into(),
+                opcode: CmpOpcode::Cmp,
+                src: src.into(),
+                dst: dst.into(),
+            }),
+            _ => panic!("Invalid operand for cmp; src = {:?}", src),
+        }
+    }
+
+    /// Set the low byte of `dst` according to the condition code and
+    /// zero extend it to the full register.
+    pub fn setcc(&mut self, cc: CC, dst: Reg) {
+        self.emit(Inst::Setcc {
+            cc,
+            dst: dst.into(),
+        });
+        self.emit(Inst::MovzxRmR {
+            ext_mode: ExtMode::BL,
+            src: dst.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Conditional register-to-register move.
+    pub fn cmov(&mut self, src: Reg, dst: Reg, cc: CC, size: OperandSize) {
+        self.emit(Inst::Cmove {
+            size: size.into(),
+            cc,
+            consequent: src.into(),
+            alternative: dst.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Sign or zero extend a register.
+    pub fn extend_rr(&mut self, src: Reg, dst: Reg, kind: ExtendKind) {
+        let ext_mode = match kind {
+            // 32-bit register moves implicitly zero the upper bits.
+            ExtendKind::I64ExtendI32U => return self.mov_rr(src, dst, OperandSize::S32),
+            ExtendKind::I64ExtendI32S => ExtMode::LQ,
+            ExtendKind::I32Extend8S => ExtMode::BL,
+            ExtendKind::I32Extend16S => ExtMode::WL,
+            ExtendKind::I64Extend8S => ExtMode::BQ,
+            ExtendKind::I64Extend16S => ExtMode::WQ,
+        };
+        self.emit(Inst::MovsxRmR {
+            ext_mode,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Unary bit counting instructions: `lzcnt`, `tzcnt`, `popcnt`,
+    /// `bsr` and `bsf`.
+    pub fn unary_rr(&mut self, op: UnaryRmROpcode, src: Reg, dst: Reg, size: OperandSize) {
+        self.emit(Inst::UnaryRmR {
+            size: size.into(),
+            op,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Negate a register.
+    pub fn neg(&mut self, dst: Reg, size: OperandSize) {
+        self.emit(Inst::Neg {
+            size: size.into(),
+            src: dst.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Scalar float binary operation; `dst = dst op src`.
+    pub fn xmm_rm_r(&mut self, op: SseOpcode, src: Reg, dst: Reg) {
+        self.emit(Inst::XmmRmR {
+            op,
+            src1: dst.into(),
+            src2: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// WebAssembly float minimum or maximum; `dst = op(lhs, dst)`.
+    pub fn xmm_min_max(&mut self, lhs: Reg, dst: Reg, is_min: bool, size: OperandSize) {
+        self.emit(Inst::XmmMinMaxSeq {
+            size: size.into(),
+            is_min,
+            lhs: lhs.into(),
+            rhs: dst.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Float square root.
+    pub fn sqrt(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        let op = match size {
+            OperandSize::S32 => SseOpcode::Sqrtss,
+            _ => SseOpcode::Sqrtsd,
+        };
+        self.emit(Inst::XmmUnaryRmRUnaligned {
+            op,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Float rounding; requires SSE4.1.
+    pub fn round(&mut self, mode: RoundingMode, src: Reg, dst: Reg, size: OperandSize) {
+        let op = match size {
+            OperandSize::S32 => SseOpcode::Roundss,
+            _ => SseOpcode::Roundsd,
+        };
+        let imm = match mode {
+            RoundingMode::Nearest => 0,
+            RoundingMode::Down => 1,
+            RoundingMode::Up => 2,
+            RoundingMode::Zero => 3,
+        };
+        self.emit(Inst::XmmUnaryRmRImm {
+            op,
+            src: src.into(),
+            imm,
+            dst: dst.into(),
+        });
+    }
+
+    /// Unordered float comparison; sets the flags according to
+    /// `dst - src`.
+    pub fn ucomis(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        let op = match size {
+            OperandSize::S32 => SseOpcode::Ucomiss,
+            _ => SseOpcode::Ucomisd,
+        };
+        self.emit(Inst::XmmCmpRmR {
+            op,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Float conditional move.
+    pub fn xmm_cmov(&mut self, src: Reg, dst: Reg, cc: CC, size: OperandSize) {
+        let ty = match size {
+            OperandSize::S32 => types::F32,
+            _ => types::F64,
+        };
+        self.emit(Inst::XmmCmove {
+            ty,
+            cc,
+            consequent: src.into(),
+            alternative: dst.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Convert a signed integer to a float.
+    pub fn cvt_sint_to_float(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        let op = match dst_size {
+            OperandSize::S32 => SseOpcode::Cvtsi2ss,
+            _ => SseOpcode::Cvtsi2sd,
+        };
+        self.emit(Inst::GprToXmm {
+            op,
+            src: src.into(),
+            dst: dst.into(),
+            src_size: src_size.into(),
+        });
+    }
+
+    /// Convert an unsigned 64-bit integer to a float.
+    pub fn cvt_uint64_to_float(&mut self, src: Reg, dst: Reg, tmp: Reg, dst_size: OperandSize) {
+        self.emit(Inst::CvtUint64ToFloatSeq {
+            dst_size: dst_size.into(),
+            src: src.into(),
+            dst: dst.into(),
+            tmp_gpr1: tmp.into(),
+            tmp_gpr2: regs::scratch().into(),
+        });
+    }
+
+    /// Convert a float to a signed integer, trapping or saturating on
+    /// NaN and out of range values.
+    pub fn cvt_float_to_sint(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        is_saturating: bool,
+    ) {
+        self.emit(Inst::CvtFloatToSintSeq {
+            dst_size: dst_size.into(),
+            src_size: src_size.into(),
+            is_saturating,
+            src: src.into(),
+            dst: dst.into(),
+            tmp_gpr: regs::scratch().into(),
+            tmp_xmm: regs::scratch_xmm().into(),
+        });
+    }
+
+    /// Convert a float to an unsigned integer, trapping or saturating
+    /// on NaN and out of range values.
+    pub fn cvt_float_to_uint(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        is_saturating: bool,
+    ) {
+        self.emit(Inst::CvtFloatToUintSeq {
+            dst_size: dst_size.into(),
+            src_size: src_size.into(),
+            is_saturating,
+            src: src.into(),
+            dst: dst.into(),
+            tmp_gpr: regs::scratch().into(),
+            tmp_xmm: tmp.into(),
+            tmp_xmm2: regs::scratch_xmm().into(),
+        });
+    }
+
+    /// Convert a float of the given size to the other float size.
+    pub fn cvt_float_to_float(&mut self, src: Reg, dst: Reg, src_size: OperandSize) {
+        let op = match src_size {
+            OperandSize::S32 => SseOpcode::Cvtss2sd,
+            _ => SseOpcode::Cvtsd2ss,
+        };
+        self.emit(Inst::XmmUnaryRmR {
+            op,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+
+    /// Unconditional jump to a label.
+    pub fn jmp(&mut self, target: MachLabel) {
+        self.emit(Inst::JmpKnown { dst: target });
+    }
+
+    /// Conditional jump to a label.
+    pub fn jmp_if(&mut self, cc: CC, taken: MachLabel) {
+        self.emit(Inst::JmpIf { cc, taken });
+    }
+
+    /// Allocate a new label.
+    pub fn get_label(&mut self) -> MachLabel {
+        self.buffer.get_label()
+    }
+
+    /// Bind a label to the current offset.
+    pub fn bind(&mut self, label: MachLabel) {
+        self.buffer.bind_label(label);
+    }
+
+    /// Trap instruction.
+    pub fn trap(&mut self, code: TrapCode) {
+        self.emit(Inst::Ud2 { trap_code: code });
+    }
+
+    /// Conditional trap.
+    pub fn trap_if(&mut self, cc: CC, code: TrapCode) {
+        self.emit(Inst::TrapIf {
+            cc,
+            trap_code: code,
+        });
+    }
+
+    /// Function call.
+    pub fn call(&mut self, callee: CalleeKind) {
+        let info = Box::new(CallInfo {
+            uses: smallvec![],
+            defs: smallvec![],
+            clobbers: Default::default(),
+            opcode: Opcode::Call,
+            callee_conv: CallConv::SystemV,
+        });
+        match callee {
+            CalleeKind::Direct(index) => {
+                let dest = ExternalName::user(UserExternalNameRef::new(index as usize));
+                self.emit(Inst::CallKnown { dest, info });
+            }
+            CalleeKind::LibCall(libcall) => {
+                // Library calls are resolved through absolute
+                // relocations, hence the indirect call.
+                let scratch = regs::scratch();
+                self.emit(Inst::LoadExtName {
+                    dst: Writable::from_reg(scratch.into()),
+                    name: Box::new(ExternalName::LibCall(libcall)),
+                    offset: 0,
+                });
+                self.emit(Inst::CallUnknown {
+                    dest: RegMem::reg(scratch.into()),
+                    info,
+                });
+            }
+            CalleeKind::Indirect(reg) => {
+                self.emit(Inst::CallUnknown {
+                    dest: RegMem::reg(reg.into()),
+                    info,
+                });
+            }
+        }
+    }
+}
+
+/// The scalar float move opcode for the given size.
+fn float_mov_opcode(size: OperandSize) -> SseOpcode {
+    match size {
+        OperandSize::S32 => SseOpcode::Movss,
+        _ => SseOpcode::Movsd,
+    }
 }
@@ -1,12 +1,29 @@
 use super::{
+    abi::X64ABI,
     address::Address,
     asm::{Assembler, Operand},
     regs::{self, rbp, rsp},
 };
+use crate::abi::ABI;
 use crate::isa::reg::Reg;
-use crate::masm::{DivKind, MacroAssembler as Masm, OperandSize, RegImm, RemKind};
-use crate::{abi::LocalSlot, codegen::CodeGenContext, stack::Val};
-use cranelift_codegen::{isa::x64::settings as x64_settings, settings, Final, MachBufferFinalized};
+use crate::masm::{
+    CalleeKind, CmpKind, DivKind, ExtendKind, FloatCmpKind, MacroAssembler as Masm, OperandSize,
+    RegImm, RemKind, RoundingMode, ShiftKind, TruncKind,
+};
+use crate::{
+    abi::LocalSlot,
+    codegen::{CodeGenContext, FnCall},
+    stack::Val,
+};
+use cranelift_codegen::{
+    ir::{LibCall, TrapCode},
+    isa::x64::{
+        args::{SseOpcode, UnaryRmROpcode, CC},
+        settings as x64_settings,
+    },
+    settings, Final, MachBufferFinalized, MachLabel,
+};
+use wasmparser::ValType;
 
 /// x64 MacroAssembler.
 pub(crate) struct MacroAssembler {
@@ -14,6 +31,8 @@ pub(crate) struct MacroAssembler {
     sp_offset: u32,
     /// Low level assembler.
     asm: Assembler,
+    /// ISA specific flags.
+    isa_flags: x64_settings::Flags,
 }
 
 // Conversions between generic masm arguments and x64 operands.
@@ -52,11 +71,19 @@ impl Masm for MacroAssembler {
     }
 
     fn push(&mut self, reg: Reg) -> u32 {
-        self.asm.push_r(reg);
-        // In x64 the push instruction takes either
-        // 2 or 8 bytes; in our case we're always
-        // assuming 8 bytes per push.
-        self.increment_sp(8);
+        if reg.is_int() {
+            self.asm.push_r(reg);
+            // In x64 the push instruction takes either
+            // 2 or 8 bytes; in our case we're always
+            // assuming 8 bytes per push.
+            self.increment_sp(8);
+        } else {
+            // There's no push instruction for floating point
+            // registers; reserve a slot and store the register.
+            self.reserve_stack(8);
+            let addr = Address::offset(rsp(), 0);
+            self.asm.mov(reg.into(), addr.into(), OperandSize::S64);
+        }
 
         self.sp_offset
     }
@@ -101,6 +128,10 @@ impl Masm for MacroAssembler {
         Address::offset(regs::rsp(), offset)
     }
 
+    fn address_at_reg(&self, reg: Reg, offset: u32) -> Self::Address {
+        Address::offset(reg, offset)
+    }
+
     fn store(&mut self, src: RegImm, dst: Address, size: OperandSize) {
         let src: Operand = src.into();
         let dst: Operand = dst.into();
@@ -108,13 +139,23 @@ impl Masm for MacroAssembler {
         self.asm.mov(src, dst, size);
     }
 
+    fn load_addr(&mut self, src: Address, dst: Reg, size: OperandSize) {
+        self.asm.lea(src, dst, size);
+    }
+
     fn pop(&mut self, dst: Reg) {
-        self.asm.pop_r(dst);
-        // Similar to the comment in `push`, we assume 8 bytes per pop.
-        self.decrement_sp(8);
+        if dst.is_int() {
+            self.asm.pop_r(dst);
+            // Similar to the comment in `push`, we assume 8 bytes per pop.
+            self.decrement_sp(8);
+        } else {
+            let addr = Address::offset(rsp(), 0);
+            self.asm.mov(addr.into(), dst.into(), OperandSize::S64);
+            self.free_stack(8);
+        }
     }
 
-    fn call(&mut self, callee: u32) {
+    fn call(&mut self, callee: CalleeKind) {
         self.asm.call(callee);
     }
 
@@ -124,6 +165,12 @@ impl Masm for MacroAssembler {
         self.asm.mov(src, dst, size);
     }
 
+    fn load_sext(&mut self, src: Address, dst: Reg, size: OperandSize) {
+        match src {
+            Address::Offset { base, offset } => self.asm.movsx_mr(base, offset, dst, size),
+        }
+    }
+
     fn sp_offset(&mut self) -> u32 {
         self.sp_offset
     }
@@ -178,48 +225,471 @@ impl Masm for MacroAssembler {
         self.asm.mul(src, dst, size);
     }
 
+    fn checked_uadd(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize, trap: TrapCode) {
+        if dst != lhs {
+            self.asm.mov_rr(lhs, dst, size);
+        }
+        self.asm.add(rhs.into(), dst.into(), size);
+        // The carry flag is set on unsigned overflow.
+        self.asm.trap_if(CC::B, trap);
+    }
+
+    fn and(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        let (src, dst): (Operand, Operand) = if dst == lhs {
+            (rhs.into(), dst.into())
+        } else {
+            panic!(
+                "the destination and first source argument must be the same, dst={:?}, lhs={:?}",
+                dst, lhs
+            );
+        };
+
+        self.asm.and(src, dst, size);
+    }
+
+    fn or(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        let (src, dst): (Operand, Operand) = if dst == lhs {
+            (rhs.into(), dst.into())
+        } else {
+            panic!(
+                "the destination and first source argument must be the same, dst={:?}, lhs={:?}",
+                dst, lhs
+            );
+        };
+
+        self.asm.or(src, dst, size);
+    }
+
+    fn xor(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize) {
+        let (src, dst): (Operand, Operand) = if dst == lhs {
+            (rhs.into(), dst.into())
+        } else {
+            panic!(
+                "the destination and first source argument must be the same, dst={:?}, lhs={:?}",
+                dst, lhs
+            );
+        };
+
+        self.asm.xor(src, dst, size);
+    }
+
+    fn shift(&mut self, context: &mut CodeGenContext, kind: ShiftKind, size: OperandSize) {
+        let top = context.stack.peek().expect("value at stack top");
+
+        if top.is_i32_const() || top.is_i64_const() {
+            // The hardware masks the shift amount to the operand width,
+            // which matches WebAssembly's semantics.
+            let amount = match context.stack.pop().expect("value at stack top") {
+                Val::I32(v) => v as u8,
+                Val::I64(v) => v as u8,
+                _ => unreachable!(),
+            };
+            let dst = context.pop_to_reg(self, None);
+            self.asm.shift_ir(amount, dst.reg, kind, size);
+            context.stack.push(dst.into());
+        } else {
+            // The shift amount must be in `cl`.
+            let rcx = context.pop_to_reg(self, Some(regs::rcx()));
+            let dst = context.pop_to_reg(self, None);
+            self.asm.shift_rr(dst.reg, kind, size);
+            context.free_reg(rcx);
+            context.stack.push(dst.into());
+        }
+    }
+
+    fn clz(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        if self.isa_flags.use_lzcnt() {
+            self.asm.unary_rr(UnaryRmROpcode::Lzcnt, src, dst, size);
+        } else {
+            // `bsr` computes the index of the most significant set
+            // bit, and leaves the destination undefined if the source
+            // is zero. Map a zero source to -1 so that the result of
+            // `bits - 1 - index` is `bits`.
+            let scratch = regs::scratch();
+            self.asm.mov_ir(u64::MAX, scratch, size);
+            self.asm.unary_rr(UnaryRmROpcode::Bsr, src, dst, size);
+            self.asm.cmov(scratch, dst, CC::Z, size);
+            self.asm.neg(dst, size);
+            self.asm.add_ir(size.num_bits() as i32 - 1, dst, size);
+        }
+    }
+
+    fn ctz(&mut self, src: Reg, dst: Reg, size: OperandSize) {
+        if self.isa_flags.use_bmi1() {
+            self.asm.unary_rr(UnaryRmROpcode::Tzcnt, src, dst, size);
+        } else {
+            // `bsf` leaves the destination undefined if the source is
+            // zero, in which case the result is the operand width.
+            let scratch = regs::scratch();
+            self.asm.mov_ir(size.num_bits().into(), scratch, size);
+            self.asm.unary_rr(UnaryRmROpcode::Bsf, src, dst, size);
+            self.asm.cmov(scratch, dst, CC::Z, size);
+        }
+    }
+
+    fn popcnt(&mut self, context: &mut CodeGenContext, size: OperandSize) {
+        let ty = int_type(size);
+        if self.isa_flags.use_popcnt() {
+            context.unop(self, ty, &mut |masm, reg| {
+                masm.asm.unary_rr(UnaryRmROpcode::Popcnt, reg, reg, size)
+            });
+            return;
+        }
+
+        // Fall back to counting the bits in parallel; see
+        // https://en.wikipedia.org/wiki/Hamming_weight.
+        let (m1, m2, m4, h01) = match size {
+            OperandSize::S32 => (0x55555555, 0x33333333, 0x0f0f0f0f, 0x01010101),
+            _ => (
+                0x5555555555555555,
+                0x3333333333333333,
+                0x0f0f0f0f0f0f0f0f,
+                0x0101010101010101,
+            ),
+        };
+        let dst = context.pop_to_reg(self, None).reg;
+        let tmp = context.any_gpr(self);
+
+        // x -= (x >> 1) & m1
+        self.asm.mov_rr(dst, tmp, size);
+        self.asm.shift_ir(1, tmp, ShiftKind::ShrU, size);
+        self.asm.and(Operand::Imm(m1), tmp.into(), size);
+        self.asm.sub_rr(tmp, dst, size);
+
+        // x = (x & m2) + ((x >> 2) & m2)
+        self.asm.mov_rr(dst, tmp, size);
+        self.asm.shift_ir(2, tmp, ShiftKind::ShrU, size);
+        self.asm.and(Operand::Imm(m2), tmp.into(), size);
+        self.asm.and(Operand::Imm(m2), dst.into(), size);
+        self.asm.add_rr(tmp, dst, size);
+
+        // x = (x + (x >> 4)) & m4
+        self.asm.mov_rr(dst, tmp, size);
+        self.asm.shift_ir(4, tmp, ShiftKind::ShrU, size);
+        self.asm.add_rr(tmp, dst, size);
+        self.asm.and(Operand::Imm(m4), dst.into(), size);
+
+        // x = (x * h01) >> (bits - 8)
+        self.asm.mul(Operand::Imm(h01), dst.into(), size);
+        self.asm
+            .shift_ir(size.num_bits() as u8 - 8, dst, ShiftKind::ShrU, size);
+
+        context.free_reg(tmp);
+        context.stack.push(Val::reg(dst, ty));
+    }
+
+    fn cmp_with_set(&mut self, src: RegImm, dst: RegImm, kind: CmpKind, size: OperandSize) {
+        let dst = match dst {
+            RegImm::Reg(reg) => reg,
+            RegImm::Imm(_) => panic!("the destination of a comparison must be a register"),
+        };
+        self.asm.cmp(src.into(), dst, size);
+        self.asm.setcc(kind.into(), dst);
+    }
+
+    fn float_cmp_with_set(
+        &mut self,
+        lhs: Reg,
+        rhs: Reg,
+        dst: Reg,
+        kind: FloatCmpKind,
+        size: OperandSize,
+    ) {
+        // An unordered comparison sets the zero, parity and carry
+        // flags; the condition codes are chosen so that comparisons
+        // involving NaN are false, except for `ne`.
+        match kind {
+            FloatCmpKind::Gt => {
+                self.asm.ucomis(rhs, lhs, size);
+                self.asm.setcc(CC::NBE, dst);
+            }
+            FloatCmpKind::Ge => {
+                self.asm.ucomis(rhs, lhs, size);
+                self.asm.setcc(CC::NB, dst);
+            }
+            FloatCmpKind::Lt => {
+                self.asm.ucomis(lhs, rhs, size);
+                self.asm.setcc(CC::NBE, dst);
+            }
+            FloatCmpKind::Le => {
+                self.asm.ucomis(lhs, rhs, size);
+                self.asm.setcc(CC::NB, dst);
+            }
+            FloatCmpKind::Eq => {
+                let scratch = regs::scratch();
+                self.asm.ucomis(rhs, lhs, size);
+                self.asm.setcc(CC::Z, dst);
+                self.asm.setcc(CC::NP, scratch);
+                self.asm.and(scratch.into(), dst.into(), OperandSize::S32);
+            }
+            FloatCmpKind::Ne => {
+                let scratch = regs::scratch();
+                self.asm.ucomis(rhs, lhs, size);
+                self.asm.setcc(CC::NZ, dst);
+                self.asm.setcc(CC::P, scratch);
+                self.asm.or(scratch.into(), dst.into(), OperandSize::S32);
+            }
+        }
+    }
+
+    fn cmov_if_zero(&mut self, cond: Reg, src: Reg, dst: Reg, size: OperandSize) {
+        self.asm.cmp(Operand::Imm(0), cond, OperandSize::S32);
+        if dst.is_float() {
+            self.asm.xmm_cmov(src, dst, CC::Z, size);
+        } else {
+            self.asm.cmov(src, dst, CC::Z, size);
+        }
+    }
+
+    fn extend(&mut self, src: Reg, dst: Reg, kind: ExtendKind) {
+        self.asm.extend_rr(src, dst, kind);
+    }
+
+    fn float_add(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.float_binop(dst, lhs, rhs, (SseOpcode::Addss, SseOpcode::Addsd), size);
+    }
+
+    fn float_sub(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.float_binop(dst, lhs, rhs, (SseOpcode::Subss, SseOpcode::Subsd), size);
+    }
+
+    fn float_mul(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.float_binop(dst, lhs, rhs, (SseOpcode::Mulss, SseOpcode::Mulsd), size);
+    }
+
+    fn float_div(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        self.float_binop(dst, lhs, rhs, (SseOpcode::Divss, SseOpcode::Divsd), size);
+    }
+
+    fn float_min(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        assert_eq!(
+            dst, lhs,
+            "the destination and first source must be the same"
+        );
+        // The minimum is commutative, including the handling of NaN
+        // and signed zeros, so the operands can be swapped to satisfy
+        // the constraints of the instruction sequence.
+        self.asm.xmm_min_max(rhs, dst, true, size);
+    }
+
+    fn float_max(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        assert_eq!(
+            dst, lhs,
+            "the destination and first source must be the same"
+        );
+        self.asm.xmm_min_max(rhs, dst, false, size);
+    }
+
+    fn float_copysign(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize) {
+        assert_eq!(
+            dst, lhs,
+            "the destination and first source must be the same"
+        );
+        let sign_mask = sign_mask(size);
+        let mask = regs::scratch_xmm();
+        // Clear the sign of the destination.
+        self.load_float_mask(!sign_mask, mask, size);
+        self.asm.xmm_rm_r(SseOpcode::Andps, mask, dst);
+        // Extract the sign of the source and merge it in.
+        self.load_float_mask(sign_mask, mask, size);
+        self.asm.xmm_rm_r(SseOpcode::Andps, rhs, mask);
+        self.asm.xmm_rm_r(SseOpcode::Orps, mask, dst);
+    }
+
+    fn float_neg(&mut self, dst: Reg, size: OperandSize) {
+        let mask = regs::scratch_xmm();
+        self.load_float_mask(sign_mask(size), mask, size);
+        self.asm.xmm_rm_r(SseOpcode::Xorps, mask, dst);
+    }
+
+    fn float_abs(&mut self, dst: Reg, size: OperandSize) {
+        let mask = regs::scratch_xmm();
+        self.load_float_mask(!sign_mask(size), mask, size);
+        self.asm.xmm_rm_r(SseOpcode::Andps, mask, dst);
+    }
+
+    fn float_sqrt(&mut self, dst: Reg, src: Reg, size: OperandSize) {
+        self.asm.sqrt(src, dst, size);
+    }
+
+    fn float_round(&mut self, mode: RoundingMode, context: &mut CodeGenContext, size: OperandSize) {
+        let ty = float_type(size);
+        if self.isa_flags.use_sse41() {
+            context.unop(self, ty, &mut |masm, reg| {
+                masm.asm.round(mode, reg, reg, size)
+            });
+            return;
+        }
+
+        let libcall = match (mode, size) {
+            (RoundingMode::Nearest, OperandSize::S32) => LibCall::NearestF32,
+            (RoundingMode::Nearest, _) => LibCall::NearestF64,
+            (RoundingMode::Down, OperandSize::S32) => LibCall::FloorF32,
+            (RoundingMode::Down, _) => LibCall::FloorF64,
+            (RoundingMode::Up, OperandSize::S32) => LibCall::CeilF32,
+            (RoundingMode::Up, _) => LibCall::CeilF64,
+            (RoundingMode::Zero, OperandSize::S32) => LibCall::TruncF32,
+            (RoundingMode::Zero, _) => LibCall::TruncF64,
+        };
+        let abi = X64ABI::default();
+        let sig = abi
+            .sig_from(&[ty], &[ty])
+            .expect("valid library call signature");
+        let fncall = FnCall::new(&abi, &sig, context, self);
+        fncall.emit::<Self, X64ABI>(self, context, CalleeKind::LibCall(libcall));
+    }
+
+    fn signed_convert(&mut self, src: Reg, dst: Reg, src_size: OperandSize, dst_size: OperandSize) {
+        self.asm.cvt_sint_to_float(src, dst, src_size, dst_size);
+    }
+
+    fn unsigned_convert(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    ) {
+        match src_size {
+            // A zero extended 32-bit value is always representable as
+            // a signed 64-bit value.
+            OperandSize::S32 => {
+                self.asm.mov_rr(src, tmp, OperandSize::S32);
+                self.asm
+                    .cvt_sint_to_float(tmp, dst, OperandSize::S64, dst_size);
+            }
+            _ => self.asm.cvt_uint64_to_float(src, dst, tmp, dst_size),
+        }
+    }
+
+    fn signed_truncate(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    ) {
+        self.asm
+            .cvt_float_to_sint(src, dst, src_size, dst_size, kind == TruncKind::Saturating);
+    }
+
+    fn unsigned_truncate(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    ) {
+        self.asm.cvt_float_to_uint(
+            src,
+            dst,
+            tmp,
+            src_size,
+            dst_size,
+            kind == TruncKind::Saturating,
+        );
+    }
+
+    fn promote(&mut self, src: Reg, dst: Reg) {
+        self.asm.cvt_float_to_float(src, dst, OperandSize::S32);
+    }
+
+    fn demote(&mut self, src: Reg, dst: Reg) {
+        self.asm.cvt_float_to_float(src, dst, OperandSize::S64);
+    }
+
+    fn branch(
+        &mut self,
+        kind: CmpKind,
+        lhs: Reg,
+        rhs: RegImm,
+        taken: MachLabel,
+        size: OperandSize,
+    ) {
+        self.asm.cmp(rhs.into(), lhs, size);
+        self.asm.jmp_if(kind.into(), taken);
+    }
+
+    fn jmp(&mut self, target: MachLabel) {
+        self.asm.jmp(target);
+    }
+
+    fn get_label(&mut self) -> MachLabel {
+        self.asm.get_label()
+    }
+
+    fn bind(&mut self, label: MachLabel) {
+        self.asm.bind(label);
+    }
+
+    fn unreachable(&mut self) {
+        self.asm.trap(TrapCode::UnreachableCodeReached);
+    }
+
+    fn trap(&mut self, code: TrapCode) {
+        self.asm.trap(code);
+    }
+
+    fn trap_if(&mut self, kind: CmpKind, lhs: Reg, rhs: RegImm, size: OperandSize, code: TrapCode) {
+        self.asm.cmp(rhs.into(), lhs, size);
+        self.asm.trap_if(kind.into(), code);
+    }
+
+    fn check_stack(&mut self, limit: Reg) {
+        self.asm.cmp(limit.into(), rsp(), OperandSize::S64);
+        self.asm.trap_if(CC::B, TrapCode::StackOverflow);
+    }
+
+    fn reset_stack_pointer(&mut self, offset: u32) {
+        self.sp_offset = offset;
+    }
+
     fn div(&mut self, context: &mut CodeGenContext, kind: DivKind, size: OperandSize) {
         // Allocate rdx:rax.
-        let rdx = context.gpr(regs::rdx(), self);
-        let rax = context.gpr(regs::rax(), self);
+        let rdx = context.reg(regs::rdx(), self);
+        let rax = context.reg(regs::rax(), self);
 
         // Allocate the divisor, which can be any gpr.
-        let divisor = context.pop_to_reg(self, None, size);
+        let divisor = context.pop_to_reg(self, None);
 
         // Mark rax as allocatable.
-        context.regalloc.free_gpr(rax);
+        context.free_reg(rax);
         // Move the top value to rax.
-        let rax = context.pop_to_reg(self, Some(rax), size);
-        self.asm.div(divisor, (rax, rdx), kind, size);
+        let rax = context.pop_to_reg(self, Some(rax));
+        self.asm.div(divisor.reg, (rax.reg, rdx), kind, size);
 
         // Free the divisor and rdx.
-        context.free_gpr(divisor);
-        context.free_gpr(rdx);
+        context.free_reg(divisor);
+        context.free_reg(rdx);
 
         // Push the quotient.
-        context.stack.push(Val::reg(rax));
+        context.stack.push(rax.into());
     }
 
     fn rem(&mut self, context: &mut CodeGenContext, kind: RemKind, size: OperandSize) {
         // Allocate rdx:rax.
-        let rdx = context.gpr(regs::rdx(), self);
-        let rax = context.gpr(regs::rax(), self);
+        let rdx = context.reg(regs::rdx(), self);
+        let rax = context.reg(regs::rax(), self);
 
         // Allocate the divisor, which can be any gpr.
-        let divisor = context.pop_to_reg(self, None, size);
+        let divisor = context.pop_to_reg(self, None);
 
         // Mark rax as allocatable.
-        context.regalloc.free_gpr(rax);
+        context.free_reg(rax);
         // Move the top value to rax.
-        let rax = context.pop_to_reg(self, Some(rax), size);
-        self.asm.rem(divisor, (rax, rdx), kind, size);
+        let rax = context.pop_to_reg(self, Some(rax));
+        self.asm.rem(divisor.reg, (rax.reg, rdx), kind, size);
 
         // Free the divisor and rax.
-        context.free_gpr(divisor);
-        context.free_gpr(rax);
+        context.free_reg(divisor);
+        context.free_reg(rax);
 
         // Push the remainder.
-        context.stack.push(Val::reg(rdx));
+        context.stack.push(Val::reg(rdx, rax.ty));
     }
 
     fn epilogue(&mut self, locals_size: u32) {
@@ -236,6 +706,14 @@ impl Masm for MacroAssembler {
     fn finalize(self) -> MachBufferFinalized<Final> {
         self.asm.finalize()
     }
+
+    fn start_source_loc(&mut self, offset: u32) {
+        self.asm.start_source_loc(offset);
+    }
+
+    fn end_source_loc(&mut self) {
+        self.asm.end_source_loc();
+    }
 }
 
 impl MacroAssembler {
@@ -243,7 +721,8 @@ impl MacroAssembler {
     pub fn new(shared_flags: settings::Flags, isa_flags: x64_settings::Flags) -> Self {
         Self {
             sp_offset: 0,
-            asm: Assembler::new(shared_flags, isa_flags),
+            asm: Assembler::new(shared_flags, isa_flags.clone()),
+            isa_flags,
         }
     }
 
@@ -260,4 +739,55 @@ impl MacroAssembler {
         );
         self.sp_offset -= bytes;
     }
+
+    /// Emit a two-operand scalar floating point operation, picking
+    /// the single or double precision opcode according to the size.
+    fn float_binop(
+        &mut self,
+        dst: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        (single, double): (SseOpcode, SseOpcode),
+        size: OperandSize,
+    ) {
+        assert_eq!(
+            dst, lhs,
+            "the destination and first source must be the same"
+        );
+        let op = match size {
+            OperandSize::S32 => single,
+            _ => double,
+        };
+        self.asm.xmm_rm_r(op, rhs, dst);
+    }
+
+    /// Load a bit mask into a floating point register, through the
+    /// integer scratch register.
+    fn load_float_mask(&mut self, mask: u64, dst: Reg, size: OperandSize) {
+        let scratch = regs::scratch();
+        self.asm.mov_ir(mask, scratch, size);
+        self.asm.gpr_to_xmm(scratch, dst, size);
+    }
+}
+
+/// The mask of the sign bit of a floating point value of the given size.
+fn sign_mask(size: OperandSize) -> u64 {
+    match size {
+        OperandSize::S32 => 0x8000_0000,
+        _ => 0x8000_0000_0000_0000,
+    }
+}
+
+fn int_type(size: OperandSize) -> ValType {
+    match size {
+        OperandSize::S32 => ValType::I32,
+        _ => ValType::I64,
+    }
+}
+
+fn float_type(size: OperandSize) -> ValType {
+    match size {
+        OperandSize::S32 => ValType::F32,
+        _ => ValType::F64,
+    }
 }
@@ -17,7 +17,7 @@ use cranelift_codegen::{MachTextSectionBuilder, TextSectionBuilder};
 use target_lexicon::Triple;
 use wasmparser::{FuncType, FuncValidator, FunctionBody, ValidatorResources};
 
-use self::regs::ALL_GPR;
+use self::regs::{ALL_FPR, ALL_GPR};
 
 mod abi;
 mod address;
@@ -93,10 +93,9 @@ impl TargetIsa for X64 {
         let mut masm = X64Masm::new(self.shared_flags.clone(), self.isa_flags.clone());
         let stack = Stack::new();
         let abi = abi::X64ABI::default();
-        let abi_sig = abi.sig(sig);
+        let abi_sig = abi.sig(sig)?;
         let frame = Frame::new(&abi_sig, &mut body, &mut validator, &abi)?;
-        // TODO Add in floating point bitmask
-        let regalloc = RegAlloc::new(RegSet::new(ALL_GPR, 0), regs::scratch());
+        let regalloc = RegAlloc::new(RegSet::new(ALL_GPR, ALL_FPR), regs::scratch());
         let codegen_context = CodeGenContext::new(regalloc, stack, &frame);
         let mut codegen = CodeGen::new(&mut masm, &abi, codegen_context, env, abi_sig);
 
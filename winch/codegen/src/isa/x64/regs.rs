@@ -81,6 +81,11 @@ pub(crate) fn scratch() -> Reg {
     r11()
 }
 
+/// The floating point scratch register.
+pub(crate) fn scratch_xmm() -> Reg {
+    xmm15()
+}
+
 fn fpr(enc: u8) -> Reg {
     Reg::new(PReg::new(enc as usize, RegClass::Float))
 }
@@ -138,7 +143,19 @@ pub(crate) fn xmm15() -> Reg {
 
 const GPR: u32 = 16;
 const ALLOCATABLE_GPR: u32 = (1 << GPR) - 1;
-const NON_ALLOCATABLE_GPR: u32 = (1 << ENC_RBP) | (1 << ENC_RSP) | (1 << ENC_R11);
+// Callee saved registers in the system calling convention are never
+// allocated; see the default ABI documentation.
+const CALLEE_SAVED_GPR: u32 =
+    (1 << ENC_RBX) | (1 << ENC_R12) | (1 << ENC_R13) | (1 << ENC_R14) | (1 << ENC_R15);
+const NON_ALLOCATABLE_GPR: u32 =
+    (1 << ENC_RBP) | (1 << ENC_RSP) | (1 << ENC_R11) | CALLEE_SAVED_GPR;
 
 /// Bitmask to represent the available general purpose registers.
 pub(crate) const ALL_GPR: u32 = ALLOCATABLE_GPR & !NON_ALLOCATABLE_GPR;
+
+const FPR: u32 = 16;
+const ALLOCATABLE_FPR: u32 = (1 << FPR) - 1;
+const NON_ALLOCATABLE_FPR: u32 = 1 << 15;
+
+/// Bitmask to represent the available floating point registers.
+pub(crate) const ALL_FPR: u32 = ALLOCATABLE_FPR & !NON_ALLOCATABLE_FPR;
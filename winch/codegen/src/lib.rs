@@ -8,7 +8,10 @@
 
 mod abi;
 mod codegen;
-pub use codegen::{Callee, FuncEnv};
+pub use codegen::{
+    BuiltinFunction, Callee, DataLocation, FuncEnv, GlobalData, HeapData, ImportedFunctionData,
+    TableData, VMContextOffsets,
+};
 mod frame;
 pub mod isa;
 pub use isa::*;
@@ -2,7 +2,7 @@ use crate::abi::{align_to, LocalSlot};
 use crate::codegen::CodeGenContext;
 use crate::isa::reg::Reg;
 use crate::regalloc::RegAlloc;
-use cranelift_codegen::{Final, MachBufferFinalized};
+use cranelift_codegen::{ir::LibCall, ir::TrapCode, Final, MachBufferFinalized, MachLabel};
 use std::{fmt::Debug, ops::Range};
 
 #[derive(Eq, PartialEq)]
@@ -14,6 +14,7 @@ pub(crate) enum DivKind {
 }
 
 /// Remainder kind.
+#[derive(Eq, PartialEq)]
 pub(crate) enum RemKind {
     /// Signed remainder.
     Signed,
@@ -21,15 +22,146 @@ pub(crate) enum RemKind {
     Unsigned,
 }
 
+/// Shift kind.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ShiftKind {
+    /// Left shift.
+    Shl,
+    /// Signed right shift.
+    ShrS,
+    /// Unsigned right shift.
+    ShrU,
+    /// Left rotate.
+    Rotl,
+    /// Right rotate.
+    Rotr,
+}
+
+/// Integer comparison kind.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum CmpKind {
+    /// Equal.
+    Eq,
+    /// Not equal.
+    Ne,
+    /// Signed less than.
+    LtS,
+    /// Unsigned less than.
+    LtU,
+    /// Signed greater than.
+    GtS,
+    /// Unsigned greater than.
+    GtU,
+    /// Signed less than or equal.
+    LeS,
+    /// Unsigned less than or equal.
+    LeU,
+    /// Signed greater than or equal.
+    GeS,
+    /// Unsigned greater than or equal.
+    GeU,
+}
+
+/// Floating point comparison kind.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum FloatCmpKind {
+    /// Equal.
+    Eq,
+    /// Not equal.
+    Ne,
+    /// Less than.
+    Lt,
+    /// Greater than.
+    Gt,
+    /// Less than or equal.
+    Le,
+    /// Greater than or equal.
+    Ge,
+}
+
+/// Kinds of integer extension.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ExtendKind {
+    /// Sign extend a 32-bit value to 64 bits.
+    I64ExtendI32S,
+    /// Zero extend a 32-bit value to 64 bits.
+    I64ExtendI32U,
+    /// Sign extend the low 8 bits of a 32-bit value.
+    I32Extend8S,
+    /// Sign extend the low 16 bits of a 32-bit value.
+    I32Extend16S,
+    /// Sign extend the low 8 bits of a 64-bit value.
+    I64Extend8S,
+    /// Sign extend the low 16 bits of a 64-bit value.
+    I64Extend16S,
+}
+
+/// Floating point rounding modes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum RoundingMode {
+    /// Round to the nearest integer, ties to even.
+    Nearest,
+    /// Round towards negative infinity.
+    Down,
+    /// Round towards positive infinity.
+    Up,
+    /// Round towards zero.
+    Zero,
+}
+
+/// Kinds of float to integer truncation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum TruncKind {
+    /// Trap if the value is NaN or out of the range of the
+    /// destination type.
+    Checked,
+    /// Saturate to the range of the destination type, with NaN
+    /// converted to zero.
+    Saturating,
+}
+
 /// Operand size, in bits.
 #[derive(Copy, Debug, Clone, Eq, PartialEq)]
 pub(crate) enum OperandSize {
+    /// 8 bits.
+    S8,
+    /// 16 bits.
+    S16,
     /// 32 bits.
     S32,
     /// 64 bits.
     S64,
 }
 
+impl OperandSize {
+    /// The number of bits in the operand.
+    pub fn num_bits(&self) -> u32 {
+        match self {
+            OperandSize::S8 => 8,
+            OperandSize::S16 => 16,
+            OperandSize::S32 => 32,
+            OperandSize::S64 => 64,
+        }
+    }
+
+    /// The number of bytes in the operand.
+    pub fn bytes(&self) -> u32 {
+        self.num_bits() / 8
+    }
+}
+
+/// The target of a function call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum CalleeKind {
+    /// A locally defined function, by its index in the function
+    /// index space.
+    Direct(u32),
+    /// A function whose address is in the given register.
+    Indirect(Reg),
+    /// A runtime library function.
+    LibCall(LibCall),
+}
+
 /// An abstraction over a register or immediate.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(crate) enum RegImm {
@@ -76,7 +208,7 @@ impl From<Reg> for RegImm {
 
 pub(crate) trait MacroAssembler {
     /// The addressing mode.
-    type Address;
+    type Address: Copy;
 
     /// Emit the function prologue.
     fn prologue(&mut self);
@@ -102,22 +234,36 @@ pub(crate) trait MacroAssembler {
     /// current position of the stack pointer (e.g. [sp + offset].
     fn address_at_sp(&self, offset: u32) -> Self::Address;
 
-    /// Emit a function call to a locally defined function.
-    fn call(&mut self, callee: u32);
+    /// Constructs an address with an offset relative to the given
+    /// register (e.g. [reg + offset]).
+    fn address_at_reg(&self, reg: Reg, offset: u32) -> Self::Address;
+
+    /// Emit a function call.
+    fn call(&mut self, callee: CalleeKind);
 
     /// Get stack pointer offset.
     fn sp_offset(&mut self) -> u32;
 
-    /// Perform a stack store.
+    /// Perform a store. Integer sources are truncated to `size`.
     fn store(&mut self, src: RegImm, dst: Self::Address, size: OperandSize);
 
-    /// Perform a stack load.
+    /// Perform a load. Integer values narrower than 64 bits are zero
+    /// extended.
     fn load(&mut self, src: Self::Address, dst: Reg, size: OperandSize);
 
+    /// Perform an integer load, sign extending the loaded value to 64
+    /// bits.
+    fn load_sext(&mut self, src: Self::Address, dst: Reg, size: OperandSize);
+
+    /// Load the effective address of the given location into the given
+    /// register.
+    fn load_addr(&mut self, src: Self::Address, dst: Reg, size: OperandSize);
+
     /// Pop a value from the machine stack into the given register.
     fn pop(&mut self, dst: Reg);
 
-    /// Perform a move.
+    /// Perform a move. Moves between general purpose and floating
+    /// point registers preserve the bit pattern of the value.
     fn mov(&mut self, src: RegImm, dst: RegImm, size: OperandSize);
 
     /// Perform add operation.
@@ -145,12 +291,191 @@ pub(crate) trait MacroAssembler {
     /// Calculate remainder.
     fn rem(&mut self, context: &mut CodeGenContext, kind: RemKind, size: OperandSize);
 
+    /// Perform an unsigned addition of `lhs` and `rhs`, trapping with
+    /// the given trap code if the addition overflows.
+    fn checked_uadd(&mut self, dst: Reg, lhs: Reg, rhs: RegImm, size: OperandSize, trap: TrapCode);
+
+    /// Perform logical and operation.
+    fn and(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize);
+
+    /// Perform logical or operation.
+    fn or(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize);
+
+    /// Perform logical exclusive or operation.
+    fn xor(&mut self, dst: RegImm, lhs: RegImm, rhs: RegImm, size: OperandSize);
+
+    /// Perform a shift or rotate operation.
+    /// Similar to division, some architectures require the shift
+    /// amount to be in a specific register, so this function is given
+    /// access to the code generation context to pop its operands and
+    /// push the result.
+    fn shift(&mut self, context: &mut CodeGenContext, kind: ShiftKind, size: OperandSize);
+
+    /// Count the leading zeros of the value in `src` and put the
+    /// result in `dst`.
+    fn clz(&mut self, src: Reg, dst: Reg, size: OperandSize);
+
+    /// Count the trailing zeros of the value in `src` and put the
+    /// result in `dst`.
+    fn ctz(&mut self, src: Reg, dst: Reg, size: OperandSize);
+
+    /// Count the number of set bits of the value at the top of the
+    /// value stack. Similar to division, the code generation context
+    /// is given, so that implementations lacking a native instruction
+    /// can request temporary registers.
+    fn popcnt(&mut self, context: &mut CodeGenContext, size: OperandSize);
+
+    /// Compare `src` and `dst` and put the result of the comparison,
+    /// either 0 or 1, in `dst`.
+    fn cmp_with_set(&mut self, src: RegImm, dst: RegImm, kind: CmpKind, size: OperandSize);
+
+    /// Compare the floating point values in `lhs` and `rhs` and put
+    /// the result of the comparison, either 0 or 1, in `dst`.
+    fn float_cmp_with_set(
+        &mut self,
+        lhs: Reg,
+        rhs: Reg,
+        dst: Reg,
+        kind: FloatCmpKind,
+        size: OperandSize,
+    );
+
+    /// Conditionally move `src` to `dst` if the value in `cond` is zero.
+    fn cmov_if_zero(&mut self, cond: Reg, src: Reg, dst: Reg, size: OperandSize);
+
+    /// Extend the value in `src` and put the result in `dst`.
+    fn extend(&mut self, src: Reg, dst: Reg, kind: ExtendKind);
+
+    /// Perform a floating point addition.
+    fn float_add(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Perform a floating point subtraction.
+    fn float_sub(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Perform a floating point multiplication.
+    fn float_mul(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Perform a floating point division.
+    fn float_div(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Compute the WebAssembly minimum of two floating point values.
+    fn float_min(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Compute the WebAssembly maximum of two floating point values.
+    fn float_max(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Copy the sign of `rhs` to the magnitude of `lhs`.
+    fn float_copysign(&mut self, dst: Reg, lhs: Reg, rhs: Reg, size: OperandSize);
+
+    /// Negate the floating point value in `dst`.
+    fn float_neg(&mut self, dst: Reg, size: OperandSize);
+
+    /// Compute the absolute value of the floating point value in `dst`.
+    fn float_abs(&mut self, dst: Reg, size: OperandSize);
+
+    /// Compute the square root of the floating point value in `src`.
+    fn float_sqrt(&mut self, dst: Reg, src: Reg, size: OperandSize);
+
+    /// Round the floating point value at the top of the value stack
+    /// to an integral value. The code generation context is given so
+    /// that implementations lacking a native instruction can fall
+    /// back to a library call.
+    fn float_round(&mut self, mode: RoundingMode, context: &mut CodeGenContext, size: OperandSize);
+
+    /// Convert the signed integer in `src` to a floating point value
+    /// in `dst`.
+    fn signed_convert(&mut self, src: Reg, dst: Reg, src_size: OperandSize, dst_size: OperandSize);
+
+    /// Convert the unsigned integer in `src` to a floating point value
+    /// in `dst`, using `tmp` as a temporary general purpose register.
+    fn unsigned_convert(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+    );
+
+    /// Truncate the floating point value in `src` to a signed integer
+    /// in `dst`.
+    fn signed_truncate(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    );
+
+    /// Truncate the floating point value in `src` to an unsigned
+    /// integer in `dst`, using `tmp` as a temporary floating point
+    /// register.
+    fn unsigned_truncate(
+        &mut self,
+        src: Reg,
+        dst: Reg,
+        tmp: Reg,
+        src_size: OperandSize,
+        dst_size: OperandSize,
+        kind: TruncKind,
+    );
+
+    /// Convert the 32-bit floating point value in `src` to a 64-bit
+    /// floating point value in `dst`.
+    fn promote(&mut self, src: Reg, dst: Reg);
+
+    /// Convert the 64-bit floating point value in `src` to a 32-bit
+    /// floating point value in `dst`.
+    fn demote(&mut self, src: Reg, dst: Reg);
+
+    /// Compare `lhs` against `rhs` and branch to `taken` if the
+    /// comparison described by `kind` holds.
+    fn branch(&mut self, kind: CmpKind, lhs: Reg, rhs: RegImm, taken: MachLabel, size: OperandSize);
+
+    /// Emit an unconditional jump to the given label.
+    fn jmp(&mut self, target: MachLabel);
+
+    /// Allocate a new, unbound, label.
+    fn get_label(&mut self) -> MachLabel;
+
+    /// Bind the given label to the current code offset.
+    fn bind(&mut self, label: MachLabel);
+
+    /// Emit an unconditional trap.
+    fn unreachable(&mut self);
+
+    /// Emit an unconditional trap with the given trap code.
+    fn trap(&mut self, code: TrapCode);
+
+    /// Compare `lhs` against `rhs` and trap with the given trap code
+    /// if the comparison described by `kind` holds.
+    fn trap_if(&mut self, kind: CmpKind, lhs: Reg, rhs: RegImm, size: OperandSize, code: TrapCode);
+
+    /// Trap with a stack overflow if the stack pointer is below the
+    /// limit in the given register.
+    fn check_stack(&mut self, limit: Reg);
+
+    /// Reset the tracked stack pointer offset, without emitting any
+    /// code. This is used when the code following a branch is reached
+    /// through a control flow edge in which the stack pointer offset
+    /// differs from the one observed at the branch.
+    fn reset_stack_pointer(&mut self, offset: u32);
+
     /// Push the register to the stack, returning the offset.
     fn push(&mut self, src: Reg) -> u32;
 
     /// Finalize the assembly and return the result.
     fn finalize(self) -> MachBufferFinalized<Final>;
 
+    /// Mark the start of the code emitted for the WebAssembly operator
+    /// at the given offset in the module.
+    fn start_source_loc(&mut self, offset: u32);
+
+    /// Mark the end of the code emitted for the current WebAssembly
+    /// operator.
+    fn end_source_loc(&mut self);
+
     /// Zero a particular register.
     fn zero(&mut self, reg: Reg);
 
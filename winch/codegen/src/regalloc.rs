@@ -1,4 +1,5 @@
 use crate::{isa::reg::Reg, regset::RegSet};
+use wasmparser::ValType;
 
 /// The register allocator.
 ///
@@ -35,27 +36,50 @@ impl RegAlloc {
         })
     }
 
-    /// Checks if a general purpose register is avaiable.
-    pub fn gpr_available(&self, reg: Reg) -> bool {
-        self.regset.named_gpr_available(reg.hw_enc() as u32)
+    /// Allocate the next available floating point register,
+    /// spilling if none available.
+    pub fn any_fpr<F>(&mut self, spill: &mut F) -> Reg
+    where
+        F: FnMut(&mut RegAlloc),
+    {
+        self.regset.any_fpr().unwrap_or_else(|| {
+            spill(self);
+            self.regset.any_fpr().expect("any fpr to be available")
+        })
+    }
+
+    /// Allocate the next available register of the class suitable
+    /// to hold a value of the given type, spilling if none available.
+    pub fn reg_for_type<F>(&mut self, ty: ValType, spill: &mut F) -> Reg
+    where
+        F: FnMut(&mut RegAlloc),
+    {
+        match ty {
+            ValType::F32 | ValType::F64 => self.any_fpr(spill),
+            _ => self.any_gpr(spill),
+        }
+    }
+
+    /// Checks if a register is avaiable.
+    pub fn reg_available(&self, reg: Reg) -> bool {
+        self.regset.named_reg_available(reg)
     }
 
-    /// Request a specific general purpose register,
-    /// spilling if not available.
-    pub fn gpr<F>(&mut self, named: Reg, spill: &mut F) -> Reg
+    /// Request a specific register, spilling if not available.
+    pub fn reg<F>(&mut self, named: Reg, spill: &mut F) -> Reg
     where
         F: FnMut(&mut RegAlloc),
     {
-        self.regset.gpr(named).unwrap_or_else(|| {
+        self.regset.reg(named).unwrap_or_else(|| {
             spill(self);
             self.regset
-                .gpr(named)
-                .expect(&format!("gpr {:?} to be available", named))
+                .reg(named)
+                .expect(&format!("reg {:?} to be available", named))
         })
     }
 
-    /// Mark a particular general purpose register as available.
-    pub fn free_gpr(&mut self, reg: Reg) {
-        self.regset.free_gpr(reg);
+    /// Mark a particular register as available.
+    pub fn free(&mut self, reg: Reg) {
+        self.regset.free(reg);
     }
 }
@@ -1,56 +1,80 @@
 use crate::isa::reg::Reg;
+use regalloc2::RegClass;
 
 /// A bit set to track regiter availability.
 pub(crate) struct RegSet {
     /// Bitset to track general purpose register availability.
     gpr: u32,
     /// Bitset to track floating-point register availability.
-    _fpr: u32,
+    fpr: u32,
 }
 
 impl RegSet {
     /// Create a new register set.
     pub fn new(gpr: u32, fpr: u32) -> Self {
-        Self { gpr, _fpr: fpr }
+        Self { gpr, fpr }
     }
 
     /// Request a general purpose register.
     pub fn any_gpr(&mut self) -> Option<Reg> {
         self.gpr_available().then(|| {
             let index = self.gpr.trailing_zeros();
-            self.allocate(index);
+            Self::allocate(&mut self.gpr, index);
             Reg::int(index as usize)
         })
     }
 
-    /// Request a specific general purpose register.
-    pub fn gpr(&mut self, reg: Reg) -> Option<Reg> {
-        let index = reg.hw_enc();
-        self.named_gpr_available(index as u32).then(|| {
-            self.allocate(index as u32);
-            Reg::int(index as usize)
+    /// Request a floating point register.
+    pub fn any_fpr(&mut self) -> Option<Reg> {
+        self.fpr_available().then(|| {
+            let index = self.fpr.trailing_zeros();
+            Self::allocate(&mut self.fpr, index);
+            Reg::float(index as usize)
+        })
+    }
+
+    /// Request a specific register.
+    pub fn reg(&mut self, reg: Reg) -> Option<Reg> {
+        let index = reg.hw_enc() as u32;
+        self.named_reg_available(reg).then(|| {
+            Self::allocate(self.bitset_mut(reg), index);
+            reg
         })
     }
 
-    /// Free the given general purpose register.
-    pub fn free_gpr(&mut self, reg: Reg) {
+    /// Free the given register.
+    pub fn free(&mut self, reg: Reg) {
         let index = reg.hw_enc() as u32;
-        self.gpr |= 1 << index;
+        *self.bitset_mut(reg) |= 1 << index;
     }
 
-    /// Returns true if the given general purpose register
-    /// is available.
-    pub fn named_gpr_available(&self, index: u32) -> bool {
-        let index = 1 << index;
-        (!self.gpr & index) == 0
+    /// Returns true if the given register is available.
+    pub fn named_reg_available(&self, reg: Reg) -> bool {
+        let bitset = match reg.class() {
+            RegClass::Int => self.gpr,
+            RegClass::Float => self.fpr,
+        };
+        let index = 1 << reg.hw_enc();
+        (!bitset & index) == 0
     }
 
     fn gpr_available(&self) -> bool {
         self.gpr != 0
     }
 
-    fn allocate(&mut self, index: u32) {
-        self.gpr &= !(1 << index);
+    fn fpr_available(&self) -> bool {
+        self.fpr != 0
+    }
+
+    fn bitset_mut(&mut self, reg: Reg) -> &mut u32 {
+        match reg.class() {
+            RegClass::Int => &mut self.gpr,
+            RegClass::Float => &mut self.fpr,
+        }
+    }
+
+    fn allocate(bitset: &mut u32, index: u32) {
+        *bitset &= !(1 << index);
     }
 }
 
@@ -72,19 +96,41 @@ mod tests {
         assert!(set.any_gpr().is_none())
     }
 
+    #[test]
+    fn test_any_fpr() {
+        let mut set = RegSet::new(0, UNIVERSE);
+        for _ in 0..16 {
+            let fpr = set.any_fpr();
+            assert!(fpr.is_some_and(|r| r.is_float()));
+        }
+
+        assert!(!set.fpr_available());
+        assert!(set.any_fpr().is_none());
+        assert!(set.any_gpr().is_none());
+    }
+
     #[test]
     fn test_gpr() {
         let all = UNIVERSE & !(1 << 5);
         let target = Reg::int(5);
         let mut set = RegSet::new(all, 0);
-        assert!(set.gpr(target).is_none());
+        assert!(set.reg(target).is_none());
     }
 
     #[test]
     fn test_free_gpr() {
         let mut set = RegSet::new(UNIVERSE, 0);
         let gpr = set.any_gpr().unwrap();
-        set.free_gpr(gpr);
-        assert!(set.gpr(gpr).is_some());
+        set.free(gpr);
+        assert!(set.reg(gpr).is_some());
+    }
+
+    #[test]
+    fn test_free_fpr() {
+        let mut set = RegSet::new(UNIVERSE, UNIVERSE);
+        let fpr = set.any_fpr().unwrap();
+        assert!(set.reg(Reg::int(fpr.hw_enc() as usize)).is_some());
+        set.free(fpr);
+        assert!(set.reg(fpr).is_some());
     }
 }
@@ -1,5 +1,34 @@
 use crate::isa::reg::Reg;
 use std::collections::VecDeque;
+use wasmparser::ValType;
+
+/// A register, along with the type of the value it holds.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct TypedReg {
+    /// The type of the value.
+    pub ty: ValType,
+    /// The register holding the value.
+    pub reg: Reg,
+}
+
+impl TypedReg {
+    /// Create a new typed register.
+    pub fn new(ty: ValType, reg: Reg) -> Self {
+        Self { ty, reg }
+    }
+}
+
+impl From<TypedReg> for Reg {
+    fn from(tr: TypedReg) -> Self {
+        tr.reg
+    }
+}
+
+impl From<TypedReg> for Val {
+    fn from(tr: TypedReg) -> Self {
+        Val::Reg(tr)
+    }
+}
 
 /// Value definition to be used within the shadow stack.
 #[derive(Debug, Eq, PartialEq)]
@@ -8,12 +37,26 @@ pub(crate) enum Val {
     I32(i32),
     /// I64 Constant.
     I64(i64),
+    /// F32 Constant, as its bit pattern.
+    F32(u32),
+    /// F64 Constant, as its bit pattern.
+    F64(u64),
     /// A register.
-    Reg(Reg),
+    Reg(TypedReg),
     /// A local slot.
-    Local(u32),
+    Local {
+        /// The index of the local.
+        index: u32,
+        /// The type of the local.
+        ty: ValType,
+    },
     /// Offset to a memory location.
-    Memory(u32),
+    Memory {
+        /// The type of the value.
+        ty: ValType,
+        /// The stack pointer offset of the value.
+        offset: u32,
+    },
 }
 
 impl Val {
@@ -27,14 +70,41 @@ impl Val {
         Self::I64(v)
     }
 
+    /// Create a new F32 constant value from its bit pattern.
+    pub fn f32(bits: u32) -> Self {
+        Self::F32(bits)
+    }
+
+    /// Create a new F64 constant value from its bit pattern.
+    pub fn f64(bits: u64) -> Self {
+        Self::F64(bits)
+    }
+
     /// Create a new Reg value.
-    pub fn reg(r: Reg) -> Self {
-        Self::Reg(r)
+    pub fn reg(reg: Reg, ty: ValType) -> Self {
+        Self::Reg(TypedReg::new(ty, reg))
     }
 
     /// Create a new Local value.
-    pub fn local(index: u32) -> Self {
-        Self::Local(index)
+    pub fn local(index: u32, ty: ValType) -> Self {
+        Self::Local { index, ty }
+    }
+
+    /// Create a new Memory value.
+    pub fn mem(ty: ValType, offset: u32) -> Self {
+        Self::Memory { ty, offset }
+    }
+
+    /// Get the type of the value.
+    pub fn ty(&self) -> ValType {
+        match *self {
+            Self::I32(_) => ValType::I32,
+            Self::I64(_) => ValType::I64,
+            Self::F32(_) => ValType::F32,
+            Self::F64(_) => ValType::F64,
+            Self::Reg(r) => r.ty,
+            Self::Local { ty, .. } | Self::Memory { ty, .. } => ty,
+        }
     }
 
     /// Check whether the value is a register.
@@ -48,7 +118,7 @@ impl Val {
     /// Check wheter the value is a memory offset.
     pub fn is_mem(&self) -> bool {
         match *self {
-            Self::Memory(_) => true,
+            Self::Memory { .. } => true,
             _ => false,
         }
     }
@@ -57,7 +127,7 @@ impl Val {
     ///
     /// # Panics
     /// This method will panic if the value is not a register.
-    pub fn get_reg(&self) -> Reg {
+    pub fn get_reg(&self) -> TypedReg {
         match self {
             Self::Reg(r) => *r,
             v => panic!("expected value {:?} to be a register", v),
@@ -167,7 +237,7 @@ impl Stack {
 
     /// Pops the element at the top of the stack if it is a register;
     /// returns `None` otherwise.
-    pub fn pop_reg(&mut self) -> Option<Reg> {
+    pub fn pop_reg(&mut self) -> Option<TypedReg> {
         match self.peek() {
             Some(v) => v.is_reg().then(|| self.pop().unwrap().get_reg()),
             _ => None,
@@ -176,9 +246,11 @@ impl Stack {
 
     /// Pops the given register if it is at the top of the stack;
     /// returns `None` otherwise.
-    pub fn pop_named_reg(&mut self, reg: Reg) -> Option<Reg> {
+    pub fn pop_named_reg(&mut self, reg: Reg) -> Option<TypedReg> {
         match self.peek() {
-            Some(v) => (v.is_reg() && v.get_reg() == reg).then(|| self.pop().unwrap().get_reg()),
+            Some(v) => {
+                (v.is_reg() && v.get_reg().reg == reg).then(|| self.pop().unwrap().get_reg())
+            }
             _ => None,
         }
     }
@@ -191,8 +263,9 @@ impl Stack {
 
 #[cfg(test)]
 mod tests {
-    use super::{Stack, Val};
+    use super::{Stack, TypedReg, Val};
     use crate::isa::reg::Reg;
+    use wasmparser::ValType;
 
     #[test]
     fn test_pop_i32_const() {
@@ -200,7 +273,7 @@ mod tests {
         stack.push(Val::i32(33i32));
         assert_eq!(33, stack.pop_i32_const().unwrap());
 
-        stack.push(Val::local(10));
+        stack.push(Val::local(10, ValType::I32));
         assert!(stack.pop_i32_const().is_none());
     }
 
@@ -208,23 +281,26 @@ mod tests {
     fn test_pop_reg() {
         let mut stack = Stack::new();
         let reg = Reg::int(2usize);
-        stack.push(Val::reg(reg));
+        stack.push(Val::reg(reg, ValType::I32));
         stack.push(Val::i32(4));
 
         assert_eq!(None, stack.pop_reg());
         let _ = stack.pop().unwrap();
-        assert_eq!(reg, stack.pop_reg().unwrap());
+        assert_eq!(TypedReg::new(ValType::I32, reg), stack.pop_reg().unwrap());
     }
 
     #[test]
     fn test_pop_named_reg() {
         let mut stack = Stack::new();
         let reg = Reg::int(2usize);
-        stack.push(Val::reg(reg));
-        stack.push(Val::reg(Reg::int(4)));
+        stack.push(Val::reg(reg, ValType::I64));
+        stack.push(Val::reg(Reg::int(4), ValType::I64));
 
         assert_eq!(None, stack.pop_named_reg(reg));
         let _ = stack.pop().unwrap();
-        assert_eq!(reg, stack.pop_named_reg(reg).unwrap());
+        assert_eq!(
+            TypedReg::new(ValType::I64, reg),
+            stack.pop_named_reg(reg).unwrap()
+        );
     }
 }
@@ -4,18 +4,23 @@
 //! which validates and dispatches to the corresponding
 //! machine code emitter.
 
-use crate::abi::ABI;
-use crate::codegen::CodeGen;
-use crate::masm::{DivKind, MacroAssembler, OperandSize, RegImm, RemKind};
+use crate::abi::{ensure_supported_ty, ABI};
+use crate::codegen::{
+    BlockState, BlockTypes, BuiltinArg, BuiltinFunction, CodeGen, ControlStackFrame,
+};
+use crate::masm::{
+    CmpKind, DivKind, ExtendKind, FloatCmpKind, MacroAssembler, OperandSize, RegImm, RemKind,
+    RoundingMode, ShiftKind, TruncKind,
+};
 use crate::stack::Val;
-use wasmparser::ValType;
-use wasmparser::VisitOperator;
+use anyhow::{anyhow, bail, Result};
+use wasmparser::{BlockType, BrTable, HeapType, Ieee32, Ieee64, MemArg, ValType, VisitOperator};
 
 /// A macro to define unsupported WebAssembly operators.
 ///
 /// This macro calls itself recursively;
 /// 1. It no-ops when matching a supported operator.
-/// 2. Defines the visitor function and panics when
+/// 2. Defines the visitor function and returns an error when
 /// matching an unsupported operator.
 macro_rules! def_unsupported {
     ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
@@ -26,7 +31,7 @@ macro_rules! def_unsupported {
 
 		fn $visit(&mut self $($(,$arg: $argty)*)?) -> Self::Output {
 		    $($(drop($arg);)*)?
-		    todo!(stringify!($op))
+		    Err(anyhow!("Unsupported Wasm operator: {}", stringify!($op)))
 		}
 	    );
         )*
@@ -34,24 +39,205 @@ macro_rules! def_unsupported {
 
     (emit I32Const $($rest:tt)*) => {};
     (emit I64Const $($rest:tt)*) => {};
+    (emit F32Const $($rest:tt)*) => {};
+    (emit F64Const $($rest:tt)*) => {};
     (emit I32Add $($rest:tt)*) => {};
     (emit I64Add $($rest:tt)*) => {};
     (emit I32Sub $($rest:tt)*) => {};
+    (emit I64Sub $($rest:tt)*) => {};
     (emit I32Mul $($rest:tt)*) => {};
+    (emit I64Mul $($rest:tt)*) => {};
     (emit I32DivS $($rest:tt)*) => {};
     (emit I32DivU $($rest:tt)*) => {};
     (emit I64DivS $($rest:tt)*) => {};
     (emit I64DivU $($rest:tt)*) => {};
-    (emit I64RemU $($rest:tt)*) => {};
-    (emit I64RemS $($rest:tt)*) => {};
-    (emit I32RemU $($rest:tt)*) => {};
     (emit I32RemS $($rest:tt)*) => {};
-    (emit I64Mul $($rest:tt)*) => {};
-    (emit I64Sub $($rest:tt)*) => {};
+    (emit I32RemU $($rest:tt)*) => {};
+    (emit I64RemS $($rest:tt)*) => {};
+    (emit I64RemU $($rest:tt)*) => {};
+    (emit I32And $($rest:tt)*) => {};
+    (emit I64And $($rest:tt)*) => {};
+    (emit I32Or $($rest:tt)*) => {};
+    (emit I64Or $($rest:tt)*) => {};
+    (emit I32Xor $($rest:tt)*) => {};
+    (emit I64Xor $($rest:tt)*) => {};
+    (emit I32Shl $($rest:tt)*) => {};
+    (emit I64Shl $($rest:tt)*) => {};
+    (emit I32ShrS $($rest:tt)*) => {};
+    (emit I64ShrS $($rest:tt)*) => {};
+    (emit I32ShrU $($rest:tt)*) => {};
+    (emit I64ShrU $($rest:tt)*) => {};
+    (emit I32Rotl $($rest:tt)*) => {};
+    (emit I64Rotl $($rest:tt)*) => {};
+    (emit I32Rotr $($rest:tt)*) => {};
+    (emit I64Rotr $($rest:tt)*) => {};
+    (emit I32Clz $($rest:tt)*) => {};
+    (emit I64Clz $($rest:tt)*) => {};
+    (emit I32Ctz $($rest:tt)*) => {};
+    (emit I64Ctz $($rest:tt)*) => {};
+    (emit I32Popcnt $($rest:tt)*) => {};
+    (emit I64Popcnt $($rest:tt)*) => {};
+    (emit I32Eqz $($rest:tt)*) => {};
+    (emit I64Eqz $($rest:tt)*) => {};
+    (emit I32Eq $($rest:tt)*) => {};
+    (emit I64Eq $($rest:tt)*) => {};
+    (emit I32Ne $($rest:tt)*) => {};
+    (emit I64Ne $($rest:tt)*) => {};
+    (emit I32LtS $($rest:tt)*) => {};
+    (emit I64LtS $($rest:tt)*) => {};
+    (emit I32LtU $($rest:tt)*) => {};
+    (emit I64LtU $($rest:tt)*) => {};
+    (emit I32GtS $($rest:tt)*) => {};
+    (emit I64GtS $($rest:tt)*) => {};
+    (emit I32GtU $($rest:tt)*) => {};
+    (emit I64GtU $($rest:tt)*) => {};
+    (emit I32LeS $($rest:tt)*) => {};
+    (emit I64LeS $($rest:tt)*) => {};
+    (emit I32LeU $($rest:tt)*) => {};
+    (emit I64LeU $($rest:tt)*) => {};
+    (emit I32GeS $($rest:tt)*) => {};
+    (emit I64GeS $($rest:tt)*) => {};
+    (emit I32GeU $($rest:tt)*) => {};
+    (emit I64GeU $($rest:tt)*) => {};
+    (emit I32WrapI64 $($rest:tt)*) => {};
+    (emit I64ExtendI32S $($rest:tt)*) => {};
+    (emit I64ExtendI32U $($rest:tt)*) => {};
+    (emit I32Extend8S $($rest:tt)*) => {};
+    (emit I32Extend16S $($rest:tt)*) => {};
+    (emit I64Extend8S $($rest:tt)*) => {};
+    (emit I64Extend16S $($rest:tt)*) => {};
+    (emit I64Extend32S $($rest:tt)*) => {};
+    (emit F32Add $($rest:tt)*) => {};
+    (emit F64Add $($rest:tt)*) => {};
+    (emit F32Sub $($rest:tt)*) => {};
+    (emit F64Sub $($rest:tt)*) => {};
+    (emit F32Mul $($rest:tt)*) => {};
+    (emit F64Mul $($rest:tt)*) => {};
+    (emit F32Div $($rest:tt)*) => {};
+    (emit F64Div $($rest:tt)*) => {};
+    (emit F32Min $($rest:tt)*) => {};
+    (emit F64Min $($rest:tt)*) => {};
+    (emit F32Max $($rest:tt)*) => {};
+    (emit F64Max $($rest:tt)*) => {};
+    (emit F32Copysign $($rest:tt)*) => {};
+    (emit F64Copysign $($rest:tt)*) => {};
+    (emit F32Abs $($rest:tt)*) => {};
+    (emit F64Abs $($rest:tt)*) => {};
+    (emit F32Neg $($rest:tt)*) => {};
+    (emit F64Neg $($rest:tt)*) => {};
+    (emit F32Sqrt $($rest:tt)*) => {};
+    (emit F64Sqrt $($rest:tt)*) => {};
+    (emit F32Ceil $($rest:tt)*) => {};
+    (emit F64Ceil $($rest:tt)*) => {};
+    (emit F32Floor $($rest:tt)*) => {};
+    (emit F64Floor $($rest:tt)*) => {};
+    (emit F32Trunc $($rest:tt)*) => {};
+    (emit F64Trunc $($rest:tt)*) => {};
+    (emit F32Nearest $($rest:tt)*) => {};
+    (emit F64Nearest $($rest:tt)*) => {};
+    (emit F32Eq $($rest:tt)*) => {};
+    (emit F64Eq $($rest:tt)*) => {};
+    (emit F32Ne $($rest:tt)*) => {};
+    (emit F64Ne $($rest:tt)*) => {};
+    (emit F32Lt $($rest:tt)*) => {};
+    (emit F64Lt $($rest:tt)*) => {};
+    (emit F32Gt $($rest:tt)*) => {};
+    (emit F64Gt $($rest:tt)*) => {};
+    (emit F32Le $($rest:tt)*) => {};
+    (emit F64Le $($rest:tt)*) => {};
+    (emit F32Ge $($rest:tt)*) => {};
+    (emit F64Ge $($rest:tt)*) => {};
+    (emit F32ConvertI32S $($rest:tt)*) => {};
+    (emit F32ConvertI32U $($rest:tt)*) => {};
+    (emit F32ConvertI64S $($rest:tt)*) => {};
+    (emit F32ConvertI64U $($rest:tt)*) => {};
+    (emit F64ConvertI32S $($rest:tt)*) => {};
+    (emit F64ConvertI32U $($rest:tt)*) => {};
+    (emit F64ConvertI64S $($rest:tt)*) => {};
+    (emit F64ConvertI64U $($rest:tt)*) => {};
+    (emit I32TruncF32S $($rest:tt)*) => {};
+    (emit I32TruncF32U $($rest:tt)*) => {};
+    (emit I32TruncF64S $($rest:tt)*) => {};
+    (emit I32TruncF64U $($rest:tt)*) => {};
+    (emit I64TruncF32S $($rest:tt)*) => {};
+    (emit I64TruncF32U $($rest:tt)*) => {};
+    (emit I64TruncF64S $($rest:tt)*) => {};
+    (emit I64TruncF64U $($rest:tt)*) => {};
+    (emit I32TruncSatF32S $($rest:tt)*) => {};
+    (emit I32TruncSatF32U $($rest:tt)*) => {};
+    (emit I32TruncSatF64S $($rest:tt)*) => {};
+    (emit I32TruncSatF64U $($rest:tt)*) => {};
+    (emit I64TruncSatF32S $($rest:tt)*) => {};
+    (emit I64TruncSatF32U $($rest:tt)*) => {};
+    (emit I64TruncSatF64S $($rest:tt)*) => {};
+    (emit I64TruncSatF64U $($rest:tt)*) => {};
+    (emit F32DemoteF64 $($rest:tt)*) => {};
+    (emit F64PromoteF32 $($rest:tt)*) => {};
+    (emit I32ReinterpretF32 $($rest:tt)*) => {};
+    (emit I64ReinterpretF64 $($rest:tt)*) => {};
+    (emit F32ReinterpretI32 $($rest:tt)*) => {};
+    (emit F64ReinterpretI64 $($rest:tt)*) => {};
+    (emit I32Load $($rest:tt)*) => {};
+    (emit I64Load $($rest:tt)*) => {};
+    (emit F32Load $($rest:tt)*) => {};
+    (emit F64Load $($rest:tt)*) => {};
+    (emit I32Load8S $($rest:tt)*) => {};
+    (emit I32Load8U $($rest:tt)*) => {};
+    (emit I32Load16S $($rest:tt)*) => {};
+    (emit I32Load16U $($rest:tt)*) => {};
+    (emit I64Load8S $($rest:tt)*) => {};
+    (emit I64Load8U $($rest:tt)*) => {};
+    (emit I64Load16S $($rest:tt)*) => {};
+    (emit I64Load16U $($rest:tt)*) => {};
+    (emit I64Load32S $($rest:tt)*) => {};
+    (emit I64Load32U $($rest:tt)*) => {};
+    (emit I32Store $($rest:tt)*) => {};
+    (emit I64Store $($rest:tt)*) => {};
+    (emit F32Store $($rest:tt)*) => {};
+    (emit F64Store $($rest:tt)*) => {};
+    (emit I32Store8 $($rest:tt)*) => {};
+    (emit I32Store16 $($rest:tt)*) => {};
+    (emit I64Store8 $($rest:tt)*) => {};
+    (emit I64Store16 $($rest:tt)*) => {};
+    (emit I64Store32 $($rest:tt)*) => {};
+    (emit MemorySize $($rest:tt)*) => {};
+    (emit MemoryGrow $($rest:tt)*) => {};
+    (emit MemoryCopy $($rest:tt)*) => {};
+    (emit MemoryFill $($rest:tt)*) => {};
+    (emit MemoryInit $($rest:tt)*) => {};
+    (emit DataDrop $($rest:tt)*) => {};
+    (emit TableGet $($rest:tt)*) => {};
+    (emit TableSet $($rest:tt)*) => {};
+    (emit TableSize $($rest:tt)*) => {};
+    (emit TableGrow $($rest:tt)*) => {};
+    (emit TableFill $($rest:tt)*) => {};
+    (emit TableCopy $($rest:tt)*) => {};
+    (emit TableInit $($rest:tt)*) => {};
+    (emit ElemDrop $($rest:tt)*) => {};
+    (emit RefNull $($rest:tt)*) => {};
+    (emit RefIsNull $($rest:tt)*) => {};
+    (emit RefFunc $($rest:tt)*) => {};
+    (emit GlobalGet $($rest:tt)*) => {};
+    (emit GlobalSet $($rest:tt)*) => {};
     (emit LocalGet $($rest:tt)*) => {};
     (emit LocalSet $($rest:tt)*) => {};
+    (emit LocalTee $($rest:tt)*) => {};
     (emit Call $($rest:tt)*) => {};
+    (emit CallIndirect $($rest:tt)*) => {};
     (emit End $($rest:tt)*) => {};
+    (emit Drop $($rest:tt)*) => {};
+    (emit Select $($rest:tt)*) => {};
+    (emit TypedSelect $($rest:tt)*) => {};
+    (emit Nop $($rest:tt)*) => {};
+    (emit Unreachable $($rest:tt)*) => {};
+    (emit Block $($rest:tt)*) => {};
+    (emit Loop $($rest:tt)*) => {};
+    (emit If $($rest:tt)*) => {};
+    (emit Else $($rest:tt)*) => {};
+    (emit Br $($rest:tt)*) => {};
+    (emit BrIf $($rest:tt)*) => {};
+    (emit BrTable $($rest:tt)*) => {};
+    (emit Return $($rest:tt)*) => {};
 
     (emit $unsupported:tt $($rest:tt)*) => {$($rest)*};
 }
@@ -61,155 +247,1814 @@ where
     M: MacroAssembler,
     A: ABI,
 {
-    type Output = ();
+    type Output = Result<()>;
 
-    fn visit_i32_const(&mut self, val: i32) {
+    fn visit_i32_const(&mut self, val: i32) -> Result<()> {
         self.context.stack.push(Val::i32(val));
+        Ok(())
     }
 
-    fn visit_i64_const(&mut self, val: i64) {
+    fn visit_i64_const(&mut self, val: i64) -> Result<()> {
         self.context.stack.push(Val::i64(val));
+        Ok(())
+    }
+
+    fn visit_f32_const(&mut self, val: Ieee32) -> Result<()> {
+        self.context.stack.push(Val::f32(val.bits()));
+        Ok(())
     }
 
-    fn visit_i32_add(&mut self) {
+    fn visit_f64_const(&mut self, val: Ieee64) -> Result<()> {
+        self.context.stack.push(Val::f64(val.bits()));
+        Ok(())
+    }
+
+    fn visit_i32_add(&mut self) -> Result<()> {
         self.context
             .i32_binop(self.masm, &mut |masm, dst, src, size| {
                 masm.add(dst, dst, src, size);
             });
+        Ok(())
     }
 
-    fn visit_i64_add(&mut self) {
+    fn visit_i32_sub(&mut self) -> Result<()> {
         self.context
-            .i64_binop(self.masm, &mut |masm, dst, src, size| {
-                masm.add(dst, dst, src, size);
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.sub(dst, dst, src, size);
             });
+        Ok(())
     }
 
-    fn visit_i32_sub(&mut self) {
+    fn visit_i32_mul(&mut self) -> Result<()> {
         self.context
             .i32_binop(self.masm, &mut |masm, dst, src, size| {
-                masm.sub(dst, dst, src, size);
+                masm.mul(dst, dst, src, size);
             });
+        Ok(())
     }
 
-    fn visit_i64_sub(&mut self) {
+    fn visit_i64_add(&mut self) -> Result<()> {
         self.context
             .i64_binop(self.masm, &mut |masm, dst, src, size| {
-                masm.sub(dst, dst, src, size);
+                masm.add(dst, dst, src, size);
             });
+        Ok(())
     }
 
-    fn visit_i32_mul(&mut self) {
+    fn visit_i64_sub(&mut self) -> Result<()> {
         self.context
-            .i32_binop(self.masm, &mut |masm, dst, src, size| {
-                masm.mul(dst, dst, src, size);
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.sub(dst, dst, src, size);
             });
+        Ok(())
     }
 
-    fn visit_i64_mul(&mut self) {
+    fn visit_i64_mul(&mut self) -> Result<()> {
         self.context
             .i64_binop(self.masm, &mut |masm, dst, src, size| {
                 masm.mul(dst, dst, src, size);
             });
+        Ok(())
     }
 
-    fn visit_i32_div_s(&mut self) {
+    fn visit_i32_div_s(&mut self) -> Result<()> {
         use DivKind::*;
         use OperandSize::*;
 
         self.masm.div(&mut self.context, Signed, S32);
+        Ok(())
     }
 
-    fn visit_i32_div_u(&mut self) {
+    fn visit_i32_div_u(&mut self) -> Result<()> {
         use DivKind::*;
         use OperandSize::*;
 
         self.masm.div(&mut self.context, Unsigned, S32);
+        Ok(())
     }
 
-    fn visit_i64_div_s(&mut self) {
+    fn visit_i64_div_s(&mut self) -> Result<()> {
         use DivKind::*;
         use OperandSize::*;
 
         self.masm.div(&mut self.context, Signed, S64);
+        Ok(())
     }
 
-    fn visit_i64_div_u(&mut self) {
+    fn visit_i64_div_u(&mut self) -> Result<()> {
         use DivKind::*;
         use OperandSize::*;
 
         self.masm.div(&mut self.context, Unsigned, S64);
+        Ok(())
     }
 
-    fn visit_i32_rem_s(&mut self) {
+    fn visit_i32_rem_s(&mut self) -> Result<()> {
         use OperandSize::*;
         use RemKind::*;
 
         self.masm.rem(&mut self.context, Signed, S32);
+        Ok(())
     }
 
-    fn visit_i32_rem_u(&mut self) {
+    fn visit_i32_rem_u(&mut self) -> Result<()> {
         use OperandSize::*;
         use RemKind::*;
 
         self.masm.rem(&mut self.context, Unsigned, S32);
+        Ok(())
     }
 
-    fn visit_i64_rem_s(&mut self) {
+    fn visit_i64_rem_s(&mut self) -> Result<()> {
         use OperandSize::*;
         use RemKind::*;
 
         self.masm.rem(&mut self.context, Signed, S64);
+        Ok(())
     }
 
-    fn visit_i64_rem_u(&mut self) {
+    fn visit_i64_rem_u(&mut self) -> Result<()> {
         use OperandSize::*;
         use RemKind::*;
 
         self.masm.rem(&mut self.context, Unsigned, S64);
+        Ok(())
     }
 
-    fn visit_end(&mut self) {}
+    fn visit_i32_and(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.and(dst, dst, src, size);
+            });
+        Ok(())
+    }
 
-    fn visit_local_get(&mut self, index: u32) {
-        let context = &mut self.context;
-        let slot = context
-            .frame
-            .get_local(index)
-            .expect(&format!("valid local at slot = {}", index));
-        match slot.ty {
-            ValType::I32 | ValType::I64 => context.stack.push(Val::local(index)),
-            _ => panic!("Unsupported type {:?} for local", slot.ty),
-        }
+    fn visit_i32_or(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.or(dst, dst, src, size);
+            });
+        Ok(())
     }
 
-    // TODO verify the case where the target local is on the stack.
-    fn visit_local_set(&mut self, index: u32) {
-        let context = &mut self.context;
-        let frame = context.frame;
-        let slot = frame
-            .get_local(index)
-            .expect(&format!("vald local at slot = {}", index));
-        let size: OperandSize = slot.ty.into();
-        let src = self.context.pop_to_reg(self.masm, None, size);
-        let addr = self.masm.local_address(&slot);
-        self.masm.store(RegImm::reg(src), addr, size);
-        self.context.regalloc.free_gpr(src);
+    fn visit_i32_xor(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.xor(dst, dst, src, size);
+            });
+        Ok(())
     }
 
-    fn visit_call(&mut self, index: u32) {
-        self.emit_call(index);
+    fn visit_i64_and(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.and(dst, dst, src, size);
+            });
+        Ok(())
     }
 
-    wasmparser::for_each_operator!(def_unsupported);
-}
+    fn visit_i64_or(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.or(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_xor(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.xor(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_shl(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, Shl, S32);
+        Ok(())
+    }
+
+    fn visit_i64_shl(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, Shl, S64);
+        Ok(())
+    }
+
+    fn visit_i32_shr_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, ShrS, S32);
+        Ok(())
+    }
+
+    fn visit_i64_shr_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, ShrS, S64);
+        Ok(())
+    }
+
+    fn visit_i32_shr_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, ShrU, S32);
+        Ok(())
+    }
+
+    fn visit_i64_shr_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, ShrU, S64);
+        Ok(())
+    }
+
+    fn visit_i32_rotl(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, Rotl, S32);
+        Ok(())
+    }
+
+    fn visit_i64_rotl(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, Rotl, S64);
+        Ok(())
+    }
+
+    fn visit_i32_rotr(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, Rotr, S32);
+        Ok(())
+    }
+
+    fn visit_i64_rotr(&mut self) -> Result<()> {
+        use OperandSize::*;
+        use ShiftKind::*;
+
+        self.masm.shift(&mut self.context, Rotr, S64);
+        Ok(())
+    }
+
+    fn visit_i32_clz(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I32, &mut |masm, reg| {
+                masm.clz(reg, reg, OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_clz(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.clz(reg, reg, OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_ctz(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I32, &mut |masm, reg| {
+                masm.ctz(reg, reg, OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_ctz(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.ctz(reg, reg, OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_popcnt(&mut self) -> Result<()> {
+        self.masm.popcnt(&mut self.context, OperandSize::S32);
+        Ok(())
+    }
+
+    fn visit_i64_popcnt(&mut self) -> Result<()> {
+        self.masm.popcnt(&mut self.context, OperandSize::S64);
+        Ok(())
+    }
+
+    fn visit_i32_eqz(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let reg = self.context.pop_to_reg(self.masm, None).reg;
+        self.masm
+            .cmp_with_set(RegImm::imm(0), reg.into(), CmpKind::Eq, S32);
+        self.context.stack.push(Val::reg(reg, ValType::I32));
+        Ok(())
+    }
+
+    fn visit_i64_eqz(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let reg = self.context.pop_to_reg(self.masm, None).reg;
+        self.masm
+            .cmp_with_set(RegImm::imm(0), reg.into(), CmpKind::Eq, S64);
+        self.context.stack.push(Val::reg(reg, ValType::I32));
+        Ok(())
+    }
+
+    fn visit_i32_eq(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::Eq, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_eq(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::Eq, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_ne(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::Ne, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_ne(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::Ne, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_lt_s(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LtS, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_lt_s(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LtS, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_lt_u(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LtU, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_lt_u(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LtU, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_gt_s(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GtS, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_gt_s(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GtS, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_gt_u(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GtU, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_gt_u(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GtU, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_le_s(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LeS, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_le_s(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LeS, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_le_u(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LeU, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_le_u(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::LeU, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_ge_s(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GeS, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_ge_s(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GeS, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_ge_u(&mut self) -> Result<()> {
+        self.context
+            .i32_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GeU, size);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_ge_u(&mut self) -> Result<()> {
+        self.context
+            .i64_binop(self.masm, &mut |masm, dst, src, size| {
+                masm.cmp_with_set(src, dst, CmpKind::GeU, size);
+            });
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i32_wrap_i64(&mut self) -> Result<()> {
+        // Only the low 32 bits of a 64-bit register are used by i32
+        // operations, so this is a no-op.
+        self.retype_stack_top(ValType::I32);
+        Ok(())
+    }
+
+    fn visit_i64_extend_i32_s(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I64ExtendI32S);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_extend_i32_u(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I64ExtendI32U);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_extend8_s(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I32, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I32Extend8S);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_extend16_s(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I32, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I32Extend16S);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_extend8_s(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I64Extend8S);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_extend16_s(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I64Extend16S);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_extend32_s(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::I64, &mut |masm, reg| {
+                masm.extend(reg, reg, ExtendKind::I64ExtendI32S);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_add(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_add(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_add(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_add(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_sub(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_sub(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_sub(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_sub(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_mul(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_mul(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_mul(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_mul(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_div(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_div(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_div(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_div(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_min(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_min(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_min(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_min(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_max(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_max(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_max(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_max(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_copysign(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S32, &mut |masm, dst, src, size| {
+                masm.float_copysign(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_copysign(&mut self) -> Result<()> {
+        self.context
+            .float_binop(self.masm, OperandSize::S64, &mut |masm, dst, src, size| {
+                masm.float_copysign(dst, dst, src, size);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_abs(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::F32, &mut |masm, reg| {
+                masm.float_abs(reg, OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_abs(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::F64, &mut |masm, reg| {
+                masm.float_abs(reg, OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_neg(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::F32, &mut |masm, reg| {
+                masm.float_neg(reg, OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_neg(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::F64, &mut |masm, reg| {
+                masm.float_neg(reg, OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_sqrt(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::F32, &mut |masm, reg| {
+                masm.float_sqrt(reg, reg, OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_sqrt(&mut self) -> Result<()> {
+        self.context
+            .unop(self.masm, ValType::F64, &mut |masm, reg| {
+                masm.float_sqrt(reg, reg, OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_ceil(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Up, &mut self.context, OperandSize::S32);
+        Ok(())
+    }
+
+    fn visit_f64_ceil(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Up, &mut self.context, OperandSize::S64);
+        Ok(())
+    }
+
+    fn visit_f32_floor(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Down, &mut self.context, OperandSize::S32);
+        Ok(())
+    }
+
+    fn visit_f64_floor(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Down, &mut self.context, OperandSize::S64);
+        Ok(())
+    }
+
+    fn visit_f32_trunc(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Zero, &mut self.context, OperandSize::S32);
+        Ok(())
+    }
+
+    fn visit_f64_trunc(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Zero, &mut self.context, OperandSize::S64);
+        Ok(())
+    }
+
+    fn visit_f32_nearest(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Nearest, &mut self.context, OperandSize::S32);
+        Ok(())
+    }
+
+    fn visit_f64_nearest(&mut self) -> Result<()> {
+        self.masm
+            .float_round(RoundingMode::Nearest, &mut self.context, OperandSize::S64);
+        Ok(())
+    }
+
+    fn visit_f32_eq(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S32,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Eq, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f64_eq(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S64,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Eq, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f32_ne(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S32,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Ne, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f64_ne(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S64,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Ne, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f32_lt(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S32,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Lt, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f64_lt(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S64,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Lt, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f32_gt(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S32,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Gt, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f64_gt(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S64,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Gt, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f32_le(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S32,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Le, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f64_le(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S64,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Le, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f32_ge(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S32,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Ge, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f64_ge(&mut self) -> Result<()> {
+        self.context.float_cmp_op(
+            self.masm,
+            OperandSize::S64,
+            &mut |masm, dst, lhs, rhs, size| {
+                masm.float_cmp_with_set(lhs, rhs, dst, FloatCmpKind::Ge, size);
+            },
+        );
+        Ok(())
+    }
+
+    fn visit_f32_convert_i32_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::F32, &mut |masm, src, dst| {
+                masm.signed_convert(src, dst, S32, S32);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_convert_i32_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_gpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::F32, &mut |masm, src, dst| {
+                masm.unsigned_convert(src, dst, tmp, S32, S32);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_f32_convert_i64_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::F32, &mut |masm, src, dst| {
+                masm.signed_convert(src, dst, S64, S32);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_convert_i64_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_gpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::F32, &mut |masm, src, dst| {
+                masm.unsigned_convert(src, dst, tmp, S64, S32);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_f64_convert_i32_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::F64, &mut |masm, src, dst| {
+                masm.signed_convert(src, dst, S32, S64);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_convert_i32_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_gpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::F64, &mut |masm, src, dst| {
+                masm.unsigned_convert(src, dst, tmp, S32, S64);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_f64_convert_i64_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::F64, &mut |masm, src, dst| {
+                masm.signed_convert(src, dst, S64, S64);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_convert_i64_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_gpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::F64, &mut |masm, src, dst| {
+                masm.unsigned_convert(src, dst, tmp, S64, S64);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i32_trunc_f32_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S32, S32, TruncKind::Checked);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_trunc_f32_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S32, S32, TruncKind::Checked);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i32_trunc_f64_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S64, S32, TruncKind::Checked);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_trunc_f64_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S64, S32, TruncKind::Checked);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i64_trunc_f32_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S32, S64, TruncKind::Checked);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_trunc_f32_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S32, S64, TruncKind::Checked);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i64_trunc_f64_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S64, S64, TruncKind::Checked);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_trunc_f64_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S64, S64, TruncKind::Checked);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i32_trunc_sat_f32_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S32, S32, TruncKind::Saturating);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_trunc_sat_f32_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S32, S32, TruncKind::Saturating);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i32_trunc_sat_f64_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S64, S32, TruncKind::Saturating);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_trunc_sat_f64_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S64, S32, TruncKind::Saturating);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i64_trunc_sat_f32_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S32, S64, TruncKind::Saturating);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_trunc_sat_f32_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S32, S64, TruncKind::Saturating);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_i64_trunc_sat_f64_s(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.signed_truncate(src, dst, S64, S64, TruncKind::Saturating);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_trunc_sat_f64_u(&mut self) -> Result<()> {
+        use OperandSize::*;
+
+        let tmp = self.context.any_fpr(self.masm);
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.unsigned_truncate(src, dst, tmp, S64, S64, TruncKind::Saturating);
+            });
+        self.context.free_reg(tmp);
+        Ok(())
+    }
+
+    fn visit_f32_demote_f64(&mut self) -> Result<()> {
+        self.context
+            .convert_op(self.masm, ValType::F32, &mut |masm, src, dst| {
+                masm.demote(src, dst);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_promote_f32(&mut self) -> Result<()> {
+        self.context
+            .convert_op(self.masm, ValType::F64, &mut |masm, src, dst| {
+                masm.promote(src, dst);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_reinterpret_f32(&mut self) -> Result<()> {
+        self.context
+            .convert_op(self.masm, ValType::I32, &mut |masm, src, dst| {
+                masm.mov(src.into(), dst.into(), OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_i64_reinterpret_f64(&mut self) -> Result<()> {
+        self.context
+            .convert_op(self.masm, ValType::I64, &mut |masm, src, dst| {
+                masm.mov(src.into(), dst.into(), OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_f32_reinterpret_i32(&mut self) -> Result<()> {
+        self.context
+            .convert_op(self.masm, ValType::F32, &mut |masm, src, dst| {
+                masm.mov(src.into(), dst.into(), OperandSize::S32);
+            });
+        Ok(())
+    }
+
+    fn visit_f64_reinterpret_i64(&mut self) -> Result<()> {
+        self.context
+            .convert_op(self.masm, ValType::F64, &mut |masm, src, dst| {
+                masm.mov(src.into(), dst.into(), OperandSize::S64);
+            });
+        Ok(())
+    }
+
+    fn visit_i32_load(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I32, OperandSize::S32, false)
+    }
+
+    fn visit_i64_load(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S64, false)
+    }
+
+    fn visit_f32_load(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::F32, OperandSize::S32, false)
+    }
+
+    fn visit_f64_load(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::F64, OperandSize::S64, false)
+    }
+
+    fn visit_i32_load8_s(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I32, OperandSize::S8, true)
+    }
+
+    fn visit_i32_load8_u(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I32, OperandSize::S8, false)
+    }
+
+    fn visit_i32_load16_s(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I32, OperandSize::S16, true)
+    }
+
+    fn visit_i32_load16_u(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I32, OperandSize::S16, false)
+    }
+
+    fn visit_i64_load8_s(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S8, true)
+    }
+
+    fn visit_i64_load8_u(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S8, false)
+    }
+
+    fn visit_i64_load16_s(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S16, true)
+    }
+
+    fn visit_i64_load16_u(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S16, false)
+    }
+
+    fn visit_i64_load32_s(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S32, true)
+    }
+
+    fn visit_i64_load32_u(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_load(&memarg, ValType::I64, OperandSize::S32, false)
+    }
+
+    fn visit_i32_store(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S32)
+    }
+
+    fn visit_i64_store(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S64)
+    }
+
+    fn visit_f32_store(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S32)
+    }
+
+    fn visit_f64_store(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S64)
+    }
+
+    fn visit_i32_store8(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S8)
+    }
+
+    fn visit_i32_store16(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S16)
+    }
+
+    fn visit_i64_store8(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S8)
+    }
+
+    fn visit_i64_store16(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S16)
+    }
+
+    fn visit_i64_store32(&mut self, memarg: MemArg) -> Result<()> {
+        self.emit_wasm_store(&memarg, OperandSize::S32)
+    }
+
+    fn visit_memory_size(&mut self, mem: u32, _: u8) -> Result<()> {
+        let heap = self.env.heap(mem);
+        let reg = self.context.any_gpr(self.masm);
+        let offset = self.emit_data_location(heap.location, reg);
+        let addr = self
+            .masm
+            .address_at_reg(reg, offset + heap.current_length_offset);
+        self.masm.load(addr, reg, OperandSize::S64);
+        self.context.stack.push(Val::reg(reg, ValType::I64));
+        // The size in pages always fits in the memory's index type, so
+        // the shift is performed on the full length.
+        self.context.stack.push(Val::i64(WASM_PAGE_SIZE_LOG2));
+        self.masm
+            .shift(&mut self.context, ShiftKind::ShrU, OperandSize::S64);
+        self.retype_stack_top(heap.index_type);
+        Ok(())
+    }
+
+    fn visit_memory_grow(&mut self, mem: u32, _: u8) -> Result<()> {
+        let heap = self.env.heap(mem);
+        self.emit_builtin_call(
+            BuiltinFunction::MemoryGrow,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::Stack(0),
+                BuiltinArg::I32(mem),
+            ],
+        )?;
+        self.retype_stack_top(heap.index_type);
+        Ok(())
+    }
+
+    fn visit_memory_copy(&mut self, dst_mem: u32, src_mem: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::MemoryCopy,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(dst_mem),
+                BuiltinArg::Stack(0),
+                BuiltinArg::I32(src_mem),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(2),
+            ],
+        )
+    }
+
+    fn visit_memory_fill(&mut self, mem: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::MemoryFill,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(mem),
+                BuiltinArg::Stack(0),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(2),
+            ],
+        )
+    }
+
+    fn visit_memory_init(&mut self, data_index: u32, mem: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::MemoryInit,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(mem),
+                BuiltinArg::I32(data_index),
+                BuiltinArg::Stack(0),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(2),
+            ],
+        )
+    }
+
+    fn visit_data_drop(&mut self, data_index: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::DataDrop,
+            &[BuiltinArg::VMContext, BuiltinArg::I32(data_index)],
+        )
+    }
+
+    fn visit_table_get(&mut self, table: u32) -> Result<()> {
+        let reg = self.emit_table_get_funcref(table)?;
+        self.context.stack.push(Val::reg(reg, ValType::FUNCREF));
+        Ok(())
+    }
+
+    fn visit_table_set(&mut self, table: u32) -> Result<()> {
+        self.emit_table_set_funcref(table)
+    }
+
+    fn visit_table_size(&mut self, table: u32) -> Result<()> {
+        let data = self.env.table(table);
+        let reg = self.context.any_gpr(self.masm);
+        let offset = self.emit_data_location(data.location, reg);
+        let addr = self
+            .masm
+            .address_at_reg(reg, offset + data.current_elements_offset);
+        self.masm.load(addr, reg, OperandSize::S32);
+        self.context.stack.push(Val::reg(reg, ValType::I32));
+        Ok(())
+    }
+
+    fn visit_table_grow(&mut self, table: u32) -> Result<()> {
+        self.ensure_funcref_table(table)?;
+        // The operands are the initial value followed by the delta,
+        // while the builtin takes them in reverse order.
+        self.emit_builtin_call(
+            BuiltinFunction::TableGrowFuncRef,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(table),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(0),
+            ],
+        )
+    }
+
+    fn visit_table_fill(&mut self, table: u32) -> Result<()> {
+        self.ensure_funcref_table(table)?;
+        self.emit_builtin_call(
+            BuiltinFunction::TableFillFuncRef,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(table),
+                BuiltinArg::Stack(0),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(2),
+            ],
+        )
+    }
+
+    fn visit_table_copy(&mut self, dst_table: u32, src_table: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::TableCopy,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(dst_table),
+                BuiltinArg::I32(src_table),
+                BuiltinArg::Stack(0),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(2),
+            ],
+        )
+    }
+
+    fn visit_table_init(&mut self, elem_index: u32, table: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::TableInit,
+            &[
+                BuiltinArg::VMContext,
+                BuiltinArg::I32(table),
+                BuiltinArg::I32(elem_index),
+                BuiltinArg::Stack(0),
+                BuiltinArg::Stack(1),
+                BuiltinArg::Stack(2),
+            ],
+        )
+    }
+
+    fn visit_elem_drop(&mut self, elem_index: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::ElemDrop,
+            &[BuiltinArg::VMContext, BuiltinArg::I32(elem_index)],
+        )
+    }
+
+    fn visit_ref_null(&mut self, hty: HeapType) -> Result<()> {
+        if hty != HeapType::Func {
+            bail!("Unsupported reference type {:?}", hty);
+        }
+        let reg = self.context.any_gpr(self.masm);
+        self.masm.zero(reg);
+        self.context.stack.push(Val::reg(reg, ValType::FUNCREF));
+        Ok(())
+    }
+
+    fn visit_ref_is_null(&mut self) -> Result<()> {
+        let reg = self.context.pop_to_reg(self.masm, None).reg;
+        self.masm
+            .cmp_with_set(RegImm::imm(0), reg.into(), CmpKind::Eq, OperandSize::S64);
+        self.context.stack.push(Val::reg(reg, ValType::I32));
+        Ok(())
+    }
+
+    fn visit_ref_func(&mut self, function_index: u32) -> Result<()> {
+        self.emit_builtin_call(
+            BuiltinFunction::RefFunc,
+            &[BuiltinArg::VMContext, BuiltinArg::I32(function_index)],
+        )?;
+        self.retype_stack_top(ValType::FUNCREF);
+        Ok(())
+    }
+
+    fn visit_global_get(&mut self, global_index: u32) -> Result<()> {
+        let global = self.env.global(global_index);
+        ensure_supported_ty(&global.ty)?;
+        let base = self.context.any_gpr(self.masm);
+        let offset = self.emit_data_location(global.location, base);
+        let dst = self.context.reg_for_type(global.ty, self.masm);
+        let addr = self.masm.address_at_reg(base, offset);
+        self.masm.load(addr, dst, global.ty.into());
+        self.context.free_reg(base);
+        self.context.stack.push(Val::reg(dst, global.ty));
+        Ok(())
+    }
+
+    fn visit_global_set(&mut self, global_index: u32) -> Result<()> {
+        let global = self.env.global(global_index);
+        ensure_supported_ty(&global.ty)?;
+        let src = self.context.pop_to_reg(self.masm, None);
+        let base = self.context.any_gpr(self.masm);
+        let offset = self.emit_data_location(global.location, base);
+        let addr = self.masm.address_at_reg(base, offset);
+        self.masm.store(src.reg.into(), addr, global.ty.into());
+        self.context.free_reg(base);
+        self.context.free_reg(src);
+        Ok(())
+    }
+
+    fn visit_nop(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn visit_unreachable(&mut self) -> Result<()> {
+        self.masm.unreachable();
+        self.reachable = false;
+        Ok(())
+    }
+
+    fn visit_drop(&mut self) -> Result<()> {
+        match self.context.stack.pop().expect("value at stack top") {
+            Val::Reg(reg) => self.context.free_reg(reg),
+            Val::Memory { offset, .. } => {
+                assert_eq!(offset, self.masm.sp_offset());
+                self.masm.free_stack(<A as ABI>::word_bytes());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn visit_select(&mut self) -> Result<()> {
+        let cond = self.context.pop_to_reg(self.masm, None).reg;
+        let alternative = self.context.pop_to_reg(self.masm, None);
+        let consequent = self.context.pop_to_reg(self.masm, None);
+        self.masm
+            .cmov_if_zero(cond, alternative.reg, consequent.reg, consequent.ty.into());
+        self.context.free_reg(cond);
+        self.context.free_reg(alternative);
+        self.context.stack.push(consequent.into());
+        Ok(())
+    }
+
+    fn visit_typed_select(&mut self, ty: ValType) -> Result<()> {
+        ensure_supported_ty(&ty)?;
+        self.visit_select()
+    }
+
+    fn visit_block(&mut self, blockty: BlockType) -> Result<()> {
+        self.context.spill(self.masm);
+        let state = self.block_state(blockty)?;
+        let exit = self.masm.get_label();
+        self.control_frames
+            .push(ControlStackFrame::Block { exit, state });
+        Ok(())
+    }
+
+    fn visit_loop(&mut self, blockty: BlockType) -> Result<()> {
+        self.context.spill(self.masm);
+        let state = self.block_state(blockty)?;
+        // Branches to the loop pass the loop parameters, so they're
+        // moved to the location in which branches place them.
+        self.pop_frame_values(state.sp_offset, state.stack_len, &state.params);
+        let head = self.masm.get_label();
+        self.masm.bind(head);
+        self.push_frame_values(state.sp_offset, &state.params);
+        self.control_frames
+            .push(ControlStackFrame::Loop { head, state });
+        Ok(())
+    }
+
+    fn visit_if(&mut self, blockty: BlockType) -> Result<()> {
+        let cond = self.context.pop_to_reg(self.masm, None).reg;
+        self.context.spill(self.masm);
+        let state = self.block_state(blockty)?;
+        // The parameters are saved in stack slots, from which they're
+        // restored at the start of the alternative, while the
+        // consequent operates on a copy of them.
+        let params = state.params.len();
+        if params > 0 {
+            self.emit_copy_to_slots(state.sp_offset, params);
+            self.context.truncate_stack(state.stack_len);
+        }
+        let cont = self.masm.get_label();
+        let exit = self.masm.get_label();
+        self.masm
+            .branch(CmpKind::Eq, cond, RegImm::imm(0), cont, OperandSize::S32);
+        self.context.free_reg(cond);
+        if params > 0 {
+            let saved = state.sp_offset + params as u32 * <A as ABI>::word_bytes();
+            self.push_slot_values(state.sp_offset, &state.params);
+            self.emit_copy_to_slots(saved, params);
+            self.context.truncate_stack(state.stack_len);
+            self.push_slot_values(saved, &state.params);
+        }
+        self.control_frames
+            .push(ControlStackFrame::If { cont, exit, state });
+        Ok(())
+    }
+
+    fn visit_else(&mut self) -> Result<()> {
+        let (cont, exit, state) = match self.control_frames.pop() {
+            Some(ControlStackFrame::If { cont, exit, state }) => (cont, exit, state),
+            frame => panic!("expected an if control frame, found {:?}", frame),
+        };
+
+        let reachable = self.reachable;
+        self.end_control_frame(&state);
+        if reachable {
+            self.masm.jmp(exit);
+        }
+        self.masm.bind(cont);
+        self.reachable = true;
+        // Restore the parameters saved at the entry of the if.
+        let saved = state.sp_offset + state.params.len() as u32 * <A as ABI>::word_bytes();
+        self.masm.reset_stack_pointer(saved);
+        self.push_slot_values(state.sp_offset, &state.params);
+        self.control_frames
+            .push(ControlStackFrame::Else { exit, state });
+        Ok(())
+    }
+
+    fn visit_end(&mut self) -> Result<()> {
+        let frame = self.control_frames.pop().expect("control frame");
+        if matches!(frame, ControlStackFrame::If { .. }) && !frame.state().params.is_empty() {
+            // An if with parameters and without an alternative passes
+            // its parameters through when the condition is false, which
+            // is what an empty alternative does.
+            self.control_frames.push(frame);
+            self.visit_else()?;
+            return self.visit_end();
+        }
+
+        let state = frame.state().clone();
+        self.end_control_frame(&state);
+
+        match frame {
+            ControlStackFrame::If { cont, exit, .. } => {
+                self.masm.bind(cont);
+                self.masm.bind(exit);
+            }
+            ControlStackFrame::Else { exit, .. } | ControlStackFrame::Block { exit, .. } => {
+                self.masm.bind(exit)
+            }
+            ControlStackFrame::Loop { .. } => {}
+        }
+
+        self.reachable = true;
+        self.push_frame_values(state.sp_offset, &state.results);
+        Ok(())
+    }
+
+    fn visit_br(&mut self, relative_depth: u32) -> Result<()> {
+        let index = self.control_index(relative_depth);
+        let tys = BlockTypes::from(self.control_frames[index].branch_types());
+        if let Some(reg) = self.pop_block_result(&tys) {
+            self.context.free_reg(reg);
+        }
+        self.emit_jump_to(index);
+        self.reachable = false;
+        Ok(())
+    }
+
+    fn visit_br_if(&mut self, relative_depth: u32) -> Result<()> {
+        use OperandSize::*;
+
+        let index = self.control_index(relative_depth);
+        let frame = &self.control_frames[index];
+        let tys = BlockTypes::from(frame.branch_types());
+        let (target, sp_offset) = (frame.branch_target(), frame.state().sp_offset);
+
+        let cond = self.pop_branch_operand(&tys);
+        let result = self.pop_block_result(&tys);
+
+        if tys.len() <= 1 && self.masm.sp_offset() == sp_offset {
+            self.masm
+                .branch(CmpKind::Ne, cond, RegImm::imm(0), target, S32);
+        } else {
+            let skip = self.masm.get_label();
+            self.masm
+                .branch(CmpKind::Eq, cond, RegImm::imm(0), skip, S32);
+            self.emit_jump_to(index);
+            self.masm.bind(skip);
+        }
+
+        self.context.free_reg(cond);
+        if let Some(reg) = result {
+            self.context.stack.push(reg.into());
+        }
+        Ok(())
+    }
+
+    fn visit_br_table(&mut self, targets: BrTable<'a>) -> Result<()> {
+        let default = self.control_index(targets.default());
+        let tys = BlockTypes::from(self.control_frames[default].branch_types());
+
+        let index = self.pop_branch_operand(&tys);
+        let result = self.pop_block_result(&tys);
+
+        for (i, depth) in targets.targets().enumerate() {
+            let depth = depth?;
+            let next = self.masm.get_label();
+            self.masm.branch(
+                CmpKind::Ne,
+                index,
+                RegImm::imm(i as i64),
+                next,
+                OperandSize::S32,
+            );
+            self.emit_jump_to(self.control_index(depth));
+            self.masm.bind(next);
+        }
+        self.emit_jump_to(default);
+
+        self.context.free_reg(index);
+        if let Some(reg) = result {
+            self.context.free_reg(reg);
+        }
+        self.reachable = false;
+        Ok(())
+    }
+
+    fn visit_return(&mut self) -> Result<()> {
+        self.visit_br(self.control_frames.len() as u32 - 1)
+    }
+
+    fn visit_local_get(&mut self, index: u32) -> Result<()> {
+        let context = &mut self.context;
+        let slot = context
+            .frame
+            .get_local(index)
+            .expect(&format!("valid local at slot = {}", index));
+        context.stack.push(Val::local(index, slot.ty));
+        Ok(())
+    }
+
+    fn visit_local_set(&mut self, index: u32) -> Result<()> {
+        let src = self.emit_set_local(index);
+        self.context.free_reg(src);
+        Ok(())
+    }
+
+    fn visit_local_tee(&mut self, index: u32) -> Result<()> {
+        let src = self.emit_set_local(index);
+        self.context.stack.push(src.into());
+        Ok(())
+    }
+
+    fn visit_call(&mut self, index: u32) -> Result<()> {
+        self.emit_call(index)
+    }
+
+    fn visit_call_indirect(&mut self, type_index: u32, table_index: u32, _: u8) -> Result<()> {
+        self.emit_call_indirect(type_index, table_index)
+    }
+
+    wasmparser::for_each_operator!(def_unsupported);
+}
+
+impl<'a, A, M> CodeGen<'a, A, M>
+where
+    M: MacroAssembler,
+    A: ABI,
+{
+    /// Creates the state of a control frame of the given type entered
+    /// at the current position, whose parameters are at the top of the
+    /// value stack.
+    fn block_state(&mut self, blockty: BlockType) -> Result<BlockState> {
+        BlockState::new(
+            blockty,
+            self.env,
+            &self.context,
+            self.masm,
+            <A as ABI>::word_bytes(),
+        )
+    }
+
+    /// Returns an error if the table at the given index doesn't hold
+    /// `funcref` elements.
+    fn ensure_funcref_table(&self, table: u32) -> Result<()> {
+        let ty = self.env.table(table).element_type;
+        if ty != ValType::FUNCREF {
+            bail!("Unsupported table element type {:?}", ty);
+        }
+        Ok(())
+    }
+}
+
+/// The base 2 logarithm of the WebAssembly page size.
+const WASM_PAGE_SIZE_LOG2: i64 = 16;
 
 impl From<ValType> for OperandSize {
     fn from(ty: ValType) -> OperandSize {
         match ty {
-            ValType::I32 => OperandSize::S32,
-            ValType::I64 => OperandSize::S64,
-            ty => todo!("unsupported type {:?}", ty),
+            ValType::I32 | ValType::F32 => OperandSize::S32,
+            ValType::I64 | ValType::F64 | ValType::FUNCREF => OperandSize::S64,
+            ty => unreachable!("unsupported type {:?}", ty),
         }
     }
 }
@@ -4,8 +4,15 @@
 //! `winch_codegen::FuncEnv` trait.
 
 use wasmparser::types::Types;
-use wasmtime_environ::{FuncIndex, Module};
-use winch_codegen::{self, Callee, TargetIsa};
+use wasmparser::{FuncType, ValType};
+use wasmtime_environ::{
+    BuiltinFunctionIndex, FuncIndex, GlobalIndex, MemoryIndex, Module, PtrSize, TableIndex,
+    TypeIndex, VMOffsets, WasmType,
+};
+use winch_codegen::{
+    self, BuiltinFunction, Callee, DataLocation, GlobalData, HeapData, ImportedFunctionData,
+    TableData, TargetIsa, VMContextOffsets,
+};
 
 /// Function environment containing module and runtime specific
 /// information.
@@ -16,6 +23,8 @@ pub struct FuncEnv<'a> {
     pub types: &'a Types,
     /// The current ISA.
     pub isa: &'a dyn TargetIsa,
+    /// Offsets to the fields within the `VMContext` of the module.
+    offsets: VMOffsets<u8>,
 }
 
 impl<'a> winch_codegen::FuncEnv for FuncEnv<'a> {
@@ -31,11 +40,145 @@ impl<'a> winch_codegen::FuncEnv for FuncEnv<'a> {
             index,
         }
     }
+
+    fn vmctx_offsets(&self) -> VMContextOffsets {
+        let ptr = &self.offsets.ptr;
+        VMContextOffsets {
+            runtime_limits: self.offsets.vmctx_runtime_limits(),
+            stack_limit: ptr.vmruntime_limits_stack_limit().into(),
+            builtin_functions: self.offsets.vmctx_builtin_functions(),
+            signature_ids: self.offsets.vmctx_signature_ids_array(),
+            signature_id_size: self.offsets.size_of_vmshared_signature_index().into(),
+            func_ref_func_ptr: ptr.vmcaller_checked_func_ref_func_ptr().into(),
+            func_ref_type_index: ptr.vmcaller_checked_func_ref_type_index().into(),
+            func_ref_vmctx: ptr.vmcaller_checked_func_ref_vmctx().into(),
+        }
+    }
+
+    fn imported_function(&self, index: u32) -> ImportedFunctionData {
+        let index = FuncIndex::from_u32(index);
+        assert!(self.module.is_imported_function(index));
+        ImportedFunctionData {
+            body: self.offsets.vmctx_vmfunction_import_body(index),
+            vmctx: self.offsets.vmctx_vmfunction_import_vmctx(index),
+        }
+    }
+
+    fn global(&self, index: u32) -> GlobalData {
+        let index = GlobalIndex::from_u32(index);
+        let location = match self.module.defined_global_index(index) {
+            Some(defined) => {
+                DataLocation::VMContext(self.offsets.vmctx_vmglobal_definition(defined))
+            }
+            None => DataLocation::Pointer(self.offsets.vmctx_vmglobal_import_from(index)),
+        };
+
+        GlobalData {
+            ty: val_type(self.module.globals[index].wasm_ty),
+            location,
+        }
+    }
+
+    fn heap(&self, index: u32) -> HeapData {
+        let index = MemoryIndex::from_u32(index);
+        // Both defined and imported memories are accessed through a
+        // pointer to their `VMMemoryDefinition`.
+        let location = match self.module.defined_memory_index(index) {
+            Some(defined) => DataLocation::Pointer(self.offsets.vmctx_vmmemory_pointer(defined)),
+            None => DataLocation::Pointer(self.offsets.vmctx_vmmemory_import_from(index)),
+        };
+        let ptr = &self.offsets.ptr;
+
+        HeapData {
+            location,
+            base_offset: ptr.vmmemory_definition_base().into(),
+            current_length_offset: ptr.vmmemory_definition_current_length().into(),
+            index_type: if self.module.memory_plans[index].memory.memory64 {
+                ValType::I64
+            } else {
+                ValType::I32
+            },
+        }
+    }
+
+    fn table(&self, index: u32) -> TableData {
+        let index = TableIndex::from_u32(index);
+        let location = match self.module.defined_table_index(index) {
+            Some(defined) => {
+                DataLocation::VMContext(self.offsets.vmctx_vmtable_definition(defined))
+            }
+            None => DataLocation::Pointer(self.offsets.vmctx_vmtable_import_from(index)),
+        };
+
+        TableData {
+            location,
+            base_offset: self.offsets.vmtable_definition_base().into(),
+            current_elements_offset: self.offsets.vmtable_definition_current_elements().into(),
+            element_type: val_type(self.module.table_plans[index].table.wasm_ty),
+        }
+    }
+
+    fn func_type_at(&self, type_index: u32) -> FuncType {
+        self.types
+            .func_type_at(type_index)
+            .unwrap_or_else(|| panic!("function type at type index: {}", type_index))
+            .clone()
+    }
+
+    fn signature_index(&self, type_index: u32) -> u32 {
+        self.module.types[TypeIndex::from_u32(type_index)]
+            .unwrap_function()
+            .as_u32()
+    }
+
+    fn builtin_function_index(&self, builtin: BuiltinFunction) -> u32 {
+        let index = match builtin {
+            BuiltinFunction::MemoryGrow => BuiltinFunctionIndex::memory32_grow(),
+            BuiltinFunction::MemoryCopy => BuiltinFunctionIndex::memory_copy(),
+            BuiltinFunction::MemoryFill => BuiltinFunctionIndex::memory_fill(),
+            BuiltinFunction::MemoryInit => BuiltinFunctionIndex::memory_init(),
+            BuiltinFunction::DataDrop => BuiltinFunctionIndex::data_drop(),
+            BuiltinFunction::TableCopy => BuiltinFunctionIndex::table_copy(),
+            BuiltinFunction::TableInit => BuiltinFunctionIndex::table_init(),
+            BuiltinFunction::ElemDrop => BuiltinFunctionIndex::elem_drop(),
+            BuiltinFunction::TableGrowFuncRef => BuiltinFunctionIndex::table_grow_funcref(),
+            BuiltinFunction::TableFillFuncRef => BuiltinFunctionIndex::table_fill_funcref(),
+            BuiltinFunction::TableGetLazyInitFuncRef => {
+                BuiltinFunctionIndex::table_get_lazy_init_funcref()
+            }
+            BuiltinFunction::RefFunc => BuiltinFunctionIndex::ref_func(),
+        };
+        index.index()
+    }
 }
 
 impl<'a> FuncEnv<'a> {
     /// Create a new function environment.
     pub fn new(module: &'a Module, types: &'a Types, isa: &'a dyn TargetIsa) -> Self {
-        Self { module, types, isa }
+        let pointer_bytes = isa
+            .triple()
+            .pointer_width()
+            .expect("pointer width of the target")
+            .bytes();
+        let offsets = VMOffsets::new(pointer_bytes, module);
+        Self {
+            module,
+            types,
+            isa,
+            offsets,
+        }
+    }
+}
+
+/// Convert a Wasm type, as seen by the runtime, into a `ValType`.
+fn val_type(ty: WasmType) -> ValType {
+    match ty {
+        WasmType::I32 => ValType::I32,
+        WasmType::I64 => ValType::I64,
+        WasmType::F32 => ValType::F32,
+        WasmType::F64 => ValType::F64,
+        WasmType::V128 => ValType::V128,
+        WasmType::FuncRef => ValType::FUNCREF,
+        WasmType::ExternRef => ValType::EXTERNREF,
     }
 }
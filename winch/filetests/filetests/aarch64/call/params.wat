@@ -0,0 +1,175 @@
+;;! target = "aarch64"
+
+(module
+  (func (export "main") (param i32) (param i32) (result i32)
+    (local.get 1)
+    (local.get 0)
+    (i32.div_u)
+
+    (call $add (i32.const 1) (i32.const 2) (i32.const 3) (i32.const 4) (i32.const 5) (i32.const 6) (i32.const 7) (i32.const 8))
+
+    (local.get 1)
+    (local.get 0)
+    (i32.div_u)
+
+    (call $add (i32.const 2) (i32.const 3) (i32.const 4) (i32.const 5) (i32.const 6) (i32.const 7) (i32.const 8))
+  )
+
+  (func $add (param i32 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)
+    (local.get 0)
+    (local.get 1)
+    (i32.add)
+    (local.get 2)
+    (i32.add)
+    (local.get 3)
+    (i32.add)
+    (local.get 4)
+    (i32.add)
+    (local.get 5)
+    (i32.add)
+    (local.get 6)
+    (i32.add)
+    (local.get 7)
+    (i32.add)
+    (local.get 8)
+    (i32.add)
+  )
+)
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 a30a0054             	b.lo	#0x178
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 824300b8             	stur	w2, [x28, #4]
+;;   30:	 830300b8             	stur	w3, [x28]
+;;   34:	 804340b8             	ldur	w0, [x28, #4]
+;;   38:	 810340b8             	ldur	w1, [x28]
+;;   3c:	 1f000071             	cmp	w0, #0
+;;   40:	 e0090054             	b.eq	#0x17c
+;;   44:	 e003002a             	mov	w0, w0
+;;   48:	 e103012a             	mov	w1, w1
+;;   4c:	 2108c09a             	udiv	x1, x1, x0
+;;   50:	 808340f8             	ldur	x0, [x28, #8]
+;;   54:	 828340f8             	ldur	x2, [x28, #8]
+;;   58:	 ff2300d1             	sub	sp, sp, #8
+;;   5c:	 fc030091             	mov	x28, sp
+;;   60:	 800300f8             	stur	x0, [x28]
+;;   64:	 ff2300d1             	sub	sp, sp, #8
+;;   68:	 fc030091             	mov	x28, sp
+;;   6c:	 820300f8             	stur	x2, [x28]
+;;   70:	 ff2300d1             	sub	sp, sp, #8
+;;   74:	 fc030091             	mov	x28, sp
+;;   78:	 810300f8             	stur	x1, [x28]
+;;   7c:	 ff6300d1             	sub	sp, sp, #0x18
+;;   80:	 fc030091             	mov	x28, sp
+;;   84:	 808342f8             	ldur	x0, [x28, #0x28]
+;;   88:	 810342f8             	ldur	x1, [x28, #0x20]
+;;   8c:	 828341b8             	ldur	w2, [x28, #0x18]
+;;   90:	 230080d2             	mov	x3, #1
+;;   94:	 440080d2             	mov	x4, #2
+;;   98:	 650080d2             	mov	x5, #3
+;;   9c:	 860080d2             	mov	x6, #4
+;;   a0:	 a70080d2             	mov	x7, #5
+;;   a4:	 d00080d2             	mov	x16, #6
+;;   a8:	 900300b8             	stur	w16, [x28]
+;;   ac:	 f00080d2             	mov	x16, #7
+;;   b0:	 908300b8             	stur	w16, [x28, #8]
+;;   b4:	 100180d2             	mov	x16, #8
+;;   b8:	 900301b8             	stur	w16, [x28, #0x10]
+;;   bc:	 00000094             	bl	#0xbc
+;;   c0:	 ffc30091             	add	sp, sp, #0x30
+;;   c4:	 fc030091             	mov	x28, sp
+;;   c8:	 814340b8             	ldur	w1, [x28, #4]
+;;   cc:	 820340b8             	ldur	w2, [x28]
+;;   d0:	 3f000071             	cmp	w1, #0
+;;   d4:	 60050054             	b.eq	#0x180
+;;   d8:	 e103012a             	mov	w1, w1
+;;   dc:	 e203022a             	mov	w2, w2
+;;   e0:	 4208c19a             	udiv	x2, x2, x1
+;;   e4:	 818340f8             	ldur	x1, [x28, #8]
+;;   e8:	 838340f8             	ldur	x3, [x28, #8]
+;;   ec:	 ff2300d1             	sub	sp, sp, #8
+;;   f0:	 fc030091             	mov	x28, sp
+;;   f4:	 810300f8             	stur	x1, [x28]
+;;   f8:	 ff2300d1             	sub	sp, sp, #8
+;;   fc:	 fc030091             	mov	x28, sp
+;;  100:	 830300f8             	stur	x3, [x28]
+;;  104:	 ff2300d1             	sub	sp, sp, #8
+;;  108:	 fc030091             	mov	x28, sp
+;;  10c:	 800300f8             	stur	x0, [x28]
+;;  110:	 ff2300d1             	sub	sp, sp, #8
+;;  114:	 fc030091             	mov	x28, sp
+;;  118:	 820300f8             	stur	x2, [x28]
+;;  11c:	 ff8300d1             	sub	sp, sp, #0x20
+;;  120:	 fc030091             	mov	x28, sp
+;;  124:	 808343f8             	ldur	x0, [x28, #0x38]
+;;  128:	 810343f8             	ldur	x1, [x28, #0x30]
+;;  12c:	 828342b8             	ldur	w2, [x28, #0x28]
+;;  130:	 830342b8             	ldur	w3, [x28, #0x20]
+;;  134:	 440080d2             	mov	x4, #2
+;;  138:	 650080d2             	mov	x5, #3
+;;  13c:	 860080d2             	mov	x6, #4
+;;  140:	 a70080d2             	mov	x7, #5
+;;  144:	 d00080d2             	mov	x16, #6
+;;  148:	 900300b8             	stur	w16, [x28]
+;;  14c:	 f00080d2             	mov	x16, #7
+;;  150:	 908300b8             	stur	w16, [x28, #8]
+;;  154:	 100180d2             	mov	x16, #8
+;;  158:	 900301b8             	stur	w16, [x28, #0x10]
+;;  15c:	 00000094             	bl	#0x15c
+;;  160:	 ff030191             	add	sp, sp, #0x40
+;;  164:	 fc030091             	mov	x28, sp
+;;  168:	 ff430091             	add	sp, sp, #0x10
+;;  16c:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;  170:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;  174:	 c0035fd6             	ret	
+;;  178:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;  17c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;  180:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff8300d1             	sub	sp, sp, #0x20
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 c3030054             	b.lo	#0x9c
+;;   28:	 808301f8             	stur	x0, [x28, #0x18]
+;;   2c:	 824301b8             	stur	w2, [x28, #0x14]
+;;   30:	 830301b8             	stur	w3, [x28, #0x10]
+;;   34:	 84c300b8             	stur	w4, [x28, #0xc]
+;;   38:	 858300b8             	stur	w5, [x28, #8]
+;;   3c:	 864300b8             	stur	w6, [x28, #4]
+;;   40:	 870300b8             	stur	w7, [x28]
+;;   44:	 800341b8             	ldur	w0, [x28, #0x10]
+;;   48:	 814341b8             	ldur	w1, [x28, #0x14]
+;;   4c:	 2160200b             	add	w1, w1, w0, uxtx
+;;   50:	 80c340b8             	ldur	w0, [x28, #0xc]
+;;   54:	 2160200b             	add	w1, w1, w0, uxtx
+;;   58:	 808340b8             	ldur	w0, [x28, #8]
+;;   5c:	 2160200b             	add	w1, w1, w0, uxtx
+;;   60:	 804340b8             	ldur	w0, [x28, #4]
+;;   64:	 2160200b             	add	w1, w1, w0, uxtx
+;;   68:	 800340b8             	ldur	w0, [x28]
+;;   6c:	 2160200b             	add	w1, w1, w0, uxtx
+;;   70:	 a00341b8             	ldur	w0, [x29, #0x10]
+;;   74:	 2160200b             	add	w1, w1, w0, uxtx
+;;   78:	 a08341b8             	ldur	w0, [x29, #0x18]
+;;   7c:	 2160200b             	add	w1, w1, w0, uxtx
+;;   80:	 a00342b8             	ldur	w0, [x29, #0x20]
+;;   84:	 2160200b             	add	w1, w1, w0, uxtx
+;;   88:	 e003012a             	mov	w0, w1
+;;   8c:	 ff830091             	add	sp, sp, #0x20
+;;   90:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   94:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   98:	 c0035fd6             	ret	
+;;   9c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,100 @@
+;;! target = "aarch64"
+
+(module
+    (type $t (func (param i32) (result i32)))
+    (table 2 funcref)
+    (func (param i32) (param i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (call_indirect (type $t))
+    )
+)
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 83090054             	b.lo	#0x154
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 824300b8             	stur	w2, [x28, #4]
+;;   30:	 830300b8             	stur	w3, [x28]
+;;   34:	 904340b8             	ldur	w16, [x28, #4]
+;;   38:	 ff2300d1             	sub	sp, sp, #8
+;;   3c:	 fc030091             	mov	x28, sp
+;;   40:	 900300f8             	stur	x16, [x28]
+;;   44:	 908340b8             	ldur	w16, [x28, #8]
+;;   48:	 ff2300d1             	sub	sp, sp, #8
+;;   4c:	 fc030091             	mov	x28, sp
+;;   50:	 900300f8             	stur	x16, [x28]
+;;   54:	 800340f8             	ldur	x0, [x28]
+;;   58:	 ff230091             	add	sp, sp, #8
+;;   5c:	 fc030091             	mov	x28, sp
+;;   60:	 810341f8             	ldur	x1, [x28, #0x10]
+;;   64:	 308045b8             	ldur	w16, [x1, #0x58]
+;;   68:	 1f00106b             	cmp	w0, w16
+;;   6c:	 62070054             	b.hs	#0x158
+;;   70:	 210045f8             	ldur	x1, [x1, #0x50]
+;;   74:	 f003002a             	mov	w16, w0
+;;   78:	 10f27dd3             	lsl	x16, x16, #3
+;;   7c:	 2160308b             	add	x1, x1, x16, uxtx
+;;   80:	 220040f8             	ldur	x2, [x1]
+;;   84:	 42f87f92             	and	x2, x2, #0xfffffffffffffffe
+;;   88:	 5f0000f1             	cmp	x2, #0
+;;   8c:	 81020054             	b.ne	#0xdc
+;;   90:	 810341f8             	ldur	x1, [x28, #0x10]
+;;   94:	 ff2300d1             	sub	sp, sp, #8
+;;   98:	 fc030091             	mov	x28, sp
+;;   9c:	 810300f8             	stur	x1, [x28]
+;;   a0:	 ff2300d1             	sub	sp, sp, #8
+;;   a4:	 fc030091             	mov	x28, sp
+;;   a8:	 800300f8             	stur	x0, [x28]
+;;   ac:	 910342f8             	ldur	x17, [x28, #0x20]
+;;   b0:	 318243f8             	ldur	x17, [x17, #0x38]
+;;   b4:	 318244f8             	ldur	x17, [x17, #0x48]
+;;   b8:	 ff2300d1             	sub	sp, sp, #8
+;;   bc:	 fc030091             	mov	x28, sp
+;;   c0:	 800341f8             	ldur	x0, [x28, #0x10]
+;;   c4:	 010080d2             	mov	x1, #0
+;;   c8:	 828340b8             	ldur	w2, [x28, #8]
+;;   cc:	 20023fd6             	blr	x17
+;;   d0:	 ff630091             	add	sp, sp, #0x18
+;;   d4:	 fc030091             	mov	x28, sp
+;;   d8:	 e20300aa             	mov	x2, x0
+;;   dc:	 5f0000f1             	cmp	x2, #0
+;;   e0:	 e0030054             	b.eq	#0x15c
+;;   e4:	 900341f8             	ldur	x16, [x28, #0x10]
+;;   e8:	 100244f8             	ldur	x16, [x16, #0x40]
+;;   ec:	 100240b8             	ldur	w16, [x16]
+;;   f0:	 408040b8             	ldur	w0, [x2, #8]
+;;   f4:	 1f00106b             	cmp	w0, w16
+;;   f8:	 41030054             	b.ne	#0x160
+;;   fc:	 400041f8             	ldur	x0, [x2, #0x10]
+;;  100:	 420040f8             	ldur	x2, [x2]
+;;  104:	 810341f8             	ldur	x1, [x28, #0x10]
+;;  108:	 ff2300d1             	sub	sp, sp, #8
+;;  10c:	 fc030091             	mov	x28, sp
+;;  110:	 800300f8             	stur	x0, [x28]
+;;  114:	 ff2300d1             	sub	sp, sp, #8
+;;  118:	 fc030091             	mov	x28, sp
+;;  11c:	 810300f8             	stur	x1, [x28]
+;;  120:	 f10302aa             	mov	x17, x2
+;;  124:	 ff2300d1             	sub	sp, sp, #8
+;;  128:	 fc030091             	mov	x28, sp
+;;  12c:	 800341f8             	ldur	x0, [x28, #0x10]
+;;  130:	 818340f8             	ldur	x1, [x28, #8]
+;;  134:	 828341b8             	ldur	w2, [x28, #0x18]
+;;  138:	 20023fd6             	blr	x17
+;;  13c:	 ff830091             	add	sp, sp, #0x20
+;;  140:	 fc030091             	mov	x28, sp
+;;  144:	 ff430091             	add	sp, sp, #0x10
+;;  148:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;  14c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;  150:	 c0035fd6             	ret	
+;;  154:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;  158:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;  15c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;  160:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,44 @@
+;;! target = "aarch64"
+
+(module
+    (func (param i32) (result i32)
+        (block
+            (block
+                (block
+                    (br_table 0 1 2 (local.get 0))
+                )
+                (return (i32.const 10))
+            )
+            (return (i32.const 20))
+        )
+        (i32.const 30)
+    )
+)
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 43020054             	b.lo	#0x6c
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 824300b8             	stur	w2, [x28, #4]
+;;   30:	 804340b8             	ldur	w0, [x28, #4]
+;;   34:	 1f000071             	cmp	w0, #0
+;;   38:	 80000054             	b.eq	#0x48
+;;   3c:	 1f040071             	cmp	w0, #1
+;;   40:	 80000054             	b.eq	#0x50
+;;   44:	 05000014             	b	#0x58
+;;   48:	 400180d2             	mov	x0, #0xa
+;;   4c:	 04000014             	b	#0x5c
+;;   50:	 800280d2             	mov	x0, #0x14
+;;   54:	 02000014             	b	#0x5c
+;;   58:	 c00380d2             	mov	x0, #0x1e
+;;   5c:	 ff430091             	add	sp, sp, #0x10
+;;   60:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   64:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   68:	 c0035fd6             	ret	
+;;   6c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -0,0 +1,40 @@
+;;! target = "aarch64"
+
+(module
+    (func (param f32) (result i32)
+        (local.get 0)
+        (i32.trunc_f32_s)
+    )
+)
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 63020054             	b.lo	#0x70
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 804300bc             	stur	s0, [x28, #4]
+;;   30:	 804340bc             	ldur	s0, [x28, #4]
+;;   34:	 0020201e             	fcmp	s0, s0
+;;   38:	 e6010054             	b.vs	#0x74
+;;   3c:	 10e0b9d2             	mov	x16, #0xcf000000
+;;   40:	 1f02271e             	fmov	s31, w16
+;;   44:	 00203f1e             	fcmp	s0, s31
+;;   48:	 84010054             	b.mi	#0x78
+;;   4c:	 10e0a9d2             	mov	x16, #0x4f000000
+;;   50:	 1f02271e             	fmov	s31, w16
+;;   54:	 00203f1e             	fcmp	s0, s31
+;;   58:	 2a010054             	b.ge	#0x7c
+;;   5c:	 0000381e             	fcvtzs	w0, s0
+;;   60:	 ff430091             	add	sp, sp, #0x10
+;;   64:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   68:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   6c:	 c0035fd6             	ret	
+;;   70:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   74:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   78:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   7c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
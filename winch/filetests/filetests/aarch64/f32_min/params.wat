@@ -0,0 +1,31 @@
+;;! target = "aarch64"
+
+(module
+    (func (param f32) (param f32) (result f32)
+        (local.get 0)
+        (local.get 1)
+        (f32.min)
+    )
+)
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 83010054             	b.lo	#0x54
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 804300bc             	stur	s0, [x28, #4]
+;;   30:	 810300bc             	stur	s1, [x28]
+;;   34:	 800340bc             	ldur	s0, [x28]
+;;   38:	 814340bc             	ldur	s1, [x28, #4]
+;;   3c:	 2158201e             	fmin	s1, s1, s0
+;;   40:	 2040601e             	fmov	d0, d1
+;;   44:	 ff430091             	add	sp, sp, #0x10
+;;   48:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   4c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   50:	 c0035fd6             	ret	
+;;   54:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
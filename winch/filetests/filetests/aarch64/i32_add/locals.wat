@@ -18,22 +18,27 @@
 )
 ;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;    4:	 fd030091             	mov	x29, sp
-;;    8:	 fc030091             	mov	x28, sp
-;;    c:	 ff2300d1             	sub	sp, sp, #8
-;;   10:	 fc030091             	mov	x28, sp
-;;   14:	 100080d2             	mov	x16, #0
-;;   18:	 900300f8             	stur	x16, [x28]
-;;   1c:	 500180d2             	mov	x16, #0xa
-;;   20:	 e003102a             	mov	w0, w16
-;;   24:	 804300b8             	stur	w0, [x28, #4]
-;;   28:	 900280d2             	mov	x16, #0x14
-;;   2c:	 e003102a             	mov	w0, w16
-;;   30:	 800300b8             	stur	w0, [x28]
-;;   34:	 800340b8             	ldur	w0, [x28]
-;;   38:	 814340b8             	ldur	w1, [x28, #4]
-;;   3c:	 2160200b             	add	w1, w1, w0, uxtx
-;;   40:	 e00301aa             	mov	x0, x1
-;;   44:	 ff230091             	add	sp, sp, #8
-;;   48:	 fc030091             	mov	x28, sp
-;;   4c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
-;;   50:	 c0035fd6             	ret	
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 03020054             	b.lo	#0x64
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 100080d2             	mov	x16, #0
+;;   30:	 900300f8             	stur	x16, [x28]
+;;   34:	 400180d2             	mov	x0, #0xa
+;;   38:	 804300b8             	stur	w0, [x28, #4]
+;;   3c:	 800280d2             	mov	x0, #0x14
+;;   40:	 800300b8             	stur	w0, [x28]
+;;   44:	 800340b8             	ldur	w0, [x28]
+;;   48:	 814340b8             	ldur	w1, [x28, #4]
+;;   4c:	 2160200b             	add	w1, w1, w0, uxtx
+;;   50:	 e003012a             	mov	w0, w1
+;;   54:	 ff430091             	add	sp, sp, #0x10
+;;   58:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   5c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   60:	 c0035fd6             	ret	
+;;   64:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -8,9 +8,19 @@
 )
 ;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;    4:	 fd030091             	mov	x29, sp
-;;    8:	 fc030091             	mov	x28, sp
-;;    c:	 f07b40b2             	orr	x16, xzr, #0x7fffffff
-;;   10:	 e003102a             	mov	w0, w16
-;;   14:	 00040011             	add	w0, w0, #1
-;;   18:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
-;;   1c:	 c0035fd6             	ret	
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff2300d1             	sub	sp, sp, #8
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 03010054             	b.lo	#0x44
+;;   28:	 800300f8             	stur	x0, [x28]
+;;   2c:	 e07b40b2             	orr	x0, xzr, #0x7fffffff
+;;   30:	 00040011             	add	w0, w0, #1
+;;   34:	 ff230091             	add	sp, sp, #8
+;;   38:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   3c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   40:	 c0035fd6             	ret	
+;;   44:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -9,10 +9,20 @@
 )
 ;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;    4:	 fd030091             	mov	x29, sp
-;;    8:	 fc030091             	mov	x28, sp
-;;    c:	 10008092             	mov	x16, #-1
-;;   10:	 e003102a             	mov	w0, w16
-;;   14:	 10008092             	mov	x16, #-1
-;;   18:	 0060300b             	add	w0, w0, w16, uxtx
-;;   1c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
-;;   20:	 c0035fd6             	ret	
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff2300d1             	sub	sp, sp, #8
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 23010054             	b.lo	#0x48
+;;   28:	 800300f8             	stur	x0, [x28]
+;;   2c:	 00008092             	mov	x0, #-1
+;;   30:	 10008092             	mov	x16, #-1
+;;   34:	 0060300b             	add	w0, w0, w16, uxtx
+;;   38:	 ff230091             	add	sp, sp, #8
+;;   3c:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   40:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   44:	 c0035fd6             	ret	
+;;   48:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
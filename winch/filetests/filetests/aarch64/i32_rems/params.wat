@@ -0,0 +1,37 @@
+;;! target = "aarch64"
+
+(module
+    (func (param i32) (param i32) (result i32)
+	(local.get 0)
+	(local.get 1)
+	(i32.rem_s)
+    )
+)
+;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
+;;    4:	 fd030091             	mov	x29, sp
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff4300d1             	sub	sp, sp, #0x10
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 23020054             	b.lo	#0x68
+;;   28:	 808300f8             	stur	x0, [x28, #8]
+;;   2c:	 824300b8             	stur	w2, [x28, #4]
+;;   30:	 830300b8             	stur	w3, [x28]
+;;   34:	 800340b8             	ldur	w0, [x28]
+;;   38:	 814340b8             	ldur	w1, [x28, #4]
+;;   3c:	 1f000071             	cmp	w0, #0
+;;   40:	 60010054             	b.eq	#0x6c
+;;   44:	 007c4093             	sxtw	x0, w0
+;;   48:	 217c4093             	sxtw	x1, w1
+;;   4c:	 300cc09a             	sdiv	x16, x1, x0
+;;   50:	 0186001b             	msub	w1, w16, w0, w1
+;;   54:	 e003012a             	mov	w0, w1
+;;   58:	 ff430091             	add	sp, sp, #0x10
+;;   5c:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   60:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   64:	 c0035fd6             	ret	
+;;   68:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
+;;   6c:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
@@ -18,23 +18,28 @@
 )
 ;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;    4:	 fd030091             	mov	x29, sp
-;;    8:	 fc030091             	mov	x28, sp
-;;    c:	 ff4300d1             	sub	sp, sp, #0x10
-;;   10:	 fc030091             	mov	x28, sp
-;;   14:	 100080d2             	mov	x16, #0
-;;   18:	 908300f8             	stur	x16, [x28, #8]
-;;   1c:	 900300f8             	stur	x16, [x28]
-;;   20:	 500180d2             	mov	x16, #0xa
-;;   24:	 e00310aa             	mov	x0, x16
-;;   28:	 808300f8             	stur	x0, [x28, #8]
-;;   2c:	 900280d2             	mov	x16, #0x14
-;;   30:	 e00310aa             	mov	x0, x16
-;;   34:	 800300f8             	stur	x0, [x28]
-;;   38:	 800340f8             	ldur	x0, [x28]
-;;   3c:	 818340f8             	ldur	x1, [x28, #8]
-;;   40:	 2160208b             	add	x1, x1, x0, uxtx
-;;   44:	 e00301aa             	mov	x0, x1
-;;   48:	 ff430091             	add	sp, sp, #0x10
-;;   4c:	 fc030091             	mov	x28, sp
-;;   50:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
-;;   54:	 c0035fd6             	ret	
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff6300d1             	sub	sp, sp, #0x18
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 23020054             	b.lo	#0x68
+;;   28:	 800301f8             	stur	x0, [x28, #0x10]
+;;   2c:	 100080d2             	mov	x16, #0
+;;   30:	 908300f8             	stur	x16, [x28, #8]
+;;   34:	 900300f8             	stur	x16, [x28]
+;;   38:	 400180d2             	mov	x0, #0xa
+;;   3c:	 808300f8             	stur	x0, [x28, #8]
+;;   40:	 800280d2             	mov	x0, #0x14
+;;   44:	 800300f8             	stur	x0, [x28]
+;;   48:	 800340f8             	ldur	x0, [x28]
+;;   4c:	 818340f8             	ldur	x1, [x28, #8]
+;;   50:	 2160208b             	add	x1, x1, x0, uxtx
+;;   54:	 e00301aa             	mov	x0, x1
+;;   58:	 ff630091             	add	sp, sp, #0x18
+;;   5c:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   60:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   64:	 c0035fd6             	ret	
+;;   68:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
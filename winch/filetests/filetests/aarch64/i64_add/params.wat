@@ -9,16 +9,23 @@
 )
 ;;    0:	 fd7bbfa9             	stp	x29, x30, [sp, #-0x10]!
 ;;    4:	 fd030091             	mov	x29, sp
-;;    8:	 fc030091             	mov	x28, sp
-;;    c:	 ff4300d1             	sub	sp, sp, #0x10
-;;   10:	 fc030091             	mov	x28, sp
-;;   14:	 808300f8             	stur	x0, [x28, #8]
-;;   18:	 810300f8             	stur	x1, [x28]
-;;   1c:	 800340f8             	ldur	x0, [x28]
-;;   20:	 818340f8             	ldur	x1, [x28, #8]
-;;   24:	 2160208b             	add	x1, x1, x0, uxtx
-;;   28:	 e00301aa             	mov	x0, x1
-;;   2c:	 ff430091             	add	sp, sp, #0x10
-;;   30:	 fc030091             	mov	x28, sp
-;;   34:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
-;;   38:	 c0035fd6             	ret	
+;;    8:	 fc0f1ff8             	str	x28, [sp, #-0x10]!
+;;    c:	 fc030091             	mov	x28, sp
+;;   10:	 ff6300d1             	sub	sp, sp, #0x18
+;;   14:	 fc030091             	mov	x28, sp
+;;   18:	 108040f8             	ldur	x16, [x0, #8]
+;;   1c:	 100240f8             	ldur	x16, [x16]
+;;   20:	 ff6330eb             	cmp	sp, x16
+;;   24:	 83010054             	b.lo	#0x54
+;;   28:	 800301f8             	stur	x0, [x28, #0x10]
+;;   2c:	 828300f8             	stur	x2, [x28, #8]
+;;   30:	 830300f8             	stur	x3, [x28]
+;;   34:	 800340f8             	ldur	x0, [x28]
+;;   38:	 818340f8             	ldur	x1, [x28, #8]
+;;   3c:	 2160208b             	add	x1, x1, x0, uxtx
+;;   40:	 e00301aa             	mov	x0, x1
+;;   44:	 ff630091             	add	sp, sp, #0x18
+;;   48:	 fc0741f8             	ldr	x28, [sp], #0x10
+;;   4c:	 fd7bc1a8             	ldp	x29, x30, [sp], #0x10
+;;   50:	 c0035fd6             	ret	
+;;   54:	 1fc10000             	.byte	0x1f, 0xc1, 0x00, 0x00
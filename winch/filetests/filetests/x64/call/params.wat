@@ -37,85 +37,111 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec08             	sub	rsp, 8
-;;    8:	 897c2404             	mov	dword ptr [rsp + 4], edi
-;;    c:	 893424               	mov	dword ptr [rsp], esi
-;;    f:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
-;;   13:	 8b0424               	mov	eax, dword ptr [rsp]
-;;   16:	 31d2                 	xor	edx, edx
-;;   18:	 f7f1                 	div	ecx
-;;   1a:	 50                   	push	rax
-;;   1b:	 4883ec20             	sub	rsp, 0x20
-;;   1f:	 8b7c2420             	mov	edi, dword ptr [rsp + 0x20]
-;;   23:	 be01000000           	mov	esi, 1
-;;   28:	 ba02000000           	mov	edx, 2
-;;   2d:	 b903000000           	mov	ecx, 3
-;;   32:	 41b804000000         	mov	r8d, 4
-;;   38:	 41b905000000         	mov	r9d, 5
-;;   3e:	 41bb06000000         	mov	r11d, 6
-;;   44:	 44891c24             	mov	dword ptr [rsp], r11d
-;;   48:	 41bb07000000         	mov	r11d, 7
-;;   4e:	 44895c2408           	mov	dword ptr [rsp + 8], r11d
-;;   53:	 41bb08000000         	mov	r11d, 8
-;;   59:	 44895c2410           	mov	dword ptr [rsp + 0x10], r11d
-;;   5e:	 e800000000           	call	0x63
-;;   63:	 4883c428             	add	rsp, 0x28
-;;   67:	 50                   	push	rax
-;;   68:	 448b5c2408           	mov	r11d, dword ptr [rsp + 8]
-;;   6d:	 4153                 	push	r11
-;;   6f:	 448b5c2414           	mov	r11d, dword ptr [rsp + 0x14]
-;;   74:	 4153                 	push	r11
-;;   76:	 59                   	pop	rcx
-;;   77:	 58                   	pop	rax
-;;   78:	 31d2                 	xor	edx, edx
-;;   7a:	 f7f1                 	div	ecx
-;;   7c:	 50                   	push	rax
-;;   7d:	 4883ec20             	sub	rsp, 0x20
-;;   81:	 8b7c2428             	mov	edi, dword ptr [rsp + 0x28]
-;;   85:	 8b742420             	mov	esi, dword ptr [rsp + 0x20]
-;;   89:	 ba02000000           	mov	edx, 2
-;;   8e:	 b903000000           	mov	ecx, 3
-;;   93:	 41b804000000         	mov	r8d, 4
-;;   99:	 41b905000000         	mov	r9d, 5
-;;   9f:	 41bb06000000         	mov	r11d, 6
-;;   a5:	 44891c24             	mov	dword ptr [rsp], r11d
-;;   a9:	 41bb07000000         	mov	r11d, 7
-;;   af:	 44895c2408           	mov	dword ptr [rsp + 8], r11d
-;;   b4:	 41bb08000000         	mov	r11d, 8
-;;   ba:	 44895c2410           	mov	dword ptr [rsp + 0x10], r11d
-;;   bf:	 e800000000           	call	0xc4
-;;   c4:	 4883c430             	add	rsp, 0x30
-;;   c8:	 4883c408             	add	rsp, 8
-;;   cc:	 5d                   	pop	rbp
-;;   cd:	 c3                   	ret	
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f820f010000         	jb	0x127
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   28:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   2b:	 31d2                 	xor	edx, edx
+;;   2d:	 f7f1                 	div	ecx
+;;   2f:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;   34:	 488b542408           	mov	rdx, qword ptr [rsp + 8]
+;;   39:	 51                   	push	rcx
+;;   3a:	 52                   	push	rdx
+;;   3b:	 50                   	push	rax
+;;   3c:	 4883ec28             	sub	rsp, 0x28
+;;   40:	 488b7c2438           	mov	rdi, qword ptr [rsp + 0x38]
+;;   45:	 488b742430           	mov	rsi, qword ptr [rsp + 0x30]
+;;   4a:	 8b542428             	mov	edx, dword ptr [rsp + 0x28]
+;;   4e:	 b901000000           	mov	ecx, 1
+;;   53:	 41b802000000         	mov	r8d, 2
+;;   59:	 41b903000000         	mov	r9d, 3
+;;   5f:	 41bb04000000         	mov	r11d, 4
+;;   65:	 44891c24             	mov	dword ptr [rsp], r11d
+;;   69:	 41bb05000000         	mov	r11d, 5
+;;   6f:	 44895c2408           	mov	dword ptr [rsp + 8], r11d
+;;   74:	 41bb06000000         	mov	r11d, 6
+;;   7a:	 44895c2410           	mov	dword ptr [rsp + 0x10], r11d
+;;   7f:	 41bb07000000         	mov	r11d, 7
+;;   85:	 44895c2418           	mov	dword ptr [rsp + 0x18], r11d
+;;   8a:	 41bb08000000         	mov	r11d, 8
+;;   90:	 44895c2420           	mov	dword ptr [rsp + 0x20], r11d
+;;   95:	 e800000000           	call	0x9a
+;;   9a:	 4883c440             	add	rsp, 0x40
+;;   9e:	 50                   	push	rax
+;;   9f:	 448b5c2408           	mov	r11d, dword ptr [rsp + 8]
+;;   a4:	 4153                 	push	r11
+;;   a6:	 448b5c2414           	mov	r11d, dword ptr [rsp + 0x14]
+;;   ab:	 4153                 	push	r11
+;;   ad:	 59                   	pop	rcx
+;;   ae:	 58                   	pop	rax
+;;   af:	 31d2                 	xor	edx, edx
+;;   b1:	 f7f1                 	div	ecx
+;;   b3:	 488b4c2410           	mov	rcx, qword ptr [rsp + 0x10]
+;;   b8:	 488b542410           	mov	rdx, qword ptr [rsp + 0x10]
+;;   bd:	 51                   	push	rcx
+;;   be:	 52                   	push	rdx
+;;   bf:	 50                   	push	rax
+;;   c0:	 4883ec30             	sub	rsp, 0x30
+;;   c4:	 488b7c2440           	mov	rdi, qword ptr [rsp + 0x40]
+;;   c9:	 488b742438           	mov	rsi, qword ptr [rsp + 0x38]
+;;   ce:	 8b542448             	mov	edx, dword ptr [rsp + 0x48]
+;;   d2:	 8b4c2430             	mov	ecx, dword ptr [rsp + 0x30]
+;;   d6:	 41b802000000         	mov	r8d, 2
+;;   dc:	 41b903000000         	mov	r9d, 3
+;;   e2:	 41bb04000000         	mov	r11d, 4
+;;   e8:	 44891c24             	mov	dword ptr [rsp], r11d
+;;   ec:	 41bb05000000         	mov	r11d, 5
+;;   f2:	 44895c2408           	mov	dword ptr [rsp + 8], r11d
+;;   f7:	 41bb06000000         	mov	r11d, 6
+;;   fd:	 44895c2410           	mov	dword ptr [rsp + 0x10], r11d
+;;  102:	 41bb07000000         	mov	r11d, 7
+;;  108:	 44895c2418           	mov	dword ptr [rsp + 0x18], r11d
+;;  10d:	 41bb08000000         	mov	r11d, 8
+;;  113:	 44895c2420           	mov	dword ptr [rsp + 0x20], r11d
+;;  118:	 e800000000           	call	0x11d
+;;  11d:	 4883c450             	add	rsp, 0x50
+;;  121:	 4883c410             	add	rsp, 0x10
+;;  125:	 5d                   	pop	rbp
+;;  126:	 c3                   	ret	
+;;  127:	 0f0b                 	ud2	
 ;;
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
 ;;    4:	 4883ec18             	sub	rsp, 0x18
-;;    8:	 897c2414             	mov	dword ptr [rsp + 0x14], edi
-;;    c:	 89742410             	mov	dword ptr [rsp + 0x10], esi
-;;   10:	 8954240c             	mov	dword ptr [rsp + 0xc], edx
-;;   14:	 894c2408             	mov	dword ptr [rsp + 8], ecx
-;;   18:	 4489442404           	mov	dword ptr [rsp + 4], r8d
-;;   1d:	 44890c24             	mov	dword ptr [rsp], r9d
-;;   21:	 8b442410             	mov	eax, dword ptr [rsp + 0x10]
-;;   25:	 8b4c2414             	mov	ecx, dword ptr [rsp + 0x14]
-;;   29:	 01c1                 	add	ecx, eax
-;;   2b:	 8b44240c             	mov	eax, dword ptr [rsp + 0xc]
-;;   2f:	 01c1                 	add	ecx, eax
-;;   31:	 8b442408             	mov	eax, dword ptr [rsp + 8]
-;;   35:	 01c1                 	add	ecx, eax
-;;   37:	 8b442404             	mov	eax, dword ptr [rsp + 4]
-;;   3b:	 01c1                 	add	ecx, eax
-;;   3d:	 8b0424               	mov	eax, dword ptr [rsp]
-;;   40:	 01c1                 	add	ecx, eax
-;;   42:	 8b4510               	mov	eax, dword ptr [rbp + 0x10]
-;;   45:	 01c1                 	add	ecx, eax
-;;   47:	 8b4518               	mov	eax, dword ptr [rbp + 0x18]
-;;   4a:	 01c1                 	add	ecx, eax
-;;   4c:	 8b4520               	mov	eax, dword ptr [rbp + 0x20]
-;;   4f:	 01c1                 	add	ecx, eax
-;;   51:	 4889c8               	mov	rax, rcx
-;;   54:	 4883c418             	add	rsp, 0x18
-;;   58:	 5d                   	pop	rbp
-;;   59:	 c3                   	ret	
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f824c000000         	jb	0x64
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 8954240c             	mov	dword ptr [rsp + 0xc], edx
+;;   21:	 894c2408             	mov	dword ptr [rsp + 8], ecx
+;;   25:	 4489442404           	mov	dword ptr [rsp + 4], r8d
+;;   2a:	 44890c24             	mov	dword ptr [rsp], r9d
+;;   2e:	 8b442408             	mov	eax, dword ptr [rsp + 8]
+;;   32:	 8b4c240c             	mov	ecx, dword ptr [rsp + 0xc]
+;;   36:	 01c1                 	add	ecx, eax
+;;   38:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   3c:	 01c1                 	add	ecx, eax
+;;   3e:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   41:	 01c1                 	add	ecx, eax
+;;   43:	 8b4510               	mov	eax, dword ptr [rbp + 0x10]
+;;   46:	 01c1                 	add	ecx, eax
+;;   48:	 8b4518               	mov	eax, dword ptr [rbp + 0x18]
+;;   4b:	 01c1                 	add	ecx, eax
+;;   4d:	 8b4520               	mov	eax, dword ptr [rbp + 0x20]
+;;   50:	 01c1                 	add	ecx, eax
+;;   52:	 8b4528               	mov	eax, dword ptr [rbp + 0x28]
+;;   55:	 01c1                 	add	ecx, eax
+;;   57:	 8b4530               	mov	eax, dword ptr [rbp + 0x30]
+;;   5a:	 01c1                 	add	ecx, eax
+;;   5c:	 89c8                 	mov	eax, ecx
+;;   5e:	 4883c418             	add	rsp, 0x18
+;;   62:	 5d                   	pop	rbp
+;;   63:	 c3                   	ret	
+;;   64:	 0f0b                 	ud2	
@@ -15,35 +15,52 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec08             	sub	rsp, 8
-;;    8:	 48c7042400000000     	mov	qword ptr [rsp], 0
-;;   10:	 4883ec10             	sub	rsp, 0x10
-;;   14:	 bf14000000           	mov	edi, 0x14
-;;   19:	 be50000000           	mov	esi, 0x50
-;;   1e:	 e800000000           	call	0x23
-;;   23:	 4883c410             	add	rsp, 0x10
-;;   27:	 b902000000           	mov	ecx, 2
-;;   2c:	 894c2404             	mov	dword ptr [rsp + 4], ecx
-;;   30:	 50                   	push	rax
-;;   31:	 448b5c240c           	mov	r11d, dword ptr [rsp + 0xc]
-;;   36:	 4153                 	push	r11
-;;   38:	 59                   	pop	rcx
-;;   39:	 58                   	pop	rax
-;;   3a:	 31d2                 	xor	edx, edx
-;;   3c:	 f7f1                 	div	ecx
-;;   3e:	 4883c408             	add	rsp, 8
-;;   42:	 5d                   	pop	rbp
-;;   43:	 c3                   	ret	
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8252000000         	jb	0x6a
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 48c7042400000000     	mov	qword ptr [rsp], 0
+;;   25:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   2a:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;   2f:	 50                   	push	rax
+;;   30:	 51                   	push	rcx
+;;   31:	 488b7c2408           	mov	rdi, qword ptr [rsp + 8]
+;;   36:	 488b3424             	mov	rsi, qword ptr [rsp]
+;;   3a:	 ba14000000           	mov	edx, 0x14
+;;   3f:	 b950000000           	mov	ecx, 0x50
+;;   44:	 e800000000           	call	0x49
+;;   49:	 4883c410             	add	rsp, 0x10
+;;   4d:	 b902000000           	mov	ecx, 2
+;;   52:	 894c2404             	mov	dword ptr [rsp + 4], ecx
+;;   56:	 50                   	push	rax
+;;   57:	 448b5c240c           	mov	r11d, dword ptr [rsp + 0xc]
+;;   5c:	 4153                 	push	r11
+;;   5e:	 59                   	pop	rcx
+;;   5f:	 58                   	pop	rax
+;;   60:	 31d2                 	xor	edx, edx
+;;   62:	 f7f1                 	div	ecx
+;;   64:	 4883c410             	add	rsp, 0x10
+;;   68:	 5d                   	pop	rbp
+;;   69:	 c3                   	ret	
+;;   6a:	 0f0b                 	ud2	
 ;;
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec08             	sub	rsp, 8
-;;    8:	 897c2404             	mov	dword ptr [rsp + 4], edi
-;;    c:	 893424               	mov	dword ptr [rsp], esi
-;;    f:	 8b0424               	mov	eax, dword ptr [rsp]
-;;   12:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
-;;   16:	 0fafc8               	imul	ecx, eax
-;;   19:	 4889c8               	mov	rax, rcx
-;;   1c:	 4883c408             	add	rsp, 8
-;;   20:	 5d                   	pop	rbp
-;;   21:	 c3                   	ret	
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821e000000         	jb	0x36
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   27:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   2b:	 0fafc8               	imul	ecx, eax
+;;   2e:	 89c8                 	mov	eax, ecx
+;;   30:	 4883c410             	add	rsp, 0x10
+;;   34:	 5d                   	pop	rbp
+;;   35:	 c3                   	ret	
+;;   36:	 0f0b                 	ud2	
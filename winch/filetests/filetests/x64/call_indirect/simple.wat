@@ -0,0 +1,78 @@
+;;! target = "x86_64"
+
+(module
+    (type $t (func (param i32) (result i32)))
+    (table 2 funcref)
+    (func (param i32) (param i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (call_indirect (type $t))
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f82ce000000         	jb	0xe6
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 448b5c2404           	mov	r11d, dword ptr [rsp + 4]
+;;   29:	 4153                 	push	r11
+;;   2b:	 448b5c2408           	mov	r11d, dword ptr [rsp + 8]
+;;   30:	 4153                 	push	r11
+;;   32:	 58                   	pop	rax
+;;   33:	 488b4c2410           	mov	rcx, qword ptr [rsp + 0x10]
+;;   38:	 448b5958             	mov	r11d, dword ptr [rcx + 0x58]
+;;   3c:	 4439d8               	cmp	eax, r11d
+;;   3f:	 0f83a3000000         	jae	0xe8
+;;   45:	 488b4950             	mov	rcx, qword ptr [rcx + 0x50]
+;;   49:	 4189c3               	mov	r11d, eax
+;;   4c:	 4d6bdb08             	imul	r11, r11, 8
+;;   50:	 4c01d9               	add	rcx, r11
+;;   53:	 488b11               	mov	rdx, qword ptr [rcx]
+;;   56:	 4883e2fe             	and	rdx, 0xfffffffffffffffe
+;;   5a:	 4883fa00             	cmp	rdx, 0
+;;   5e:	 0f8530000000         	jne	0x94
+;;   64:	 488b4c2410           	mov	rcx, qword ptr [rsp + 0x10]
+;;   69:	 51                   	push	rcx
+;;   6a:	 50                   	push	rax
+;;   6b:	 4c8b542420           	mov	r10, qword ptr [rsp + 0x20]
+;;   70:	 4d8b5238             	mov	r10, qword ptr [r10 + 0x38]
+;;   74:	 4d8b5248             	mov	r10, qword ptr [r10 + 0x48]
+;;   78:	 4883ec08             	sub	rsp, 8
+;;   7c:	 488b7c2410           	mov	rdi, qword ptr [rsp + 0x10]
+;;   81:	 be00000000           	mov	esi, 0
+;;   86:	 8b542408             	mov	edx, dword ptr [rsp + 8]
+;;   8a:	 41ffd2               	call	r10
+;;   8d:	 4883c418             	add	rsp, 0x18
+;;   91:	 4889c2               	mov	rdx, rax
+;;   94:	 4883fa00             	cmp	rdx, 0
+;;   98:	 0f844c000000         	je	0xea
+;;   9e:	 4c8b5c2410           	mov	r11, qword ptr [rsp + 0x10]
+;;   a3:	 4d8b5b40             	mov	r11, qword ptr [r11 + 0x40]
+;;   a7:	 458b1b               	mov	r11d, dword ptr [r11]
+;;   aa:	 8b4208               	mov	eax, dword ptr [rdx + 8]
+;;   ad:	 4439d8               	cmp	eax, r11d
+;;   b0:	 0f8536000000         	jne	0xec
+;;   b6:	 488b4210             	mov	rax, qword ptr [rdx + 0x10]
+;;   ba:	 488b12               	mov	rdx, qword ptr [rdx]
+;;   bd:	 488b4c2410           	mov	rcx, qword ptr [rsp + 0x10]
+;;   c2:	 50                   	push	rax
+;;   c3:	 51                   	push	rcx
+;;   c4:	 4989d2               	mov	r10, rdx
+;;   c7:	 4883ec08             	sub	rsp, 8
+;;   cb:	 488b7c2410           	mov	rdi, qword ptr [rsp + 0x10]
+;;   d0:	 488b742408           	mov	rsi, qword ptr [rsp + 8]
+;;   d5:	 8b542418             	mov	edx, dword ptr [rsp + 0x18]
+;;   d9:	 41ffd2               	call	r10
+;;   dc:	 4883c420             	add	rsp, 0x20
+;;   e0:	 4883c410             	add	rsp, 0x10
+;;   e4:	 5d                   	pop	rbp
+;;   e5:	 c3                   	ret	
+;;   e6:	 0f0b                 	ud2	
+;;   e8:	 0f0b                 	ud2	
+;;   ea:	 0f0b                 	ud2	
+;;   ec:	 0f0b                 	ud2	
@@ -0,0 +1,31 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (block (result i32)
+            (i32.const 1)
+            (local.get 0)
+            (i32.add)
+        )
+        (i32.const 2)
+        (i32.mul)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821f000000         	jb	0x37
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   25:	 b901000000           	mov	ecx, 1
+;;   2a:	 01c1                 	add	ecx, eax
+;;   2c:	 89c8                 	mov	eax, ecx
+;;   2e:	 6bc002               	imul	eax, eax, 2
+;;   31:	 4883c410             	add	rsp, 0x10
+;;   35:	 5d                   	pop	rbp
+;;   36:	 c3                   	ret	
+;;   37:	 0f0b                 	ud2	
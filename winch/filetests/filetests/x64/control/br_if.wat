@@ -0,0 +1,32 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (block (result i32)
+            (i32.const 1)
+            (local.get 0)
+            (br_if 0)
+            (drop)
+            (i32.const 2)
+        )
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8228000000         	jb	0x40
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   25:	 89c1                 	mov	ecx, eax
+;;   27:	 b801000000           	mov	eax, 1
+;;   2c:	 83f900               	cmp	ecx, 0
+;;   2f:	 0f8505000000         	jne	0x3a
+;;   35:	 b802000000           	mov	eax, 2
+;;   3a:	 4883c410             	add	rsp, 0x10
+;;   3e:	 5d                   	pop	rbp
+;;   3f:	 c3                   	ret	
+;;   40:	 0f0b                 	ud2	
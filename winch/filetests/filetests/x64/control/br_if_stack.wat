@@ -0,0 +1,41 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (block (result i32)
+            (local.get 0)
+            (i32.const 1)
+            (i32.add)
+            (block
+                (drop (br_if 1 (i32.const 3) (local.get 0)))
+            )
+            (i32.const 2)
+            (i32.add)
+        )
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8238000000         	jb	0x50
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   25:	 83c001               	add	eax, 1
+;;   28:	 50                   	push	rax
+;;   29:	 8b44240c             	mov	eax, dword ptr [rsp + 0xc]
+;;   2d:	 89c1                 	mov	ecx, eax
+;;   2f:	 b803000000           	mov	eax, 3
+;;   34:	 83f900               	cmp	ecx, 0
+;;   37:	 0f8409000000         	je	0x46
+;;   3d:	 4883c408             	add	rsp, 8
+;;   41:	 e904000000           	jmp	0x4a
+;;   46:	 58                   	pop	rax
+;;   47:	 83c002               	add	eax, 2
+;;   4a:	 4883c410             	add	rsp, 0x10
+;;   4e:	 5d                   	pop	rbp
+;;   4f:	 c3                   	ret	
+;;   50:	 0f0b                 	ud2	
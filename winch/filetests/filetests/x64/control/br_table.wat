@@ -0,0 +1,41 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (block
+            (block
+                (block
+                    (br_table 0 1 2 (local.get 0))
+                )
+                (return (i32.const 10))
+            )
+            (return (i32.const 20))
+        )
+        (i32.const 30)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8248000000         	jb	0x60
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   25:	 83f800               	cmp	eax, 0
+;;   28:	 0f8505000000         	jne	0x33
+;;   2e:	 e90e000000           	jmp	0x41
+;;   33:	 83f801               	cmp	eax, 1
+;;   36:	 0f8519000000         	jne	0x55
+;;   3c:	 e90a000000           	jmp	0x4b
+;;   41:	 b80a000000           	mov	eax, 0xa
+;;   46:	 e90f000000           	jmp	0x5a
+;;   4b:	 b814000000           	mov	eax, 0x14
+;;   50:	 e905000000           	jmp	0x5a
+;;   55:	 b81e000000           	mov	eax, 0x1e
+;;   5a:	 4883c410             	add	rsp, 0x10
+;;   5e:	 5d                   	pop	rbp
+;;   5f:	 c3                   	ret	
+;;   60:	 0f0b                 	ud2	
@@ -0,0 +1,33 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (local i32)
+        (local.get 0)
+        (if
+            (then (local.set 1 (i32.const 10)))
+        )
+        (local.get 1)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8231000000         	jb	0x49
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 c7042400000000       	mov	dword ptr [rsp], 0
+;;   28:	 4531db               	xor	r11d, r11d
+;;   2b:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   2f:	 83f800               	cmp	eax, 0
+;;   32:	 0f8408000000         	je	0x40
+;;   38:	 b80a000000           	mov	eax, 0xa
+;;   3d:	 890424               	mov	dword ptr [rsp], eax
+;;   40:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   43:	 4883c410             	add	rsp, 0x10
+;;   47:	 5d                   	pop	rbp
+;;   48:	 c3                   	ret	
+;;   49:	 0f0b                 	ud2	
@@ -0,0 +1,30 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (local.get 0)
+        (if (result i32)
+            (then (i32.const 1))
+            (else (i32.const 2))
+        )
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822b000000         	jb	0x43
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   25:	 83f800               	cmp	eax, 0
+;;   28:	 0f840a000000         	je	0x38
+;;   2e:	 b801000000           	mov	eax, 1
+;;   33:	 e905000000           	jmp	0x3d
+;;   38:	 b802000000           	mov	eax, 2
+;;   3d:	 4883c410             	add	rsp, 0x10
+;;   41:	 5d                   	pop	rbp
+;;   42:	 c3                   	ret	
+;;   43:	 0f0b                 	ud2	
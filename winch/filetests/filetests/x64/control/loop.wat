@@ -0,0 +1,39 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (local i32)
+        (loop
+            (local.set 1 (i32.add (local.get 1) (local.get 0)))
+            (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+            (br_if 0 (local.get 0))
+        )
+        (local.get 1)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8240000000         	jb	0x58
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 c7042400000000       	mov	dword ptr [rsp], 0
+;;   28:	 4531db               	xor	r11d, r11d
+;;   2b:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   2f:	 8b0c24               	mov	ecx, dword ptr [rsp]
+;;   32:	 01c1                 	add	ecx, eax
+;;   34:	 890c24               	mov	dword ptr [rsp], ecx
+;;   37:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   3b:	 83e801               	sub	eax, 1
+;;   3e:	 89442404             	mov	dword ptr [rsp + 4], eax
+;;   42:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   46:	 83f800               	cmp	eax, 0
+;;   49:	 0f85dcffffff         	jne	0x2b
+;;   4f:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   52:	 4883c410             	add	rsp, 0x10
+;;   56:	 5d                   	pop	rbp
+;;   57:	 c3                   	ret	
+;;   58:	 0f0b                 	ud2	
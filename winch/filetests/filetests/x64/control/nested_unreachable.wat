@@ -0,0 +1,29 @@
+;;! target = "x86_64"
+
+(module
+    (func (result i32)
+        (block (result i32)
+            (i32.const 1)
+            (br 0)
+            (block
+                (loop
+                    (br 1)
+                )
+            )
+            (i32.const 2)
+        )
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f820f000000         	jb	0x27
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 b801000000           	mov	eax, 1
+;;   21:	 4883c408             	add	rsp, 8
+;;   25:	 5d                   	pop	rbp
+;;   26:	 c3                   	ret	
+;;   27:	 0f0b                 	ud2	
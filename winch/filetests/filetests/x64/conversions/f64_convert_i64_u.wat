@@ -0,0 +1,33 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i64) (result f64)
+        (local.get 0)
+        (f64.convert_i64_u)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8241000000         	jb	0x59
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 48891424             	mov	qword ptr [rsp], rdx
+;;   21:	 488b0c24             	mov	rcx, qword ptr [rsp]
+;;   25:	 4883f900             	cmp	rcx, 0
+;;   29:	 0f8c0a000000         	jl	0x39
+;;   2f:	 f2480f2ac1           	cvtsi2sd	xmm0, rcx
+;;   34:	 e91a000000           	jmp	0x53
+;;   39:	 4889c8               	mov	rax, rcx
+;;   3c:	 48c1e801             	shr	rax, 1
+;;   40:	 4989cb               	mov	r11, rcx
+;;   43:	 4983e301             	and	r11, 1
+;;   47:	 4909c3               	or	r11, rax
+;;   4a:	 f2490f2ac3           	cvtsi2sd	xmm0, r11
+;;   4f:	 f20f58c0             	addsd	xmm0, xmm0
+;;   53:	 4883c410             	add	rsp, 0x10
+;;   57:	 5d                   	pop	rbp
+;;   58:	 c3                   	ret	
+;;   59:	 0f0b                 	ud2	
@@ -0,0 +1,37 @@
+;;! target = "x86_64"
+
+(module
+    (func (param f32) (result i32)
+        (local.get 0)
+        (i32.trunc_f32_s)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8251000000         	jb	0x69
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 f30f11442404         	movss	dword ptr [rsp + 4], xmm0
+;;   23:	 f30f10442404         	movss	xmm0, dword ptr [rsp + 4]
+;;   29:	 f30f2cc0             	cvttss2si	eax, xmm0
+;;   2d:	 83f801               	cmp	eax, 1
+;;   30:	 0f812d000000         	jno	0x63
+;;   36:	 0f2ec0               	ucomiss	xmm0, xmm0
+;;   39:	 0f8a2c000000         	jp	0x6b
+;;   3f:	 41bb000000cf         	mov	r11d, 0xcf000000
+;;   45:	 66450f6efb           	movd	xmm15, r11d
+;;   4a:	 410f2ec7             	ucomiss	xmm0, xmm15
+;;   4e:	 0f8219000000         	jb	0x6d
+;;   54:	 66450f57ff           	xorpd	xmm15, xmm15
+;;   59:	 440f2ef8             	ucomiss	xmm15, xmm0
+;;   5d:	 0f820c000000         	jb	0x6f
+;;   63:	 4883c410             	add	rsp, 0x10
+;;   67:	 5d                   	pop	rbp
+;;   68:	 c3                   	ret	
+;;   69:	 0f0b                 	ud2	
+;;   6b:	 0f0b                 	ud2	
+;;   6d:	 0f0b                 	ud2	
+;;   6f:	 0f0b                 	ud2	
@@ -0,0 +1,22 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i64) (result i32)
+        (local.get 0)
+        (i32.wrap_i64)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8213000000         	jb	0x2b
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 48891424             	mov	qword ptr [rsp], rdx
+;;   21:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   25:	 4883c410             	add	rsp, 0x10
+;;   29:	 5d                   	pop	rbp
+;;   2a:	 c3                   	ret	
+;;   2b:	 0f0b                 	ud2	
@@ -0,0 +1,27 @@
+;;! target = "x86_64"
+
+(module
+    (func (result f32)
+        (f32.const 1.1)
+        (f32.const 2.2)
+        (f32.add)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8227000000         	jb	0x3f
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 41bbcdcc0c40         	mov	r11d, 0x400ccccd
+;;   22:	 66410f6ec3           	movd	xmm0, r11d
+;;   27:	 41bbcdcc8c3f         	mov	r11d, 0x3f8ccccd
+;;   2d:	 66410f6ecb           	movd	xmm1, r11d
+;;   32:	 f30f58c8             	addss	xmm1, xmm0
+;;   36:	 0f28c1               	movaps	xmm0, xmm1
+;;   39:	 4883c408             	add	rsp, 8
+;;   3d:	 5d                   	pop	rbp
+;;   3e:	 c3                   	ret	
+;;   3f:	 0f0b                 	ud2	
@@ -0,0 +1,27 @@
+;;! target = "x86_64"
+
+(module
+    (func (param f32) (param f32) (result f32)
+        (local.get 0)
+        (local.get 1)
+        (f32.add)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8228000000         	jb	0x40
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 f30f11442404         	movss	dword ptr [rsp + 4], xmm0
+;;   23:	 f30f110c24           	movss	dword ptr [rsp], xmm1
+;;   28:	 f30f100424           	movss	xmm0, dword ptr [rsp]
+;;   2d:	 f30f104c2404         	movss	xmm1, dword ptr [rsp + 4]
+;;   33:	 f30f58c8             	addss	xmm1, xmm0
+;;   37:	 0f28c1               	movaps	xmm0, xmm1
+;;   3a:	 4883c410             	add	rsp, 0x10
+;;   3e:	 5d                   	pop	rbp
+;;   3f:	 c3                   	ret	
+;;   40:	 0f0b                 	ud2	
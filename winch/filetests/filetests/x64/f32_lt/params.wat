@@ -0,0 +1,28 @@
+;;! target = "x86_64"
+
+(module
+    (func (param f32) (param f32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (f32.lt)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822b000000         	jb	0x43
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 f30f11442404         	movss	dword ptr [rsp + 4], xmm0
+;;   23:	 f30f110c24           	movss	dword ptr [rsp], xmm1
+;;   28:	 f30f100424           	movss	xmm0, dword ptr [rsp]
+;;   2d:	 f30f104c2404         	movss	xmm1, dword ptr [rsp + 4]
+;;   33:	 0f2ec1               	ucomiss	xmm0, xmm1
+;;   36:	 400f97c0             	seta	al
+;;   3a:	 0fb6c0               	movzx	eax, al
+;;   3d:	 4883c410             	add	rsp, 0x10
+;;   41:	 5d                   	pop	rbp
+;;   42:	 c3                   	ret	
+;;   43:	 0f0b                 	ud2	
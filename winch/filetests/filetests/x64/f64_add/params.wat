@@ -0,0 +1,27 @@
+;;! target = "x86_64"
+
+(module
+    (func (param f64) (param f64) (result f64)
+        (local.get 0)
+        (local.get 1)
+        (f64.add)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8228000000         	jb	0x40
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 f20f11442408         	movsd	qword ptr [rsp + 8], xmm0
+;;   23:	 f20f110c24           	movsd	qword ptr [rsp], xmm1
+;;   28:	 f20f100424           	movsd	xmm0, qword ptr [rsp]
+;;   2d:	 f20f104c2408         	movsd	xmm1, qword ptr [rsp + 8]
+;;   33:	 f20f58c8             	addsd	xmm1, xmm0
+;;   37:	 0f28c1               	movaps	xmm0, xmm1
+;;   3a:	 4883c418             	add	rsp, 0x18
+;;   3e:	 5d                   	pop	rbp
+;;   3f:	 c3                   	ret	
+;;   40:	 0f0b                 	ud2	
@@ -0,0 +1,23 @@
+;;! target = "x86_64"
+
+(module
+    (func (param f64) (result f64)
+        (local.get 0)
+        (f64.ceil)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821b000000         	jb	0x33
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 f20f110424           	movsd	qword ptr [rsp], xmm0
+;;   22:	 f20f100424           	movsd	xmm0, qword ptr [rsp]
+;;   27:	 660f3a0bc002         	roundsd	xmm0, xmm0, 2
+;;   2d:	 4883c410             	add	rsp, 0x10
+;;   31:	 5d                   	pop	rbp
+;;   32:	 c3                   	ret	
+;;   33:	 0f0b                 	ud2	
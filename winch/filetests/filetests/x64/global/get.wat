@@ -0,0 +1,23 @@
+;;! target = "x86_64"
+
+(module
+    (global $g (mut i32) (i32.const 0))
+    (func (result i32)
+        (global.get $g)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8213000000         	jb	0x2b
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   20:	 8b4850               	mov	ecx, dword ptr [rax + 0x50]
+;;   23:	 89c8                 	mov	eax, ecx
+;;   25:	 4883c408             	add	rsp, 8
+;;   29:	 5d                   	pop	rbp
+;;   2a:	 c3                   	ret	
+;;   2b:	 0f0b                 	ud2	
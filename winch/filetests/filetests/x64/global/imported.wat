@@ -0,0 +1,32 @@
+;;! target = "x86_64"
+
+(module
+    (import "env" "g" (global $g (mut i64)))
+    (func (param i64)
+        (global.get $g)
+        (local.get 0)
+        (i64.add)
+        (global.set $g)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822e000000         	jb	0x46
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 48891424             	mov	qword ptr [rsp], rdx
+;;   21:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   26:	 488b4050             	mov	rax, qword ptr [rax + 0x50]
+;;   2a:	 488b08               	mov	rcx, qword ptr [rax]
+;;   2d:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   31:	 4801c1               	add	rcx, rax
+;;   34:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   39:	 488b4050             	mov	rax, qword ptr [rax + 0x50]
+;;   3d:	 488908               	mov	qword ptr [rax], rcx
+;;   40:	 4883c410             	add	rsp, 0x10
+;;   44:	 5d                   	pop	rbp
+;;   45:	 c3                   	ret	
+;;   46:	 0f0b                 	ud2	
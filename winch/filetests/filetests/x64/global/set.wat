@@ -0,0 +1,25 @@
+;;! target = "x86_64"
+
+(module
+    (global $g (mut f64) (f64.const 0))
+    (func (param f64)
+        (local.get 0)
+        (global.set $g)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821f000000         	jb	0x37
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 f20f110424           	movsd	qword ptr [rsp], xmm0
+;;   22:	 f20f100424           	movsd	xmm0, qword ptr [rsp]
+;;   27:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   2c:	 f20f114050           	movsd	qword ptr [rax + 0x50], xmm0
+;;   31:	 4883c410             	add	rsp, 0x10
+;;   35:	 5d                   	pop	rbp
+;;   36:	 c3                   	ret	
+;;   37:	 0f0b                 	ud2	
@@ -9,7 +9,15 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 b80a000000           	mov	eax, 0xa
-;;    9:	 83c014               	add	eax, 0x14
-;;    c:	 5d                   	pop	rbp
-;;    d:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8212000000         	jb	0x2a
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 b80a000000           	mov	eax, 0xa
+;;   21:	 83c014               	add	eax, 0x14
+;;   24:	 4883c408             	add	rsp, 8
+;;   28:	 5d                   	pop	rbp
+;;   29:	 c3                   	ret	
+;;   2a:	 0f0b                 	ud2	
@@ -9,13 +9,19 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec08             	sub	rsp, 8
-;;    8:	 897c2404             	mov	dword ptr [rsp + 4], edi
-;;    c:	 893424               	mov	dword ptr [rsp], esi
-;;    f:	 8b0424               	mov	eax, dword ptr [rsp]
-;;   12:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
-;;   16:	 01c1                 	add	ecx, eax
-;;   18:	 4889c8               	mov	rax, rcx
-;;   1b:	 4883c408             	add	rsp, 8
-;;   1f:	 5d                   	pop	rbp
-;;   20:	 c3                   	ret	
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821d000000         	jb	0x35
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   27:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   2b:	 01c1                 	add	ecx, eax
+;;   2d:	 89c8                 	mov	eax, ecx
+;;   2f:	 4883c410             	add	rsp, 0x10
+;;   33:	 5d                   	pop	rbp
+;;   34:	 c3                   	ret	
+;;   35:	 0f0b                 	ud2	
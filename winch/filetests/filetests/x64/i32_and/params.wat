@@ -0,0 +1,27 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (param i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (i32.and)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821d000000         	jb	0x35
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   27:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   2b:	 21c1                 	and	ecx, eax
+;;   2d:	 89c8                 	mov	eax, ecx
+;;   2f:	 4883c410             	add	rsp, 0x10
+;;   33:	 5d                   	pop	rbp
+;;   34:	 c3                   	ret	
+;;   35:	 0f0b                 	ud2	
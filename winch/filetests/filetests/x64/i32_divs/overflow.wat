@@ -9,12 +9,20 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 b9ffffffff           	mov	ecx, 0xffffffff
-;;    9:	 b800000080           	mov	eax, 0x80000000
-;;    e:	 83f900               	cmp	ecx, 0
-;;   11:	 0f8405000000         	je	0x1c
-;;   17:	 99                   	cdq	
-;;   18:	 f7f9                 	idiv	ecx
-;;   1a:	 5d                   	pop	rbp
-;;   1b:	 c3                   	ret	
-;;   1c:	 0f0b                 	ud2	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8220000000         	jb	0x38
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 b9ffffffff           	mov	ecx, 0xffffffff
+;;   21:	 b800000080           	mov	eax, 0x80000000
+;;   26:	 83f900               	cmp	ecx, 0
+;;   29:	 0f840b000000         	je	0x3a
+;;   2f:	 99                   	cdq	
+;;   30:	 f7f9                 	idiv	ecx
+;;   32:	 4883c408             	add	rsp, 8
+;;   36:	 5d                   	pop	rbp
+;;   37:	 c3                   	ret	
+;;   38:	 0f0b                 	ud2	
+;;   3a:	 0f0b                 	ud2	
@@ -9,9 +9,17 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 b900000000           	mov	ecx, 0
-;;    9:	 b800000000           	mov	eax, 0
-;;    e:	 31d2                 	xor	edx, edx
-;;   10:	 f7f1                 	div	ecx
-;;   12:	 5d                   	pop	rbp
-;;   13:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8218000000         	jb	0x30
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 b900000000           	mov	ecx, 0
+;;   21:	 b800000000           	mov	eax, 0
+;;   26:	 31d2                 	xor	edx, edx
+;;   28:	 f7f1                 	div	ecx
+;;   2a:	 4883c408             	add	rsp, 8
+;;   2e:	 5d                   	pop	rbp
+;;   2f:	 c3                   	ret	
+;;   30:	 0f0b                 	ud2	
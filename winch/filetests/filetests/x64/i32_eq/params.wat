@@ -0,0 +1,29 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (param i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (i32.eq)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8224000000         	jb	0x3c
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   27:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   2b:	 39c1                 	cmp	ecx, eax
+;;   2d:	 400f94c1             	sete	cl
+;;   31:	 0fb6c9               	movzx	ecx, cl
+;;   34:	 89c8                 	mov	eax, ecx
+;;   36:	 4883c410             	add	rsp, 0x10
+;;   3a:	 5d                   	pop	rbp
+;;   3b:	 c3                   	ret	
+;;   3c:	 0f0b                 	ud2	
@@ -9,14 +9,22 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 b905000000           	mov	ecx, 5
-;;    9:	 b807000000           	mov	eax, 7
-;;    e:	 99                   	cdq	
-;;    f:	 83f9ff               	cmp	ecx, -1
-;;   12:	 0f850a000000         	jne	0x22
-;;   18:	 ba00000000           	mov	edx, 0
-;;   1d:	 e902000000           	jmp	0x24
-;;   22:	 f7f9                 	idiv	ecx
-;;   24:	 4889d0               	mov	rax, rdx
-;;   27:	 5d                   	pop	rbp
-;;   28:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822c000000         	jb	0x44
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 b905000000           	mov	ecx, 5
+;;   21:	 b807000000           	mov	eax, 7
+;;   26:	 99                   	cdq	
+;;   27:	 83f9ff               	cmp	ecx, -1
+;;   2a:	 0f850a000000         	jne	0x3a
+;;   30:	 ba00000000           	mov	edx, 0
+;;   35:	 e902000000           	jmp	0x3c
+;;   3a:	 f7f9                 	idiv	ecx
+;;   3c:	 89d0                 	mov	eax, edx
+;;   3e:	 4883c408             	add	rsp, 8
+;;   42:	 5d                   	pop	rbp
+;;   43:	 c3                   	ret	
+;;   44:	 0f0b                 	ud2	
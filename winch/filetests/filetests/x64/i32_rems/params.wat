@@ -9,18 +9,24 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec08             	sub	rsp, 8
-;;    8:	 897c2404             	mov	dword ptr [rsp + 4], edi
-;;    c:	 893424               	mov	dword ptr [rsp], esi
-;;    f:	 8b0c24               	mov	ecx, dword ptr [rsp]
-;;   12:	 8b442404             	mov	eax, dword ptr [rsp + 4]
-;;   16:	 99                   	cdq	
-;;   17:	 83f9ff               	cmp	ecx, -1
-;;   1a:	 0f850a000000         	jne	0x2a
-;;   20:	 ba00000000           	mov	edx, 0
-;;   25:	 e902000000           	jmp	0x2c
-;;   2a:	 f7f9                 	idiv	ecx
-;;   2c:	 4889d0               	mov	rax, rdx
-;;   2f:	 4883c408             	add	rsp, 8
-;;   33:	 5d                   	pop	rbp
-;;   34:	 c3                   	ret	
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8231000000         	jb	0x49
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0c24               	mov	ecx, dword ptr [rsp]
+;;   27:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   2b:	 99                   	cdq	
+;;   2c:	 83f9ff               	cmp	ecx, -1
+;;   2f:	 0f850a000000         	jne	0x3f
+;;   35:	 ba00000000           	mov	edx, 0
+;;   3a:	 e902000000           	jmp	0x41
+;;   3f:	 f7f9                 	idiv	ecx
+;;   41:	 89d0                 	mov	eax, edx
+;;   43:	 4883c410             	add	rsp, 0x10
+;;   47:	 5d                   	pop	rbp
+;;   48:	 c3                   	ret	
+;;   49:	 0f0b                 	ud2	
@@ -9,10 +9,18 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 b905000000           	mov	ecx, 5
-;;    9:	 b807000000           	mov	eax, 7
-;;    e:	 31d2                 	xor	edx, edx
-;;   10:	 f7f1                 	div	ecx
-;;   12:	 4889d0               	mov	rax, rdx
-;;   15:	 5d                   	pop	rbp
-;;   16:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821a000000         	jb	0x32
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 b905000000           	mov	ecx, 5
+;;   21:	 b807000000           	mov	eax, 7
+;;   26:	 31d2                 	xor	edx, edx
+;;   28:	 f7f1                 	div	ecx
+;;   2a:	 89d0                 	mov	eax, edx
+;;   2c:	 4883c408             	add	rsp, 8
+;;   30:	 5d                   	pop	rbp
+;;   31:	 c3                   	ret	
+;;   32:	 0f0b                 	ud2	
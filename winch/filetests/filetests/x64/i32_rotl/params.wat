@@ -0,0 +1,26 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (param i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (i32.rotl)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821b000000         	jb	0x33
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0c24               	mov	ecx, dword ptr [rsp]
+;;   27:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   2b:	 d3c0                 	rol	eax, cl
+;;   2d:	 4883c410             	add	rsp, 0x10
+;;   31:	 5d                   	pop	rbp
+;;   32:	 c3                   	ret	
+;;   33:	 0f0b                 	ud2	
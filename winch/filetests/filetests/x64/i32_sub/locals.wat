@@ -18,16 +18,22 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec08             	sub	rsp, 8
-;;    8:	 48c7042400000000     	mov	qword ptr [rsp], 0
-;;   10:	 b80a000000           	mov	eax, 0xa
-;;   15:	 89442404             	mov	dword ptr [rsp + 4], eax
-;;   19:	 b814000000           	mov	eax, 0x14
-;;   1e:	 890424               	mov	dword ptr [rsp], eax
-;;   21:	 8b0424               	mov	eax, dword ptr [rsp]
-;;   24:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
-;;   28:	 29c1                 	sub	ecx, eax
-;;   2a:	 4889c8               	mov	rax, rcx
-;;   2d:	 4883c408             	add	rsp, 8
-;;   31:	 5d                   	pop	rbp
-;;   32:	 c3                   	ret	
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822f000000         	jb	0x47
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 48c7042400000000     	mov	qword ptr [rsp], 0
+;;   25:	 b80a000000           	mov	eax, 0xa
+;;   2a:	 89442404             	mov	dword ptr [rsp + 4], eax
+;;   2e:	 b814000000           	mov	eax, 0x14
+;;   33:	 890424               	mov	dword ptr [rsp], eax
+;;   36:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   39:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   3d:	 29c1                 	sub	ecx, eax
+;;   3f:	 89c8                 	mov	eax, ecx
+;;   41:	 4883c410             	add	rsp, 0x10
+;;   45:	 5d                   	pop	rbp
+;;   46:	 c3                   	ret	
+;;   47:	 0f0b                 	ud2	
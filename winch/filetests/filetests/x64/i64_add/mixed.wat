@@ -9,7 +9,15 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c0ffffffff       	mov	rax, 0xffffffffffffffff
-;;    b:	 4883c001             	add	rax, 1
-;;    f:	 5d                   	pop	rbp
-;;   10:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8215000000         	jb	0x2d
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c0ffffffff       	mov	rax, 0xffffffffffffffff
+;;   23:	 4883c001             	add	rax, 1
+;;   27:	 4883c408             	add	rsp, 8
+;;   2b:	 5d                   	pop	rbp
+;;   2c:	 c3                   	ret	
+;;   2d:	 0f0b                 	ud2	
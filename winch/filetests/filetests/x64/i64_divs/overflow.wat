@@ -9,13 +9,21 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c1ffffffff       	mov	rcx, 0xffffffffffffffff
-;;    b:	 48b80000000000000080 	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822a000000         	jb	0x42
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c1ffffffff       	mov	rcx, 0xffffffffffffffff
+;;   23:	 48b80000000000000080 	
 ;; 				movabs	rax, 0x8000000000000000
-;;   15:	 4883f900             	cmp	rcx, 0
-;;   19:	 0f8407000000         	je	0x26
-;;   1f:	 4899                 	cqo	
-;;   21:	 48f7f9               	idiv	rcx
-;;   24:	 5d                   	pop	rbp
-;;   25:	 c3                   	ret	
-;;   26:	 0f0b                 	ud2	
+;;   2d:	 4883f900             	cmp	rcx, 0
+;;   31:	 0f840d000000         	je	0x44
+;;   37:	 4899                 	cqo	
+;;   39:	 48f7f9               	idiv	rcx
+;;   3c:	 4883c408             	add	rsp, 8
+;;   40:	 5d                   	pop	rbp
+;;   41:	 c3                   	ret	
+;;   42:	 0f0b                 	ud2	
+;;   44:	 0f0b                 	ud2	
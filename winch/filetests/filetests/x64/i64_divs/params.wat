@@ -9,16 +9,22 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec10             	sub	rsp, 0x10
-;;    8:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
-;;    d:	 48893424             	mov	qword ptr [rsp], rsi
-;;   11:	 488b0c24             	mov	rcx, qword ptr [rsp]
-;;   15:	 488b442408           	mov	rax, qword ptr [rsp + 8]
-;;   1a:	 4883f900             	cmp	rcx, 0
-;;   1e:	 0f840b000000         	je	0x2f
-;;   24:	 4899                 	cqo	
-;;   26:	 48f7f9               	idiv	rcx
-;;   29:	 4883c410             	add	rsp, 0x10
-;;   2d:	 5d                   	pop	rbp
-;;   2e:	 c3                   	ret	
-;;   2f:	 0f0b                 	ud2	
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822c000000         	jb	0x44
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 4889542408           	mov	qword ptr [rsp + 8], rdx
+;;   22:	 48890c24             	mov	qword ptr [rsp], rcx
+;;   26:	 488b0c24             	mov	rcx, qword ptr [rsp]
+;;   2a:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   2f:	 4883f900             	cmp	rcx, 0
+;;   33:	 0f840d000000         	je	0x46
+;;   39:	 4899                 	cqo	
+;;   3b:	 48f7f9               	idiv	rcx
+;;   3e:	 4883c418             	add	rsp, 0x18
+;;   42:	 5d                   	pop	rbp
+;;   43:	 c3                   	ret	
+;;   44:	 0f0b                 	ud2	
+;;   46:	 0f0b                 	ud2	
@@ -9,12 +9,20 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c100000000       	mov	rcx, 0
-;;    b:	 48c7c000000000       	mov	rax, 0
-;;   12:	 4883f900             	cmp	rcx, 0
-;;   16:	 0f8407000000         	je	0x23
-;;   1c:	 4899                 	cqo	
-;;   1e:	 48f7f9               	idiv	rcx
-;;   21:	 5d                   	pop	rbp
-;;   22:	 c3                   	ret	
-;;   23:	 0f0b                 	ud2	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8227000000         	jb	0x3f
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c100000000       	mov	rcx, 0
+;;   23:	 48c7c000000000       	mov	rax, 0
+;;   2a:	 4883f900             	cmp	rcx, 0
+;;   2e:	 0f840d000000         	je	0x41
+;;   34:	 4899                 	cqo	
+;;   36:	 48f7f9               	idiv	rcx
+;;   39:	 4883c408             	add	rsp, 8
+;;   3d:	 5d                   	pop	rbp
+;;   3e:	 c3                   	ret	
+;;   3f:	 0f0b                 	ud2	
+;;   41:	 0f0b                 	ud2	
@@ -9,9 +9,17 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c10a000000       	mov	rcx, 0xa
-;;    b:	 48c7c014000000       	mov	rax, 0x14
-;;   12:	 4831d2               	xor	rdx, rdx
-;;   15:	 48f7f1               	div	rcx
-;;   18:	 5d                   	pop	rbp
-;;   19:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821e000000         	jb	0x36
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c10a000000       	mov	rcx, 0xa
+;;   23:	 48c7c014000000       	mov	rax, 0x14
+;;   2a:	 4831d2               	xor	rdx, rdx
+;;   2d:	 48f7f1               	div	rcx
+;;   30:	 4883c408             	add	rsp, 8
+;;   34:	 5d                   	pop	rbp
+;;   35:	 c3                   	ret	
+;;   36:	 0f0b                 	ud2	
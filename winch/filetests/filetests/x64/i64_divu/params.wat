@@ -9,13 +9,19 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec10             	sub	rsp, 0x10
-;;    8:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
-;;    d:	 48893424             	mov	qword ptr [rsp], rsi
-;;   11:	 488b0c24             	mov	rcx, qword ptr [rsp]
-;;   15:	 488b442408           	mov	rax, qword ptr [rsp + 8]
-;;   1a:	 4831d2               	xor	rdx, rdx
-;;   1d:	 48f7f1               	div	rcx
-;;   20:	 4883c410             	add	rsp, 0x10
-;;   24:	 5d                   	pop	rbp
-;;   25:	 c3                   	ret	
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8223000000         	jb	0x3b
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 4889542408           	mov	qword ptr [rsp + 8], rdx
+;;   22:	 48890c24             	mov	qword ptr [rsp], rcx
+;;   26:	 488b0c24             	mov	rcx, qword ptr [rsp]
+;;   2a:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   2f:	 4831d2               	xor	rdx, rdx
+;;   32:	 48f7f1               	div	rcx
+;;   35:	 4883c418             	add	rsp, 0x18
+;;   39:	 5d                   	pop	rbp
+;;   3a:	 c3                   	ret	
+;;   3b:	 0f0b                 	ud2	
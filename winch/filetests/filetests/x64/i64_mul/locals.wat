@@ -18,18 +18,24 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec10             	sub	rsp, 0x10
-;;    8:	 4531db               	xor	r11d, r11d
-;;    b:	 4c895c2408           	mov	qword ptr [rsp + 8], r11
-;;   10:	 4c891c24             	mov	qword ptr [rsp], r11
-;;   14:	 48c7c00a000000       	mov	rax, 0xa
-;;   1b:	 4889442408           	mov	qword ptr [rsp + 8], rax
-;;   20:	 48c7c014000000       	mov	rax, 0x14
-;;   27:	 48890424             	mov	qword ptr [rsp], rax
-;;   2b:	 488b0424             	mov	rax, qword ptr [rsp]
-;;   2f:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
-;;   34:	 480fafc8             	imul	rcx, rax
-;;   38:	 4889c8               	mov	rax, rcx
-;;   3b:	 4883c410             	add	rsp, 0x10
-;;   3f:	 5d                   	pop	rbp
-;;   40:	 c3                   	ret	
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f823e000000         	jb	0x56
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 4531db               	xor	r11d, r11d
+;;   20:	 4c895c2408           	mov	qword ptr [rsp + 8], r11
+;;   25:	 4c891c24             	mov	qword ptr [rsp], r11
+;;   29:	 48c7c00a000000       	mov	rax, 0xa
+;;   30:	 4889442408           	mov	qword ptr [rsp + 8], rax
+;;   35:	 48c7c014000000       	mov	rax, 0x14
+;;   3c:	 48890424             	mov	qword ptr [rsp], rax
+;;   40:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   44:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;   49:	 480fafc8             	imul	rcx, rax
+;;   4d:	 4889c8               	mov	rax, rcx
+;;   50:	 4883c418             	add	rsp, 0x18
+;;   54:	 5d                   	pop	rbp
+;;   55:	 c3                   	ret	
+;;   56:	 0f0b                 	ud2	
@@ -9,8 +9,16 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48b80000000000000080 	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8218000000         	jb	0x30
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48b80000000000000080 	
 ;; 				movabs	rax, 0x8000000000000000
-;;    e:	 486bc0ff             	imul	rax, rax, -1
-;;   12:	 5d                   	pop	rbp
-;;   13:	 c3                   	ret	
+;;   26:	 486bc0ff             	imul	rax, rax, -1
+;;   2a:	 4883c408             	add	rsp, 8
+;;   2e:	 5d                   	pop	rbp
+;;   2f:	 c3                   	ret	
+;;   30:	 0f0b                 	ud2	
@@ -9,13 +9,19 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec10             	sub	rsp, 0x10
-;;    8:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
-;;    d:	 48893424             	mov	qword ptr [rsp], rsi
-;;   11:	 488b0424             	mov	rax, qword ptr [rsp]
-;;   15:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
-;;   1a:	 480fafc8             	imul	rcx, rax
-;;   1e:	 4889c8               	mov	rax, rcx
-;;   21:	 4883c410             	add	rsp, 0x10
-;;   25:	 5d                   	pop	rbp
-;;   26:	 c3                   	ret	
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8224000000         	jb	0x3c
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 4889542408           	mov	qword ptr [rsp + 8], rdx
+;;   22:	 48890c24             	mov	qword ptr [rsp], rcx
+;;   26:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   2a:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;   2f:	 480fafc8             	imul	rcx, rax
+;;   33:	 4889c8               	mov	rax, rcx
+;;   36:	 4883c418             	add	rsp, 0x18
+;;   3a:	 5d                   	pop	rbp
+;;   3b:	 c3                   	ret	
+;;   3c:	 0f0b                 	ud2	
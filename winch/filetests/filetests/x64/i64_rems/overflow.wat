@@ -9,15 +9,23 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c1ffffffff       	mov	rcx, 0xffffffffffffffff
-;;    b:	 48b80000000000000080 	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8237000000         	jb	0x4f
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c1ffffffff       	mov	rcx, 0xffffffffffffffff
+;;   23:	 48b80000000000000080 	
 ;; 				movabs	rax, 0x8000000000000000
-;;   15:	 4899                 	cqo	
-;;   17:	 4883f9ff             	cmp	rcx, -1
-;;   1b:	 0f850a000000         	jne	0x2b
-;;   21:	 ba00000000           	mov	edx, 0
-;;   26:	 e903000000           	jmp	0x2e
-;;   2b:	 48f7f9               	idiv	rcx
-;;   2e:	 4889d0               	mov	rax, rdx
-;;   31:	 5d                   	pop	rbp
-;;   32:	 c3                   	ret	
+;;   2d:	 4899                 	cqo	
+;;   2f:	 4883f9ff             	cmp	rcx, -1
+;;   33:	 0f850a000000         	jne	0x43
+;;   39:	 ba00000000           	mov	edx, 0
+;;   3e:	 e903000000           	jmp	0x46
+;;   43:	 48f7f9               	idiv	rcx
+;;   46:	 4889d0               	mov	rax, rdx
+;;   49:	 4883c408             	add	rsp, 8
+;;   4d:	 5d                   	pop	rbp
+;;   4e:	 c3                   	ret	
+;;   4f:	 0f0b                 	ud2	
@@ -9,18 +9,24 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 4883ec10             	sub	rsp, 0x10
-;;    8:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
-;;    d:	 48893424             	mov	qword ptr [rsp], rsi
-;;   11:	 488b0c24             	mov	rcx, qword ptr [rsp]
-;;   15:	 488b442408           	mov	rax, qword ptr [rsp + 8]
-;;   1a:	 4899                 	cqo	
-;;   1c:	 4883f9ff             	cmp	rcx, -1
-;;   20:	 0f850a000000         	jne	0x30
-;;   26:	 ba00000000           	mov	edx, 0
-;;   2b:	 e903000000           	jmp	0x33
-;;   30:	 48f7f9               	idiv	rcx
-;;   33:	 4889d0               	mov	rax, rdx
-;;   36:	 4883c410             	add	rsp, 0x10
-;;   3a:	 5d                   	pop	rbp
-;;   3b:	 c3                   	ret	
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8239000000         	jb	0x51
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 4889542408           	mov	qword ptr [rsp + 8], rdx
+;;   22:	 48890c24             	mov	qword ptr [rsp], rcx
+;;   26:	 488b0c24             	mov	rcx, qword ptr [rsp]
+;;   2a:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   2f:	 4899                 	cqo	
+;;   31:	 4883f9ff             	cmp	rcx, -1
+;;   35:	 0f850a000000         	jne	0x45
+;;   3b:	 ba00000000           	mov	edx, 0
+;;   40:	 e903000000           	jmp	0x48
+;;   45:	 48f7f9               	idiv	rcx
+;;   48:	 4889d0               	mov	rax, rdx
+;;   4b:	 4883c418             	add	rsp, 0x18
+;;   4f:	 5d                   	pop	rbp
+;;   50:	 c3                   	ret	
+;;   51:	 0f0b                 	ud2	
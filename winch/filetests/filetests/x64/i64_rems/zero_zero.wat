@@ -9,14 +9,22 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c100000000       	mov	rcx, 0
-;;    b:	 48c7c000000000       	mov	rax, 0
-;;   12:	 4899                 	cqo	
-;;   14:	 4883f9ff             	cmp	rcx, -1
-;;   18:	 0f850a000000         	jne	0x28
-;;   1e:	 ba00000000           	mov	edx, 0
-;;   23:	 e903000000           	jmp	0x2b
-;;   28:	 48f7f9               	idiv	rcx
-;;   2b:	 4889d0               	mov	rax, rdx
-;;   2e:	 5d                   	pop	rbp
-;;   2f:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8234000000         	jb	0x4c
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c100000000       	mov	rcx, 0
+;;   23:	 48c7c000000000       	mov	rax, 0
+;;   2a:	 4899                 	cqo	
+;;   2c:	 4883f9ff             	cmp	rcx, -1
+;;   30:	 0f850a000000         	jne	0x40
+;;   36:	 ba00000000           	mov	edx, 0
+;;   3b:	 e903000000           	jmp	0x43
+;;   40:	 48f7f9               	idiv	rcx
+;;   43:	 4889d0               	mov	rax, rdx
+;;   46:	 4883c408             	add	rsp, 8
+;;   4a:	 5d                   	pop	rbp
+;;   4b:	 c3                   	ret	
+;;   4c:	 0f0b                 	ud2	
@@ -9,10 +9,18 @@
 )
 ;;    0:	 55                   	push	rbp
 ;;    1:	 4889e5               	mov	rbp, rsp
-;;    4:	 48c7c105000000       	mov	rcx, 5
-;;    b:	 48c7c007000000       	mov	rax, 7
-;;   12:	 4831d2               	xor	rdx, rdx
-;;   15:	 48f7f1               	div	rcx
-;;   18:	 4889d0               	mov	rax, rdx
-;;   1b:	 5d                   	pop	rbp
-;;   1c:	 c3                   	ret	
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8221000000         	jb	0x39
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 48c7c105000000       	mov	rcx, 5
+;;   23:	 48c7c007000000       	mov	rax, 7
+;;   2a:	 4831d2               	xor	rdx, rdx
+;;   2d:	 48f7f1               	div	rcx
+;;   30:	 4889d0               	mov	rax, rdx
+;;   33:	 4883c408             	add	rsp, 8
+;;   37:	 5d                   	pop	rbp
+;;   38:	 c3                   	ret	
+;;   39:	 0f0b                 	ud2	
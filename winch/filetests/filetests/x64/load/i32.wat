@@ -0,0 +1,36 @@
+;;! target = "x86_64"
+
+(module
+    (memory 1)
+    (func (param i32) (result i32)
+        (local.get 0)
+        (i32.load offset=4)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f823e000000         	jb	0x56
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   25:	 89c0                 	mov	eax, eax
+;;   27:	 4889c1               	mov	rcx, rax
+;;   2a:	 4883c108             	add	rcx, 8
+;;   2e:	 488b542408           	mov	rdx, qword ptr [rsp + 8]
+;;   33:	 488b5250             	mov	rdx, qword ptr [rdx + 0x50]
+;;   37:	 4c8b5a08             	mov	r11, qword ptr [rdx + 8]
+;;   3b:	 4c39d9               	cmp	rcx, r11
+;;   3e:	 0f8714000000         	ja	0x58
+;;   44:	 488b12               	mov	rdx, qword ptr [rdx]
+;;   47:	 4801c2               	add	rdx, rax
+;;   4a:	 4883c204             	add	rdx, 4
+;;   4e:	 8b02                 	mov	eax, dword ptr [rdx]
+;;   50:	 4883c410             	add	rsp, 0x10
+;;   54:	 5d                   	pop	rbp
+;;   55:	 c3                   	ret	
+;;   56:	 0f0b                 	ud2	
+;;   58:	 0f0b                 	ud2	
@@ -0,0 +1,28 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (result i32)
+        (local.get 0)
+        (i32.const 1)
+        (local.tee 0)
+        (i32.add)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8220000000         	jb	0x38
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 b801000000           	mov	eax, 1
+;;   26:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   2a:	 89442404             	mov	dword ptr [rsp + 4], eax
+;;   2e:	 01c1                 	add	ecx, eax
+;;   30:	 89c8                 	mov	eax, ecx
+;;   32:	 4883c410             	add	rsp, 0x10
+;;   36:	 5d                   	pop	rbp
+;;   37:	 c3                   	ret	
+;;   38:	 0f0b                 	ud2	
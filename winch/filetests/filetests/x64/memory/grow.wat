@@ -0,0 +1,33 @@
+;;! target = "x86_64"
+
+(module
+    (memory 1)
+    (func (param i32) (result i32)
+        (local.get 0)
+        (memory.grow)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f823a000000         	jb	0x52
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 488b442408           	mov	rax, qword ptr [rsp + 8]
+;;   26:	 50                   	push	rax
+;;   27:	 4c8b542410           	mov	r10, qword ptr [rsp + 0x10]
+;;   2c:	 4d8b5238             	mov	r10, qword ptr [r10 + 0x38]
+;;   30:	 4d8b12               	mov	r10, qword ptr [r10]
+;;   33:	 4883ec08             	sub	rsp, 8
+;;   37:	 488b7c2408           	mov	rdi, qword ptr [rsp + 8]
+;;   3c:	 8b742414             	mov	esi, dword ptr [rsp + 0x14]
+;;   40:	 ba00000000           	mov	edx, 0
+;;   45:	 41ffd2               	call	r10
+;;   48:	 4883c410             	add	rsp, 0x10
+;;   4c:	 4883c410             	add	rsp, 0x10
+;;   50:	 5d                   	pop	rbp
+;;   51:	 c3                   	ret	
+;;   52:	 0f0b                 	ud2	
@@ -0,0 +1,24 @@
+;;! target = "x86_64"
+
+(module
+    (memory 1)
+    (func (result i32)
+        (memory.size)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f821a000000         	jb	0x32
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   20:	 488b4050             	mov	rax, qword ptr [rax + 0x50]
+;;   24:	 488b4008             	mov	rax, qword ptr [rax + 8]
+;;   28:	 48c1e810             	shr	rax, 0x10
+;;   2c:	 4883c408             	add	rsp, 8
+;;   30:	 5d                   	pop	rbp
+;;   31:	 c3                   	ret	
+;;   32:	 0f0b                 	ud2	
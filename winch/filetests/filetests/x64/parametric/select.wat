@@ -0,0 +1,31 @@
+;;! target = "x86_64"
+
+(module
+    (func (param i32) (param i32) (param i32) (result i32)
+        (local.get 0)
+        (local.get 1)
+        (local.get 2)
+        (select)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f822c000000         	jb	0x44
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 8954240c             	mov	dword ptr [rsp + 0xc], edx
+;;   21:	 894c2408             	mov	dword ptr [rsp + 8], ecx
+;;   25:	 4489442404           	mov	dword ptr [rsp + 4], r8d
+;;   2a:	 8b442404             	mov	eax, dword ptr [rsp + 4]
+;;   2e:	 8b4c2408             	mov	ecx, dword ptr [rsp + 8]
+;;   32:	 8b54240c             	mov	edx, dword ptr [rsp + 0xc]
+;;   36:	 83f800               	cmp	eax, 0
+;;   39:	 0f44d1               	cmove	edx, ecx
+;;   3c:	 89d0                 	mov	eax, edx
+;;   3e:	 4883c418             	add	rsp, 0x18
+;;   42:	 5d                   	pop	rbp
+;;   43:	 c3                   	ret	
+;;   44:	 0f0b                 	ud2	
@@ -0,0 +1,39 @@
+;;! target = "x86_64"
+
+(module
+    (memory 1)
+    (func (param i32) (param i32)
+        (local.get 0)
+        (local.get 1)
+        (i32.store offset=8)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8244000000         	jb	0x5c
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 890c24               	mov	dword ptr [rsp], ecx
+;;   24:	 8b0424               	mov	eax, dword ptr [rsp]
+;;   27:	 8b4c2404             	mov	ecx, dword ptr [rsp + 4]
+;;   2b:	 89c9                 	mov	ecx, ecx
+;;   2d:	 4889ca               	mov	rdx, rcx
+;;   30:	 4883c20c             	add	rdx, 0xc
+;;   34:	 488b742408           	mov	rsi, qword ptr [rsp + 8]
+;;   39:	 488b7650             	mov	rsi, qword ptr [rsi + 0x50]
+;;   3d:	 4c8b5e08             	mov	r11, qword ptr [rsi + 8]
+;;   41:	 4c39da               	cmp	rdx, r11
+;;   44:	 0f8714000000         	ja	0x5e
+;;   4a:	 488b36               	mov	rsi, qword ptr [rsi]
+;;   4d:	 4801ce               	add	rsi, rcx
+;;   50:	 4883c608             	add	rsi, 8
+;;   54:	 8906                 	mov	dword ptr [rsi], eax
+;;   56:	 4883c410             	add	rsp, 0x10
+;;   5a:	 5d                   	pop	rbp
+;;   5b:	 c3                   	ret	
+;;   5c:	 0f0b                 	ud2	
+;;   5e:	 0f0b                 	ud2	
@@ -0,0 +1,38 @@
+;;! target = "x86_64"
+
+(module
+    (memory 1)
+    (func (param i32) (param i64)
+        (local.get 0)
+        (local.get 1)
+        (i64.store16)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec18             	sub	rsp, 0x18
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8243000000         	jb	0x5b
+;;   18:	 48897c2410           	mov	qword ptr [rsp + 0x10], rdi
+;;   1d:	 8954240c             	mov	dword ptr [rsp + 0xc], edx
+;;   21:	 48890c24             	mov	qword ptr [rsp], rcx
+;;   25:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   29:	 8b4c240c             	mov	ecx, dword ptr [rsp + 0xc]
+;;   2d:	 89c9                 	mov	ecx, ecx
+;;   2f:	 4889ca               	mov	rdx, rcx
+;;   32:	 4883c202             	add	rdx, 2
+;;   36:	 488b742410           	mov	rsi, qword ptr [rsp + 0x10]
+;;   3b:	 488b7650             	mov	rsi, qword ptr [rsi + 0x50]
+;;   3f:	 4c8b5e08             	mov	r11, qword ptr [rsi + 8]
+;;   43:	 4c39da               	cmp	rdx, r11
+;;   46:	 0f8711000000         	ja	0x5d
+;;   4c:	 488b36               	mov	rsi, qword ptr [rsi]
+;;   4f:	 4801ce               	add	rsi, rcx
+;;   52:	 668906               	mov	word ptr [rsi], ax
+;;   55:	 4883c418             	add	rsp, 0x18
+;;   59:	 5d                   	pop	rbp
+;;   5a:	 c3                   	ret	
+;;   5b:	 0f0b                 	ud2	
+;;   5d:	 0f0b                 	ud2	
@@ -0,0 +1,51 @@
+;;! target = "x86_64"
+
+(module
+    (table 10 funcref)
+    (func (param i32) (result funcref)
+        (local.get 0)
+        (table.get 0)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec10             	sub	rsp, 0x10
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8276000000         	jb	0x8e
+;;   18:	 48897c2408           	mov	qword ptr [rsp + 8], rdi
+;;   1d:	 89542404             	mov	dword ptr [rsp + 4], edx
+;;   21:	 448b5c2404           	mov	r11d, dword ptr [rsp + 4]
+;;   26:	 4153                 	push	r11
+;;   28:	 58                   	pop	rax
+;;   29:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;   2e:	 448b5958             	mov	r11d, dword ptr [rcx + 0x58]
+;;   32:	 4439d8               	cmp	eax, r11d
+;;   35:	 0f8355000000         	jae	0x90
+;;   3b:	 488b4950             	mov	rcx, qword ptr [rcx + 0x50]
+;;   3f:	 4189c3               	mov	r11d, eax
+;;   42:	 4d6bdb08             	imul	r11, r11, 8
+;;   46:	 4c01d9               	add	rcx, r11
+;;   49:	 488b11               	mov	rdx, qword ptr [rcx]
+;;   4c:	 4883e2fe             	and	rdx, 0xfffffffffffffffe
+;;   50:	 4883fa00             	cmp	rdx, 0
+;;   54:	 0f852b000000         	jne	0x85
+;;   5a:	 488b4c2408           	mov	rcx, qword ptr [rsp + 8]
+;;   5f:	 51                   	push	rcx
+;;   60:	 50                   	push	rax
+;;   61:	 4c8b542418           	mov	r10, qword ptr [rsp + 0x18]
+;;   66:	 4d8b5238             	mov	r10, qword ptr [r10 + 0x38]
+;;   6a:	 4d8b5248             	mov	r10, qword ptr [r10 + 0x48]
+;;   6e:	 488b7c2408           	mov	rdi, qword ptr [rsp + 8]
+;;   73:	 be00000000           	mov	esi, 0
+;;   78:	 8b1424               	mov	edx, dword ptr [rsp]
+;;   7b:	 41ffd2               	call	r10
+;;   7e:	 4883c410             	add	rsp, 0x10
+;;   82:	 4889c2               	mov	rdx, rax
+;;   85:	 4889d0               	mov	rax, rdx
+;;   88:	 4883c410             	add	rsp, 0x10
+;;   8c:	 5d                   	pop	rbp
+;;   8d:	 c3                   	ret	
+;;   8e:	 0f0b                 	ud2	
+;;   90:	 0f0b                 	ud2	
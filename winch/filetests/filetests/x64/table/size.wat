@@ -0,0 +1,22 @@
+;;! target = "x86_64"
+
+(module
+    (table 10 funcref)
+    (func (result i32)
+        (table.size 0)
+    )
+)
+;;    0:	 55                   	push	rbp
+;;    1:	 4889e5               	mov	rbp, rsp
+;;    4:	 4883ec08             	sub	rsp, 8
+;;    8:	 4c8b5f08             	mov	r11, qword ptr [rdi + 8]
+;;    c:	 4d8b1b               	mov	r11, qword ptr [r11]
+;;    f:	 4c39dc               	cmp	rsp, r11
+;;   12:	 0f8211000000         	jb	0x29
+;;   18:	 48893c24             	mov	qword ptr [rsp], rdi
+;;   1c:	 488b0424             	mov	rax, qword ptr [rsp]
+;;   20:	 8b4058               	mov	eax, dword ptr [rax + 0x58]
+;;   23:	 4883c408             	add	rsp, 8
+;;   27:	 5d                   	pop	rbp
+;;   28:	 c3                   	ret	
+;;   29:	 0f0b                 	ud2	